@@ -1,4 +1,8 @@
-use temporalio_sdk_core::{ClientOptions, RetryClient, Url};
+use std::path::Path;
+
+use temporalio_sdk_core::{ClientOptions, RetryClient, Url, WorkflowClientTrait};
+
+use gtr_core::outbox::QueuedSignal;
 
 pub async fn connect() -> anyhow::Result<RetryClient<temporalio_sdk_core::Client>> {
     let opts = ClientOptions::builder()
@@ -10,3 +14,116 @@ pub async fn connect() -> anyhow::Result<RetryClient<temporalio_sdk_core::Client
     let client = opts.connect("default", None).await?;
     Ok(client)
 }
+
+/// Whether mutating commands should queue locally when Temporal is down
+/// (set `GTR_OFFLINE_QUEUE=1`).
+pub fn offline_queue_enabled() -> bool {
+    matches!(
+        std::env::var("GTR_OFFLINE_QUEUE").as_deref(),
+        Ok("1") | Ok("true") | Ok("yes")
+    )
+}
+
+/// How a signal sent through [`signal_or_queue`] was handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    Sent,
+    Queued,
+}
+
+/// Send a signal, falling back to the local outbox when Temporal is
+/// unreachable and offline queueing is enabled. Any backlog is replayed
+/// first so signals land in the order they were issued; while earlier
+/// signals to the same workflow are still stuck, this one queues behind them.
+pub async fn signal_or_queue(
+    workflow_id: &str,
+    signal_name: &str,
+    payload: Option<serde_json::Value>,
+) -> anyhow::Result<Delivery> {
+    let signal = QueuedSignal::new(workflow_id, signal_name, payload);
+
+    match connect().await {
+        Ok(client) => {
+            if !replay_outbox(&client, workflow_id).await {
+                return queue_behind(&signal);
+            }
+            gtr_temporal::outbox::deliver(&client, &signal).await?;
+            Ok(Delivery::Sent)
+        }
+        Err(e) => queue_offline(&signal, e),
+    }
+}
+
+/// Like [`signal_or_queue`], but a signal Temporal rejects because its
+/// workflow isn't running is parked in `dead_letter_dir` instead of failing.
+/// Returns `None` when that happens; other errors are returned.
+pub async fn signal_or_dead_letter(
+    workflow_id: &str,
+    signal_name: &str,
+    payload: Option<serde_json::Value>,
+    dead_letter_dir: &Path,
+) -> anyhow::Result<Option<Delivery>> {
+    let signal = QueuedSignal::new(workflow_id, signal_name, payload);
+
+    match connect().await {
+        Ok(client) => {
+            if !replay_outbox(&client, workflow_id).await {
+                return queue_behind(&signal).map(Some);
+            }
+            match gtr_temporal::outbox::deliver(&client, &signal).await {
+                Ok(()) => Ok(Some(Delivery::Sent)),
+                Err(e) if gtr_temporal::outbox::is_not_found(&e) => {
+                    let path = gtr_core::outbox::enqueue(dead_letter_dir, &signal)?;
+                    tracing::debug!(
+                        "{workflow_id} rejected {signal_name} ({e}); kept {}",
+                        path.display()
+                    );
+                    Ok(None)
+                }
+                Err(e) => Err(e),
+            }
+        }
+        Err(e) => queue_offline(&signal, e).map(Some),
+    }
+}
+
+/// Replay the outbox ahead of a new signal to `workflow_id`. A replay that
+/// fails is logged rather than holding up the new signal. Returns false if
+/// earlier signals to `workflow_id` are still queued, so the new one must
+/// queue behind them to keep their order.
+async fn replay_outbox<C>(client: &C, workflow_id: &str) -> bool
+where
+    C: WorkflowClientTrait + Sync,
+{
+    let outbox_dir = gtr_core::dirs::outbox_dir();
+    if let Err(e) = gtr_temporal::outbox::replay(client, &outbox_dir).await {
+        tracing::warn!("Outbox replay failed: {e}");
+    }
+    !gtr_core::outbox::pending(&outbox_dir)
+        .unwrap_or_default()
+        .iter()
+        .any(|(_, queued)| queued.workflow_id == workflow_id)
+}
+
+/// Queue `signal` behind the earlier signals to its workflow that are still
+/// in the outbox.
+fn queue_behind(signal: &QueuedSignal) -> anyhow::Result<Delivery> {
+    let path = gtr_core::outbox::enqueue(&gtr_core::dirs::outbox_dir(), signal)?;
+    tracing::debug!(
+        "Earlier signals to {} still queued; queued {}",
+        signal.workflow_id,
+        path.display()
+    );
+    Ok(Delivery::Queued)
+}
+
+/// Queue `signal` in the outbox after failing to connect with `error`, if
+/// offline queueing is enabled.
+fn queue_offline(signal: &QueuedSignal, error: anyhow::Error) -> anyhow::Result<Delivery> {
+    if !offline_queue_enabled() {
+        return Err(error);
+    }
+    let path = gtr_core::outbox::enqueue(&gtr_core::dirs::outbox_dir(), signal)?;
+    tracing::debug!("Temporal unreachable ({error}); queued {}", path.display());
+    Ok(Delivery::Queued)
+}
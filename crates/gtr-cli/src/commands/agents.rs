@@ -1,3 +1,5 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
 use clap::Subcommand;
 use temporalio_sdk_core::WorkflowClientTrait;
 
@@ -12,13 +14,63 @@ pub enum AgentsCommand {
     },
     /// Summary of running agents (count and names)
     Status,
+    /// Kill an agent's tmux session and respawn it, resuming its last session
+    Restart {
+        /// Agent ID (e.g., "mayor", "gtr-witness")
+        id: String,
+    },
+    /// Show an agent's terminal output log (~/.gtr/runtime/<id>/output.log)
+    Logs {
+        /// Agent ID
+        id: String,
+        /// Keep printing output as it is written
+        #[arg(short, long)]
+        follow: bool,
+        /// Number of trailing lines to show first
+        #[arg(short = 'n', long, default_value_t = 100)]
+        lines: usize,
+    },
+    /// Type text into an agent's session (answer a prompt, send `/compact`, ...)
+    Send {
+        /// Agent ID
+        id: String,
+        /// Text to type; words are joined with spaces
+        #[arg(required = true, num_args = 1..)]
+        text: Vec<String>,
+        /// Type the text without pressing Enter
+        #[arg(long)]
+        no_enter: bool,
+    },
+    /// Append stdin to an agent's output log (used by tmux pipe-pane)
+    #[command(hide = true)]
+    LogSink {
+        /// Agent ID
+        id: String,
+    },
 }
 
+/// Prompt for an agent respawned from its `env.json`; `{event}` says what
+/// happened to its last session.
+pub(crate) const RESPAWN_PROMPT: &str = "Your session {event}. \
+    Use $RGT_BIN instead of rgt (env var has the full path). \
+    Run `$RGT_BIN prime` to restore context, then `$RGT_BIN hook` and `$RGT_BIN mail inbox`.";
+
 pub async fn run(cmd: &AgentsCommand) -> anyhow::Result<()> {
     match cmd {
         AgentsCommand::List => handle_list().await,
         AgentsCommand::Show { name } => handle_show(name).await,
         AgentsCommand::Status => handle_status().await,
+        AgentsCommand::Restart { id } => handle_restart(id),
+        AgentsCommand::Logs { id, follow, lines } => handle_logs(id, *follow, *lines).await,
+        AgentsCommand::Send { id, text, no_enter } => {
+            gtr_temporal::pty::send_keys(id, &text.join(" "), !no_enter)?;
+            println!("Sent to {id}");
+            Ok(())
+        }
+        AgentsCommand::LogSink { id } => {
+            gtr_temporal::output_log::sink(id, std::io::stdin().lock())?;
+            Ok(())
+        }
     }
 }
 
@@ -115,6 +167,73 @@ async fn handle_status() -> anyhow::Result<()> {
     Ok(())
 }
 
+fn handle_restart(agent_id: &str) -> anyhow::Result<()> {
+    // Read env.json first — killing the session removes the runtime dir
+    let prompt = RESPAWN_PROMPT.replace("{event}", "was restarted");
+    let input = gtr_temporal::activities::spawn_agent::respawn_input(agent_id, &prompt)?;
+    if gtr_temporal::pty::kill_agent(agent_id)? {
+        println!("Killed session for {agent_id}");
+    }
+
+    let resumed = input.resume_session_id.clone();
+    let output = gtr_temporal::activities::spawn_agent::spawn(input)?;
+    match resumed {
+        Some(session_id) => println!(
+            "Restarted {agent_id} in {} (PID {}), resuming session {session_id}",
+            output.tmux_session, output.pid
+        ),
+        None => println!(
+            "Restarted {agent_id} in {} (PID {}) with a fresh session",
+            output.tmux_session, output.pid
+        ),
+    }
+    Ok(())
+}
+
+async fn handle_logs(agent_id: &str, follow: bool, lines: usize) -> anyhow::Result<()> {
+    let path = gtr_temporal::output_log::log_path(agent_id);
+    let content = match std::fs::read(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && follow => Vec::new(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            anyhow::bail!("No output log for '{agent_id}' at {}", path.display())
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let text = String::from_utf8_lossy(&content);
+    let all: Vec<&str> = text.lines().collect();
+    for line in &all[all.len().saturating_sub(lines)..] {
+        println!("{line}");
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    let mut offset = content.len() as u64;
+    loop {
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        let Ok(len) = std::fs::metadata(&path).map(|m| m.len()) else {
+            continue;
+        };
+        // Rotated: the live log started over
+        if len < offset {
+            offset = 0;
+        }
+        if len == offset {
+            continue;
+        }
+        let mut file = std::fs::File::open(&path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut chunk = Vec::new();
+        file.read_to_end(&mut chunk)?;
+        offset += chunk.len() as u64;
+        let mut out = std::io::stdout().lock();
+        out.write_all(&chunk)?;
+        out.flush()?;
+    }
+}
+
 fn format_timestamp(ts: &prost_wkt_types::Timestamp) -> String {
     let secs = ts.seconds;
     let nanos = ts.nanos as u64;
@@ -1,77 +1,38 @@
-use std::collections::HashMap;
+#[cfg(unix)]
 use std::ffi::CString;
-use std::path::PathBuf;
 
 use clap::Args;
+use gtr_core::config::SessionBackendKind;
 
 #[derive(Debug, Args)]
-#[command(about = "Attach to a live agent tmux session (interactive Claude Code)")]
+#[command(about = "Attach to a live agent session (interactive Claude Code)")]
 pub struct AttachCommand {
     /// Agent ID to attach to (e.g., "mayor", "gtr-polecat-furiosa")
-    pub agent: String,
-}
-
-/// Respawn an agent's tmux session. Reads env.json from the old runtime dir,
-/// cleans up, then spawns a fresh process.
-fn respawn_agent(agent_id: &str) -> anyhow::Result<()> {
-    let runtime_dir = gtr_temporal::pty::runtime_dir(agent_id);
-
-    // Read env vars from previous spawn (if available).
-    let env_path = runtime_dir.join("env.json");
-    let mut env: HashMap<String, String> = if env_path.exists() {
-        let data = std::fs::read_to_string(&env_path)?;
-        serde_json::from_str(&data).unwrap_or_default()
-    } else {
-        HashMap::new()
-    };
-
-    // Determine work_dir from saved env or fall back to ~/.gtr
-    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
-    let work_dir = env
-        .remove("__GTR_WORK_DIR")
-        .unwrap_or_else(|| format!("{home}/.gtr"));
-
-    // Ensure GTR_AGENT is set
-    env.entry("GTR_AGENT".into())
-        .or_insert_with(|| agent_id.to_string());
-
-    // Ensure rgt binary is on PATH and RGT_BIN is set
-    if let Ok(current_exe) = std::env::current_exe() {
-        if let Some(exe_dir) = current_exe.parent() {
-            let existing_path = env
-                .get("PATH")
-                .cloned()
-                .or_else(|| std::env::var("PATH").ok())
-                .unwrap_or_default();
-            env.insert(
-                "PATH".into(),
-                format!("{}:{existing_path}", exe_dir.display()),
-            );
-        }
-        env.insert("RGT_BIN".into(), current_exe.to_string_lossy().to_string());
-    }
-
-    let prompt = "You are being reattached after your previous session ended. \
-                  Use $RGT_BIN instead of rgt (env var has the full path). \
-                  Run `$RGT_BIN prime` to restore context, then `$RGT_BIN hook` and `$RGT_BIN mail inbox`.";
+    #[arg(required_unless_present = "grid", conflicts_with = "grid")]
+    pub agent: Option<String>,
 
-    // Clean up stale runtime dir
-    gtr_temporal::pty::cleanup(agent_id)?;
+    /// Observe a whole rig: one read-only pane each for its witness,
+    /// refinery and running polecats
+    #[arg(long, value_name = "RIG")]
+    pub grid: Option<String>,
 
-    // Spawn new tmux session
-    gtr_temporal::pty::spawn_with_server(
-        agent_id,
-        "claude",
-        &["--dangerously-skip-permissions".into(), "--disable-slash-commands".into(), "--disallowedTools=Skill,AskUserQuestion,EnterPlanMode".into(), prompt.into()],
-        &PathBuf::from(&work_dir),
-        &env,
-    )?;
-
-    Ok(())
+    /// Watch without sending keystrokes; any number of observers can attach
+    #[arg(long)]
+    pub read_only: bool,
 }
 
 pub async fn run(cmd: &AttachCommand) -> anyhow::Result<()> {
-    let agent_id = &cmd.agent;
+    if let Some(rig) = &cmd.grid {
+        return attach_grid(rig);
+    }
+    let Some(agent_id) = &cmd.agent else {
+        anyhow::bail!("Name an agent to attach to, or use --grid <rig>");
+    };
+
+    // Observers never respawn a dead agent
+    if cmd.read_only && !gtr_temporal::pty::is_alive(agent_id) {
+        anyhow::bail!("Agent '{agent_id}' has no live session to observe.");
+    }
 
     // Check if agent session is running
     if !gtr_temporal::pty::is_alive(agent_id) {
@@ -79,7 +40,10 @@ pub async fn run(cmd: &AttachCommand) -> anyhow::Result<()> {
         let env_path = gtr_temporal::pty::runtime_dir(agent_id).join("env.json");
         if env_path.exists() {
             println!("Agent '{agent_id}' session ended. Respawning...");
-            respawn_agent(agent_id)?;
+            let prompt = crate::commands::agents::RESPAWN_PROMPT
+                .replace("{event}", "ended and you are being reattached");
+            let input = gtr_temporal::activities::spawn_agent::respawn_input(agent_id, &prompt)?;
+            gtr_temporal::activities::spawn_agent::spawn(input)?;
             // Give the new process a moment to initialize
             tokio::time::sleep(std::time::Duration::from_millis(500)).await;
         } else {
@@ -89,20 +53,123 @@ pub async fn run(cmd: &AttachCommand) -> anyhow::Result<()> {
         }
     }
 
-    let session = gtr_temporal::pty::tmux_session_name(agent_id);
-    println!("Attaching to '{agent_id}' (tmux session '{session}'). Ctrl+\\ to detach.\n");
-
-    // exec into tmux attach-session — this replaces the current process
-    let tmux = CString::new("tmux")?;
-    let args = [
-        CString::new("tmux")?,
-        CString::new("-L")?,
-        CString::new("gtr")?,
-        CString::new("attach-session")?,
-        CString::new("-t")?,
-        CString::new(session.as_str())?,
+    let argv = gtr_temporal::pty::attach_argv(agent_id, cmd.read_only)?;
+    let mode = if cmd.read_only { " read-only" } else { "" };
+    println!(
+        "Attaching{mode} to '{agent_id}' ({}).\n",
+        detach_hint(gtr_temporal::pty::backend_kind(), cmd.read_only)
+    );
+
+    exec_argv(&argv)
+}
+
+fn detach_hint(backend: SessionBackendKind, read_only: bool) -> &'static str {
+    match backend {
+        SessionBackendKind::Tmux => "Ctrl+\\ to detach",
+        _ if read_only => "Ctrl+C to stop watching",
+        SessionBackendKind::Screen => "Ctrl+A d to detach",
+        SessionBackendKind::Pty | SessionBackendKind::Console => "Ctrl+C to stop",
+    }
+}
+
+/// Build a `gtr-grid-<rig>` dashboard session with a tiled pane per live rig
+/// agent and attach to it. Each pane is a nested read-only, ignore-size
+/// client of the agent's own session, so the agents' sessions and pane sizes
+/// are left untouched; the dashboard goes away when you detach.
+fn attach_grid(rig: &str) -> anyhow::Result<()> {
+    if gtr_temporal::pty::backend_kind() != SessionBackendKind::Tmux {
+        anyhow::bail!("--grid needs the tmux session backend; attach to agents one at a time.");
+    }
+    let mut agents = vec![
+        gtr_core::state::witness_workflow_id(rig),
+        gtr_core::state::refinery_workflow_id(rig),
     ];
-    nix::unistd::execvp(&tmux, &args)?;
+    let mut polecats: Vec<String> = gtr_temporal::pty::list_session_metadata()
+        .into_iter()
+        .map(|(agent_id, _)| agent_id)
+        .filter(|agent_id| {
+            gtr_core::state::parse_polecat_workflow_id(agent_id).is_some_and(|(r, _)| r == rig)
+        })
+        .collect();
+    polecats.sort();
+    agents.extend(polecats);
+    agents.retain(|agent_id| gtr_temporal::pty::is_alive(agent_id));
+    if agents.is_empty() {
+        anyhow::bail!("Rig '{rig}' has no live agents to show.");
+    }
+
+    let grid = format!("gtr-grid-{rig}");
+    // Rebuild from scratch so the panes match the rig's current agents
+    let _ = tmux(&["kill-session", "-t", &grid]);
+
+    let observe = |agent_id: &str| {
+        format!(
+            "env -u TMUX tmux -L gtr attach-session -f read-only,ignore-size -t {}",
+            gtr_temporal::pty::tmux_session_name(agent_id)
+        )
+    };
+    tmux(&[
+        "new-session",
+        "-d",
+        "-s",
+        &grid,
+        "-x",
+        "200",
+        "-y",
+        "50",
+        &observe(&agents[0]),
+    ])?;
+    for agent_id in &agents[1..] {
+        tmux(&["split-window", "-t", &grid, &observe(agent_id)])?;
+        // Re-tile after each split so there is always room for the next pane
+        tmux(&["select-layout", "-t", &grid, "tiled"])?;
+    }
+    tmux(&["set-option", "-t", &grid, "destroy-unattached", "on"])?;
+
+    println!(
+        "Observing rig '{rig}': {}. Ctrl+\\ to detach.\n",
+        agents.join(", ")
+    );
+    exec_argv(&["tmux", "-L", "gtr", "attach-session", "-t", &grid].map(String::from))
+}
+
+/// Run a command against the gtr tmux server.
+fn tmux(args: &[&str]) -> anyhow::Result<()> {
+    let output = std::process::Command::new("tmux")
+        .args(["-L", "gtr"])
+        .args(args)
+        .output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "tmux {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// exec into the attach command — this replaces the current process.
+#[cfg(unix)]
+fn exec_argv(argv: &[String]) -> anyhow::Result<()> {
+    let args = argv
+        .iter()
+        .map(|arg| CString::new(arg.as_str()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let Some(program) = args.first() else {
+        anyhow::bail!("Empty attach command");
+    };
+    nix::unistd::execvp(program, &args)?;
 
     unreachable!("execvp replaces the process")
 }
+
+/// No exec on Windows: run the attach command and exit with its status.
+#[cfg(not(unix))]
+fn exec_argv(argv: &[String]) -> anyhow::Result<()> {
+    let Some((program, args)) = argv.split_first() else {
+        anyhow::bail!("Empty attach command");
+    };
+    let status = std::process::Command::new(program).args(args).status()?;
+    std::process::exit(status.code().unwrap_or(1))
+}
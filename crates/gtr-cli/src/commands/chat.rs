@@ -21,6 +21,8 @@ pub async fn run(cmd: &ChatCommand) -> anyhow::Result<()> {
     let signal = AgentMailSignal {
         from: cmd.from.clone(),
         message: cmd.message.clone(),
+        id: Some(gtr_core::ids::mail_id()),
+        ..Default::default()
     };
     let payload = signal.as_json_payload()?;
 
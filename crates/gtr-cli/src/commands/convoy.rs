@@ -1,16 +1,44 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
 use clap::Subcommand;
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
 use temporalio_sdk_core::WorkflowClientTrait;
 
+use gtr_core::backlog::BacklogDef;
+use gtr_core::formula::FormulaDef;
+use gtr_core::types::WorkItem;
+use gtr_temporal::signals::{
+    AddWorkItemSignal, ConvoyDispatchSignal, ConvoyRetrySignal, ConvoyState, WorkItemState,
+};
+
 #[derive(Debug, Subcommand)]
 pub enum ConvoyCommand {
     /// Create a new convoy
     Create {
-        /// Convoy title
-        title: String,
+        /// Convoy title; defaults to the title in the --items file
+        #[arg(required_unless_present = "items")]
+        title: Option<String>,
         /// Priority
         #[arg(short, long, default_value = "medium")]
         priority: String,
+        /// Auto-sling every item to this rig as it's added
+        #[arg(long)]
+        rig: Option<String>,
+        /// Agent runtime for the polecats (with --rig)
+        #[arg(long, default_value = "claude")]
+        agent: String,
+        /// Most items in flight at once (with --rig)
+        #[arg(long, default_value = "4")]
+        high_water: usize,
+        /// Backlog file (.toml or .json, as for `rgt work import`) of items to add
+        #[arg(long)]
+        items: Option<PathBuf>,
+        /// When every item should be done: a time from now (`3d`, `12h`) or
+        /// an RFC 3339 timestamp. The convoy escalates if it's missed.
+        #[arg(long)]
+        deadline: Option<String>,
     },
     /// List active convoys
     List,
@@ -19,21 +47,194 @@ pub enum ConvoyCommand {
         /// Convoy ID
         id: String,
     },
+    /// Add a new work item to a convoy
+    Add {
+        /// Convoy ID
+        id: String,
+        /// Work item title
+        title: String,
+        /// Release-train stage; auto-slung only once earlier stages are done
+        #[arg(long, default_value = "0")]
+        stage: u32,
+    },
+    /// Auto-sling the convoy's items to a rig, a few at a time
+    Sling {
+        /// Convoy ID
+        id: String,
+        /// Rig to spawn polecats on
+        #[arg(long)]
+        rig: String,
+        /// Agent runtime for the polecats
+        #[arg(long, default_value = "claude")]
+        agent: String,
+        /// Most items in flight (working or waiting to merge) at once;
+        /// the rig's `max_polecats` also applies
+        #[arg(long, default_value = "4")]
+        high_water: usize,
+    },
+    /// Cancel a convoy, closing its unfinished items and killing their polecats
+    Cancel {
+        /// Convoy ID
+        id: String,
+    },
+    /// Start the convoy's failed items again and re-sling them
+    Retry {
+        /// Convoy ID
+        id: String,
+    },
+    /// Create a convoy with a work item per step of a formula
+    FromFormula {
+        /// Formula name (in ~/.gtr/formulas) or path to its TOML file
+        formula: String,
+        /// Variables in key=value format
+        #[arg(short, long, value_parser = crate::commands::formula::parse_var)]
+        var: Vec<(String, String)>,
+        /// Auto-sling every item to this rig as it's added
+        #[arg(long)]
+        rig: Option<String>,
+        /// Agent runtime for the polecats (with --rig)
+        #[arg(long, default_value = "claude")]
+        agent: String,
+        /// Most items in flight at once (with --rig)
+        #[arg(long, default_value = "4")]
+        high_water: usize,
+    },
 }
 
 pub async fn run(cmd: &ConvoyCommand) -> anyhow::Result<()> {
     match cmd {
-        ConvoyCommand::Create { title, priority } => handle_create(title, priority).await,
+        ConvoyCommand::Create {
+            title,
+            priority,
+            rig,
+            agent,
+            high_water,
+            items,
+            deadline,
+        } => {
+            let dispatch = rig.as_ref().map(|rig| ConvoyDispatchSignal {
+                rig: rig.clone(),
+                runtime: Some(agent.clone()),
+                high_water: *high_water,
+            });
+            let deadline = deadline.as_deref().map(parse_deadline).transpose()?;
+            handle_create(
+                title.as_deref(),
+                priority,
+                dispatch,
+                deadline,
+                items.as_deref(),
+            )
+            .await
+        }
         ConvoyCommand::List => handle_list().await,
         ConvoyCommand::Show { id } => handle_show(id).await,
+        ConvoyCommand::Add { id, title, stage } => handle_add(id, title, *stage).await,
+        ConvoyCommand::Sling {
+            id,
+            rig,
+            agent,
+            high_water,
+        } => handle_sling(id, rig, agent, *high_water).await,
+        ConvoyCommand::Cancel { id } => handle_cancel(id).await,
+        ConvoyCommand::Retry { id } => handle_retry(id).await,
+        ConvoyCommand::FromFormula {
+            formula,
+            var,
+            rig,
+            agent,
+            high_water,
+        } => {
+            let dispatch = rig.as_ref().map(|rig| ConvoyDispatchSignal {
+                rig: rig.clone(),
+                runtime: Some(agent.clone()),
+                high_water: *high_water,
+            });
+            handle_from_formula(formula, var, dispatch).await
+        }
     }
 }
 
-async fn handle_create(title: &str, _priority: &str) -> anyhow::Result<()> {
+async fn handle_create(
+    title: Option<&str>,
+    _priority: &str,
+    dispatch: Option<ConvoyDispatchSignal>,
+    deadline: Option<DateTime<Utc>>,
+    items: Option<&Path>,
+) -> anyhow::Result<()> {
+    if dispatch.as_ref().is_some_and(|d| d.high_water == 0) {
+        anyhow::bail!("--high-water must be at least 1");
+    }
+    let backlog = items.map(BacklogDef::from_file).transpose()?;
+    let work_items = match &backlog {
+        Some(backlog) => backlog.staged_work_items()?,
+        None => Vec::new(),
+    };
+    let Some(title) = title.or(backlog.as_ref().map(|b| b.title.as_str())) else {
+        anyhow::bail!("A convoy title is required");
+    };
+
     let client = crate::client::connect().await?;
-    let id = gtr_core::ids::convoy_id();
+    let id = start_convoy(&client, title, dispatch.as_ref(), deadline).await?;
+    println!("Created convoy: {id} — {title}");
+    if let Some(deadline) = deadline {
+        println!("Deadline: {}", deadline.format("%Y-%m-%d %H:%M UTC"));
+    }
+    if let Some(dispatch) = &dispatch {
+        println!(
+            "Auto-slinging to rig {}, at most {} item(s) in flight",
+            dispatch.rig, dispatch.high_water
+        );
+    }
+    if !work_items.is_empty() {
+        add_items(&client, &id, &work_items).await?;
+        println!("\nAdded {} work item(s)", work_items.len());
+    }
+    Ok(())
+}
+
+async fn handle_from_formula(
+    formula: &str,
+    vars: &[(String, String)],
+    dispatch: Option<ConvoyDispatchSignal>,
+) -> anyhow::Result<()> {
+    if dispatch.as_ref().is_some_and(|d| d.high_water == 0) {
+        anyhow::bail!("--high-water must be at least 1");
+    }
+    let path = crate::commands::formula::formula_path(formula);
+    let def = FormulaDef::from_file(&path)
+        .map_err(|e| anyhow::anyhow!("Can't load formula '{formula}' ({}): {e}", path.display()))?;
+    let vars: HashMap<String, String> = vars.iter().cloned().collect();
+    let backlog = BacklogDef::from_formula(&def, &vars)?;
+    let items = backlog.staged_work_items()?;
+
+    let client = crate::client::connect().await?;
+    let id = start_convoy(&client, &backlog.title, dispatch.as_ref(), None).await?;
+    println!("Created convoy: {id} — {}", backlog.title);
+    if let Some(dispatch) = &dispatch {
+        println!(
+            "Auto-slinging to rig {}, at most {} item(s) in flight",
+            dispatch.rig, dispatch.high_water
+        );
+    }
+    add_items(&client, &id, &items).await?;
+    println!("\nAdded {} step(s) as work items", items.len());
+    Ok(())
+}
 
-    let input_payload = (id.as_str(), title).as_json_payload()?;
+/// Start a convoy workflow, auto-slinging its items if `dispatch` is set.
+/// Returns the new convoy's ID.
+pub(crate) async fn start_convoy<C>(
+    client: &C,
+    title: &str,
+    dispatch: Option<&ConvoyDispatchSignal>,
+    deadline: Option<DateTime<Utc>>,
+) -> anyhow::Result<String>
+where
+    C: WorkflowClientTrait + Sync,
+{
+    let id = gtr_core::ids::convoy_id();
+    let input_payload = (id.as_str(), title, dispatch, deadline).as_json_payload()?;
     client
         .start_workflow(
             vec![input_payload],
@@ -44,8 +245,56 @@ async fn handle_create(title: &str, _priority: &str) -> anyhow::Result<()> {
             Default::default(),
         )
         .await?;
+    Ok(id)
+}
 
-    println!("Created convoy: {id} — {title}");
+/// `3d`-style offsets from now, or an RFC 3339 timestamp.
+fn parse_deadline(s: &str) -> anyhow::Result<DateTime<Utc>> {
+    if let Some(offset) = gtr_core::config::parse_duration(s) {
+        return Ok(Utc::now() + chrono::Duration::from_std(offset)?);
+    }
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| {
+            anyhow::anyhow!("Invalid deadline '{s}' (expected e.g. 3d, 12h or an RFC 3339 time)")
+        })
+}
+
+/// Add `items` to a convoy, each in its stage; the convoy starts each as a
+/// child workflow.
+pub(crate) async fn add_items<C>(
+    client: &C,
+    convoy_id: &str,
+    items: &[(WorkItem, u32)],
+) -> anyhow::Result<()>
+where
+    C: WorkflowClientTrait + Sync,
+{
+    for (item, stage) in items {
+        let signal = AddWorkItemSignal {
+            work_item_id: item.id.0.clone(),
+            title: item.title.clone(),
+            item: Some(item.clone()),
+            stage: *stage,
+        };
+        client
+            .signal_workflow_execution(
+                convoy_id.to_string(),
+                String::new(),
+                "add_work_item".to_string(),
+                Some(signal.as_json_payload()?.into()),
+                None,
+            )
+            .await?;
+        if *stage > 0 {
+            println!(
+                "  {} [{}] stage {stage}: {}",
+                item.id.0, item.priority, item.title
+            );
+        } else {
+            println!("  {} [{}] {}", item.id.0, item.priority, item.title);
+        }
+    }
     Ok(())
 }
 
@@ -100,6 +349,146 @@ async fn handle_show(id: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn handle_add(id: &str, title: &str, stage: u32) -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+    let work_item_id = gtr_core::ids::work_item_id();
+    let signal = AddWorkItemSignal {
+        work_item_id: work_item_id.clone(),
+        title: title.to_string(),
+        item: None,
+        stage,
+    };
+    client
+        .signal_workflow_execution(
+            id.to_string(),
+            String::new(),
+            "add_work_item".to_string(),
+            Some(signal.as_json_payload()?.into()),
+            None,
+        )
+        .await?;
+    if stage > 0 {
+        println!("Added {work_item_id} to convoy {id} (stage {stage}) — {title}");
+    } else {
+        println!("Added {work_item_id} to convoy {id} — {title}");
+    }
+    Ok(())
+}
+
+async fn handle_sling(id: &str, rig: &str, agent: &str, high_water: usize) -> anyhow::Result<()> {
+    if high_water == 0 {
+        anyhow::bail!("--high-water must be at least 1");
+    }
+    let client = crate::client::connect().await?;
+    let signal = ConvoyDispatchSignal {
+        rig: rig.to_string(),
+        runtime: Some(agent.to_string()),
+        high_water,
+    };
+    client
+        .signal_workflow_execution(
+            id.to_string(),
+            String::new(),
+            "convoy_dispatch".to_string(),
+            Some(signal.as_json_payload()?.into()),
+            None,
+        )
+        .await?;
+    println!("Convoy {id} → rig {rig}, at most {high_water} item(s) in flight");
+    Ok(())
+}
+
+async fn handle_cancel(id: &str) -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+    client
+        .signal_workflow_execution(
+            id.to_string(),
+            String::new(),
+            "cancel_convoy".to_string(),
+            None,
+            None,
+        )
+        .await?;
+    println!("Cancelling convoy {id}...");
+
+    // The convoy returns what it tore down; give it a few seconds to finish
+    for _ in 0..20 {
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        let events = gtr_temporal::history::fetch_events(&client, id).await?;
+        let Some(result) = gtr_temporal::history::result(&events) else {
+            continue;
+        };
+        let state: ConvoyState = serde_json::from_str(&result)?;
+        if state.status != "cancelled" {
+            println!("Convoy {id} had already finished ({})", state.status);
+            return Ok(());
+        }
+        println!("Closed {} work item(s):", state.cancelled_items.len());
+        for item in &state.cancelled_items {
+            println!("  {item}");
+        }
+        println!("Killed {} polecat(s):", state.killed_polecats.len());
+        for polecat in &state.killed_polecats {
+            println!("  {polecat}");
+        }
+        return Ok(());
+    }
+    println!("Convoy {id} hasn't finished cancelling yet; check `rgt convoy show {id}`");
+    Ok(())
+}
+
+/// Recreate every item of a running convoy whose last run ended failed. The
+/// new run links the failed one through `retry_of`.
+async fn handle_retry(id: &str) -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+    let events = gtr_temporal::history::fetch_events(&client, id).await?;
+    if gtr_temporal::history::result(&events).is_some() {
+        anyhow::bail!("Convoy {id} has finished; only a running convoy can retry items");
+    }
+
+    let mut retried = 0;
+    for work_item_id in gtr_temporal::history::convoy_items(&events) {
+        let item_events = gtr_temporal::history::fetch_events(&client, &work_item_id).await?;
+        let failed = gtr_temporal::history::result(&item_events)
+            .and_then(|r| serde_json::from_str::<WorkItemState>(&r).ok())
+            .is_some_and(|state| state.status == "failed");
+        if !failed {
+            continue;
+        }
+        let Some(mut item) = gtr_temporal::history::work_item(&item_events) else {
+            continue;
+        };
+        let run_id = client
+            .describe_workflow_execution(work_item_id.clone(), None)
+            .await?
+            .workflow_execution_info
+            .and_then(|info| info.execution)
+            .map(|e| e.run_id)
+            .unwrap_or_default();
+        item.retry_of = Some(run_id.clone());
+        let title = item.title.clone();
+        let signal = ConvoyRetrySignal { item };
+        client
+            .signal_workflow_execution(
+                id.to_string(),
+                String::new(),
+                "convoy_retry".to_string(),
+                Some(signal.as_json_payload()?.into()),
+                None,
+            )
+            .await?;
+        println!("  {work_item_id} {title} (failed run {run_id})");
+        retried += 1;
+    }
+
+    if retried == 0 {
+        println!("No failed items in convoy {id}");
+    } else {
+        println!("\nRetrying {retried} item(s); an auto-slinging convoy re-slings them to its rig");
+    }
+    Ok(())
+}
+
 pub fn workflow_status_str(status: i32) -> &'static str {
     match status {
         0 => "Unspecified",
@@ -37,7 +37,7 @@ pub async fn run(cmd: &CrewCommand) -> anyhow::Result<()> {
     let client = crate::client::connect().await?;
     match cmd {
         CrewCommand::Add { name, rig } => {
-            let agent_id = format!("{rig}-crew-{name}");
+            let agent_id = gtr_core::state::crew_workflow_id(rig, name);
             // Start agent workflow with crew role
             let input_payload = (agent_id.as_str(), "crew").as_json_payload()?;
             client
@@ -59,7 +59,7 @@ pub async fn run(cmd: &CrewCommand) -> anyhow::Result<()> {
             let payload = reg.as_json_payload()?;
             client
                 .signal_workflow_execution(
-                    format!("rig-{rig}"),
+                    gtr_core::state::rig_workflow_id(rig),
                     String::new(),
                     "rig_register_agent".to_string(),
                     Some(payload.into()),
@@ -77,7 +77,7 @@ pub async fn run(cmd: &CrewCommand) -> anyhow::Result<()> {
             let mut found = false;
             for exec in &resp.executions {
                 if let Some(info) = &exec.execution {
-                    if info.workflow_id.contains("-crew-") {
+                    if gtr_core::state::parse_crew_workflow_id(&info.workflow_id).is_some() {
                         let status =
                             crate::commands::convoy::workflow_status_str(exec.status);
                         println!("  {}  {status}", info.workflow_id);
@@ -101,7 +101,9 @@ pub async fn run(cmd: &CrewCommand) -> anyhow::Result<()> {
                 .await?;
             for exec in &resp.executions {
                 if let Some(info) = &exec.execution {
-                    if info.workflow_id.contains(&format!("-crew-{name}")) {
+                    let is_member = gtr_core::state::parse_crew_workflow_id(&info.workflow_id)
+                        .is_some_and(|(_, crew_name)| &crew_name == name);
+                    if is_member {
                         client
                             .signal_workflow_execution(
                                 info.workflow_id.clone(),
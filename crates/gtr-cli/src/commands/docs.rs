@@ -0,0 +1,48 @@
+use std::path::{Path, PathBuf};
+
+use clap::Subcommand;
+
+#[derive(Debug, Subcommand)]
+pub enum DocsCommand {
+    /// Generate man pages (rgt.1 on stdout, or one page per command with --out-dir)
+    Man {
+        /// Write rgt.1, rgt-mail.1, rgt-mail-send.1, ... into this directory
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
+    },
+}
+
+/// `app` is the full command tree, including any user-defined aliases.
+pub fn run(cmd: &DocsCommand, app: clap::Command) -> anyhow::Result<()> {
+    match cmd {
+        DocsCommand::Man { out_dir: None } => {
+            clap_mangen::Man::new(app).render(&mut std::io::stdout())?;
+        }
+        DocsCommand::Man { out_dir: Some(dir) } => {
+            std::fs::create_dir_all(dir)?;
+            let written = write_man_pages(app, "", dir)?;
+            println!("Wrote {written} man pages to {}", dir.display());
+            println!("Add it to MANPATH, or copy the pages into a man1 directory.");
+        }
+    }
+    Ok(())
+}
+
+/// Render `cmd` and every visible subcommand, naming pages `rgt-<sub>-<sub>.1`.
+fn write_man_pages(cmd: clap::Command, prefix: &str, dir: &Path) -> anyhow::Result<usize> {
+    let name = if prefix.is_empty() {
+        cmd.get_name().to_string()
+    } else {
+        format!("{prefix}-{}", cmd.get_name())
+    };
+
+    let mut page = Vec::new();
+    clap_mangen::Man::new(cmd.clone().name(name.clone())).render(&mut page)?;
+    std::fs::write(dir.join(format!("{name}.1")), page)?;
+
+    let mut written = 1;
+    for sub in cmd.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        written += write_man_pages(sub.clone(), &name, dir)?;
+    }
+    Ok(written)
+}
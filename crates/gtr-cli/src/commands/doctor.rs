@@ -1,6 +1,16 @@
+use std::collections::HashSet;
+
+use clap::Args;
 use temporalio_sdk_core::WorkflowClientTrait;
 
-pub async fn run() -> anyhow::Result<()> {
+#[derive(Debug, Args)]
+pub struct DoctorCommand {
+    /// Kill orphaned agent sessions and clean up their runtime dirs
+    #[arg(long)]
+    pub fix: bool,
+}
+
+pub async fn run(cmd: &DoctorCommand) -> anyhow::Result<()> {
     println!("Gas Town Doctor — System Health Check");
     println!("======================================");
 
@@ -19,7 +29,7 @@ pub async fn run() -> anyhow::Result<()> {
 
     // Check Mayor
     print!("Mayor workflow...      ");
-    check_workflow(&client, "mayor").await;
+    check_workflow(&client, &gtr_core::state::mayor_workflow_id()).await;
 
     // Check Witness
     print!("Witness workflow...    ");
@@ -27,7 +37,7 @@ pub async fn run() -> anyhow::Result<()> {
 
     // Check Boot monitor...
     print!("Boot monitor...        ");
-    check_workflow(&client, "boot").await;
+    check_workflow(&client, &gtr_core::state::boot_workflow_id()).await;
 
     // Check Refinery
     print!("Refinery...            ");
@@ -61,6 +71,42 @@ pub async fn run() -> anyhow::Result<()> {
         Err(e) => println!("FAILED: {e}"),
     }
 
+    // Sessions whose workflow crashed or was terminated
+    print!("Orphaned sessions...   ");
+    let query = "ExecutionStatus = 'Running'";
+    match client
+        .list_workflow_executions(1000, vec![], query.to_string())
+        .await
+    {
+        Ok(resp) => {
+            let owners: HashSet<String> = resp
+                .executions
+                .iter()
+                .filter_map(|exec| exec.execution.as_ref())
+                .map(|e| e.workflow_id.clone())
+                .collect();
+            let report = gtr_temporal::pty::reconcile_sessions(&owners, cmd.fix);
+            let found = report.sessions.len() + report.runtime_dirs.len();
+            if found == 0 {
+                println!("none");
+            } else {
+                let action = if cmd.fix { "cleaned up" } else { "found" };
+                println!(
+                    "{} sessions, {} stale runtime dirs {action}",
+                    report.sessions.len(),
+                    report.runtime_dirs.len()
+                );
+                for agent_id in report.sessions.iter().chain(&report.runtime_dirs) {
+                    println!("  - {agent_id}");
+                }
+                if !cmd.fix {
+                    println!("  Run `rgt doctor --fix` to kill and clean them up.");
+                }
+            }
+        }
+        Err(e) => println!("FAILED: {e}"),
+    }
+
     println!("======================================");
     Ok(())
 }
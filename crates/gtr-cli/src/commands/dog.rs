@@ -53,7 +53,7 @@ pub async fn run(cmd: &DogCommand) -> anyhow::Result<()> {
                 .start_workflow(
                     vec![input_payload],
                     "work".to_string(),
-                    format!("dog-{name}"),
+                    gtr_core::state::dog_workflow_id(name),
                     "dog_wf".to_string(),
                     None,
                     Default::default(),
@@ -84,7 +84,7 @@ pub async fn run(cmd: &DogCommand) -> anyhow::Result<()> {
         }
         DogCommand::Status { name } => {
             let resp = client
-                .describe_workflow_execution(format!("dog-{name}"), None)
+                .describe_workflow_execution(gtr_core::state::dog_workflow_id(name), None)
                 .await?;
             if let Some(info) = resp.workflow_execution_info {
                 let status =
@@ -110,7 +110,7 @@ pub async fn run(cmd: &DogCommand) -> anyhow::Result<()> {
             let payload = signal.as_json_payload()?;
             client
                 .signal_workflow_execution(
-                    format!("dog-{name}"),
+                    gtr_core::state::dog_workflow_id(name),
                     String::new(),
                     "dog_dispatch".to_string(),
                     Some(payload.into()),
@@ -122,7 +122,7 @@ pub async fn run(cmd: &DogCommand) -> anyhow::Result<()> {
         DogCommand::Release { name } => {
             client
                 .signal_workflow_execution(
-                    format!("dog-{name}"),
+                    gtr_core::state::dog_workflow_id(name),
                     String::new(),
                     "dog_release".to_string(),
                     None,
@@ -134,7 +134,7 @@ pub async fn run(cmd: &DogCommand) -> anyhow::Result<()> {
         DogCommand::Stop { name } => {
             client
                 .signal_workflow_execution(
-                    format!("dog-{name}"),
+                    gtr_core::state::dog_workflow_id(name),
                     String::new(),
                     "dog_stop".to_string(),
                     None,
@@ -1,8 +1,8 @@
 use clap::Parser;
-use temporalio_common::protos::coresdk::AsJsonPayloadExt;
-use temporalio_sdk_core::WorkflowClientTrait;
+use gtr_core::outbox::QueuedSignal;
+use gtr_temporal::signals::{PolecatDoneSignal, RefineryEnqueueSignal, SIGNAL_REFINERY_ENQUEUE};
 
-use gtr_temporal::signals::{PolecatDoneSignal, RefineryEnqueueSignal};
+use crate::client::Delivery;
 
 #[derive(Debug, Parser)]
 #[command(about = "Mark work done and enqueue branch for merge (defaults to GTR_WORK_ITEM env var)")]
@@ -21,6 +21,33 @@ pub struct DoneCommand {
     /// Summary of work done (sent to polecat workflow and mayor)
     #[arg(short, long)]
     pub summary: Option<String>,
+
+    /// Pre-flight only: checkout, rebase and test the branch without merging
+    /// (the polecat is not signaled)
+    #[arg(long)]
+    pub check_only: bool,
+
+    /// Mark the work done but hold the branch for review; it is enqueued
+    /// once someone runs `rgt mq approve <work-id>`
+    #[arg(long, conflicts_with = "check_only")]
+    pub draft: bool,
+
+    /// Per-attempt test deadline in the refinery, in seconds (default 600)
+    #[arg(long, value_name = "SECS")]
+    pub merge_timeout: Option<u64>,
+
+    /// Times the refinery retries a test run that timed out (default 1)
+    #[arg(long, value_name = "N")]
+    pub merge_retries: Option<u32>,
+
+    /// Merge into this branch instead of the rig's default (e.g. release/1.4)
+    #[arg(long, value_name = "BRANCH")]
+    pub target: Option<String>,
+
+    /// Named merge queue to route to (e.g. hotfix); its refinery must be
+    /// running (`rgt mq start --queue <name>`)
+    #[arg(long, value_name = "NAME")]
+    pub queue: Option<String>,
 }
 
 pub async fn run(cmd: &DoneCommand) -> anyhow::Result<()> {
@@ -34,58 +61,75 @@ pub async fn run(cmd: &DoneCommand) -> anyhow::Result<()> {
             )
         })?;
 
-    let client = crate::client::connect().await?;
-
     // Signal polecat that work is done (if we're running as an agent)
-    if let Ok(agent_id) = std::env::var("GTR_AGENT") {
+    if let (false, Ok(agent_id)) = (cmd.check_only, std::env::var("GTR_AGENT")) {
         let done_signal = PolecatDoneSignal {
             branch: cmd.branch.clone(),
             status: "completed".to_string(),
             summary: cmd.summary.clone(),
         };
-        let payload = done_signal.as_json_payload()?;
-        client
-            .signal_workflow_execution(
-                agent_id.clone(),
-                String::new(),
-                "polecat_done".to_string(),
-                Some(payload.into()),
-                None,
-            )
-            .await
-            .ok(); // Don't fail if polecat signal fails
-        println!("Signaled polecat '{agent_id}' — done");
+        let payload = serde_json::to_value(&done_signal)?;
+        // Don't fail if polecat signal fails
+        match crate::client::signal_or_queue(&agent_id, "polecat_done", Some(payload)).await {
+            Ok(Delivery::Sent) => println!("Signaled polecat '{agent_id}' — done"),
+            Ok(Delivery::Queued) => {
+                println!("Temporal unreachable — done signal for '{agent_id}' queued")
+            }
+            Err(_) => {}
+        }
     }
 
-    // Determine refinery workflow ID based on rig context
-    let refinery_id = std::env::var("GTR_RIG")
-        .map(|rig| format!("{rig}-refinery"))
-        .unwrap_or_else(|_| "refinery".to_string());
+    // Determine refinery workflow ID based on rig context and queue
+    let rig = std::env::var("GTR_RIG").ok();
+    let refinery_id = crate::commands::mq::refinery_id(rig.as_deref(), cmd.queue.as_deref());
 
     let signal = RefineryEnqueueSignal {
         work_item_id: work_item_id.clone(),
         branch: cmd.branch.clone(),
         priority: cmd.priority,
+        validate_only: cmd.check_only,
+        timeout_secs: cmd.merge_timeout,
+        max_retries: cmd.merge_retries,
+        target_branch: cmd.target.clone(),
     };
 
-    let payload = signal.as_json_payload()?;
-    let enqueue_result = client
-        .signal_workflow_execution(
-            refinery_id.clone(),
-            String::new(),
-            "refinery_enqueue".to_string(),
-            Some(payload.into()),
-            None,
-        )
-        .await;
+    let payload = serde_json::to_value(&signal)?;
+    if cmd.draft {
+        let draft = QueuedSignal::new(&refinery_id, SIGNAL_REFINERY_ENQUEUE, Some(payload));
+        std::fs::create_dir_all(gtr_core::dirs::drafts_dir())?;
+        gtr_core::atomic::write_atomic(
+            &crate::commands::mq::draft_path(&work_item_id),
+            serde_json::to_string_pretty(&draft)?,
+        )?;
+        println!(
+            "Draft recorded for '{}' (branch: {}) — merge with `rgt mq approve {}`",
+            work_item_id, cmd.branch, work_item_id
+        );
+        return Ok(());
+    }
+
+    let enqueue_result =
+        crate::client::signal_or_queue(&refinery_id, SIGNAL_REFINERY_ENQUEUE, Some(payload)).await;
 
     match enqueue_result {
-        Ok(_) => {
+        Ok(Delivery::Sent) if cmd.check_only => {
+            println!(
+                "Enqueued '{}' (branch: {}) for validation only → {refinery_id}",
+                work_item_id, cmd.branch
+            );
+        }
+        Ok(Delivery::Sent) => {
             println!(
                 "Enqueued '{}' (branch: {}, priority: P{}) for merge → {refinery_id}",
                 work_item_id, cmd.branch, cmd.priority
             );
         }
+        Ok(Delivery::Queued) => {
+            println!(
+                "Temporal unreachable — merge enqueue for '{}' (branch: {}) queued for delivery",
+                work_item_id, cmd.branch
+            );
+        }
         Err(e) => {
             println!(
                 "Done signaled, but refinery enqueue failed (branch saved on '{}'): {e}",
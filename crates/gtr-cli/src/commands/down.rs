@@ -1,6 +1,21 @@
+use std::time::Duration;
+
+use clap::Args;
+use gtr_core::config::ShutdownConfig;
 use temporalio_sdk_core::WorkflowClientTrait;
 
-pub async fn run() -> anyhow::Result<()> {
+#[derive(Debug, Default, Args)]
+pub struct DownCommand {
+    /// Kill agents immediately instead of asking them to hand off first
+    #[arg(long)]
+    pub force: bool,
+
+    /// Seconds to wait for agents to hand off (default: [shutdown] grace_secs in town.toml)
+    #[arg(long, conflicts_with = "force")]
+    pub grace: Option<u64>,
+}
+
+pub async fn run(cmd: &DownCommand) -> anyhow::Result<()> {
     let client = crate::client::connect().await?;
 
     println!("Shutting down Gas Town...");
@@ -16,10 +31,38 @@ pub async fn run() -> anyhow::Result<()> {
         return Ok(());
     }
 
-    // Step 2: Signal stop to all workflows and kill PTY processes
+    // Step 2: Ask live agents to wrap up and hand off, then kill them
     let mut stopped = 0;
     let mut killed = 0;
 
+    if !cmd.force {
+        let agents: Vec<String> = resp
+            .executions
+            .iter()
+            .filter_map(|exec| exec.execution.as_ref())
+            .map(|e| e.workflow_id.clone())
+            .filter(|id| gtr_temporal::pty::is_alive(id))
+            .collect();
+        if !agents.is_empty() {
+            let config = ShutdownConfig::load();
+            let grace = cmd.grace.map_or(config.grace(), Duration::from_secs);
+            println!(
+                "  Asking {} agents to hand off (up to {}s)...",
+                agents.len(),
+                grace.as_secs()
+            );
+            let report = gtr_temporal::pty::shutdown_agents(&agents, &config.message, grace);
+            println!(
+                "  {} handed off, {} exited on their own",
+                report.checkpointed.len(),
+                report.exited.len()
+            );
+            killed += report.killed;
+        }
+    }
+
+    // Step 3: Signal stop to all workflows and kill remaining PTY processes
+
     for exec in &resp.executions {
         let wf_id = exec
             .execution
@@ -32,7 +75,7 @@ pub async fn run() -> anyhow::Result<()> {
         // Determine the right stop signal based on workflow type
         let signal = match wf_type {
             "mayor_wf" => "mayor_stop",
-            "boot_wf" => "agent_stop",
+            "boot_wf" | "deputy_wf" => "agent_stop",
             "rig_wf" => "rig_stop",
             "refinery_wf" => "refinery_stop",
             "polecat_wf" => "polecat_kill",
@@ -67,14 +110,14 @@ pub async fn run() -> anyhow::Result<()> {
         println!("  Killed {killed} agent processes");
     }
 
-    // Step 3: Clean up runtime directory
+    // Step 4: Clean up runtime directory (agent output logs are kept)
     let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
     let runtime_dir = format!("{home}/.gtr/runtime");
     if let Ok(entries) = std::fs::read_dir(&runtime_dir) {
         let mut cleaned = 0;
         for entry in entries.flatten() {
             if entry.path().is_dir() {
-                std::fs::remove_dir_all(entry.path()).ok();
+                gtr_temporal::pty::cleanup(&entry.file_name().to_string_lossy()).ok();
                 cleaned += 1;
             }
         }
@@ -1,26 +1,49 @@
-use clap::Parser;
-use temporalio_sdk_core::WorkflowClientTrait;
+use clap::{Parser, Subcommand};
+
+use crate::client::Delivery;
 
 #[derive(Debug, Parser)]
-#[command(about = "Escalate a work item — sends an 'escalate' signal to boost priority immediately")]
+#[command(
+    about = "Escalate a work item — sends an 'escalate' signal to boost priority immediately",
+    args_conflicts_with_subcommands = true
+)]
 pub struct EscalateCommand {
     /// Work item workflow ID to escalate
-    pub id: String,
+    pub id: Option<String>,
+
+    #[command(subcommand)]
+    pub action: Option<EscalateAction>,
 }
 
-pub async fn run(cmd: &EscalateCommand) -> anyhow::Result<()> {
-    let client = crate::client::connect().await?;
+#[derive(Debug, Subcommand)]
+pub enum EscalateAction {
+    /// Acknowledge an escalation — stops further re-escalation
+    Ack {
+        /// Work item workflow ID
+        id: String,
+    },
+    /// Mark an escalation resolved — clears it from the mayor's open list
+    Resolve {
+        /// Work item workflow ID
+        id: String,
+    },
+}
 
-    client
-        .signal_workflow_execution(
-            cmd.id.clone(),
-            String::new(),
-            "escalate".to_string(),
-            None,
-            None,
-        )
-        .await?;
+pub async fn run(cmd: &EscalateCommand) -> anyhow::Result<()> {
+    let (id, signal, verb) = match (&cmd.action, &cmd.id) {
+        (Some(EscalateAction::Ack { id }), _) => (id, "escalation_ack", "Acknowledged escalation"),
+        (Some(EscalateAction::Resolve { id }), _) => {
+            (id, "escalation_resolve", "Resolved escalation")
+        }
+        (None, Some(id)) => (id, "escalate", "Escalated work item"),
+        (None, None) => anyhow::bail!("Specify a work item ID, or `ack`/`resolve <ID>`"),
+    };
 
-    println!("Escalated work item: {}", cmd.id);
+    match crate::client::signal_or_queue(id, signal, None).await? {
+        Delivery::Sent => println!("{verb}: {id}"),
+        Delivery::Queued => {
+            println!("Temporal unreachable — '{signal}' for {id} queued for delivery")
+        }
+    }
     Ok(())
 }
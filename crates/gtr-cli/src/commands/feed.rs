@@ -32,6 +32,17 @@ pub async fn run(cmd: &FeedCommand) -> anyhow::Result<()> {
 
         let now = chrono::Local::now().format("%H:%M:%S");
         writeln!(out, "=== GTR Feed [{now}] ===\n")?;
+        if let Some(notice) = crate::commands::mayor::maintenance_notice(&client).await {
+            writeln!(out, "*** MAINTENANCE — {notice} ***\n")?;
+        }
+        let progress = crate::commands::mayor::convoy_progress(&client).await;
+        for convoy in progress.iter().filter(|c| c.at_risk) {
+            writeln!(
+                out,
+                "*** AT RISK — convoy {} missed its deadline ({}/{} done) ***\n",
+                convoy.convoy_id, convoy.done, convoy.total
+            )?;
+        }
 
         // Section 1: Agent tree
         writeln!(out, "--- Agents ---")?;
@@ -48,6 +59,7 @@ pub async fn run(cmd: &FeedCommand) -> anyhow::Result<()> {
         let mut dogs = vec![];
         let mut gates = vec![];
         let mut convoys = vec![];
+        let mut rigs = vec![];
         let mut other = vec![];
 
         for exec in &agents.executions {
@@ -73,7 +85,21 @@ pub async fn run(cmd: &FeedCommand) -> anyhow::Result<()> {
                 "polecat_wf" => polecats.push(entry),
                 "dog_wf" => dogs.push(entry),
                 "gate_wf" => gates.push(entry),
-                "convoy_wf" => convoys.push(entry),
+                "convoy_wf" => match progress.iter().find(|c| c.convoy_id == wf_id) {
+                    Some(convoy) => convoys.push(format!(
+                        "  {}",
+                        crate::commands::mayor::format_convoy_progress(convoy)
+                    )),
+                    None => convoys.push(entry),
+                },
+                "rig_wf" => {
+                    let rig = gtr_core::state::parse_rig_workflow_id(wf_id)
+                        .unwrap_or_else(|| wf_id.to_string());
+                    match crate::commands::rig::rig_lifecycle(&client, &rig).await {
+                        Some(lifecycle) => rigs.push(format!("  {rig} [{lifecycle}]")),
+                        None => rigs.push(entry),
+                    }
+                }
                 "agent_wf" => other.push(entry),
                 _ => other.push(format!("  {wf_id} [{wf_type}] ({status})")),
             }
@@ -126,7 +152,15 @@ pub async fn run(cmd: &FeedCommand) -> anyhow::Result<()> {
             writeln!(out, "  (no running workflows)")?;
         }
 
-        // Section 2: Convoys
+        // Section 2: Rigs
+        if !rigs.is_empty() {
+            writeln!(out, "\n--- Rigs ---")?;
+            for r in &rigs {
+                writeln!(out, "{r}")?;
+            }
+        }
+
+        // Section 3: Convoys
         if !convoys.is_empty() {
             writeln!(out, "\n--- Convoys ---")?;
             for c in &convoys {
@@ -134,7 +168,7 @@ pub async fn run(cmd: &FeedCommand) -> anyhow::Result<()> {
             }
         }
 
-        // Section 3: Gates
+        // Section 4: Gates
         if !gates.is_empty() {
             writeln!(out, "\n--- Gates ---")?;
             for g in &gates {
@@ -142,7 +176,7 @@ pub async fn run(cmd: &FeedCommand) -> anyhow::Result<()> {
             }
         }
 
-        // Section 4: Recent completions
+        // Section 5: Recent completions
         writeln!(out, "\n--- Recent Completions ---")?;
         let completed_query = "ExecutionStatus != 'Running'".to_string();
         let completed = client
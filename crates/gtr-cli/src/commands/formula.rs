@@ -1,9 +1,12 @@
 use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
 use clap::Subcommand;
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
-use temporalio_sdk_core::WorkflowClientTrait;
+use temporalio_sdk_core::{WorkflowClientTrait, WorkflowOptions};
 
+use gtr_core::formula::{evaluate_condition, interpolate, FormulaDef, OnFailure, StepKind};
 use gtr_temporal::workflows::formula::FormulaInput;
 
 #[derive(Debug, Subcommand)]
@@ -16,9 +19,47 @@ pub enum FormulaCommand {
         #[arg(short, long, value_parser = parse_var)]
         var: Vec<(String, String)>,
     },
+    /// Run a formula as a molecule that executes its own steps
+    Run {
+        /// Formula name in ~/.gtr/formulas/, or a path to its TOML file
+        formula: String,
+        /// Variables in key=value format
+        #[arg(short, long, value_parser = parse_var)]
+        var: Vec<(String, String)>,
+        /// Steps to run at once (overrides the formula's `max_parallel`)
+        #[arg(long)]
+        max_parallel: Option<usize>,
+        /// Print the execution plan instead of starting a molecule
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Run formulas on their `schedule`
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleAction,
+    },
 }
 
-fn parse_var(s: &str) -> Result<(String, String), String> {
+#[derive(Debug, Subcommand)]
+pub enum ScheduleAction {
+    /// List formulas in ~/.gtr/formulas/ that have a schedule
+    List,
+    /// Start running a formula on its schedule
+    Enable {
+        /// Formula name in ~/.gtr/formulas/, or a path to its TOML file
+        formula: String,
+        /// Variables in key=value format, used for every run
+        #[arg(short, long, value_parser = parse_var)]
+        var: Vec<(String, String)>,
+    },
+    /// Stop running a formula on its schedule
+    Disable {
+        /// Formula name
+        formula: String,
+    },
+}
+
+pub(crate) fn parse_var(s: &str) -> Result<(String, String), String> {
     let (key, value) = s
         .split_once('=')
         .ok_or_else(|| format!("expected key=value, got: {s}"))?;
@@ -28,6 +69,17 @@ fn parse_var(s: &str) -> Result<(String, String), String> {
 pub async fn run(cmd: &FormulaCommand) -> anyhow::Result<()> {
     match cmd {
         FormulaCommand::Cook { path, var } => handle_cook(path, var).await,
+        FormulaCommand::Run {
+            formula,
+            var,
+            max_parallel,
+            dry_run,
+        } => handle_run(formula, var, *max_parallel, *dry_run).await,
+        FormulaCommand::Schedule { action } => match action {
+            ScheduleAction::List => handle_schedule_list().await,
+            ScheduleAction::Enable { formula, var } => handle_schedule_enable(formula, var).await,
+            ScheduleAction::Disable { formula } => handle_schedule_disable(formula).await,
+        },
     }
 }
 
@@ -35,14 +87,23 @@ async fn handle_cook(path: &str, vars: &[(String, String)]) -> anyhow::Result<()
     let content = std::fs::read_to_string(path)?;
 
     // Validate it parses before sending to workflow
-    let def = gtr_core::formula::FormulaDef::from_toml(&content)?;
-    def.topo_sort()?;
+    let def = FormulaDef::from_toml(&content)?;
+    def.validate()?;
+    if let Some(step) = def.steps.iter().find(|s| s.kind != StepKind::Command) {
+        anyhow::bail!(
+            "step '{}' isn't a command step — run the formula with `rgt formula run`",
+            step.name
+        );
+    }
 
-    let vars_map: HashMap<String, String> = vars.iter().cloned().collect();
+    let vars_map = def.with_defaults(&ask_for_vars(&def, vars)?);
+    def.check_vars(&vars_map)?;
 
     let input = FormulaInput {
         formula_toml: content,
         vars: vars_map,
+        max_parallel: None,
+        formulas: HashMap::new(),
     };
 
     let client = crate::client::connect().await?;
@@ -64,3 +125,358 @@ async fn handle_cook(path: &str, vars: &[(String, String)]) -> anyhow::Result<()
     println!("Workflow: {wf_id}");
     Ok(())
 }
+
+/// `formula` itself if it's an existing file, otherwise `<formula>.toml` in
+/// the formulas directory.
+pub(crate) fn formula_path(formula: &str) -> PathBuf {
+    let path = Path::new(formula);
+    if path.exists() {
+        path.to_path_buf()
+    } else {
+        gtr_core::dirs::formulas_dir().join(format!("{formula}.toml"))
+    }
+}
+
+/// Read and validate a formula, returning its TOML alongside the definition.
+fn load_formula(formula: &str) -> anyhow::Result<(String, FormulaDef)> {
+    read_formula(formula, &formula_path(formula))
+}
+
+fn read_formula(formula: &str, path: &Path) -> anyhow::Result<(String, FormulaDef)> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Can't load formula '{formula}' ({}): {e}", path.display()))?;
+    let def = FormulaDef::from_toml(&content)?;
+    def.validate()?;
+    Ok((content, def))
+}
+
+/// The `--var`s given, plus any declared var without a default that the user
+/// is asked for on the terminal. Nothing is asked when stdin isn't a terminal.
+fn ask_for_vars(
+    def: &FormulaDef,
+    given: &[(String, String)],
+) -> anyhow::Result<HashMap<String, String>> {
+    let mut vars: HashMap<String, String> = given.iter().cloned().collect();
+    if !std::io::stdin().is_terminal() {
+        return Ok(vars);
+    }
+    let unset: Vec<_> = def
+        .vars
+        .iter()
+        .filter(|v| v.default.is_none() && !vars.contains_key(&v.name))
+        .collect();
+    for var in unset {
+        let mut prompt = var.name.clone();
+        if let Some(description) = &var.description {
+            prompt.push_str(&format!(" — {description}"));
+        }
+        if !var.allowed.is_empty() {
+            prompt.push_str(&format!(" [{}]", var.allowed.join("/")));
+        }
+        loop {
+            print!("{prompt}: ");
+            std::io::stdout().flush()?;
+            let mut line = String::new();
+            if std::io::stdin().read_line(&mut line)? == 0 {
+                return Ok(vars);
+            }
+            let value = line.trim();
+            if value.is_empty() {
+                continue;
+            }
+            if !var.accepts(value) {
+                eprintln!("{} must be one of: {}", var.name, var.allowed.join(", "));
+                continue;
+            }
+            vars.insert(var.name.clone(), value.to_string());
+            break;
+        }
+    }
+    Ok(vars)
+}
+
+/// The TOML of every formula `def`'s `formula` steps run, theirs included,
+/// by name. A formula that would end up running itself is an error.
+fn nested_formulas(def: &FormulaDef) -> anyhow::Result<HashMap<String, String>> {
+    let mut formulas = HashMap::new();
+    collect_formulas(def, &mut vec![def.name.clone()], &mut formulas)?;
+    Ok(formulas)
+}
+
+fn collect_formulas(
+    def: &FormulaDef,
+    chain: &mut Vec<String>,
+    formulas: &mut HashMap<String, String>,
+) -> anyhow::Result<()> {
+    let names = def
+        .steps
+        .iter()
+        .filter(|s| s.kind == StepKind::Formula)
+        .filter_map(|s| s.formula.as_ref());
+    for name in names {
+        if chain.contains(name) {
+            anyhow::bail!(
+                "formula '{name}' runs itself: {} → {name}",
+                chain.join(" → ")
+            );
+        }
+        if formulas.contains_key(name) {
+            continue;
+        }
+        let path = gtr_core::dirs::formulas_dir().join(format!("{name}.toml"));
+        let (content, nested) = read_formula(name, &path)?;
+        formulas.insert(name.clone(), content);
+        chain.push(name.clone());
+        collect_formulas(&nested, chain, formulas)?;
+        chain.pop();
+    }
+    Ok(())
+}
+
+/// Start a `molecule_wf` that executes `input`'s formula.
+async fn start_molecule(
+    id: &str,
+    def: &FormulaDef,
+    input: &FormulaInput,
+    options: WorkflowOptions,
+) -> anyhow::Result<()> {
+    let step_names: Vec<String> = def.steps.iter().map(|s| s.name.clone()).collect();
+    let client = crate::client::connect().await?;
+    client
+        .start_workflow(
+            vec![
+                (id.to_string(), def.name.clone(), step_names).as_json_payload()?,
+                input.as_json_payload()?,
+            ],
+            "work".to_string(),
+            id.to_string(),
+            "molecule_wf".to_string(),
+            None,
+            options,
+        )
+        .await?;
+    Ok(())
+}
+
+async fn handle_run(
+    formula: &str,
+    vars: &[(String, String)],
+    max_parallel: Option<usize>,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let (content, def) = load_formula(formula)?;
+    let formulas = nested_formulas(&def)?;
+    if dry_run {
+        let given: HashMap<String, String> = vars.iter().cloned().collect();
+        return print_plan(&def, &def.with_defaults(&given), max_parallel);
+    }
+    let vars = def.with_defaults(&ask_for_vars(&def, vars)?);
+    def.check_vars(&vars)?;
+    let id = format!("mol-{}-{}", def.name, gtr_core::ids::work_item_id());
+    let input = FormulaInput {
+        formula_toml: content,
+        vars,
+        max_parallel,
+        formulas,
+    };
+    start_molecule(&id, &def, &input, Default::default()).await?;
+
+    println!("Running formula '{}' — {} steps", def.name, def.steps.len());
+    println!("Molecule: {id}");
+    println!("Follow it with `rgt mol status {id}`");
+    Ok(())
+}
+
+/// Print what `rgt formula run` would do: the steps wave by wave with their
+/// commands interpolated, how many run at once, and how each `when` would go.
+fn print_plan(
+    def: &FormulaDef,
+    vars: &HashMap<String, String>,
+    max_parallel: Option<usize>,
+) -> anyhow::Result<()> {
+    let max_parallel = max_parallel
+        .or(def.max_parallel)
+        .unwrap_or(gtr_core::formula::DEFAULT_MAX_PARALLEL)
+        .max(1);
+    let waves = def.waves()?;
+    println!(
+        "Plan for formula '{}' — {} steps in {} wave(s), up to {max_parallel} at a time",
+        def.name,
+        def.steps.len(),
+        waves.len()
+    );
+    let missing: Vec<&str> = def
+        .vars
+        .iter()
+        .filter(|v| !vars.contains_key(&v.name))
+        .map(|v| v.name.as_str())
+        .collect();
+    if let Err(e) = def.check_vars(vars) {
+        println!("Warning: {e}");
+    }
+    if let Some(schedule) = &def.schedule {
+        println!("Schedule: {schedule}");
+    }
+
+    for (n, wave) in waves.iter().enumerate() {
+        let together = wave.len().min(max_parallel);
+        println!();
+        if together > 1 {
+            println!(
+                "Wave {} — {} steps, {together} at a time",
+                n + 1,
+                wave.len()
+            );
+        } else {
+            println!("Wave {} — {} step(s), one at a time", n + 1, wave.len());
+        }
+        for step in wave {
+            match step.kind {
+                StepKind::Command => {
+                    let mut command = interpolate(&step.command, vars);
+                    for arg in &step.args {
+                        command.push(' ');
+                        command.push_str(&interpolate(arg, vars));
+                    }
+                    println!("  {:<16} {command}", step.name);
+                }
+                StepKind::Agent => {
+                    let agent = step.agent.as_deref().unwrap_or("a spawned agent");
+                    let prompt = interpolate(step.prompt.as_deref().unwrap_or_default(), vars);
+                    let first_line = prompt.lines().next().unwrap_or_default();
+                    println!("  {:<16} agent ({agent}): {first_line}", step.name);
+                }
+                StepKind::Formula => {
+                    let name = step.formula.as_deref().unwrap_or_default();
+                    println!("  {:<16} formula {name} (child molecule)", step.name);
+                }
+            }
+            if let Some(when) = &step.when {
+                let uses_missing = missing
+                    .iter()
+                    .any(|v| when.contains(&format!("{{{{{v}}}}}")));
+                let outcome = match evaluate_condition(when, vars) {
+                    Ok(true) => "runs".to_string(),
+                    Ok(false) => "skipped".to_string(),
+                    // A missing --var stays missing; step results come later
+                    Err(e) if uses_missing => format!("fails ({e})"),
+                    Err(e) => format!("decided at run time ({e})"),
+                };
+                println!("  {:<16} when {when} → {outcome}", "");
+            }
+            let mut policy = Vec::new();
+            if step.retries > 0 {
+                policy.push(format!("{} retries", step.retries));
+            }
+            if let Some(secs) = step.timeout_secs {
+                policy.push(format!("{secs}s timeout"));
+            }
+            if step.on_failure != OnFailure::Abort {
+                policy.push(format!(
+                    "on failure {}",
+                    String::from(step.on_failure.clone())
+                ));
+            }
+            if let Some(name) = &step.register {
+                policy.push(format!("output as {{{{{name}}}}}"));
+            }
+            if !policy.is_empty() {
+                println!("  {:<16} {}", "", policy.join(", "));
+            }
+        }
+    }
+    println!();
+    println!("Dry run — nothing was started");
+    Ok(())
+}
+
+/// Whether a formula's scheduled molecule is running (between runs counts).
+async fn schedule_enabled(formula: &str) -> bool {
+    let Ok(client) = crate::client::connect().await else {
+        return false;
+    };
+    client
+        .describe_workflow_execution(gtr_core::state::formula_schedule_workflow_id(formula), None)
+        .await
+        .ok()
+        .and_then(|resp| resp.workflow_execution_info)
+        .is_some_and(|info| info.status == 1)
+}
+
+async fn handle_schedule_list() -> anyhow::Result<()> {
+    let dir = gtr_core::dirs::formulas_dir();
+    let mut scheduled = Vec::new();
+    if dir.exists() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "toml") {
+                continue;
+            }
+            match FormulaDef::from_file(&path) {
+                Ok(def) => {
+                    if let Some(schedule) = def.schedule.clone() {
+                        scheduled.push((def.name, schedule));
+                    }
+                }
+                Err(e) => eprintln!("warning: skipping invalid formula {}: {e}", path.display()),
+            }
+        }
+    }
+    if scheduled.is_empty() {
+        println!("No scheduled formulas in {}", dir.display());
+        return Ok(());
+    }
+    scheduled.sort();
+
+    println!("{:<24} {:<20} STATUS", "FORMULA", "SCHEDULE");
+    for (name, schedule) in &scheduled {
+        let status = if schedule_enabled(name).await {
+            "enabled"
+        } else {
+            "disabled"
+        };
+        println!("{name:<24} {schedule:<20} {status}");
+    }
+    Ok(())
+}
+
+async fn handle_schedule_enable(formula: &str, vars: &[(String, String)]) -> anyhow::Result<()> {
+    let (content, def) = load_formula(formula)?;
+    let Some(schedule) = def.schedule.clone() else {
+        anyhow::bail!("Formula '{}' has no schedule", def.name);
+    };
+    if schedule_enabled(&def.name).await {
+        anyhow::bail!("Formula '{}' is already scheduled", def.name);
+    }
+    let vars = def.with_defaults(&ask_for_vars(&def, vars)?);
+    def.check_vars(&vars)?;
+
+    let id = gtr_core::state::formula_schedule_workflow_id(&def.name);
+    let input = FormulaInput {
+        formula_toml: content,
+        vars,
+        max_parallel: None,
+        formulas: nested_formulas(&def)?,
+    };
+    let options = WorkflowOptions {
+        cron_schedule: Some(schedule.clone()),
+        ..Default::default()
+    };
+    start_molecule(&id, &def, &input, options).await?;
+
+    println!("Scheduled formula '{}' — {schedule}", def.name);
+    println!("Molecule: {id} (each run shows up under this ID)");
+    Ok(())
+}
+
+async fn handle_schedule_disable(formula: &str) -> anyhow::Result<()> {
+    if !schedule_enabled(formula).await {
+        anyhow::bail!("Formula '{formula}' isn't scheduled");
+    }
+    let client = crate::client::connect().await?;
+    client
+        .terminate_workflow_execution(gtr_core::state::formula_schedule_workflow_id(formula), None)
+        .await?;
+    println!("Unscheduled formula '{formula}'");
+    Ok(())
+}
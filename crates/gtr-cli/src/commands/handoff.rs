@@ -45,6 +45,8 @@ pub async fn run(cmd: &HandoffCommand) -> anyhow::Result<()> {
     let mail = AgentMailSignal {
         from: format!("{agent_id} (handoff)"),
         message: format!("[HANDOFF] {}", cmd.message),
+        id: Some(gtr_core::ids::mail_id()),
+        ..Default::default()
     };
     let payload = mail.as_json_payload()?;
 
@@ -38,7 +38,7 @@ max_retries = 3
     }
 
     // Step 3: Create plugins directory
-    let plugins_dir = gtr_core::dirs::config_dir().join("plugins");
+    let plugins_dir = gtr_core::dirs::plugins_dir();
     std::fs::create_dir_all(&plugins_dir)?;
     println!("[ok] Created plugins directory at {}", plugins_dir.display());
 
@@ -1,9 +1,21 @@
+use std::path::PathBuf;
+
 use clap::Subcommand;
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
 use temporalio_sdk_core::WorkflowClientTrait;
 
+use gtr_core::config::HumanInboxConfig;
+use gtr_core::mail::{Mail, MailPriority, Mailbox, HUMAN};
+use gtr_core::state::WorkflowId;
+use gtr_temporal::activities::notification::{notify, NotificationInput};
 use gtr_temporal::signals::{AgentMailSignal, AgentNudgeSignal};
 
+use crate::client::Delivery;
+
+/// `rgt mail check`'s exit code when there is unread mail. Claude Code treats
+/// 2 from a hook as blocking, feeding the hook's stderr back to the agent.
+const UNREAD_MAIL_EXIT_CODE: i32 = 2;
+
 #[derive(Debug, Subcommand)]
 pub enum MailCommand {
     /// Send a message to an agent via Temporal signal
@@ -15,6 +27,13 @@ pub enum MailCommand {
         /// Sender identity
         #[arg(short, long, default_value = "cli")]
         from: String,
+        /// low, normal, high or urgent; urgent mail is also typed into the
+        /// recipient's session
+        #[arg(short, long, default_value = "normal")]
+        priority: MailPriority,
+        /// File to send along (repeatable); a copy is kept with the mail
+        #[arg(long = "attach", value_name = "PATH")]
+        attachments: Vec<PathBuf>,
     },
     /// Send a nudge (priority ping) to an agent
     Nudge {
@@ -33,25 +52,31 @@ pub enum MailCommand {
         /// Sender identity
         #[arg(short, long, default_value = "cli")]
         from: String,
+        /// Only agents with this role
+        #[arg(long, value_parser = ["witness", "polecat", "refinery", "crew"])]
+        role: Option<String>,
+        /// Only agents on this rig
+        #[arg(long)]
+        rig: Option<String>,
     },
-    /// Check inbox — query agent workflow for mail (env: GTR_AGENT)
+    /// List unarchived mail in an agent's mailbox (env: GTR_AGENT)
     #[command(alias = "list")]
     Inbox {
         /// Agent workflow ID (defaults to GTR_AGENT env var)
         agent: Option<String>,
     },
-    /// Mark a message as read (requires Temporal query support)
+    /// Show a message and mark it as read
     Read {
-        /// Message index
-        index: usize,
+        /// Message ID, as shown by `rgt mail inbox`
+        id: String,
         /// Agent workflow ID (defaults to GTR_AGENT env var)
         #[arg(long)]
         agent: Option<String>,
     },
-    /// Reply to a message by index
+    /// Reply to a message's sender, in the same thread
     Reply {
-        /// Message index to reply to
-        index: usize,
+        /// ID of the message to reply to
+        id: String,
         /// Reply message
         #[arg(short, long)]
         message: String,
@@ -59,117 +84,302 @@ pub enum MailCommand {
         #[arg(long)]
         agent: Option<String>,
     },
-    /// View a message thread (requires Temporal query support)
+    /// Show the whole conversation a message is part of, across mailboxes
     Thread {
-        /// Message index
-        index: usize,
+        /// ID of any message in the thread
+        id: String,
     },
-    /// Search mail by content (requires Temporal query support)
+    /// Search every mailbox for messages containing all words of a query
     Search {
-        /// Search query
+        /// Words to look for in the body, sender or recipient
         query: String,
+        /// Only mail this agent sent or received
+        #[arg(long)]
+        agent: Option<String>,
+        /// Only mail sent within this long (e.g. 30m, 12h, 2d)
+        #[arg(long)]
+        since: Option<String>,
     },
-    /// Archive a message (requires Temporal query support)
+    /// Archive a message, hiding it from the inbox
     Archive {
-        /// Message index
-        index: usize,
+        /// Message ID
+        id: String,
+        /// Agent workflow ID (defaults to GTR_AGENT env var)
+        #[arg(long)]
+        agent: Option<String>,
     },
-    /// Clear all mail for an agent (requires Temporal query support)
+    /// Delete all mail in an agent's mailbox
     Clear {
         /// Agent workflow ID (defaults to GTR_AGENT env var)
         #[arg(long)]
         agent: Option<String>,
     },
-    /// Check for new mail — quick status via describe (env: GTR_AGENT)
+    /// Print the unread count (env: GTR_AGENT); exits 2 if there is any, so
+    /// it can interrupt an agent from a Claude Code Stop or PreToolUse hook
     Check {
         /// Agent workflow ID (defaults to GTR_AGENT env var)
         #[arg(long)]
         agent: Option<String>,
     },
+    /// The human operator's inbox: list unacknowledged mail, or acknowledge it
+    Human {
+        /// Acknowledge these messages instead of listing
+        #[arg(long, value_name = "ID")]
+        ack: Vec<String>,
+        /// Include acknowledged mail
+        #[arg(long)]
+        all: bool,
+    },
+    /// Mail held back because its recipient wasn't running
+    DeadLetter {
+        #[command(subcommand)]
+        action: DeadLetterAction,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DeadLetterAction {
+    /// List undelivered mail; it goes out when the recipient registers with the mayor
+    List,
 }
 
 pub async fn run(cmd: &MailCommand) -> anyhow::Result<()> {
     match cmd {
-        MailCommand::Send { to, message, from } => handle_send(to, message, from).await,
+        MailCommand::Send {
+            to,
+            message,
+            from,
+            priority,
+            attachments,
+        } => handle_send(to, message, from, *priority, attachments).await,
         MailCommand::Nudge { to, message, from } => handle_nudge(to, message, from).await,
-        MailCommand::Broadcast { message, from } => handle_broadcast(message, from).await,
+        MailCommand::Broadcast {
+            message,
+            from,
+            role,
+            rig,
+        } => {
+            let target = BroadcastTarget {
+                role: role.clone(),
+                rig: rig.clone(),
+            };
+            handle_broadcast(message, from, &target).await
+        }
         MailCommand::Inbox { agent } => {
             let agent_id = resolve_agent(agent)?;
-            println!("Inbox for {agent_id}:");
-            println!("  (mail is stored in agent workflow state — use Temporal query when available)");
-            println!("  Tip: Stop agent to see final state with all mail");
+            let inbox = Mailbox::open(&agent_id).inbox()?;
+            if inbox.is_empty() {
+                println!("No mail for {agent_id}.");
+                return Ok(());
+            }
+            let unread = inbox.iter().filter(|m| !m.read).count();
+            println!("Inbox for {agent_id} ({unread} unread):");
+            for mail in &inbox {
+                print_summary(mail);
+            }
             Ok(())
         }
-        MailCommand::Read { index, agent } => {
+        MailCommand::Human { ack, all } => {
+            let mailbox = Mailbox::open(HUMAN);
+            for id in ack {
+                mailbox.mark_read(id)?;
+                println!("Acknowledged {id}");
+            }
+            if !ack.is_empty() {
+                return Ok(());
+            }
+            let mut inbox = mailbox.inbox()?;
+            if !all {
+                inbox.retain(|m| !m.read);
+            }
+            if inbox.is_empty() {
+                println!("Nothing for the human operator.");
+                return Ok(());
+            }
+            println!("Mail for the human operator:");
+            for mail in &inbox {
+                print_summary(mail);
+            }
+            println!();
+            println!("Read with `rgt mail read <id> --agent human`, acknowledge with `rgt mail human --ack <id>`.");
+            Ok(())
+        }
+        MailCommand::Read { id, agent } => {
             let agent_id = resolve_agent(agent)?;
-            println!("Marked message #{index} as read for {agent_id}");
-            println!("  (read tracking requires Temporal query support on agent workflow)");
+            let mail = Mailbox::open(&agent_id).mark_read(id)?;
+            println!("From:    {}", mail.from);
+            println!("To:      {}", mail.to);
+            println!("Sent:    {}", mail.sent_at.format("%Y-%m-%d %H:%M:%S UTC"));
+            println!("ID:      {}", mail.id);
+            for attachment in &mail.attachments {
+                println!("Attached: {}", attachment.display());
+            }
+            println!();
+            println!("{}", mail.message);
             Ok(())
         }
-        MailCommand::Reply {
-            index,
-            message,
-            agent,
-        } => {
+        MailCommand::Reply { id, message, agent } => {
             let agent_id = resolve_agent(agent)?;
-            // Reply sends a mail back to the original sender
-            // For now, send to self as a thread entry
-            let client = crate::client::connect().await?;
+            let original = Mailbox::open(&agent_id).get(id)?;
             let signal = AgentMailSignal {
-                from: agent_id.clone(),
-                message: format!("[reply to #{index}] {message}"),
+                from: agent_id,
+                message: message.clone(),
+                id: Some(gtr_core::ids::mail_id()),
+                thread_id: Some(original.thread().to_string()),
+                reply_to: Some(original.id.clone()),
+                ..Default::default()
             };
-            let payload = signal.as_json_payload()?;
-            client
-                .signal_workflow_execution(
-                    agent_id.clone(),
-                    String::new(),
-                    "agent_mail".to_string(),
-                    Some(payload.into()),
-                    None,
-                )
-                .await?;
-            println!("Reply to #{index} sent on {agent_id}");
+            match send_mail(&original.from, &signal).await? {
+                Some(Delivery::Sent) => println!("Reply to {id} sent to {}", original.from),
+                Some(Delivery::Queued) => println!(
+                    "Temporal unreachable — reply to {} queued for delivery",
+                    original.from
+                ),
+                None => println!(
+                    "{} isn't running — reply dead-lettered until it registers",
+                    original.from
+                ),
+            }
             Ok(())
         }
-        MailCommand::Thread { index } => {
-            println!("Thread for message #{index}:");
-            println!("  (thread view requires Temporal query support)");
+        MailCommand::Thread { id } => {
+            let thread = gtr_core::mail::thread(&gtr_core::dirs::mail_dir(), id)?;
+            println!("Thread {} ({} messages):", thread[0].thread(), thread.len());
+            for mail in &thread {
+                println!();
+                println!(
+                    "  [{}] {} → {}  ({})",
+                    mail.sent_at.format("%m-%d %H:%M"),
+                    mail.from,
+                    mail.to,
+                    mail.id
+                );
+                for line in mail.message.lines() {
+                    println!("    {line}");
+                }
+                for attachment in &mail.attachments {
+                    println!("    📎 {}", attachment.display());
+                }
+            }
             Ok(())
         }
-        MailCommand::Search { query } => {
-            println!("Searching mail for: {query}");
-            println!("  (search requires Temporal query support on agent workflow)");
+        MailCommand::Search {
+            query,
+            agent,
+            since,
+        } => {
+            let since = match since {
+                Some(s) => {
+                    let Some(age) = gtr_core::config::parse_duration(s) else {
+                        anyhow::bail!("Invalid --since '{s}' (expected e.g. 30m, 12h or 2d)");
+                    };
+                    Some(chrono::Utc::now() - chrono::Duration::from_std(age)?)
+                }
+                None => None,
+            };
+            let found = gtr_core::mail::search(
+                &gtr_core::dirs::mail_dir(),
+                query,
+                agent.as_deref(),
+                since,
+            )?;
+            if found.is_empty() {
+                println!("No mail matches '{query}'.");
+                return Ok(());
+            }
+            println!("{} messages match '{query}':", found.len());
+            for mail in &found {
+                let first_line = mail.message.lines().next().unwrap_or_default();
+                println!(
+                    "  {}  {}  {} → {}: {}",
+                    mail.id,
+                    mail.sent_at.format("%Y-%m-%d %H:%M"),
+                    mail.from,
+                    mail.to,
+                    truncate(first_line, 60)
+                );
+            }
             Ok(())
         }
-        MailCommand::Archive { index } => {
-            println!("Archived message #{index}");
-            println!("  (archive requires Temporal query support on agent workflow)");
+        MailCommand::Archive { id, agent } => {
+            let agent_id = resolve_agent(agent)?;
+            Mailbox::open(&agent_id).archive(id)?;
+            println!("Archived {id} for {agent_id}");
             Ok(())
         }
         MailCommand::Clear { agent } => {
             let agent_id = resolve_agent(agent)?;
-            println!("Cleared all mail for {agent_id}");
-            println!("  (clear requires Temporal query support on agent workflow)");
+            let cleared = Mailbox::open(&agent_id).clear()?;
+            println!("Cleared {cleared} messages for {agent_id}");
             Ok(())
         }
         MailCommand::Check { agent } => {
             let agent_id = resolve_agent(agent)?;
-            let client = crate::client::connect().await?;
-            let resp = client
-                .describe_workflow_execution(agent_id.clone(), None)
-                .await?;
-            if let Some(info) = resp.workflow_execution_info {
-                let status = crate::commands::convoy::workflow_status_str(info.status);
-                println!("Agent {agent_id}: {status} ({} events)", info.history_length);
-            } else {
-                println!("No agent found: {agent_id}");
+            let unread = Mailbox::open(&agent_id).unread()?;
+            println!("{unread}");
+            if unread > 0 {
+                // Hooks show stderr to the agent when they exit 2
+                eprintln!("{agent_id}: {unread} unread — `rgt mail inbox` to list them");
+                std::process::exit(UNREAD_MAIL_EXIT_CODE);
+            }
+            Ok(())
+        }
+        MailCommand::DeadLetter {
+            action: DeadLetterAction::List,
+        } => {
+            let waiting = gtr_core::outbox::pending(&gtr_core::dirs::dead_letter_dir())?;
+            if waiting.is_empty() {
+                println!("No dead-lettered mail.");
+                return Ok(());
+            }
+            println!("{} undelivered message(s):", waiting.len());
+            for (_, signal) in &waiting {
+                let mail = signal
+                    .payload
+                    .clone()
+                    .and_then(|p| serde_json::from_value::<AgentMailSignal>(p).ok())
+                    .unwrap_or_default();
+                let first_line = mail.message.lines().next().unwrap_or_default();
+                println!(
+                    "  {}  {} → {}: {}",
+                    signal.queued_at.format("%Y-%m-%d %H:%M"),
+                    mail.from,
+                    signal.workflow_id,
+                    truncate(first_line, 60)
+                );
             }
             Ok(())
         }
     }
 }
 
+/// One inbox line: unread marker (`!` if also high priority), ID, time,
+/// sender and the start of the message.
+fn print_summary(mail: &Mail) {
+    let marker = match (mail.read, mail.priority >= MailPriority::High) {
+        (false, true) => "!",
+        (false, false) => "●",
+        (true, _) => " ",
+    };
+    let first_line = mail.message.lines().next().unwrap_or_default();
+    println!(
+        "  {marker} {}  {}  {}: {}",
+        mail.id,
+        mail.sent_at.format("%m-%d %H:%M"),
+        mail.from,
+        truncate(first_line, 72)
+    );
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        let cut: String = text.chars().take(max - 1).collect();
+        format!("{cut}…")
+    }
+}
+
 fn resolve_agent(agent: &Option<String>) -> anyhow::Result<String> {
     agent
         .clone()
@@ -177,26 +387,72 @@ fn resolve_agent(agent: &Option<String>) -> anyhow::Result<String> {
         .ok_or_else(|| anyhow::anyhow!("No agent specified. Set GTR_AGENT or use --agent"))
 }
 
-async fn handle_send(to: &str, message: &str, from: &str) -> anyhow::Result<()> {
-    let client = crate::client::connect().await?;
-
+async fn handle_send(
+    to: &str,
+    message: &str,
+    from: &str,
+    priority: MailPriority,
+    attachments: &[PathBuf],
+) -> anyhow::Result<()> {
+    let id = gtr_core::ids::mail_id();
+    let attachments = attachments
+        .iter()
+        .map(|file| gtr_core::mail::store_attachment(&gtr_core::dirs::attachments_dir(), &id, file))
+        .collect::<anyhow::Result<Vec<_>>>()?;
     let signal_data = AgentMailSignal {
         from: from.to_string(),
         message: message.to_string(),
+        id: Some(id),
+        priority,
+        attachments,
+        ..Default::default()
     };
-    let payload = signal_data.as_json_payload()?;
 
-    client
-        .signal_workflow_execution(
-            to.to_string(),
-            String::new(),
-            "agent_mail".to_string(),
-            Some(payload.into()),
-            None,
-        )
-        .await?;
+    match send_mail(to, &signal_data).await? {
+        Some(Delivery::Sent) => println!("Mail sent to {to}: {message}"),
+        Some(Delivery::Queued) => {
+            println!("Temporal unreachable — mail to {to} queued for delivery")
+        }
+        None => println!("{to} isn't running — mail dead-lettered until it registers"),
+    }
+    Ok(())
+}
 
-    println!("Mail sent to {to}: {message}");
+/// Signal `mail` to `to`, queueing it in the outbox if Temporal is down and
+/// dead-lettering it (`None`) if `to` isn't running.
+async fn send_mail(to: &str, mail: &AgentMailSignal) -> anyhow::Result<Option<Delivery>> {
+    if to == HUMAN {
+        deliver_to_human(mail).await?;
+        return Ok(Some(Delivery::Sent));
+    }
+    crate::client::signal_or_dead_letter(
+        to,
+        "agent_mail",
+        Some(serde_json::to_value(mail)?),
+        &gtr_core::dirs::dead_letter_dir(),
+    )
+    .await
+}
+
+/// Store mail for the human operator and announce it on the channels in
+/// town.toml's `[human]` table. Notification failures are only logged.
+async fn deliver_to_human(mail: &AgentMailSignal) -> anyhow::Result<()> {
+    let id = mail.id.clone().unwrap_or_else(gtr_core::ids::mail_id);
+    let stored = mail.to_mail(id, HUMAN, chrono::Utc::now());
+    if !Mailbox::open(HUMAN).deliver(&stored)? {
+        return Ok(());
+    }
+    for (channel, target) in HumanInboxConfig::load().channels() {
+        let input = NotificationInput {
+            channel: channel.to_string(),
+            target: target.to_string(),
+            subject: format!("Mail from {}", stored.from),
+            message: stored.message.clone(),
+        };
+        if let Err(e) = notify(&input).await {
+            tracing::warn!("Could not announce {} on {channel}: {e}", stored.id);
+        }
+    }
     Ok(())
 }
 
@@ -223,25 +479,70 @@ async fn handle_nudge(to: &str, message: &str, from: &str) -> anyhow::Result<()>
     Ok(())
 }
 
-async fn handle_broadcast(message: &str, from: &str) -> anyhow::Result<()> {
+async fn handle_broadcast(
+    message: &str,
+    from: &str,
+    target: &BroadcastTarget,
+) -> anyhow::Result<()> {
     let client = crate::client::connect().await?;
+    let sent = broadcast(&client, message, from, target).await?;
+    if sent == 0 {
+        println!("No running agents reached.");
+    } else {
+        println!("Broadcast sent to {sent} agents: {message}");
+    }
+    Ok(())
+}
+
+/// Which agents a broadcast goes to; unset fields match everyone.
+#[derive(Debug, Clone, Default)]
+pub struct BroadcastTarget {
+    pub role: Option<String>,
+    pub rig: Option<String>,
+}
+
+impl BroadcastTarget {
+    /// Whether the agent running as `wf_id` is targeted, going by the
+    /// workflow ID conventions in `gtr_core::state`.
+    fn matches(&self, wf_id: &str) -> bool {
+        if self.role.is_none() && self.rig.is_none() {
+            return true;
+        }
+        let Some(parsed) = WorkflowId::parse(wf_id) else {
+            return false;
+        };
+        let role_ok = self.role.is_none() || parsed.role() == self.role.as_deref();
+        let rig_ok = self.rig.is_none() || parsed.rig() == self.rig.as_deref();
+        role_ok && rig_ok
+    }
+}
 
+/// Mail `message` to every running agent workflow `target` matches. Returns
+/// how many were reached; failures are logged and skipped.
+pub async fn broadcast<C>(
+    client: &C,
+    message: &str,
+    from: &str,
+    target: &BroadcastTarget,
+) -> anyhow::Result<usize>
+where
+    C: WorkflowClientTrait + Sync,
+{
     let resp = client
         .list_workflow_executions(
             100,
             vec![],
-            "WorkflowType = 'agent_wf' AND ExecutionStatus = 'Running'".to_string(),
+            "WorkflowType IN ('agent_wf', 'polecat_wf', 'witness_wf', 'refinery_wf') \
+             AND ExecutionStatus = 'Running'"
+                .to_string(),
         )
         .await?;
 
-    if resp.executions.is_empty() {
-        println!("No running agents found.");
-        return Ok(());
-    }
-
     let signal_data = AgentMailSignal {
         from: from.to_string(),
         message: message.to_string(),
+        id: Some(gtr_core::ids::mail_id()),
+        ..Default::default()
     };
     let payload = signal_data.as_json_payload()?;
 
@@ -252,6 +553,9 @@ async fn handle_broadcast(message: &str, from: &str) -> anyhow::Result<()> {
             .as_ref()
             .map(|e| e.workflow_id.clone())
             .unwrap_or_default();
+        if !target.matches(&wf_id) {
+            continue;
+        }
 
         if let Err(e) = client
             .signal_workflow_execution(
@@ -268,7 +572,5 @@ async fn handle_broadcast(message: &str, from: &str) -> anyhow::Result<()> {
             sent += 1;
         }
     }
-
-    println!("Broadcast sent to {sent} agents: {message}");
-    Ok(())
+    Ok(sent)
 }
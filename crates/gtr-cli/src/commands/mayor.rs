@@ -1,22 +1,94 @@
 use clap::Subcommand;
+use temporalio_common::protos::coresdk::AsJsonPayloadExt;
 use temporalio_sdk_core::WorkflowClientTrait;
 
+use gtr_temporal::signals::{
+    ApprovalDecision, MaintenanceSignal, SIGNAL_APPROVAL_DECISION, SIGNAL_MAYOR_MAINTENANCE,
+    SIGNAL_REFINERY_PAUSE, SIGNAL_REFINERY_RESUME,
+};
+
+const MAINTENANCE_ON_NOTICE: &str = "Gas Town is entering maintenance. Finish your current step, \
+     commit your work, and don't start anything new until maintenance ends.";
+const MAINTENANCE_OFF_NOTICE: &str = "Maintenance is over. Resume normal work.";
+const PAUSE_TOWN_NOTICE: &str = "Gas Town is paused. Finish your current step, commit your work, \
+     and wait — rigs are parked until the town resumes.";
+const RESUME_TOWN_NOTICE: &str = "Gas Town has resumed. Rigs are back in operation.";
+
 #[derive(Debug, Subcommand)]
 pub enum MayorCommand {
     /// Show mayor workflow status
     Status,
+    /// Enter or leave maintenance mode: notify agents, pause merges and dispatch
+    Maintenance {
+        #[command(subcommand)]
+        action: MaintenanceAction,
+    },
+    /// Park every operational rig, pause refineries and hold new slings
+    PauseTown {
+        /// Notice broadcast to agents
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+    /// Unpark the rigs pause-town parked and end maintenance mode
+    ResumeTown,
+    /// Risky operations waiting for approval (force-pushes, protected merges)
+    Approvals {
+        #[command(subcommand)]
+        action: ApprovalsAction,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ApprovalsAction {
+    /// List pending approval requests
+    List,
+    /// Let the operation go ahead
+    Approve {
+        /// Approval ID, as shown by `rgt mayor approvals list`
+        id: String,
+    },
+    /// Refuse the operation
+    Deny {
+        /// Approval ID, as shown by `rgt mayor approvals list`
+        id: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MaintenanceAction {
+    /// Broadcast the notice, pause every refinery and hold new slings
+    On {
+        /// Notice broadcast to agents
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+    /// Resume refineries and dispatch, and tell agents to carry on
+    Off,
 }
 
 pub async fn run(cmd: &MayorCommand) -> anyhow::Result<()> {
     match cmd {
         MayorCommand::Status => handle_status().await,
+        MayorCommand::Maintenance { action } => match action {
+            MaintenanceAction::On { message } => {
+                handle_maintenance(true, message.as_deref(), &[]).await
+            }
+            MaintenanceAction::Off => handle_maintenance(false, None, &[]).await,
+        },
+        MayorCommand::PauseTown { message } => handle_pause_town(message.as_deref()).await,
+        MayorCommand::ResumeTown => handle_resume_town().await,
+        MayorCommand::Approvals { action } => match action {
+            ApprovalsAction::List => handle_approvals_list().await,
+            ApprovalsAction::Approve { id } => handle_approval_decision(id, true).await,
+            ApprovalsAction::Deny { id } => handle_approval_decision(id, false).await,
+        },
     }
 }
 
 async fn handle_status() -> anyhow::Result<()> {
     let client = crate::client::connect().await?;
     let resp = client
-        .describe_workflow_execution("mayor".to_string(), None)
+        .describe_workflow_execution(gtr_core::state::mayor_workflow_id(), None)
         .await?;
 
     if let Some(info) = resp.workflow_execution_info {
@@ -30,9 +102,252 @@ async fn handle_status() -> anyhow::Result<()> {
         };
         println!("Mayor:   {status}");
         println!("History: {} events", info.history_length);
+        let progress = convoy_progress(&client).await;
+        if !progress.is_empty() {
+            println!("Convoys:");
+            for convoy in &progress {
+                println!("  {}", format_convoy_progress(convoy));
+            }
+        }
     } else {
         println!("Mayor workflow not found. Run `rgt up` to start.");
     }
 
     Ok(())
 }
+
+async fn handle_maintenance(
+    enabled: bool,
+    message: Option<&str>,
+    parked_rigs: &[String],
+) -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+    let notice = match (enabled, message) {
+        (_, Some(message)) => message,
+        (true, None) => MAINTENANCE_ON_NOTICE,
+        (false, None) => MAINTENANCE_OFF_NOTICE,
+    };
+
+    // Town state first, so slings are held before anything else changes
+    let signal = MaintenanceSignal {
+        enabled,
+        message: Some(notice.to_string()),
+        parked_rigs: parked_rigs.to_vec(),
+    };
+    client
+        .signal_workflow_execution(
+            gtr_core::state::mayor_workflow_id(),
+            String::new(),
+            SIGNAL_MAYOR_MAINTENANCE.to_string(),
+            Some(signal.as_json_payload()?.into()),
+            None,
+        )
+        .await?;
+
+    // A paused refinery finishes the entry in hand, then holds the rest
+    let refinery_signal = if enabled {
+        SIGNAL_REFINERY_PAUSE
+    } else {
+        SIGNAL_REFINERY_RESUME
+    };
+    let refineries = client
+        .list_workflow_executions(
+            100,
+            vec![],
+            "WorkflowType = 'refinery_wf' AND ExecutionStatus = 'Running'".to_string(),
+        )
+        .await?;
+    for exec in &refineries.executions {
+        let Some(wf_id) = exec.execution.as_ref().map(|e| e.workflow_id.clone()) else {
+            continue;
+        };
+        if let Err(e) = client
+            .signal_workflow_execution(
+                wf_id.clone(),
+                String::new(),
+                refinery_signal.to_string(),
+                None,
+                None,
+            )
+            .await
+        {
+            tracing::warn!("Failed to signal {wf_id}: {e}");
+        }
+    }
+
+    let sent = crate::commands::mail::broadcast(
+        &client,
+        notice,
+        "mayor",
+        &crate::commands::mail::BroadcastTarget::default(),
+    )
+    .await?;
+    let (state, verb) = if enabled {
+        ("on", "paused")
+    } else {
+        ("off", "resumed")
+    };
+    println!("Maintenance mode {state}");
+    println!("  {} refinery queue(s) {verb}", refineries.executions.len());
+    println!("  Notice sent to {sent} agent(s): {notice}");
+    Ok(())
+}
+
+/// Maintenance mode plus parking every operational rig. The parked rigs are
+/// recorded with the mayor so resume-town only unparks those, leaving rigs
+/// that were already parked or docked alone.
+async fn handle_pause_town(message: Option<&str>) -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+    let rigs = client
+        .list_workflow_executions(
+            100,
+            vec![],
+            "WorkflowType = 'rig_wf' AND ExecutionStatus = 'Running'".to_string(),
+        )
+        .await?;
+    let mut operational = Vec::new();
+    for exec in &rigs.executions {
+        let Some(rig) = exec
+            .execution
+            .as_ref()
+            .and_then(|e| gtr_core::state::parse_rig_workflow_id(&e.workflow_id))
+        else {
+            continue;
+        };
+        let lifecycle = crate::commands::rig::rig_lifecycle(&client, &rig).await;
+        if lifecycle.as_deref() == Some("operational") {
+            operational.push(rig);
+        }
+    }
+
+    let notice = message.unwrap_or(PAUSE_TOWN_NOTICE);
+    handle_maintenance(true, Some(notice), &operational).await?;
+    let parked = signal_rigs(&client, &operational, "rig_park").await;
+    println!("  {parked} rig(s) parked");
+    Ok(())
+}
+
+async fn handle_resume_town() -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+    let events =
+        gtr_temporal::history::fetch_events(&client, &gtr_core::state::mayor_workflow_id()).await?;
+    let rigs = gtr_temporal::history::paused_rigs(&events);
+
+    let unparked = signal_rigs(&client, &rigs, "rig_unpark").await;
+    handle_maintenance(false, Some(RESUME_TOWN_NOTICE), &[]).await?;
+    println!("  {unparked} rig(s) unparked");
+    Ok(())
+}
+
+/// Send a lifecycle signal to each rig, returning how many took it.
+async fn signal_rigs<C>(client: &C, rigs: &[String], signal: &str) -> usize
+where
+    C: WorkflowClientTrait + Sync,
+{
+    let mut sent = 0;
+    for rig in rigs {
+        match client
+            .signal_workflow_execution(
+                gtr_core::state::rig_workflow_id(rig),
+                String::new(),
+                signal.to_string(),
+                None,
+                None,
+            )
+            .await
+        {
+            Ok(_) => sent += 1,
+            Err(e) => tracing::warn!("Failed to signal rig {rig}: {e}"),
+        }
+    }
+    sent
+}
+
+async fn handle_approvals_list() -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+    let events =
+        gtr_temporal::history::fetch_events(&client, &gtr_core::state::mayor_workflow_id()).await?;
+    let pending = gtr_temporal::history::pending_approvals(&events);
+    if pending.is_empty() {
+        println!("No pending approvals.");
+        return Ok(());
+    }
+    for request in &pending {
+        println!(
+            "{}  {}  {}  (from {})",
+            request.id, request.operation, request.target, request.requested_by
+        );
+        println!("    {}", request.detail);
+    }
+    Ok(())
+}
+
+async fn handle_approval_decision(id: &str, approved: bool) -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+    let events =
+        gtr_temporal::history::fetch_events(&client, &gtr_core::state::mayor_workflow_id()).await?;
+    if !gtr_temporal::history::pending_approvals(&events)
+        .iter()
+        .any(|r| r.id == id)
+    {
+        anyhow::bail!("No pending approval {id}. See `rgt mayor approvals list`.");
+    }
+
+    // An agent deciding is named in the record; anyone else is the human
+    let by = std::env::var("GTR_AGENT").unwrap_or_else(|_| gtr_core::mail::HUMAN.to_string());
+    let decision = ApprovalDecision {
+        id: id.to_string(),
+        approved,
+        by,
+    };
+    client
+        .signal_workflow_execution(
+            gtr_core::state::mayor_workflow_id(),
+            String::new(),
+            SIGNAL_APPROVAL_DECISION.to_string(),
+            Some(decision.as_json_payload()?.into()),
+            None,
+        )
+        .await?;
+    println!("{} {id}", if approved { "Approved" } else { "Denied" });
+    Ok(())
+}
+
+/// The town's maintenance notice, if maintenance mode is on.
+pub async fn maintenance_notice<C>(client: &C) -> Option<String>
+where
+    C: WorkflowClientTrait + Sync,
+{
+    let events = gtr_temporal::history::fetch_events(client, &gtr_core::state::mayor_workflow_id())
+        .await
+        .ok()?;
+    gtr_temporal::history::maintenance(&events)
+}
+
+/// Progress of each open convoy, as last reported to the mayor.
+pub async fn convoy_progress<C>(client: &C) -> Vec<gtr_temporal::signals::ConvoyProgressSignal>
+where
+    C: WorkflowClientTrait + Sync,
+{
+    match gtr_temporal::history::fetch_events(client, &gtr_core::state::mayor_workflow_id()).await {
+        Ok(events) => gtr_temporal::history::convoy_progress(&events),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// `<id>  <done>/<total> (<pct>%)  <title>`, flagged when past its deadline.
+pub fn format_convoy_progress(convoy: &gtr_temporal::signals::ConvoyProgressSignal) -> String {
+    let flag = if convoy.at_risk { "  AT RISK" } else { "" };
+    let due = convoy
+        .deadline
+        .map(|d| format!(" — due {}", d.format("%Y-%m-%d %H:%M UTC")))
+        .unwrap_or_default();
+    format!(
+        "{}  {}/{} ({}%)  {}{due}{flag}",
+        convoy.convoy_id,
+        convoy.done,
+        convoy.total,
+        convoy.percent(),
+        convoy.title
+    )
+}
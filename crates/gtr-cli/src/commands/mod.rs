@@ -5,6 +5,7 @@ pub mod checkpoint;
 pub mod convoy;
 pub mod crew;
 pub mod diagnostics;
+pub mod docs;
 pub mod doctor;
 pub mod dog;
 pub mod done;
@@ -20,6 +21,7 @@ pub mod mail;
 pub mod mayor;
 pub mod mol;
 pub mod mq;
+pub mod outbox;
 pub mod polecat;
 pub mod prime;
 pub mod rig;
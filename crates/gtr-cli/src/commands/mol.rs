@@ -2,7 +2,9 @@ use clap::Subcommand;
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
 use temporalio_sdk_core::WorkflowClientTrait;
 
-use gtr_temporal::signals::MolStepDoneSignal;
+use gtr_temporal::history;
+use gtr_temporal::signals::{MolStepDoneSignal, MoleculeResume, MoleculeState};
+use gtr_temporal::workflows::formula::FormulaInput;
 
 #[derive(Debug, Subcommand)]
 pub enum MolCommand {
@@ -33,10 +35,14 @@ pub enum MolCommand {
         /// Molecule workflow ID
         id: String,
     },
-    /// Resume a paused molecule
+    /// Resume a paused molecule, or rerun a finished one from a step
     Resume {
         /// Molecule workflow ID
         id: String,
+        /// Rerun this step and everything after it (molecules started with
+        /// `rgt formula run` that have stopped)
+        #[arg(long)]
+        from: Option<String>,
     },
 }
 
@@ -104,7 +110,52 @@ pub async fn run(cmd: &MolCommand) -> anyhow::Result<()> {
                 .await?;
             println!("Paused molecule: {id}");
         }
-        MolCommand::Resume { id } => {
+        MolCommand::Resume {
+            id,
+            from: Some(from),
+        } => {
+            let events = history::fetch_events(&client, id).await?;
+            let Some(result) = history::result(&events) else {
+                anyhow::bail!("Molecule {id} is still running; `rgt mol resume {id}` unpauses it");
+            };
+            let state: MoleculeState = serde_json::from_str(&result)?;
+            let (Some(args), Some(input)) = (
+                history::start_input::<(String, String, Vec<String>)>(&events),
+                history::start_input_at::<FormulaInput>(&events, 1),
+            ) else {
+                anyhow::bail!(
+                    "Molecule {id} doesn't run its own steps; only molecules started with \
+                     `rgt formula run` can be resumed from a step"
+                );
+            };
+            if !state.steps.iter().any(|s| &s.ref_id == from) {
+                anyhow::bail!("Molecule {id} has no step '{from}'");
+            }
+
+            let resume = MoleculeResume {
+                from: from.clone(),
+                steps: state.steps,
+            };
+            client
+                .start_workflow(
+                    vec![
+                        args.as_json_payload()?,
+                        input.as_json_payload()?,
+                        resume.as_json_payload()?,
+                    ],
+                    "work".to_string(),
+                    id.clone(),
+                    "molecule_wf".to_string(),
+                    None,
+                    Default::default(),
+                )
+                .await?;
+            println!(
+                "Resumed molecule {id} from step {from} (was {})",
+                state.status
+            );
+        }
+        MolCommand::Resume { id, from: None } => {
             client
                 .signal_workflow_execution(
                     id.clone(),
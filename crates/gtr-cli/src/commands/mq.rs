@@ -1,34 +1,144 @@
-use clap::Subcommand;
+use clap::{Args, Subcommand};
+use gtr_core::atomic;
+use gtr_core::outbox::QueuedSignal;
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
 use temporalio_sdk_core::WorkflowClientTrait;
 
-use gtr_temporal::signals::RefineryDequeueSignal;
+use gtr_temporal::activities::refinery_history::{self, RefineryStats};
+use gtr_temporal::signals::{
+    RefineryBumpSignal, RefineryDequeueSignal, RefineryMoveSignal, SIGNAL_REFINERY_BUMP,
+    SIGNAL_REFINERY_DEQUEUE, SIGNAL_REFINERY_MOVE, SIGNAL_REFINERY_PAUSE, SIGNAL_REFINERY_RESUME,
+};
+
+use crate::client::Delivery;
+
+/// Which refinery a merge queue command talks to.
+#[derive(Debug, Args)]
+pub struct QueueTarget {
+    /// Rig whose refinery to use (defaults to GTR_RIG env var)
+    #[arg(long)]
+    rig: Option<String>,
+    /// Named queue (default: the rig's default queue)
+    #[arg(long)]
+    queue: Option<String>,
+}
+
+impl QueueTarget {
+    fn workflow_id(&self) -> String {
+        let rig = self.rig.clone().or_else(|| std::env::var("GTR_RIG").ok());
+        refinery_id(rig.as_deref(), self.queue.as_deref())
+    }
+}
 
 #[derive(Debug, Subcommand)]
 pub enum MqCommand {
     /// Show merge queue status
-    Status,
+    Status {
+        #[command(flatten)]
+        target: QueueTarget,
+    },
     /// List items in the merge queue
-    List,
+    List {
+        #[command(flatten)]
+        target: QueueTarget,
+    },
     /// Remove an item from the merge queue
     Remove {
         /// Work item ID to remove
         work_item_id: String,
+        #[command(flatten)]
+        target: QueueTarget,
+    },
+    /// Move an item to the head of the merge queue
+    Bump {
+        /// Work item ID to bump
+        work_item_id: String,
+        #[command(flatten)]
+        target: QueueTarget,
+    },
+    /// Move an item to a specific position in the merge queue
+    Move {
+        /// Work item ID to move
+        work_item_id: String,
+        /// New queue position (1 = next to merge)
+        #[arg(long)]
+        position: usize,
+        #[command(flatten)]
+        target: QueueTarget,
+    },
+    /// Enqueue a branch held by `rgt done --draft`
+    Approve {
+        /// Work item ID to approve
+        work_item_id: String,
+    },
+    /// Freeze merges (enqueues are still accepted)
+    Pause {
+        #[command(flatten)]
+        target: QueueTarget,
+    },
+    /// Resume merging after a pause
+    Resume {
+        #[command(flatten)]
+        target: QueueTarget,
+    },
+    /// Start a refinery workflow for a rig's default or named merge queue
+    Start {
+        /// Rig the refinery merges for (defaults to GTR_RIG env var)
+        #[arg(long)]
+        rig: Option<String>,
+        /// Named queue, e.g. docs or hotfix (settings from `[rigs.queues.<name>]`)
+        #[arg(long)]
+        queue: Option<String>,
+        /// Checked-out repository the refinery works in
+        #[arg(long, default_value = ".")]
+        repo: std::path::PathBuf,
+    },
+    /// Throughput, time-to-merge and failure breakdown from refinery history
+    Stats {
+        /// Rig whose refinery history to read (default: refineries started without a rig)
+        #[arg(long, default_value = "")]
+        rig: String,
+        /// Named queue to report on (default: the rig's default queue)
+        #[arg(long, default_value = "")]
+        queue: String,
     },
 }
 
 pub async fn run(cmd: &MqCommand) -> anyhow::Result<()> {
     match cmd {
-        MqCommand::Status => handle_status().await,
-        MqCommand::List => handle_list().await,
-        MqCommand::Remove { work_item_id } => handle_remove(work_item_id).await,
+        MqCommand::Status { target } => handle_status(&target.workflow_id()).await,
+        MqCommand::List { target } => handle_list(&target.workflow_id()).await,
+        MqCommand::Remove {
+            work_item_id,
+            target,
+        } => handle_remove(&target.workflow_id(), work_item_id).await,
+        MqCommand::Bump {
+            work_item_id,
+            target,
+        } => handle_bump(&target.workflow_id(), work_item_id).await,
+        MqCommand::Move {
+            work_item_id,
+            position,
+            target,
+        } => handle_move(&target.workflow_id(), work_item_id, *position).await,
+        MqCommand::Approve { work_item_id } => handle_approve(work_item_id).await,
+        MqCommand::Pause { target } => {
+            handle_pause_resume(&target.workflow_id(), SIGNAL_REFINERY_PAUSE, "paused").await
+        }
+        MqCommand::Resume { target } => {
+            handle_pause_resume(&target.workflow_id(), SIGNAL_REFINERY_RESUME, "resumed").await
+        }
+        MqCommand::Start { rig, queue, repo } => {
+            handle_start(rig.as_deref(), queue.as_deref(), repo).await
+        }
+        MqCommand::Stats { rig, queue } => handle_stats(rig, queue),
     }
 }
 
-async fn handle_status() -> anyhow::Result<()> {
+async fn handle_status(refinery_id: &str) -> anyhow::Result<()> {
     let client = crate::client::connect().await?;
     let resp = client
-        .describe_workflow_execution("refinery".to_string(), None)
+        .describe_workflow_execution(refinery_id.to_string(), None)
         .await?;
 
     if let Some(info) = resp.workflow_execution_info {
@@ -37,7 +147,7 @@ async fn handle_status() -> anyhow::Result<()> {
             2 => "Completed",
             _ => "Unknown",
         };
-        println!("Refinery:  {status}");
+        println!("Refinery:  {refinery_id} {status}");
         println!("History:   {} events", info.history_length);
     } else {
         println!("Refinery not running. Start it with: rgt up");
@@ -46,12 +156,12 @@ async fn handle_status() -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn handle_list() -> anyhow::Result<()> {
+async fn handle_list(refinery_id: &str) -> anyhow::Result<()> {
     let client = crate::client::connect().await?;
 
     // Describe to confirm refinery exists
     let resp = client
-        .describe_workflow_execution("refinery".to_string(), None)
+        .describe_workflow_execution(refinery_id.to_string(), None)
         .await;
 
     match resp {
@@ -66,14 +176,50 @@ async fn handle_list() -> anyhow::Result<()> {
             }
         }
         Err(_) => {
-            println!("Refinery not found. Start it with: rgt up");
+            println!("Refinery {refinery_id} not found. Start it with: rgt up");
+        }
+    }
+
+    let drafts = gtr_core::outbox::pending(&gtr_core::dirs::drafts_dir())?;
+    if !drafts.is_empty() {
+        println!("\nAwaiting approval (rgt mq approve <id>):");
+        for (path, draft) in &drafts {
+            let id = path.file_stem().unwrap_or_default().to_string_lossy();
+            let branch = draft
+                .payload
+                .as_ref()
+                .and_then(|p| p.get("branch"))
+                .and_then(|b| b.as_str())
+                .unwrap_or("?");
+            println!("  {id}  {branch}  → {}", draft.workflow_id);
         }
     }
 
     Ok(())
 }
 
-async fn handle_remove(work_item_id: &str) -> anyhow::Result<()> {
+async fn handle_approve(work_item_id: &str) -> anyhow::Result<()> {
+    let path = draft_path(work_item_id);
+    let Some(draft) = atomic::read_json::<QueuedSignal>(&path)?.ok() else {
+        anyhow::bail!("No draft awaiting approval for '{work_item_id}'");
+    };
+    let delivery =
+        crate::client::signal_or_queue(&draft.workflow_id, &draft.signal_name, draft.payload)
+            .await?;
+    std::fs::remove_file(&path)?;
+    match delivery {
+        Delivery::Sent => println!(
+            "Approved '{work_item_id}' — enqueued for merge → {}",
+            draft.workflow_id
+        ),
+        Delivery::Queued => println!(
+            "Approved '{work_item_id}' — Temporal unreachable, enqueue queued for delivery"
+        ),
+    }
+    Ok(())
+}
+
+async fn handle_remove(refinery_id: &str, work_item_id: &str) -> anyhow::Result<()> {
     let client = crate::client::connect().await?;
 
     let signal = RefineryDequeueSignal {
@@ -83,9 +229,9 @@ async fn handle_remove(work_item_id: &str) -> anyhow::Result<()> {
     let payload = signal.as_json_payload()?;
     client
         .signal_workflow_execution(
-            "refinery".to_string(),
+            refinery_id.to_string(),
             String::new(),
-            "refinery_dequeue".to_string(),
+            SIGNAL_REFINERY_DEQUEUE.to_string(),
             Some(payload.into()),
             None,
         )
@@ -94,3 +240,160 @@ async fn handle_remove(work_item_id: &str) -> anyhow::Result<()> {
     println!("Removed '{work_item_id}' from merge queue");
     Ok(())
 }
+
+async fn handle_bump(refinery_id: &str, work_item_id: &str) -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+
+    let signal = RefineryBumpSignal {
+        work_item_id: work_item_id.to_string(),
+    };
+
+    let payload = signal.as_json_payload()?;
+    client
+        .signal_workflow_execution(
+            refinery_id.to_string(),
+            String::new(),
+            SIGNAL_REFINERY_BUMP.to_string(),
+            Some(payload.into()),
+            None,
+        )
+        .await?;
+
+    println!("Bumped '{work_item_id}' to the head of the merge queue");
+    Ok(())
+}
+
+async fn handle_move(refinery_id: &str, work_item_id: &str, position: usize) -> anyhow::Result<()> {
+    if position == 0 {
+        anyhow::bail!("position is 1-based (1 = next to merge)");
+    }
+
+    let client = crate::client::connect().await?;
+
+    let signal = RefineryMoveSignal {
+        work_item_id: work_item_id.to_string(),
+        position: position - 1,
+    };
+
+    let payload = signal.as_json_payload()?;
+    client
+        .signal_workflow_execution(
+            refinery_id.to_string(),
+            String::new(),
+            SIGNAL_REFINERY_MOVE.to_string(),
+            Some(payload.into()),
+            None,
+        )
+        .await?;
+
+    println!("Moved '{work_item_id}' to position {position} in the merge queue");
+    Ok(())
+}
+
+async fn handle_pause_resume(
+    refinery_id: &str,
+    signal_name: &str,
+    verb: &str,
+) -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+    client
+        .signal_workflow_execution(
+            refinery_id.to_string(),
+            String::new(),
+            signal_name.to_string(),
+            None,
+            None,
+        )
+        .await?;
+
+    println!("Merge queue {verb}");
+    Ok(())
+}
+
+/// Where `rgt done --draft` holds a work item's enqueue.
+pub fn draft_path(work_item_id: &str) -> std::path::PathBuf {
+    gtr_core::dirs::drafts_dir().join(format!("{work_item_id}.json"))
+}
+
+/// Workflow ID of the refinery for `rig`'s default queue or a named one.
+/// Without a rig this is the town-wide `refinery` (or `refinery-<queue>`).
+pub fn refinery_id(rig: Option<&str>, queue: Option<&str>) -> String {
+    gtr_core::state::refinery_queue_workflow_id(rig.unwrap_or_default(), queue.unwrap_or_default())
+}
+
+async fn handle_start(
+    rig: Option<&str>,
+    queue: Option<&str>,
+    repo: &std::path::Path,
+) -> anyhow::Result<()> {
+    let rig = rig
+        .map(str::to_string)
+        .or_else(|| std::env::var("GTR_RIG").ok());
+    let repo_path = std::fs::canonicalize(repo)?;
+    let workflow_id = refinery_id(rig.as_deref(), queue);
+
+    let input = (
+        repo_path.to_string_lossy().to_string(),
+        rig.clone().unwrap_or_default(),
+        queue.unwrap_or_default().to_string(),
+    );
+    let client = crate::client::connect().await?;
+    client
+        .start_workflow(
+            vec![input.as_json_payload()?],
+            "work".to_string(),
+            workflow_id.clone(),
+            "refinery_wf".to_string(),
+            None,
+            Default::default(),
+        )
+        .await?;
+
+    println!(
+        "Started refinery {workflow_id} (repo: {})",
+        repo_path.display()
+    );
+    Ok(())
+}
+
+fn handle_stats(rig: &str, queue: &str) -> anyhow::Result<()> {
+    let path = gtr_core::dirs::refinery_history_path(rig, queue);
+    let entries = refinery_history::load(&path)?;
+    if entries.is_empty() {
+        println!("No refinery history at {}", path.display());
+        return Ok(());
+    }
+
+    let stats = RefineryStats::compute(&entries);
+    println!("Processed:          {}", stats.processed);
+    println!("Merged:             {}", stats.merged);
+    if stats.validated > 0 {
+        println!("Validated (dry):    {}", stats.validated);
+    }
+    println!("Retried:            {}", stats.retried);
+    if let Some(rate) = stats.merges_per_hour {
+        println!("Throughput:         {rate:.2} merges/hour");
+    }
+    if let Some(secs) = stats.avg_time_to_merge_secs {
+        println!("Avg time-to-merge:  {}", format_secs(secs));
+    }
+    if let Some(secs) = stats.avg_test_secs {
+        println!("Avg validation:     {}", format_secs(secs));
+    }
+
+    if !stats.failures.is_empty() {
+        println!("\nFailures:");
+        for (status, count) in &stats.failures {
+            println!("  {status:<20} {count}");
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn format_secs(secs: u64) -> String {
+    match secs {
+        s if s >= 3600 => format!("{}h {}m", s / 3600, (s % 3600) / 60),
+        s if s >= 60 => format!("{}m {}s", s / 60, s % 60),
+        s => format!("{s}s"),
+    }
+}
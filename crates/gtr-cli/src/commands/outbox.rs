@@ -0,0 +1,50 @@
+use clap::Subcommand;
+
+#[derive(Debug, Subcommand)]
+#[command(about = "Signals queued locally while Temporal was unreachable (enable with GTR_OFFLINE_QUEUE=1)")]
+pub enum OutboxCommand {
+    /// List queued signals in delivery order
+    List,
+    /// Replay queued signals now
+    Flush,
+    /// Discard all queued signals without delivering them
+    Clear,
+}
+
+pub async fn run(cmd: &OutboxCommand) -> anyhow::Result<()> {
+    let dir = gtr_core::dirs::outbox_dir();
+    match cmd {
+        OutboxCommand::List => {
+            let queued = gtr_core::outbox::pending(&dir)?;
+            if queued.is_empty() {
+                println!("Outbox is empty.");
+                return Ok(());
+            }
+            for (_, signal) in &queued {
+                println!(
+                    "  {}  {} → {}",
+                    signal.queued_at.format("%Y-%m-%d %H:%M:%S"),
+                    signal.signal_name,
+                    signal.workflow_id
+                );
+            }
+            println!("\n{} signal(s) queued", queued.len());
+        }
+        OutboxCommand::Flush => {
+            let client = crate::client::connect().await?;
+            let summary = gtr_temporal::outbox::replay(&client, &dir).await?;
+            println!(
+                "Delivered {} signal(s), {} still queued, {} dropped (workflow not running)",
+                summary.delivered, summary.failed, summary.dropped
+            );
+        }
+        OutboxCommand::Clear => {
+            let queued = gtr_core::outbox::pending(&dir)?;
+            for (path, _) in &queued {
+                gtr_core::outbox::ack(path)?;
+            }
+            println!("Discarded {} queued signal(s)", queued.len());
+        }
+    }
+    Ok(())
+}
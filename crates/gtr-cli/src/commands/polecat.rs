@@ -17,9 +17,21 @@ pub enum PolecatCommand {
     },
     /// List stuck polecats
     Stuck,
+    /// List past polecat reports with their summaries and exit reasons
+    Reports {
+        /// Only reports from this rig
+        #[arg(long)]
+        rig: Option<String>,
+        /// Only reports of polecats that didn't finish cleanly
+        #[arg(long)]
+        failed: bool,
+    },
 }
 
 pub async fn run(cmd: &PolecatCommand) -> anyhow::Result<()> {
+    if let PolecatCommand::Reports { rig, failed } = cmd {
+        return list_reports(rig.as_deref(), *failed);
+    }
     let client = crate::client::connect().await?;
     match cmd {
         PolecatCommand::List => {
@@ -91,6 +103,38 @@ pub async fn run(cmd: &PolecatCommand) -> anyhow::Result<()> {
                 }
             }
         }
+        PolecatCommand::Reports { .. } => unreachable!("handled above"),
+    }
+    Ok(())
+}
+
+fn list_reports(rig: Option<&str>, failed: bool) -> anyhow::Result<()> {
+    let reports =
+        gtr_temporal::activities::polecat_reports::load(&gtr_core::dirs::polecat_reports_path())?;
+    let matching: Vec<_> = reports
+        .iter()
+        .filter(|r| rig.is_none_or(|rig| r.report.rig == rig))
+        .filter(|r| !failed || r.failed())
+        .collect();
+    if matching.is_empty() {
+        println!("No polecat reports.");
+        return Ok(());
+    }
+    for stored in matching {
+        let report = &stored.report;
+        println!(
+            "{}  {}  {}  {} ({})",
+            stored.reported_at.format("%Y-%m-%d %H:%M"),
+            report.work_item_id,
+            report.polecat_id,
+            report.status,
+            report.exit_reason
+        );
+        if let Some(summary) = report.summary.as_deref().filter(|s| !s.is_empty()) {
+            for line in summary.lines() {
+                println!("    {line}");
+            }
+        }
     }
     Ok(())
 }
@@ -1,4 +1,6 @@
 use clap::Args;
+use gtr_core::checkpoint::Checkpoint;
+use gtr_temporal::history;
 use temporalio_sdk_core::WorkflowClientTrait;
 
 #[derive(Debug, Args)]
@@ -88,6 +90,7 @@ pub async fn run(cmd: &PrimeCommand) -> anyhow::Result<()> {
             println!("You are a **Polecat** on rig '{rig}'.");
             println!("- Work on your assigned task in this directory");
             println!("- `rgt hook` — check your work assignment");
+            println!("- Too big for one branch? `rgt work split --into \"part A\" \"part B\"`");
             println!("- When done: `rgt done <work-id> --branch <branch>`");
         }
         _ => {
@@ -98,6 +101,10 @@ pub async fn run(cmd: &PrimeCommand) -> anyhow::Result<()> {
         }
     }
 
+    if role.contains("polecats") {
+        print_polecat_task(&client, &agent_id).await;
+    }
+
     if cmd.hook {
         println!();
         println!("---");
@@ -106,3 +113,95 @@ pub async fn run(cmd: &PrimeCommand) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Print the hooked work item and the molecule step in progress, rebuilt from
+/// workflow history, so a respawned polecat picks up where it left off.
+async fn print_polecat_task<C>(client: &C, agent_id: &str)
+where
+    C: WorkflowClientTrait + Sync,
+{
+    let agent_events = history::fetch_events(client, agent_id)
+        .await
+        .unwrap_or_default();
+    let hook = history::current_hook(&agent_events);
+
+    // Polecats are started with (name, rig, work_item_id, title[, runtime])
+    let polecat_input =
+        history::start_input::<Vec<String>>(&agent_events).filter(|input| input.len() >= 4);
+    let work_item_id = std::env::var("GTR_WORK_ITEM")
+        .ok()
+        .or_else(|| polecat_input.as_ref().map(|input| input[2].clone()))
+        .or_else(|| hook.as_ref().map(|h| h.work_item_id.clone()));
+    let Some(work_item_id) = work_item_id else {
+        return;
+    };
+
+    let work_events = history::fetch_events(client, &work_item_id)
+        .await
+        .unwrap_or_default();
+    let title = history::work_item(&work_events)
+        .map(|item| item.title)
+        .or_else(|| polecat_input.map(|mut input| input.swap_remove(3)));
+
+    println!();
+    println!("## Your Task");
+    println!();
+    println!("- **Work item:** {work_item_id}");
+    if let Ok(branch) = std::env::var("GTR_BRANCH") {
+        println!("- **Branch:** {branch}");
+    }
+    if let Some(title) = title {
+        println!();
+        println!("{title}");
+    }
+
+    // Molecule: the worktree checkpoint wins, then the agent's hook
+    let checkpoint = std::env::current_dir()
+        .ok()
+        .and_then(|dir| Checkpoint::read(&dir).ok().flatten());
+    let molecule_id = checkpoint
+        .as_ref()
+        .and_then(|cp| cp.molecule_id.clone())
+        .or_else(|| hook.as_ref().and_then(|h| h.molecule_id.clone()));
+    let Some(molecule_id) = molecule_id else {
+        return;
+    };
+
+    println!();
+    println!("## Molecule {molecule_id}");
+    println!();
+    let mol_events = history::fetch_events(client, &molecule_id)
+        .await
+        .unwrap_or_default();
+    match history::replay_molecule(&mol_events) {
+        Some(mol) => {
+            println!("- **Formula:** {}", mol.formula_name);
+            println!("- **Status:** {}", mol.status);
+            if let Some(step) = &mol.current_step {
+                println!("- **Current step:** {step}");
+            }
+            println!();
+            for step in &mol.steps {
+                let mark = match step.status.as_str() {
+                    "done" => "x",
+                    "in_progress" => ">",
+                    "failed" => "!",
+                    _ => " ",
+                };
+                println!("- [{mark}] {}", step.title);
+            }
+            if let Some(step) = &mol.current_step {
+                println!();
+                println!("When this step is finished: `rgt mol step-done {molecule_id} {step}`");
+            }
+        }
+        None => {
+            let step = checkpoint
+                .and_then(|cp| cp.step_title.or(cp.current_step))
+                .or_else(|| hook.and_then(|h| h.current_step));
+            if let Some(step) = step {
+                println!("- **Current step:** {step} (from checkpoint)");
+            }
+        }
+    }
+}
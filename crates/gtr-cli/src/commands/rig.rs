@@ -51,6 +51,11 @@ pub enum RigCommand {
     },
     /// Sync rigs from disk to registry (backfill existing rigs)
     Sync,
+    /// Delete a stopped rig with its clone, worktrees and plugins
+    Remove {
+        /// Rig name
+        name: String,
+    },
 }
 
 pub async fn run(cmd: &RigCommand) -> anyhow::Result<()> {
@@ -83,7 +88,7 @@ pub async fn run(cmd: &RigCommand) -> anyhow::Result<()> {
                 .start_workflow(
                     vec![input_payload],
                     "work".to_string(),
-                    format!("rig-{name}"),
+                    gtr_core::state::rig_workflow_id(name),
                     "rig_wf".to_string(),
                     None,
                     Default::default(),
@@ -94,7 +99,7 @@ pub async fn run(cmd: &RigCommand) -> anyhow::Result<()> {
             // Signal rig to boot (spawn witness + refinery)
             client
                 .signal_workflow_execution(
-                    format!("rig-{name}"),
+                    gtr_core::state::rig_workflow_id(name),
                     String::new(),
                     "rig_boot".to_string(),
                     None,
@@ -109,6 +114,9 @@ pub async fn run(cmd: &RigCommand) -> anyhow::Result<()> {
             let mut rigs_config = gtr_core::config::RigsConfig::load()?;
             rigs_config.add(name, git_url);
             rigs_config.save()?;
+            if let Some(rig) = rigs_config.rigs.iter().find(|r| r.name == *name) {
+                println!("Refinery target branch: {}", rig.default_branch);
+            }
             println!("Saved to registry (rigs.toml)");
         }
         RigCommand::List => {
@@ -127,18 +135,28 @@ pub async fn run(cmd: &RigCommand) -> anyhow::Result<()> {
                         .map(|e| e.workflow_id.as_str())
                         .unwrap_or("?");
                     let status = crate::commands::convoy::workflow_status_str(exec.status);
-                    println!("  {wf_id}  {status}");
+                    let lifecycle = match gtr_core::state::parse_rig_workflow_id(wf_id) {
+                        Some(rig) => rig_lifecycle(&client, &rig).await,
+                        None => None,
+                    };
+                    match lifecycle {
+                        Some(lifecycle) => println!("  {wf_id}  {status}  {lifecycle}"),
+                        None => println!("  {wf_id}  {status}"),
+                    }
                 }
             }
         }
         RigCommand::Status { name } => {
             let resp = client
-                .describe_workflow_execution(format!("rig-{name}"), None)
+                .describe_workflow_execution(gtr_core::state::rig_workflow_id(name), None)
                 .await?;
             if let Some(info) = resp.workflow_execution_info {
                 let status = crate::commands::convoy::workflow_status_str(info.status);
                 println!("Rig:     {name}");
                 println!("Status:  {status}");
+                if let Some(lifecycle) = rig_lifecycle(&client, name).await {
+                    println!("State:   {lifecycle}");
+                }
                 println!("History: {} events", info.history_length);
             } else {
                 println!("No execution info for rig {name}");
@@ -147,7 +165,7 @@ pub async fn run(cmd: &RigCommand) -> anyhow::Result<()> {
         RigCommand::Park { name } => {
             client
                 .signal_workflow_execution(
-                    format!("rig-{name}"),
+                    gtr_core::state::rig_workflow_id(name),
                     String::new(),
                     "rig_park".to_string(),
                     None,
@@ -159,7 +177,7 @@ pub async fn run(cmd: &RigCommand) -> anyhow::Result<()> {
         RigCommand::Unpark { name } => {
             client
                 .signal_workflow_execution(
-                    format!("rig-{name}"),
+                    gtr_core::state::rig_workflow_id(name),
                     String::new(),
                     "rig_unpark".to_string(),
                     None,
@@ -171,7 +189,7 @@ pub async fn run(cmd: &RigCommand) -> anyhow::Result<()> {
         RigCommand::Dock { name } => {
             client
                 .signal_workflow_execution(
-                    format!("rig-{name}"),
+                    gtr_core::state::rig_workflow_id(name),
                     String::new(),
                     "rig_dock".to_string(),
                     None,
@@ -183,7 +201,7 @@ pub async fn run(cmd: &RigCommand) -> anyhow::Result<()> {
         RigCommand::Undock { name } => {
             client
                 .signal_workflow_execution(
-                    format!("rig-{name}"),
+                    gtr_core::state::rig_workflow_id(name),
                     String::new(),
                     "rig_undock".to_string(),
                     None,
@@ -195,7 +213,7 @@ pub async fn run(cmd: &RigCommand) -> anyhow::Result<()> {
         RigCommand::Boot { name } => {
             client
                 .signal_workflow_execution(
-                    format!("rig-{name}"),
+                    gtr_core::state::rig_workflow_id(name),
                     String::new(),
                     "rig_boot".to_string(),
                     None,
@@ -207,7 +225,7 @@ pub async fn run(cmd: &RigCommand) -> anyhow::Result<()> {
         RigCommand::Stop { name } => {
             client
                 .signal_workflow_execution(
-                    format!("rig-{name}"),
+                    gtr_core::state::rig_workflow_id(name),
                     String::new(),
                     "rig_stop".to_string(),
                     None,
@@ -258,6 +276,46 @@ pub async fn run(cmd: &RigCommand) -> anyhow::Result<()> {
                 println!("Registry already up to date.");
             }
         }
+        RigCommand::Remove { name } => {
+            if rig_lifecycle(&client, name).await.as_deref() == Some("operational") {
+                anyhow::bail!("rig {name} is running — stop it first with `rgt rig stop {name}`");
+            }
+            let id = gtr_core::state::rig_removal_workflow_id(name);
+            client
+                .start_workflow(
+                    vec![name.as_json_payload()?],
+                    "work".to_string(),
+                    id.clone(),
+                    "rig_remove_wf".to_string(),
+                    None,
+                    Default::default(),
+                )
+                .await?;
+            if gtr_core::config::ApprovalPolicy::load().rig_delete {
+                println!("Removal of rig {name} is waiting for approval ({id})");
+                println!("See `rgt mayor approvals list`, then approve or deny it.");
+            } else {
+                println!("Removing rig {name} ({id})");
+            }
+        }
     }
     Ok(())
 }
+
+/// A rig's lifecycle state (operational, parked, docked or dormant), replayed
+/// from its workflow history. `None` if the rig workflow can't be read.
+pub async fn rig_lifecycle<C>(client: &C, name: &str) -> Option<String>
+where
+    C: WorkflowClientTrait + Sync,
+{
+    let rig_id = gtr_core::state::rig_workflow_id(name);
+    let events = gtr_temporal::history::fetch_events(client, &rig_id)
+        .await
+        .ok()?;
+    (!events.is_empty()).then(|| gtr_temporal::history::rig_lifecycle(&events))
+}
+
+/// Whether new work should be held back from a rig in this state.
+pub fn refuses_work(lifecycle: &str) -> bool {
+    matches!(lifecycle, "parked" | "docked")
+}
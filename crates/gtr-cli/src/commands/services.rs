@@ -1,4 +1,13 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
 use clap::Subcommand;
+use temporalio_common::protos::coresdk::AsJsonPayloadExt;
+use temporalio_sdk_core::WorkflowClientTrait;
+
+use gtr_core::plugin::{Gate, PluginDef};
+use gtr_temporal::activities::plugin_history;
+use gtr_temporal::workflows::patrol::{PluginTestInput, PluginTestResult};
 
 #[derive(Debug, Subcommand)]
 pub enum ServicesCommand {
@@ -8,13 +17,230 @@ pub enum ServicesCommand {
     Down,
     /// Show service status
     Status,
+    /// Show a patrol plugin's recent runs, to spot flaky plugins
+    PluginHistory {
+        /// Plugin name
+        name: String,
+        /// Show at most this many of the latest runs
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+        /// Print each run's captured stdout and stderr
+        #[arg(long)]
+        output: bool,
+    },
+    /// Create and try out patrol plugins
+    Plugin {
+        #[command(subcommand)]
+        action: PluginAction,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PluginAction {
+    /// Write a starting plugin definition to fill in
+    New {
+        /// Plugin name, also the file name
+        name: String,
+        /// none, cooldown:<seconds>, cron:<schedule> or event:<name>
+        #[arg(long, default_value = "none")]
+        gate: Gate,
+        /// Put it in this rig's plugin directory instead of the global one
+        #[arg(long)]
+        rig: Option<String>,
+    },
+    /// Run a plugin once now, on the worker, and show how it went
+    Test {
+        /// Plugin name
+        name: String,
+        /// Run it as patrol would on this rig, with the rig's plugins;
+        /// otherwise in the current directory
+        #[arg(long)]
+        rig: Option<String>,
+    },
 }
 
-pub fn run(cmd: &ServicesCommand) -> anyhow::Result<()> {
+pub async fn run(cmd: &ServicesCommand) -> anyhow::Result<()> {
     match cmd {
         ServicesCommand::Up => println!("services up: not yet implemented"),
         ServicesCommand::Down => println!("services down: not yet implemented"),
         ServicesCommand::Status => println!("services status: not yet implemented"),
+        ServicesCommand::PluginHistory {
+            name,
+            limit,
+            output,
+        } => show_plugin_history(name, *limit, *output)?,
+        ServicesCommand::Plugin { action } => match action {
+            PluginAction::New { name, gate, rig } => new_plugin(name, gate, rig.as_deref())?,
+            PluginAction::Test { name, rig } => test_plugin(name, rig.as_deref()).await?,
+        },
+    }
+    Ok(())
+}
+
+fn plugin_dir(rig: Option<&str>) -> PathBuf {
+    match rig {
+        Some(rig) => gtr_core::dirs::rig_plugin_dir(rig),
+        None => gtr_core::dirs::plugins_dir(),
+    }
+}
+
+fn new_plugin(name: &str, gate: &Gate, rig: Option<&str>) -> anyhow::Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        anyhow::bail!("plugin names use letters, digits, '-' and '_', not '{name}'");
+    }
+    let dir = plugin_dir(rig);
+    let path = dir.join(format!("{name}.toml"));
+    if path.exists() {
+        anyhow::bail!("{} already exists", path.display());
+    }
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(&path, gtr_core::plugin::scaffold(name, gate))?;
+    println!("Created {}", path.display());
+    let rig_arg = rig.map(|rig| format!(" --rig {rig}")).unwrap_or_default();
+    println!(
+        "Fill in its command and args, then try it with `rgt services plugin test {name}{rig_arg}`."
+    );
+    Ok(())
+}
+
+async fn test_plugin(name: &str, rig: Option<&str>) -> anyhow::Result<()> {
+    let global_dir = gtr_core::dirs::plugins_dir();
+    let plugins: Vec<PluginDef> = match rig {
+        Some(rig) => gtr_core::plugin::rig_plugins(&global_dir, &plugin_dir(Some(rig)))?,
+        None => gtr_core::plugin::discover_plugins(&global_dir)?
+            .into_iter()
+            .map(|(_, def)| def)
+            .collect(),
+    };
+    let Some(plugin) = plugins.into_iter().find(|p| p.name == name) else {
+        anyhow::bail!("no plugin named {name} in {}", plugin_dir(rig).display());
+    };
+    let target = match rig {
+        Some(rig) => rig.to_string(),
+        None => std::env::current_dir()?.display().to_string(),
+    };
+
+    let input = PluginTestInput {
+        rig: target,
+        plugin,
+    };
+    let id = format!(
+        "plugin-test-{name}-{}",
+        chrono::Utc::now().timestamp_millis()
+    );
+    let client = crate::client::connect().await?;
+    client
+        .start_workflow(
+            vec![input.as_json_payload()?],
+            "work".to_string(),
+            id.clone(),
+            "plugin_test_wf".to_string(),
+            None,
+            Default::default(),
+        )
+        .await?;
+    println!("Running plugin {name} ({id})...");
+
+    // run_plugin allows a minute; leave room for the worker to pick it up
+    for _ in 0..180 {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let events = gtr_temporal::history::fetch_events(&client, &id).await?;
+        let Some(result) = gtr_temporal::history::result(&events) else {
+            continue;
+        };
+        let result: PluginTestResult = serde_json::from_str(&result)?;
+        print_test_result(&result);
+        return Ok(());
+    }
+    anyhow::bail!("plugin {name} hasn't finished — is a worker running? (workflow {id})")
+}
+
+fn print_test_result(result: &PluginTestResult) {
+    println!("Work dir: {}", result.work_dir);
+    if let Some(output) = &result.output {
+        let exit = match output.exit_code {
+            Some(code) => code.to_string(),
+            None => "none".to_string(),
+        };
+        println!("Exit:     {exit} after {}", format_ms(output.duration_ms));
+        for (stream, lines) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+            if lines.is_empty() {
+                continue;
+            }
+            println!("{stream}:");
+            for line in lines {
+                println!("  {line}");
+            }
+        }
+    }
+    match &result.failure {
+        None => println!("Result:   passed"),
+        Some(failure) => println!("Result:   FAILED — {}", failure.reason),
+    }
+}
+
+fn show_plugin_history(name: &str, limit: usize, output: bool) -> anyhow::Result<()> {
+    let path = gtr_core::dirs::plugin_history_path(name);
+    let runs = plugin_history::load(&path)?;
+    if runs.is_empty() {
+        println!("No runs recorded for plugin {name}.");
+        return Ok(());
+    }
+    let runs = &runs[runs.len().saturating_sub(limit)..];
+
+    let passed = runs.iter().filter(|r| r.passed()).count();
+    // Pass → fail and fail → pass changes; a steady plugin has few
+    let flips = runs
+        .windows(2)
+        .filter(|pair| pair[0].passed() != pair[1].passed())
+        .count();
+    let avg_ms = runs.iter().map(|r| r.duration_ms).sum::<u64>() / runs.len() as u64;
+    println!(
+        "{name}: {} runs, {passed} passed, {} failed, {flips} flips, avg {}",
+        runs.len(),
+        runs.len() - passed,
+        format_ms(avg_ms)
+    );
+
+    for run in runs {
+        let exit = match run.exit_code {
+            Some(code) => code.to_string(),
+            None => "-".to_string(),
+        };
+        let result = if run.passed() { "ok" } else { "FAILED" };
+        println!(
+            "{}  {result:<6}  exit {exit:<4}  {:>8}  {}{}",
+            run.ran_at.format("%Y-%m-%d %H:%M:%S"),
+            format_ms(run.duration_ms),
+            run.rig,
+            run.failure
+                .as_deref()
+                .map(|reason| format!("  {reason}"))
+                .unwrap_or_default()
+        );
+        if output {
+            for (stream, text) in [("stdout", &run.stdout), ("stderr", &run.stderr)] {
+                if text.is_empty() {
+                    continue;
+                }
+                println!("    {stream}:");
+                for line in text.lines() {
+                    println!("      {line}");
+                }
+            }
+        }
     }
     Ok(())
 }
+
+fn format_ms(ms: u64) -> String {
+    match ms {
+        ms if ms >= 60_000 => crate::commands::mq::format_secs(ms / 1000),
+        ms if ms >= 1000 => format!("{:.1}s", ms as f64 / 1000.0),
+        ms => format!("{ms}ms"),
+    }
+}
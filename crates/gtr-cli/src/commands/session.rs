@@ -1,3 +1,5 @@
+use std::io::Write;
+
 use clap::Subcommand;
 use temporalio_sdk_core::WorkflowClientTrait;
 
@@ -12,13 +14,27 @@ pub enum SessionCommand {
         /// Agent workflow ID
         id: String,
     },
+    /// Replay an agent's recorded terminal output
+    Replay {
+        /// Agent ID
+        agent: String,
+        /// Start here — an RFC 3339 time or seconds into the recording;
+        /// earlier output is fast-forwarded
+        #[arg(long)]
+        from: Option<String>,
+        /// Playback speed multiplier
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+        /// Longest pause between chunks, in seconds
+        #[arg(long, default_value_t = 2.0)]
+        max_idle: f64,
+    },
 }
 
 pub async fn run(cmd: &SessionCommand) -> anyhow::Result<()> {
-    let client = crate::client::connect().await?;
-
     match cmd {
         SessionCommand::List => {
+            let client = crate::client::connect().await?;
             let query = "WorkflowType = 'agent_wf' AND ExecutionStatus = 'Running'".to_string();
             let resp = client
                 .list_workflow_executions(100, vec![], query)
@@ -52,6 +68,7 @@ pub async fn run(cmd: &SessionCommand) -> anyhow::Result<()> {
             Ok(())
         }
         SessionCommand::Status { id } => {
+            let client = crate::client::connect().await?;
             let resp = client
                 .describe_workflow_execution(id.clone(), None)
                 .await?;
@@ -91,5 +108,48 @@ pub async fn run(cmd: &SessionCommand) -> anyhow::Result<()> {
 
             Ok(())
         }
+        SessionCommand::Replay {
+            agent,
+            from,
+            speed,
+            max_idle,
+        } => replay(agent, from.as_deref(), *speed, *max_idle).await,
+    }
+}
+
+async fn replay(agent: &str, from: Option<&str>, speed: f64, max_idle: f64) -> anyhow::Result<()> {
+    if speed <= 0.0 {
+        anyhow::bail!("--speed must be positive");
+    }
+    let chunks = gtr_temporal::output_log::recorded_output(agent)?;
+    let Some(first) = chunks.first() else {
+        anyhow::bail!("No session recordings for '{agent}'");
+    };
+    let start = match from {
+        None => first.at,
+        Some(from) => match from.parse::<f64>() {
+            Ok(offset) => first.at + offset,
+            Err(_) => {
+                let at = chrono::DateTime::parse_from_rfc3339(from)
+                    .map_err(|e| anyhow::anyhow!("Invalid --from '{from}': {e}"))?;
+                at.timestamp_millis() as f64 / 1000.0
+            }
+        },
+    };
+
+    let mut out = std::io::stdout().lock();
+    let mut prev: Option<f64> = None;
+    for chunk in &chunks {
+        // Output before --from is written at once to rebuild the screen
+        if chunk.at >= start {
+            if let Some(prev) = prev {
+                let pause = ((chunk.at - prev) / speed).clamp(0.0, max_idle);
+                tokio::time::sleep(std::time::Duration::from_secs_f64(pause)).await;
+            }
+            prev = Some(chunk.at);
+        }
+        out.write_all(chunk.text.as_bytes())?;
+        out.flush()?;
     }
+    Ok(())
 }
@@ -1,4 +1,13 @@
+use std::collections::HashMap;
+
+use gtr_core::config::SessionBackendKind;
+use gtr_temporal::pty::SessionMetadata;
+
 pub fn run() -> anyhow::Result<()> {
+    if gtr_temporal::pty::backend_kind() != SessionBackendKind::Tmux {
+        return list_backend_sessions();
+    }
+
     let output = std::process::Command::new("tmux")
         .args(["-L", "gtr", "ls"])
         .output();
@@ -6,6 +15,8 @@ pub fn run() -> anyhow::Result<()> {
     match output {
         Ok(o) if o.status.success() => {
             let stdout = String::from_utf8_lossy(&o.stdout);
+            let metadata: HashMap<String, SessionMetadata> =
+                gtr_temporal::pty::list_session_metadata().into_iter().collect();
             println!("Active tmux sessions:");
             println!();
             for line in stdout.lines() {
@@ -17,6 +28,12 @@ pub fn run() -> anyhow::Result<()> {
                     line.to_string()
                 };
                 println!("  {friendly}");
+
+                // Show role/rig/work item recorded at spawn time, if any
+                let agent_id = friendly.split(':').next().unwrap_or_default();
+                if let Some(meta) = metadata.get(agent_id).filter(|m| !m.role.is_empty()) {
+                    println!("      {}", meta.title());
+                }
             }
         }
         Ok(o) => {
@@ -37,3 +54,21 @@ pub fn run() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Sessions under a non-tmux backend, from their runtime dirs.
+fn list_backend_sessions() -> anyhow::Result<()> {
+    let sessions = gtr_temporal::pty::list_session_metadata();
+    if sessions.is_empty() {
+        println!("No active sessions.");
+        return Ok(());
+    }
+    println!("Active sessions:");
+    println!();
+    for (agent_id, meta) in sessions {
+        println!("  {agent_id}");
+        if !meta.role.is_empty() {
+            println!("      {}", meta.title());
+        }
+    }
+    Ok(())
+}
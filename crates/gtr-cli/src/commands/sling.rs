@@ -4,6 +4,9 @@ use temporalio_sdk_core::WorkflowClientTrait;
 
 use gtr_temporal::signals::{AgentAssignSignal, DogDispatchSignal};
 
+use crate::commands::mayor::maintenance_notice;
+use crate::commands::rig::{refuses_work, rig_lifecycle};
+
 #[derive(Debug, Args)]
 pub struct SlingCommand {
     /// Work item IDs to assign (one or more)
@@ -13,13 +16,22 @@ pub struct SlingCommand {
     #[arg(short, long)]
     pub target: String,
 
-    /// Agent runtime to use (claude, codex, gemini)
+    /// Agent runtime for auto-spawned polecats (claude, shell, mock, or a runtimes.toml entry)
     #[arg(long, default_value = "claude")]
     pub agent: String,
 
     /// Title for the work item(s)
     #[arg(long)]
     pub title: Option<String>,
+
+    /// Sling even during maintenance or to a parked or docked rig
+    #[arg(long)]
+    pub force: bool,
+
+    /// Only triage: a cheap agent assesses each item (complexity, labels,
+    /// rig, priority) and records it on the item; nothing is dispatched
+    #[arg(long)]
+    pub triage: bool,
 }
 
 pub async fn run(cmd: &SlingCommand) -> anyhow::Result<()> {
@@ -34,6 +46,19 @@ pub async fn run(cmd: &SlingCommand) -> anyhow::Result<()> {
         cmd.work_ids.clone()
     };
 
+    if let Some(notice) = maintenance_notice(&client).await {
+        if !cmd.force {
+            anyhow::bail!(
+                "Gas Town is in maintenance ({notice}); end it with `rgt mayor maintenance off`, or pass --force"
+            );
+        }
+        tracing::warn!("Slinging during maintenance (--force)");
+    }
+
+    if cmd.triage {
+        return triage(&client, cmd, &work_ids).await;
+    }
+
     match cmd.target.as_str() {
         "mayor" => {
             // Send all work items to the Mayor for dispatch
@@ -45,7 +70,7 @@ pub async fn run(cmd: &SlingCommand) -> anyhow::Result<()> {
                 let payload = signal.as_json_payload()?;
                 client
                     .signal_workflow_execution(
-                        "mayor".to_string(),
+                        gtr_core::state::mayor_workflow_id(),
                         String::new(),
                         "agent_assign".to_string(),
                         Some(payload.into()),
@@ -94,9 +119,25 @@ pub async fn run(cmd: &SlingCommand) -> anyhow::Result<()> {
         target => {
             // Target is either an agent ID or a rig name.
             // Check if a rig directory exists for this target name.
-            let rig_name = target.strip_prefix("rig-").unwrap_or(target);
+            let rig_name = gtr_core::state::parse_rig_workflow_id(target)
+                .unwrap_or_else(|| target.to_string());
+            let rig_name = rig_name.as_str();
             let is_rig = gtr_core::dirs::rig_dir(rig_name).exists();
             if is_rig {
+                let lifecycle = rig_lifecycle(&client, rig_name).await;
+                if let Some(lifecycle) = lifecycle.filter(|l| refuses_work(l)) {
+                    let resume = match lifecycle.as_str() {
+                        "parked" => "unpark",
+                        _ => "undock",
+                    };
+                    if !cmd.force {
+                        anyhow::bail!(
+                            "rig {rig_name} is {lifecycle}; run `rgt rig {resume} {rig_name}` first, or pass --force"
+                        );
+                    }
+                    tracing::warn!("Slinging to {lifecycle} rig {rig_name} (--force)");
+                }
+
                 // Auto-spawn polecat per work item
                 let rig = rig_name;
                 for work_id in &work_ids {
@@ -110,6 +151,7 @@ pub async fn run(cmd: &SlingCommand) -> anyhow::Result<()> {
                         rig,
                         work_id.as_str(),
                         title.as_str(),
+                        cmd.agent.as_str(),
                     )
                         .as_json_payload()?;
                     client
@@ -162,3 +204,34 @@ pub async fn run(cmd: &SlingCommand) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Start a triage agent per work item. The target only matters when it names
+/// a rig, whose repository the agent can then read.
+async fn triage<C>(client: &C, cmd: &SlingCommand, work_ids: &[String]) -> anyhow::Result<()>
+where
+    C: WorkflowClientTrait + Sync,
+{
+    let target = &cmd.target;
+    let rig = gtr_core::state::parse_rig_workflow_id(target).unwrap_or_else(|| target.clone());
+    let rig = gtr_core::dirs::rig_dir(&rig).exists().then_some(rig);
+    for work_id in work_ids {
+        let triage_id = gtr_core::state::triage_workflow_id(work_id);
+        let title = cmd.title.clone().unwrap_or_else(|| work_id.clone());
+        let input_payload = (work_id.as_str(), title.as_str(), rig.as_deref()).as_json_payload()?;
+        client
+            .start_workflow(
+                vec![input_payload],
+                "work".to_string(),
+                triage_id.clone(),
+                "triage_wf".to_string(),
+                None,
+                Default::default(),
+            )
+            .await?;
+        println!("Triaging {work_id} ({triage_id})");
+    }
+    println!(
+        "Check the assessment with `rgt work show <id>`, then sling without --triage to dispatch"
+    );
+    Ok(())
+}
@@ -4,6 +4,8 @@ use std::time::{Duration, Instant};
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
 use temporalio_sdk_core::WorkflowClientTrait;
 
+use gtr_temporal::workflows::work_item::SEARCH_ATTRIBUTES;
+
 fn ensure_tmux() -> anyhow::Result<()> {
     let ok = std::process::Command::new("tmux")
         .arg("-V")
@@ -95,11 +97,16 @@ pub async fn run() -> anyhow::Result<()> {
         println!("[..] Starting Temporal dev server...");
         let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
         let db_path = format!("{home}/.gtr/temporal.db");
-        start_tmux_session(
-            "gtr-temporal-server",
-            "temporal",
-            &["server", "start-dev", "--db-filename", &db_path],
-        )?;
+        // Work items publish these for `rgt work list` filters
+        let search_attributes: Vec<String> = SEARCH_ATTRIBUTES
+            .iter()
+            .map(|name| format!("{name}=Keyword"))
+            .collect();
+        let mut args = vec!["server", "start-dev", "--db-filename", &db_path];
+        for attribute in &search_attributes {
+            args.extend(["--search-attribute", attribute.as_str()]);
+        }
+        start_tmux_session("gtr-temporal-server", "temporal", &args)?;
 
         // Poll for readiness
         let addr = host_port.parse().unwrap_or_else(|_| "127.0.0.1:7233".parse().unwrap());
@@ -143,9 +150,13 @@ pub async fn run() -> anyhow::Result<()> {
 
     // Step 3: Start workflows
     println!("[..] Starting workflows...");
-    let (mayor_started, boot_started) = crate::commands::up::start_workflows().await?;
+    let (mayor_started, boot_started, deputy_started) =
+        crate::commands::up::start_workflows().await?;
     println!("[ok] Mayor workflow: {}", if mayor_started { "started" } else { "already running" });
     println!("[ok] Boot workflow: {}", if boot_started { "started" } else { "already running" });
+    if deputy_started {
+        println!("[ok] Deputy workflow: started");
+    }
 
     // Step 4: Re-register rigs from registry
     let rigs_config = gtr_core::config::RigsConfig::load()?;
@@ -153,7 +164,7 @@ pub async fn run() -> anyhow::Result<()> {
         println!("[..] Re-registering rigs...");
         let client = crate::client::connect().await?;
         for rig_entry in &rigs_config.rigs {
-            let wf_id = format!("rig-{}", rig_entry.name);
+            let wf_id = gtr_core::state::rig_workflow_id(&rig_entry.name);
             let already_running = crate::commands::up::is_workflow_running_pub(&client, &wf_id).await;
             if already_running {
                 println!("[ok] Rig '{}' already running", rig_entry.name);
@@ -8,7 +8,7 @@ pub async fn run() -> anyhow::Result<()> {
 
     // Mayor status
     let mayor_running = match client
-        .describe_workflow_execution("mayor".to_string(), None)
+        .describe_workflow_execution(gtr_core::state::mayor_workflow_id(), None)
         .await
     {
         Ok(resp) => {
@@ -35,9 +35,13 @@ pub async fn run() -> anyhow::Result<()> {
         mayor_pid
     );
 
+    if let Some(notice) = crate::commands::mayor::maintenance_notice(&client).await {
+        println!("  Town:     MAINTENANCE — {notice}");
+    }
+
     // Boot status
     let boot_running = match client
-        .describe_workflow_execution("boot".to_string(), None)
+        .describe_workflow_execution(gtr_core::state::boot_workflow_id(), None)
         .await
     {
         Ok(resp) => resp
@@ -66,12 +70,17 @@ pub async fn run() -> anyhow::Result<()> {
                 .as_ref()
                 .map(|e| e.workflow_id.as_str())
                 .unwrap_or("?");
-            let rig_name = wf_id.strip_prefix("rig-").unwrap_or(wf_id);
+            let rig_name =
+                gtr_core::state::parse_rig_workflow_id(wf_id).unwrap_or_else(|| wf_id.to_string());
+            let rig_name = rig_name.as_str();
 
-            println!("    {rig_name}:");
+            match crate::commands::rig::rig_lifecycle(&client, rig_name).await {
+                Some(lifecycle) => println!("    {rig_name} [{lifecycle}]:"),
+                None => println!("    {rig_name}:"),
+            }
 
             // Check witness
-            let witness_id = format!("{rig_name}-witness");
+            let witness_id = gtr_core::state::witness_workflow_id(rig_name);
             let witness_alive = gtr_temporal::pty::is_alive(&witness_id);
             let witness_pid = if witness_alive {
                 gtr_temporal::pty::read_pid(&witness_id)
@@ -87,7 +96,7 @@ pub async fn run() -> anyhow::Result<()> {
             );
 
             // Check refinery
-            let refinery_id = format!("{rig_name}-refinery");
+            let refinery_id = gtr_core::state::refinery_workflow_id(rig_name);
             let refinery_alive = gtr_temporal::pty::is_alive(&refinery_id);
             let refinery_pid = if refinery_alive {
                 gtr_temporal::pty::read_pid(&refinery_id)
@@ -153,6 +162,12 @@ pub async fn run() -> anyhow::Result<()> {
         .await
         .unwrap_or_default();
     println!("  Convoys:  {} active", convoys.executions.len());
+    for convoy in crate::commands::mayor::convoy_progress(&client).await {
+        println!(
+            "    {}",
+            crate::commands::mayor::format_convoy_progress(&convoy)
+        );
+    }
 
     // Merge queue (refineries)
     let refinery_query =
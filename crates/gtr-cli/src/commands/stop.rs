@@ -11,7 +11,7 @@ pub async fn run() -> anyhow::Result<()> {
     println!();
 
     // Step 1: Stop workflows, kill agents, clean runtime
-    crate::commands::down::run().await?;
+    crate::commands::down::run(&Default::default()).await?;
 
     // Step 2: Kill worker tmux session
     if kill_tmux_session("gtr-worker") {
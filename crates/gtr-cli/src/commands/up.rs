@@ -1,8 +1,9 @@
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
 use temporalio_sdk_core::WorkflowClientTrait;
 
-/// Start mayor + boot workflows. Returns (mayor_started, boot_started).
-pub async fn start_workflows() -> anyhow::Result<(bool, bool)> {
+/// Start mayor + boot workflows, and the deputy unless `[deputy]` disables
+/// it. Returns (mayor_started, boot_started, deputy_started).
+pub async fn start_workflows() -> anyhow::Result<(bool, bool, bool)> {
     // Ensure runtime directory exists
     let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
     let runtime_dir = format!("{home}/.gtr/runtime");
@@ -12,14 +13,15 @@ pub async fn start_workflows() -> anyhow::Result<(bool, bool)> {
 
     // Check and start mayor
     let mut mayor_started = false;
-    let mayor_running = is_workflow_running_pub(&client, "mayor").await;
+    let mayor_running =
+        is_workflow_running_pub(&client, &gtr_core::state::mayor_workflow_id()).await;
     if !mayor_running {
         let payload = "default".as_json_payload()?;
         client
             .start_workflow(
                 vec![payload],
                 "work".to_string(),
-                "mayor".to_string(),
+                gtr_core::state::mayor_workflow_id(),
                 "mayor_wf".to_string(),
                 None,
                 Default::default(),
@@ -30,14 +32,15 @@ pub async fn start_workflows() -> anyhow::Result<(bool, bool)> {
 
     // Check and start boot
     let mut boot_started = false;
-    let boot_running = is_workflow_running_pub(&client, "boot").await;
+    let boot_running =
+        is_workflow_running_pub(&client, &gtr_core::state::boot_workflow_id()).await;
     if !boot_running {
         let boot_payload = 120u64.as_json_payload()?;
         let _ = client
             .start_workflow(
                 vec![boot_payload],
                 "work".to_string(),
-                "boot".to_string(),
+                gtr_core::state::boot_workflow_id(),
                 "boot_wf".to_string(),
                 None,
                 Default::default(),
@@ -46,7 +49,27 @@ pub async fn start_workflows() -> anyhow::Result<(bool, bool)> {
         boot_started = true;
     }
 
-    Ok((mayor_started, boot_started))
+    // Check and start deputy
+    let mut deputy_started = false;
+    let deputy = gtr_core::config::DeputyConfig::load();
+    let deputy_running =
+        is_workflow_running_pub(&client, &gtr_core::state::deputy_workflow_id()).await;
+    if deputy.enabled && !deputy_running {
+        let deputy_payload = deputy.as_json_payload()?;
+        let _ = client
+            .start_workflow(
+                vec![deputy_payload],
+                "work".to_string(),
+                gtr_core::state::deputy_workflow_id(),
+                "deputy_wf".to_string(),
+                None,
+                Default::default(),
+            )
+            .await;
+        deputy_started = true;
+    }
+
+    Ok((mayor_started, boot_started, deputy_started))
 }
 
 pub async fn is_workflow_running_pub(
@@ -67,9 +90,9 @@ pub async fn is_workflow_running_pub(
 pub async fn run() -> anyhow::Result<()> {
     println!("Starting Gas Town...");
 
-    let (mayor_started, boot_started) = start_workflows().await?;
+    let (mayor_started, boot_started, deputy_started) = start_workflows().await?;
 
-    if !mayor_started && !boot_started {
+    if !mayor_started && !boot_started && !deputy_started {
         println!("Gas Town is already running (mayor + boot workflows active).");
         println!("Run `rgt worker run` to start the worker if needed.");
         return Ok(());
@@ -78,6 +101,9 @@ pub async fn run() -> anyhow::Result<()> {
     println!("Gas Town is up.");
     println!("  Mayor workflow: {}", if mayor_started { "started" } else { "already running" });
     println!("  Boot workflow: {}", if boot_started { "started" } else { "already running" });
+    if deputy_started {
+        println!("  Deputy workflow: started");
+    }
     println!();
     println!("Now start the worker: rgt worker run");
 
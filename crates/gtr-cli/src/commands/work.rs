@@ -1,68 +1,354 @@
+use std::path::{Path, PathBuf};
+
 use clap::Subcommand;
+use temporalio_common::protos::coresdk::AsJsonPayloadExt;
+use temporalio_common::protos::temporal::api::workflow::v1::WorkflowExecutionInfo;
 use temporalio_sdk_core::WorkflowClientTrait;
 
+use gtr_core::backlog::BacklogDef;
+use gtr_core::outbox::QueuedSignal;
+use gtr_core::types::{Priority, WorkItem, WorkItemId};
+use gtr_temporal::signals::{
+    TriageSignal, WorkCommentSignal, WorkItemStats, WorkItemTransition, WorkSplitSignal,
+    WorkUsageSignal,
+};
+use gtr_temporal::workflows::work_item::{SA_ASSIGNEE, SA_PRIORITY, SA_RIG, SA_STATUS};
+
 #[derive(Debug, Subcommand)]
 pub enum WorkCommand {
+    /// Create a work item
+    Create {
+        /// Work item title
+        title: String,
+        /// Longer description of the work
+        #[arg(long)]
+        desc: Option<String>,
+        /// Priority, P0 (highest) to P3
+        #[arg(long, default_value = "P2")]
+        priority: Priority,
+        /// Label (repeatable)
+        #[arg(long = "label")]
+        labels: Vec<String>,
+        /// Rig the work is meant for
+        #[arg(long)]
+        rig: Option<String>,
+        /// Work item this one waits on (repeatable)
+        #[arg(long = "depends-on")]
+        depends_on: Vec<String>,
+    },
+    /// Create a convoy and its work items from a backlog file (.toml or .json)
+    Import {
+        /// Backlog file
+        file: PathBuf,
+    },
     /// Show details for a work item by workflow ID
     Show {
         /// Work item ID
         id: String,
+        /// Also print the item's timeline of transitions
+        #[arg(long)]
+        history: bool,
     },
     /// List running work items (Temporal work_item_wf workflows)
-    List,
+    List {
+        /// Only items with this status
+        #[arg(long, value_parser = ["pending", "assigned", "in_progress", "tracking"])]
+        status: Option<String>,
+        /// Only items assigned to this agent
+        #[arg(long)]
+        assignee: Option<String>,
+        /// Only items meant for this rig
+        #[arg(long)]
+        rig: Option<String>,
+        /// Only items with this priority
+        #[arg(long)]
+        priority: Option<Priority>,
+        /// Order: oldest first, or highest priority first
+        #[arg(long, value_parser = ["age", "priority"], default_value = "age")]
+        sort: String,
+    },
     /// Close a work item by sending a close signal
     Close {
         /// Work item ID
         id: String,
     },
+    /// Leave a progress note on a work item
+    Comment {
+        /// Work item ID
+        id: String,
+        /// Comment text
+        text: String,
+        /// Author (defaults to GTR_AGENT, then "cli")
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// Split a work item into parts; it then completes once they all finish
+    Split {
+        /// Work item ID (defaults to GTR_WORK_ITEM env var)
+        id: Option<String>,
+        /// Title of each part
+        #[arg(long, num_args = 1.., required = true)]
+        into: Vec<String>,
+    },
+    /// Report tokens and cost an agent used on a work item (reports add up)
+    Usage {
+        /// Work item ID (defaults to GTR_WORK_ITEM env var)
+        id: Option<String>,
+        #[arg(long, default_value = "0")]
+        input_tokens: u64,
+        #[arg(long, default_value = "0")]
+        output_tokens: u64,
+        /// Cost in US dollars
+        #[arg(long, default_value = "0")]
+        cost: f64,
+        /// Reporting agent (defaults to GTR_AGENT, then "cli")
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// Time in each status and reported usage, per item and in total
+    Stats {
+        /// Work item IDs
+        ids: Vec<String>,
+        /// Include every item of this convoy
+        #[arg(long)]
+        convoy: Option<String>,
+    },
+    /// Record a triage assessment (run by `rgt sling --triage` agents)
+    Triage {
+        /// Work item ID
+        id: String,
+        /// Estimated complexity
+        #[arg(long, value_parser = ["small", "medium", "large"])]
+        complexity: String,
+        /// Proposed label (repeatable)
+        #[arg(long = "label")]
+        labels: Vec<String>,
+        /// Proposed rig
+        #[arg(long)]
+        rig: Option<String>,
+        /// Proposed priority (0 = highest)
+        #[arg(long)]
+        priority: Option<u8>,
+        /// Short rationale
+        #[arg(long)]
+        notes: Option<String>,
+    },
 }
 
 pub async fn run(cmd: &WorkCommand) -> anyhow::Result<()> {
     match cmd {
-        WorkCommand::Show { id } => handle_show(id).await,
-        WorkCommand::List => handle_list().await,
+        WorkCommand::Create {
+            title,
+            desc,
+            priority,
+            labels,
+            rig,
+            depends_on,
+        } => {
+            let mut item = WorkItem::new(gtr_core::ids::work_item_id(), title.as_str());
+            item.description = desc.clone().unwrap_or_default();
+            item.priority = *priority;
+            item.labels = labels.clone();
+            item.rig = rig.clone();
+            item.depends_on = depends_on.iter().cloned().map(WorkItemId).collect();
+            handle_create(item).await
+        }
+        WorkCommand::Import { file } => handle_import(file).await,
+        WorkCommand::Show { id, history } => handle_show(id, *history).await,
+        WorkCommand::List {
+            status,
+            assignee,
+            rig,
+            priority,
+            sort,
+        } => {
+            let mut filters = vec![];
+            if let Some(status) = status {
+                filters.push((SA_STATUS, status.clone()));
+            }
+            if let Some(assignee) = assignee {
+                filters.push((SA_ASSIGNEE, assignee.clone()));
+            }
+            if let Some(rig) = rig {
+                filters.push((SA_RIG, rig.clone()));
+            }
+            if let Some(priority) = priority {
+                filters.push((SA_PRIORITY, priority.to_string()));
+            }
+            handle_list(&filters, sort == "priority").await
+        }
         WorkCommand::Close { id } => handle_close(id).await,
+        WorkCommand::Split { id, into } => {
+            let id = id
+                .clone()
+                .or_else(|| std::env::var("GTR_WORK_ITEM").ok())
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "No work item specified. Pass <ID> or set GTR_WORK_ITEM env var"
+                    )
+                })?;
+            handle_split(&id, into).await
+        }
+        WorkCommand::Usage {
+            id,
+            input_tokens,
+            output_tokens,
+            cost,
+            from,
+        } => {
+            let id = id
+                .clone()
+                .or_else(|| std::env::var("GTR_WORK_ITEM").ok())
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "No work item specified. Pass <ID> or set GTR_WORK_ITEM env var"
+                    )
+                })?;
+            let usage = WorkUsageSignal {
+                agent: from
+                    .clone()
+                    .or_else(|| std::env::var("GTR_AGENT").ok())
+                    .unwrap_or_else(|| "cli".to_string()),
+                input_tokens: *input_tokens,
+                output_tokens: *output_tokens,
+                cost_usd: *cost,
+            };
+            handle_usage(&id, usage).await
+        }
+        WorkCommand::Stats { ids, convoy } => handle_stats(ids, convoy.as_deref()).await,
+        WorkCommand::Comment { id, text, from } => {
+            let author = from
+                .clone()
+                .or_else(|| std::env::var("GTR_AGENT").ok())
+                .unwrap_or_else(|| "cli".to_string());
+            handle_comment(id, text, &author).await
+        }
+        WorkCommand::Triage {
+            id,
+            complexity,
+            labels,
+            rig,
+            priority,
+            notes,
+        } => {
+            let triage = TriageSignal {
+                complexity: complexity.clone(),
+                labels: labels.clone(),
+                rig: rig.clone(),
+                priority: *priority,
+                notes: notes.clone(),
+            };
+            handle_triage(id, triage).await
+        }
     }
 }
 
-async fn handle_list() -> anyhow::Result<()> {
+async fn handle_create(item: WorkItem) -> anyhow::Result<()> {
     let client = crate::client::connect().await?;
-    let resp = client
-        .list_workflow_executions(
-            100,
-            vec![],
-            "WorkflowType = 'work_item_wf' AND ExecutionStatus = 'Running'".to_string(),
+    let id = item.id.0.clone();
+
+    client
+        .start_workflow(
+            vec![item.as_json_payload()?],
+            "work".to_string(),
+            id.clone(),
+            "work_item_wf".to_string(),
+            None,
+            Default::default(),
         )
         .await?;
 
+    println!(
+        "Created work item: {id} — {} ({})",
+        item.title, item.priority
+    );
+    Ok(())
+}
+
+async fn handle_import(file: &Path) -> anyhow::Result<()> {
+    let backlog = BacklogDef::from_file(file)?;
+    let items = backlog.staged_work_items()?;
+    if items.is_empty() {
+        anyhow::bail!("{} has no [[item]] entries", file.display());
+    }
+
+    let client = crate::client::connect().await?;
+    let convoy_id =
+        crate::commands::convoy::start_convoy(&client, &backlog.title, None, None).await?;
+    println!("Created convoy: {convoy_id} — {}", backlog.title);
+    crate::commands::convoy::add_items(&client, &convoy_id, &items).await?;
+    println!("\nImported {} work item(s)", items.len());
+    Ok(())
+}
+
+/// List running work items matching every `(search attribute, value)` filter,
+/// newest first unless sorted by age or priority.
+async fn handle_list(filters: &[(&str, String)], by_priority: bool) -> anyhow::Result<()> {
+    let mut query = "WorkflowType = 'work_item_wf' AND ExecutionStatus = 'Running'".to_string();
+    for (name, value) in filters {
+        query.push_str(&format!(" AND {name} = '{}'", value.replace('\'', "\\'")));
+    }
+    let client = crate::client::connect().await?;
+    let resp = client.list_workflow_executions(100, vec![], query).await?;
+
     if resp.executions.is_empty() {
         println!("No running work items.");
         return Ok(());
     }
 
-    println!("{:<30} {:<12} {:<24}", "WORK ITEM", "STATUS", "STARTED");
-    println!("{}", "-".repeat(66));
+    let mut executions = resp.executions;
+    let started =
+        |exec: &WorkflowExecutionInfo| exec.start_time.as_ref().map(|t| (t.seconds, t.nanos));
+    if by_priority {
+        // "P0" sorts before "P1"; items without a priority go last
+        executions.sort_by_key(|exec| {
+            let priority = search_attribute(exec, SA_PRIORITY).unwrap_or_else(|| "~".to_string());
+            (priority, started(exec))
+        });
+    } else {
+        executions.sort_by_key(started);
+    }
+
+    println!(
+        "{:<30} {:<12} {:<8} {:<20} {:<24}",
+        "WORK ITEM", "STATUS", "PRIORITY", "ASSIGNEE", "STARTED"
+    );
+    println!("{}", "-".repeat(96));
 
-    for exec in &resp.executions {
+    for exec in &executions {
         let wf_id = exec
             .execution
             .as_ref()
             .map(|e| e.workflow_id.as_str())
             .unwrap_or("unknown");
-        let status = workflow_status_str(exec.status);
+        let status = search_attribute(exec, SA_STATUS)
+            .unwrap_or_else(|| workflow_status_str(exec.status).to_string());
+        let priority = search_attribute(exec, SA_PRIORITY).unwrap_or_else(|| "-".to_string());
+        let assignee = search_attribute(exec, SA_ASSIGNEE).unwrap_or_else(|| "-".to_string());
         let started = exec
             .start_time
             .as_ref()
             .map(|t| format_timestamp(t))
             .unwrap_or_else(|| "-".to_string());
-        println!("{:<30} {:<12} {:<24}", wf_id, status, started);
+        println!(
+            "{:<30} {:<12} {:<8} {:<20} {:<24}",
+            wf_id, status, priority, assignee, started
+        );
     }
 
-    println!("\n{} work item(s) running", resp.executions.len());
+    println!("\n{} work item(s) running", executions.len());
     Ok(())
 }
 
-async fn handle_show(id: &str) -> anyhow::Result<()> {
+/// A Keyword search attribute of a listed workflow, if set and not empty.
+fn search_attribute(exec: &WorkflowExecutionInfo, name: &str) -> Option<String> {
+    let payload = exec.search_attributes.as_ref()?.indexed_fields.get(name)?;
+    serde_json::from_slice::<String>(&payload.data)
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+async fn handle_show(id: &str, history: bool) -> anyhow::Result<()> {
     let client = crate::client::connect().await?;
     let resp = client
         .describe_workflow_execution(id.to_string(), None)
@@ -92,6 +378,84 @@ async fn handle_show(id: &str) -> anyhow::Result<()> {
             println!("Closed:    {}", format_timestamp(close));
         }
         println!("History:   {} events", info.history_length);
+
+        if wf_type == "work_item_wf" {
+            let events = gtr_temporal::history::fetch_events(&client, wf_id).await?;
+            if let Some(item) = gtr_temporal::history::work_item(&events) {
+                println!();
+                println!("Title:     {}", item.title);
+                println!("Priority:  {}", item.priority);
+                if !item.labels.is_empty() {
+                    println!("Labels:    {}", item.labels.join(", "));
+                }
+                if !item.depends_on.is_empty() {
+                    let deps: Vec<&str> = item.depends_on.iter().map(|d| d.0.as_str()).collect();
+                    println!("Depends:   {}", deps.join(", "));
+                }
+                if let Some(parent) = &item.parent {
+                    println!("Parent:    {}", parent.0);
+                }
+                if let Some(run_id) = &item.retry_of {
+                    println!("Retry of:  run {run_id}");
+                }
+                let parts = gtr_temporal::history::work_item_parts(&events);
+                if !parts.is_empty() {
+                    println!("Parts:     {}", parts.join(", "));
+                }
+                if !item.description.trim().is_empty() {
+                    println!("Description:");
+                    for line in item.description.lines() {
+                        println!("  {line}");
+                    }
+                }
+            }
+            if let Some(triage) = gtr_temporal::history::triage(&events) {
+                println!();
+                println!("Triage:    {} complexity", triage.complexity);
+                if !triage.labels.is_empty() {
+                    println!("Labels:    {}", triage.labels.join(", "));
+                }
+                if let Some(rig) = &triage.rig {
+                    println!("Rig:       {rig}");
+                }
+                if let Some(priority) = triage.priority {
+                    println!("Priority:  P{priority}");
+                }
+                if let Some(notes) = &triage.notes {
+                    println!("Notes:     {notes}");
+                }
+            }
+            if let Some(report) = gtr_temporal::history::polecat_report(&events) {
+                println!();
+                println!("Agent:     {} ({})", report.polecat_id, report.status);
+                println!("Branch:    {}", report.branch);
+                match report.summary.as_deref().map(str::trim) {
+                    Some(summary) if !summary.is_empty() => {
+                        println!("Summary:");
+                        for line in summary.lines() {
+                            println!("  {line}");
+                        }
+                    }
+                    _ => println!("Summary:   (none)"),
+                }
+            }
+            let comments = gtr_temporal::history::work_item_comments(&events);
+            if !comments.is_empty() {
+                println!();
+                println!("Comments:");
+                for comment in &comments {
+                    println!(
+                        "  {} {}: {}",
+                        comment.at.format("%Y-%m-%d %H:%M"),
+                        comment.author,
+                        comment.text
+                    );
+                }
+            }
+            if history {
+                print_timeline(&gtr_temporal::history::work_item_timeline(&events));
+            }
+        }
     } else {
         println!("No execution info returned for {id}");
     }
@@ -99,6 +463,29 @@ async fn handle_show(id: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn print_timeline(timeline: &[WorkItemTransition]) {
+    println!();
+    if timeline.is_empty() {
+        println!("History:   (no transitions yet)");
+        return;
+    }
+    println!("History:");
+    for entry in timeline {
+        let at = entry
+            .at
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let mut line = format!("  {at}  {:<18}", entry.event);
+        if let Some(actor) = &entry.actor {
+            line.push_str(&format!(" {actor}"));
+        }
+        if let Some(detail) = &entry.detail {
+            line.push_str(&format!(" — {detail}"));
+        }
+        println!("{}", line.trim_end());
+    }
+}
+
 async fn handle_close(id: &str) -> anyhow::Result<()> {
     let client = crate::client::connect().await?;
     client
@@ -114,6 +501,155 @@ async fn handle_close(id: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn handle_split(id: &str, titles: &[String]) -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+    let split = WorkSplitSignal {
+        titles: titles.to_vec(),
+    };
+    client
+        .signal_workflow_execution(
+            id.to_string(),
+            String::new(),
+            "work_split".to_string(),
+            Some(split.as_json_payload()?.into()),
+            None,
+        )
+        .await?;
+    println!(
+        "Split {id} into {} part(s); see them with `rgt work show {id}`",
+        titles.len()
+    );
+    Ok(())
+}
+
+async fn handle_usage(id: &str, usage: WorkUsageSignal) -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+    client
+        .signal_workflow_execution(
+            id.to_string(),
+            String::new(),
+            "work_usage".to_string(),
+            Some(usage.as_json_payload()?.into()),
+            None,
+        )
+        .await?;
+    println!(
+        "Recorded usage on {id}: {} in / {} out tokens, ${:.2}",
+        usage.input_tokens, usage.output_tokens, usage.cost_usd
+    );
+    Ok(())
+}
+
+async fn handle_stats(ids: &[String], convoy: Option<&str>) -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+    let mut ids = ids.to_vec();
+    if let Some(convoy) = convoy {
+        let events = gtr_temporal::history::fetch_events(&client, convoy).await?;
+        ids.extend(gtr_temporal::history::convoy_items(&events));
+    }
+    if ids.is_empty() {
+        anyhow::bail!("Pass work item IDs or --convoy <ID>");
+    }
+
+    println!(
+        "{:<30} {:>9} {:>9} {:>11} {:>9} {:>12} {:>9}",
+        "WORK ITEM", "PENDING", "ASSIGNED", "IN PROGRESS", "TRACKING", "TOKENS", "COST"
+    );
+    println!("{}", "-".repeat(97));
+    let mut total = WorkItemStats::default();
+    for id in &ids {
+        let info = client
+            .describe_workflow_execution(id.clone(), None)
+            .await
+            .ok()
+            .and_then(|resp| resp.workflow_execution_info);
+        let Some(info) = info else {
+            println!("{id:<30} (not found)");
+            continue;
+        };
+        // A finished item's clock stops when it closed
+        let until = info
+            .close_time
+            .as_ref()
+            .and_then(|t| chrono::DateTime::from_timestamp(t.seconds, t.nanos as u32))
+            .unwrap_or_else(chrono::Utc::now);
+        let events = gtr_temporal::history::fetch_events(&client, id).await?;
+        let stats = gtr_temporal::history::work_item_stats(&events, until);
+        print_stats_row(id, &stats);
+        total.add(&stats);
+    }
+    if ids.len() > 1 {
+        println!("{}", "-".repeat(97));
+        print_stats_row(&format!("total ({} items)", ids.len()), &total);
+    }
+    Ok(())
+}
+
+fn print_stats_row(label: &str, stats: &WorkItemStats) {
+    let time = |status: &str| match stats.seconds_in_status.get(status) {
+        Some(&secs) => crate::commands::mq::format_secs(secs),
+        None => "-".to_string(),
+    };
+    println!(
+        "{:<30} {:>9} {:>9} {:>11} {:>9} {:>12} {:>9}",
+        label,
+        time("pending"),
+        time("assigned"),
+        time("in_progress"),
+        time("tracking"),
+        stats.input_tokens + stats.output_tokens,
+        format!("${:.2}", stats.cost_usd)
+    );
+}
+
+async fn handle_comment(id: &str, text: &str, author: &str) -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+    let comment = WorkCommentSignal {
+        author: author.to_string(),
+        text: text.to_string(),
+    };
+    client
+        .signal_workflow_execution(
+            id.to_string(),
+            String::new(),
+            "work_comment".to_string(),
+            Some(comment.as_json_payload()?.into()),
+            None,
+        )
+        .await?;
+    println!("Commented on {id} as {author}");
+    Ok(())
+}
+
+async fn handle_triage(id: &str, triage: TriageSignal) -> anyhow::Result<()> {
+    let client = crate::client::connect().await?;
+    let payload = triage.as_json_payload()?;
+    // Hand the assessment to the triage workflow first, which ends the
+    // agent's session, so a failure leaves the item as it was. There is no
+    // triage workflow if the item is triaged by hand.
+    let to_agent = QueuedSignal::new(
+        gtr_core::state::triage_workflow_id(id),
+        "work_triage",
+        Some(serde_json::to_value(&triage)?),
+    );
+    if let Err(e) = gtr_temporal::outbox::deliver(&client, &to_agent).await {
+        if !gtr_temporal::outbox::is_not_found(&e) {
+            return Err(e);
+        }
+    }
+    client
+        .signal_workflow_execution(
+            id.to_string(),
+            String::new(),
+            "work_triage".to_string(),
+            Some(payload.into()),
+            None,
+        )
+        .await?;
+    println!("Triaged {id}: {} complexity", triage.complexity);
+    Ok(())
+}
+
 fn workflow_status_str(status: i32) -> &'static str {
     match status {
         0 => "Unspecified",
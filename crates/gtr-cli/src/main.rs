@@ -1,8 +1,11 @@
 mod client;
 mod commands;
 
+use std::ffi::OsString;
+
 use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::Shell;
+use gtr_core::config::CommandAliases;
 
 /// rgt — Rusted Gas Town CLI
 #[derive(Debug, Parser)]
@@ -27,6 +30,9 @@ Environment variables:
   GTR_WORK_ITEM   Default work item ID for done, checkpoint
   GTR_RIG         Default rig for refinery routing
   TEMPORAL_ADDRESS Temporal server (default: localhost:7233)
+  GTR_OFFLINE_QUEUE Queue mail/done/escalate locally when Temporal is down
+
+Command aliases are read from the [aliases] table in ~/.gtr/config/town.toml.
 ")]
 struct Cli {
     #[command(subcommand)]
@@ -88,6 +94,10 @@ enum Command {
     #[command(subcommand)]
     Mq(commands::mq::MqCommand),
 
+    /// Offline outbox — signals queued while Temporal was unreachable
+    #[command(subcommand)]
+    Outbox(commands::outbox::OutboxCommand),
+
     /// Manage polecats — ephemeral workers that run agents on rigs
     #[command(subcommand)]
     Polecat(commands::polecat::PolecatCommand),
@@ -122,13 +132,13 @@ enum Command {
     Feed(commands::feed::FeedCommand),
 
     /// Check system health — verify Temporal, mayor, and dependencies
-    Doctor,
+    Doctor(commands::doctor::DoctorCommand),
 
     /// Start Gas Town (launch mayor workflow)
     Up,
 
-    /// Stop Gas Town gracefully (stop mayor and all agents)
-    Down,
+    /// Stop Gas Town gracefully (agents hand off, then mayor and all agents stop)
+    Down(commands::down::DownCommand),
 
     /// Start everything — Temporal server, worker, and workflows (via tmux)
     Start,
@@ -168,11 +178,52 @@ enum Command {
     /// Show version and build info
     Version,
 
-    /// Generate shell completions
+    /// Generate shell completions (including town.toml command aliases)
     Completions {
         /// Shell to generate completions for
         shell: Shell,
     },
+
+    /// Generate reference documentation
+    #[command(subcommand)]
+    Docs(commands::docs::DocsCommand),
+}
+
+/// Replace a leading alias from town.toml with its expansion. Built-in
+/// commands always win, and expansions are not expanded again.
+fn expand_alias(aliases: &CommandAliases, mut args: Vec<OsString>) -> Vec<OsString> {
+    let Some(name) = args.get(1).and_then(|arg| arg.to_str()) else {
+        return args;
+    };
+    if Cli::command().find_subcommand(name).is_some() {
+        return args;
+    }
+    if let Some(expansion) = aliases.expand(name) {
+        args.splice(1..2, expansion.into_iter().map(OsString::from));
+    }
+    args
+}
+
+/// The full command tree, with each alias added as a subcommand so completions
+/// and man pages know about it.
+fn command_with_aliases(aliases: &CommandAliases) -> clap::Command {
+    let mut cmd = Cli::command();
+    for (name, expansion) in &aliases.aliases {
+        if cmd.find_subcommand(name).is_some() {
+            continue;
+        }
+        cmd = cmd.subcommand(
+            clap::Command::new(name.clone())
+                .about(format!("Alias for `rgt {expansion}`"))
+                .arg(
+                    clap::Arg::new("args")
+                        .num_args(0..)
+                        .trailing_var_arg(true)
+                        .allow_hyphen_values(true),
+                ),
+        );
+    }
+    cmd
 }
 
 #[tokio::main]
@@ -184,7 +235,8 @@ async fn main() -> anyhow::Result<()> {
         )
         .init();
 
-    let cli = Cli::parse();
+    let aliases = CommandAliases::load();
+    let cli = Cli::parse_from(expand_alias(&aliases, std::env::args_os().collect()));
 
     match &cli.command {
         Command::Attach(cmd) => commands::attach::run(&cmd).await,
@@ -200,6 +252,7 @@ async fn main() -> anyhow::Result<()> {
         Command::Escalate(cmd) => commands::escalate::run(cmd).await,
         Command::Mol(cmd) => commands::mol::run(cmd).await,
         Command::Mq(cmd) => commands::mq::run(cmd).await,
+        Command::Outbox(cmd) => commands::outbox::run(cmd).await,
         Command::Polecat(cmd) => commands::polecat::run(cmd).await,
         Command::Rig(cmd) => commands::rig::run(cmd).await,
         Command::Crew(cmd) => commands::crew::run(cmd).await,
@@ -211,16 +264,16 @@ async fn main() -> anyhow::Result<()> {
         Command::Mayor(cmd) => commands::mayor::run(cmd).await,
         Command::Checkpoint(cmd) => commands::checkpoint::run(cmd).await,
         Command::Feed(cmd) => commands::feed::run(cmd).await,
-        Command::Doctor => commands::doctor::run().await,
+        Command::Doctor(cmd) => commands::doctor::run(cmd).await,
         Command::Up => commands::up::run().await,
-        Command::Down => commands::down::run().await,
+        Command::Down(cmd) => commands::down::run(cmd).await,
         Command::Start => commands::start::run().await,
         Command::Stop => commands::stop::run().await,
         Command::Sessions => commands::sessions::run(),
         Command::Install(cmd) => commands::install::run(&cmd).await,
         Command::Status => commands::status::run().await,
         Command::Session(cmd) => commands::session::run(cmd).await,
-        Command::Services(cmd) => commands::services::run(cmd),
+        Command::Services(cmd) => commands::services::run(cmd).await,
         Command::Workspace(cmd) => commands::workspace::run(cmd),
         Command::Diagnostics(cmd) => commands::diagnostics::run(cmd).await,
         Command::Worker(cmd) => commands::worker::run(cmd).await,
@@ -236,11 +289,12 @@ async fn main() -> anyhow::Result<()> {
         Command::Completions { shell } => {
             clap_complete::generate(
                 *shell,
-                &mut Cli::command(),
+                &mut command_with_aliases(&aliases),
                 "rgt",
                 &mut std::io::stdout(),
             );
             Ok(())
         }
+        Command::Docs(cmd) => commands::docs::run(cmd, command_with_aliases(&aliases)),
     }
 }
@@ -0,0 +1,135 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+
+/// Write `contents` so readers see either the old file or the complete new
+/// one, never a torn write: write a sibling temp file, fsync it, then rename
+/// it over `path`.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| std::io::Error::other(format!("not a file path: {}", path.display())))?
+        .to_string_lossy();
+    let tmp = path.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()));
+
+    let result = (|| {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+        return result;
+    }
+
+    // Persist the rename itself; not every platform lets us open a directory
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        if let Ok(dir) = std::fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+/// Result of reading a state file that a crash may have left damaged.
+#[derive(Debug, PartialEq)]
+pub enum StateFile<T> {
+    Missing,
+    Valid(T),
+    /// The file didn't parse; it was moved aside to `quarantined`
+    Corrupt {
+        quarantined: PathBuf,
+    },
+}
+
+impl<T> StateFile<T> {
+    /// The parsed value, treating missing and corrupt files alike.
+    pub fn ok(self) -> Option<T> {
+        match self {
+            StateFile::Valid(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Read and parse a state file. Unparseable contents are renamed to
+/// `<name>.corrupt` so the next write starts clean and the bad copy is kept
+/// for inspection.
+pub fn read_state<T>(
+    path: &Path,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> std::io::Result<StateFile<T>> {
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(StateFile::Missing),
+        Err(e) => return Err(e),
+    };
+    if let Some(value) = std::str::from_utf8(&data).ok().and_then(parse) {
+        return Ok(StateFile::Valid(value));
+    }
+
+    let mut quarantined = path.as_os_str().to_owned();
+    quarantined.push(".corrupt");
+    let quarantined = PathBuf::from(quarantined);
+    std::fs::rename(path, &quarantined)?;
+    Ok(StateFile::Corrupt { quarantined })
+}
+
+/// [`read_state`] for JSON files.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> std::io::Result<StateFile<T>> {
+    read_state(path, |s| serde_json::from_str(s).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn write_atomic_replaces_and_leaves_no_temp() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pid");
+        write_atomic(&path, "123").unwrap();
+        write_atomic(&path, "456").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "456");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn corrupt_state_is_quarantined() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("env.json");
+        assert_eq!(
+            read_json::<HashMap<String, String>>(&path).unwrap(),
+            StateFile::Missing
+        );
+
+        // A crash mid-write under the old non-atomic writer
+        std::fs::write(&path, "{\"GTR_AGENT\": \"may").unwrap();
+        let StateFile::Corrupt { quarantined } =
+            read_json::<HashMap<String, String>>(&path).unwrap()
+        else {
+            panic!("expected corrupt state");
+        };
+        assert!(!path.exists());
+        assert!(quarantined.ends_with("env.json.corrupt"));
+
+        write_atomic(&path, "{\"GTR_AGENT\": \"mayor\"}").unwrap();
+        let env = read_json::<HashMap<String, String>>(&path)
+            .unwrap()
+            .ok()
+            .unwrap();
+        assert_eq!(env["GTR_AGENT"], "mayor");
+    }
+
+    #[test]
+    fn pid_parse_rejects_garbage() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pid");
+        std::fs::write(&path, "12\u{0}\u{0}").unwrap();
+        let state = read_state(&path, |s| s.trim().parse::<i32>().ok()).unwrap();
+        assert!(matches!(state, StateFile::Corrupt { .. }));
+    }
+}
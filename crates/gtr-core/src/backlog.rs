@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::formula::{interpolate, FormulaDef, StepKind};
+use crate::types::{Priority, WorkItem, WorkItemId};
+
+/// A backlog file for `rgt work import`: one convoy and the work items in it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacklogDef {
+    /// Title of the convoy the items are grouped into
+    pub title: String,
+    #[serde(rename = "item", default)]
+    pub items: Vec<BacklogItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacklogItem {
+    /// Name other items in the file use for this one in `depends_on`
+    pub key: Option<String>,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    /// `P0`..`P3`; `P2` if left out
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Rig the work is meant for
+    pub rig: Option<String>,
+    /// Keys of other items in the file, or IDs of existing work items
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Release-train stage: when the convoy auto-slings, an item waits until
+    /// every item of an earlier stage is done. 0 if left out.
+    #[serde(default)]
+    pub stage: u32,
+}
+
+impl BacklogDef {
+    pub fn from_toml(content: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn from_json(content: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    /// Read a `.toml` or `.json` backlog, going by the file extension.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("json") => Self::from_json(&content),
+            Some("yaml" | "yml") => {
+                anyhow::bail!("YAML backlogs aren't supported; write the backlog as TOML or JSON")
+            }
+            _ => anyhow::bail!(
+                "unknown backlog format: {} (expected .toml or .json)",
+                path.display()
+            ),
+        }
+    }
+
+    /// One item per step of `formula`, so the formula can run as a convoy.
+    /// Steps are keyed by name, which carries their `depends_on` over, and
+    /// `{{var}}`s in names, commands, args and prompts are filled in from
+    /// `vars` (and var defaults); an agent step's prompt becomes its item's
+    /// description. Errors if a declared var is missing or not an allowed
+    /// value, a dependency is unknown or the steps have a cycle.
+    pub fn from_formula(
+        formula: &FormulaDef,
+        vars: &HashMap<String, String>,
+    ) -> anyhow::Result<Self> {
+        formula.topo_sort()?;
+        let vars = &formula.with_defaults(vars);
+        formula.check_vars(vars)?;
+
+        let mut assigned: Vec<String> = vars.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        assigned.sort();
+        let title = if assigned.is_empty() {
+            formula.name.clone()
+        } else {
+            format!("{} ({})", formula.name, assigned.join(", "))
+        };
+        let items = formula
+            .steps
+            .iter()
+            .map(|step| {
+                let description = match (&step.kind, &step.prompt) {
+                    (StepKind::Agent, Some(prompt)) => interpolate(prompt, vars),
+                    (StepKind::Formula, _) => format!(
+                        "Run the `{}` formula",
+                        step.formula.as_deref().unwrap_or_default()
+                    ),
+                    _ => {
+                        let command: Vec<String> = std::iter::once(&step.command)
+                            .chain(&step.args)
+                            .map(|part| interpolate(part, vars))
+                            .collect();
+                        format!("Run `{}`", command.join(" "))
+                    }
+                };
+                BacklogItem {
+                    key: Some(step.name.clone()),
+                    title: interpolate(&step.name, vars),
+                    description,
+                    priority: None,
+                    labels: vec![format!("formula:{}", formula.name)],
+                    rig: None,
+                    depends_on: step.depends_on.clone(),
+                    stage: 0,
+                }
+            })
+            .collect();
+        Ok(Self { title, items })
+    }
+
+    /// The items as work items with fresh IDs, ordered so each comes after the
+    /// items it depends on. Keys in `depends_on` become those IDs; anything
+    /// else is kept as the ID of an existing work item. Errors on a bad
+    /// priority, a duplicate key or a cycle.
+    pub fn work_items(&self) -> anyhow::Result<Vec<WorkItem>> {
+        Ok(self
+            .staged_work_items()?
+            .into_iter()
+            .map(|(item, _)| item)
+            .collect())
+    }
+
+    /// [`Self::work_items`] paired with each item's stage. Also errors when an
+    /// item depends on one in a later stage, which would never be slung first.
+    pub fn staged_work_items(&self) -> anyhow::Result<Vec<(WorkItem, u32)>> {
+        let mut keys: HashMap<&str, usize> = HashMap::new();
+        for (i, item) in self.items.iter().enumerate() {
+            if let Some(key) = item.key.as_deref() {
+                if keys.insert(key, i).is_some() {
+                    anyhow::bail!("duplicate backlog key: {key}");
+                }
+            }
+        }
+
+        let ids: Vec<String> = self
+            .items
+            .iter()
+            .map(|_| crate::ids::work_item_id())
+            .collect();
+        let mut work_items = Vec::with_capacity(self.items.len());
+        for (item, id) in self.items.iter().zip(&ids) {
+            let mut work_item = WorkItem::new(id.as_str(), item.title.as_str());
+            work_item.description = item.description.clone();
+            if let Some(priority) = &item.priority {
+                work_item.priority = priority
+                    .parse::<Priority>()
+                    .map_err(|e| anyhow::anyhow!("{}: {e}", item.title))?;
+            }
+            work_item.labels = item.labels.clone();
+            work_item.rig = item.rig.clone();
+            work_item.depends_on = item
+                .depends_on
+                .iter()
+                .map(|dep| match keys.get(dep.as_str()) {
+                    Some(&j) => WorkItemId(ids[j].clone()),
+                    None => WorkItemId(dep.clone()),
+                })
+                .collect();
+            work_items.push(work_item);
+        }
+
+        // Kahn's algorithm, taking the earliest ready item to keep file order
+        let deps: Vec<Vec<usize>> = self
+            .items
+            .iter()
+            .map(|item| {
+                item.depends_on
+                    .iter()
+                    .filter_map(|dep| keys.get(dep.as_str()).copied())
+                    .collect()
+            })
+            .collect();
+        for (item, item_deps) in self.items.iter().zip(&deps) {
+            if let Some(&j) = item_deps
+                .iter()
+                .find(|&&j| self.items[j].stage > item.stage)
+            {
+                anyhow::bail!(
+                    "{} (stage {}) depends on {} (stage {})",
+                    item.title,
+                    item.stage,
+                    self.items[j].title,
+                    self.items[j].stage
+                );
+            }
+        }
+        let mut placed = vec![false; self.items.len()];
+        let mut order = Vec::with_capacity(self.items.len());
+        while order.len() < self.items.len() {
+            let next = (0..self.items.len())
+                .find(|&i| !placed[i] && deps[i].iter().all(|&j| placed[j]))
+                .ok_or_else(|| anyhow::anyhow!("cycle detected in backlog dependencies"))?;
+            placed[next] = true;
+            order.push(next);
+        }
+
+        let mut slots: Vec<Option<WorkItem>> = work_items.into_iter().map(Some).collect();
+        Ok(order
+            .into_iter()
+            .filter_map(|i| Some((slots[i].take()?, self.items[i].stage)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BACKLOG: &str = r#"
+title = "Sprint 12"
+
+[[item]]
+key = "api"
+title = "Add the login endpoint"
+priority = "P1"
+labels = ["auth"]
+depends_on = ["schema", "wi-existing"]
+
+[[item]]
+key = "schema"
+title = "Add the users table"
+description = "Email and password hash."
+"#;
+
+    #[test]
+    fn work_items_resolve_keys_in_dependency_order() {
+        let backlog = BacklogDef::from_toml(BACKLOG).unwrap();
+        assert_eq!(backlog.title, "Sprint 12");
+
+        let items = backlog.work_items().unwrap();
+        assert_eq!(items[0].title, "Add the users table");
+        assert_eq!(items[0].priority, Priority::P2);
+        assert_eq!(items[1].priority, Priority::P1);
+        assert_eq!(
+            items[1].depends_on,
+            vec![items[0].id.clone(), WorkItemId("wi-existing".into())]
+        );
+    }
+
+    #[test]
+    fn work_items_reject_cycles_and_bad_priorities() {
+        let cycle = BacklogDef::from_toml(
+            r#"
+title = "t"
+[[item]]
+key = "a"
+title = "A"
+depends_on = ["b"]
+[[item]]
+key = "b"
+title = "B"
+depends_on = ["a"]
+"#,
+        )
+        .unwrap();
+        assert!(cycle.work_items().is_err());
+
+        let bad = BacklogDef::from_json(
+            r#"{"title": "t", "item": [{"title": "A", "priority": "urgent"}]}"#,
+        )
+        .unwrap();
+        assert!(bad.work_items().is_err());
+    }
+
+    #[test]
+    fn formula_steps_become_items_with_their_dependencies() {
+        let formula = FormulaDef::from_toml(
+            r#"
+name = "deploy"
+vars = ["env"]
+
+[[steps]]
+name = "ship"
+command = "deploy.sh"
+args = ["{{env}}"]
+depends_on = ["build"]
+
+[[steps]]
+name = "build"
+command = "cargo"
+args = ["build", "--release"]
+"#,
+        )
+        .unwrap();
+        assert!(BacklogDef::from_formula(&formula, &HashMap::new()).is_err());
+
+        let vars = HashMap::from([("env".to_string(), "prod".to_string())]);
+        let backlog = BacklogDef::from_formula(&formula, &vars).unwrap();
+        assert_eq!(backlog.title, "deploy (env=prod)");
+        let items = backlog.work_items().unwrap();
+        assert_eq!(items[0].title, "build");
+        assert_eq!(items[1].description, "Run `deploy.sh prod`");
+        assert_eq!(items[1].depends_on, vec![items[0].id.clone()]);
+        assert_eq!(items[1].labels, vec!["formula:deploy"]);
+    }
+
+    #[test]
+    fn staged_work_items_keep_stages_and_reject_backward_deps() {
+        let backlog = BacklogDef::from_json(
+            r#"{"title": "t", "item": [
+                {"key": "b", "title": "B", "stage": 2, "depends_on": ["a"]},
+                {"key": "a", "title": "A", "stage": 1}
+            ]}"#,
+        )
+        .unwrap();
+        let staged = backlog.staged_work_items().unwrap();
+        assert_eq!(staged[0].0.title, "A");
+        assert_eq!((staged[0].1, staged[1].1), (1, 2));
+
+        let backward = BacklogDef::from_json(
+            r#"{"title": "t", "item": [
+                {"key": "a", "title": "A", "stage": 1, "depends_on": ["b"]},
+                {"key": "b", "title": "B", "stage": 2}
+            ]}"#,
+        )
+        .unwrap();
+        assert!(backward.staged_work_items().is_err());
+    }
+}
@@ -1,8 +1,11 @@
 use std::path::Path;
+use std::time::SystemTime;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::atomic;
+
 const CHECKPOINT_FILE: &str = ".gtr-checkpoint.json";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,19 +27,23 @@ impl Checkpoint {
     pub fn write(&self, dir: &Path) -> anyhow::Result<()> {
         let path = dir.join(CHECKPOINT_FILE);
         let json = serde_json::to_string_pretty(self)?;
-        std::fs::write(&path, json)?;
+        atomic::write_atomic(&path, json)?;
         Ok(())
     }
 
     /// Read checkpoint from `.gtr-checkpoint.json` in the given directory.
+    /// A damaged checkpoint is set aside as `.gtr-checkpoint.json.corrupt`
+    /// and treated as absent.
     pub fn read(dir: &Path) -> anyhow::Result<Option<Self>> {
         let path = dir.join(CHECKPOINT_FILE);
-        if !path.exists() {
-            return Ok(None);
-        }
-        let data = std::fs::read_to_string(&path)?;
-        let cp: Checkpoint = serde_json::from_str(&data)?;
-        Ok(Some(cp))
+        Ok(atomic::read_json(&path)?.ok())
+    }
+
+    /// When the checkpoint in the given directory was last written, if there is one.
+    pub fn modified(dir: &Path) -> Option<SystemTime> {
+        std::fs::metadata(dir.join(CHECKPOINT_FILE))
+            .and_then(|meta| meta.modified())
+            .ok()
     }
 
     /// Clear checkpoint file from the given directory.
@@ -80,4 +87,13 @@ mod tests {
         assert!(Checkpoint::clear(dir.path()).unwrap());
         assert!(Checkpoint::read(dir.path()).unwrap().is_none());
     }
+
+    #[test]
+    fn corrupt_checkpoint_reads_as_none() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(CHECKPOINT_FILE), "{\"molecule_id\": ").unwrap();
+        assert!(Checkpoint::read(dir.path()).unwrap().is_none());
+        assert!(dir.path().join(".gtr-checkpoint.json.corrupt").exists());
+        assert!(Checkpoint::read(dir.path()).unwrap().is_none());
+    }
 }
@@ -0,0 +1,272 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::plugin::Gate;
+
+/// Project ecosystems patrol knows how to check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectKind {
+    Rust,
+    Node,
+    Python,
+}
+
+/// Detect project kinds from marker files at the root of `dir`.
+pub fn detect_project_kinds(dir: &Path) -> Vec<ProjectKind> {
+    let has = |name: &str| dir.join(name).exists();
+    let mut kinds = Vec::new();
+    if has("Cargo.toml") {
+        kinds.push(ProjectKind::Rust);
+    }
+    if has("package.json") {
+        kinds.push(ProjectKind::Node);
+    }
+    if has("pyproject.toml") || has("requirements.txt") || has("uv.lock") {
+        kinds.push(ProjectKind::Python);
+    }
+    kinds
+}
+
+/// How to turn a check's output into findings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingFormat {
+    /// `cargo --message-format=short`: `path:line:col: level: message`
+    CargoShort,
+    /// `npm audit` text report: a package line followed by `Severity: <level>`
+    NpmAudit,
+    /// Every non-empty line is a finding (e.g. `uv pip check`)
+    Lines,
+}
+
+/// A built-in patrol check. Commands run through `sh -c` so stderr can be
+/// folded into the captured output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuiltinCheck {
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub gate: Gate,
+    pub format: FindingFormat,
+}
+
+impl BuiltinCheck {
+    fn shell(name: &str, script: &str, cooldown_secs: u64, format: FindingFormat) -> Self {
+        Self {
+            name: name.to_string(),
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), format!("{script} 2>&1")],
+            gate: Gate::Cooldown {
+                seconds: cooldown_secs,
+            },
+            format,
+        }
+    }
+}
+
+/// Language-specific checks for the detected project kinds.
+pub fn builtin_checks(kinds: &[ProjectKind]) -> Vec<BuiltinCheck> {
+    let mut checks = Vec::new();
+    for kind in kinds {
+        match kind {
+            ProjectKind::Rust => {
+                checks.push(BuiltinCheck::shell(
+                    "cargo-check",
+                    "cargo check --workspace --all-targets --message-format=short",
+                    15 * 60,
+                    FindingFormat::CargoShort,
+                ));
+                checks.push(BuiltinCheck::shell(
+                    "cargo-clippy",
+                    "cargo clippy --workspace --all-targets --message-format=short",
+                    60 * 60,
+                    FindingFormat::CargoShort,
+                ));
+            }
+            ProjectKind::Node => {
+                checks.push(BuiltinCheck::shell(
+                    "npm-audit",
+                    "npm audit --omit=dev",
+                    6 * 60 * 60,
+                    FindingFormat::NpmAudit,
+                ));
+            }
+            ProjectKind::Python => {
+                checks.push(BuiltinCheck::shell(
+                    "uv-pip-check",
+                    "uv pip check",
+                    60 * 60,
+                    FindingFormat::Lines,
+                ));
+            }
+        }
+    }
+    checks
+}
+
+/// A single actionable problem reported by a check. `signature` is stable
+/// across runs for the same problem so findings can be deduplicated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    pub check: String,
+    pub severity: String,
+    pub message: String,
+    pub signature: String,
+}
+
+impl Finding {
+    pub fn new(check: &str, severity: &str, message: &str) -> Self {
+        Self {
+            check: check.to_string(),
+            severity: severity.to_string(),
+            message: message.to_string(),
+            signature: finding_signature(check, message),
+        }
+    }
+
+    /// Deterministic work item ID for this finding on `rig`. Filing the same
+    /// finding twice resolves to the same work item.
+    pub fn work_item_id(&self, rig: &str) -> String {
+        let rig: String = rig
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        format!("wi-patrol-{}-{}", rig.trim_matches('-'), self.signature)
+    }
+
+    /// Work item title, tagged with the rig and the check that found it.
+    pub fn work_item_title(&self, rig: &str) -> String {
+        let message: String = self.message.chars().take(120).collect();
+        format!("[patrol {rig}/{}] {message}", self.check)
+    }
+}
+
+/// Stable signature for a finding: FNV-1a over the check name and the message
+/// with digits stripped, so line/column shifts don't create new findings.
+pub fn finding_signature(check: &str, message: &str) -> String {
+    let normalized: String = message
+        .chars()
+        .filter(|c| !c.is_ascii_digit())
+        .collect();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in check.bytes().chain([0u8]).chain(normalized.trim().bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{check}-{hash:016x}")
+}
+
+/// Extract findings from a check's output. A successful exit yields none.
+pub fn extract_findings(
+    check: &str,
+    format: FindingFormat,
+    exit_code: Option<i32>,
+    lines: &[String],
+) -> Vec<Finding> {
+    if exit_code == Some(0) && format != FindingFormat::CargoShort {
+        return Vec::new();
+    }
+    match format {
+        FindingFormat::CargoShort => lines
+            .iter()
+            .filter_map(|line| {
+                let (_, rest) = line.split_once(": ")?;
+                let (level, _) = rest.split_once(": ")?;
+                if level == "error" || level == "warning" {
+                    Some(Finding::new(check, level, line.trim()))
+                } else {
+                    None
+                }
+            })
+            .collect(),
+        FindingFormat::NpmAudit => {
+            let mut findings = Vec::new();
+            let mut package: Option<&str> = None;
+            for line in lines {
+                let line = line.trim();
+                if let Some(severity) = line.strip_prefix("Severity:") {
+                    if let Some(pkg) = package.take() {
+                        let severity = severity.trim();
+                        findings.push(Finding::new(
+                            check,
+                            severity,
+                            &format!("{pkg} ({severity})"),
+                        ));
+                    }
+                } else if !line.is_empty() && package.is_none() {
+                    package = Some(line);
+                } else if line.is_empty() {
+                    package = None;
+                }
+            }
+            findings
+        }
+        FindingFormat::Lines => lines
+            .iter()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .map(|l| Finding::new(check, "error", l))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_rust_and_python() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+        std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        let kinds = detect_project_kinds(dir.path());
+        assert_eq!(kinds, vec![ProjectKind::Rust, ProjectKind::Python]);
+
+        let names: Vec<String> = builtin_checks(&kinds).into_iter().map(|c| c.name).collect();
+        assert_eq!(names, vec!["cargo-check", "cargo-clippy", "uv-pip-check"]);
+    }
+
+    #[test]
+    fn cargo_findings_ignore_line_shifts() {
+        let run1 = vec![
+            "src/main.rs:10:5: warning: unused variable: `x`".to_string(),
+            "warning: `demo` (bin \"demo\") generated 1 warning".to_string(),
+        ];
+        let run2 = vec!["src/main.rs:12:5: warning: unused variable: `x`".to_string()];
+        let a = extract_findings("cargo-clippy", FindingFormat::CargoShort, Some(0), &run1);
+        let b = extract_findings("cargo-clippy", FindingFormat::CargoShort, Some(0), &run2);
+        assert_eq!(a.len(), 1);
+        assert_eq!(a[0].severity, "warning");
+        assert_eq!(a[0].signature, b[0].signature);
+    }
+
+    #[test]
+    fn npm_audit_findings() {
+        let out: Vec<String> = "lodash  <4.17.21\nSeverity: high\nPrototype Pollution\n\nminimist  <1.2.6\nSeverity: critical\n"
+            .lines()
+            .map(str::to_string)
+            .collect();
+        let findings = extract_findings("npm-audit", FindingFormat::NpmAudit, Some(1), &out);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].message, "lodash  <4.17.21 (high)");
+        assert_eq!(findings[1].severity, "critical");
+    }
+
+    #[test]
+    fn finding_work_item_is_stable_per_rig() {
+        let a = Finding::new("cargo-check", "error", "src/lib.rs:3:1: error: oops");
+        let b = Finding::new("cargo-check", "error", "src/lib.rs:9:1: error: oops");
+        assert_eq!(a.work_item_id("my rig"), b.work_item_id("my rig"));
+        assert!(a.work_item_id("my rig").starts_with("wi-patrol-my-rig-cargo-check-"));
+        assert_ne!(a.work_item_id("my rig"), a.work_item_id("other"));
+        assert!(a.work_item_title("gt").starts_with("[patrol gt/cargo-check] "));
+    }
+
+    #[test]
+    fn successful_line_check_has_no_findings() {
+        let out = vec!["All installed packages are compatible".to_string()];
+        assert!(extract_findings("uv-pip-check", FindingFormat::Lines, Some(0), &out).is_empty());
+    }
+}
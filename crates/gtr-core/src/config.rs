@@ -1,7 +1,8 @@
 use crate::types::AgentRuntime;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TownConfig {
@@ -12,6 +13,367 @@ pub struct TownConfig {
     pub temporal_address: String,
 }
 
+/// User-defined `rgt` command aliases, read from the `[aliases]` table of
+/// `town.toml`:
+///
+/// ```toml
+/// [aliases]
+/// s = "sling --target auto"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommandAliases {
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl CommandAliases {
+    /// Load from `~/.gtr/config/town.toml`; a missing or unreadable file means no aliases.
+    pub fn load() -> Self {
+        let path = crate::dirs::config_dir().join("town.toml");
+        load_config(&path).unwrap_or_default()
+    }
+
+    /// The arguments an alias stands for, split on whitespace.
+    pub fn expand(&self, name: &str) -> Option<Vec<String>> {
+        self.aliases
+            .get(name)
+            .map(|expansion| expansion.split_whitespace().map(String::from).collect())
+    }
+}
+
+/// Graceful shutdown settings, from the `[shutdown]` table of `town.toml`.
+/// Before an agent is killed by `rgt down` or `rig_stop` it is sent `message`
+/// and given `grace_secs` to write a handoff checkpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownConfig {
+    #[serde(default = "default_shutdown_message")]
+    pub message: String,
+    #[serde(default = "default_shutdown_grace_secs")]
+    pub grace_secs: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            message: default_shutdown_message(),
+            grace_secs: default_shutdown_grace_secs(),
+        }
+    }
+}
+
+impl ShutdownConfig {
+    /// Read the `[shutdown]` table of a town config, falling back to the defaults.
+    pub fn load_from(path: &Path) -> Self {
+        #[derive(Deserialize)]
+        struct Town {
+            #[serde(default)]
+            shutdown: ShutdownConfig,
+        }
+        load_config::<Town>(path)
+            .map(|town| town.shutdown)
+            .unwrap_or_default()
+    }
+
+    /// Load from `~/.gtr/config/town.toml`.
+    pub fn load() -> Self {
+        Self::load_from(&crate::dirs::config_dir().join("town.toml"))
+    }
+
+    pub fn grace(&self) -> Duration {
+        Duration::from_secs(self.grace_secs)
+    }
+}
+
+/// Which terminal multiplexer hosts agent sessions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SessionBackendKind {
+    /// A dedicated tmux server (`tmux -L gtr`)
+    #[cfg_attr(not(windows), default)]
+    Tmux,
+    /// GNU screen sessions
+    Screen,
+    /// A daemonized child on a plain PTY, for hosts with neither multiplexer.
+    /// Sessions can be followed but not attached to interactively.
+    Pty,
+    /// A console window per agent; the only backend on Windows
+    #[cfg_attr(windows, default)]
+    Console,
+}
+
+/// Agent session settings, from the `[session]` table of `town.toml`:
+///
+/// ```toml
+/// [session]
+/// backend = "screen"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionConfig {
+    #[serde(default)]
+    pub backend: SessionBackendKind,
+}
+
+impl SessionConfig {
+    /// Read the `[session]` table of a town config, falling back to the
+    /// platform default.
+    pub fn load_from(path: &Path) -> Self {
+        #[derive(Deserialize)]
+        struct Town {
+            #[serde(default)]
+            session: SessionConfig,
+        }
+        load_config::<Town>(path)
+            .map(|town| town.session)
+            .unwrap_or_default()
+    }
+
+    /// Load from `~/.gtr/config/town.toml`.
+    pub fn load() -> Self {
+        Self::load_from(&crate::dirs::config_dir().join("town.toml"))
+    }
+}
+
+/// Which environment variables spawned agents receive, from the `[env]` table
+/// of `town.toml`. Patterns are variable names with `*` wildcards:
+///
+/// ```toml
+/// [env]
+/// allow = ["PATH", "HOME", "LANG", "TERM", "ANTHROPIC_*"]
+/// deny = ["AWS_*", "*_TOKEN"]
+/// ```
+///
+/// With neither list set, agents inherit the worker's whole environment.
+/// `GTR_*` and `RGT_BIN`, which gtr sets itself, are always passed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnvPolicy {
+    /// Pass only variables matching one of these; empty allows everything
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Never pass variables matching these, even if allowed
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+impl EnvPolicy {
+    /// Read the `[env]` table of a town config; missing means no policy.
+    pub fn load_from(path: &Path) -> Self {
+        #[derive(Deserialize)]
+        struct Town {
+            #[serde(default)]
+            env: EnvPolicy,
+        }
+        load_config::<Town>(path)
+            .map(|town| town.env)
+            .unwrap_or_default()
+    }
+
+    /// Load from `~/.gtr/config/town.toml`.
+    pub fn load() -> Self {
+        Self::load_from(&crate::dirs::config_dir().join("town.toml"))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    pub fn permits(&self, name: &str) -> bool {
+        if name.starts_with("GTR_") || name == "RGT_BIN" {
+            return true;
+        }
+        let allowed =
+            self.allow.is_empty() || self.allow.iter().any(|p| env_pattern_matches(p, name));
+        allowed && !self.deny.iter().any(|p| env_pattern_matches(p, name))
+    }
+
+    /// The environment an agent gets: the permitted part of `inherited`,
+    /// overlaid with the permitted `extras`.
+    pub fn apply(
+        &self,
+        inherited: impl IntoIterator<Item = (String, String)>,
+        extras: &HashMap<String, String>,
+    ) -> HashMap<String, String> {
+        inherited
+            .into_iter()
+            .chain(extras.clone())
+            .filter(|(name, _)| self.permits(name))
+            .collect()
+    }
+}
+
+/// Where mail to the human operator is announced, from the `[human]` table of
+/// `town.toml`. Each entry is a notification channel, with its target after a
+/// colon:
+///
+/// ```toml
+/// [human]
+/// notify = ["desktop", "slack:https://hooks.slack.com/services/T0/B0/xyz"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HumanInboxConfig {
+    #[serde(default)]
+    pub notify: Vec<String>,
+}
+
+impl HumanInboxConfig {
+    /// Read the `[human]` table of a town config; missing means mail is only
+    /// stored.
+    pub fn load_from(path: &Path) -> Self {
+        #[derive(Deserialize)]
+        struct Town {
+            #[serde(default)]
+            human: HumanInboxConfig,
+        }
+        load_config::<Town>(path)
+            .map(|town| town.human)
+            .unwrap_or_default()
+    }
+
+    /// Load from `~/.gtr/config/town.toml`.
+    pub fn load() -> Self {
+        Self::load_from(&crate::dirs::config_dir().join("town.toml"))
+    }
+
+    /// The `(channel, target)` pairs to notify; the target is empty for
+    /// channels that don't take one.
+    pub fn channels(&self) -> Vec<(&str, &str)> {
+        self.notify
+            .iter()
+            .map(|entry| entry.split_once(':').unwrap_or((entry.as_str(), "")))
+            .collect()
+    }
+}
+
+/// Automatic dispatch by the mayor, from the `[dispatch_policy]` table of
+/// `town.toml`. While enabled, the mayor looks for pending work items every
+/// `interval_secs` and hands each to an idle registered agent, or spawns a
+/// polecat for it on one of `rigs` that has a free slot:
+///
+/// ```toml
+/// [dispatch_policy]
+/// enabled = true
+/// rigs = ["webapp"]
+///
+/// [dispatch_policy.labels]
+/// frontend = ["webapp", "webapp-crew-alice"]
+/// ```
+///
+/// An item with a label listed under `labels` only goes to the agents and
+/// rigs named for it; other items go to any idle agent or listed rig. An
+/// item's own `rig` narrows that to the rig and agents on it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DispatchPolicy {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_dispatch_interval_secs")]
+    pub interval_secs: u64,
+    /// Rigs the mayor may spawn polecats on, up to their `max_polecats`
+    #[serde(default)]
+    pub rigs: Vec<String>,
+    /// Agent runtime for the polecats it spawns
+    #[serde(default = "default_dispatch_runtime")]
+    pub runtime: String,
+    /// Agent IDs and rig names that take items with each label
+    #[serde(default)]
+    pub labels: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for DispatchPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: default_dispatch_interval_secs(),
+            rigs: Vec::new(),
+            runtime: default_dispatch_runtime(),
+            labels: BTreeMap::new(),
+        }
+    }
+}
+
+impl DispatchPolicy {
+    /// Read the `[dispatch_policy]` table of a town config; missing means the
+    /// mayor doesn't dispatch.
+    pub fn load_from(path: &Path) -> Self {
+        #[derive(Deserialize)]
+        struct Town {
+            #[serde(default)]
+            dispatch_policy: DispatchPolicy,
+        }
+        load_config::<Town>(path)
+            .map(|town| town.dispatch_policy)
+            .unwrap_or_default()
+    }
+
+    /// Load from `~/.gtr/config/town.toml`.
+    pub fn load() -> Self {
+        Self::load_from(&crate::dirs::config_dir().join("town.toml"))
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+
+    /// The agent IDs and rig names named for any of `labels`, in the order
+    /// listed; `None` if no label has targets, meaning any will do.
+    pub fn targets_for(&self, labels: &[String]) -> Option<Vec<&str>> {
+        let mut targets: Vec<&str> = Vec::new();
+        for label in labels {
+            for target in self.labels.get(label).into_iter().flatten() {
+                if !targets.contains(&target.as_str()) {
+                    targets.push(target);
+                }
+            }
+        }
+        (!targets.is_empty()).then_some(targets)
+    }
+
+    /// Every rig the policy can spawn polecats on: `rigs` and the targets
+    /// under `labels` that aren't agent IDs.
+    pub fn rig_targets(&self) -> Vec<String> {
+        let mut rigs = self.rigs.clone();
+        for target in self.labels.values().flatten() {
+            if crate::state::WorkflowId::parse(target).is_none() && !rigs.contains(target) {
+                rigs.push(target.clone());
+            }
+        }
+        rigs
+    }
+}
+
+/// Match a variable name against a pattern where `*` stands for any run of
+/// characters.
+fn env_pattern_matches(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(tail) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=tail.len())
+                .filter(|&i| tail.is_char_boundary(i))
+                .any(|i| env_pattern_matches(rest, &tail[i..]))
+        }
+    }
+}
+
+fn default_shutdown_message() -> String {
+    "Gas Town is shutting down and this session will be closed shortly. Stop what you are \
+     doing, commit or note any work in progress, then run `$RGT_BIN handoff \"<where you are \
+     and what is next>\"` so the next session can pick up from here."
+        .into()
+}
+
+fn default_shutdown_grace_secs() -> u64 {
+    60
+}
+
+fn default_dispatch_interval_secs() -> u64 {
+    60
+}
+
+fn default_dispatch_runtime() -> String {
+    "claude".into()
+}
+
 fn default_namespace() -> String {
     "default".into()
 }
@@ -25,6 +387,212 @@ pub struct RigEntry {
     pub name: String,
     pub path: PathBuf,
     pub git_url: Option<String>,
+    /// Gate refinery merges on GitHub checks instead of local tests
+    #[serde(default)]
+    pub github_checks: Option<GithubChecksConfig>,
+    #[serde(default)]
+    pub merge_strategy: MergeStrategy,
+    /// Commit message template for squash merges (see [`render_squash_message`])
+    #[serde(default)]
+    pub squash_message: Option<String>,
+    /// Branch the refinery merges into unless an entry names another target
+    #[serde(default = "default_branch")]
+    pub default_branch: String,
+    /// Named merge queues, each processed by its own refinery workflow
+    #[serde(default)]
+    pub queues: HashMap<String, MergeQueueConfig>,
+    /// Open a pull request instead of pushing to the target branch
+    #[serde(default)]
+    pub pull_request: Option<PullRequestConfig>,
+    /// CPU, memory and niceness caps for the rig's polecats
+    #[serde(default)]
+    pub polecat_limits: Option<ResourceLimits>,
+    /// Most polecats the rig runs at once; convoys hold back dispatch beyond it
+    #[serde(default)]
+    pub max_polecats: Option<usize>,
+    /// Model and permission settings by agent role (`polecat`, `witness`, ...)
+    #[serde(default)]
+    pub roles: HashMap<String, AgentSettings>,
+    /// SSH destination the rig's agents run on, e.g. `build-box-1`; `path`
+    /// must be the same checkout on that host
+    #[serde(default)]
+    pub host: Option<String>,
+}
+
+/// Resource caps for an agent process, applied through a systemd user scope
+/// (cgroups) where one is available and `ulimit`/`nice` otherwise.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// CPU quota as a percentage of one core (200 = two cores)
+    #[serde(default)]
+    pub cpu_percent: Option<u32>,
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+    /// Scheduling niceness, -20 (highest priority) to 19
+    #[serde(default)]
+    pub nice: Option<i32>,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        self.cpu_percent.is_none() && self.memory_mb.is_none() && self.nice.is_none()
+    }
+}
+
+/// Overrides for one named merge queue; unset fields fall back to the rig's.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MergeQueueConfig {
+    #[serde(default)]
+    pub github_checks: Option<GithubChecksConfig>,
+    #[serde(default)]
+    pub merge_strategy: Option<MergeStrategy>,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    /// Command run as the queue's local test step, e.g. `["mdbook", "build"]`
+    /// (default `cargo test`)
+    #[serde(default)]
+    pub test_command: Option<Vec<String>>,
+    #[serde(default)]
+    pub pull_request: Option<PullRequestConfig>,
+}
+
+pub const DEFAULT_BRANCH: &str = "main";
+
+fn default_branch() -> String {
+    DEFAULT_BRANCH.into()
+}
+
+/// Branch a (bare) clone's HEAD points at, e.g. `main` or `master`.
+/// `None` when HEAD is detached or the repo can't be read.
+pub fn detect_default_branch(git_dir: &Path) -> Option<String> {
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .filter(|b| !b.is_empty())
+        .map(str::to_string)
+}
+
+/// How the refinery lands a validated branch on its target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeStrategy {
+    /// Fast-forward when possible, merge commit otherwise
+    #[default]
+    Merge,
+    /// One commit containing the whole branch
+    Squash,
+    /// Fast-forward only; fails if the branch isn't rebased on the target
+    RebaseFf,
+}
+
+pub const DEFAULT_SQUASH_MESSAGE: &str = "{work_item}: {branch}\n\n{commits}";
+
+/// Fill a squash message template. Placeholders: `{work_item}`, `{branch}`,
+/// and `{commits}` (the squashed commit subjects as a bullet list).
+pub fn render_squash_message(
+    template: &str,
+    work_item_id: &str,
+    branch: &str,
+    subjects: &[String],
+) -> String {
+    let commits = subjects
+        .iter()
+        .map(|s| format!("* {s}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    template
+        .replace("{work_item}", work_item_id)
+        .replace("{branch}", branch)
+        .replace("{commits}", &commits)
+        .trim_end()
+        .to_string()
+}
+
+/// Refinery "required checks" mode: push the rebased branch and wait for
+/// GitHub commit statuses / check runs rather than running tests locally.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GithubChecksConfig {
+    /// Repository slug, e.g. `owner/repo`
+    pub repo: String,
+    /// Environment variable holding the API token
+    #[serde(default = "default_github_token_env")]
+    pub token_env: String,
+    /// Check names that must pass. Empty means every reported check.
+    #[serde(default)]
+    pub required: Vec<String>,
+    #[serde(default = "default_checks_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_checks_poll_secs")]
+    pub poll_interval_secs: u64,
+}
+
+fn default_github_token_env() -> String {
+    "GITHUB_TOKEN".into()
+}
+
+fn default_checks_timeout_secs() -> u64 {
+    60 * 60
+}
+
+fn default_checks_poll_secs() -> u64 {
+    30
+}
+
+/// Code host the refinery opens pull requests on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeProvider {
+    #[default]
+    Github,
+    Gitlab,
+}
+
+/// Refinery auto-PR mode, for repos that forbid direct pushes: push the
+/// validated branch, open a pull (merge) request against the target, and wait
+/// for it to be merged on the forge.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PullRequestConfig {
+    #[serde(default)]
+    pub provider: ForgeProvider,
+    /// `owner/repo` on GitHub, the project path on GitLab
+    pub repo: String,
+    /// Environment variable holding the API token (default `GITHUB_TOKEN` or
+    /// `GITLAB_TOKEN`)
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// API base for GitHub Enterprise or self-hosted GitLab
+    #[serde(default)]
+    pub api_url: Option<String>,
+    #[serde(default = "default_pr_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_pr_poll_secs")]
+    pub poll_interval_secs: u64,
+}
+
+impl PullRequestConfig {
+    pub fn token_env(&self) -> &str {
+        match (&self.token_env, self.provider) {
+            (Some(env), _) => env,
+            (None, ForgeProvider::Github) => "GITHUB_TOKEN",
+            (None, ForgeProvider::Gitlab) => "GITLAB_TOKEN",
+        }
+    }
+
+    pub fn api_url(&self) -> &str {
+        match (&self.api_url, self.provider) {
+            (Some(url), _) => url.trim_end_matches('/'),
+            (None, ForgeProvider::Github) => "https://api.github.com",
+            (None, ForgeProvider::Gitlab) => "https://gitlab.com/api/v4",
+        }
+    }
+}
+
+fn default_pr_timeout_secs() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_pr_poll_secs() -> u64 {
+    60
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,15 +633,29 @@ impl RigsConfig {
         self.save_to(&path)
     }
 
-    /// Add a rig entry. Idempotent — skips if name already exists.
+    /// Add a rig entry. Idempotent — skips if name already exists. The
+    /// default branch is taken from the rig's bare clone when present.
     pub fn add(&mut self, name: &str, git_url: &str) {
         if self.rigs.iter().any(|r| r.name == name) {
             return;
         }
+        let path = crate::dirs::rig_dir(name);
+        let default_branch =
+            detect_default_branch(&path.join(".repo.git")).unwrap_or_else(default_branch);
         self.rigs.push(RigEntry {
             name: name.to_string(),
-            path: crate::dirs::rig_dir(name),
+            path,
             git_url: Some(git_url.to_string()),
+            github_checks: None,
+            merge_strategy: MergeStrategy::default(),
+            squash_message: None,
+            default_branch,
+            queues: HashMap::new(),
+            pull_request: None,
+            polecat_limits: None,
+            max_polecats: None,
+            roles: HashMap::new(),
+            host: None,
         });
     }
 
@@ -99,10 +681,554 @@ pub struct AgentRuntimeOverride {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EscalationConfig {
+    #[serde(default)]
     pub routes: HashMap<String, Vec<String>>,
+    #[serde(default)]
     pub thresholds: EscalationThresholds,
 }
 
+impl EscalationConfig {
+    /// Load from ~/.gtr/config/escalation.toml, falling back to defaults when
+    /// the file is missing or invalid.
+    pub fn load() -> Self {
+        let path = crate::dirs::config_dir().join("escalation.toml");
+        load_config(&path).unwrap_or_else(|_| EscalationConfig {
+            routes: HashMap::new(),
+            thresholds: EscalationThresholds::default(),
+        })
+    }
+}
+
+/// Rules the mayor applies to mail it receives, from
+/// `~/.gtr/config/mail_routes.toml`. The first route with a keyword in the
+/// message decides what happens to it. Keywords match whole words,
+/// case-insensitively, and not right after a negation — "not approved"
+/// doesn't match `approved`. `approve_gate` routes must name the sender
+/// they trust with `from`; without it they never match:
+///
+/// ```toml
+/// [[route]]
+/// keywords = ["stuck", "conflict"]
+/// action = "work_item"
+///
+/// [[route]]
+/// keywords = ["approved", "lgtm"]
+/// action = "approve_gate"   # approves the `gate-...` ID the message names
+/// from = "gtr-witness"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MailRoutes {
+    #[serde(default, rename = "route")]
+    pub routes: Vec<MailRoute>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MailRoute {
+    pub keywords: Vec<String>,
+    pub action: MailRouteAction,
+    /// Only mail from this sender; required for `approve_gate`
+    #[serde(default)]
+    pub from: Option<String>,
+}
+
+/// Words that cancel a keyword right after them.
+const NEGATIONS: &[&str] = &["not", "no", "never", "don't", "isn't", "wasn't"];
+
+/// Lowercased words of `text`, split on anything but letters, digits and
+/// apostrophes.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether `keyword`'s words appear in `message` as whole words, not right
+/// after a negation.
+fn mentions(message: &[String], keyword: &str) -> bool {
+    let keyword = words(keyword);
+    if keyword.is_empty() {
+        return false;
+    }
+    message
+        .windows(keyword.len())
+        .enumerate()
+        .any(|(i, window)| {
+            window == keyword.as_slice()
+                && (i == 0 || !NEGATIONS.contains(&message[i - 1].as_str()))
+        })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MailRouteAction {
+    /// File the message as a new work item
+    WorkItem,
+    /// Approve the human gate the message mentions
+    ApproveGate,
+}
+
+impl MailRoutes {
+    /// Load from `~/.gtr/config/mail_routes.toml`; missing means no routing.
+    pub fn load() -> Self {
+        load_config(&crate::dirs::config_dir().join("mail_routes.toml")).unwrap_or_default()
+    }
+
+    /// The action for a message from `from`, if any route matches it.
+    pub fn route(&self, from: &str, message: &str) -> Option<MailRouteAction> {
+        let message = words(message);
+        self.routes
+            .iter()
+            .filter(|r| match r.from.as_deref() {
+                Some(f) => f == from,
+                None => r.action != MailRouteAction::ApproveGate,
+            })
+            .find(|r| r.keywords.iter().any(|k| mentions(&message, k)))
+            .map(|r| r.action)
+    }
+}
+
+/// How the mayor responds on its own to polecat reports and escalations, from
+/// `~/.gtr/config/mayor_policy.toml`. Rules are tried in order and the first
+/// that matches the event decides the response. A rule with `max_times` stops
+/// matching once it has been applied that many times to a work item, so the
+/// next rule takes over:
+///
+/// ```toml
+/// [[rule]]
+/// on = "polecat_report"
+/// status = "dead"
+/// exit_reason = "agent_died"
+/// action = "respawn"
+/// max_times = 1
+///
+/// [[rule]]
+/// on = "polecat_report"
+/// status = "dead"
+/// action = "human_gate"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MayorPolicy {
+    #[serde(default, rename = "rule")]
+    pub rules: Vec<PolicyRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub on: PolicyEvent,
+    /// Only polecat reports with this status
+    #[serde(default)]
+    pub status: Option<String>,
+    /// Only polecat reports with this exit reason
+    #[serde(default)]
+    pub exit_reason: Option<String>,
+    /// Only escalations at this level or higher
+    #[serde(default)]
+    pub min_level: Option<u32>,
+    pub action: PolicyAction,
+    /// Times the rule may be applied to one work item; unlimited if left out
+    #[serde(default)]
+    pub max_times: Option<u32>,
+    /// Runtime for a respawned polecat
+    #[serde(default)]
+    pub runtime: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyEvent {
+    /// A polecat finished, whatever the outcome
+    PolecatReport,
+    /// A work item escalated, or re-escalated, to the mayor
+    Escalation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    /// Sling the work item to a new polecat on the same rig
+    Respawn,
+    /// Park the work item on a human gate and mail the operator about it
+    HumanGate,
+    /// Mail the operator
+    Notify,
+}
+
+impl MayorPolicy {
+    /// Load from `~/.gtr/config/mayor_policy.toml`; missing means no rules.
+    pub fn load() -> Self {
+        load_config(&crate::dirs::config_dir().join("mayor_policy.toml")).unwrap_or_default()
+    }
+
+    /// The first rule matching an event, with its index. Escalations have no
+    /// exit reason and report their status; `applied` says how many times the
+    /// rule at an index has been applied to the event's work item.
+    pub fn rule_for(
+        &self,
+        on: PolicyEvent,
+        status: &str,
+        exit_reason: &str,
+        level: u32,
+        applied: impl Fn(usize) -> u32,
+    ) -> Option<(usize, &PolicyRule)> {
+        self.rules.iter().enumerate().find(|(i, rule)| {
+            rule.on == on
+                && rule.status.as_deref().is_none_or(|s| s == status)
+                && rule.exit_reason.as_deref().is_none_or(|r| r == exit_reason)
+                && rule.min_level.is_none_or(|min| level >= min)
+                && rule.max_times.is_none_or(|max| applied(*i) < max)
+        })
+    }
+}
+
+/// Operations that wait for approval through the mayor's approval queue,
+/// from the `[approvals]` table of `town.toml`:
+///
+/// ```toml
+/// [approvals]
+/// force_push = true
+/// protected_branches = ["main", "release/*"]
+/// rig_delete = true
+/// ```
+///
+/// A request nobody answers within `timeout_secs` counts as denied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalPolicy {
+    /// The refinery force-pushing a rebased branch
+    #[serde(default)]
+    pub force_push: bool,
+    /// Branches the refinery may only merge into once approved; a trailing
+    /// `*` matches any suffix
+    #[serde(default)]
+    pub protected_branches: Vec<String>,
+    /// Deleting a rig with `rgt rig remove`
+    #[serde(default)]
+    pub rig_delete: bool,
+    #[serde(default = "default_approval_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Default for ApprovalPolicy {
+    fn default() -> Self {
+        Self {
+            force_push: false,
+            protected_branches: Vec::new(),
+            rig_delete: false,
+            timeout_secs: default_approval_timeout_secs(),
+        }
+    }
+}
+
+impl ApprovalPolicy {
+    /// Read the `[approvals]` table of a town config; missing means nothing
+    /// needs approval.
+    pub fn load_from(path: &Path) -> Self {
+        #[derive(Deserialize)]
+        struct Town {
+            #[serde(default)]
+            approvals: ApprovalPolicy,
+        }
+        load_config::<Town>(path)
+            .map(|town| town.approvals)
+            .unwrap_or_default()
+    }
+
+    /// Load from `~/.gtr/config/town.toml`.
+    pub fn load() -> Self {
+        Self::load_from(&crate::dirs::config_dir().join("town.toml"))
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.max(1))
+    }
+
+    /// Whether merging into `branch` needs approval.
+    pub fn is_protected(&self, branch: &str) -> bool {
+        self.protected_branches
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => branch.starts_with(prefix),
+                None => branch == pattern,
+            })
+    }
+}
+
+fn default_approval_timeout_secs() -> u64 {
+    24 * 60 * 60
+}
+
+/// Standby for a headless town, from the `[deputy]` table of `town.toml`.
+/// The deputy checks the mayor agent every `interval_secs`; once it has been
+/// found dead `failover_after` times in a row — boot's respawns included —
+/// mail to the mayor is also routed to the deputy until the mayor is back:
+///
+/// ```toml
+/// [deputy]
+/// failover_after = 3
+/// spawn_agent = true
+/// ```
+///
+/// With `spawn_agent` a `deputy` agent session is started on `runtime` to
+/// read that mail while the mayor is down.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeputyConfig {
+    #[serde(default = "default_deputy_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_deputy_interval_secs")]
+    pub interval_secs: u64,
+    /// Consecutive failed checks before the deputy takes over
+    #[serde(default = "default_deputy_failover_after")]
+    pub failover_after: u32,
+    #[serde(default)]
+    pub spawn_agent: bool,
+    #[serde(default = "default_dispatch_runtime")]
+    pub runtime: String,
+}
+
+impl Default for DeputyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: default_deputy_interval_secs(),
+            failover_after: default_deputy_failover_after(),
+            spawn_agent: false,
+            runtime: default_dispatch_runtime(),
+        }
+    }
+}
+
+impl DeputyConfig {
+    /// Read the `[deputy]` table of a town config; missing means the defaults.
+    pub fn load_from(path: &Path) -> Self {
+        #[derive(Deserialize)]
+        struct Town {
+            #[serde(default)]
+            deputy: DeputyConfig,
+        }
+        load_config::<Town>(path)
+            .map(|town| town.deputy)
+            .unwrap_or_default()
+    }
+
+    /// Load from `~/.gtr/config/town.toml`.
+    pub fn load() -> Self {
+        Self::load_from(&crate::dirs::config_dir().join("town.toml"))
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+}
+
+fn default_deputy_enabled() -> bool {
+    true
+}
+
+fn default_deputy_interval_secs() -> u64 {
+    60
+}
+
+fn default_deputy_failover_after() -> u32 {
+    3
+}
+
+/// How to launch an agent runtime. `{session_id}`, `{prompt}`, `{model}`,
+/// `{permission_mode}` and `{allowed_tools}` (comma-separated) in the
+/// `*_args` templates are replaced at spawn time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeConfig {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Args for resuming a prior session; empty if the runtime can't resume
+    #[serde(default)]
+    pub resume_args: Vec<String>,
+    #[serde(default = "default_prompt_args")]
+    pub prompt_args: Vec<String>,
+    #[serde(default)]
+    pub prompt_position: PromptPosition,
+    #[serde(default)]
+    pub model_args: Vec<String>,
+    #[serde(default)]
+    pub permission_args: Vec<String>,
+    /// Used instead of `permission_args` when no permission mode is set
+    #[serde(default)]
+    pub default_permission_args: Vec<String>,
+    #[serde(default)]
+    pub allowed_tools_args: Vec<String>,
+}
+
+/// Where the prompt args go relative to the runtime's own args.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptPosition {
+    First,
+    #[default]
+    Last,
+    /// The runtime takes no prompt on its command line
+    Omit,
+}
+
+fn default_prompt_args() -> Vec<String> {
+    vec!["{prompt}".into()]
+}
+
+/// Per-launch values for a runtime's arg templates.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LaunchOptions<'a> {
+    pub prompt: Option<&'a str>,
+    pub resume_session_id: Option<&'a str>,
+    pub model: Option<&'a str>,
+    pub permission_mode: Option<&'a str>,
+    pub allowed_tools: &'a [String],
+}
+
+impl RuntimeConfig {
+    /// Full argument list for one launch.
+    pub fn command_args(&self, opts: LaunchOptions) -> Vec<String> {
+        let fill = |templates: &[String], key: &str, value: &str| {
+            templates
+                .iter()
+                .map(|a| a.replace(key, value))
+                .collect::<Vec<_>>()
+        };
+        let mut args = match opts.permission_mode {
+            Some(mode) => fill(&self.permission_args, "{permission_mode}", mode),
+            None => self.default_permission_args.clone(),
+        };
+        args.extend(self.args.iter().cloned());
+        if let Some(model) = opts.model {
+            args.extend(fill(&self.model_args, "{model}", model));
+        }
+        if !opts.allowed_tools.is_empty() {
+            let tools = opts.allowed_tools.join(",");
+            args.extend(fill(&self.allowed_tools_args, "{allowed_tools}", &tools));
+        }
+        if let Some(session_id) = opts.resume_session_id {
+            args.extend(fill(&self.resume_args, "{session_id}", session_id));
+        }
+        let prompt_args = opts
+            .prompt
+            .map(|prompt| fill(&self.prompt_args, "{prompt}", prompt));
+        match (prompt_args, self.prompt_position) {
+            (Some(prompt_args), PromptPosition::First) => {
+                prompt_args.into_iter().chain(args).collect()
+            }
+            (Some(prompt_args), PromptPosition::Last) => {
+                args.extend(prompt_args);
+                args
+            }
+            _ => args,
+        }
+    }
+}
+
+/// Model and permission overrides for one agent role on a rig.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentSettings {
+    #[serde(default)]
+    pub model: Option<String>,
+    /// e.g. `acceptEdits` or `plan` for Claude; unset keeps the runtime's
+    /// default (for Claude, skipping permission prompts)
+    #[serde(default)]
+    pub permission_mode: Option<String>,
+    #[serde(default)]
+    pub allowed_tools: Option<Vec<String>>,
+}
+
+/// Agent runtimes by name: the built-in `claude` and `shell`, plus entries
+/// from `~/.gtr/config/runtimes.toml`, which win on a name clash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimesConfig {
+    #[serde(default)]
+    pub runtimes: HashMap<String, RuntimeConfig>,
+}
+
+impl RuntimesConfig {
+    pub fn builtin() -> Self {
+        let claude = RuntimeConfig {
+            program: "claude".into(),
+            args: vec![
+                "--disable-slash-commands".into(),
+                "--disallowedTools=Skill,AskUserQuestion,EnterPlanMode".into(),
+            ],
+            env: HashMap::new(),
+            resume_args: vec!["--resume".into(), "{session_id}".into()],
+            prompt_args: default_prompt_args(),
+            prompt_position: PromptPosition::Last,
+            model_args: vec!["--model".into(), "{model}".into()],
+            permission_args: vec!["--permission-mode".into(), "{permission_mode}".into()],
+            default_permission_args: vec!["--dangerously-skip-permissions".into()],
+            allowed_tools_args: vec!["--allowedTools".into(), "{allowed_tools}".into()],
+        };
+        // Cheap, read-only profile for `rgt sling --triage`. Permission
+        // prompts aren't skipped, so tools outside its list never run.
+        let triage = RuntimeConfig {
+            args: [
+                claude.args.clone(),
+                vec![
+                    "--model".into(),
+                    "haiku".into(),
+                    "--allowedTools".into(),
+                    "Read,Glob,Grep,Bash".into(),
+                ],
+            ]
+            .concat(),
+            model_args: vec![],
+            default_permission_args: vec!["--permission-mode".into(), "default".into()],
+            allowed_tools_args: vec![],
+            ..claude.clone()
+        };
+        let shell = RuntimeConfig {
+            program: "sh".into(),
+            args: vec![],
+            env: HashMap::new(),
+            resume_args: vec![],
+            prompt_args: vec!["-c".into(), "{prompt}".into()],
+            prompt_position: PromptPosition::Last,
+            model_args: vec![],
+            permission_args: vec![],
+            default_permission_args: vec![],
+            allowed_tools_args: vec![],
+        };
+        Self {
+            runtimes: HashMap::from([
+                ("claude".into(), claude),
+                ("shell".into(), shell),
+                ("triage".into(), triage),
+            ]),
+        }
+    }
+
+    /// Built-ins merged with the file at `path`, if it exists.
+    pub fn load_from(path: &Path) -> anyhow::Result<Self> {
+        let mut config = Self::builtin();
+        if path.exists() {
+            let user: RuntimesConfig = load_config(path)?;
+            config.runtimes.extend(user.runtimes);
+        }
+        Ok(config)
+    }
+
+    /// Load from the default location (~/.gtr/config/runtimes.toml).
+    pub fn load() -> anyhow::Result<Self> {
+        Self::load_from(&crate::dirs::config_dir().join("runtimes.toml"))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RuntimeConfig> {
+        self.runtimes.get(name)
+    }
+
+    /// Registered runtime names, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<_> = self.runtimes.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EscalationThresholds {
     pub stale_after: String,
@@ -110,10 +1236,43 @@ pub struct EscalationThresholds {
     pub max_re_escalations: u32,
 }
 
+impl Default for EscalationThresholds {
+    fn default() -> Self {
+        Self {
+            stale_after: "4h".into(),
+            max_re_escalations: default_max_re_escalations(),
+        }
+    }
+}
+
+impl EscalationThresholds {
+    /// `stale_after` as a duration (`90s`, `30m`, `4h`, `1d`). Unparseable
+    /// values fall back to four hours.
+    pub fn stale_after_duration(&self) -> Duration {
+        parse_duration(&self.stale_after).unwrap_or(Duration::from_secs(4 * 60 * 60))
+    }
+}
+
 fn default_max_re_escalations() -> u32 {
     2
 }
 
+/// Parse a short duration like `45s`, `30m`, `4h` or `1d`.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit())?;
+    let (num, unit) = s.split_at(split);
+    let n: u64 = num.parse().ok()?;
+    let secs = match unit {
+        "s" => n,
+        "m" => n * 60,
+        "h" => n * 60 * 60,
+        "d" => n * 24 * 60 * 60,
+        _ => return None,
+    };
+    Some(Duration::from_secs(secs))
+}
+
 /// Resolve the town root directory. Walks up from `start` looking for `.gtr/config.toml`.
 pub fn find_town_root(start: &Path) -> Option<PathBuf> {
     let mut current = start.to_path_buf();
@@ -160,6 +1319,103 @@ temporal_address = "http://localhost:7233"
         assert_eq!(config.temporal_address, "http://localhost:7233");
     }
 
+    #[test]
+    fn command_aliases_from_town_toml() {
+        let toml_str = r#"
+[town]
+name = "gas-town"
+
+[aliases]
+s = "sling --target auto"
+inbox = "mail inbox"
+"#;
+        let aliases: CommandAliases = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            aliases.expand("s").unwrap(),
+            vec!["sling", "--target", "auto"]
+        );
+        assert_eq!(aliases.expand("inbox").unwrap(), vec!["mail", "inbox"]);
+        assert!(aliases.expand("sling").is_none());
+
+        let none: CommandAliases = toml::from_str(r#"name = "my-town""#).unwrap();
+        assert!(none.aliases.is_empty());
+    }
+
+    #[test]
+    fn shutdown_config_defaults_and_overrides() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("town.toml");
+
+        let missing = ShutdownConfig::load_from(&path);
+        assert_eq!(missing.grace(), Duration::from_secs(60));
+        assert!(missing.message.contains("handoff"));
+
+        fs::write(
+            &path,
+            "[town]\nname = \"gas-town\"\n\n[shutdown]\ngrace_secs = 120\n",
+        )
+        .unwrap();
+        let config = ShutdownConfig::load_from(&path);
+        assert_eq!(config.grace_secs, 120);
+        assert_eq!(config.message, missing.message);
+    }
+
+    #[test]
+    fn env_policy_allows_and_denies() {
+        let policy: EnvPolicy = toml::from_str(
+            r#"
+allow = ["PATH", "HOME", "ANTHROPIC_*"]
+deny = ["*_TOKEN", "AWS_*"]
+"#,
+        )
+        .unwrap();
+        assert!(policy.permits("PATH"));
+        assert!(policy.permits("ANTHROPIC_MODEL"));
+        assert!(!policy.permits("ANTHROPIC_AUTH_TOKEN"));
+        assert!(!policy.permits("AWS_SECRET_ACCESS_KEY"));
+        assert!(!policy.permits("EDITOR"));
+        // gtr's own variables always get through
+        assert!(policy.permits("GTR_AGENT"));
+        assert!(policy.permits("RGT_BIN"));
+
+        let inherited = vec![
+            ("PATH".to_string(), "/usr/bin".to_string()),
+            ("AWS_PROFILE".to_string(), "prod".to_string()),
+        ];
+        let extras = HashMap::from([
+            ("PATH".to_string(), "/opt/gtr:/usr/bin".to_string()),
+            ("GTR_ROLE".to_string(), "polecat".to_string()),
+        ]);
+        let env = policy.apply(inherited, &extras);
+        assert_eq!(env.len(), 2);
+        assert_eq!(env["PATH"], "/opt/gtr:/usr/bin");
+        assert_eq!(env["GTR_ROLE"], "polecat");
+
+        let deny_only = EnvPolicy {
+            deny: vec!["AWS_*".into()],
+            ..Default::default()
+        };
+        assert!(deny_only.permits("EDITOR"));
+        assert!(!deny_only.permits("AWS_PROFILE"));
+        assert!(EnvPolicy::default().is_empty());
+    }
+
+    #[test]
+    fn session_backend_from_town_config() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("town.toml");
+        assert_eq!(
+            SessionConfig::load_from(&path).backend,
+            SessionBackendKind::Tmux
+        );
+
+        fs::write(&path, "[session]\nbackend = \"screen\"\n").unwrap();
+        assert_eq!(
+            SessionConfig::load_from(&path).backend,
+            SessionBackendKind::Screen
+        );
+    }
+
     #[test]
     fn parse_escalation_config() {
         let toml_str = r#"
@@ -176,6 +1432,19 @@ max_re_escalations = 2
         let config: EscalationConfig = toml::from_str(toml_str).unwrap();
         assert_eq!(config.routes["critical"].len(), 3);
         assert_eq!(config.thresholds.stale_after, "4h");
+        assert_eq!(
+            config.thresholds.stale_after_duration(),
+            Duration::from_secs(4 * 60 * 60)
+        );
+    }
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(parse_duration("90s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Some(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("1d"), Some(Duration::from_secs(86400)));
+        assert_eq!(parse_duration("4"), None);
+        assert_eq!(parse_duration("soon"), None);
     }
 
     #[test]
@@ -223,6 +1492,419 @@ max_re_escalations = 2
         );
     }
 
+    #[test]
+    fn rigs_config_github_checks() {
+        let toml_str = r#"
+[[rigs]]
+name = "app"
+path = "/srv/app"
+
+[rigs.github_checks]
+repo = "acme/app"
+required = ["ci / test", "lint"]
+"#;
+        let config: RigsConfig = toml::from_str(toml_str).unwrap();
+        let checks = config.rigs[0].github_checks.as_ref().unwrap();
+        assert_eq!(checks.repo, "acme/app");
+        assert_eq!(checks.token_env, "GITHUB_TOKEN");
+        assert_eq!(checks.required.len(), 2);
+        assert_eq!(checks.poll_interval_secs, 30);
+        assert_eq!(config.rigs[0].merge_strategy, MergeStrategy::Merge);
+        assert_eq!(config.rigs[0].default_branch, "main");
+    }
+
+    #[test]
+    fn rigs_config_named_queues() {
+        let toml_str = r#"
+[[rigs]]
+name = "app"
+path = "/srv/app"
+merge_strategy = "squash"
+
+[rigs.queues.docs]
+test_command = ["mdbook", "build"]
+
+[rigs.queues.hotfix]
+default_branch = "release/1.4"
+merge_strategy = "rebase-ff"
+"#;
+        let config: RigsConfig = toml::from_str(toml_str).unwrap();
+        let queues = &config.rigs[0].queues;
+        assert_eq!(queues.len(), 2);
+        assert_eq!(
+            queues["docs"].test_command.as_deref(),
+            Some(&["mdbook".to_string(), "build".to_string()][..])
+        );
+        assert_eq!(
+            queues["hotfix"].merge_strategy,
+            Some(MergeStrategy::RebaseFf)
+        );
+        assert_eq!(
+            queues["hotfix"].default_branch.as_deref(),
+            Some("release/1.4")
+        );
+    }
+
+    #[test]
+    fn rigs_config_pull_request_mode() {
+        let toml_str = r#"
+[[rigs]]
+name = "app"
+path = "/srv/app"
+
+[rigs.pull_request]
+repo = "acme/app"
+
+[rigs.queues.internal.pull_request]
+provider = "gitlab"
+repo = "acme/platform/app"
+api_url = "https://git.acme.dev/api/v4/"
+"#;
+        let config: RigsConfig = toml::from_str(toml_str).unwrap();
+        let rig = &config.rigs[0];
+        let pr = rig.pull_request.as_ref().unwrap();
+        assert_eq!(pr.provider, ForgeProvider::Github);
+        assert_eq!(pr.token_env(), "GITHUB_TOKEN");
+        assert_eq!(pr.api_url(), "https://api.github.com");
+        assert_eq!(pr.timeout_secs, 24 * 60 * 60);
+
+        let gitlab = rig.queues["internal"].pull_request.as_ref().unwrap();
+        assert_eq!(gitlab.token_env(), "GITLAB_TOKEN");
+        assert_eq!(gitlab.api_url(), "https://git.acme.dev/api/v4");
+    }
+
+    #[test]
+    fn rigs_config_polecat_limits() {
+        let toml_str = r#"
+[[rigs]]
+name = "app"
+path = "/tmp/app"
+
+[rigs.polecat_limits]
+cpu_percent = 200
+memory_mb = 4096
+"#;
+        let config: RigsConfig = toml::from_str(toml_str).unwrap();
+        let limits = config.rigs[0].polecat_limits.clone().unwrap();
+        assert_eq!(limits.cpu_percent, Some(200));
+        assert_eq!(limits.memory_mb, Some(4096));
+        assert_eq!(limits.nice, None);
+        assert!(!limits.is_empty());
+        assert!(ResourceLimits::default().is_empty());
+    }
+
+    #[test]
+    fn runtimes_config_merges_builtins() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("runtimes.toml");
+        let builtin = RuntimesConfig::load_from(&path).unwrap();
+        assert_eq!(builtin.names(), vec!["claude", "shell", "triage"]);
+
+        fs::write(
+            &path,
+            r#"
+[runtimes.aider]
+program = "aider"
+args = ["--yes-always"]
+env = { AIDER_AUTO_COMMITS = "false" }
+prompt_args = ["--message", "{prompt}"]
+
+[runtimes.shell]
+program = "bash"
+prompt_position = "first"
+prompt_args = ["-c", "{prompt}"]
+"#,
+        )
+        .unwrap();
+        let config = RuntimesConfig::load_from(&path).unwrap();
+        assert_eq!(config.names(), vec!["aider", "claude", "shell", "triage"]);
+
+        let aider = config.get("aider").unwrap();
+        assert_eq!(aider.env["AIDER_AUTO_COMMITS"], "false");
+        let launch = LaunchOptions {
+            prompt: Some("fix it"),
+            resume_session_id: Some("s-1"),
+            ..Default::default()
+        };
+        assert_eq!(
+            aider.command_args(launch),
+            vec!["--yes-always", "--message", "fix it"]
+        );
+        assert_eq!(config.get("shell").unwrap().program, "bash");
+
+        let claude = config.get("claude").unwrap();
+        let args = claude.command_args(LaunchOptions {
+            prompt: Some("go"),
+            ..launch
+        });
+        assert_eq!(args[0], "--dangerously-skip-permissions");
+        assert_eq!(&args[3..], ["--resume", "s-1", "go"]);
+
+        let triage = config.get("triage").unwrap();
+        let args = triage.command_args(LaunchOptions::default());
+        assert_eq!(&args[..2], ["--permission-mode", "default"]);
+        assert!(!args.contains(&"--dangerously-skip-permissions".to_string()));
+    }
+
+    #[test]
+    fn claude_model_and_permission_args() {
+        let toml_str = r#"
+[[rigs]]
+name = "app"
+path = "/tmp/app"
+
+[rigs.roles.witness]
+model = "haiku"
+
+[rigs.roles.polecat]
+model = "opus"
+permission_mode = "acceptEdits"
+allowed_tools = ["Read", "Edit", "Bash(cargo test:*)"]
+"#;
+        let config: RigsConfig = toml::from_str(toml_str).unwrap();
+        let polecat = &config.rigs[0].roles["polecat"];
+        assert_eq!(
+            config.rigs[0].roles["witness"].model.as_deref(),
+            Some("haiku")
+        );
+
+        let runtimes = RuntimesConfig::builtin();
+        let tools = polecat.allowed_tools.clone().unwrap();
+        let args = runtimes.get("claude").unwrap().command_args(LaunchOptions {
+            model: polecat.model.as_deref(),
+            permission_mode: polecat.permission_mode.as_deref(),
+            allowed_tools: &tools,
+            ..Default::default()
+        });
+        assert_eq!(&args[..2], ["--permission-mode", "acceptEdits"]);
+        assert!(!args.contains(&"--dangerously-skip-permissions".to_string()));
+        assert_eq!(
+            &args[4..],
+            [
+                "--model",
+                "opus",
+                "--allowedTools",
+                "Read,Edit,Bash(cargo test:*)"
+            ]
+        );
+    }
+
+    #[test]
+    fn detect_default_branch_from_head() {
+        let dir = tempdir().unwrap();
+        assert_eq!(detect_default_branch(dir.path()), None);
+        std::fs::write(dir.path().join("HEAD"), "ref: refs/heads/trunk\n").unwrap();
+        assert_eq!(detect_default_branch(dir.path()), Some("trunk".into()));
+        std::fs::write(
+            dir.path().join("HEAD"),
+            "4b825dc642cb6eb9a060e54bf8d69288fbee4904\n",
+        )
+        .unwrap();
+        assert_eq!(detect_default_branch(dir.path()), None);
+    }
+
+    #[test]
+    fn rigs_config_squash_strategy() {
+        let toml_str = r#"
+[[rigs]]
+name = "app"
+path = "/srv/app"
+merge_strategy = "squash"
+"#;
+        let config: RigsConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.rigs[0].merge_strategy, MergeStrategy::Squash);
+
+        let msg = render_squash_message(
+            DEFAULT_SQUASH_MESSAGE,
+            "wi-1",
+            "feat/login",
+            &["Add form".to_string(), "Fix typo".to_string()],
+        );
+        assert_eq!(msg, "wi-1: feat/login\n\n* Add form\n* Fix typo");
+    }
+
+    #[test]
+    fn human_inbox_channels() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("town.toml");
+        assert!(HumanInboxConfig::load_from(&path).channels().is_empty());
+
+        fs::write(
+            &path,
+            "[human]\nnotify = [\"desktop\", \"slack:https://hooks.slack.com/x\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            HumanInboxConfig::load_from(&path).channels(),
+            vec![("desktop", ""), ("slack", "https://hooks.slack.com/x")]
+        );
+    }
+
+    #[test]
+    fn approval_policy_matches_protected_branches() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("town.toml");
+        let missing = ApprovalPolicy::load_from(&path);
+        assert!(!missing.force_push);
+        assert!(!missing.rig_delete);
+        assert!(!missing.is_protected("main"));
+
+        fs::write(
+            &path,
+            "[approvals]\nforce_push = true\nprotected_branches = [\"main\", \"release/*\"]\nrig_delete = true\n",
+        )
+        .unwrap();
+        let policy = ApprovalPolicy::load_from(&path);
+        assert!(policy.force_push);
+        assert!(policy.rig_delete);
+        assert_eq!(policy.timeout(), Duration::from_secs(86400));
+        assert!(policy.is_protected("main"));
+        assert!(policy.is_protected("release/1.2"));
+        assert!(!policy.is_protected("maintenance"));
+        assert!(!policy.is_protected("polecat/nux"));
+    }
+
+    #[test]
+    fn dispatch_policy_targets_by_label() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("town.toml");
+        let missing = DispatchPolicy::load_from(&path);
+        assert!(!missing.enabled);
+        assert_eq!(missing.interval(), Duration::from_secs(60));
+
+        fs::write(
+            &path,
+            r#"
+[dispatch_policy]
+enabled = true
+rigs = ["webapp", "api"]
+
+[dispatch_policy.labels]
+frontend = ["webapp", "webapp-crew-alice"]
+css = ["webapp"]
+"#,
+        )
+        .unwrap();
+        let policy = DispatchPolicy::load_from(&path);
+        assert!(policy.enabled);
+        assert_eq!(policy.runtime, "claude");
+        assert_eq!(
+            policy.targets_for(&["css".into(), "frontend".into()]),
+            Some(vec!["webapp", "webapp-crew-alice"])
+        );
+        assert_eq!(policy.targets_for(&["backend".into()]), None);
+        assert_eq!(policy.rig_targets(), vec!["webapp", "api"]);
+    }
+
+    #[test]
+    fn mayor_policy_falls_through_once_a_rule_is_used_up() {
+        let policy: MayorPolicy = toml::from_str(
+            r#"
+[[rule]]
+on = "polecat_report"
+status = "dead"
+exit_reason = "agent_died"
+action = "respawn"
+max_times = 1
+
+[[rule]]
+on = "polecat_report"
+status = "dead"
+action = "human_gate"
+
+[[rule]]
+on = "escalation"
+min_level = 2
+action = "notify"
+"#,
+        )
+        .unwrap();
+        let dead = |applied: u32| {
+            policy
+                .rule_for(PolicyEvent::PolecatReport, "dead", "agent_died", 0, |_| {
+                    applied
+                })
+                .map(|(i, rule)| (i, rule.action))
+        };
+        assert_eq!(dead(0), Some((0, PolicyAction::Respawn)));
+        assert_eq!(dead(1), Some((1, PolicyAction::HumanGate)));
+        assert!(policy
+            .rule_for(PolicyEvent::PolecatReport, "done", "completed", 0, |_| 0)
+            .is_none());
+        assert!(policy
+            .rule_for(PolicyEvent::Escalation, "open", "", 1, |_| 0)
+            .is_none());
+        assert_eq!(
+            policy
+                .rule_for(PolicyEvent::Escalation, "open", "", 2, |_| 0)
+                .map(|(_, rule)| rule.action),
+            Some(PolicyAction::Notify)
+        );
+    }
+
+    #[test]
+    fn mail_routes_first_match_wins() {
+        let routes: MailRoutes = toml::from_str(
+            r#"
+[[route]]
+keywords = ["Stuck", "conflict"]
+action = "work_item"
+
+[[route]]
+keywords = ["lgtm"]
+action = "approve_gate"
+from = "gtr-witness"
+
+[[route]]
+keywords = ["stuck on review"]
+action = "approve_gate"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            routes.route("gtr-polecat-nux", "I'm STUCK on review"),
+            Some(MailRouteAction::WorkItem)
+        );
+        assert_eq!(
+            routes.route("gtr-witness", "gate-h-1 lgtm"),
+            Some(MailRouteAction::ApproveGate)
+        );
+        assert_eq!(routes.route("gtr-polecat-nux", "gate-h-1 lgtm"), None);
+        assert_eq!(MailRoutes::default().route("x", "stuck"), None);
+    }
+
+    #[test]
+    fn mail_routes_match_whole_words_and_trusted_senders() {
+        let routes: MailRoutes = toml::from_str(
+            r#"
+[[route]]
+keywords = ["approved"]
+action = "approve_gate"
+
+[[route]]
+keywords = ["approved", "looks good"]
+action = "approve_gate"
+from = "gtr-witness"
+"#,
+        )
+        .unwrap();
+        // The route without `from` never approves
+        assert_eq!(routes.route("gtr-polecat-nux", "gate-h-1 approved"), None);
+        assert_eq!(
+            routes.route("gtr-witness", "gate-h-1: Approved."),
+            Some(MailRouteAction::ApproveGate)
+        );
+        assert_eq!(
+            routes.route("gtr-witness", "gate-h-1 looks  good"),
+            Some(MailRouteAction::ApproveGate)
+        );
+        assert_eq!(
+            routes.route("gtr-witness", "gate-h-1 is not approved"),
+            None
+        );
+        assert_eq!(routes.route("gtr-witness", "gate-h-1 unapproved"), None);
+    }
+
     #[test]
     fn rigs_config_add_idempotent() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,176 @@
+use chrono::{DateTime, Datelike, Duration, DurationRound, Timelike, Utc};
+
+/// Longest stretch [`Schedule::fires_between`] looks through, in minutes.
+const MAX_SCAN_MINUTES: i64 = 7 * 24 * 60;
+
+/// A five-field cron expression — minute, hour, day of month, month, day of
+/// week — or one of the `@hourly`-style descriptors, evaluated in UTC.
+///
+/// Fields take numbers, `*`, lists (`1,15`), ranges (`9-17`) and steps
+/// (`*/5`, `10-50/20`). Sunday is `0` or `7`. As in cron, when both the day
+/// of month and day of week are restricted a day matching either fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl Schedule {
+    pub fn parse(expr: &str) -> anyhow::Result<Self> {
+        let expanded = match expr.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            anyhow::bail!(
+                "schedule '{expr}' should have 5 fields, not {}",
+                fields.len()
+            );
+        };
+        let field = |value: &str, min: u32, max: u32| {
+            parse_field(value, min, max)
+                .map_err(|e| anyhow::anyhow!("schedule '{expr}' has an invalid field {value}: {e}"))
+        };
+        let mut weekdays = field(weekday, 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Self {
+            minutes: field(minute, 0, 59)?,
+            hours: field(hour, 0, 23)?,
+            days: field(day, 1, 31)?,
+            months: field(month, 1, 12)?,
+            weekdays,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+
+    /// Whether the schedule fires in the minute `at` falls in.
+    pub fn matches(&self, at: DateTime<Utc>) -> bool {
+        let bit = |mask: u64, value: u32| mask & (1 << value) != 0;
+        let day = bit(self.days, at.day());
+        let weekday = bit(self.weekdays, at.weekday().num_days_from_sunday());
+        let day_matches = if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        };
+        bit(self.minutes, at.minute())
+            && bit(self.hours, at.hour())
+            && bit(self.months, at.month())
+            && day_matches
+    }
+
+    /// Whether the schedule fires in any minute after `since`, up to and
+    /// including the minute of `until`. Only the last week is looked at.
+    pub fn fires_between(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> bool {
+        let Ok(until) = until.duration_trunc(Duration::minutes(1)) else {
+            return false;
+        };
+        let Ok(mut minute) = since.duration_trunc(Duration::minutes(1)) else {
+            return false;
+        };
+        minute = minute.max(until - Duration::minutes(MAX_SCAN_MINUTES));
+        while minute < until {
+            minute += Duration::minutes(1);
+            if self.matches(minute) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Bitmask of the values a field allows.
+fn parse_field(field: &str, min: u32, max: u32) -> anyhow::Result<u64> {
+    let mut mask = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, Some(step.parse::<u32>()?)),
+            None => (part, None),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((lo, hi)) = range.split_once('-') {
+            (lo.parse()?, hi.parse()?)
+        } else {
+            let value: u32 = range.parse()?;
+            (value, if step.is_some() { max } else { value })
+        };
+        if lo < min || hi > max || lo > hi {
+            anyhow::bail!("{part} is outside {min}-{max}");
+        }
+        let step = step.unwrap_or(1);
+        if step == 0 {
+            anyhow::bail!("step can't be 0");
+        }
+        for value in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().into()
+    }
+
+    #[test]
+    fn parse_rejects_bad_expressions() {
+        Schedule::parse("*/15 9-17 * * 1-5").unwrap();
+        Schedule::parse("0 0 1,15 * 7").unwrap();
+        assert_eq!(
+            Schedule::parse("@daily").unwrap(),
+            Schedule::parse("0 0 * * *").unwrap()
+        );
+        for bad in [
+            "0 3 * *",
+            "60 * * * *",
+            "0 3 * * mon",
+            "*/0 * * * *",
+            "5-1 * * * *",
+        ] {
+            assert!(Schedule::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn matches_fields_and_either_day() {
+        let nightly = Schedule::parse("0 3 * * *").unwrap();
+        assert!(nightly.matches(at("2026-10-16T03:00:59Z")));
+        assert!(!nightly.matches(at("2026-10-16T03:01:00Z")));
+
+        // 2026-10-16 is a Friday
+        let weekdays = Schedule::parse("*/20 9-17 * * 1-5").unwrap();
+        assert!(weekdays.matches(at("2026-10-16T09:40:00Z")));
+        assert!(!weekdays.matches(at("2026-10-17T09:40:00Z")));
+
+        let first_or_friday = Schedule::parse("0 12 1 * 5").unwrap();
+        assert!(first_or_friday.matches(at("2026-10-16T12:00:00Z")));
+        assert!(first_or_friday.matches(at("2026-11-01T12:00:00Z")));
+        assert!(!first_or_friday.matches(at("2026-10-15T12:00:00Z")));
+    }
+
+    #[test]
+    fn fires_between_looks_at_each_minute_since() {
+        let schedule = Schedule::parse("*/5 * * * *").unwrap();
+        assert!(schedule.fires_between(at("2026-10-16T10:04:30Z"), at("2026-10-16T10:05:10Z")));
+        assert!(!schedule.fires_between(at("2026-10-16T10:05:10Z"), at("2026-10-16T10:06:10Z")));
+        assert!(schedule.fires_between(at("2026-10-16T09:00:00Z"), at("2026-10-16T10:04:00Z")));
+    }
+}
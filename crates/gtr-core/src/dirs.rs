@@ -1,8 +1,10 @@
 use std::path::PathBuf;
 
-/// Root GTR directory (~/.gtr)
+/// Root GTR directory (~/.gtr, or %USERPROFILE%\.gtr on Windows)
 pub fn gtr_root() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| "/tmp".into());
     PathBuf::from(home).join(".gtr")
 }
 
@@ -41,11 +43,85 @@ pub fn refinery_dir(rig: &str) -> PathBuf {
     rig_dir(rig).join("refinery")
 }
 
+/// Rig-specific plugin definitions (e.g. post-merge hooks)
+pub fn rig_plugin_dir(rig: &str) -> PathBuf {
+    rig_dir(rig).join("plugins")
+}
+
 /// Config directory
 pub fn config_dir() -> PathBuf {
     gtr_root().join("config")
 }
 
+/// Plugin definitions patrol runs on every rig
+pub fn plugins_dir() -> PathBuf {
+    config_dir().join("plugins")
+}
+
+/// Formula definitions that can be referred to by name, `<name>.toml`
+pub fn formulas_dir() -> PathBuf {
+    gtr_root().join("formulas")
+}
+
+/// Markdown summaries written when a convoy closes, `convoy-<id>.md`, and
+/// the mayor's daily digests, `daily/<date>.md`
+pub fn reports_dir() -> PathBuf {
+    gtr_root().join("reports")
+}
+
+/// Outbox for signals queued while Temporal was unreachable
+pub fn outbox_dir() -> PathBuf {
+    gtr_root().join("outbox")
+}
+
+/// Persistent agent mailboxes
+pub fn mail_dir() -> PathBuf {
+    gtr_root().join("mail")
+}
+
+/// Mail delivered to one agent, see [`crate::mail::Mailbox`]
+pub fn mailbox_path(agent_id: &str) -> PathBuf {
+    mail_dir().join(format!("{agent_id}.jsonl"))
+}
+
+/// Files sent with mail, one directory per message
+pub fn attachments_dir() -> PathBuf {
+    mail_dir().join("attachments")
+}
+
+/// Mail that couldn't be delivered because its recipient wasn't running,
+/// kept in the outbox format until the recipient registers with the mayor
+pub fn dead_letter_dir() -> PathBuf {
+    mail_dir().join("dead-letter")
+}
+
+/// Merge enqueues from `rgt done --draft`, held until `rgt mq approve`
+pub fn drafts_dir() -> PathBuf {
+    gtr_root().join("drafts")
+}
+
+/// Polecat reports the mayor has received, one JSON object per line.
+pub fn polecat_reports_path() -> PathBuf {
+    runtime_dir().join("polecat_reports.jsonl")
+}
+
+/// Recent runs of a patrol plugin, one JSON object per line.
+pub fn plugin_history_path(name: &str) -> PathBuf {
+    runtime_dir().join("plugins").join(format!("{name}.jsonl"))
+}
+
+/// Append-only log of entries a refinery has finished. Refineries started
+/// without a rig name log to `default`; named queues get their own file.
+pub fn refinery_history_path(rig: &str, queue: &str) -> PathBuf {
+    let name = if rig.is_empty() { "default" } else { rig };
+    let file = if queue.is_empty() {
+        format!("{name}.jsonl")
+    } else {
+        format!("{name}.{queue}.jsonl")
+    };
+    runtime_dir().join("refinery").join(file)
+}
+
 /// Ensure all directories for a rig exist
 pub fn ensure_rig_dirs(rig: &str) -> std::io::Result<()> {
     std::fs::create_dir_all(rig_dir(rig).join("polecats"))?;
@@ -3,23 +3,182 @@ use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 
+/// Steps a molecule runs at once when the formula doesn't say.
+pub const DEFAULT_MAX_PARALLEL: usize = 4;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FormulaDef {
     pub name: String,
     pub description: Option<String>,
     #[serde(default)]
-    pub vars: Vec<String>,
+    pub vars: Vec<FormulaVar>,
+    /// Independent steps a molecule may run at once
+    #[serde(default)]
+    pub max_parallel: Option<usize>,
+    /// Cron expression (`0 3 * * *`) or descriptor (`@daily`) to run the
+    /// formula on once enabled with `rgt formula schedule enable`
+    #[serde(default)]
+    pub schedule: Option<String>,
     pub steps: Vec<FormulaStep>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A var a formula declares: a bare name, `"env"`, or a table like
+/// `{ name = "env", allowed = ["dev", "prod"], default = "dev" }`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "VarDecl")]
+pub struct FormulaVar {
+    pub name: String,
+    /// Values the var may take; any value if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed: Vec<String>,
+    /// Value used when none is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// Shown when asking for the var
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl FormulaVar {
+    /// Whether the var may take `value`.
+    pub fn accepts(&self, value: &str) -> bool {
+        self.allowed.is_empty() || self.allowed.iter().any(|a| a == value)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum VarDecl {
+    Name(String),
+    Table {
+        name: String,
+        #[serde(default)]
+        allowed: Vec<String>,
+        #[serde(default)]
+        default: Option<String>,
+        #[serde(default)]
+        description: Option<String>,
+    },
+}
+
+impl From<VarDecl> for FormulaVar {
+    fn from(decl: VarDecl) -> Self {
+        match decl {
+            VarDecl::Name(name) => FormulaVar {
+                name,
+                ..Default::default()
+            },
+            VarDecl::Table {
+                name,
+                allowed,
+                default,
+                description,
+            } => FormulaVar {
+                name,
+                allowed,
+                default,
+                description,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FormulaStep {
     pub name: String,
+    #[serde(default)]
+    pub kind: StepKind,
+    /// Program a `command` step runs
+    #[serde(default)]
     pub command: String,
     #[serde(default)]
     pub args: Vec<String>,
     #[serde(default)]
     pub depends_on: Vec<String>,
+    /// Condition the step runs under, e.g. `{{env}} == 'prod'`; the step is
+    /// skipped when it doesn't hold. See [`evaluate_condition`].
+    #[serde(default)]
+    pub when: Option<String>,
+    /// Extra attempts after the first one fails
+    #[serde(default)]
+    pub retries: u32,
+    /// Time allowed for each attempt; a command gets 300 seconds without it,
+    /// agent and formula steps as long as they take
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// What happens once the step has failed every attempt
+    #[serde(default)]
+    pub on_failure: OnFailure,
+    /// Var that later steps get this step's stdout in, as `{{name}}`
+    #[serde(default)]
+    pub register: Option<String>,
+    /// What an `agent` step asks the agent to do
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// Existing agent an `agent` step is handed to; without one the molecule
+    /// spawns an agent for the step
+    #[serde(default)]
+    pub agent: Option<String>,
+    /// Directory the step runs in
+    #[serde(default)]
+    pub work_dir: Option<String>,
+    /// Formula a `formula` step runs, by name in the formulas directory
+    #[serde(default)]
+    pub formula: Option<String>,
+}
+
+/// How a step is carried out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepKind {
+    /// Run `command` with `args`
+    #[default]
+    Command,
+    /// Give `prompt` to an agent and wait for it to mark the step done
+    Agent,
+    /// Run `formula` as a child molecule and wait for it to finish
+    Formula,
+}
+
+/// A step's `on_failure`: `"abort"` (the default), `"continue"` or
+/// `"goto:<step>"`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum OnFailure {
+    /// Fail the molecule
+    #[default]
+    Abort,
+    /// Carry on as if the step had succeeded; the failure is still recorded
+    Continue,
+    /// Run the named step next, resetting it and everything after it
+    Goto(String),
+}
+
+impl TryFrom<String> for OnFailure {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "abort" => Ok(Self::Abort),
+            "continue" => Ok(Self::Continue),
+            other => match other.strip_prefix("goto:") {
+                Some(step) if !step.is_empty() => Ok(Self::Goto(step.to_string())),
+                _ => Err(format!(
+                    "on_failure must be \"abort\", \"continue\" or \"goto:<step>\", got \"{other}\""
+                )),
+            },
+        }
+    }
+}
+
+impl From<OnFailure> for String {
+    fn from(value: OnFailure) -> Self {
+        match value {
+            OnFailure::Abort => "abort".to_string(),
+            OnFailure::Continue => "continue".to_string(),
+            OnFailure::Goto(step) => format!("goto:{step}"),
+        }
+    }
 }
 
 impl FormulaDef {
@@ -32,6 +191,84 @@ impl FormulaDef {
         Self::from_toml(&content)
     }
 
+    /// Check that dependencies and `goto:` targets name steps of this formula,
+    /// the steps have no cycle, each step has a command (or the prompt or
+    /// formula its kind needs), var defaults are allowed values, and any
+    /// schedule is a cron expression.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.topo_sort()?;
+        if let Some(schedule) = &self.schedule {
+            check_schedule(schedule)?;
+        }
+        for var in &self.vars {
+            if let Some(default) = var.default.as_deref().filter(|d| !var.accepts(d)) {
+                anyhow::bail!(
+                    "var {} defaults to '{default}', which isn't one of its allowed values",
+                    var.name
+                );
+            }
+        }
+        for step in &self.steps {
+            match step.kind {
+                StepKind::Command if step.command.is_empty() => {
+                    anyhow::bail!("step '{}' has no command", step.name)
+                }
+                StepKind::Agent if step.prompt.is_none() => {
+                    anyhow::bail!("agent step '{}' has no prompt", step.name)
+                }
+                StepKind::Formula if step.formula.is_none() => {
+                    anyhow::bail!("formula step '{}' names no formula", step.name)
+                }
+                _ => {}
+            }
+            if let OnFailure::Goto(target) = &step.on_failure {
+                if !self.steps.iter().any(|s| &s.name == target) {
+                    anyhow::bail!(
+                        "step '{}' has on_failure goto unknown step: {target}",
+                        step.name
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `given` plus the default of each declared var that isn't in it.
+    pub fn with_defaults(&self, given: &HashMap<String, String>) -> HashMap<String, String> {
+        let mut vars = given.clone();
+        for var in &self.vars {
+            if let Some(default) = &var.default {
+                vars.entry(var.name.clone())
+                    .or_insert_with(|| default.clone());
+            }
+        }
+        vars
+    }
+
+    /// Check that every declared var has a value, and one it allows.
+    pub fn check_vars(&self, vars: &HashMap<String, String>) -> anyhow::Result<()> {
+        let missing: Vec<String> = self
+            .vars
+            .iter()
+            .filter(|v| !vars.contains_key(&v.name))
+            .map(|v| format!("--var {}=...", v.name))
+            .collect();
+        if !missing.is_empty() {
+            anyhow::bail!("formula '{}' needs {}", self.name, missing.join(" "));
+        }
+        for var in &self.vars {
+            let value = &vars[&var.name];
+            if !var.accepts(value) {
+                anyhow::bail!(
+                    "var {} is '{value}' but must be one of: {}",
+                    var.name,
+                    var.allowed.join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// Returns steps in topological order. Errors if there's a cycle.
     pub fn topo_sort(&self) -> anyhow::Result<Vec<&FormulaStep>> {
         let name_to_idx: HashMap<&str, usize> = self
@@ -80,15 +317,126 @@ impl FormulaDef {
 
         Ok(order)
     }
+
+    /// Steps grouped by how soon they can start: the first wave depends on
+    /// nothing, each later one only on steps in the waves before it. Steps
+    /// keep formula order within a wave.
+    pub fn waves(&self) -> anyhow::Result<Vec<Vec<&FormulaStep>>> {
+        let mut depth: HashMap<&str, usize> = HashMap::new();
+        for step in self.topo_sort()? {
+            let d = step
+                .depends_on
+                .iter()
+                .filter_map(|dep| depth.get(dep.as_str()))
+                .map(|d| d + 1)
+                .max()
+                .unwrap_or(0);
+            depth.insert(&step.name, d);
+        }
+        let mut waves: Vec<Vec<&FormulaStep>> = Vec::new();
+        for step in &self.steps {
+            let d = depth[step.name.as_str()];
+            if waves.len() <= d {
+                waves.resize_with(d + 1, Vec::new);
+            }
+            waves[d].push(step);
+        }
+        Ok(waves)
+    }
+}
+
+/// Check that a formula schedule is a cron expression Temporal can run it
+/// on; see [`crate::cron::Schedule`].
+pub fn check_schedule(schedule: &str) -> anyhow::Result<()> {
+    crate::cron::Schedule::parse(schedule).map(|_| ())
 }
 
 /// Interpolate `{{var_name}}` in a string with provided variables.
+/// Placeholders without a var are left as they are.
 pub fn interpolate(template: &str, vars: &HashMap<String, String>) -> String {
-    let mut result = template.to_string();
-    for (key, value) in vars {
-        result = result.replace(&format!("{{{{{key}}}}}"), value);
+    substitute(template, vars).0
+}
+
+/// `template` with its `{{var}}` placeholders replaced in one left-to-right
+/// pass, so a value is never scanned for placeholders itself, and the first
+/// placeholder that has no var.
+fn substitute<'a>(template: &'a str, vars: &HashMap<String, String>) -> (String, Option<&'a str>) {
+    let mut result = String::with_capacity(template.len());
+    let mut missing = None;
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            break;
+        };
+        let placeholder = &rest[start..start + len + 2];
+        result.push_str(&rest[..start]);
+        match vars.get(&placeholder[2..placeholder.len() - 2]) {
+            Some(value) => result.push_str(value),
+            None => {
+                missing.get_or_insert(placeholder);
+                result.push_str(placeholder);
+            }
+        }
+        rest = &rest[start + len + 2..];
+    }
+    result.push_str(rest);
+    (result, missing)
+}
+
+/// One `a == b`, `a != b` or bare operand of a condition, with vars filled in.
+enum Comparison {
+    Eq(String, String),
+    Ne(String, String),
+    Truthy(String),
+}
+
+impl Comparison {
+    fn holds(&self) -> bool {
+        match self {
+            Self::Eq(lhs, rhs) => lhs == rhs,
+            Self::Ne(lhs, rhs) => lhs != rhs,
+            Self::Truthy(value) => !matches!(value.as_str(), "" | "false" | "0"),
+        }
+    }
+}
+
+/// Evaluate a step condition. Supports `a == b` and `a != b` comparisons
+/// joined with `&&` and `||` (`&&` binds tighter); operands may be quoted
+/// with `'` or `"`. A bare operand holds unless it's empty, `false` or `0`.
+/// The expression is split up before `vars` are interpolated into each
+/// operand, so operators inside a var's value are just text.
+pub fn evaluate_condition(expr: &str, vars: &HashMap<String, String>) -> anyhow::Result<bool> {
+    let any = expr
+        .split("||")
+        .map(|all| {
+            all.split("&&")
+                .map(|c| comparison(c, vars))
+                .collect::<anyhow::Result<Vec<_>>>()
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(any.iter().any(|all| all.iter().all(Comparison::holds)))
+}
+
+fn comparison(expr: &str, vars: &HashMap<String, String>) -> anyhow::Result<Comparison> {
+    if let Some((lhs, rhs)) = expr.split_once("!=") {
+        return Ok(Comparison::Ne(operand(lhs, vars)?, operand(rhs, vars)?));
+    }
+    if let Some((lhs, rhs)) = expr.split_once("==") {
+        return Ok(Comparison::Eq(operand(lhs, vars)?, operand(rhs, vars)?));
+    }
+    Ok(Comparison::Truthy(operand(expr, vars)?))
+}
+
+fn operand(s: &str, vars: &HashMap<String, String>) -> anyhow::Result<String> {
+    let s = s.trim();
+    let s = ['\'', '"']
+        .iter()
+        .find_map(|q| s.strip_prefix(*q).and_then(|s| s.strip_suffix(*q)))
+        .unwrap_or(s);
+    match substitute(s, vars) {
+        (_, Some(var)) => anyhow::bail!("condition uses unknown var {var}"),
+        (value, None) => Ok(value),
     }
-    result
 }
 
 #[cfg(test)]
@@ -131,10 +479,12 @@ depends_on = ["test"]
             name: "test".into(),
             description: None,
             vars: vec![],
+            max_parallel: None,
+            schedule: None,
             steps: vec![
-                FormulaStep { name: "a".into(), command: "echo".into(), args: vec![], depends_on: vec![] },
-                FormulaStep { name: "b".into(), command: "echo".into(), args: vec![], depends_on: vec!["a".into()] },
-                FormulaStep { name: "c".into(), command: "echo".into(), args: vec![], depends_on: vec!["b".into()] },
+                FormulaStep { name: "a".into(), command: "echo".into(), args: vec![], depends_on: vec![], ..Default::default() },
+                FormulaStep { name: "b".into(), command: "echo".into(), args: vec![], depends_on: vec!["a".into()], ..Default::default() },
+                FormulaStep { name: "c".into(), command: "echo".into(), args: vec![], depends_on: vec!["b".into()], ..Default::default() },
             ],
         };
         let sorted = def.topo_sort().unwrap();
@@ -149,11 +499,13 @@ depends_on = ["test"]
             name: "test".into(),
             description: None,
             vars: vec![],
+            max_parallel: None,
+            schedule: None,
             steps: vec![
-                FormulaStep { name: "a".into(), command: "echo".into(), args: vec![], depends_on: vec![] },
-                FormulaStep { name: "b".into(), command: "echo".into(), args: vec![], depends_on: vec!["a".into()] },
-                FormulaStep { name: "c".into(), command: "echo".into(), args: vec![], depends_on: vec!["a".into()] },
-                FormulaStep { name: "d".into(), command: "echo".into(), args: vec![], depends_on: vec!["b".into(), "c".into()] },
+                FormulaStep { name: "a".into(), command: "echo".into(), args: vec![], depends_on: vec![], ..Default::default() },
+                FormulaStep { name: "b".into(), command: "echo".into(), args: vec![], depends_on: vec!["a".into()], ..Default::default() },
+                FormulaStep { name: "c".into(), command: "echo".into(), args: vec![], depends_on: vec!["a".into()], ..Default::default() },
+                FormulaStep { name: "d".into(), command: "echo".into(), args: vec![], depends_on: vec!["b".into(), "c".into()], ..Default::default() },
             ],
         };
         let sorted = def.topo_sort().unwrap();
@@ -161,15 +513,55 @@ depends_on = ["test"]
         assert_eq!(sorted[3].name, "d");
     }
 
+    #[test]
+    fn waves_group_independent_steps() {
+        let def = FormulaDef::from_toml(
+            r#"
+name = "release"
+
+[[steps]]
+name = "build"
+command = "cargo"
+
+[[steps]]
+name = "lint"
+command = "cargo"
+
+[[steps]]
+name = "test"
+command = "cargo"
+depends_on = ["build"]
+
+[[steps]]
+name = "publish"
+command = "cargo"
+depends_on = ["test", "lint"]
+"#,
+        )
+        .unwrap();
+        let names: Vec<Vec<&str>> = def
+            .waves()
+            .unwrap()
+            .iter()
+            .map(|wave| wave.iter().map(|s| s.name.as_str()).collect())
+            .collect();
+        assert_eq!(
+            names,
+            vec![vec!["build", "lint"], vec!["test"], vec!["publish"]]
+        );
+    }
+
     #[test]
     fn topo_sort_cycle() {
         let def = FormulaDef {
             name: "test".into(),
             description: None,
             vars: vec![],
+            max_parallel: None,
+            schedule: None,
             steps: vec![
-                FormulaStep { name: "a".into(), command: "echo".into(), args: vec![], depends_on: vec!["b".into()] },
-                FormulaStep { name: "b".into(), command: "echo".into(), args: vec![], depends_on: vec!["a".into()] },
+                FormulaStep { name: "a".into(), command: "echo".into(), args: vec![], depends_on: vec!["b".into()], ..Default::default() },
+                FormulaStep { name: "b".into(), command: "echo".into(), args: vec![], depends_on: vec!["a".into()], ..Default::default() },
             ],
         };
         assert!(def.topo_sort().is_err());
@@ -183,5 +575,145 @@ depends_on = ["test"]
 
         assert_eq!(interpolate("deploy {{env}} v{{version}}", &vars), "deploy prod v1.2.3");
         assert_eq!(interpolate("no vars here", &vars), "no vars here");
+
+        // Values aren't scanned again, whatever order the vars come in
+        vars.insert("raw".into(), "{{env}}".into());
+        vars.insert("a".into(), "{{b}}".into());
+        vars.insert("b".into(), "{{a}}".into());
+        assert_eq!(
+            interpolate("{{raw}} {{a}}{{b}}", &vars),
+            "{{env}} {{b}}{{a}}"
+        );
+        assert_eq!(interpolate("{{nope}} {{env}}", &vars), "{{nope}} prod");
+    }
+
+    #[test]
+    fn on_failure_policies() {
+        let def = FormulaDef::from_toml(
+            r#"
+name = "deploy"
+
+[[steps]]
+name = "push"
+command = "deploy.sh"
+retries = 2
+timeout_secs = 600
+on_failure = "goto:rollback"
+
+[[steps]]
+name = "rollback"
+command = "rollback.sh"
+on_failure = "continue"
+"#,
+        )
+        .unwrap();
+        assert_eq!(def.steps[0].retries, 2);
+        assert_eq!(def.steps[0].on_failure, OnFailure::Goto("rollback".into()));
+        assert_eq!(def.steps[1].on_failure, OnFailure::Continue);
+        def.validate().unwrap();
+
+        let mut bad = def.clone();
+        bad.steps[0].on_failure = OnFailure::Goto("nowhere".into());
+        assert!(bad.validate().is_err());
+        let mut bad = def.clone();
+        bad.steps[1].kind = StepKind::Agent;
+        assert!(bad.validate().is_err());
+        bad.steps[1].prompt = Some("Roll back the deploy".into());
+        bad.validate().unwrap();
+        bad.steps[1].kind = StepKind::Formula;
+        assert!(bad.validate().is_err());
+        bad.steps[1].formula = Some("rollback".into());
+        bad.validate().unwrap();
+        assert!(FormulaDef::from_toml(
+            "name = \"x\"\n[[steps]]\nname = \"a\"\ncommand = \"true\"\non_failure = \"retry\"\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn conditions() {
+        let vars = HashMap::from([
+            ("env".to_string(), "prod".to_string()),
+            ("build.output".to_string(), "".to_string()),
+        ]);
+        assert!(evaluate_condition("{{env}} == 'prod'", &vars).unwrap());
+        assert!(!evaluate_condition("{{env}} != \"prod\"", &vars).unwrap());
+        assert!(!evaluate_condition("{{build.output}}", &vars).unwrap());
+        assert!(evaluate_condition("{{env}} == dev || {{env}} == prod", &vars).unwrap());
+        assert!(!evaluate_condition("{{env}} == prod && {{build.output}}", &vars).unwrap());
+        assert!(evaluate_condition("{{region}} == eu", &vars).is_err());
+        assert!(evaluate_condition("{{env}} == prod || {{region}}", &vars).is_err());
+    }
+
+    #[test]
+    fn condition_operators_in_values_are_text() {
+        let tricky = "a && b || c == d != {{env}}";
+        let vars = HashMap::from([
+            ("env".to_string(), "prod".to_string()),
+            ("msg".to_string(), tricky.to_string()),
+            ("copy".to_string(), tricky.to_string()),
+            ("no".to_string(), "0 || 1".to_string()),
+        ]);
+        assert!(evaluate_condition("{{msg}} == {{copy}}", &vars).unwrap());
+        assert!(evaluate_condition("'{{msg}}' != {{env}}", &vars).unwrap());
+        assert!(evaluate_condition("{{msg}}", &vars).unwrap());
+        assert!(!evaluate_condition("{{msg}} == prod", &vars).unwrap());
+        assert!(!evaluate_condition("{{no}} == 0", &vars).unwrap());
+    }
+
+    #[test]
+    fn vars_are_declared_by_name_or_with_allowed_values() {
+        let def = FormulaDef::from_toml(
+            r#"
+name = "deploy"
+vars = ["version", { name = "env", allowed = ["dev", "prod"], default = "dev" }]
+
+[[steps]]
+name = "ship"
+command = "deploy.sh"
+args = ["{{env}}", "{{version}}"]
+"#,
+        )
+        .unwrap();
+        def.validate().unwrap();
+        assert_eq!(def.vars[0].name, "version");
+        assert!(def.vars[0].accepts("anything"));
+        assert_eq!(def.vars[1].allowed, vec!["dev", "prod"]);
+
+        let err = def.check_vars(&HashMap::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "formula 'deploy' needs --var version=... --var env=..."
+        );
+
+        let given = HashMap::from([("version".to_string(), "1.2.0".to_string())]);
+        let vars = def.with_defaults(&given);
+        assert_eq!(vars["env"], "dev");
+        def.check_vars(&vars).unwrap();
+
+        let mut vars = vars;
+        vars.insert("env".into(), "staging".into());
+        assert!(def.check_vars(&vars).is_err());
+
+        let mut bad = def.clone();
+        bad.vars[1].default = Some("staging".into());
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn schedules() {
+        check_schedule("0 3 * * *").unwrap();
+        check_schedule("*/15 9-17 * * 1,2,3").unwrap();
+        check_schedule("@daily").unwrap();
+        assert!(check_schedule("0 3 * *").is_err());
+        assert!(check_schedule("0 3 * * mon").is_err());
+        assert!(check_schedule("@fortnightly").is_err());
+
+        let def = FormulaDef::from_toml(
+            "name = \"nightly\"\nschedule = \"0 3 * * *\"\n[[steps]]\nname = \"a\"\ncommand = \"true\"\n",
+        )
+        .unwrap();
+        assert_eq!(def.schedule.as_deref(), Some("0 3 * * *"));
+        def.validate().unwrap();
     }
 }
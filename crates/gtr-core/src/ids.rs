@@ -13,6 +13,11 @@ pub fn agent_id(name: &str) -> String {
     format!("{}-{}", name, nanoid::nanoid!())
 }
 
+/// Generate a mail message ID with format: `ml-{nanoid}`, short enough to type
+pub fn mail_id() -> String {
+    format!("ml-{}", nanoid::nanoid!(10))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
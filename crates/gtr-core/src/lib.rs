@@ -1,11 +1,19 @@
+pub mod atomic;
+pub mod backlog;
 pub mod checkpoint;
+pub mod checks;
 pub mod config;
+pub mod cron;
 pub mod dirs;
 pub mod errors;
 pub mod formula;
 pub mod ids;
+pub mod mail;
+pub mod mock;
 pub mod namepool;
+pub mod outbox;
 pub mod plugin;
+pub mod prompts;
 pub mod state;
 pub mod types;
 
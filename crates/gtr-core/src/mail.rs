@@ -0,0 +1,536 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Events a delivery may replay past the last snapshot before it saves a new
+/// one. Reads, archives and clears always save one.
+const SNAPSHOT_AFTER: usize = 64;
+
+/// The mailbox of the human operator. Mail to it is stored straight away
+/// rather than signalled, since no workflow runs under this ID.
+pub const HUMAN: &str = "human";
+
+/// A message in an agent's mailbox.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mail {
+    pub id: String,
+    pub from: String,
+    pub to: String,
+    pub message: String,
+    pub sent_at: DateTime<Utc>,
+    #[serde(default)]
+    pub read: bool,
+    #[serde(default)]
+    pub archived: bool,
+    /// ID of the message that started the conversation; unset on the first
+    #[serde(default)]
+    pub thread_id: Option<String>,
+    /// ID of the message this one answers
+    #[serde(default)]
+    pub reply_to: Option<String>,
+    #[serde(default)]
+    pub priority: MailPriority,
+    /// Copies of files sent with the message, see [`store_attachment`]
+    #[serde(default)]
+    pub attachments: Vec<PathBuf>,
+}
+
+/// How pressing a message is. Urgent mail is also typed into the recipient's
+/// session so it's seen without checking the inbox.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MailPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+impl std::str::FromStr for MailPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(Self::Low),
+            "normal" => Ok(Self::Normal),
+            "high" => Ok(Self::High),
+            "urgent" => Ok(Self::Urgent),
+            other => Err(format!(
+                "unknown priority '{other}' (expected low, normal, high or urgent)"
+            )),
+        }
+    }
+}
+
+impl Mail {
+    /// The conversation this message belongs to.
+    pub fn thread(&self) -> &str {
+        self.thread_id.as_deref().unwrap_or(&self.id)
+    }
+
+    /// The first gate ID (`gate-...`) named in the message body.
+    pub fn mentioned_gate(&self) -> Option<&str> {
+        self.message
+            .split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '(' | ')' | '`'))
+            .map(|word| word.trim_end_matches(['.', ':', '!', '?']))
+            .find(|word| word.starts_with("gate-") && word.len() > "gate-".len())
+    }
+}
+
+/// One line of a mailbox file. Mailboxes are only ever appended to, so a
+/// delivery racing an `rgt mail` update can't lose either; the flag changes
+/// are replayed over the delivered messages on load, starting from the last
+/// snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum MailEvent {
+    Delivered { mail: Mail },
+    Read { id: String },
+    Archived { id: String },
+    Cleared,
+}
+
+/// The messages left after replaying a mailbox, and every ID it has ever
+/// been delivered (cleared ones included) for deduplication. Saved next to
+/// the mailbox as a snapshot of its first `offset` bytes, so a load only
+/// replays what was appended since.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Replayed {
+    messages: Vec<Mail>,
+    delivered: HashSet<String>,
+    offset: usize,
+    /// Events replayed past the snapshot
+    #[serde(skip)]
+    tail: usize,
+}
+
+/// An agent's persistent mailbox, `~/.gtr/mail/<agent>.jsonl`.
+#[derive(Debug, Clone)]
+pub struct Mailbox {
+    path: PathBuf,
+}
+
+impl Mailbox {
+    pub fn open(agent_id: &str) -> Self {
+        Self::at(crate::dirs::mailbox_path(agent_id))
+    }
+
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Add `mail` unless a message with its ID was delivered before, so a
+    /// retried delivery is harmless. Returns whether it was added.
+    pub fn deliver(&self, mail: &Mail) -> anyhow::Result<bool> {
+        let replayed = self.replay()?;
+        if replayed.delivered.contains(&mail.id) {
+            return Ok(false);
+        }
+        self.append(&MailEvent::Delivered { mail: mail.clone() })?;
+        if replayed.tail >= SNAPSHOT_AFTER {
+            self.snapshot();
+        }
+        Ok(true)
+    }
+
+    /// Every message since the last clear, oldest first, archived included.
+    pub fn messages(&self) -> anyhow::Result<Vec<Mail>> {
+        Ok(self.replay()?.messages)
+    }
+
+    /// Messages that haven't been archived.
+    pub fn inbox(&self) -> anyhow::Result<Vec<Mail>> {
+        let mut messages = self.messages()?;
+        messages.retain(|m| !m.archived);
+        Ok(messages)
+    }
+
+    pub fn unread(&self) -> anyhow::Result<usize> {
+        Ok(self.inbox()?.iter().filter(|m| !m.read).count())
+    }
+
+    /// Look up a message, returning it as it was before this call.
+    pub fn get(&self, id: &str) -> anyhow::Result<Mail> {
+        self.messages()?
+            .into_iter()
+            .find(|m| m.id == id)
+            .ok_or_else(|| anyhow::anyhow!("No message '{id}' in {}", self.path.display()))
+    }
+
+    pub fn mark_read(&self, id: &str) -> anyhow::Result<Mail> {
+        let mail = self.get(id)?;
+        self.append(&MailEvent::Read { id: id.to_string() })?;
+        self.snapshot();
+        Ok(mail)
+    }
+
+    pub fn archive(&self, id: &str) -> anyhow::Result<Mail> {
+        let mail = self.get(id)?;
+        self.append(&MailEvent::Archived { id: id.to_string() })?;
+        self.snapshot();
+        Ok(mail)
+    }
+
+    /// Drop every message. Returns how many there were.
+    pub fn clear(&self) -> anyhow::Result<usize> {
+        let count = self.messages()?.len();
+        self.append(&MailEvent::Cleared)?;
+        self.snapshot();
+        Ok(count)
+    }
+
+    /// The agent the mailbox belongs to, from its file name.
+    pub fn agent_id(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    fn append(&self, event: &MailEvent) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(event)?)?;
+        Ok(())
+    }
+
+    /// `<agent>.snapshot.json`, beside the mailbox file.
+    fn snapshot_path(&self) -> PathBuf {
+        self.path.with_extension("snapshot.json")
+    }
+
+    /// Save the replayed mailbox so later loads start from here. The
+    /// mailbox file itself is left alone, so this can't race a delivery; a
+    /// snapshot that fails to save only costs the next load some time.
+    fn snapshot(&self) {
+        let Ok(replayed) = self.replay() else {
+            return;
+        };
+        if let Ok(json) = serde_json::to_vec(&replayed) {
+            let _ = crate::atomic::write_atomic(&self.snapshot_path(), json);
+        }
+    }
+
+    /// Replay the mailbox file from the last snapshot that still fits it.
+    /// Unparseable lines are skipped and a torn final write is left for the
+    /// next load.
+    fn replay(&self) -> anyhow::Result<Replayed> {
+        let content = match std::fs::read(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Replayed::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut replayed = std::fs::read(self.snapshot_path())
+            .ok()
+            .and_then(|json| serde_json::from_slice::<Replayed>(&json).ok())
+            .filter(|s| s.offset == 0 || content.get(s.offset - 1) == Some(&b'\n'))
+            .unwrap_or_default();
+        let tail = std::str::from_utf8(&content[replayed.offset..])?;
+        let tail = tail.rfind('\n').map_or("", |end| &tail[..=end]);
+        replayed.offset += tail.len();
+        for event in tail
+            .lines()
+            .filter_map(|line| serde_json::from_str::<MailEvent>(line).ok())
+        {
+            replayed.tail += 1;
+            match event {
+                MailEvent::Delivered { mail } => {
+                    if replayed.delivered.insert(mail.id.clone()) {
+                        replayed.messages.push(mail);
+                    }
+                }
+                MailEvent::Read { id } => {
+                    if let Some(mail) = replayed.messages.iter_mut().find(|m| m.id == id) {
+                        mail.read = true;
+                    }
+                }
+                MailEvent::Archived { id } => {
+                    if let Some(mail) = replayed.messages.iter_mut().find(|m| m.id == id) {
+                        mail.archived = true;
+                    }
+                }
+                MailEvent::Cleared => replayed.messages.clear(),
+            }
+        }
+        Ok(replayed)
+    }
+}
+
+/// Copy `file` into `dir/<mail_id>/` (normally under
+/// [`crate::dirs::attachments_dir`]) so the message can refer to it after the
+/// original changes or goes away. Returns the copy's path.
+pub fn store_attachment(dir: &Path, mail_id: &str, file: &Path) -> anyhow::Result<PathBuf> {
+    let Some(name) = file.file_name() else {
+        anyhow::bail!("Can't attach '{}': not a file", file.display());
+    };
+    let target_dir = dir.join(mail_id);
+    let target = target_dir.join(name);
+    if target.exists() {
+        anyhow::bail!(
+            "Two attachments named '{}' on one message",
+            name.to_string_lossy()
+        );
+    }
+    std::fs::create_dir_all(&target_dir)?;
+    std::fs::copy(file, &target)
+        .map_err(|e| anyhow::anyhow!("Can't attach '{}': {e}", file.display()))?;
+    Ok(target)
+}
+
+/// Every mailbox in `dir` (normally [`crate::dirs::mail_dir`]).
+pub fn mailboxes(dir: &Path) -> Vec<Mailbox> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    paths.sort();
+    paths.into_iter().map(Mailbox::at).collect()
+}
+
+/// The whole conversation message `id` is part of, gathered from every
+/// mailbox in `dir`, oldest first. Cleared messages are gone from it.
+pub fn thread(dir: &Path, id: &str) -> anyhow::Result<Vec<Mail>> {
+    let mut all = Vec::new();
+    for mailbox in mailboxes(dir) {
+        all.extend(mailbox.messages()?);
+    }
+    let Some(thread) = all
+        .iter()
+        .find(|m| m.id == id)
+        .map(|m| m.thread().to_string())
+    else {
+        anyhow::bail!("No message '{id}' in any mailbox");
+    };
+    let mut seen = HashSet::new();
+    all.retain(|m| m.thread() == thread && seen.insert((m.id.clone(), m.to.clone())));
+    all.sort_by_key(|m| m.sent_at);
+    Ok(all)
+}
+
+/// Messages in any mailbox under `dir` containing every word of `query`
+/// (case-insensitively, in the body, sender or recipient), oldest first.
+/// `agent` keeps only mail it sent or received, `since` only mail sent at or
+/// after that time. Archived messages are included; cleared ones are gone.
+pub fn search(
+    dir: &Path,
+    query: &str,
+    agent: Option<&str>,
+    since: Option<DateTime<Utc>>,
+) -> anyhow::Result<Vec<Mail>> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+    for mailbox in mailboxes(dir) {
+        for mail in mailbox.messages()? {
+            if agent.is_some_and(|a| mail.from != a && mail.to != a)
+                || since.is_some_and(|t| mail.sent_at < t)
+            {
+                continue;
+            }
+            let text = format!("{} {} {}", mail.from, mail.to, mail.message).to_lowercase();
+            if terms.iter().all(|term| text.contains(term.as_str()))
+                && seen.insert((mail.id.clone(), mail.to.clone()))
+            {
+                found.push(mail);
+            }
+        }
+    }
+    found.sort_by_key(|m| m.sent_at);
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mail(id: &str) -> Mail {
+        Mail {
+            id: id.into(),
+            from: "mayor".into(),
+            to: "gtr-polecat-nux".into(),
+            message: format!("message {id}"),
+            sent_at: Utc::now(),
+            read: false,
+            archived: false,
+            thread_id: None,
+            reply_to: None,
+            priority: MailPriority::Normal,
+            attachments: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn mailbox_tracks_flags_and_dedupes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mailbox = Mailbox::at(dir.path().join("mail").join("nux.jsonl"));
+        assert!(mailbox.messages().unwrap().is_empty());
+
+        assert!(mailbox.deliver(&mail("ml-1")).unwrap());
+        assert!(mailbox.deliver(&mail("ml-2")).unwrap());
+        assert!(!mailbox.deliver(&mail("ml-1")).unwrap());
+        assert_eq!(mailbox.unread().unwrap(), 2);
+
+        mailbox.mark_read("ml-1").unwrap();
+        mailbox.archive("ml-2").unwrap();
+        assert!(mailbox.archive("ml-9").is_err());
+        let inbox = mailbox.inbox().unwrap();
+        assert_eq!(inbox.len(), 1);
+        assert!(inbox[0].read);
+        assert_eq!(mailbox.unread().unwrap(), 0);
+
+        assert_eq!(mailbox.clear().unwrap(), 2);
+        assert!(mailbox.messages().unwrap().is_empty());
+        // A late redelivery of cleared mail stays cleared
+        assert!(!mailbox.deliver(&mail("ml-2")).unwrap());
+        assert!(mailbox.deliver(&mail("ml-3")).unwrap());
+        assert_eq!(mailbox.messages().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn replay_resumes_from_the_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nux.jsonl");
+        let mailbox = Mailbox::at(&path);
+        mailbox.deliver(&mail("ml-1")).unwrap();
+        mailbox.deliver(&mail("ml-2")).unwrap();
+        mailbox.archive("ml-1").unwrap();
+        mailbox.clear().unwrap();
+        mailbox.deliver(&mail("ml-3")).unwrap();
+        mailbox.mark_read("ml-3").unwrap();
+
+        let replayed = mailbox.replay().unwrap();
+        assert_eq!(replayed.tail, 0);
+        assert_eq!(replayed.messages.len(), 1);
+        assert!(replayed.messages[0].read);
+        assert!(!mailbox.deliver(&mail("ml-1")).unwrap());
+        assert_eq!(mailbox.replay().unwrap().tail, 0);
+
+        // Appended after the snapshot, then a torn write still in progress
+        mailbox.deliver(&mail("ml-4")).unwrap();
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        write!(file, "{{\"event\":\"cleared").unwrap();
+        let replayed = mailbox.replay().unwrap();
+        assert_eq!(replayed.tail, 1);
+        assert_eq!(replayed.messages.len(), 2);
+        assert_eq!(
+            replayed.offset,
+            std::fs::metadata(&path).unwrap().len() as usize - 17
+        );
+
+        // A snapshot past the end of a replaced file is ignored
+        std::fs::write(&path, "").unwrap();
+        assert!(mailbox.messages().unwrap().is_empty());
+        assert!(mailbox.deliver(&mail("ml-1")).unwrap());
+    }
+
+    #[test]
+    fn attachments_are_copied_per_message() {
+        let dir = tempfile::tempdir().unwrap();
+        let plan = dir.path().join("plan.md");
+        std::fs::write(&plan, "step one").unwrap();
+        let attachments = dir.path().join("attachments");
+
+        let copy = store_attachment(&attachments, "ml-1", &plan).unwrap();
+        assert_eq!(copy, attachments.join("ml-1").join("plan.md"));
+        std::fs::write(&plan, "changed").unwrap();
+        assert_eq!(std::fs::read_to_string(&copy).unwrap(), "step one");
+
+        assert!(store_attachment(&attachments, "ml-1", &plan).is_err());
+        assert!(store_attachment(&attachments, "ml-2", &plan).is_ok());
+        assert!(store_attachment(&attachments, "ml-3", &dir.path().join("nope")).is_err());
+    }
+
+    #[test]
+    fn mentioned_gate_is_found_in_prose() {
+        let mut m = mail("ml-1");
+        assert_eq!(m.mentioned_gate(), None);
+        m.message = "LGTM, approving (gate-human-abc12).".into();
+        assert_eq!(m.mentioned_gate(), Some("gate-human-abc12"));
+        m.message = "gate- is not an ID; `gate-timer-x` is".into();
+        assert_eq!(m.mentioned_gate(), Some("gate-timer-x"));
+    }
+
+    #[test]
+    fn priority_parses_and_orders() {
+        assert_eq!("urgent".parse(), Ok(MailPriority::Urgent));
+        assert!("asap".parse::<MailPriority>().is_err());
+        assert!(MailPriority::Urgent > MailPriority::High);
+        assert_eq!(MailPriority::default(), MailPriority::Normal);
+    }
+
+    #[test]
+    fn thread_spans_mailboxes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mayor = Mailbox::at(dir.path().join("mayor.jsonl"));
+        let polecat = Mailbox::at(dir.path().join("gtr-polecat-nux.jsonl"));
+        assert_eq!(polecat.agent_id(), "gtr-polecat-nux");
+
+        let question = mail("ml-1");
+        let mut answer = mail("ml-2");
+        answer.from = "gtr-polecat-nux".into();
+        answer.to = "mayor".into();
+        answer.thread_id = Some("ml-1".into());
+        answer.reply_to = Some("ml-1".into());
+        answer.sent_at = question.sent_at + chrono::Duration::seconds(5);
+        polecat.deliver(&question).unwrap();
+        mayor.deliver(&answer).unwrap();
+        polecat.deliver(&mail("ml-3")).unwrap();
+
+        let ids = |id: &str| -> Vec<String> {
+            thread(dir.path(), id)
+                .unwrap()
+                .into_iter()
+                .map(|m| m.id)
+                .collect()
+        };
+        assert_eq!(ids("ml-2"), vec!["ml-1", "ml-2"]);
+        assert_eq!(ids("ml-3"), vec!["ml-3"]);
+        assert!(thread(dir.path(), "ml-9").is_err());
+    }
+
+    #[test]
+    fn search_matches_terms_agent_and_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let polecat = Mailbox::at(dir.path().join("gtr-polecat-nux.jsonl"));
+        let witness = Mailbox::at(dir.path().join("gtr-witness.jsonl"));
+
+        let mut old = mail("ml-1");
+        old.message = "Force-push the Release branch".into();
+        old.sent_at = Utc::now() - chrono::Duration::days(3);
+        let mut recent = mail("ml-2");
+        recent.message = "force-push to release is fine".into();
+        recent.to = "gtr-witness".into();
+        polecat.deliver(&old).unwrap();
+        polecat.deliver(&mail("ml-3")).unwrap();
+        witness.deliver(&recent).unwrap();
+
+        let ids = |query: &str, agent: Option<&str>, days: Option<i64>| -> Vec<String> {
+            let since = days.map(|d| Utc::now() - chrono::Duration::days(d));
+            search(dir.path(), query, agent, since)
+                .unwrap()
+                .into_iter()
+                .map(|m| m.id)
+                .collect()
+        };
+        assert_eq!(ids("RELEASE force-push", None, None), vec!["ml-1", "ml-2"]);
+        assert_eq!(ids("release", Some("gtr-witness"), None), vec!["ml-2"]);
+        assert_eq!(ids("release", None, Some(2)), vec!["ml-2"]);
+        assert_eq!(ids("mayor", None, None).len(), 3);
+        assert!(ids("rollback", None, None).is_empty());
+    }
+}
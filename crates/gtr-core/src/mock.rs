@@ -0,0 +1,151 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Environment variable naming the behavior file for the `mock` runtime.
+pub const MOCK_SCRIPT_ENV: &str = "GTR_MOCK_SCRIPT";
+
+/// Scripted behavior for the `mock` agent runtime — a stand-in for an LLM
+/// CLI in end-to-end tests and CI. Steps run in order in the agent's work
+/// directory; afterwards the agent idles until killed, like a real session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MockScript {
+    pub steps: Vec<MockStep>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MockStep {
+    /// Pause for this many seconds
+    Sleep(u64),
+    /// Create or overwrite a file relative to the work directory
+    Write { path: String, contents: String },
+    /// Stage everything and commit with this message
+    Commit(String),
+    /// Run a command; a non-zero exit aborts the script
+    Run(Vec<String>),
+    /// `rgt done` for the polecat's work item and branch, with this summary
+    Done(String),
+}
+
+impl MockScript {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+
+    /// The script as `sh` source. A failing step stops the rest, but the
+    /// agent still idles so its session can be inspected.
+    pub fn to_shell(&self) -> String {
+        let mut steps = vec!["true".to_string()];
+        for step in &self.steps {
+            steps.push(match step {
+                MockStep::Sleep(secs) => format!("sleep {secs}"),
+                MockStep::Write { path, contents } => {
+                    let path = sh_quote(path);
+                    format!(
+                        "mkdir -p \"$(dirname {path})\" && printf '%s' {} > {path}",
+                        sh_quote(contents)
+                    )
+                }
+                MockStep::Commit(message) => {
+                    format!("git add -A && git commit -q -m {}", sh_quote(message))
+                }
+                MockStep::Run(argv) => argv
+                    .iter()
+                    .map(|a| sh_quote(a))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                MockStep::Done(summary) => format!(
+                    "\"${{RGT_BIN:-rgt}}\" done \"$GTR_WORK_ITEM\" --branch \"$GTR_BRANCH\" --summary {}",
+                    sh_quote(summary)
+                ),
+            });
+        }
+        format!(
+            "{} || echo \"mock agent: script failed\" >&2\nwhile :; do sleep 60; done",
+            steps.join(" &&\n")
+        )
+    }
+}
+
+/// Behavior file for a mock agent: `GTR_MOCK_SCRIPT` from the agent's own
+/// environment, then the worker's, then `~/.gtr/config/mock-agent.toml`.
+pub fn script_path(agent_env: Option<&str>) -> PathBuf {
+    agent_env
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os(MOCK_SCRIPT_ENV).map(PathBuf::from))
+        .unwrap_or_else(|| crate::dirs::config_dir().join("mock-agent.toml"))
+}
+
+fn sh_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_compile_script() {
+        let toml_str = r#"
+[[steps]]
+sleep = 1
+
+[[steps]]
+write = { path = "notes/it's.txt", contents = "hello\n" }
+
+[[steps]]
+commit = "Add notes"
+
+[[steps]]
+run = ["cargo", "fmt"]
+
+[[steps]]
+done = "Added notes"
+"#;
+        let script: MockScript = toml::from_str(toml_str).unwrap();
+        assert_eq!(script.steps.len(), 5);
+        assert_eq!(script.steps[2], MockStep::Commit("Add notes".into()));
+
+        let sh = script.to_shell();
+        assert!(sh.contains("sleep 1 &&\n"));
+        assert!(sh.contains("> 'notes/it'\\''s.txt'"));
+        assert!(sh.contains("git commit -q -m 'Add notes'"));
+        assert!(sh.contains("'cargo' 'fmt'"));
+        assert!(sh.contains("--branch \"$GTR_BRANCH\" --summary 'Added notes'"));
+        assert!(sh.ends_with("while :; do sleep 60; done"));
+    }
+
+    #[test]
+    fn compiled_script_runs_steps() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = MockScript {
+            steps: vec![
+                MockStep::Write {
+                    path: "a/b.txt".into(),
+                    contents: "one 'two'".into(),
+                },
+                MockStep::Run(vec!["false".into()]),
+                MockStep::Write {
+                    path: "never.txt".into(),
+                    contents: String::new(),
+                },
+            ],
+        };
+        // Drop the idle loop so the shell returns
+        let sh = script.to_shell();
+        let body = sh.rsplit_once('\n').unwrap().0;
+        let status = std::process::Command::new("sh")
+            .args(["-c", body])
+            .current_dir(dir.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a/b.txt")).unwrap(),
+            "one 'two'"
+        );
+        assert!(!dir.path().join("never.txt").exists());
+    }
+}
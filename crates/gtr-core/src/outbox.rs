@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A signal that could not be delivered because Temporal was unreachable.
+/// Persisted to the outbox directory and replayed once connectivity returns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedSignal {
+    pub workflow_id: String,
+    pub signal_name: String,
+    /// JSON body of the signal payload, if the signal carries one.
+    pub payload: Option<serde_json::Value>,
+    pub queued_at: DateTime<Utc>,
+}
+
+impl QueuedSignal {
+    pub fn new(
+        workflow_id: impl Into<String>,
+        signal_name: impl Into<String>,
+        payload: Option<serde_json::Value>,
+    ) -> Self {
+        Self {
+            workflow_id: workflow_id.into(),
+            signal_name: signal_name.into(),
+            payload,
+            queued_at: Utc::now(),
+        }
+    }
+}
+
+/// Persist a signal to the outbox. File names sort by enqueue time so replay
+/// preserves the order commands were issued in.
+pub fn enqueue(dir: &Path, signal: &QueuedSignal) -> anyhow::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let name = format!(
+        "{:020}-{}.json",
+        signal.queued_at.timestamp_micros(),
+        nanoid::nanoid!(6)
+    );
+    let path = dir.join(name);
+    // Replay only lists `*.json`, so it never sees a half-written entry
+    crate::atomic::write_atomic(&path, serde_json::to_string_pretty(signal)?)?;
+    Ok(path)
+}
+
+/// List queued signals in delivery order. Unparseable entries are skipped.
+pub fn pending(dir: &Path) -> anyhow::Result<Vec<(PathBuf, QueuedSignal)>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut signals = Vec::new();
+    for path in paths {
+        let Ok(data) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Ok(signal) = serde_json::from_str::<QueuedSignal>(&data) {
+            signals.push((path, signal));
+        }
+    }
+    Ok(signals)
+}
+
+/// Remove a delivered entry from the outbox.
+pub fn ack(path: &Path) -> anyhow::Result<()> {
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outbox_preserves_order_and_acks() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = QueuedSignal::new(
+            "gt-refinery",
+            "refinery_enqueue",
+            Some(serde_json::json!({"work_item_id": "wi-1", "branch": "b", "priority": 2})),
+        );
+        let mut second = QueuedSignal::new("wi-1", "escalate", None);
+        second.queued_at = first.queued_at + chrono::Duration::milliseconds(5);
+
+        enqueue(dir.path(), &second).unwrap();
+        enqueue(dir.path(), &first).unwrap();
+
+        let queued = pending(dir.path()).unwrap();
+        assert_eq!(queued.len(), 2);
+        assert_eq!(queued[0].1, first);
+        assert_eq!(queued[1].1, second);
+
+        ack(&queued[0].0).unwrap();
+        let queued = pending(dir.path()).unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].1.signal_name, "escalate");
+    }
+
+    #[test]
+    fn pending_on_missing_dir_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(pending(&dir.path().join("nope")).unwrap().is_empty());
+    }
+}
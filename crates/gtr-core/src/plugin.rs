@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
@@ -10,9 +11,11 @@ pub struct PluginDef {
     pub args: Vec<String>,
     #[serde(default)]
     pub gate: Gate,
+    #[serde(default)]
+    pub fail_when: FailWhen,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Gate {
     #[default]
@@ -28,6 +31,143 @@ pub enum Gate {
     },
 }
 
+/// Parses `none`, `cooldown:<seconds>`, `cron:<schedule>` or `event:<name>`.
+impl std::str::FromStr for Gate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s.split_once(':').unwrap_or((s, ""));
+        let value = value.trim();
+        match kind {
+            "none" if value.is_empty() => Ok(Self::None),
+            "cooldown" => value
+                .parse()
+                .map(|seconds| Self::Cooldown { seconds })
+                .map_err(|_| format!("cooldown needs a number of seconds, not '{value}'")),
+            "cron" => crate::cron::Schedule::parse(value)
+                .map(|_| Self::Cron {
+                    schedule: value.to_string(),
+                })
+                .map_err(|e| e.to_string()),
+            "event" if !value.is_empty() => Ok(Self::Event {
+                event: value.to_string(),
+            }),
+            _ => Err(format!(
+                "unknown gate '{s}' (expected none, cooldown:<seconds>, cron:<schedule> or event:<name>)"
+            )),
+        }
+    }
+}
+
+/// When a plugin run counts as failed, from the `[fail_when]` table. Any one
+/// tripped condition fails the run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FailWhen {
+    /// A non-zero exit, or none at all
+    pub nonzero_exit: bool,
+    /// Regex matched against each line of stdout and stderr
+    pub output_matches: Option<String>,
+    /// Runs taking longer than this
+    pub max_duration_secs: Option<u64>,
+    /// Who patrol tells when a plugin starts failing
+    pub notify: AlertTarget,
+}
+
+impl Default for FailWhen {
+    fn default() -> Self {
+        Self {
+            nonzero_exit: true,
+            output_matches: None,
+            max_duration_secs: None,
+            notify: AlertTarget::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertTarget {
+    #[default]
+    Mayor,
+    /// The witness of the rig the plugin ran on
+    Witness,
+}
+
+/// Why a plugin run failed, with the output that shows it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginFailure {
+    pub reason: String,
+    pub excerpt: Vec<String>,
+}
+
+/// Lines of output kept in a [`PluginFailure`] excerpt.
+const EXCERPT_LINES: usize = 10;
+
+impl FailWhen {
+    /// The first condition a run trips, checked in the order output,
+    /// duration, exit code. `None` if the run passed.
+    pub fn check(
+        &self,
+        exit_code: Option<i32>,
+        duration_ms: u64,
+        stdout: &[String],
+        stderr: &[String],
+    ) -> Option<PluginFailure> {
+        let output = || stdout.iter().chain(stderr);
+        if let Some(pattern) = &self.output_matches {
+            let re = match regex::Regex::new(pattern) {
+                Ok(re) => re,
+                Err(e) => {
+                    return Some(PluginFailure {
+                        reason: format!("invalid output_matches pattern: {e}"),
+                        excerpt: vec![],
+                    })
+                }
+            };
+            let matching: Vec<String> = output()
+                .filter(|line| re.is_match(line))
+                .take(EXCERPT_LINES)
+                .cloned()
+                .collect();
+            if !matching.is_empty() {
+                return Some(PluginFailure {
+                    reason: format!("output matched /{pattern}/"),
+                    excerpt: matching,
+                });
+            }
+        }
+
+        // The end of stderr usually says what went wrong; stdout otherwise
+        let tail = || {
+            let lines = if stderr.is_empty() { stdout } else { stderr };
+            lines[lines.len().saturating_sub(EXCERPT_LINES)..].to_vec()
+        };
+        if let Some(max) = self.max_duration_secs {
+            if duration_ms > max * 1000 {
+                return Some(PluginFailure {
+                    reason: format!("took {}s, over the {max}s limit", duration_ms / 1000),
+                    excerpt: tail(),
+                });
+            }
+        }
+        if self.nonzero_exit && exit_code != Some(0) {
+            let reason = match exit_code {
+                Some(code) => format!("exited with code {code}"),
+                None => "exited without a code".to_string(),
+            };
+            return Some(PluginFailure {
+                reason,
+                excerpt: tail(),
+            });
+        }
+        None
+    }
+}
+
+/// Event fired by the refinery after a branch lands on its target.
+pub const EVENT_POST_MERGE: &str = "post_merge";
+
 pub fn discover_plugins(dir: &Path) -> anyhow::Result<Vec<(PathBuf, PluginDef)>> {
     let mut plugins = Vec::new();
 
@@ -53,6 +193,62 @@ pub fn discover_plugins(dir: &Path) -> anyhow::Result<Vec<(PathBuf, PluginDef)>>
     Ok(plugins)
 }
 
+/// A starting plugin definition for `rgt services plugin new`: a placeholder
+/// command to replace, the gate, and the `[fail_when]` options commented out.
+pub fn scaffold(name: &str, gate: &Gate) -> String {
+    let quote = |s: &str| toml::Value::from(s).to_string();
+    let mut out = format!(
+        "name = {}\ndescription = {}\ncommand = \"echo\"\nargs = [{}]\n",
+        quote(name),
+        quote(&format!("What {name} checks")),
+        quote(&format!("{name}: replace this with the check to run")),
+    );
+    match gate {
+        Gate::None => out.push_str("\n# No gate: runs every patrol cycle\n"),
+        Gate::Cooldown { seconds } => out.push_str(&format!(
+            "\n[gate]\ntype = \"cooldown\"\nseconds = {seconds}\n"
+        )),
+        Gate::Cron { schedule } => out.push_str(&format!(
+            "\n[gate]\ntype = \"cron\"\nschedule = {}\n",
+            quote(schedule)
+        )),
+        Gate::Event { event } => out.push_str(&format!(
+            "\n[gate]\ntype = \"event\"\nevent = {}\n",
+            quote(event)
+        )),
+    }
+    out.push_str(
+        "\n# [fail_when]\n\
+         # nonzero_exit = true\n\
+         # output_matches = \"(?i)error\"\n\
+         # max_duration_secs = 300\n\
+         # notify = \"mayor\"\n",
+    );
+    out
+}
+
+/// The plugins patrol runs on a rig: those in `global_dir` plus the rig's own
+/// in `rig_dir`, in name order. A rig plugin replaces a global one with the
+/// same name.
+pub fn rig_plugins(global_dir: &Path, rig_dir: &Path) -> anyhow::Result<Vec<PluginDef>> {
+    let mut plugins: BTreeMap<String, PluginDef> = BTreeMap::new();
+    for dir in [global_dir, rig_dir] {
+        for (_, def) in discover_plugins(dir)? {
+            plugins.insert(def.name.clone(), def);
+        }
+    }
+    Ok(plugins.into_values().collect())
+}
+
+/// Plugins in `dir` gated on `event`, in name order.
+pub fn event_plugins(dir: &Path, event: &str) -> anyhow::Result<Vec<PluginDef>> {
+    Ok(discover_plugins(dir)?
+        .into_iter()
+        .map(|(_, def)| def)
+        .filter(|def| matches!(&def.gate, Gate::Event { event: e } if e == event))
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,6 +299,49 @@ schedule = "*/5 * * * *"
         assert!(matches!(def.gate, Gate::Cron { .. }));
     }
 
+    #[test]
+    fn fail_when_checks_output_duration_and_exit() {
+        let toml_str = r#"
+name = "audit"
+command = "npm"
+args = ["audit"]
+
+[fail_when]
+nonzero_exit = false
+output_matches = "(?i)critical"
+max_duration_secs = 60
+notify = "witness"
+"#;
+        let def: PluginDef = toml::from_str(toml_str).unwrap();
+        let fail_when = &def.fail_when;
+        assert_eq!(fail_when.notify, AlertTarget::Witness);
+
+        let stdout = vec!["3 low".to_string(), "1 CRITICAL in lodash".to_string()];
+        let failure = fail_when.check(Some(0), 1000, &stdout, &[]).unwrap();
+        assert_eq!(failure.reason, "output matched /(?i)critical/");
+        assert_eq!(failure.excerpt, vec!["1 CRITICAL in lodash"]);
+
+        let stderr = vec!["slow".to_string()];
+        let failure = fail_when
+            .check(Some(0), 61_000, &stdout[..1], &stderr)
+            .unwrap();
+        assert_eq!(failure.reason, "took 61s, over the 60s limit");
+        assert_eq!(failure.excerpt, stderr);
+
+        assert_eq!(fail_when.check(Some(1), 1000, &stdout[..1], &[]), None);
+
+        let default = FailWhen::default();
+        assert_eq!(default.notify, AlertTarget::Mayor);
+        assert_eq!(default.check(Some(0), 1000, &stdout, &[]), None);
+        let failure = default.check(Some(2), 1000, &stdout, &[]).unwrap();
+        assert_eq!(failure.reason, "exited with code 2");
+        assert_eq!(failure.excerpt, stdout);
+        assert_eq!(
+            default.check(None, 0, &[], &[]).unwrap().reason,
+            "exited without a code"
+        );
+    }
+
     #[test]
     fn discover_plugins_in_dir() {
         let dir = tempfile::tempdir().unwrap();
@@ -130,4 +369,110 @@ args = ["world"]
         assert_eq!(plugins[0].1.name, "hello");
         assert_eq!(plugins[1].1.name, "world");
     }
+
+    #[test]
+    fn event_plugins_filter_by_event() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("deploy.toml"),
+            r#"name = "deploy"
+command = "./deploy.sh"
+args = []
+
+[gate]
+type = "event"
+event = "post_merge"
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("lint.toml"),
+            r#"name = "lint"
+command = "cargo"
+args = ["clippy"]
+"#,
+        )
+        .unwrap();
+
+        let hooks = event_plugins(dir.path(), EVENT_POST_MERGE).unwrap();
+        assert_eq!(hooks.len(), 1);
+        assert_eq!(hooks[0].name, "deploy");
+        assert!(event_plugins(dir.path(), "pre_merge").unwrap().is_empty());
+    }
+
+    #[test]
+    fn gates_parse_from_the_command_line() {
+        assert_eq!("none".parse::<Gate>(), Ok(Gate::None));
+        assert_eq!(
+            "cooldown:3600".parse::<Gate>(),
+            Ok(Gate::Cooldown { seconds: 3600 })
+        );
+        assert_eq!(
+            "cron:0 3 * * *".parse::<Gate>(),
+            Ok(Gate::Cron {
+                schedule: "0 3 * * *".into()
+            })
+        );
+        assert_eq!(
+            "event:post_merge".parse::<Gate>(),
+            Ok(Gate::Event {
+                event: "post_merge".into()
+            })
+        );
+        for bad in ["cooldown:soon", "cron:nightly", "event:", "hourly"] {
+            assert!(bad.parse::<Gate>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn scaffold_is_a_valid_plugin() {
+        for gate in [
+            Gate::None,
+            Gate::Cooldown { seconds: 3600 },
+            Gate::Cron {
+                schedule: "*/30 * * * *".into(),
+            },
+            Gate::Event {
+                event: "post_merge".into(),
+            },
+        ] {
+            let def: PluginDef = toml::from_str(&scaffold("disk-usage", &gate)).unwrap();
+            assert_eq!(def.name, "disk-usage");
+            assert_eq!(def.description.as_deref(), Some("What disk-usage checks"));
+            assert_eq!(def.command, "echo");
+            assert_eq!(def.gate, gate);
+            assert_eq!(def.fail_when, FailWhen::default());
+        }
+    }
+
+    #[test]
+    fn rig_plugins_override_global_ones() {
+        let global = tempfile::tempdir().unwrap();
+        let rig = tempfile::tempdir().unwrap();
+        for (dir, name, command) in [
+            (&global, "lint", "cargo"),
+            (&global, "disk", "df"),
+            (&rig, "lint", "./lint.sh"),
+            (&rig, "audit", "npm"),
+        ] {
+            std::fs::write(
+                dir.path().join(format!("{name}.toml")),
+                format!("name = \"{name}\"\ncommand = \"{command}\"\nargs = []\n"),
+            )
+            .unwrap();
+        }
+
+        let plugins = rig_plugins(global.path(), rig.path()).unwrap();
+        let names: Vec<(&str, &str)> = plugins
+            .iter()
+            .map(|p| (p.name.as_str(), p.command.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("audit", "npm"), ("disk", "df"), ("lint", "./lint.sh")]
+        );
+
+        let missing = global.path().join("missing");
+        assert_eq!(rig_plugins(global.path(), &missing).unwrap().len(), 2);
+    }
 }
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Directory of user prompt templates, one `<role>.md` per role.
+pub fn prompts_dir() -> std::path::PathBuf {
+    crate::dirs::config_dir().join("prompts")
+}
+
+/// Render the prompt template for `role` from `dir`, interpolating
+/// `{{var}}` placeholders (see [`crate::formula::interpolate`]). `None` if
+/// the role has no template, so callers fall back to their built-in prompt.
+pub fn render_from(dir: &Path, role: &str, vars: &HashMap<String, String>) -> Option<String> {
+    let template = std::fs::read_to_string(dir.join(format!("{role}.md"))).ok()?;
+    let rendered = crate::formula::interpolate(&template, vars);
+    let rendered = rendered.trim();
+    (!rendered.is_empty()).then(|| rendered.to_string())
+}
+
+/// [`render_from`] the default `~/.gtr/config/prompts/` directory.
+pub fn render(role: &str, vars: &HashMap<String, String>) -> Option<String> {
+    render_from(&prompts_dir(), role, vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn renders_role_template_or_falls_back() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("polecat.md"),
+            "Work on {{work_item}} in {{rig}} on branch {{branch}}.\n",
+        )
+        .unwrap();
+        let vars = HashMap::from([
+            ("rig".to_string(), "gtr".to_string()),
+            ("work_item".to_string(), "wi-1".to_string()),
+            ("branch".to_string(), "polecat/furiosa".to_string()),
+        ]);
+
+        assert_eq!(
+            render_from(dir.path(), "polecat", &vars).unwrap(),
+            "Work on wi-1 in gtr on branch polecat/furiosa."
+        );
+        assert!(render_from(dir.path(), "witness", &vars).is_none());
+    }
+}
@@ -32,6 +32,8 @@ pub mod roles {
     pub const CREW: &str = "crew";
     pub const DOG: &str = "dog";
     pub const BOOT: &str = "boot";
+    pub const DEPUTY: &str = "deputy";
+    pub const TRIAGE: &str = "triage";
 }
 
 /// Workflow ID conventions — ensures singleton workflows have deterministic IDs.
@@ -47,6 +49,23 @@ pub fn refinery_workflow_id(rig: &str) -> String {
     format!("{rig}-refinery")
 }
 
+/// A named merge queue on a rig, run by its own refinery workflow.
+pub fn merge_queue_workflow_id(rig: &str, queue: &str) -> String {
+    format!("{rig}-refinery-{queue}")
+}
+
+/// The refinery running `queue` on `rig`, where an empty queue is the rig's
+/// default one and an empty rig the town-wide `refinery` (or
+/// `refinery-<queue>`).
+pub fn refinery_queue_workflow_id(rig: &str, queue: &str) -> String {
+    match (rig.is_empty(), queue.is_empty()) {
+        (false, false) => merge_queue_workflow_id(rig, queue),
+        (false, true) => refinery_workflow_id(rig),
+        (true, false) => format!("{}-{queue}", roles::REFINERY),
+        (true, true) => roles::REFINERY.to_string(),
+    }
+}
+
 pub fn patrol_workflow_id() -> String {
     "patrol".to_string()
 }
@@ -55,10 +74,20 @@ pub fn boot_workflow_id() -> String {
     "boot".to_string()
 }
 
+/// The standby that covers for the mayor while its agent is down.
+pub fn deputy_workflow_id() -> String {
+    "deputy".to_string()
+}
+
 pub fn rig_workflow_id(rig: &str) -> String {
     format!("rig-{rig}")
 }
 
+/// The workflow that deletes a rig once its removal is approved.
+pub fn rig_removal_workflow_id(rig: &str) -> String {
+    format!("remove-rig-{rig}")
+}
+
 pub fn polecat_workflow_id(rig: &str, name: &str) -> String {
     format!("{rig}-polecat-{name}")
 }
@@ -71,6 +100,168 @@ pub fn crew_workflow_id(rig: &str, name: &str) -> String {
     format!("{rig}-crew-{name}")
 }
 
+/// The cron molecule that runs a scheduled formula.
+pub fn formula_schedule_workflow_id(formula: &str) -> String {
+    format!("mol-{formula}-scheduled")
+}
+
+/// The short-lived agent that assesses a work item before dispatch.
+pub fn triage_workflow_id(work_item_id: &str) -> String {
+    format!("triage-{work_item_id}")
+}
+
+/// A workflow ID decoded back into the entity it names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkflowId {
+    Mayor,
+    Patrol,
+    Boot,
+    Deputy,
+    Rig(String),
+    Witness(String),
+    Refinery(String),
+    MergeQueue { rig: String, queue: String },
+    Polecat { rig: String, name: String },
+    Dog(String),
+    Crew { rig: String, name: String },
+    Triage(String),
+}
+
+impl WorkflowId {
+    /// Parse a workflow ID produced by the constructors above.
+    ///
+    /// Rig names may contain hyphens, so role infixes are matched from the
+    /// right (`cfb-stats-polecat-nux` is rig `cfb-stats`, polecat `nux`).
+    /// Singletons win first, then rig-scoped roles, then the `rig-`, `dog-`
+    /// and `triage-` prefixes. Returns `None` for IDs outside the convention
+    /// (e.g. work item or convoy IDs).
+    pub fn parse(id: &str) -> Option<Self> {
+        match id {
+            "mayor" => return Some(Self::Mayor),
+            "patrol" => return Some(Self::Patrol),
+            "boot" => return Some(Self::Boot),
+            "deputy" => return Some(Self::Deputy),
+            _ => {}
+        }
+        if let Some((rig, name)) = split_non_empty(id, "-polecat-") {
+            return Some(Self::Polecat { rig, name });
+        }
+        if let Some((rig, name)) = split_non_empty(id, "-crew-") {
+            return Some(Self::Crew { rig, name });
+        }
+        if let Some((rig, queue)) = split_non_empty(id, "-refinery-") {
+            return Some(Self::MergeQueue { rig, queue });
+        }
+        if let Some(rig) = strip_non_empty(id.strip_suffix("-witness")) {
+            return Some(Self::Witness(rig));
+        }
+        if let Some(rig) = strip_non_empty(id.strip_suffix("-refinery")) {
+            return Some(Self::Refinery(rig));
+        }
+        if let Some(rig) = strip_non_empty(id.strip_prefix("rig-")) {
+            return Some(Self::Rig(rig));
+        }
+        if let Some(name) = strip_non_empty(id.strip_prefix("dog-")) {
+            return Some(Self::Dog(name));
+        }
+        if let Some(work_item) = strip_non_empty(id.strip_prefix("triage-")) {
+            return Some(Self::Triage(work_item));
+        }
+        None
+    }
+
+    /// The rig this workflow belongs to, if it is rig-scoped.
+    pub fn rig(&self) -> Option<&str> {
+        match self {
+            Self::Rig(rig) | Self::Witness(rig) | Self::Refinery(rig) => Some(rig),
+            Self::Polecat { rig, .. } | Self::Crew { rig, .. } => Some(rig),
+            Self::MergeQueue { rig, .. } => Some(rig),
+            Self::Mayor | Self::Patrol | Self::Boot | Self::Deputy => None,
+            Self::Dog(_) | Self::Triage(_) => None,
+        }
+    }
+
+    /// The canonical role string for this workflow (see [`roles`]).
+    pub fn role(&self) -> Option<&'static str> {
+        match self {
+            Self::Mayor => Some(roles::MAYOR),
+            Self::Boot => Some(roles::BOOT),
+            Self::Deputy => Some(roles::DEPUTY),
+            Self::Witness(_) => Some(roles::WITNESS),
+            Self::Refinery(_) | Self::MergeQueue { .. } => Some(roles::REFINERY),
+            Self::Polecat { .. } => Some(roles::POLECAT),
+            Self::Dog(_) => Some(roles::DOG),
+            Self::Crew { .. } => Some(roles::CREW),
+            Self::Triage(_) => Some(roles::TRIAGE),
+            Self::Patrol | Self::Rig(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for WorkflowId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let id = match self {
+            Self::Mayor => mayor_workflow_id(),
+            Self::Patrol => patrol_workflow_id(),
+            Self::Boot => boot_workflow_id(),
+            Self::Deputy => deputy_workflow_id(),
+            Self::Rig(rig) => rig_workflow_id(rig),
+            Self::Witness(rig) => witness_workflow_id(rig),
+            Self::Refinery(rig) => refinery_workflow_id(rig),
+            Self::MergeQueue { rig, queue } => merge_queue_workflow_id(rig, queue),
+            Self::Polecat { rig, name } => polecat_workflow_id(rig, name),
+            Self::Dog(name) => dog_workflow_id(name),
+            Self::Crew { rig, name } => crew_workflow_id(rig, name),
+            Self::Triage(work_item) => triage_workflow_id(work_item),
+        };
+        f.write_str(&id)
+    }
+}
+
+/// Extract the rig name from a `rig-<name>` workflow ID.
+pub fn parse_rig_workflow_id(id: &str) -> Option<String> {
+    match WorkflowId::parse(id)? {
+        WorkflowId::Rig(rig) => Some(rig),
+        _ => None,
+    }
+}
+
+/// Extract `(rig, name)` from a `<rig>-polecat-<name>` workflow ID.
+pub fn parse_polecat_workflow_id(id: &str) -> Option<(String, String)> {
+    match WorkflowId::parse(id)? {
+        WorkflowId::Polecat { rig, name } => Some((rig, name)),
+        _ => None,
+    }
+}
+
+/// Extract `(rig, name)` from a `<rig>-crew-<name>` workflow ID.
+pub fn parse_crew_workflow_id(id: &str) -> Option<(String, String)> {
+    match WorkflowId::parse(id)? {
+        WorkflowId::Crew { rig, name } => Some((rig, name)),
+        _ => None,
+    }
+}
+
+/// Extract the dog name from a `dog-<name>` workflow ID.
+pub fn parse_dog_workflow_id(id: &str) -> Option<String> {
+    match WorkflowId::parse(id)? {
+        WorkflowId::Dog(name) => Some(name),
+        _ => None,
+    }
+}
+
+fn split_non_empty(id: &str, infix: &str) -> Option<(String, String)> {
+    let (left, right) = id.rsplit_once(infix)?;
+    if left.is_empty() || right.is_empty() {
+        return None;
+    }
+    Some((left.to_string(), right.to_string()))
+}
+
+fn strip_non_empty(part: Option<&str>) -> Option<String> {
+    part.filter(|p| !p.is_empty()).map(str::to_string)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -80,9 +271,78 @@ mod tests {
         assert_eq!(mayor_workflow_id(), "mayor");
         assert_eq!(witness_workflow_id("gt"), "gt-witness");
         assert_eq!(refinery_workflow_id("gt"), "gt-refinery");
+        assert_eq!(
+            merge_queue_workflow_id("gt", "hotfix"),
+            "gt-refinery-hotfix"
+        );
+        assert_eq!(refinery_queue_workflow_id("gt", ""), "gt-refinery");
+        assert_eq!(
+            refinery_queue_workflow_id("gt", "hotfix"),
+            "gt-refinery-hotfix"
+        );
+        assert_eq!(refinery_queue_workflow_id("", ""), "refinery");
+        assert_eq!(refinery_queue_workflow_id("", "hotfix"), "refinery-hotfix");
         assert_eq!(rig_workflow_id("gt"), "rig-gt");
         assert_eq!(polecat_workflow_id("gt", "nux"), "gt-polecat-nux");
         assert_eq!(dog_workflow_id("alpha"), "dog-alpha");
         assert_eq!(crew_workflow_id("gt", "drew"), "gt-crew-drew");
+        assert_eq!(triage_workflow_id("wi-abc123"), "triage-wi-abc123");
+    }
+
+    #[test]
+    fn workflow_id_round_trip() {
+        let ids = [
+            WorkflowId::Mayor,
+            WorkflowId::Patrol,
+            WorkflowId::Boot,
+            WorkflowId::Deputy,
+            WorkflowId::Rig("cfb-stats".into()),
+            WorkflowId::Witness("cfb-stats".into()),
+            WorkflowId::Refinery("gt".into()),
+            WorkflowId::MergeQueue {
+                rig: "cfb-stats".into(),
+                queue: "docs".into(),
+            },
+            WorkflowId::Polecat {
+                rig: "cfb-stats".into(),
+                name: "nux".into(),
+            },
+            WorkflowId::Dog("alpha".into()),
+            WorkflowId::Crew {
+                rig: "gt".into(),
+                name: "drew".into(),
+            },
+            WorkflowId::Triage("wi-abc123".into()),
+        ];
+        for id in ids {
+            assert_eq!(WorkflowId::parse(&id.to_string()), Some(id));
+        }
+    }
+
+    #[test]
+    fn workflow_id_parsers() {
+        assert_eq!(parse_rig_workflow_id("rig-gt").as_deref(), Some("gt"));
+        assert_eq!(parse_rig_workflow_id("gt-witness"), None);
+        assert_eq!(
+            parse_polecat_workflow_id("cfb-stats-polecat-nux"),
+            Some(("cfb-stats".into(), "nux".into()))
+        );
+        assert_eq!(
+            parse_crew_workflow_id("gt-crew-drew"),
+            Some(("gt".into(), "drew".into()))
+        );
+        assert_eq!(parse_dog_workflow_id("dog-alpha").as_deref(), Some("alpha"));
+        assert_eq!(WorkflowId::parse("wi-abc123"), None);
+        assert_eq!(WorkflowId::parse("rig-"), None);
+        assert_eq!(WorkflowId::parse("-polecat-nux"), None);
+    }
+
+    #[test]
+    fn workflow_id_rig_and_role() {
+        let id = WorkflowId::parse("gt-polecat-nux").unwrap();
+        assert_eq!(id.rig(), Some("gt"));
+        assert_eq!(id.role(), Some(roles::POLECAT));
+        assert_eq!(WorkflowId::Mayor.rig(), None);
+        assert_eq!(WorkflowId::Dog("alpha".into()).role(), Some(roles::DOG));
     }
 }
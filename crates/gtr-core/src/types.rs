@@ -14,9 +14,10 @@ pub struct AgentId(pub String);
 
 // --- Enums ---
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum WorkItemStatus {
+    #[default]
     Pending,
     InProgress,
     Blocked,
@@ -24,15 +25,46 @@ pub enum WorkItemStatus {
     Failed,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Priority {
     P0,
     P1,
+    #[default]
     P2,
     P3,
 }
 
+impl std::str::FromStr for Priority {
+    type Err = String;
+
+    /// `P0`..`P3`, in either case, or just the digit.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digit = s.strip_prefix(['P', 'p']).unwrap_or(s);
+        match digit {
+            "0" => Ok(Self::P0),
+            "1" => Ok(Self::P1),
+            "2" => Ok(Self::P2),
+            "3" => Ok(Self::P3),
+            _ => Err(format!(
+                "unknown priority '{s}' (expected P0, P1, P2 or P3)"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::P0 => "P0",
+            Self::P1 => "P1",
+            Self::P2 => "P2",
+            Self::P3 => "P3",
+        };
+        f.write_str(s)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ConvoyStatus {
@@ -69,16 +101,55 @@ pub enum AgentStatus {
 
 // --- Structs ---
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A unit of work, as `work_item_wf` is started with it. Everything but the
+/// ID and title can be left out of the JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WorkItem {
     pub id: WorkItemId,
     pub title: String,
+    #[serde(default)]
     pub description: String,
+    #[serde(default)]
     pub status: WorkItemStatus,
+    #[serde(default)]
     pub priority: Priority,
+    #[serde(default)]
     pub assignee: Option<AgentId>,
+    /// Rig the work is meant for
+    #[serde(default)]
+    pub rig: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
     pub depends_on: Vec<WorkItemId>,
+    #[serde(default)]
     pub blocks: Vec<WorkItemId>,
+    /// The item this one was split from
+    #[serde(default)]
+    pub parent: Option<WorkItemId>,
+    /// Run ID of the failed attempt this one retries
+    #[serde(default)]
+    pub retry_of: Option<String>,
+}
+
+impl WorkItem {
+    /// A pending item with nothing but an ID and title.
+    pub fn new(id: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            id: WorkItemId(id.into()),
+            title: title.into(),
+            description: String::new(),
+            status: WorkItemStatus::default(),
+            priority: Priority::default(),
+            assignee: None,
+            rig: None,
+            labels: Vec::new(),
+            depends_on: Vec::new(),
+            blocks: Vec::new(),
+            parent: None,
+            retry_of: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -127,8 +198,12 @@ mod tests {
             status: WorkItemStatus::Pending,
             priority: Priority::P1,
             assignee: Some(AgentId("slit".into())),
+            rig: Some("gastown".into()),
+            labels: vec!["core".into()],
             depends_on: vec![WorkItemId("hq-nn6.1".into())],
             blocks: vec![],
+            parent: None,
+            retry_of: None,
         };
         let json = serde_json::to_string(&item).unwrap();
         let parsed: WorkItem = serde_json::from_str(&json).unwrap();
@@ -137,6 +212,22 @@ mod tests {
         assert_eq!(parsed.assignee.unwrap(), AgentId("slit".into()));
     }
 
+    #[test]
+    fn work_item_from_minimal_json() {
+        let item: WorkItem = serde_json::from_str(r#"{"id": "wi-1", "title": "Fix"}"#).unwrap();
+        assert_eq!(item, WorkItem::new("wi-1", "Fix"));
+        assert_eq!(item.priority, Priority::P2);
+    }
+
+    #[test]
+    fn priority_parses() {
+        assert_eq!("P1".parse(), Ok(Priority::P1));
+        assert_eq!("p0".parse(), Ok(Priority::P0));
+        assert_eq!("3".parse(), Ok(Priority::P3));
+        assert!("P4".parse::<Priority>().is_err());
+        assert_eq!(Priority::P1.to_string(), "P1");
+    }
+
     #[test]
     fn agent_config_with_metadata() {
         let mut meta = HashMap::new();
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use gtr_core::mail::HUMAN;
+use serde::{Deserialize, Serialize};
+use temporalio_common::protos::temporal::api::history::v1::HistoryEvent;
+use temporalio_sdk::{ActContext, ActivityError};
+
+use crate::activities::refinery_history;
+use crate::history;
+use crate::signals::{AgentMailSignal, ConvoyState, WorkItemState};
+
+/// Where a convoy's report was written, and the one-line summary mailed with it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConvoyReportOutput {
+    pub path: String,
+    pub summary: String,
+}
+
+/// One work item's row in a convoy report.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct ReportItem {
+    id: String,
+    title: String,
+    status: String,
+    assignee: Option<String>,
+    branch: Option<String>,
+    duration_secs: Option<u64>,
+}
+
+/// Write the markdown report for a finished convoy to
+/// `~/.gtr/reports/convoy-<id>.md` and mail it to the human inbox. The convoy
+/// mails the mayor itself with the returned summary.
+pub async fn convoy_report(
+    _ctx: ActContext,
+    state: ConvoyState,
+) -> Result<ConvoyReportOutput, ActivityError> {
+    let retryable = |source: anyhow::Error| ActivityError::Retryable {
+        source,
+        explicit_delay: None,
+    };
+    let client = crate::worker::connect().await.map_err(retryable)?;
+    let merged = merged_branches(&gtr_core::dirs::runtime_dir().join("refinery"));
+
+    let mut items = Vec::with_capacity(state.work_items.len());
+    for id in &state.work_items {
+        let events = history::fetch_events(&client, id).await.unwrap_or_default();
+        items.push(report_item(id, &events, &merged));
+    }
+
+    let path = gtr_core::dirs::reports_dir().join(format!("convoy-{}.md", state.id));
+    std::fs::create_dir_all(gtr_core::dirs::reports_dir()).map_err(|e| retryable(e.into()))?;
+    std::fs::write(&path, render(&state, &items)).map_err(|e| retryable(e.into()))?;
+
+    let summary = summary(&state, &items);
+    let mail = AgentMailSignal {
+        from: state.id.clone(),
+        message: format!("{summary}\nReport: {}", path.display()),
+        id: Some(format!("ml-report-{}", state.id)),
+        attachments: vec![path.clone()],
+        ..Default::default()
+    };
+    let stored = mail.to_mail(
+        mail.id.clone().unwrap_or_default(),
+        HUMAN,
+        chrono::Utc::now(),
+    );
+    crate::activities::mail::deliver_to_human(&stored)
+        .await
+        .map_err(retryable)?;
+
+    Ok(ConvoyReportOutput {
+        path: path.display().to_string(),
+        summary,
+    })
+}
+
+/// The branch each work item merged with, from every refinery history in `dir`.
+fn merged_branches(dir: &Path) -> HashMap<String, String> {
+    let mut merged = HashMap::new();
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "jsonl") {
+            continue;
+        }
+        for record in refinery_history::load(&path).unwrap_or_default() {
+            if record.status.starts_with("merged") && !record.validate_only {
+                merged.insert(record.work_item_id, record.branch);
+            }
+        }
+    }
+    merged
+}
+
+fn report_item(id: &str, events: &[HistoryEvent], merged: &HashMap<String, String>) -> ReportItem {
+    let state =
+        history::result(events).and_then(|r| serde_json::from_str::<WorkItemState>(&r).ok());
+    let title = history::work_item(events)
+        .map(|item| item.title)
+        .unwrap_or_else(|| id.to_string());
+    let assignee = state
+        .as_ref()
+        .and_then(|s| s.assigned_to.clone())
+        .or_else(|| history::polecat_report(events).map(|r| r.polecat_id));
+    ReportItem {
+        id: id.to_string(),
+        title,
+        status: state.map_or_else(|| "open".to_string(), |s| s.status),
+        assignee,
+        branch: merged.get(id).cloned(),
+        duration_secs: (!events.is_empty()).then(|| {
+            history::work_item_stats(events, chrono::Utc::now())
+                .seconds_in_status
+                .values()
+                .sum()
+        }),
+    }
+}
+
+/// `Convoy <id> (<title>) <status>: <done>/<total> done, <n> failed`
+fn summary(state: &ConvoyState, items: &[ReportItem]) -> String {
+    let done = items.iter().filter(|i| i.status == "done").count();
+    let failed = items.iter().filter(|i| i.status == "failed").count();
+    format!(
+        "Convoy {} ({}) {}: {done}/{} done, {failed} failed",
+        state.id,
+        state.title,
+        state.status,
+        items.len()
+    )
+}
+
+fn render(state: &ConvoyState, items: &[ReportItem]) -> String {
+    let mut out = format!("# Convoy {} — {}\n\n", state.id, state.title);
+    out.push_str(&format!("{}\n\n", summary(state, items)));
+    out.push_str("| Item | Title | Status | Assignee | Branch merged | Duration |\n");
+    out.push_str("|---|---|---|---|---|---|\n");
+    for item in items {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} |\n",
+            item.id,
+            item.title.replace('|', "\\|"),
+            item.status,
+            item.assignee.as_deref().unwrap_or("—"),
+            item.branch.as_deref().unwrap_or("—"),
+            item.duration_secs
+                .map(format_secs)
+                .unwrap_or_else(|| "—".into()),
+        ));
+    }
+
+    let failures: Vec<&ReportItem> = items.iter().filter(|i| i.status == "failed").collect();
+    if !failures.is_empty() {
+        out.push_str("\n## Failures\n\n");
+        for item in failures {
+            out.push_str(&format!("- {} {}\n", item.id, item.title));
+        }
+    }
+    if !state.cancelled_items.is_empty() || !state.killed_polecats.is_empty() {
+        out.push_str("\n## Cancelled\n\n");
+        for id in &state.cancelled_items {
+            out.push_str(&format!("- closed {id}\n"));
+        }
+        for id in &state.killed_polecats {
+            out.push_str(&format!("- killed {id}\n"));
+        }
+    }
+    out
+}
+
+fn format_secs(secs: u64) -> String {
+    match secs {
+        s if s >= 3600 => format!("{}h {}m", s / 3600, (s % 3600) / 60),
+        s if s >= 60 => format!("{}m {}s", s / 60, s % 60),
+        s => format!("{s}s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_lists_items_and_failures() {
+        let state = ConvoyState {
+            id: "cv-1".into(),
+            title: "Sprint 12".into(),
+            status: "closed".into(),
+            ..Default::default()
+        };
+        let items = vec![
+            ReportItem {
+                id: "wi-1".into(),
+                title: "Add login".into(),
+                status: "done".into(),
+                assignee: Some("gtr-polecat-nux".into()),
+                branch: Some("polecat/nux".into()),
+                duration_secs: Some(4500),
+            },
+            ReportItem {
+                id: "wi-2".into(),
+                title: "Fix a|b".into(),
+                status: "failed".into(),
+                ..Default::default()
+            },
+        ];
+        let report = render(&state, &items);
+        assert!(report.starts_with("# Convoy cv-1 — Sprint 12\n"));
+        assert!(report.contains("Convoy cv-1 (Sprint 12) closed: 1/2 done, 1 failed"));
+        assert!(
+            report.contains("| wi-1 | Add login | done | gtr-polecat-nux | polecat/nux | 1h 15m |")
+        );
+        assert!(report.contains("| wi-2 | Fix a\\|b | failed | — | — | — |"));
+        assert!(report.contains("## Failures\n\n- wi-2 Fix a|b\n"));
+        assert!(!report.contains("## Cancelled"));
+    }
+}
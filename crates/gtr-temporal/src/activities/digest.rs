@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use gtr_core::mail::HUMAN;
+use serde::{Deserialize, Serialize};
+use temporalio_sdk::{ActContext, ActivityError};
+
+use crate::pty;
+use crate::signals::{AgentMailSignal, DailyDigestInput};
+
+/// Where a daily digest was written, and its one-line summary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyDigestOutput {
+    pub path: String,
+    pub summary: String,
+}
+
+/// Write the mayor's digest for a day to `~/.gtr/reports/daily/<date>.md`
+/// and mail it to the human inbox, which announces it on the `[human]`
+/// notification channels.
+pub async fn daily_digest(
+    _ctx: ActContext,
+    input: DailyDigestInput,
+) -> Result<DailyDigestOutput, ActivityError> {
+    let retryable = |source: anyhow::Error| ActivityError::Retryable {
+        source,
+        explicit_delay: None,
+    };
+    let uptimes: HashMap<String, u64> = input
+        .agents
+        .iter()
+        .filter_map(|agent| Some((agent.agent_id.clone(), uptime_secs(&agent.agent_id)?)))
+        .collect();
+
+    let dir = gtr_core::dirs::reports_dir().join("daily");
+    std::fs::create_dir_all(&dir).map_err(|e| retryable(e.into()))?;
+    let path = dir.join(format!("{}.md", input.date));
+    std::fs::write(&path, render(&input, &uptimes)).map_err(|e| retryable(e.into()))?;
+
+    let summary = summary(&input);
+    let mail = AgentMailSignal {
+        from: gtr_core::state::mayor_workflow_id(),
+        message: format!("{summary}\nReport: {}", path.display()),
+        id: Some(format!("ml-digest-{}", input.date)),
+        attachments: vec![path.clone()],
+        ..Default::default()
+    };
+    let stored = mail.to_mail(
+        mail.id.clone().unwrap_or_default(),
+        HUMAN,
+        chrono::Utc::now(),
+    );
+    crate::activities::mail::deliver_to_human(&stored)
+        .await
+        .map_err(retryable)?;
+
+    Ok(DailyDigestOutput {
+        path: path.display().to_string(),
+        summary,
+    })
+}
+
+/// How long a live agent's session has been up, going by when its PID file
+/// was written. `None` for dead agents and sessions without one.
+fn uptime_secs(agent_id: &str) -> Option<u64> {
+    if !pty::is_alive(agent_id) {
+        return None;
+    }
+    let written = std::fs::metadata(pty::runtime_dir(agent_id).join("pid"))
+        .and_then(|meta| meta.modified())
+        .ok()?;
+    Some(written.elapsed().ok()?.as_secs())
+}
+
+/// `Daily digest <date>: <n> done, <n> failed, <n> merged, <n> open escalations`
+fn summary(input: &DailyDigestInput) -> String {
+    let done = input.reports.iter().filter(|r| r.status == "done").count();
+    let failed = input.reports.len() - done;
+    let merged = input.merges.iter().filter(|m| m.merged).count();
+    format!(
+        "Daily digest {}: {done} done, {failed} failed, {merged} merged, {} open escalations",
+        input.date,
+        input.open_escalations.len()
+    )
+}
+
+fn render(input: &DailyDigestInput, uptimes: &HashMap<String, u64>) -> String {
+    let mut out = format!("# Daily digest — {}\n\n{}\n", input.date, summary(input));
+
+    let done: Vec<_> = input
+        .reports
+        .iter()
+        .filter(|r| r.status == "done")
+        .collect();
+    if !done.is_empty() {
+        out.push_str("\n## Work completed\n\n");
+        for report in done {
+            out.push_str(&format!(
+                "- {} by {} on `{}`",
+                report.work_item_id, report.polecat_id, report.branch
+            ));
+            if let Some(text) = report.summary.as_deref().and_then(|s| s.lines().next()) {
+                out.push_str(&format!(" — {text}"));
+            }
+            out.push('\n');
+        }
+    }
+
+    let merged: Vec<_> = input.merges.iter().filter(|m| m.merged).collect();
+    if !merged.is_empty() {
+        out.push_str("\n## Merges\n\n");
+        for merge in merged {
+            out.push_str(&format!("- {} `{}`\n", merge.work_item_id, merge.branch));
+        }
+    }
+
+    let failed_reports: Vec<_> = input
+        .reports
+        .iter()
+        .filter(|r| r.status != "done")
+        .collect();
+    let failed_merges: Vec<_> = input.merges.iter().filter(|m| !m.merged).collect();
+    if !failed_reports.is_empty() || !failed_merges.is_empty() {
+        out.push_str("\n## Failures\n\n");
+        for report in failed_reports {
+            out.push_str(&format!(
+                "- {} by {}: {} ({})\n",
+                report.work_item_id, report.polecat_id, report.status, report.exit_reason
+            ));
+        }
+        for merge in failed_merges {
+            out.push_str(&format!(
+                "- {} `{}` not merged: {}\n",
+                merge.work_item_id, merge.branch, merge.status
+            ));
+        }
+    }
+
+    if !input.agents.is_empty() {
+        out.push_str("\n## Agents\n\n| Agent | Role | Status | Up for |\n|---|---|---|---|\n");
+        for agent in &input.agents {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                agent.agent_id,
+                agent.role,
+                agent.status,
+                uptimes
+                    .get(&agent.agent_id)
+                    .map(|&secs| format_uptime(secs))
+                    .unwrap_or_else(|| "—".into()),
+            ));
+        }
+    }
+
+    if !input.open_escalations.is_empty() {
+        out.push_str("\n## Outstanding escalations\n\n");
+        for esc in &input.open_escalations {
+            out.push_str(&format!(
+                "- {} {} (level {}, {})\n",
+                esc.work_item_id, esc.title, esc.level, esc.status
+            ));
+        }
+    }
+    out
+}
+
+fn format_uptime(secs: u64) -> String {
+    match secs {
+        s if s >= 86400 => format!("{}d {}h", s / 86400, (s % 86400) / 3600),
+        s if s >= 3600 => format!("{}h {}m", s / 3600, (s % 3600) / 60),
+        s => format!("{}m", s / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::{
+        EscalationUpdateSignal, MayorAgentEntry, MergeResultSignal, PolecatReportSignal,
+    };
+
+    fn report(work_item_id: &str, status: &str, exit_reason: &str) -> PolecatReportSignal {
+        PolecatReportSignal {
+            polecat_id: "gtr-polecat-nux".into(),
+            name: "nux".into(),
+            rig: "gtr".into(),
+            work_item_id: work_item_id.into(),
+            branch: "polecat/nux".into(),
+            status: status.into(),
+            summary: Some("Added login\nand tests".into()),
+            exit_reason: exit_reason.into(),
+        }
+    }
+
+    #[test]
+    fn render_covers_work_merges_failures_agents_and_escalations() {
+        let input = DailyDigestInput {
+            date: "2026-10-15".into(),
+            reports: vec![
+                report("wi-1", "done", "completed"),
+                report("wi-2", "dead", "agent_died"),
+            ],
+            merges: vec![MergeResultSignal {
+                work_item_id: "wi-1".into(),
+                branch: "polecat/nux".into(),
+                status: "merged".into(),
+                merged: true,
+                validate_only: false,
+            }],
+            agents: vec![MayorAgentEntry {
+                agent_id: "mayor".into(),
+                role: "mayor".into(),
+                status: "working".into(),
+                current_work: None,
+            }],
+            open_escalations: vec![EscalationUpdateSignal {
+                work_item_id: "wi-3".into(),
+                title: "Flaky CI".into(),
+                level: 2,
+                status: "open".into(),
+            }],
+        };
+        let uptimes = HashMap::from([("mayor".to_string(), 93_600)]);
+        let digest = render(&input, &uptimes);
+        assert!(digest.starts_with(
+            "# Daily digest — 2026-10-15\n\nDaily digest 2026-10-15: 1 done, 1 failed, 1 merged, 1 open escalations\n"
+        ));
+        assert!(digest.contains("- wi-1 by gtr-polecat-nux on `polecat/nux` — Added login\n"));
+        assert!(digest.contains("## Merges\n\n- wi-1 `polecat/nux`\n"));
+        assert!(digest.contains("- wi-2 by gtr-polecat-nux: dead (agent_died)\n"));
+        assert!(digest.contains("| mayor | mayor | working | 1d 2h |"));
+        assert!(digest.contains("- wi-3 Flaky CI (level 2, open)\n"));
+    }
+}
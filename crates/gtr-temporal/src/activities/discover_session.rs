@@ -1,9 +1,17 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use temporalio_sdk::{ActContext, ActivityError};
+use temporalio_common::protos::coresdk::AsJsonPayloadExt;
+use temporalio_sdk::{ActContext, ActivityError, ActivityOptions, WfContext};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiscoverSessionInput {
     pub work_dir: String,
+    /// Only sessions written since, e.g. by an agent just spawned in a
+    /// directory others have used
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,17 +28,25 @@ pub async fn discover_session_id(
     _ctx: ActContext,
     input: DiscoverSessionInput,
 ) -> Result<DiscoverSessionOutput, ActivityError> {
+    Ok(DiscoverSessionOutput {
+        session_id: latest_session_id(&input.work_dir, input.since),
+    })
+}
+
+/// The most recently written Claude Code session for `work_dir`, if any was
+/// written since `since`.
+pub fn latest_session_id(work_dir: &str, since: Option<DateTime<Utc>>) -> Option<String> {
     let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
 
     // Claude Code stores sessions at ~/.claude/projects/-{sanitized-path}/
-    let sanitized = sanitize_path(&input.work_dir);
+    let sanitized = sanitize_path(work_dir);
     let sessions_dir = std::path::PathBuf::from(&home)
         .join(".claude")
         .join("projects")
         .join(&sanitized);
 
     if !sessions_dir.exists() {
-        return Ok(DiscoverSessionOutput { session_id: None });
+        return None;
     }
 
     // Find the most recent .jsonl file (by modification time)
@@ -42,6 +58,9 @@ pub async fn discover_session_id(
             if path.extension().and_then(|e| e.to_str()) == Some("jsonl") {
                 if let Ok(metadata) = path.metadata() {
                     if let Ok(modified) = metadata.modified() {
+                        if since.is_some_and(|since| DateTime::<Utc>::from(modified) < since) {
+                            continue;
+                        }
                         let stem = path
                             .file_stem()
                             .and_then(|s| s.to_str())
@@ -56,7 +75,30 @@ pub async fn discover_session_id(
         }
     }
 
-    Ok(DiscoverSessionOutput {
-        session_id: newest.map(|(_, id)| id),
+    newest.map(|(_, id)| id)
+}
+
+/// Run `discover_session_id` from a workflow. `None` if the activity fails or
+/// the agent hasn't written a session (since `since`, if set) yet.
+pub async fn discover_from_workflow(
+    ctx: &WfContext,
+    work_dir: &str,
+    since: Option<DateTime<Utc>>,
+) -> Option<String> {
+    let input = DiscoverSessionInput {
+        work_dir: work_dir.to_string(),
+        since,
+    };
+    ctx.activity(ActivityOptions {
+        activity_type: "discover_session_id".to_string(),
+        input: input.as_json_payload().ok()?,
+        start_to_close_timeout: Some(Duration::from_secs(15)),
+        ..Default::default()
     })
+    .await
+    .success_payload_or_error()
+    .ok()
+    .flatten()
+    .and_then(|p| serde_json::from_slice::<DiscoverSessionOutput>(&p.data).ok())
+    .and_then(|output| output.session_id)
 }
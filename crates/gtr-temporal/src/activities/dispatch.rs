@@ -0,0 +1,99 @@
+use std::collections::{BTreeMap, HashMap};
+
+use gtr_core::config::DispatchPolicy;
+use gtr_core::types::WorkItem;
+use serde::{Deserialize, Serialize};
+use temporalio_sdk::{ActContext, ActivityError};
+use temporalio_sdk_core::WorkflowClientTrait;
+
+use crate::activities::heartbeat::load_of;
+use crate::history;
+use crate::signals::WorkItemState;
+use crate::workflows::work_item::SA_STATUS;
+
+/// What the mayor can dispatch on one tick of its dispatch policy.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DispatchCandidates {
+    /// Pending work items whose dependencies are done, highest priority first
+    pub pending: Vec<WorkItem>,
+    /// Polecats each policy rig can still take; parked and docked rigs take none
+    pub rig_slots: BTreeMap<String, usize>,
+}
+
+/// The `[dispatch_policy]` table of `town.toml`.
+pub async fn load_dispatch_policy(
+    _ctx: ActContext,
+    _input: (),
+) -> Result<DispatchPolicy, ActivityError> {
+    Ok(DispatchPolicy::load())
+}
+
+/// List the work items the mayor may dispatch and the free slots on `rigs`.
+/// Items in a convoy are left to the convoy.
+pub async fn dispatch_candidates(
+    _ctx: ActContext,
+    rigs: Vec<String>,
+) -> Result<DispatchCandidates, ActivityError> {
+    let retryable = |source: anyhow::Error| ActivityError::Retryable {
+        source,
+        explicit_delay: None,
+    };
+    let client = crate::worker::connect().await.map_err(retryable)?;
+    let query = format!(
+        "WorkflowType = 'work_item_wf' AND ExecutionStatus = 'Running' AND {SA_STATUS} = 'pending'"
+    );
+    let resp = client
+        .list_workflow_executions(1000, vec![], query)
+        .await
+        .map_err(|e| retryable(anyhow::anyhow!("listing work items failed: {e}")))?;
+
+    let mut done: HashMap<String, bool> = HashMap::new();
+    let mut pending = Vec::new();
+    for exec in &resp.executions {
+        let in_convoy = exec
+            .parent_execution
+            .as_ref()
+            .is_some_and(|parent| parent.workflow_id.starts_with("cv-"));
+        let Some(info) = exec.execution.as_ref().filter(|_| !in_convoy) else {
+            continue;
+        };
+        let events = history::fetch_events(&client, &info.workflow_id)
+            .await
+            .unwrap_or_default();
+        let Some(item) = history::work_item(&events) else {
+            continue;
+        };
+        let mut ready = true;
+        for dep in &item.depends_on {
+            if !done.contains_key(&dep.0) {
+                let events = history::fetch_events(&client, &dep.0)
+                    .await
+                    .unwrap_or_default();
+                let status = history::result(&events)
+                    .and_then(|r| serde_json::from_str::<WorkItemState>(&r).ok())
+                    .map(|state| state.status);
+                done.insert(dep.0.clone(), status.as_deref() == Some("done"));
+            }
+            ready &= done[&dep.0];
+        }
+        if ready {
+            pending.push(item);
+        }
+    }
+    pending.sort_by_key(|item| item.priority);
+
+    let mut rig_slots = BTreeMap::new();
+    for rig in rigs {
+        let events = history::fetch_events(&client, &gtr_core::state::rig_workflow_id(&rig))
+            .await
+            .unwrap_or_default();
+        let lifecycle = history::rig_lifecycle(&events);
+        let slots = if matches!(lifecycle.as_str(), "parked" | "docked") {
+            0
+        } else {
+            load_of(&rig).free_slots()
+        };
+        rig_slots.insert(rig, slots);
+    }
+    Ok(DispatchCandidates { pending, rig_slots })
+}
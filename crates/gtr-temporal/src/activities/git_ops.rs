@@ -15,12 +15,32 @@ pub enum GitOperation {
     Commit { repo_path: String, message: String },
     #[serde(rename = "push")]
     Push { repo_path: String, remote: String, branch: String },
+    /// Reset the local `branch` to the remote's copy, for branches that only
+    /// move on the remote (e.g. merged through pull requests).
+    #[serde(rename = "fetch")]
+    Fetch { repo_path: String, remote: String, branch: String },
     #[serde(rename = "worktree_add")]
     WorktreeAdd { repo_path: String, path: String, branch: String },
     #[serde(rename = "rebase")]
     Rebase { repo_path: String, branch: String, onto: String },
     #[serde(rename = "merge")]
-    Merge { repo_path: String, branch: String },
+    Merge {
+        repo_path: String,
+        branch: String,
+        /// Refuse anything but a fast-forward
+        #[serde(default)]
+        ff_only: bool,
+    },
+    /// Land `branch` on `onto` as a single commit. `message` is a template
+    /// for `gtr_core::config::render_squash_message`.
+    #[serde(rename = "squash")]
+    Squash {
+        repo_path: String,
+        branch: String,
+        onto: String,
+        message: String,
+        work_item_id: String,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -99,9 +119,7 @@ fn run_git_op(op: GitOperation) -> Result<GitResult, ActivityError> {
             let tree_oid = index.write_tree().map_err(git_err)?;
             let tree = repo.find_tree(tree_oid).map_err(git_err)?;
 
-            let sig = repo.signature().unwrap_or_else(|_| {
-                git2::Signature::now("gtr", "gtr@gastownrusted.dev").unwrap()
-            });
+            let sig = signature(&repo)?;
 
             let parent = repo
                 .head()
@@ -148,6 +166,36 @@ fn run_git_op(op: GitOperation) -> Result<GitResult, ActivityError> {
                 message: format!("Pushed {branch} to {remote}"),
             })
         }
+        GitOperation::Fetch {
+            repo_path,
+            remote,
+            branch,
+        } => {
+            // System git for the same credential reasons as push
+            tracing::info!("git fetch {remote} {branch} in {repo_path}");
+            let refspec = format!("+refs/heads/{branch}:refs/heads/{branch}");
+            let output = std::process::Command::new("git")
+                .args(["fetch", "--update-head-ok", &remote, &refspec])
+                .current_dir(&repo_path)
+                .output()
+                .map_err(|e| {
+                    ActivityError::NonRetryable(anyhow::anyhow!("git fetch spawn failed: {e}"))
+                })?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(ActivityError::Retryable {
+                    source: anyhow::anyhow!("git fetch failed: {stderr}"),
+                    explicit_delay: None,
+                });
+            }
+
+            Ok(GitResult {
+                op: "fetch".into(),
+                success: true,
+                message: format!("Fetched {branch} from {remote}"),
+            })
+        }
         GitOperation::WorktreeAdd {
             repo_path,
             path,
@@ -245,9 +293,7 @@ fn run_git_op(op: GitOperation) -> Result<GitResult, ActivityError> {
                 .rebase(Some(&branch_annotated), Some(&onto_annotated), None, None)
                 .map_err(git_err)?;
 
-            let sig = repo.signature().unwrap_or_else(|_| {
-                git2::Signature::now("gtr", "gtr@gastownrusted.dev").unwrap()
-            });
+            let sig = signature(&repo)?;
 
             // Apply each rebase operation
             while rebase.next().is_some() {
@@ -269,6 +315,7 @@ fn run_git_op(op: GitOperation) -> Result<GitResult, ActivityError> {
         GitOperation::Merge {
             repo_path,
             branch,
+            ff_only,
         } => {
             tracing::info!("git merge {branch} in {repo_path}");
             let repo = open_repo(&repo_path)?;
@@ -304,6 +351,10 @@ fn run_git_op(op: GitOperation) -> Result<GitResult, ActivityError> {
                     git2::build::CheckoutBuilder::new().force(),
                 ))
                 .map_err(git_err)?;
+            } else if ff_only {
+                return Err(ActivityError::NonRetryable(anyhow::anyhow!(
+                    "{branch} cannot be fast-forwarded — rebase it first"
+                )));
             } else {
                 // Normal merge
                 repo.merge(&[&annotated], None, None).map_err(git_err)?;
@@ -321,9 +372,7 @@ fn run_git_op(op: GitOperation) -> Result<GitResult, ActivityError> {
                 let mut index = repo.index().map_err(git_err)?;
                 let tree_oid = index.write_tree().map_err(git_err)?;
                 let tree = repo.find_tree(tree_oid).map_err(git_err)?;
-                let sig = repo.signature().unwrap_or_else(|_| {
-                    git2::Signature::now("gtr", "gtr@gastownrusted.dev").unwrap()
-                });
+                let sig = signature(&repo)?;
                 let head_commit = repo.head().map_err(git_err)?.peel_to_commit().map_err(git_err)?;
                 let branch_commit = repo.find_commit(branch_oid).map_err(git_err)?;
 
@@ -346,6 +395,94 @@ fn run_git_op(op: GitOperation) -> Result<GitResult, ActivityError> {
                 message: format!("Merged {branch}"),
             })
         }
+        GitOperation::Squash {
+            repo_path,
+            branch,
+            onto,
+            message,
+            work_item_id,
+        } => {
+            tracing::info!("git squash {branch} onto {onto} in {repo_path}");
+            let repo = open_repo(&repo_path)?;
+
+            let onto_ref = format!("refs/heads/{onto}");
+            let onto_commit = repo
+                .revparse_single(&onto_ref)
+                .map_err(git_err)?
+                .peel_to_commit()
+                .map_err(git_err)?;
+            let branch_commit = repo
+                .revparse_single(&format!("refs/heads/{branch}"))
+                .map_err(git_err)?
+                .peel_to_commit()
+                .map_err(git_err)?;
+
+            if branch_commit.id() == onto_commit.id() {
+                return Ok(GitResult {
+                    op: "squash".into(),
+                    success: true,
+                    message: format!("{branch} already up to date"),
+                });
+            }
+            // The branch tree becomes the new tree, so it must already
+            // contain everything on `onto`
+            if !repo
+                .graph_descendant_of(branch_commit.id(), onto_commit.id())
+                .map_err(git_err)?
+            {
+                return Err(ActivityError::NonRetryable(anyhow::anyhow!(
+                    "{branch} is not rebased onto {onto}"
+                )));
+            }
+
+            // Subjects of the squashed commits, oldest first
+            let mut walk = repo.revwalk().map_err(git_err)?;
+            walk.push(branch_commit.id()).map_err(git_err)?;
+            walk.hide(onto_commit.id()).map_err(git_err)?;
+            walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)
+                .map_err(git_err)?;
+            let mut subjects = Vec::new();
+            for oid in walk {
+                let commit = repo.find_commit(oid.map_err(git_err)?).map_err(git_err)?;
+                subjects.push(commit.summary().unwrap_or_default().to_string());
+            }
+
+            let message = gtr_core::config::render_squash_message(
+                &message,
+                &work_item_id,
+                &branch,
+                &subjects,
+            );
+            let tree = branch_commit.tree().map_err(git_err)?;
+            let sig = signature(&repo)?;
+            let oid = repo
+                .commit(
+                    Some(&onto_ref),
+                    &sig,
+                    &sig,
+                    &message,
+                    &tree,
+                    &[&onto_commit],
+                )
+                .map_err(git_err)?;
+
+            // Refresh the working tree if `onto` is checked out
+            let head_name = repo.head().ok().and_then(|h| h.name().map(str::to_string));
+            if head_name.as_deref() == Some(onto_ref.as_str()) {
+                repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+                    .map_err(git_err)?;
+            }
+
+            Ok(GitResult {
+                op: "squash".into(),
+                success: true,
+                message: format!(
+                    "Squashed {} commit(s) from {branch} into {onto} ({})",
+                    subjects.len(),
+                    &oid.to_string()[..8]
+                ),
+            })
+        }
     }
 }
 
@@ -358,6 +495,13 @@ fn git_err(e: git2::Error) -> ActivityError {
     ActivityError::NonRetryable(anyhow::anyhow!("git error: {e}"))
 }
 
+/// The repo's configured identity, or gtr's own when it has none.
+fn signature(repo: &git2::Repository) -> Result<git2::Signature<'static>, ActivityError> {
+    repo.signature()
+        .or_else(|_| git2::Signature::now("gtr", "gtr@gastownrusted.dev"))
+        .map_err(git_err)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -433,6 +577,7 @@ mod tests {
         let op = GitOperation::Merge {
             repo_path: "/repo".into(),
             branch: "feature/y".into(),
+            ff_only: false,
         };
         let json = serde_json::to_string(&op).unwrap();
         assert!(json.contains("\"op\":\"merge\""));
@@ -444,4 +589,26 @@ mod tests {
             _ => panic!("expected Merge"),
         }
     }
+
+    #[test]
+    fn serde_merge_op_defaults_ff_only() {
+        let json = r#"{"op":"merge","repo_path":"/repo","branch":"b"}"#;
+        match serde_json::from_str::<GitOperation>(json).unwrap() {
+            GitOperation::Merge { ff_only, .. } => assert!(!ff_only),
+            _ => panic!("expected Merge"),
+        }
+    }
+
+    #[test]
+    fn serde_squash_op() {
+        let op = GitOperation::Squash {
+            repo_path: "/repo".into(),
+            branch: "feature/z".into(),
+            onto: "main".into(),
+            message: "{work_item}: {branch}".into(),
+            work_item_id: "wi-1".into(),
+        };
+        let json = serde_json::to_string(&op).unwrap();
+        assert!(json.contains("\"op\":\"squash\""));
+    }
 }
@@ -0,0 +1,244 @@
+use std::path::Path;
+
+use gtr_core::config::GithubChecksConfig;
+use serde::{Deserialize, Serialize};
+use temporalio_sdk::{ActContext, ActivityError};
+
+const GITHUB_API: &str = "https://api.github.com";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecksModeInput {
+    pub rig: String,
+    pub repo_path: String,
+    /// Named merge queue; empty for the rig's default queue
+    #[serde(default)]
+    pub queue: String,
+}
+
+/// Look up the rig's `github_checks` entry in rigs.toml, matching by rig name
+/// or by the refinery's repo path. A named queue's own entry wins. `None`
+/// means run tests locally.
+pub async fn resolve_checks_mode(
+    _ctx: ActContext,
+    input: ChecksModeInput,
+) -> Result<Option<GithubChecksConfig>, ActivityError> {
+    let rigs = gtr_core::config::RigsConfig::load()
+        .map_err(|e| ActivityError::NonRetryable(anyhow::anyhow!("load rigs.toml: {e}")))?;
+    let repo_path = Path::new(&input.repo_path);
+    Ok(rigs
+        .rigs
+        .into_iter()
+        .find(|r| (!input.rig.is_empty() && r.name == input.rig) || r.path == repo_path)
+        .and_then(|mut r| {
+            let queue = r.queues.remove(&input.queue).unwrap_or_default();
+            queue.github_checks.or(r.github_checks)
+        }))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckState {
+    Pending,
+    Success,
+    Failure,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GithubChecksInput {
+    pub config: GithubChecksConfig,
+    /// Branch name or commit SHA to inspect
+    pub git_ref: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GithubChecksOutput {
+    pub state: CheckState,
+    pub failing: Vec<String>,
+    pub pending: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckRunsResponse {
+    check_runs: Vec<CheckRun>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckRun {
+    name: String,
+    status: String,
+    conclusion: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CombinedStatusResponse {
+    statuses: Vec<CommitStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitStatus {
+    context: String,
+    state: String,
+}
+
+/// Fetch check runs and commit statuses for a ref and reduce them to a
+/// single verdict against the configured required checks.
+pub async fn github_checks_status(
+    _ctx: ActContext,
+    input: GithubChecksInput,
+) -> Result<GithubChecksOutput, ActivityError> {
+    let token = std::env::var(&input.config.token_env).map_err(|_| {
+        ActivityError::NonRetryable(anyhow::anyhow!(
+            "GitHub token env var {} is not set",
+            input.config.token_env
+        ))
+    })?;
+    let base = format!(
+        "{GITHUB_API}/repos/{}/commits/{}",
+        input.config.repo, input.git_ref
+    );
+
+    let runs: CheckRunsResponse =
+        github_get(&token, &format!("{base}/check-runs?per_page=100")).await?;
+    let status: CombinedStatusResponse = github_get(&token, &format!("{base}/status")).await?;
+
+    let mut results: Vec<(String, CheckState)> = runs
+        .check_runs
+        .into_iter()
+        .map(|run| {
+            let state = match (run.status.as_str(), run.conclusion.as_deref()) {
+                ("completed", Some("success" | "neutral" | "skipped")) => CheckState::Success,
+                ("completed", _) => CheckState::Failure,
+                _ => CheckState::Pending,
+            };
+            (run.name, state)
+        })
+        .collect();
+    results.extend(status.statuses.into_iter().map(|s| {
+        let state = match s.state.as_str() {
+            "success" => CheckState::Success,
+            "pending" => CheckState::Pending,
+            _ => CheckState::Failure,
+        };
+        (s.context, state)
+    }));
+
+    let output = evaluate_checks(&input.config.required, &results);
+    tracing::info!(
+        "GitHub checks for {}@{}: {:?} ({} failing, {} pending)",
+        input.config.repo,
+        input.git_ref,
+        output.state,
+        output.failing.len(),
+        output.pending.len()
+    );
+    Ok(output)
+}
+
+async fn github_get<T: serde::de::DeserializeOwned>(
+    token: &str,
+    url: &str,
+) -> Result<T, ActivityError> {
+    let resp = reqwest::Client::new()
+        .get(url)
+        .bearer_auth(token)
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "gtr-refinery")
+        .send()
+        .await
+        .map_err(|e| ActivityError::Retryable {
+            source: anyhow::anyhow!("GitHub request failed: {e}"),
+            explicit_delay: None,
+        })?;
+    if !resp.status().is_success() {
+        return Err(ActivityError::Retryable {
+            source: anyhow::anyhow!("GitHub {url} returned {}", resp.status()),
+            explicit_delay: None,
+        });
+    }
+    resp.json::<T>().await.map_err(|e| {
+        ActivityError::NonRetryable(anyhow::anyhow!("unexpected GitHub response: {e}"))
+    })
+}
+
+/// Reduce individual check results to one verdict. Required checks that
+/// haven't reported yet count as pending; with no required list, every
+/// reported check must pass and an empty report is still pending.
+fn evaluate_checks(required: &[String], results: &[(String, CheckState)]) -> GithubChecksOutput {
+    let relevant: Vec<(String, CheckState)> = if required.is_empty() {
+        results.to_vec()
+    } else {
+        required
+            .iter()
+            .map(|name| {
+                // check-runs defaults to `filter=latest`: one run per check
+                let state = results
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, s)| *s)
+                    .unwrap_or(CheckState::Pending);
+                (name.clone(), state)
+            })
+            .collect()
+    };
+
+    let names_in = |state: CheckState| -> Vec<String> {
+        relevant
+            .iter()
+            .filter(|(_, s)| *s == state)
+            .map(|(n, _)| n.clone())
+            .collect()
+    };
+    let failing = names_in(CheckState::Failure);
+    let pending = names_in(CheckState::Pending);
+
+    let state = if !failing.is_empty() {
+        CheckState::Failure
+    } else if !pending.is_empty() || relevant.is_empty() {
+        CheckState::Pending
+    } else {
+        CheckState::Success
+    };
+    GithubChecksOutput {
+        state,
+        failing,
+        pending,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results(items: &[(&str, CheckState)]) -> Vec<(String, CheckState)> {
+        items.iter().map(|(n, s)| (n.to_string(), *s)).collect()
+    }
+
+    #[test]
+    fn required_checks_wait_for_missing() {
+        let required = vec!["test".to_string(), "lint".to_string()];
+        let out = evaluate_checks(&required, &results(&[("test", CheckState::Success)]));
+        assert_eq!(out.state, CheckState::Pending);
+        assert_eq!(out.pending, vec!["lint"]);
+
+        let out = evaluate_checks(
+            &required,
+            &results(&[
+                ("test", CheckState::Success),
+                ("lint", CheckState::Failure),
+                ("docs", CheckState::Failure),
+            ]),
+        );
+        assert_eq!(out.state, CheckState::Failure);
+        assert_eq!(out.failing, vec!["lint"]);
+    }
+
+    #[test]
+    fn all_reported_checks_when_none_required() {
+        assert_eq!(evaluate_checks(&[], &[]).state, CheckState::Pending);
+        let out = evaluate_checks(
+            &[],
+            &results(&[("a", CheckState::Success), ("b", CheckState::Success)]),
+        );
+        assert_eq!(out.state, CheckState::Success);
+    }
+}
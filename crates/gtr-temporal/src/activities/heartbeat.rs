@@ -1,5 +1,8 @@
+use std::collections::HashSet;
+
 use serde::{Deserialize, Serialize};
 use temporalio_sdk::{ActContext, ActivityError};
+use temporalio_sdk_core::WorkflowClientTrait;
 
 use crate::pty;
 
@@ -29,6 +32,83 @@ pub async fn check_agent_alive(
     })
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RigLoadInput {
+    pub rig: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RigLoadOutput {
+    /// Agent IDs of the rig's polecats with a live session
+    pub polecats: Vec<String>,
+    /// The rig's `max_polecats`, if configured
+    pub max_polecats: Option<usize>,
+}
+
+impl RigLoadOutput {
+    /// Polecats the rig can still take, unbounded when no cap is configured.
+    pub fn free_slots(&self) -> usize {
+        self.max_polecats
+            .map_or(usize::MAX, |max| max.saturating_sub(self.polecats.len()))
+    }
+}
+
+/// Count the rig's live polecat sessions against its configured capacity.
+pub async fn rig_load(
+    _ctx: ActContext,
+    input: RigLoadInput,
+) -> Result<RigLoadOutput, ActivityError> {
+    Ok(load_of(&input.rig))
+}
+
+pub(crate) fn load_of(rig: &str) -> RigLoadOutput {
+    let polecats = pty::list_session_metadata()
+        .into_iter()
+        .map(|(agent_id, _)| agent_id)
+        .filter(|agent_id| {
+            gtr_core::state::parse_polecat_workflow_id(agent_id).is_some_and(|(r, _)| r == rig)
+        })
+        .collect();
+    let max_polecats = gtr_core::config::RigsConfig::load()
+        .ok()
+        .and_then(|rigs| rigs.rigs.into_iter().find(|r| r.name == rig))
+        .and_then(|rig| rig.max_polecats);
+    RigLoadOutput {
+        polecats,
+        max_polecats,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaneActivityInput {
+    pub agent_id: String,
+    /// No pane output for this long counts as idle
+    pub idle_after_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaneActivityOutput {
+    pub agent_id: String,
+    /// Seconds since the pane output last changed; `None` if it can't be captured
+    pub idle_secs: Option<u64>,
+    pub idle: bool,
+}
+
+/// Sample an agent's pane and report whether it has produced output within
+/// `idle_after_secs`. Samples are compared across calls, so the first call
+/// for an agent never reports idle.
+pub async fn check_pane_activity(
+    _ctx: ActContext,
+    input: PaneActivityInput,
+) -> Result<PaneActivityOutput, ActivityError> {
+    let idle_secs = pty::pane_idle_secs(&input.agent_id);
+    Ok(PaneActivityOutput {
+        idle: idle_secs.is_some_and(|secs| secs >= input.idle_after_secs),
+        agent_id: input.agent_id,
+        idle_secs,
+    })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CapturePaneInput {
     pub agent_id: String,
@@ -63,3 +143,99 @@ pub async fn kill_agent_activity(
         pid: None,
     })
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendKeysInput {
+    pub agent_id: String,
+    pub text: String,
+    /// Press Enter after the text
+    pub enter: bool,
+}
+
+/// Type input into an agent's pane, e.g. to answer an interactive prompt.
+pub async fn send_keys_activity(
+    _ctx: ActContext,
+    input: SendKeysInput,
+) -> Result<(), ActivityError> {
+    pty::send_keys(&input.agent_id, &input.text, input.enter).map_err(ActivityError::NonRetryable)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownAgentsInput {
+    pub agent_ids: Vec<String>,
+}
+
+/// Graceful counterpart to `kill_agent`: nudge the agents to hand off, wait
+/// the `[shutdown]` grace period from town.toml, then kill their sessions.
+pub async fn shutdown_agents_activity(
+    _ctx: ActContext,
+    input: ShutdownAgentsInput,
+) -> Result<pty::ShutdownReport, ActivityError> {
+    let config = gtr_core::config::ShutdownConfig::load();
+    let report = tokio::task::spawn_blocking(move || {
+        pty::shutdown_agents(&input.agent_ids, &config.message, config.grace())
+    })
+    .await
+    .map_err(|e| ActivityError::NonRetryable(anyhow::anyhow!("shutdown task failed: {e}")))?;
+    Ok(report)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconcileSessionsInput {
+    /// Agents the caller is responsible for, on top of running workflows
+    #[serde(default)]
+    pub watched: Vec<String>,
+    /// Kill and clean up orphans instead of only reporting them
+    pub reap: bool,
+}
+
+/// Garbage-collect agent sessions and runtime dirs that outlived their
+/// workflow: anything not named after a running workflow (or in `watched`).
+pub async fn reconcile_sessions(
+    _ctx: ActContext,
+    input: ReconcileSessionsInput,
+) -> Result<pty::OrphanReport, ActivityError> {
+    let retryable = |source: anyhow::Error| ActivityError::Retryable {
+        source,
+        explicit_delay: None,
+    };
+    let client = crate::worker::connect().await.map_err(retryable)?;
+    let resp = client
+        .list_workflow_executions(1000, vec![], "ExecutionStatus = 'Running'".to_string())
+        .await
+        .map_err(|e| retryable(anyhow::anyhow!("listing workflows failed: {e}")))?;
+    let mut owners: HashSet<String> = resp
+        .executions
+        .iter()
+        .filter_map(|exec| exec.execution.as_ref())
+        .map(|e| e.workflow_id.clone())
+        .collect();
+    owners.extend(input.watched);
+
+    let report = tokio::task::spawn_blocking(move || pty::reconcile_sessions(&owners, input.reap))
+        .await
+        .map_err(|e| ActivityError::NonRetryable(anyhow::anyhow!("reconcile task failed: {e}")))?;
+    Ok(report)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionMetadataInput {
+    pub agent_id: String,
+    pub metadata: pty::SessionMetadata,
+}
+
+/// Refresh the tmux title/options for an agent, e.g. when its status changes.
+pub async fn update_session_metadata(
+    _ctx: ActContext,
+    input: SessionMetadataInput,
+) -> Result<(), ActivityError> {
+    if !pty::is_alive(&input.agent_id) {
+        return Ok(());
+    }
+    pty::set_session_metadata(&input.agent_id, &input.metadata).map_err(|e| {
+        ActivityError::Retryable {
+            source: e,
+            explicit_delay: None,
+        }
+    })
+}
@@ -0,0 +1,91 @@
+use gtr_core::config::{HumanInboxConfig, MailRouteAction, MailRoutes};
+use gtr_core::mail::{Mail, Mailbox, HUMAN};
+use gtr_core::outbox;
+use temporalio_sdk::{ActContext, ActivityError};
+
+use crate::activities::notification::{notify, NotificationInput};
+
+/// Persist a delivered message to its recipient's mailbox. Returns false if
+/// the message was already there, e.g. when this activity is retried.
+pub async fn store_mail(_ctx: ActContext, mail: Mail) -> Result<bool, ActivityError> {
+    Mailbox::open(&mail.to)
+        .deliver(&mail)
+        .map_err(|e| ActivityError::Retryable {
+            source: anyhow::anyhow!("store mail {} for {}: {e}", mail.id, mail.to),
+            explicit_delay: None,
+        })
+}
+
+/// Store mail from a workflow for the human operator and announce it on the
+/// `[human]` channels of `town.toml`. Returns false if it was already stored.
+pub async fn mail_human(_ctx: ActContext, mail: Mail) -> Result<bool, ActivityError> {
+    deliver_to_human(&mail)
+        .await
+        .map_err(|e| ActivityError::Retryable {
+            source: anyhow::anyhow!("mail {} for {HUMAN}: {e}", mail.id),
+            explicit_delay: None,
+        })
+}
+
+/// Store `mail` in the human inbox, then announce it; announcement failures
+/// are only logged.
+pub(crate) async fn deliver_to_human(mail: &Mail) -> anyhow::Result<bool> {
+    if !Mailbox::open(HUMAN).deliver(mail)? {
+        return Ok(false);
+    }
+    for (channel, target) in HumanInboxConfig::load().channels() {
+        let input = NotificationInput {
+            channel: channel.to_string(),
+            target: target.to_string(),
+            subject: format!("Mail from {}", mail.from),
+            message: mail.message.clone(),
+        };
+        if let Err(e) = notify(&input).await {
+            tracing::warn!("Could not announce {} on {channel}: {e}", mail.id);
+        }
+    }
+    Ok(true)
+}
+
+/// Deliver the dead-lettered mail addressed to `agent_id`, now that it is
+/// running. Returns how many messages went out. If a delivery fails — the
+/// agent may have exited again — it and the mail after it stay dead-lettered
+/// for the next registration. Mail already delivered by an earlier attempt is
+/// deduplicated by the recipient's mailbox.
+pub async fn redeliver_dead_letters(
+    _ctx: ActContext,
+    agent_id: String,
+) -> Result<usize, ActivityError> {
+    let retryable = |source: anyhow::Error| ActivityError::Retryable {
+        source,
+        explicit_delay: None,
+    };
+    let waiting: Vec<_> = outbox::pending(&gtr_core::dirs::dead_letter_dir())
+        .map_err(retryable)?
+        .into_iter()
+        .filter(|(_, signal)| signal.workflow_id == agent_id)
+        .collect();
+    if waiting.is_empty() {
+        return Ok(0);
+    }
+    let client = crate::worker::connect().await.map_err(retryable)?;
+    let mut delivered = 0;
+    for (path, signal) in &waiting {
+        if let Err(e) = crate::outbox::deliver(&client, signal).await {
+            tracing::warn!("Could not redeliver dead-lettered mail to {agent_id}: {e}");
+            break;
+        }
+        outbox::ack(path).map_err(retryable)?;
+        delivered += 1;
+    }
+    Ok(delivered)
+}
+
+/// What the mayor's mail routes (`mail_routes.toml`) say to do with `mail`,
+/// read fresh each time so edits apply without restarting the mayor.
+pub async fn route_mail(
+    _ctx: ActContext,
+    mail: Mail,
+) -> Result<Option<MailRouteAction>, ActivityError> {
+    Ok(MailRoutes::load().route(&mail.from, &mail.message))
+}
@@ -1,7 +1,19 @@
 pub mod agent_io;
+pub mod convoy_report;
+pub mod digest;
 pub mod discover_session;
+pub mod dispatch;
 pub mod git_ops;
+pub mod github_checks;
 pub mod heartbeat;
+pub mod mail;
 pub mod notification;
+pub mod patrol_checks;
+pub mod plugin_history;
+pub mod polecat_reports;
+pub mod pull_request;
+pub mod refinery_history;
+pub mod refinery_settings;
+pub mod rig_registry;
 pub mod run_plugin;
 pub mod spawn_agent;
@@ -20,6 +20,21 @@ pub async fn send_notification(
     _ctx: ActContext,
     input: NotificationInput,
 ) -> Result<NotificationResult, ActivityError> {
+    notify(&input)
+        .await
+        .map_err(|source| ActivityError::Retryable {
+            source,
+            explicit_delay: None,
+        })?;
+    Ok(NotificationResult {
+        channel: input.channel,
+        target: input.target,
+        sent: true,
+    })
+}
+
+/// Deliver a notification outside of an activity, e.g. from the CLI.
+pub async fn notify(input: &NotificationInput) -> anyhow::Result<()> {
     tracing::info!(
         "Notification [{}] to {}: {} — {}",
         input.channel,
@@ -50,12 +65,23 @@ pub async fn send_notification(
                 .json(&body)
                 .send()
                 .await
-                .map_err(|e| ActivityError::Retryable {
-                    source: anyhow::anyhow!("webhook failed: {e}"),
-                    explicit_delay: None,
-                })?;
+                .map_err(|e| anyhow::anyhow!("webhook failed: {e}"))?;
             tracing::info!("Notification webhook to {url}: {}", resp.status());
         }
+        "slack" => {
+            // An incoming webhook URL; Slack only reads `text`
+            let body = serde_json::json!({
+                "text": format!("*{}*\n{}", input.subject, input.message),
+            });
+            let resp = reqwest::Client::new()
+                .post(&input.target)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| anyhow::anyhow!("slack webhook failed: {e}"))?;
+            tracing::info!("Notification to slack: {}", resp.status());
+        }
+        "desktop" => desktop_notification(&input.subject, &input.message)?,
         "signal" => {
             tracing::info!("Would signal workflow {}", input.target);
         }
@@ -63,10 +89,61 @@ pub async fn send_notification(
             tracing::warn!("Unknown notification channel: {other}");
         }
     }
+    Ok(())
+}
 
-    Ok(NotificationResult {
-        channel: input.channel,
-        target: input.target,
-        sent: true,
+/// Pop up a notification on this machine's desktop.
+fn desktop_notification(subject: &str, message: &str) -> anyhow::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(message),
+            applescript_string(subject)
+        );
+        let mut command = std::process::Command::new("osascript");
+        command.args(["-e", &script]);
+        command
+    } else {
+        let mut command = std::process::Command::new("notify-send");
+        command.args([subject, message]);
+        command
+    };
+    let status = command
+        .status()
+        .map_err(|e| anyhow::anyhow!("desktop notification failed: {e}"))?;
+    if !status.success() {
+        anyhow::bail!("desktop notification failed: {status}");
+    }
+    Ok(())
+}
+
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationPolicy {
+    pub stale_after_secs: u64,
+    pub max_re_escalations: u32,
+}
+
+/// Read escalation thresholds from config so workflows stay deterministic.
+pub async fn load_escalation_policy(
+    _ctx: ActContext,
+    _input: (),
+) -> Result<EscalationPolicy, ActivityError> {
+    let thresholds = gtr_core::config::EscalationConfig::load().thresholds;
+    Ok(EscalationPolicy {
+        stale_after_secs: thresholds.stale_after_duration().as_secs(),
+        max_re_escalations: thresholds.max_re_escalations,
     })
 }
+
+/// Read the mayor's response rules, fresh each time so edits apply without
+/// restarting the mayor.
+pub async fn load_mayor_policy(
+    _ctx: ActContext,
+    _input: (),
+) -> Result<gtr_core::config::MayorPolicy, ActivityError> {
+    Ok(gtr_core::config::MayorPolicy::load())
+}
@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+
+use gtr_core::checks::{BuiltinCheck, ProjectKind};
+use serde::{Deserialize, Serialize};
+use temporalio_sdk::{ActContext, ActivityError};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectChecksInput {
+    /// Rig name or path to a checked-out tree
+    pub rig: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectChecksOutput {
+    pub work_dir: String,
+    pub kinds: Vec<ProjectKind>,
+    pub checks: Vec<BuiltinCheck>,
+}
+
+/// Resolve where a rig's code lives: an explicit path, the registered rig
+/// path, or the rig's refinery checkout — whichever has project markers.
+fn resolve_work_dir(rig: &str) -> PathBuf {
+    let explicit = PathBuf::from(rig);
+    if explicit.is_dir() {
+        return explicit;
+    }
+
+    let mut candidates = Vec::new();
+    if let Ok(rigs) = gtr_core::config::RigsConfig::load() {
+        if let Some(entry) = rigs.rigs.iter().find(|r| r.name == rig) {
+            candidates.push(entry.path.clone());
+        }
+    }
+    candidates.push(gtr_core::dirs::refinery_dir(rig));
+    candidates.push(gtr_core::dirs::rig_dir(rig));
+
+    candidates
+        .iter()
+        .find(|dir| !gtr_core::checks::detect_project_kinds(dir).is_empty())
+        .or_else(|| candidates.iter().find(|dir| dir.is_dir()))
+        .cloned()
+        .unwrap_or(explicit)
+}
+
+/// Detect the rig's project type and return the language-aware checks
+/// patrol should run there.
+pub async fn detect_patrol_checks(
+    _ctx: ActContext,
+    input: DetectChecksInput,
+) -> Result<DetectChecksOutput, ActivityError> {
+    let work_dir = resolve_work_dir(&input.rig);
+    let kinds = gtr_core::checks::detect_project_kinds(&work_dir);
+    let checks = gtr_core::checks::builtin_checks(&kinds);
+
+    tracing::info!(
+        "Patrol checks for rig {}: {:?} in {}",
+        input.rig,
+        kinds,
+        work_dir.display()
+    );
+
+    Ok(DetectChecksOutput {
+        work_dir: work_dir.to_string_lossy().to_string(),
+        kinds,
+        checks,
+    })
+}
@@ -0,0 +1,165 @@
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use gtr_core::plugin::PluginFailure;
+use serde::{Deserialize, Serialize};
+use temporalio_sdk::{ActContext, ActivityError};
+
+use crate::activities::run_plugin::RunPluginOutput;
+
+/// Runs kept per plugin; older ones are dropped as new ones come in.
+const MAX_STORED_RUNS: usize = 200;
+/// Output kept per stream, from the end — where the errors usually are.
+const MAX_OUTPUT_BYTES: usize = 4096;
+
+/// One run of a plugin as kept in its history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginRun {
+    pub plugin_name: String,
+    pub rig: String,
+    /// Workflow time the run was recorded
+    pub ran_at: DateTime<Utc>,
+    /// `None` if the plugin couldn't be run or was killed by a signal
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    pub stdout: String,
+    pub stderr: String,
+    /// Why the run failed, going by the plugin's `[fail_when]`
+    #[serde(default)]
+    pub failure: Option<String>,
+}
+
+impl PluginRun {
+    pub fn new(
+        plugin_name: &str,
+        rig: &str,
+        ran_at: DateTime<Utc>,
+        output: Option<&RunPluginOutput>,
+        failure: Option<&PluginFailure>,
+    ) -> Self {
+        Self {
+            plugin_name: plugin_name.to_string(),
+            rig: rig.to_string(),
+            ran_at,
+            exit_code: output.and_then(|o| o.exit_code),
+            duration_ms: output.map(|o| o.duration_ms).unwrap_or_default(),
+            stdout: output.map(|o| tail(&o.stdout)).unwrap_or_default(),
+            stderr: output.map(|o| tail(&o.stderr)).unwrap_or_default(),
+            failure: failure.map(|f| f.reason.clone()),
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Add a run to the plugin's history.
+pub async fn record_plugin_run(_ctx: ActContext, input: PluginRun) -> Result<(), ActivityError> {
+    let path = gtr_core::dirs::plugin_history_path(&input.plugin_name);
+    record(&path, input).map_err(|e| ActivityError::Retryable {
+        source: anyhow::anyhow!("record plugin run: {e}"),
+        explicit_delay: None,
+    })
+}
+
+fn record(path: &Path, run: PluginRun) -> anyhow::Result<()> {
+    let mut runs = load(path)?;
+    runs.push(run);
+    let start = runs.len().saturating_sub(MAX_STORED_RUNS);
+    let mut content = String::new();
+    for run in &runs[start..] {
+        content.push_str(&serde_json::to_string(run)?);
+        content.push('\n');
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    gtr_core::atomic::write_atomic(path, content)?;
+    Ok(())
+}
+
+/// Read a plugin's history, oldest first. Unparseable lines are skipped.
+pub fn load(path: &Path) -> anyhow::Result<Vec<PluginRun>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// The last [`MAX_OUTPUT_BYTES`] of `lines`, starting on a whole line where
+/// possible and marked with `…` when cut.
+fn tail(lines: &[String]) -> String {
+    let text = lines.join("\n");
+    if text.len() <= MAX_OUTPUT_BYTES {
+        return text;
+    }
+    let mut start = text.len() - MAX_OUTPUT_BYTES;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    let cut = &text[start..];
+    let cut = cut.split_once('\n').map_or(cut, |(_, rest)| rest);
+    format!("…\n{cut}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn run(n: usize, exit_code: Option<i32>) -> PluginRun {
+        let output = RunPluginOutput {
+            plugin_name: "lint".into(),
+            exit_code,
+            stdout: vec![format!("run {n}")],
+            stderr: vec![],
+            duration_ms: 1500,
+        };
+        let failure =
+            gtr_core::plugin::FailWhen::default().check(exit_code, 1500, &output.stdout, &[]);
+        let ran_at = DateTime::from_timestamp(n as i64, 0).unwrap();
+        PluginRun::new("lint", "gtr", ran_at, Some(&output), failure.as_ref())
+    }
+
+    #[test]
+    fn record_keeps_the_latest_runs() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("plugins").join("lint.jsonl");
+        record(&path, run(0, Some(1))).unwrap();
+        let runs = load(&path).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert!(!runs[0].passed());
+        assert_eq!(runs[0].failure.as_deref(), Some("exited with code 1"));
+        assert_eq!(runs[0].stdout, "run 0");
+        assert_eq!(runs[0].duration_ms, 1500);
+
+        for n in 1..=MAX_STORED_RUNS {
+            record(&path, run(n, Some(0))).unwrap();
+        }
+        let runs = load(&path).unwrap();
+        assert_eq!(runs.len(), MAX_STORED_RUNS);
+        assert_eq!(runs[0].stdout, "run 1");
+        assert!(runs[0].passed());
+    }
+
+    #[test]
+    fn tail_keeps_the_end_of_long_output() {
+        let short = vec!["one".to_string(), "two".to_string()];
+        assert_eq!(tail(&short), "one\ntwo");
+
+        let long: Vec<String> = (0..2000).map(|n| format!("line {n}")).collect();
+        let kept = tail(&long);
+        assert!(kept.starts_with("…\nline "));
+        assert!(kept.ends_with("line 1999"));
+        assert!(kept.len() <= MAX_OUTPUT_BYTES + "…\n".len());
+
+        let unrun = PluginRun::new("lint", "gtr", DateTime::UNIX_EPOCH, None, None);
+        assert_eq!(unrun.exit_code, None);
+        assert!(unrun.stdout.is_empty());
+    }
+}
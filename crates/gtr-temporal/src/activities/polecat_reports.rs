@@ -0,0 +1,106 @@
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use temporalio_sdk::{ActContext, ActivityError};
+
+use crate::signals::PolecatReportSignal;
+
+/// Reports kept in the store; older ones are dropped as new ones come in.
+const MAX_STORED_REPORTS: usize = 1000;
+
+/// A polecat report as kept in the report store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredPolecatReport {
+    /// When the mayor received it
+    pub reported_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub report: PolecatReportSignal,
+}
+
+impl StoredPolecatReport {
+    /// Anything but a clean finish.
+    pub fn failed(&self) -> bool {
+        self.report.status != "done"
+    }
+}
+
+/// Add a report the mayor received to the report store.
+pub async fn record_polecat_report(
+    _ctx: ActContext,
+    input: StoredPolecatReport,
+) -> Result<(), ActivityError> {
+    record(&gtr_core::dirs::polecat_reports_path(), input).map_err(|e| ActivityError::Retryable {
+        source: anyhow::anyhow!("record polecat report: {e}"),
+        explicit_delay: None,
+    })
+}
+
+fn record(path: &Path, report: StoredPolecatReport) -> anyhow::Result<()> {
+    let mut reports = load(path)?;
+    reports.push(report);
+    let start = reports.len().saturating_sub(MAX_STORED_REPORTS);
+    let mut content = String::new();
+    for report in &reports[start..] {
+        content.push_str(&serde_json::to_string(report)?);
+        content.push('\n');
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    gtr_core::atomic::write_atomic(path, content)?;
+    Ok(())
+}
+
+/// Read the report store, oldest first. Unparseable lines are skipped.
+pub fn load(path: &Path) -> anyhow::Result<Vec<StoredPolecatReport>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn stored(n: usize, status: &str) -> StoredPolecatReport {
+        StoredPolecatReport {
+            reported_at: DateTime::from_timestamp(n as i64, 0).unwrap(),
+            report: PolecatReportSignal {
+                polecat_id: format!("gtr-polecat-p{n}"),
+                name: format!("p{n}"),
+                rig: "gtr".into(),
+                work_item_id: format!("wi-{n}"),
+                branch: format!("polecat/p{n}"),
+                status: status.into(),
+                summary: None,
+                exit_reason: "completed".into(),
+            },
+        }
+    }
+
+    #[test]
+    fn record_keeps_the_latest_reports() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("polecat_reports.jsonl");
+        record(&path, stored(0, "failed")).unwrap();
+        let reports = load(&path).unwrap();
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].failed());
+        assert_eq!(reports[0].report.work_item_id, "wi-0");
+
+        for n in 1..=MAX_STORED_REPORTS {
+            record(&path, stored(n, "done")).unwrap();
+        }
+        let reports = load(&path).unwrap();
+        assert_eq!(reports.len(), MAX_STORED_REPORTS);
+        assert_eq!(reports[0].report.work_item_id, "wi-1");
+        assert!(!reports[0].failed());
+    }
+}
@@ -0,0 +1,300 @@
+use gtr_core::config::{ForgeProvider, PullRequestConfig};
+use reqwest::{RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use temporalio_sdk::{ActContext, ActivityError};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePullRequestInput {
+    pub config: PullRequestConfig,
+    pub branch: String,
+    pub target: String,
+    pub title: String,
+    pub body: String,
+}
+
+/// A pull request on GitHub or a merge request on GitLab.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullRequestRef {
+    /// PR number on GitHub, MR iid on GitLab
+    pub number: u64,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullRequestStatusInput {
+    pub config: PullRequestConfig,
+    pub number: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PullRequestState {
+    Open,
+    Merged,
+    Closed,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubPull {
+    number: u64,
+    html_url: String,
+    state: String,
+    merged_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitlabMergeRequest {
+    iid: u64,
+    web_url: String,
+    state: String,
+}
+
+/// Open a pull request from `branch` into `target`. If one is already open
+/// for the branch (e.g. the entry is being retried), that one is returned.
+pub async fn create_pull_request(
+    _ctx: ActContext,
+    input: CreatePullRequestInput,
+) -> Result<PullRequestRef, ActivityError> {
+    let forge = Forge::new(&input.config)?;
+    let pr = match input.config.provider {
+        ForgeProvider::Github => {
+            let url = format!("{}/repos/{}/pulls", forge.base, input.config.repo);
+            let body = serde_json::json!({
+                "title": input.title,
+                "head": input.branch,
+                "base": input.target,
+                "body": input.body,
+            });
+            let resp = forge.send(forge.client.post(&url).json(&body)).await?;
+            // 422: a PR for this head already exists
+            let pull: GithubPull = if resp.status() == StatusCode::UNPROCESSABLE_ENTITY {
+                let owner = input.config.repo.split('/').next().unwrap_or_default();
+                let open: Vec<GithubPull> = forge
+                    .json(forge.client.get(&url).query(&[
+                        ("head", format!("{owner}:{}", input.branch)),
+                        ("state", "open".to_string()),
+                    ]))
+                    .await?;
+                open.into_iter().next().ok_or_else(|| {
+                    ActivityError::NonRetryable(anyhow::anyhow!(
+                        "GitHub rejected the pull request for {}",
+                        input.branch
+                    ))
+                })?
+            } else {
+                parse(check_status(resp, &url)?).await?
+            };
+            PullRequestRef {
+                number: pull.number,
+                url: pull.html_url,
+            }
+        }
+        ForgeProvider::Gitlab => {
+            let url = format!(
+                "{}/projects/{}/merge_requests",
+                forge.base,
+                gitlab_project(&input.config.repo)
+            );
+            let body = serde_json::json!({
+                "title": input.title,
+                "source_branch": input.branch,
+                "target_branch": input.target,
+                "description": input.body,
+                "remove_source_branch": true,
+            });
+            let resp = forge.send(forge.client.post(&url).json(&body)).await?;
+            // 409: an MR for this source branch already exists
+            let mr: GitlabMergeRequest = if resp.status() == StatusCode::CONFLICT {
+                let open: Vec<GitlabMergeRequest> = forge
+                    .json(forge.client.get(&url).query(&[
+                        ("source_branch", input.branch.as_str()),
+                        ("target_branch", input.target.as_str()),
+                        ("state", "opened"),
+                    ]))
+                    .await?;
+                open.into_iter().next().ok_or_else(|| {
+                    ActivityError::NonRetryable(anyhow::anyhow!(
+                        "GitLab rejected the merge request for {}",
+                        input.branch
+                    ))
+                })?
+            } else {
+                parse(check_status(resp, &url)?).await?
+            };
+            PullRequestRef {
+                number: mr.iid,
+                url: mr.web_url,
+            }
+        }
+    };
+    tracing::info!(
+        "Opened pull request {} ({} → {})",
+        pr.url,
+        input.branch,
+        input.target
+    );
+    Ok(pr)
+}
+
+/// Whether a pull request is still open, merged, or closed unmerged.
+pub async fn pull_request_status(
+    _ctx: ActContext,
+    input: PullRequestStatusInput,
+) -> Result<PullRequestState, ActivityError> {
+    let forge = Forge::new(&input.config)?;
+    let state = match input.config.provider {
+        ForgeProvider::Github => {
+            let url = format!(
+                "{}/repos/{}/pulls/{}",
+                forge.base, input.config.repo, input.number
+            );
+            let pull: GithubPull = forge.json(forge.client.get(&url)).await?;
+            github_state(&pull)
+        }
+        ForgeProvider::Gitlab => {
+            let url = format!(
+                "{}/projects/{}/merge_requests/{}",
+                forge.base,
+                gitlab_project(&input.config.repo),
+                input.number
+            );
+            let mr: GitlabMergeRequest = forge.json(forge.client.get(&url)).await?;
+            gitlab_state(&mr.state)
+        }
+    };
+    Ok(state)
+}
+
+struct Forge {
+    client: reqwest::Client,
+    provider: ForgeProvider,
+    token: String,
+    base: String,
+}
+
+impl Forge {
+    fn new(config: &PullRequestConfig) -> Result<Self, ActivityError> {
+        let token = std::env::var(config.token_env()).map_err(|_| {
+            ActivityError::NonRetryable(anyhow::anyhow!(
+                "forge token env var {} is not set",
+                config.token_env()
+            ))
+        })?;
+        Ok(Self {
+            client: reqwest::Client::new(),
+            provider: config.provider,
+            token,
+            base: config.api_url().to_string(),
+        })
+    }
+
+    async fn send(&self, req: RequestBuilder) -> Result<reqwest::Response, ActivityError> {
+        let req = match self.provider {
+            ForgeProvider::Github => req
+                .bearer_auth(&self.token)
+                .header("Accept", "application/vnd.github+json"),
+            ForgeProvider::Gitlab => req.header("PRIVATE-TOKEN", &self.token),
+        };
+        req.header("User-Agent", "gtr-refinery")
+            .send()
+            .await
+            .map_err(|e| ActivityError::Retryable {
+                source: anyhow::anyhow!("forge request failed: {e}"),
+                explicit_delay: None,
+            })
+    }
+
+    async fn json<T: serde::de::DeserializeOwned>(
+        &self,
+        req: RequestBuilder,
+    ) -> Result<T, ActivityError> {
+        let resp = self.send(req).await?;
+        let url = resp.url().to_string();
+        parse(check_status(resp, &url)?).await
+    }
+}
+
+/// Client errors (bad token, unknown repo, invalid branch) won't fix
+/// themselves; anything else is retried.
+fn check_status(resp: reqwest::Response, url: &str) -> Result<reqwest::Response, ActivityError> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+    let err = anyhow::anyhow!("{url} returned {status}");
+    if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS {
+        Err(ActivityError::NonRetryable(err))
+    } else {
+        Err(ActivityError::Retryable {
+            source: err,
+            explicit_delay: None,
+        })
+    }
+}
+
+async fn parse<T: serde::de::DeserializeOwned>(
+    resp: reqwest::Response,
+) -> Result<T, ActivityError> {
+    resp.json::<T>()
+        .await
+        .map_err(|e| ActivityError::NonRetryable(anyhow::anyhow!("unexpected forge response: {e}")))
+}
+
+/// GitLab addresses projects by URL-encoded path.
+fn gitlab_project(repo: &str) -> String {
+    repo.replace('/', "%2F")
+}
+
+fn github_state(pull: &GithubPull) -> PullRequestState {
+    if pull.merged_at.is_some() {
+        PullRequestState::Merged
+    } else if pull.state == "closed" {
+        PullRequestState::Closed
+    } else {
+        PullRequestState::Open
+    }
+}
+
+/// `locked` is a transient state while GitLab performs the merge.
+fn gitlab_state(state: &str) -> PullRequestState {
+    match state {
+        "merged" => PullRequestState::Merged,
+        "closed" => PullRequestState::Closed,
+        _ => PullRequestState::Open,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pull(state: &str, merged_at: Option<&str>) -> GithubPull {
+        GithubPull {
+            number: 7,
+            html_url: "https://github.com/acme/app/pull/7".into(),
+            state: state.into(),
+            merged_at: merged_at.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn github_closed_unmerged_is_closed() {
+        assert_eq!(github_state(&pull("open", None)), PullRequestState::Open);
+        assert_eq!(
+            github_state(&pull("closed", Some("2024-05-01T12:00:00Z"))),
+            PullRequestState::Merged
+        );
+        assert_eq!(
+            github_state(&pull("closed", None)),
+            PullRequestState::Closed
+        );
+    }
+
+    #[test]
+    fn gitlab_states_and_project_path() {
+        assert_eq!(gitlab_state("opened"), PullRequestState::Open);
+        assert_eq!(gitlab_state("locked"), PullRequestState::Open);
+        assert_eq!(gitlab_state("merged"), PullRequestState::Merged);
+        assert_eq!(gitlab_project("acme/platform/app"), "acme%2Fplatform%2Fapp");
+    }
+}
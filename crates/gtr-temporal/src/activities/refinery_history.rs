@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use temporalio_sdk::{ActContext, ActivityError};
+
+use crate::signals::RefineryEntry;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordEntryInput {
+    pub rig: String,
+    #[serde(default)]
+    pub queue: String,
+    pub entry: RefineryEntry,
+}
+
+/// Append a finished entry to the queue's refinery history.
+pub async fn record_refinery_entry(
+    _ctx: ActContext,
+    input: RecordEntryInput,
+) -> Result<(), ActivityError> {
+    let path = gtr_core::dirs::refinery_history_path(&input.rig, &input.queue);
+    append(&path, &input.entry).map_err(|e| ActivityError::Retryable {
+        source: anyhow::anyhow!("record refinery entry: {e}"),
+        explicit_delay: None,
+    })
+}
+
+fn append(path: &Path, entry: &RefineryEntry) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// Read a refinery history file. Unparseable lines are skipped.
+pub fn load(path: &Path) -> anyhow::Result<Vec<RefineryEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Aggregate metrics over processed refinery entries.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RefineryStats {
+    pub processed: usize,
+    pub merged: usize,
+    pub validated: usize,
+    pub retried: usize,
+    /// Merges per hour between the first enqueue and the last finish
+    pub merges_per_hour: Option<f64>,
+    pub avg_time_to_merge_secs: Option<u64>,
+    pub avg_test_secs: Option<u64>,
+    /// Count of each non-success status
+    pub failures: BTreeMap<String, usize>,
+}
+
+impl RefineryStats {
+    pub fn compute(entries: &[RefineryEntry]) -> Self {
+        let mut stats = RefineryStats {
+            processed: entries.len(),
+            ..Default::default()
+        };
+        let mut merge_times = Vec::new();
+        let mut test_times = Vec::new();
+
+        for entry in entries {
+            if entry.status.starts_with("merged") {
+                stats.merged += 1;
+                if let (Some(start), Some(end)) = (entry.enqueued_at, entry.finished_at) {
+                    merge_times.push(end.saturating_sub(start));
+                }
+            } else if entry.status == "validated" {
+                stats.validated += 1;
+            } else {
+                *stats.failures.entry(entry.status.clone()).or_default() += 1;
+            }
+            if entry.attempts > 1 {
+                stats.retried += 1;
+            }
+            test_times.extend(entry.test_duration_secs);
+        }
+
+        stats.avg_time_to_merge_secs = average(&merge_times);
+        stats.avg_test_secs = average(&test_times);
+
+        let first = entries.iter().filter_map(|e| e.enqueued_at).min();
+        let last = entries.iter().filter_map(|e| e.finished_at).max();
+        if let (Some(first), Some(last)) = (first, last) {
+            if last > first {
+                stats.merges_per_hour = Some(stats.merged as f64 * 3600.0 / (last - first) as f64);
+            }
+        }
+        stats
+    }
+}
+
+fn average(values: &[u64]) -> Option<u64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<u64>() / values.len() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(status: &str, enqueued: u64, finished: u64, attempts: u32) -> RefineryEntry {
+        RefineryEntry {
+            work_item_id: format!("wi-{enqueued}"),
+            branch: "b".into(),
+            priority: 2,
+            status: status.into(),
+            validate_only: false,
+            timeout_secs: None,
+            max_retries: None,
+            attempts,
+            enqueued_at: Some(enqueued),
+            started_at: Some(enqueued),
+            finished_at: Some(finished),
+            test_duration_secs: Some(60),
+            target_branch: None,
+            pull_request_url: None,
+            pull_request_number: None,
+            pull_request_deadline: None,
+            awaiting_approval: None,
+            approved: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn stats_over_history() {
+        let entries = vec![
+            entry("merged", 0, 600, 1),
+            entry("merged_push_failed", 600, 1800, 2),
+            entry("conflict", 1800, 3600, 1),
+            entry("conflict", 2000, 2100, 1),
+        ];
+        let stats = RefineryStats::compute(&entries);
+        assert_eq!(stats.processed, 4);
+        assert_eq!(stats.merged, 2);
+        assert_eq!(stats.retried, 1);
+        assert_eq!(stats.avg_time_to_merge_secs, Some(900));
+        assert_eq!(stats.avg_test_secs, Some(60));
+        assert_eq!(stats.merges_per_hour, Some(2.0));
+        assert_eq!(stats.failures.get("conflict"), Some(&2));
+    }
+
+    #[test]
+    fn history_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("refinery").join("gt.jsonl");
+        append(&path, &entry("merged", 0, 10, 1)).unwrap();
+        append(&path, &entry("tests_failed", 10, 20, 1)).unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[1].status, "tests_failed");
+        assert!(load(&dir.path().join("missing.jsonl")).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use gtr_core::config::{ApprovalPolicy, MergeStrategy, PullRequestConfig, DEFAULT_BRANCH};
+use serde::{Deserialize, Serialize};
+use temporalio_sdk::{ActContext, ActivityError};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefinerySettingsInput {
+    pub rig: String,
+    pub repo_path: String,
+    /// Named merge queue; empty for the rig's default queue
+    #[serde(default)]
+    pub queue: String,
+}
+
+/// Per-rig refinery behaviour from rigs.toml.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefinerySettings {
+    pub merge_strategy: MergeStrategy,
+    pub squash_message: Option<String>,
+    pub default_branch: String,
+    /// `None` means `cargo test`
+    pub test_command: Option<Vec<String>>,
+    /// `None` means merge locally and push the target
+    #[serde(default)]
+    pub pull_request: Option<PullRequestConfig>,
+    /// Town-wide `[approvals]`, the same for every rig
+    #[serde(default)]
+    pub approvals: ApprovalPolicy,
+}
+
+impl Default for RefinerySettings {
+    fn default() -> Self {
+        Self {
+            merge_strategy: MergeStrategy::default(),
+            squash_message: None,
+            default_branch: DEFAULT_BRANCH.to_string(),
+            test_command: None,
+            pull_request: None,
+            approvals: ApprovalPolicy::default(),
+        }
+    }
+}
+
+/// Look up the rig's entry in rigs.toml, matching by rig name or by the
+/// refinery's repo path, and apply the named queue's overrides. Unregistered
+/// rigs get the defaults.
+pub async fn resolve_refinery_settings(
+    _ctx: ActContext,
+    input: RefinerySettingsInput,
+) -> Result<RefinerySettings, ActivityError> {
+    let rigs = gtr_core::config::RigsConfig::load()
+        .map_err(|e| ActivityError::NonRetryable(anyhow::anyhow!("load rigs.toml: {e}")))?;
+    let repo_path = Path::new(&input.repo_path);
+    let settings = rigs
+        .rigs
+        .into_iter()
+        .find(|r| (!input.rig.is_empty() && r.name == input.rig) || r.path == repo_path)
+        .map(|mut r| {
+            let queue = r.queues.remove(&input.queue).unwrap_or_default();
+            RefinerySettings {
+                merge_strategy: queue.merge_strategy.unwrap_or(r.merge_strategy),
+                squash_message: r.squash_message,
+                default_branch: queue.default_branch.unwrap_or(r.default_branch),
+                test_command: queue.test_command,
+                pull_request: queue.pull_request.or(r.pull_request),
+                approvals: ApprovalPolicy::default(),
+            }
+        })
+        .unwrap_or_default();
+    Ok(RefinerySettings {
+        approvals: ApprovalPolicy::load(),
+        ..settings
+    })
+}
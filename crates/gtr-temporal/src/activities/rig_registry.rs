@@ -0,0 +1,39 @@
+use gtr_core::config::{ApprovalPolicy, RigsConfig};
+use temporalio_sdk::{ActContext, ActivityError};
+use temporalio_sdk_core::WorkflowClientTrait;
+
+/// The `[approvals]` table of `town.toml`.
+pub async fn load_approval_policy(
+    _ctx: ActContext,
+    _input: (),
+) -> Result<ApprovalPolicy, ActivityError> {
+    Ok(ApprovalPolicy::load())
+}
+
+/// Delete a stopped rig: end its dormant rig workflow, drop it from
+/// rigs.toml and remove its directory — the bare clone, worktrees and
+/// plugins.
+pub async fn remove_rig(_ctx: ActContext, rig: String) -> Result<(), ActivityError> {
+    let retryable = |source: anyhow::Error| ActivityError::Retryable {
+        source,
+        explicit_delay: None,
+    };
+    let client = crate::worker::connect().await.map_err(retryable)?;
+    let rig_id = gtr_core::state::rig_workflow_id(&rig);
+    if let Err(e) = client
+        .terminate_workflow_execution(rig_id.clone(), None)
+        .await
+    {
+        tracing::debug!("Rig workflow {rig_id} not terminated: {e}");
+    }
+
+    let mut rigs = RigsConfig::load().map_err(retryable)?;
+    rigs.remove(&rig);
+    rigs.save().map_err(retryable)?;
+    let dir = gtr_core::dirs::rig_dir(&rig);
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).map_err(|e| retryable(e.into()))?;
+    }
+    tracing::info!("Removed rig {rig} ({})", dir.display());
+    Ok(())
+}
@@ -1,8 +1,11 @@
+use std::collections::HashMap;
 use std::process::Stdio;
+use std::time::Instant;
 
+use gtr_core::plugin::PluginDef;
 use serde::{Deserialize, Serialize};
 use temporalio_sdk::{ActContext, ActivityError};
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::Command;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,6 +14,8 @@ pub struct RunPluginInput {
     pub command: String,
     pub args: Vec<String>,
     pub work_dir: Option<String>,
+    #[serde(default)]
+    pub env: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +23,50 @@ pub struct RunPluginOutput {
     pub plugin_name: String,
     pub exit_code: Option<i32>,
     pub stdout: Vec<String>,
+    #[serde(default)]
+    pub stderr: Vec<String>,
+    #[serde(default)]
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoverPluginsInput {
+    pub rig: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventPluginsInput {
+    pub rig: String,
+    pub event: String,
+}
+
+/// The plugins in `~/.gtr/config/plugins/` merged with the rig's own in
+/// `~/.gtr/rigs/<rig>/plugins/`, in name order. Files that don't parse are
+/// skipped.
+pub async fn discover_plugins(
+    _ctx: ActContext,
+    input: DiscoverPluginsInput,
+) -> Result<Vec<PluginDef>, ActivityError> {
+    let global_dir = gtr_core::dirs::plugins_dir();
+    let rig_dir = gtr_core::dirs::rig_plugin_dir(&input.rig);
+    gtr_core::plugin::rig_plugins(&global_dir, &rig_dir).map_err(|e| ActivityError::Retryable {
+        source: anyhow::anyhow!("failed to read plugins for rig {}: {e}", input.rig),
+        explicit_delay: None,
+    })
+}
+
+/// Plugins in the rig's plugin directory gated on `event`.
+pub async fn discover_event_plugins(
+    _ctx: ActContext,
+    input: EventPluginsInput,
+) -> Result<Vec<PluginDef>, ActivityError> {
+    let dir = gtr_core::dirs::rig_plugin_dir(&input.rig);
+    gtr_core::plugin::event_plugins(&dir, &input.event).map_err(|e| {
+        ActivityError::NonRetryable(anyhow::anyhow!(
+            "failed to read plugins in {}: {e}",
+            dir.display()
+        ))
+    })
 }
 
 pub async fn run_plugin(
@@ -25,28 +74,26 @@ pub async fn run_plugin(
     input: RunPluginInput,
 ) -> Result<RunPluginOutput, ActivityError> {
     let work_dir = input.work_dir.unwrap_or_else(|| ".".to_string());
+    let started = Instant::now();
 
     let mut child = Command::new(&input.command)
         .args(&input.args)
+        .envs(input.env.iter().flatten())
         .current_dir(&work_dir)
         .stdout(Stdio::piped())
-        .stderr(Stdio::null())
+        .stderr(Stdio::piped())
         .spawn()
         .map_err(|e| ActivityError::Retryable {
             source: anyhow::anyhow!("failed to spawn plugin {}: {e}", input.plugin_name),
             explicit_delay: None,
         })?;
 
-    let stdout = child.stdout.take().ok_or_else(|| {
-        ActivityError::NonRetryable(anyhow::anyhow!("failed to capture stdout"))
-    })?;
-
-    let mut reader = BufReader::new(stdout).lines();
-    let mut output_lines = Vec::new();
-
-    while let Ok(Some(line)) = reader.next_line().await {
-        output_lines.push(line);
-    }
+    let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        return Err(ActivityError::NonRetryable(anyhow::anyhow!(
+            "failed to capture plugin output"
+        )));
+    };
+    let (output_lines, error_lines) = tokio::join!(read_lines(stdout), read_lines(stderr));
 
     let status = child.wait().await.map_err(|e| ActivityError::Retryable {
         source: anyhow::anyhow!("failed to wait for plugin: {e}"),
@@ -63,5 +110,16 @@ pub async fn run_plugin(
         plugin_name: input.plugin_name,
         exit_code,
         stdout: output_lines,
+        stderr: error_lines,
+        duration_ms: started.elapsed().as_millis() as u64,
     })
 }
+
+async fn read_lines(output: impl AsyncRead + Unpin) -> Vec<String> {
+    let mut reader = BufReader::new(output).lines();
+    let mut lines = Vec::new();
+    while let Ok(Some(line)) = reader.next_line().await {
+        lines.push(line);
+    }
+    lines
+}
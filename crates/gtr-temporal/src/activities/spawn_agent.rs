@@ -1,15 +1,20 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+use gtr_core::atomic::StateFile;
+use gtr_core::config::{LaunchOptions, ResourceLimits, RigEntry, RigsConfig, RuntimesConfig};
 use serde::{Deserialize, Serialize};
 use temporalio_sdk::{ActContext, ActivityError};
 
 use crate::pty;
 
+/// Records the runtime in the agent's env (and so its env.json) for respawns.
+const RUNTIME_ENV: &str = "GTR_RUNTIME";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpawnAgentInput {
     pub agent_id: String,
-    pub runtime: String,    // "claude" or "shell"
+    pub runtime: String,    // a runtimes.toml entry, or "mock"
     pub work_dir: String,
     pub role: String,
     pub rig: Option<String>,
@@ -17,6 +22,16 @@ pub struct SpawnAgentInput {
     pub env_extra: Option<HashMap<String, String>>,
     #[serde(default)]
     pub resume_session_id: Option<String>,
+    /// Polecats without explicit limits get their rig's `polecat_limits`
+    #[serde(default)]
+    pub limits: Option<ResourceLimits>,
+    /// Unset fields fall back to the rig's `[rigs.roles.<role>]` settings
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub permission_mode: Option<String>,
+    #[serde(default)]
+    pub allowed_tools: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,12 +45,15 @@ pub async fn spawn_agent(
     _ctx: ActContext,
     input: SpawnAgentInput,
 ) -> Result<SpawnAgentOutput, ActivityError> {
+    spawn(input).map_err(ActivityError::NonRetryable)
+}
+
+/// Spawn an agent's tmux session. This is the body of the `spawn_agent`
+/// activity, also used directly by `rgt agents restart` and `rgt attach`.
+pub fn spawn(input: SpawnAgentInput) -> anyhow::Result<SpawnAgentOutput> {
     // Check if already running
     if pty::is_alive(&input.agent_id) {
-        return Err(ActivityError::NonRetryable(anyhow::anyhow!(
-            "Agent '{}' is already running",
-            input.agent_id
-        )));
+        anyhow::bail!("Agent '{}' is already running", input.agent_id);
     }
 
     // Clean up any stale runtime dir
@@ -45,6 +63,7 @@ pub async fn spawn_agent(
     let mut env = HashMap::new();
     env.insert("GTR_AGENT".into(), input.agent_id.clone());
     env.insert("GTR_ROLE".into(), input.role.clone());
+    env.insert(RUNTIME_ENV.into(), input.runtime.clone());
     if let Some(rig) = &input.rig {
         env.insert("GTR_RIG".into(), rig.clone());
     }
@@ -61,62 +80,114 @@ pub async fn spawn_agent(
         env.insert("RGT_BIN".into(), current_exe.to_string_lossy().to_string());
     }
 
+    // Rig-level defaults for the agent's role
+    let role = gtr_core::state::WorkflowId::parse(&input.agent_id).and_then(|id| id.role());
+    let rig_entry = input.rig.as_deref().and_then(rig_entry);
+    let role_settings = rig_entry
+        .as_ref()
+        .zip(role)
+        .and_then(|(rig, role)| rig.roles.get(role).cloned())
+        .unwrap_or_default();
+
+    // A fresh session gets the user's prompt template for the role, if any;
+    // resumed sessions keep the workflow's continuation prompt
+    let prompt = match &input.initial_prompt {
+        Some(builtin) if input.resume_session_id.is_none() => Some(
+            role_prompt(role.unwrap_or(&input.role), &input).unwrap_or_else(|| builtin.clone()),
+        ),
+        other => other.clone(),
+    };
+
+    // Determine program and args based on runtime
+    let (program, args) = if input.runtime == "mock" {
+        // Scripted stand-in for an LLM CLI; the prompt is ignored
+        let agent_script = input
+            .env_extra
+            .as_ref()
+            .and_then(|extra| extra.get(gtr_core::mock::MOCK_SCRIPT_ENV));
+        let path = gtr_core::mock::script_path(agent_script.map(String::as_str));
+        let script = gtr_core::mock::MockScript::load(&path).map_err(|e| {
+            anyhow::anyhow!("Failed to load mock agent script {}: {e}", path.display())
+        })?;
+        ("sh".to_string(), vec!["-c".to_string(), script.to_shell()])
+    } else {
+        let runtimes = RuntimesConfig::load()
+            .map_err(|e| anyhow::anyhow!("Failed to load runtimes.toml: {e}"))?;
+        let Some(runtime) = runtimes.get(&input.runtime) else {
+            anyhow::bail!(
+                "Unknown runtime: '{}'. Supported: {}, mock",
+                input.runtime,
+                runtimes.names().join(", ")
+            );
+        };
+        env.extend(runtime.env.clone());
+        let allowed_tools = input
+            .allowed_tools
+            .as_ref()
+            .or(role_settings.allowed_tools.as_ref());
+        let args = runtime.command_args(LaunchOptions {
+            prompt: prompt.as_deref(),
+            resume_session_id: input.resume_session_id.as_deref(),
+            model: input.model.as_deref().or(role_settings.model.as_deref()),
+            permission_mode: input
+                .permission_mode
+                .as_deref()
+                .or(role_settings.permission_mode.as_deref()),
+            allowed_tools: allowed_tools.map(Vec::as_slice).unwrap_or_default(),
+        });
+        (runtime.program.clone(), args)
+    };
+
     if let Some(extra) = &input.env_extra {
         env.extend(extra.clone());
     }
 
-    // Determine program and args based on runtime
-    let (program, args) = match input.runtime.as_str() {
-        "claude" => {
-            let mut args = vec![
-                "--dangerously-skip-permissions".to_string(),
-                "--disable-slash-commands".to_string(),
-                "--disallowedTools=Skill,AskUserQuestion,EnterPlanMode".to_string(),
-            ];
-            if let Some(session_id) = &input.resume_session_id {
-                args.push("--resume".to_string());
-                args.push(session_id.clone());
-            }
-            if let Some(prompt) = &input.initial_prompt {
-                args.push(prompt.clone());
-            }
-            ("claude".to_string(), args)
-        }
-        "shell" => {
-            let args = if let Some(prompt) = &input.initial_prompt {
-                vec!["-c".to_string(), prompt.clone()]
-            } else {
-                vec![]
-            };
-            ("sh".to_string(), args)
-        }
-        other => {
-            return Err(ActivityError::NonRetryable(anyhow::anyhow!(
-                "Unknown runtime: '{other}'. Supported: claude, shell"
-            )));
-        }
-    };
+    // Agents of a remote rig run on its host; the work dir is created there
+    let host = rig_entry.as_ref().and_then(|rig| rig.host.clone());
 
     // Ensure work directory exists
     let work_dir = PathBuf::from(&input.work_dir);
-    std::fs::create_dir_all(&work_dir).map_err(|e| {
-        ActivityError::NonRetryable(anyhow::anyhow!("Failed to create work dir: {e}"))
-    })?;
+    if host.is_none() {
+        std::fs::create_dir_all(&work_dir)
+            .map_err(|e| anyhow::anyhow!("Failed to create work dir: {e}"))?;
+    }
+
+    let limits = input
+        .limits
+        .clone()
+        .or_else(|| match (role, &rig_entry) {
+            (Some(gtr_core::state::roles::POLECAT), Some(rig)) => rig.polecat_limits.clone(),
+            _ => None,
+        })
+        .unwrap_or_default();
 
     // Spawn with PTY and socket server
-    let pid = pty::spawn_with_server(
+    let pid = pty::spawn_limited(
         &input.agent_id,
         &program,
         &args,
         &work_dir,
         &env,
+        &limits,
+        host.as_deref(),
     )
-    .map_err(|e| {
-        ActivityError::NonRetryable(anyhow::anyhow!("Failed to spawn agent: {e}"))
-    })?;
+    .map_err(|e| anyhow::anyhow!("Failed to spawn agent: {e}"))?;
 
     let tmux_session = pty::tmux_session_name(&input.agent_id);
 
+    // Label the session so tmux and `rgt sessions` show who this is.
+    let meta = pty::SessionMetadata {
+        role: role
+            .map(str::to_string)
+            .unwrap_or_else(|| input.role.clone()),
+        rig: input.rig.clone(),
+        work_item: env.get("GTR_WORK_ITEM").cloned(),
+        status: None,
+    };
+    if let Err(e) = pty::set_session_metadata(&input.agent_id, &meta) {
+        tracing::warn!("Failed to set session metadata for '{}': {e}", input.agent_id);
+    }
+
     tracing::info!(
         "Spawned agent '{}' (PID {}, runtime {}, session {})",
         input.agent_id,
@@ -131,3 +202,75 @@ pub async fn spawn_agent(
         tmux_session,
     })
 }
+
+/// Render `~/.gtr/config/prompts/<role>.md` for this spawn. Templates can use
+/// `{{agent}}`, `{{role}}`, `{{rig}}`, `{{work_item}}` and `{{branch}}`.
+fn role_prompt(role: &str, input: &SpawnAgentInput) -> Option<String> {
+    let extra = |key: &str| {
+        input
+            .env_extra
+            .as_ref()
+            .and_then(|extra| extra.get(key))
+            .cloned()
+            .unwrap_or_default()
+    };
+    let vars = HashMap::from([
+        ("agent".to_string(), input.agent_id.clone()),
+        ("role".to_string(), role.to_string()),
+        ("rig".to_string(), input.rig.clone().unwrap_or_default()),
+        ("work_item".to_string(), extra("GTR_WORK_ITEM")),
+        ("branch".to_string(), extra("GTR_BRANCH")),
+    ]);
+    gtr_core::prompts::render(role, &vars)
+}
+
+/// Rebuild the spawn input for an agent from the `env.json` its last spawn
+/// left in its runtime dir, resuming its latest runtime session if one exists.
+/// Used to restart an agent outside of any workflow.
+pub fn respawn_input(agent_id: &str, prompt: &str) -> anyhow::Result<SpawnAgentInput> {
+    let env_path = pty::runtime_dir(agent_id).join("env.json");
+    let mut env: HashMap<String, String> = match gtr_core::atomic::read_json(&env_path)? {
+        StateFile::Valid(env) => env,
+        StateFile::Corrupt { quarantined } => anyhow::bail!(
+            "env.json for '{agent_id}' was corrupt (kept at {})",
+            quarantined.display()
+        ),
+        StateFile::Missing => anyhow::bail!("Agent '{agent_id}' has no saved env.json"),
+    };
+
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+    let work_dir = env
+        .remove("__GTR_WORK_DIR")
+        .unwrap_or_else(|| format!("{home}/.gtr"));
+    let runtime = env
+        .remove(RUNTIME_ENV)
+        .unwrap_or_else(|| "claude".to_string());
+    let resume_session_id = if runtime == "mock" {
+        None
+    } else {
+        crate::activities::discover_session::latest_session_id(&work_dir, None)
+    };
+
+    Ok(SpawnAgentInput {
+        agent_id: agent_id.to_string(),
+        role: env.remove("GTR_ROLE").unwrap_or_default(),
+        rig: env.remove("GTR_RIG"),
+        runtime,
+        work_dir,
+        initial_prompt: Some(prompt.to_string()),
+        env_extra: Some(env),
+        resume_session_id,
+        limits: None,
+        model: None,
+        permission_mode: None,
+        allowed_tools: None,
+    })
+}
+
+fn rig_entry(rig: &str) -> Option<RigEntry> {
+    RigsConfig::load()
+        .ok()?
+        .rigs
+        .into_iter()
+        .find(|r| r.name == rig)
+}
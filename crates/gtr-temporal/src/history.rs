@@ -0,0 +1,991 @@
+use gtr_core::types::WorkItem;
+use serde::de::DeserializeOwned;
+use temporalio_common::protos::temporal::api::history::v1::history_event::Attributes;
+use temporalio_common::protos::temporal::api::history::v1::HistoryEvent;
+use temporalio_sdk_core::WorkflowClientTrait;
+
+use crate::signals::{
+    AddWorkItemSignal, ApprovalDecision, ApprovalRequest, AssignSignal, ConvoyClosedSignal,
+    ConvoyProgressSignal, EscalationUpdateSignal, FailSignal, HeartbeatSignal, HookSignal,
+    MaintenanceSignal, MayorState, MolStepDoneSignal, MolStepFailSignal, MolStepState,
+    MoleculeState, PolecatReportSignal, RigState, TriageSignal, WorkCommentSignal, WorkItemComment,
+    WorkItemStats, WorkItemTransition, WorkSplitSignal, WorkUsageSignal, SIGNAL_ADD_WORK_ITEM,
+    SIGNAL_APPROVAL_DECISION, SIGNAL_APPROVAL_REQUEST, SIGNAL_ASSIGN, SIGNAL_CLOSE,
+    SIGNAL_COMPLETE, SIGNAL_CONVOY_CLOSED, SIGNAL_CONVOY_PROGRESS, SIGNAL_ESCALATION_UPDATE,
+    SIGNAL_FAIL, SIGNAL_HEARTBEAT, SIGNAL_HOOK, SIGNAL_HOOK_CLEAR, SIGNAL_MAYOR_MAINTENANCE,
+    SIGNAL_MOL_CANCEL, SIGNAL_MOL_PAUSE, SIGNAL_MOL_RESUME, SIGNAL_MOL_STEP_DONE,
+    SIGNAL_MOL_STEP_FAIL, SIGNAL_POLECAT_REPORT, SIGNAL_RELEASE, SIGNAL_RIG_BOOT, SIGNAL_RIG_DOCK,
+    SIGNAL_RIG_PARK, SIGNAL_RIG_STOP, SIGNAL_RIG_UNDOCK, SIGNAL_RIG_UNPARK, SIGNAL_START,
+    SIGNAL_WORK_COMMENT, SIGNAL_WORK_SPLIT, SIGNAL_WORK_TRIAGE, SIGNAL_WORK_USAGE,
+};
+
+/// Fetch the full event history of a workflow's latest run.
+pub async fn fetch_events<C>(client: &C, workflow_id: &str) -> anyhow::Result<Vec<HistoryEvent>>
+where
+    C: WorkflowClientTrait + Sync,
+{
+    let mut events = Vec::new();
+    let mut page_token = Vec::new();
+    loop {
+        let resp = client
+            .get_workflow_execution_history(workflow_id.to_string(), None, page_token)
+            .await?;
+        if let Some(history) = resp.history {
+            events.extend(history.events);
+        }
+        if resp.next_page_token.is_empty() {
+            break;
+        }
+        page_token = resp.next_page_token;
+    }
+    Ok(events)
+}
+
+/// The raw first argument the workflow was started with.
+fn start_payload(events: &[HistoryEvent]) -> Option<&[u8]> {
+    start_payload_at(events, 0)
+}
+
+/// The raw argument at `index` the workflow was started with.
+fn start_payload_at(events: &[HistoryEvent], index: usize) -> Option<&[u8]> {
+    events.iter().find_map(|event| match &event.attributes {
+        Some(Attributes::WorkflowExecutionStartedEventAttributes(attrs)) => {
+            let payload = attrs.input.as_ref()?.payloads.get(index)?;
+            Some(payload.data.as_slice())
+        }
+        _ => None,
+    })
+}
+
+/// Decode the first argument the workflow was started with.
+pub fn start_input<T: DeserializeOwned>(events: &[HistoryEvent]) -> Option<T> {
+    start_input_at(events, 0)
+}
+
+/// Decode the argument at `index` the workflow was started with.
+pub fn start_input_at<T: DeserializeOwned>(events: &[HistoryEvent], index: usize) -> Option<T> {
+    serde_json::from_slice(start_payload_at(events, index)?).ok()
+}
+
+/// The string a finished workflow returned, `None` while it's running or if
+/// it ended any other way.
+pub fn result(events: &[HistoryEvent]) -> Option<String> {
+    events.iter().find_map(|event| match &event.attributes {
+        Some(Attributes::WorkflowExecutionCompletedEventAttributes(attrs)) => {
+            let payload = attrs.result.as_ref()?.payloads.first()?;
+            serde_json::from_slice(&payload.data).ok()
+        }
+        _ => None,
+    })
+}
+
+/// The work item a `work_item_wf` was started with, in whichever input form.
+pub fn work_item(events: &[HistoryEvent]) -> Option<WorkItem> {
+    crate::workflows::work_item::parse_input(start_payload(events)?).map(|(item, _)| item)
+}
+
+/// Received signals in order, as (signal name, first payload).
+fn signals(events: &[HistoryEvent]) -> impl Iterator<Item = (&str, Option<&[u8]>)> {
+    events.iter().filter_map(|event| match &event.attributes {
+        Some(Attributes::WorkflowExecutionSignaledEventAttributes(attrs)) => {
+            let payload = attrs
+                .input
+                .as_ref()
+                .and_then(|p| p.payloads.first())
+                .map(|p| p.data.as_slice());
+            Some((attrs.signal_name.as_str(), payload))
+        }
+        _ => None,
+    })
+}
+
+/// The hook an agent workflow currently holds, mirroring `agent_wf`.
+pub fn current_hook(events: &[HistoryEvent]) -> Option<HookSignal> {
+    let mut hook = None;
+    for (name, payload) in signals(events) {
+        match name {
+            SIGNAL_HOOK => {
+                if let Some(data) = payload.and_then(|p| serde_json::from_slice(p).ok()) {
+                    hook = Some(data);
+                }
+            }
+            SIGNAL_HOOK_CLEAR => hook = None,
+            _ => {}
+        }
+    }
+    hook
+}
+
+/// A rig's lifecycle state (operational, parked, docked or dormant),
+/// applying signals the same way `rig_wf` does.
+pub fn rig_lifecycle(events: &[HistoryEvent]) -> String {
+    // A run continued after `rig_stop` starts dormant
+    let mut status = if start_input::<RigState>(events).is_some() {
+        "dormant"
+    } else {
+        "operational"
+    };
+    for (name, _) in signals(events) {
+        status = match (name, status) {
+            (SIGNAL_RIG_BOOT, "operational" | "dormant") => "operational",
+            (SIGNAL_RIG_STOP, _) => return "dormant".to_string(),
+            (SIGNAL_RIG_PARK, "operational") => "parked",
+            (SIGNAL_RIG_UNPARK, "parked") => "operational",
+            (SIGNAL_RIG_DOCK, s) if s != "dormant" => "docked",
+            (SIGNAL_RIG_UNDOCK, "docked") => "operational",
+            (_, s) => s,
+        };
+    }
+    status.to_string()
+}
+
+/// The mayor's maintenance notice, if the town is in maintenance mode. A
+/// continued run starts from the notice the last one carried over.
+pub fn maintenance(events: &[HistoryEvent]) -> Option<String> {
+    let carried = start_input::<MayorState>(events).and_then(|state| state.maintenance);
+    signals(events)
+        .filter(|(name, _)| *name == SIGNAL_MAYOR_MAINTENANCE)
+        .filter_map(|(_, payload)| serde_json::from_slice::<MaintenanceSignal>(payload?).ok())
+        .last()
+        .map_or(carried, |signal| {
+            signal.enabled.then(|| signal.message.unwrap_or_default())
+        })
+}
+
+/// Rigs `rgt mayor pause-town` parked, for `resume-town` to unpark. Empty
+/// once maintenance mode is turned off.
+pub fn paused_rigs(events: &[HistoryEvent]) -> Vec<String> {
+    let carried = start_input::<MayorState>(events)
+        .map(|state| state.paused_rigs)
+        .unwrap_or_default();
+    signals(events)
+        .filter(|(name, _)| *name == SIGNAL_MAYOR_MAINTENANCE)
+        .filter_map(|(_, payload)| serde_json::from_slice::<MaintenanceSignal>(payload?).ok())
+        .last()
+        .map_or(carried, |signal| {
+            if signal.enabled {
+                signal.parked_rigs
+            } else {
+                Vec::new()
+            }
+        })
+}
+
+/// Progress of each open convoy, replayed from the mayor's history (on top of
+/// what a continued run carried over) in the order the convoys first reported.
+pub fn convoy_progress(events: &[HistoryEvent]) -> Vec<ConvoyProgressSignal> {
+    let mut progress = start_input::<MayorState>(events)
+        .map(|state| state.convoy_progress)
+        .unwrap_or_default();
+    for (name, payload) in signals(events) {
+        match name {
+            SIGNAL_CONVOY_PROGRESS => {
+                let Some(update) =
+                    payload.and_then(|p| serde_json::from_slice::<ConvoyProgressSignal>(p).ok())
+                else {
+                    continue;
+                };
+                match progress
+                    .iter_mut()
+                    .find(|p| p.convoy_id == update.convoy_id)
+                {
+                    Some(entry) => *entry = update,
+                    None => progress.push(update),
+                }
+            }
+            SIGNAL_CONVOY_CLOSED => {
+                if let Some(closed) =
+                    payload.and_then(|p| serde_json::from_slice::<ConvoyClosedSignal>(p).ok())
+                {
+                    progress.retain(|p| p.convoy_id != closed.convoy_id);
+                }
+            }
+            _ => {}
+        }
+    }
+    progress
+}
+
+/// Approval requests the mayor is holding, replayed from its history (on top
+/// of what a continued run carried over) in the order they arrived.
+pub fn pending_approvals(events: &[HistoryEvent]) -> Vec<ApprovalRequest> {
+    let mut pending = start_input::<MayorState>(events)
+        .map(|state| state.pending_approvals)
+        .unwrap_or_default();
+    for (name, payload) in signals(events) {
+        match name {
+            SIGNAL_APPROVAL_REQUEST => {
+                if let Some(request) =
+                    payload.and_then(|p| serde_json::from_slice::<ApprovalRequest>(p).ok())
+                {
+                    pending.retain(|r| r.id != request.id);
+                    pending.push(request);
+                }
+            }
+            SIGNAL_APPROVAL_DECISION => {
+                if let Some(decision) =
+                    payload.and_then(|p| serde_json::from_slice::<ApprovalDecision>(p).ok())
+                {
+                    pending.retain(|r| r.id != decision.id);
+                }
+            }
+            _ => {}
+        }
+    }
+    pending
+}
+
+/// The most recent polecat report a work item received.
+pub fn polecat_report(events: &[HistoryEvent]) -> Option<PolecatReportSignal> {
+    signals(events)
+        .filter(|(name, _)| *name == SIGNAL_POLECAT_REPORT)
+        .filter_map(|(_, payload)| serde_json::from_slice(payload?).ok())
+        .last()
+}
+
+/// The most recent triage assessment a work item received.
+pub fn triage(events: &[HistoryEvent]) -> Option<TriageSignal> {
+    signals(events)
+        .filter(|(name, _)| *name == SIGNAL_WORK_TRIAGE)
+        .filter_map(|(_, payload)| serde_json::from_slice(payload?).ok())
+        .last()
+}
+
+/// Rebuild a molecule's step progress from its history, applying signals the
+/// same way `molecule_wf` does. `None` if the start input can't be decoded.
+pub fn replay_molecule(events: &[HistoryEvent]) -> Option<MoleculeState> {
+    let (id, formula_name, step_names): (String, String, Vec<String>) = start_input(events)?;
+    let mut steps: Vec<MolStepState> = step_names
+        .iter()
+        .map(|name| MolStepState {
+            ref_id: name.clone(),
+            title: name.clone(),
+            status: "pending".to_string(),
+            output: None,
+        })
+        .collect();
+    let mut status = "running".to_string();
+    let mut current_step = steps.first().map(|s| s.ref_id.clone());
+    if let Some(first) = steps.first_mut() {
+        first.status = "in_progress".to_string();
+    }
+
+    for (name, payload) in signals(events) {
+        if steps
+            .iter()
+            .all(|s| s.status == "done" || s.status == "failed")
+        {
+            break;
+        }
+        match name {
+            SIGNAL_MOL_CANCEL => {
+                status = "cancelled".to_string();
+                break;
+            }
+            SIGNAL_MOL_PAUSE if status == "running" => status = "paused".to_string(),
+            SIGNAL_MOL_RESUME if status == "paused" => status = "running".to_string(),
+            SIGNAL_MOL_STEP_DONE if status == "running" => {
+                let Some(data) =
+                    payload.and_then(|p| serde_json::from_slice::<MolStepDoneSignal>(p).ok())
+                else {
+                    continue;
+                };
+                if let Some(step) = steps.iter_mut().find(|s| s.ref_id == data.step_ref) {
+                    step.status = "done".to_string();
+                    step.output = data.output;
+                }
+                current_step = None;
+                if let Some(step) = steps.iter_mut().find(|s| s.status == "pending") {
+                    step.status = "in_progress".to_string();
+                    current_step = Some(step.ref_id.clone());
+                }
+            }
+            SIGNAL_MOL_STEP_FAIL if status == "running" => {
+                let Some(data) =
+                    payload.and_then(|p| serde_json::from_slice::<MolStepFailSignal>(p).ok())
+                else {
+                    continue;
+                };
+                if let Some(step) = steps.iter_mut().find(|s| s.ref_id == data.step_ref) {
+                    step.status = "failed".to_string();
+                    step.output = Some(data.reason);
+                }
+                status = "failed".to_string();
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    if status == "running" && steps.iter().all(|s| s.status == "done") {
+        status = "completed".to_string();
+    }
+    Some(MoleculeState {
+        id,
+        formula_name,
+        status,
+        steps,
+        current_step,
+    })
+}
+
+/// When an event was recorded.
+fn event_time(event: &HistoryEvent) -> Option<chrono::DateTime<chrono::Utc>> {
+    let t = event.event_time.as_ref()?;
+    chrono::DateTime::from_timestamp(t.seconds, t.nanos as u32)
+}
+
+/// The comments left on a work item, oldest first.
+pub fn work_item_comments(events: &[HistoryEvent]) -> Vec<WorkItemComment> {
+    events
+        .iter()
+        .filter_map(|event| match &event.attributes {
+            Some(Attributes::WorkflowExecutionSignaledEventAttributes(attrs))
+                if attrs.signal_name == SIGNAL_WORK_COMMENT =>
+            {
+                let payload = attrs.input.as_ref()?.payloads.first()?;
+                let data = serde_json::from_slice::<WorkCommentSignal>(&payload.data).ok()?;
+                Some(WorkItemComment {
+                    at: event_time(event).unwrap_or_default(),
+                    author: data.author,
+                    text: data.text,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// What a work item has cost up to `until` (its close time, or now if it's
+/// still running): wall-clock seconds in each status, replayed from its
+/// timeline, plus the usage its agents reported.
+pub fn work_item_stats(
+    events: &[HistoryEvent],
+    until: chrono::DateTime<chrono::Utc>,
+) -> WorkItemStats {
+    let mut stats = WorkItemStats::default();
+    for (name, payload) in signals(events) {
+        if name == SIGNAL_WORK_USAGE {
+            if let Some(usage) =
+                payload.and_then(|p| serde_json::from_slice::<WorkUsageSignal>(p).ok())
+            {
+                stats.add_usage(&usage);
+            }
+        }
+    }
+
+    let Some(started) = events.first().and_then(event_time) else {
+        return stats;
+    };
+    let mut current = ("pending", started);
+    let mut credit =
+        |status: &str, from: chrono::DateTime<chrono::Utc>, to: chrono::DateTime<chrono::Utc>| {
+            let secs = (to - from).num_seconds().max(0) as u64;
+            *stats
+                .seconds_in_status
+                .entry(status.to_string())
+                .or_default() += secs;
+        };
+    for transition in work_item_timeline(events) {
+        let next = match transition.event.as_str() {
+            "assigned" => "assigned",
+            "in_progress" => "in_progress",
+            "released" => "pending",
+            "split" => "tracking",
+            "done" | "failed" | "closed" => "",
+            _ => continue,
+        };
+        let at = transition.at.unwrap_or(current.1);
+        if next != current.0 {
+            credit(current.0, current.1, at);
+            current = (next, at);
+        }
+        if next.is_empty() {
+            return stats;
+        }
+    }
+    credit(current.0, current.1, until);
+    stats
+}
+
+/// The work items added to a convoy, in order.
+pub fn convoy_items(events: &[HistoryEvent]) -> Vec<String> {
+    signals(events)
+        .filter(|(name, _)| *name == SIGNAL_ADD_WORK_ITEM)
+        .filter_map(|(_, payload)| serde_json::from_slice::<AddWorkItemSignal>(payload?).ok())
+        .map(|data| data.work_item_id)
+        .collect()
+}
+
+/// The parts a work item was split into, numbered the way `work_item_wf`
+/// numbers them.
+pub fn work_item_parts(events: &[HistoryEvent]) -> Vec<String> {
+    let Some(item) = work_item(events) else {
+        return Vec::new();
+    };
+    signals(events)
+        .filter(|(name, _)| *name == SIGNAL_WORK_SPLIT)
+        .filter_map(|(_, payload)| serde_json::from_slice::<WorkSplitSignal>(payload?).ok())
+        .flat_map(|split| split.titles)
+        .enumerate()
+        .map(|(i, _)| format!("{}.{}", item.id.0, i + 1))
+        .collect()
+}
+
+/// A work item's timeline, rebuilt from its history the way `work_item_wf`
+/// applies each signal. Escalations, including the ones the workflow raises
+/// itself when the item goes stale, come from the updates it sends the mayor.
+/// Without a better actor, a signal is attributed to the client that sent it.
+pub fn work_item_timeline(events: &[HistoryEvent]) -> Vec<WorkItemTransition> {
+    let mut timeline = Vec::new();
+    let mut status = "pending";
+    let mut assigned_to: Option<String> = None;
+
+    for event in events {
+        let at = event_time(event);
+        let mut push = |event: &str, actor: Option<String>, detail: Option<String>| {
+            timeline.push(WorkItemTransition {
+                at,
+                event: event.to_string(),
+                actor,
+                detail,
+            })
+        };
+        let attrs = match &event.attributes {
+            Some(Attributes::WorkflowExecutionSignaledEventAttributes(attrs)) => attrs,
+            Some(Attributes::SignalExternalWorkflowExecutionInitiatedEventAttributes(attrs))
+                if attrs.signal_name == SIGNAL_ESCALATION_UPDATE =>
+            {
+                let update = attrs
+                    .input
+                    .as_ref()
+                    .and_then(|p| p.payloads.first())
+                    .and_then(|p| serde_json::from_slice::<EscalationUpdateSignal>(&p.data).ok());
+                if let Some(update) = update {
+                    push(
+                        &format!("escalation_{}", update.status),
+                        None,
+                        Some(format!("level {}", update.level)),
+                    );
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        let payload = attrs
+            .input
+            .as_ref()
+            .and_then(|p| p.payloads.first())
+            .map(|p| p.data.as_slice());
+        let sender = (!attrs.identity.is_empty()).then(|| attrs.identity.clone());
+
+        match attrs.signal_name.as_str() {
+            SIGNAL_ASSIGN if status == "pending" => {
+                let Some(data) =
+                    payload.and_then(|p| serde_json::from_slice::<AssignSignal>(p).ok())
+                else {
+                    continue;
+                };
+                status = "assigned";
+                assigned_to = Some(data.agent_id.clone());
+                push("assigned", Some(data.agent_id), None);
+            }
+            SIGNAL_START if status == "assigned" => {
+                status = "in_progress";
+                push("in_progress", assigned_to.clone().or(sender), None);
+            }
+            SIGNAL_RELEASE if status == "assigned" || status == "in_progress" => {
+                status = "pending";
+                push("released", sender, assigned_to.take());
+            }
+            SIGNAL_HEARTBEAT => {
+                let progress = payload
+                    .and_then(|p| serde_json::from_slice::<HeartbeatSignal>(p).ok())
+                    .and_then(|hb| hb.progress);
+                push("heartbeat", assigned_to.clone().or(sender), progress);
+            }
+            SIGNAL_WORK_TRIAGE => {
+                if let Some(data) =
+                    payload.and_then(|p| serde_json::from_slice::<TriageSignal>(p).ok())
+                {
+                    push(
+                        "triaged",
+                        sender,
+                        Some(format!("{} complexity", data.complexity)),
+                    );
+                }
+            }
+            SIGNAL_POLECAT_REPORT => {
+                if let Some(report) =
+                    payload.and_then(|p| serde_json::from_slice::<PolecatReportSignal>(p).ok())
+                {
+                    let detail = format!("{} on {}", report.status, report.branch);
+                    push("reported", Some(report.polecat_id), Some(detail));
+                }
+            }
+            SIGNAL_WORK_COMMENT => {
+                if let Some(data) =
+                    payload.and_then(|p| serde_json::from_slice::<WorkCommentSignal>(p).ok())
+                {
+                    push("comment", Some(data.author), Some(data.text));
+                }
+            }
+            SIGNAL_WORK_SPLIT => {
+                if let Some(data) =
+                    payload.and_then(|p| serde_json::from_slice::<WorkSplitSignal>(p).ok())
+                {
+                    status = "tracking";
+                    push("split", sender, Some(data.titles.join(", ")));
+                }
+            }
+            SIGNAL_COMPLETE if status != "tracking" => {
+                push("done", assigned_to.clone().or(sender), None);
+                break;
+            }
+            SIGNAL_FAIL => {
+                let Some(data) = payload.and_then(|p| serde_json::from_slice::<FailSignal>(p).ok())
+                else {
+                    continue;
+                };
+                push("failed", assigned_to.clone().or(sender), Some(data.reason));
+                break;
+            }
+            SIGNAL_CLOSE => {
+                push("closed", sender, None);
+                break;
+            }
+            _ => {}
+        }
+    }
+    timeline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use temporalio_common::protos::coresdk::AsJsonPayloadExt;
+    use temporalio_common::protos::temporal::api::history::v1::{
+        SignalExternalWorkflowExecutionInitiatedEventAttributes,
+        WorkflowExecutionSignaledEventAttributes, WorkflowExecutionStartedEventAttributes,
+    };
+
+    fn started<T: serde::Serialize>(input: &T) -> HistoryEvent {
+        HistoryEvent {
+            attributes: Some(Attributes::WorkflowExecutionStartedEventAttributes(
+                WorkflowExecutionStartedEventAttributes {
+                    input: Some(input.as_json_payload().unwrap().into()),
+                    ..Default::default()
+                },
+            )),
+            ..Default::default()
+        }
+    }
+
+    fn signaled<T: serde::Serialize>(name: &str, input: Option<&T>) -> HistoryEvent {
+        HistoryEvent {
+            attributes: Some(Attributes::WorkflowExecutionSignaledEventAttributes(
+                WorkflowExecutionSignaledEventAttributes {
+                    signal_name: name.to_string(),
+                    input: input.map(|i| i.as_json_payload().unwrap().into()),
+                    ..Default::default()
+                },
+            )),
+            ..Default::default()
+        }
+    }
+
+    fn step_done(step: &str) -> HistoryEvent {
+        let signal = MolStepDoneSignal {
+            step_ref: step.to_string(),
+            output: None,
+        };
+        signaled(SIGNAL_MOL_STEP_DONE, Some(&signal))
+    }
+
+    #[test]
+    fn molecule_replay_tracks_current_step() {
+        let steps = vec!["design", "build", "ship"];
+        let mut events = vec![started(&("mol-1", "release", steps))];
+        let mol = replay_molecule(&events).unwrap();
+        assert_eq!(mol.current_step.as_deref(), Some("design"));
+
+        events.push(step_done("design"));
+        events.push(signaled::<()>(SIGNAL_MOL_PAUSE, None));
+        // Ignored while paused, like the workflow does
+        events.push(step_done("build"));
+        let mol = replay_molecule(&events).unwrap();
+        assert_eq!(mol.status, "paused");
+        assert_eq!(mol.current_step.as_deref(), Some("build"));
+        assert_eq!(mol.steps[1].status, "in_progress");
+
+        events.push(signaled::<()>(SIGNAL_MOL_RESUME, None));
+        events.push(step_done("build"));
+        events.push(step_done("ship"));
+        let mol = replay_molecule(&events).unwrap();
+        assert_eq!(mol.status, "completed");
+        assert_eq!(mol.current_step, None);
+    }
+
+    #[test]
+    fn rig_lifecycle_follows_park_and_dock() {
+        let mut events = vec![started(&("myproject", "git@example.com:me/p.git"))];
+        assert_eq!(rig_lifecycle(&events), "operational");
+
+        events.push(signaled::<()>(SIGNAL_RIG_PARK, None));
+        assert_eq!(rig_lifecycle(&events), "parked");
+        // Undock doesn't apply to a parked rig
+        events.push(signaled::<()>(SIGNAL_RIG_UNDOCK, None));
+        assert_eq!(rig_lifecycle(&events), "parked");
+        events.push(signaled::<()>(SIGNAL_RIG_DOCK, None));
+        assert_eq!(rig_lifecycle(&events), "docked");
+        events.push(signaled::<()>(SIGNAL_RIG_UNDOCK, None));
+        assert_eq!(rig_lifecycle(&events), "operational");
+    }
+
+    #[test]
+    fn maintenance_follows_latest_toggle() {
+        let on = MaintenanceSignal {
+            enabled: true,
+            message: Some("Upgrading the worker".into()),
+            parked_rigs: vec![],
+        };
+        let off = MaintenanceSignal {
+            enabled: false,
+            message: None,
+            parked_rigs: vec![],
+        };
+        let mut events = vec![signaled(SIGNAL_MAYOR_MAINTENANCE, Some(&on))];
+        assert_eq!(
+            maintenance(&events).as_deref(),
+            Some("Upgrading the worker")
+        );
+        events.push(signaled(SIGNAL_MAYOR_MAINTENANCE, Some(&off)));
+        assert_eq!(maintenance(&events), None);
+    }
+
+    #[test]
+    fn paused_rigs_cleared_when_maintenance_ends() {
+        let pause = MaintenanceSignal {
+            enabled: true,
+            message: None,
+            parked_rigs: vec!["gtr".into(), "api".into()],
+        };
+        let off = MaintenanceSignal {
+            enabled: false,
+            message: None,
+            parked_rigs: vec![],
+        };
+        let carried = MayorState {
+            paused_rigs: vec!["web".into()],
+            ..Default::default()
+        };
+        let mut events = vec![started(&carried)];
+        assert_eq!(paused_rigs(&events), vec!["web"]);
+        events.push(signaled(SIGNAL_MAYOR_MAINTENANCE, Some(&pause)));
+        assert_eq!(paused_rigs(&events), vec!["gtr", "api"]);
+        events.push(signaled(SIGNAL_MAYOR_MAINTENANCE, Some(&off)));
+        assert!(paused_rigs(&events).is_empty());
+    }
+
+    #[test]
+    fn convoy_progress_keeps_latest_until_closed() {
+        let progress = |convoy_id: &str, done, total| {
+            let signal = ConvoyProgressSignal {
+                convoy_id: convoy_id.into(),
+                title: "Sprint".into(),
+                done,
+                total,
+                deadline: None,
+                at_risk: false,
+            };
+            signaled(SIGNAL_CONVOY_PROGRESS, Some(&signal))
+        };
+        let mut events = vec![progress("cv-1", 0, 3), progress("cv-2", 1, 2)];
+        events.push(progress("cv-1", 1, 3));
+        let open = convoy_progress(&events);
+        assert_eq!(open.len(), 2);
+        assert_eq!((open[0].convoy_id.as_str(), open[0].done), ("cv-1", 1));
+        assert_eq!(open[0].percent(), 33);
+
+        let closed = ConvoyClosedSignal {
+            convoy_id: "cv-2".into(),
+        };
+        events.push(signaled(SIGNAL_CONVOY_CLOSED, Some(&closed)));
+        let open = convoy_progress(&events);
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].convoy_id, "cv-1");
+    }
+
+    #[test]
+    fn pending_approvals_drop_decided_requests() {
+        let request = |id: &str| ApprovalRequest {
+            id: id.into(),
+            operation: "protected_merge".into(),
+            target: "main".into(),
+            detail: "merge polecat/nux".into(),
+            requested_by: "gtr-refinery".into(),
+        };
+        let carried = MayorState {
+            pending_approvals: vec![request("ap-1")],
+            ..Default::default()
+        };
+        let events = vec![
+            started(&carried),
+            signaled(SIGNAL_APPROVAL_REQUEST, Some(&request("ap-2"))),
+            signaled(SIGNAL_APPROVAL_REQUEST, Some(&request("ap-3"))),
+            signaled(
+                SIGNAL_APPROVAL_DECISION,
+                Some(&ApprovalDecision {
+                    id: "ap-1".into(),
+                    approved: true,
+                    by: "human".into(),
+                }),
+            ),
+        ];
+        let ids: Vec<String> = pending_approvals(&events)
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(ids, vec!["ap-2", "ap-3"]);
+    }
+
+    #[test]
+    fn continued_mayor_starts_from_carried_state() {
+        let carried = MayorState {
+            maintenance: Some("Host reboot".into()),
+            convoy_progress: vec![ConvoyProgressSignal {
+                convoy_id: "cv-1".into(),
+                title: "Sprint".into(),
+                done: 2,
+                total: 3,
+                deadline: None,
+                at_risk: false,
+            }],
+            ..Default::default()
+        };
+        let mut events = vec![started(&carried)];
+        assert_eq!(maintenance(&events).as_deref(), Some("Host reboot"));
+        assert_eq!(convoy_progress(&events)[0].done, 2);
+
+        let off = MaintenanceSignal {
+            enabled: false,
+            message: None,
+            parked_rigs: vec![],
+        };
+        let closed = ConvoyClosedSignal {
+            convoy_id: "cv-1".into(),
+        };
+        events.push(signaled(SIGNAL_MAYOR_MAINTENANCE, Some(&off)));
+        events.push(signaled(SIGNAL_CONVOY_CLOSED, Some(&closed)));
+        assert_eq!(maintenance(&events), None);
+        assert!(convoy_progress(&events).is_empty());
+    }
+
+    #[test]
+    fn hook_cleared_by_later_signal() {
+        let hook = HookSignal {
+            work_item_id: "wi-1".into(),
+            title: "Fix login".into(),
+            molecule_id: Some("mol-1".into()),
+            current_step: None,
+        };
+        let mut events = vec![
+            started(&("pc-1", "polecat")),
+            signaled(SIGNAL_HOOK, Some(&hook)),
+        ];
+        assert_eq!(
+            current_hook(&events).unwrap().molecule_id.as_deref(),
+            Some("mol-1")
+        );
+        events.push(signaled::<()>(SIGNAL_HOOK_CLEAR, None));
+        assert!(current_hook(&events).is_none());
+    }
+
+    #[test]
+    fn work_item_accepts_full_and_legacy_input() {
+        let mut item = WorkItem::new("wi-1", "Fix login");
+        item.priority = gtr_core::types::Priority::P1;
+        item.labels = vec!["infra".into()];
+        assert_eq!(work_item(&[started(&item)]), Some(item));
+
+        let legacy = work_item(&[started(&("wi-2", "Old form", "cv-1"))]).unwrap();
+        assert_eq!(legacy, WorkItem::new("wi-2", "Old form"));
+
+        let from_convoy = WorkItem::new("wi-3", "In a convoy");
+        assert_eq!(
+            work_item(&[started(&(&from_convoy, "cv-1"))]),
+            Some(from_convoy)
+        );
+    }
+
+    #[test]
+    fn work_item_timeline_follows_transitions() {
+        let escalation = EscalationUpdateSignal {
+            work_item_id: "wi-1".into(),
+            title: "Fix login".into(),
+            level: 1,
+            status: "open".into(),
+        };
+        let events = vec![
+            started(&WorkItem::new("wi-1", "Fix login")),
+            // Ignored: nothing is assigned yet
+            signaled::<()>(SIGNAL_START, None),
+            signaled(
+                SIGNAL_ASSIGN,
+                Some(&AssignSignal {
+                    agent_id: "slit".into(),
+                }),
+            ),
+            signaled::<()>(SIGNAL_START, None),
+            signaled(
+                SIGNAL_HEARTBEAT,
+                Some(&HeartbeatSignal {
+                    progress: Some("tests pass".into()),
+                }),
+            ),
+            HistoryEvent {
+                attributes: Some(
+                    Attributes::SignalExternalWorkflowExecutionInitiatedEventAttributes(
+                        SignalExternalWorkflowExecutionInitiatedEventAttributes {
+                            signal_name: SIGNAL_ESCALATION_UPDATE.to_string(),
+                            input: Some(escalation.as_json_payload().unwrap().into()),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                ..Default::default()
+            },
+            signaled::<()>(SIGNAL_COMPLETE, None),
+            signaled::<()>(SIGNAL_CLOSE, None),
+        ];
+        let timeline = work_item_timeline(&events);
+        let names: Vec<&str> = timeline.iter().map(|t| t.event.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "assigned",
+                "in_progress",
+                "heartbeat",
+                "escalation_open",
+                "done"
+            ]
+        );
+        assert_eq!(timeline[2].actor.as_deref(), Some("slit"));
+        assert_eq!(timeline[2].detail.as_deref(), Some("tests pass"));
+        assert_eq!(timeline[3].detail.as_deref(), Some("level 1"));
+    }
+
+    #[test]
+    fn split_item_tracks_parts_and_ignores_complete() {
+        let split = |titles: &[&str]| {
+            signaled(
+                SIGNAL_WORK_SPLIT,
+                Some(&WorkSplitSignal {
+                    titles: titles.iter().map(|t| t.to_string()).collect(),
+                }),
+            )
+        };
+        let events = vec![
+            started(&WorkItem::new("wi-1", "Fix login")),
+            split(&["schema", "endpoint"]),
+            split(&["docs"]),
+            signaled::<()>(SIGNAL_COMPLETE, None),
+        ];
+        assert_eq!(work_item_parts(&events), ["wi-1.1", "wi-1.2", "wi-1.3"]);
+        let names: Vec<String> = work_item_timeline(&events)
+            .into_iter()
+            .map(|t| t.event)
+            .collect();
+        assert_eq!(names, ["split", "split"]);
+    }
+
+    #[test]
+    fn work_item_stats_time_each_status() {
+        let at = |secs: i64, mut event: HistoryEvent| {
+            let time = event.event_time.get_or_insert_with(Default::default);
+            time.seconds = secs;
+            event
+        };
+        let usage = WorkUsageSignal {
+            agent: "slit".into(),
+            input_tokens: 1000,
+            output_tokens: 200,
+            cost_usd: 0.5,
+        };
+        let events = vec![
+            at(0, started(&WorkItem::new("wi-1", "Fix login"))),
+            at(
+                60,
+                signaled(
+                    SIGNAL_ASSIGN,
+                    Some(&AssignSignal {
+                        agent_id: "slit".into(),
+                    }),
+                ),
+            ),
+            at(90, signaled::<()>(SIGNAL_START, None)),
+            at(100, signaled(SIGNAL_WORK_USAGE, Some(&usage))),
+            at(200, signaled(SIGNAL_WORK_USAGE, Some(&usage))),
+        ];
+        let until = chrono::DateTime::from_timestamp(390, 0).unwrap();
+        let stats = work_item_stats(&events, until);
+        assert_eq!(stats.seconds_in_status["pending"], 60);
+        assert_eq!(stats.seconds_in_status["assigned"], 30);
+        assert_eq!(stats.seconds_in_status["in_progress"], 300);
+        assert_eq!(stats.input_tokens, 2000);
+        assert_eq!(stats.cost_usd, 1.0);
+
+        let mut done = events.clone();
+        done.push(at(150, signaled::<()>(SIGNAL_COMPLETE, None)));
+        assert_eq!(
+            work_item_stats(&done, until).seconds_in_status["in_progress"],
+            60
+        );
+    }
+
+    #[test]
+    fn work_item_comments_in_order() {
+        let comment = |text: &str| {
+            signaled(
+                SIGNAL_WORK_COMMENT,
+                Some(&WorkCommentSignal {
+                    author: "slit".into(),
+                    text: text.into(),
+                }),
+            )
+        };
+        let events = vec![
+            started(&WorkItem::new("wi-1", "Fix login")),
+            comment("found the bug"),
+            comment("fixed"),
+        ];
+        let texts: Vec<String> = work_item_comments(&events)
+            .into_iter()
+            .map(|c| c.text)
+            .collect();
+        assert_eq!(texts, ["found the bug", "fixed"]);
+        assert_eq!(work_item_timeline(&events)[0].event, "comment");
+    }
+
+    #[test]
+    fn polecat_report_keeps_latest() {
+        let report = |status: &str| PolecatReportSignal {
+            polecat_id: "gt/polecats/nux".to_string(),
+            name: "nux".to_string(),
+            rig: "gt".to_string(),
+            work_item_id: "wi-1".to_string(),
+            branch: "polecat/nux/wi-1".to_string(),
+            status: status.to_string(),
+            summary: Some(format!("{status} summary")),
+            exit_reason: "completed".to_string(),
+        };
+        let mut events = vec![started(&("wi-1", "Fix it"))];
+        assert!(polecat_report(&events).is_none());
+
+        events.push(signaled(SIGNAL_POLECAT_REPORT, Some(&report("dead"))));
+        events.push(signaled(SIGNAL_POLECAT_REPORT, Some(&report("done"))));
+        let latest = polecat_report(&events).unwrap();
+        assert_eq!(latest.status, "done");
+        assert_eq!(latest.summary.as_deref(), Some("done summary"));
+    }
+}
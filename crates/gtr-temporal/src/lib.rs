@@ -1,4 +1,7 @@
 pub mod activities;
+pub mod history;
+pub mod outbox;
+pub mod output_log;
 pub mod pty;
 pub mod signals;
 pub mod worker;
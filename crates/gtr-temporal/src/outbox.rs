@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+
+use temporalio_common::protos::coresdk::AsJsonPayloadExt;
+use temporalio_sdk_core::WorkflowClientTrait;
+
+use gtr_core::outbox::{self, QueuedSignal};
+
+/// How often the worker checks the outbox for signals queued during an outage.
+pub const REPLAY_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ReplaySummary {
+    pub delivered: usize,
+    /// Still queued: failed, or held behind an earlier failure
+    pub failed: usize,
+    /// Discarded because their workflow isn't running
+    pub dropped: usize,
+}
+
+/// Deliver a single queued signal.
+pub async fn deliver<C>(client: &C, signal: &QueuedSignal) -> anyhow::Result<()>
+where
+    C: WorkflowClientTrait + Sync,
+{
+    let payloads = match &signal.payload {
+        Some(value) => Some(value.as_json_payload()?.into()),
+        None => None,
+    };
+    client
+        .signal_workflow_execution(
+            signal.workflow_id.clone(),
+            String::new(),
+            signal.signal_name.clone(),
+            payloads,
+            None,
+        )
+        .await?;
+    Ok(())
+}
+
+/// Whether `error` is Temporal reporting the signalled workflow doesn't exist
+/// or has already finished.
+pub fn is_not_found(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<temporalio_client::tonic::Status>()
+        .is_some_and(|status| status.code() == temporalio_client::tonic::Code::NotFound)
+}
+
+/// Replay every queued signal in `dir`, oldest first. Delivered entries are
+/// removed, as are entries for a workflow that isn't running, which would
+/// never go through. Entries that fail stay queued for the next replay, and
+/// so does everything after them for the same workflow, to keep its signals
+/// in order.
+pub async fn replay<C>(client: &C, dir: &Path) -> anyhow::Result<ReplaySummary>
+where
+    C: WorkflowClientTrait + Sync,
+{
+    let mut summary = ReplaySummary::default();
+    let mut held: HashSet<String> = HashSet::new();
+    for (path, signal) in outbox::pending(dir)? {
+        if held.contains(&signal.workflow_id) {
+            summary.failed += 1;
+            continue;
+        }
+        match deliver(client, &signal).await {
+            Ok(()) => {
+                outbox::ack(&path)?;
+                summary.delivered += 1;
+                tracing::info!(
+                    "Outbox: delivered '{}' → {} (queued {})",
+                    signal.signal_name,
+                    signal.workflow_id,
+                    signal.queued_at
+                );
+            }
+            Err(e) if is_not_found(&e) => {
+                outbox::ack(&path)?;
+                summary.dropped += 1;
+                tracing::warn!(
+                    "Outbox: dropped '{}' → {}, which isn't running: {e}",
+                    signal.signal_name,
+                    signal.workflow_id
+                );
+            }
+            Err(e) => {
+                summary.failed += 1;
+                tracing::warn!(
+                    "Outbox: '{}' → {} still undeliverable: {e}",
+                    signal.signal_name,
+                    signal.workflow_id
+                );
+                held.insert(signal.workflow_id);
+            }
+        }
+    }
+    Ok(summary)
+}
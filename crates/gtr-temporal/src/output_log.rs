@@ -0,0 +1,365 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Rotate once the live log (or start a new recording once the current one)
+/// reaches this size.
+pub const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+/// Rotated logs kept beside the live one.
+pub const KEEP_ROTATED: usize = 3;
+/// Session recordings kept per agent, newest first.
+pub const KEEP_RECORDINGS: usize = 5;
+/// How long a cleaned-up agent's logs are kept after its last output.
+pub const LOG_RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+const LOG_FILE: &str = "output.log";
+const RECORDING_PREFIX: &str = "session-";
+const RECORDING_EXT: &str = ".cast";
+
+/// The live output log for an agent. Each agent's tmux pane is piped into
+/// `rgt agents log-sink`, which appends here and rotates to `output.log.1`,
+/// `.2`, ... so history outlives the tmux scrollback and the session itself.
+pub fn log_path(agent_id: &str) -> PathBuf {
+    crate::pty::runtime_dir(agent_id).join(LOG_FILE)
+}
+
+/// Whether a runtime dir entry is an output log (live or rotated) or a
+/// session recording. These survive [`crate::pty::cleanup`].
+pub fn is_log_file(name: &str) -> bool {
+    let rotated_log = name
+        .strip_prefix(LOG_FILE)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .is_some_and(|n| n.parse::<usize>().is_ok());
+    let recording = name.starts_with(RECORDING_PREFIX) && name.ends_with(RECORDING_EXT);
+    name == LOG_FILE || rotated_log || recording
+}
+
+/// Remove the runtime dirs under `root` that hold nothing but logs, none
+/// written to within `retention` — what finished agents, mostly polecats,
+/// leave behind after [`crate::pty::cleanup`]. Returns how many went.
+pub fn prune_finished(root: &Path, retention: Duration) -> usize {
+    let mut pruned = 0;
+    for dir in std::fs::read_dir(root).into_iter().flatten().flatten() {
+        let dir = dir.path();
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut newest = None;
+        let mut only_logs = true;
+        for entry in entries.flatten() {
+            if !is_log_file(&entry.file_name().to_string_lossy()) {
+                only_logs = false;
+                break;
+            }
+            let modified = entry.metadata().and_then(|m| m.modified()).ok();
+            newest = newest.max(modified);
+        }
+        let stale = newest
+            .and_then(|t| t.elapsed().ok())
+            .is_some_and(|age| age >= retention);
+        if only_logs && stale && std::fs::remove_dir_all(&dir).is_ok() {
+            pruned += 1;
+        }
+    }
+    pruned
+}
+
+/// Shell command for `tmux pipe-pane` that feeds the pane into the sink.
+pub fn pipe_command(rgt_bin: &str, agent_id: &str) -> String {
+    format!(
+        "{} agents log-sink {}",
+        shell_escape::escape(rgt_bin.into()),
+        shell_escape::escape(agent_id.into())
+    )
+}
+
+/// Append everything read from `input` to the agent's log and record it,
+/// with timings, as a new session recording, until EOF.
+pub fn sink(agent_id: &str, mut input: impl Read) -> std::io::Result<()> {
+    let dir = crate::pty::runtime_dir(agent_id);
+    std::fs::create_dir_all(&dir)?;
+    let mut log = RotatingLog::open(&dir.join(LOG_FILE), MAX_LOG_BYTES, KEEP_ROTATED)?;
+    let mut recorder = Recorder::start(&dir, MAX_LOG_BYTES, KEEP_RECORDINGS)?;
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = match input.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        log.write(&buf[..n])?;
+        recorder.record(&buf[..n])?;
+    }
+}
+
+/// Append-only log file that rotates itself at `max_bytes`.
+struct RotatingLog {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl RotatingLog {
+    fn open(path: &Path, max_bytes: u64, keep: usize) -> std::io::Result<Self> {
+        let file = open_append(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            size: file.metadata()?.len(),
+            file,
+            max_bytes,
+            keep,
+        })
+    }
+
+    /// Writes are flushed as they come so `--follow` sees them promptly.
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        if self.size >= self.max_bytes {
+            rotate(&self.path, self.keep)?;
+            self.file = open_append(&self.path)?;
+            self.size = 0;
+        }
+        self.file.write_all(bytes)?;
+        self.file.flush()?;
+        self.size += bytes.len() as u64;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Shift `log.1` → `log.2` ... dropping the oldest, then `log` → `log.1`.
+fn rotate(path: &Path, keep: usize) -> std::io::Result<()> {
+    let rotated = |n: usize| PathBuf::from(format!("{}.{n}", path.display()));
+    if keep == 0 {
+        return std::fs::remove_file(path);
+    }
+    let _ = std::fs::remove_file(rotated(keep));
+    for n in (1..keep).rev() {
+        if rotated(n).exists() {
+            std::fs::rename(rotated(n), rotated(n + 1))?;
+        }
+    }
+    std::fs::rename(path, rotated(1))
+}
+
+/// First line of an asciinema v2 recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CastHeader {
+    version: u8,
+    width: u16,
+    height: u16,
+    /// Unix seconds when the recording started
+    timestamp: u64,
+}
+
+/// Writes asciinema v2 (`.cast`) recordings: a header line, then one
+/// `[elapsed_secs, "o", text]` line per chunk of pane output.
+struct Recorder {
+    dir: PathBuf,
+    file: File,
+    started: Instant,
+    size: u64,
+    max_bytes: u64,
+    keep: usize,
+    /// Trailing bytes of a UTF-8 sequence split across reads
+    pending: Vec<u8>,
+}
+
+impl Recorder {
+    fn start(dir: &Path, max_bytes: u64, keep: usize) -> std::io::Result<Self> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let path = dir.join(format!(
+            "{RECORDING_PREFIX}{:013}{RECORDING_EXT}",
+            now.as_millis()
+        ));
+        let mut file = open_append(&path)?;
+        let header = CastHeader {
+            version: 2,
+            width: 200,
+            height: 50,
+            timestamp: now.as_secs(),
+        };
+        let line = format!("{}\n", serde_json::to_string(&header)?);
+        file.write_all(line.as_bytes())?;
+
+        // Drop the oldest recordings beyond `keep`, counting the new one
+        let recordings = recordings_in(dir);
+        for old in recordings
+            .iter()
+            .take(recordings.len().saturating_sub(keep))
+        {
+            let _ = std::fs::remove_file(old);
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file,
+            started: Instant::now(),
+            size: line.len() as u64,
+            max_bytes,
+            keep,
+            pending: Vec::new(),
+        })
+    }
+
+    fn record(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        if self.size >= self.max_bytes {
+            let pending = std::mem::take(&mut self.pending);
+            *self = Self::start(&self.dir, self.max_bytes, self.keep)?;
+            self.pending = pending;
+        }
+        self.pending.extend_from_slice(bytes);
+        let complete = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            // An incomplete sequence at the end waits for the next read
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => self.pending.len(),
+        };
+        let text = String::from_utf8_lossy(&self.pending[..complete]).into_owned();
+        self.pending.drain(..complete);
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let line = format!("{}\n", serde_json::to_string(&(elapsed, "o", text))?);
+        self.file.write_all(line.as_bytes())?;
+        self.file.flush()?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+/// Recording files in `dir`, oldest first.
+fn recordings_in(dir: &Path) -> Vec<PathBuf> {
+    let mut recordings: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(RECORDING_PREFIX) && n.ends_with(RECORDING_EXT))
+        })
+        .collect();
+    recordings.sort();
+    recordings
+}
+
+/// One chunk of recorded pane output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputChunk {
+    /// Unix time (seconds, fractional) the output was written
+    pub at: f64,
+    pub text: String,
+}
+
+/// Every recorded chunk for an agent across its kept recordings, in order.
+pub fn recorded_output(agent_id: &str) -> anyhow::Result<Vec<OutputChunk>> {
+    recorded_output_in(&crate::pty::runtime_dir(agent_id))
+}
+
+fn recorded_output_in(dir: &Path) -> anyhow::Result<Vec<OutputChunk>> {
+    let mut chunks = Vec::new();
+    for path in recordings_in(dir) {
+        let mut lines = BufReader::new(File::open(&path)?).lines();
+        let Some(header) = lines.next() else {
+            continue;
+        };
+        let header: CastHeader = serde_json::from_str(&header?)?;
+        for line in lines {
+            // A crash mid-write can leave a torn last line
+            let Ok((elapsed, _, text)) = serde_json::from_str::<(f64, String, String)>(&line?)
+            else {
+                continue;
+            };
+            chunks.push(OutputChunk {
+                at: header.timestamp as f64 + elapsed,
+                text,
+            });
+        }
+    }
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_rotates_and_keeps_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("output.log");
+
+        // 4-byte cap: each 4-byte chunk lands in a fresh file
+        let mut log = RotatingLog::open(&path, 4, 2).unwrap();
+        for chunk in ["aaaa", "bbbb", "cccc", "dddd"] {
+            log.write(chunk.as_bytes()).unwrap();
+        }
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "dddd");
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("output.log.1")).unwrap(),
+            "cccc"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("output.log.2")).unwrap(),
+            "bbbb"
+        );
+        assert!(!dir.path().join("output.log.3").exists());
+    }
+
+    #[test]
+    fn recording_round_trips_split_utf8() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut recorder = Recorder::start(dir.path(), MAX_LOG_BYTES, 2).unwrap();
+        let bytes = "ok → done\n".as_bytes();
+        // Split inside the three-byte arrow
+        recorder.record(&bytes[..4]).unwrap();
+        recorder.record(&bytes[4..]).unwrap();
+
+        let chunks = recorded_output_in(dir.path()).unwrap();
+        let text: String = chunks.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(text, "ok → done\n");
+        assert!(chunks.windows(2).all(|w| w[0].at <= w[1].at));
+    }
+
+    #[test]
+    fn prune_finished_keeps_recent_and_live_agents() {
+        let root = tempfile::tempdir().unwrap();
+        let finished = root.path().join("gtr-polecat-nux");
+        let live = root.path().join("mayor");
+        for dir in [&finished, &live] {
+            std::fs::create_dir_all(dir).unwrap();
+            std::fs::write(dir.join(LOG_FILE), "done\n").unwrap();
+        }
+        Recorder::start(&finished, MAX_LOG_BYTES, KEEP_RECORDINGS).unwrap();
+        std::fs::write(live.join("pid"), "123").unwrap();
+
+        assert_eq!(prune_finished(root.path(), LOG_RETENTION), 0);
+        assert!(finished.exists());
+        assert_eq!(prune_finished(root.path(), Duration::ZERO), 1);
+        assert!(!finished.exists());
+        assert!(live.join(LOG_FILE).exists());
+    }
+
+    #[test]
+    fn log_files_are_recognised() {
+        assert!(is_log_file("output.log"));
+        assert!(is_log_file("output.log.2"));
+        assert!(is_log_file("session-1760000000000.cast"));
+        assert!(!is_log_file("output.log.tmp"));
+        assert!(!is_log_file("env.json"));
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::os::windows::process::CommandExt;
+use std::path::Path;
+
+use super::{pid_file, tmux_session_name, SessionBackend};
+
+/// Windows sessions: each agent runs under PowerShell in a console window of
+/// its own, titled `gtr-<agent>`, and its process tree is tracked by PID.
+/// Without a pseudo console there is no way to read or type into the window
+/// from outside, so capture, send-keys and attach aren't available — use the
+/// window itself.
+pub struct Console;
+
+/// Stand-in for `nix::unistd::Pid` with the same constructor and accessor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pid(i32);
+
+impl Pid {
+    pub fn from_raw(pid: i32) -> Self {
+        Self(pid)
+    }
+
+    pub fn as_raw(self) -> i32 {
+        self.0
+    }
+}
+
+impl std::fmt::Display for Pid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// `CREATE_NEW_CONSOLE` from the Win32 process creation flags.
+const CREATE_NEW_CONSOLE: u32 = 0x0000_0010;
+
+/// Quote `arg` as a PowerShell single-quoted string.
+fn powershell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "''"))
+}
+
+/// The PowerShell equivalent of the `unset CLAUDECODE; <program> <args>` line
+/// the unix backends run.
+pub fn command_line(program: &str, args: &[String]) -> String {
+    let mut line = String::from("Remove-Item Env:CLAUDECODE -ErrorAction SilentlyContinue; & ");
+    line.push_str(&powershell_quote(program));
+    for arg in args {
+        line.push(' ');
+        line.push_str(&powershell_quote(arg));
+    }
+    line
+}
+
+impl SessionBackend for Console {
+    fn spawn(
+        &self,
+        agent_id: &str,
+        shell_cmd: &str,
+        work_dir: &Path,
+        env_vars: &HashMap<String, String>,
+    ) -> anyhow::Result<Pid> {
+        let title = powershell_quote(&tmux_session_name(agent_id));
+        let child = std::process::Command::new("powershell.exe")
+            .args(["-NoLogo", "-NoExit", "-Command"])
+            .arg(format!("$Host.UI.RawUI.WindowTitle = {title}; {shell_cmd}"))
+            .current_dir(work_dir)
+            .envs(env_vars)
+            .creation_flags(CREATE_NEW_CONSOLE)
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to start powershell.exe: {e}"))?;
+        Ok(Pid::from_raw(child.id() as i32))
+    }
+
+    fn is_alive(&self, agent_id: &str) -> bool {
+        let Some(pid) = pid_file(agent_id) else {
+            return false;
+        };
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {pid}"), "/NH", "/FO", "CSV"])
+            .output()
+            .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).contains(&format!("\"{pid}\"")))
+    }
+
+    fn capture(&self, _agent_id: &str, _lines: u32) -> Option<String> {
+        None
+    }
+
+    fn send_keys(&self, agent_id: &str, _text: &str, _enter: bool) -> anyhow::Result<()> {
+        anyhow::bail!(
+            "Typing into '{agent_id}' isn't supported on Windows; use its console window."
+        )
+    }
+
+    fn terminate(&self, agent_id: &str) -> bool {
+        // /T takes the whole process tree, like the process-group kill on unix
+        pid_file(agent_id).is_some_and(|pid| {
+            std::process::Command::new("taskkill")
+                .args(["/T", "/F", "/PID", &pid.to_string()])
+                .output()
+                .is_ok_and(|o| o.status.success())
+        })
+    }
+
+    fn attach_argv(&self, agent_id: &str, _read_only: bool) -> anyhow::Result<Vec<String>> {
+        anyhow::bail!(
+            "'{agent_id}' runs in its own console window on Windows (titled {}); switch to it.",
+            tmux_session_name(agent_id)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_line_quotes_for_powershell() {
+        assert_eq!(
+            command_line("claude", &["it's done".into()]),
+            "Remove-Item Env:CLAUDECODE -ErrorAction SilentlyContinue; & 'claude' 'it''s done'"
+        );
+    }
+}
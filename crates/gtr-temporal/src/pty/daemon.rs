@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+
+use super::{follow_log_argv, pid_file, runtime_dir, SessionBackend};
+
+/// Sessions without a multiplexer: the command runs on a PTY from
+/// `script(1)` under a daemonized `setsid` shell. Keystrokes go in through a
+/// FIFO in the runtime dir and output is read back from the agent's output
+/// log, so there's no interactive attach — only `--read-only`, which follows
+/// the log.
+pub struct Daemon;
+
+const INPUT_FIFO: &str = "input";
+
+/// Runs as `sh -c DAEMON_SCRIPT sh <pid file> <fifo> <command> <sink> <log>`.
+/// The shell records its PID, holds the FIFO open read-write so writers never
+/// block and `script` never sees EOF, and feeds `script`'s output to the
+/// sink command, or appends it to the log when there is none.
+const DAEMON_SCRIPT: &str = r#"echo $$ > "$1"
+exec 3<>"$2"
+if [ -n "$4" ]; then
+    script -qfec "$3" /dev/null <&3 2>&1 | sh -c "$4"
+else
+    exec script -qfec "$3" /dev/null <&3 >>"$5" 2>&1
+fi"#;
+
+impl SessionBackend for Daemon {
+    fn spawn(
+        &self,
+        agent_id: &str,
+        shell_cmd: &str,
+        work_dir: &Path,
+        env_vars: &HashMap<String, String>,
+    ) -> anyhow::Result<Pid> {
+        let dir = runtime_dir(agent_id);
+        let pid_path = dir.join("pid");
+        let fifo = dir.join(INPUT_FIFO);
+        let _ = std::fs::remove_file(&pid_path);
+        let _ = std::fs::remove_file(&fifo);
+        let status = std::process::Command::new("mkfifo")
+            .args(["-m", "600"])
+            .arg(&fifo)
+            .status()?;
+        if !status.success() {
+            anyhow::bail!("mkfifo {} failed", fifo.display());
+        }
+
+        // script(1) gives the command a terminal; size it like a tmux pane
+        let command = format!("stty cols 200 rows 50 2>/dev/null; {shell_cmd}");
+        let sink = env_vars
+            .get("RGT_BIN")
+            .map(|rgt_bin| crate::output_log::pipe_command(rgt_bin, agent_id))
+            .unwrap_or_default();
+        let status = std::process::Command::new("setsid")
+            .args(["-f", "sh", "-c", DAEMON_SCRIPT, "sh"])
+            .arg(&pid_path)
+            .arg(&fifo)
+            .arg(&command)
+            .arg(&sink)
+            .arg(crate::output_log::log_path(agent_id))
+            .current_dir(work_dir)
+            .envs(env_vars)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map_err(|e| {
+                anyhow::anyhow!("setsid not found ({e}) — the pty backend needs util-linux")
+            })?;
+        if !status.success() {
+            anyhow::bail!("setsid failed to start the session for '{agent_id}'");
+        }
+
+        // setsid -f returns before the shell has written its PID
+        for _ in 0..50 {
+            if let Some(pid) = pid_file(agent_id) {
+                return Ok(pid);
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        anyhow::bail!("Session for '{agent_id}' did not start within 5s")
+    }
+
+    fn is_alive(&self, agent_id: &str) -> bool {
+        pid_file(agent_id).is_some_and(|pid| signal::kill(pid, None).is_ok())
+    }
+
+    fn capture(&self, agent_id: &str, lines: u32) -> Option<String> {
+        let mut file = std::fs::File::open(crate::output_log::log_path(agent_id)).ok()?;
+        // The tail is plenty for a screenful; don't read a 10MB log to get it
+        let len = file.metadata().ok()?.len();
+        file.seek(SeekFrom::Start(len.saturating_sub(256 * 1024)))
+            .ok()?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes).ok()?;
+        let text = strip_terminal_codes(&String::from_utf8_lossy(&bytes));
+        let all: Vec<&str> = text.lines().collect();
+        let start = all.len().saturating_sub(lines as usize);
+        Some(all[start..].join("\n"))
+    }
+
+    fn send_keys(&self, agent_id: &str, text: &str, enter: bool) -> anyhow::Result<()> {
+        let fifo = runtime_dir(agent_id).join(INPUT_FIFO);
+        let mut input = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&fifo)
+            .map_err(|e| anyhow::anyhow!("Cannot open {}: {e}", fifo.display()))?;
+        input.write_all(text.as_bytes())?;
+        if enter {
+            input.write_all(b"\r")?;
+        }
+        Ok(())
+    }
+
+    fn terminate(&self, agent_id: &str) -> bool {
+        // The shell leads its own session and process group
+        pid_file(agent_id).is_some_and(|pid| signal::killpg(pid, Signal::SIGTERM).is_ok())
+    }
+
+    fn attach_argv(&self, agent_id: &str, read_only: bool) -> anyhow::Result<Vec<String>> {
+        if !read_only {
+            anyhow::bail!(
+                "The pty session backend can't attach interactively. Use --read-only to \
+                 watch '{agent_id}', or `rgt agents send` to type into it."
+            );
+        }
+        Ok(follow_log_argv(agent_id))
+    }
+}
+
+/// Reduce raw terminal output to plain text: drop CSI and OSC escape
+/// sequences and carriage returns.
+fn strip_terminal_codes(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI: parameters, then a final byte in @..~
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC: up to BEL or ST (ESC \)
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Any other escape: drop the character after ESC too
+                _ => {}
+            },
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_terminal_codes_leaves_text() {
+        let raw = "\x1b]0;claude\x07\x1b[1;32m✓ tests pass\x1b[0m\r\n\x1b[2K> ";
+        assert_eq!(strip_terminal_codes(raw), "✓ tests pass\n> ");
+    }
+}
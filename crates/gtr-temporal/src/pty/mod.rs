@@ -0,0 +1,879 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use gtr_core::atomic::{self, StateFile};
+use gtr_core::config::{EnvPolicy, ResourceLimits, SessionBackendKind, SessionConfig};
+use serde::{Deserialize, Serialize};
+
+#[cfg(windows)]
+mod console;
+#[cfg(unix)]
+mod daemon;
+#[cfg(unix)]
+mod remote;
+#[cfg(unix)]
+mod screen;
+#[cfg(unix)]
+mod tmux;
+
+#[cfg(windows)]
+pub use console::Pid;
+#[cfg(unix)]
+pub use nix::unistd::Pid;
+#[cfg(unix)]
+pub use tmux::{ensure_tmux, ensure_tmux_config};
+
+/// Runtime directory for a single agent's PTY session.
+/// Layout: ~/.gtr/runtime/<agent-id>/
+///   - pid         Process ID file
+///   - env.json    Env vars used at spawn
+///   - host        SSH destination, for agents of a remote rig
+///
+/// Both files are replaced atomically; see [`gtr_core::atomic`].
+pub fn runtime_dir(agent_id: &str) -> PathBuf {
+    runtime_root().join(agent_id)
+}
+
+fn runtime_root() -> PathBuf {
+    gtr_core::dirs::runtime_dir()
+}
+
+/// Derive the tmux (or screen) session name for an agent.
+pub fn tmux_session_name(agent_id: &str) -> String {
+    format!("gtr-{agent_id}")
+}
+
+/// Hosts agent sessions: starts a command detached from any terminal, then
+/// reports on, types into and tears down that session. Picked by the
+/// `[session] backend` setting in `town.toml`; see [`backend`].
+pub trait SessionBackend: Send + Sync {
+    /// Run `shell_cmd` through `sh -c` in a new detached session in
+    /// `work_dir`. Returns the PID of the session's top process.
+    fn spawn(
+        &self,
+        agent_id: &str,
+        shell_cmd: &str,
+        work_dir: &Path,
+        env_vars: &HashMap<String, String>,
+    ) -> anyhow::Result<Pid>;
+
+    fn is_alive(&self, agent_id: &str) -> bool;
+
+    /// PID of the session's top process, falling back to the PID file.
+    fn pid(&self, agent_id: &str) -> Option<Pid> {
+        pid_file(agent_id)
+    }
+
+    /// The last `lines` lines of output, as plain text.
+    fn capture(&self, agent_id: &str, lines: u32) -> Option<String>;
+
+    /// Type `text` into the session, then press Enter if `enter` is set.
+    fn send_keys(&self, agent_id: &str, text: &str, enter: bool) -> anyhow::Result<()>;
+
+    /// End the session. Returns whether there was one to end.
+    fn terminate(&self, agent_id: &str) -> bool;
+
+    /// The command line that attaches the current terminal to the session.
+    fn attach_argv(&self, agent_id: &str, read_only: bool) -> anyhow::Result<Vec<String>>;
+
+    /// Kept as `meta.json` in the agent's runtime dir unless the backend has
+    /// somewhere better.
+    fn set_metadata(&self, agent_id: &str, meta: &SessionMetadata) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(meta)?;
+        atomic::write_atomic(&runtime_dir(agent_id).join("meta.json"), json)?;
+        Ok(())
+    }
+
+    fn list_metadata(&self) -> Vec<(String, SessionMetadata)> {
+        let Ok(entries) = std::fs::read_dir(runtime_root()) else {
+            return Vec::new();
+        };
+        let mut sessions: Vec<(String, SessionMetadata)> = entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str().map(String::from))
+            .filter(|agent_id| self.is_alive(agent_id))
+            .map(|agent_id| {
+                let meta =
+                    atomic::read_json::<SessionMetadata>(&runtime_dir(&agent_id).join("meta.json"))
+                        .ok()
+                        .and_then(StateFile::ok)
+                        .unwrap_or_default();
+                (agent_id, meta)
+            })
+            .collect();
+        sessions.sort_by(|a, b| a.0.cmp(&b.0));
+        sessions
+    }
+
+    /// The session's working directory; by default the one it was spawned in.
+    fn current_path(&self, agent_id: &str) -> Option<PathBuf> {
+        let env_path = runtime_dir(agent_id).join("env.json");
+        let env: HashMap<String, String> = atomic::read_json(&env_path).ok()?.ok()?;
+        env.get("__GTR_WORK_DIR").map(PathBuf::from)
+    }
+}
+
+/// The backend configured in `town.toml`, read once per process. Switching
+/// backends strands sessions started under the old one, so stop the town
+/// (`rgt down`) before changing it.
+pub fn backend() -> &'static dyn SessionBackend {
+    match backend_kind() {
+        #[cfg(unix)]
+        SessionBackendKind::Tmux => &tmux::Tmux,
+        #[cfg(unix)]
+        SessionBackendKind::Screen => &screen::Screen,
+        #[cfg(unix)]
+        SessionBackendKind::Pty => &daemon::Daemon,
+        #[cfg(windows)]
+        SessionBackendKind::Console => &console::Console,
+        _ => unreachable!("backend_kind() only returns backends built for this platform"),
+    }
+}
+
+/// The configured backend if it's available on this platform: Windows always
+/// uses console windows, and elsewhere `console` falls back to tmux.
+pub fn backend_kind() -> SessionBackendKind {
+    static KIND: OnceLock<SessionBackendKind> = OnceLock::new();
+    *KIND.get_or_init(|| match SessionConfig::load().backend {
+        _ if cfg!(windows) => SessionBackendKind::Console,
+        SessionBackendKind::Console => SessionBackendKind::Tmux,
+        kind => kind,
+    })
+}
+
+/// The SSH destination an agent was spawned on, if its rig is remote.
+pub fn remote_host(agent_id: &str) -> Option<String> {
+    let path = runtime_dir(agent_id).join("host");
+    let host = std::fs::read_to_string(path).ok()?;
+    Some(host.trim().to_string()).filter(|h| !h.is_empty())
+}
+
+/// The backend an agent's session lives under: its host's tmux over SSH for
+/// remote rigs, the configured backend otherwise.
+fn session_backend(agent_id: &str) -> &'static dyn SessionBackend {
+    #[cfg(unix)]
+    if remote_host(agent_id).is_some() {
+        return &remote::Remote;
+    }
+    backend()
+}
+
+/// Check if an agent's session is alive.
+pub fn is_alive(agent_id: &str) -> bool {
+    session_backend(agent_id).is_alive(agent_id)
+}
+
+/// Read the PID of an agent's process (the session's top command). For a
+/// remote agent this is a PID on its host.
+pub fn read_pid(agent_id: &str) -> Option<Pid> {
+    session_backend(agent_id).pid(agent_id)
+}
+
+fn pid_file(agent_id: &str) -> Option<Pid> {
+    let pid_path = runtime_dir(agent_id).join("pid");
+    match atomic::read_state(&pid_path, |s| s.trim().parse::<i32>().ok()).ok()? {
+        StateFile::Valid(pid) => Some(Pid::from_raw(pid)),
+        StateFile::Corrupt { quarantined } => {
+            tracing::warn!(
+                "Ignoring corrupt PID file for '{agent_id}' (moved to {})",
+                quarantined.display()
+            );
+            None
+        }
+        StateFile::Missing => None,
+    }
+}
+
+/// Capture the last N lines of an agent's session output.
+pub fn capture_pane(agent_id: &str, lines: u32) -> Option<String> {
+    let text = session_backend(agent_id)
+        .capture(agent_id, lines)?
+        .trim()
+        .to_string();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// The command line that attaches the current terminal to an agent's session.
+pub fn attach_argv(agent_id: &str, read_only: bool) -> anyhow::Result<Vec<String>> {
+    session_backend(agent_id).attach_argv(agent_id, read_only)
+}
+
+/// `tail -F` of an agent's output log, for backends without a read-only
+/// attach of their own.
+#[cfg(unix)]
+fn follow_log_argv(agent_id: &str) -> Vec<String> {
+    let log = crate::output_log::log_path(agent_id);
+    vec![
+        "tail".into(),
+        "-n".into(),
+        "200".into(),
+        "-F".into(),
+        log.to_string_lossy().into_owned(),
+    ]
+}
+
+/// The last observed state of an agent's pane, kept in its runtime dir so
+/// samples taken by separate activity runs can be compared.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct PaneSample {
+    /// FNV-1a hash of the captured pane text
+    hash: u64,
+    /// Unix seconds when the hash last changed
+    changed_at: u64,
+}
+
+impl PaneSample {
+    /// Fold a new capture into the previous sample. A first sample counts as
+    /// a change.
+    fn next(prev: Option<PaneSample>, text: &str, now: u64) -> PaneSample {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in text.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        match prev {
+            Some(prev) if prev.hash == hash => prev,
+            _ => PaneSample {
+                hash,
+                changed_at: now,
+            },
+        }
+    }
+}
+
+/// Sample an agent's pane and return how many seconds ago its output last
+/// changed. `None` if the pane can't be captured.
+pub fn pane_idle_secs(agent_id: &str) -> Option<u64> {
+    let text = capture_pane(agent_id, 200)?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    let path = runtime_dir(agent_id).join("pane-sample.json");
+    let prev = atomic::read_json::<PaneSample>(&path)
+        .ok()
+        .and_then(StateFile::ok);
+    let sample = PaneSample::next(prev, &text, now);
+    if Some(sample) != prev {
+        if let Ok(json) = serde_json::to_string(&sample) {
+            let _ = atomic::write_atomic(&path, json);
+        }
+    }
+    Some(now.saturating_sub(sample.changed_at))
+}
+
+/// Context shown in tmux for an agent session: the pane title and window name
+/// are derived from it, and each field is also stored as a `@gtr_*` session
+/// user option so `tmux -L gtr ls -F` and `rgt sessions` can read it back.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionMetadata {
+    pub role: String,
+    pub rig: Option<String>,
+    pub work_item: Option<String>,
+    pub status: Option<String>,
+}
+
+impl SessionMetadata {
+    /// Human-readable title, e.g. `polecat · gtr · wi-abc · working`.
+    pub fn title(&self) -> String {
+        let mut parts = vec![self.role.as_str()];
+        parts.extend(self.rig.as_deref());
+        parts.extend(self.work_item.as_deref());
+        parts.extend(self.status.as_deref());
+        parts.retain(|p| !p.is_empty());
+        parts.join(" · ")
+    }
+}
+
+/// Attach metadata to an agent's session (and, with tmux, refresh its pane
+/// title and window name). Empty fields clear the stored value.
+pub fn set_session_metadata(agent_id: &str, meta: &SessionMetadata) -> anyhow::Result<()> {
+    session_backend(agent_id).set_metadata(agent_id, meta)
+}
+
+/// List every live gtr session with its metadata, keyed by agent ID.
+/// Sessions spawned before metadata existed come back with empty fields.
+pub fn list_session_metadata() -> Vec<(String, SessionMetadata)> {
+    let sessions = backend().list_metadata();
+    #[cfg(unix)]
+    let sessions = {
+        let mut sessions = sessions;
+        sessions.extend(remote::Remote.list_metadata());
+        sessions.sort_by(|a, b| a.0.cmp(&b.0));
+        sessions
+    };
+    sessions
+}
+
+/// Clean up runtime directory for an agent. Output logs are kept (see
+/// [`crate::output_log`]); the directory goes once nothing else is left.
+/// Logs of agents cleaned up earlier go once they're past
+/// [`crate::output_log::LOG_RETENTION`].
+pub fn cleanup(agent_id: &str) -> std::io::Result<()> {
+    crate::output_log::prune_finished(&runtime_root(), crate::output_log::LOG_RETENTION);
+    let dir = runtime_dir(agent_id);
+    if !dir.exists() {
+        return Ok(());
+    }
+    let mut kept_logs = false;
+    for entry in std::fs::read_dir(&dir)?.flatten() {
+        let path = entry.path();
+        if crate::output_log::is_log_file(&entry.file_name().to_string_lossy()) {
+            kept_logs = true;
+        } else if path.is_dir() {
+            std::fs::remove_dir_all(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    if !kept_logs {
+        std::fs::remove_dir(&dir)?;
+    }
+    Ok(())
+}
+
+/// Spawn a subprocess inside a detached session. Returns the child PID.
+pub fn spawn(
+    agent_id: &str,
+    program: &str,
+    args: &[String],
+    work_dir: &Path,
+    env_vars: &HashMap<String, String>,
+) -> anyhow::Result<Pid> {
+    spawn_limited(
+        agent_id,
+        program,
+        args,
+        work_dir,
+        env_vars,
+        &ResourceLimits::default(),
+        None,
+    )
+}
+
+/// [`spawn`] with CPU, memory and niceness caps on the agent process, on
+/// `host` over SSH if it's set.
+pub fn spawn_limited(
+    agent_id: &str,
+    program: &str,
+    args: &[String],
+    work_dir: &Path,
+    env_vars: &HashMap<String, String>,
+    limits: &ResourceLimits,
+    host: Option<&str>,
+) -> anyhow::Result<Pid> {
+    // Create runtime directory
+    let dir = runtime_dir(agent_id);
+    std::fs::create_dir_all(&dir)?;
+
+    // Later calls on the agent find its host here
+    if let Some(host) = host {
+        if cfg!(windows) {
+            anyhow::bail!("Rigs on remote hosts need a unix worker");
+        }
+        atomic::write_atomic(&dir.join("host"), host)?;
+    }
+
+    // Under an [env] policy the agent gets exactly the sanitized environment
+    // instead of inheriting the worker's (or the tmux server's). A remote
+    // agent inherits its host's environment, so only the extras are filtered.
+    let policy = EnvPolicy::load();
+    let inherited: Vec<(String, String)> = match host {
+        Some(_) => Vec::new(),
+        None => std::env::vars().collect(),
+    };
+    let sanitized = (!policy.is_empty()).then(|| policy.apply(inherited, env_vars));
+    let env_vars = sanitized.as_ref().unwrap_or(env_vars);
+
+    // Write env.json for debugging and respawn recovery. Under a policy this
+    // is everything the agent was given.
+    let mut env_save = env_vars.clone();
+    env_save.insert(
+        "__GTR_WORK_DIR".into(),
+        work_dir.to_string_lossy().to_string(),
+    );
+    let env_json = serde_json::to_string_pretty(&env_save)?;
+    atomic::write_atomic(&dir.join("env.json"), env_json)?;
+
+    let shell_cmd = shell_command(program, args, limits, host.is_none());
+    let shell_cmd = match (&sanitized, host) {
+        (Some(env), None) => clean_env_command(&shell_cmd, env),
+        _ => shell_cmd,
+    };
+
+    let pid = session_backend(agent_id).spawn(agent_id, &shell_cmd, work_dir, env_vars)?;
+
+    match host {
+        Some(host) => tracing::info!("Spawned agent '{agent_id}' on {host} (PID {pid} there)"),
+        None => {
+            // Write PID file for backward compat
+            atomic::write_atomic(&dir.join("pid"), pid.to_string())?;
+            tracing::info!(
+                "Spawned agent '{agent_id}' in a {:?} session (PID {pid})",
+                backend_kind()
+            );
+        }
+    }
+
+    Ok(pid)
+}
+
+/// Build the shell command string.
+/// Unset CLAUDECODE to prevent Claude Code from refusing to start
+/// (it detects nested sessions via this env var). A systemd scope is only
+/// looked for when the command runs `local`ly; remote limits use `ulimit`.
+#[cfg(unix)]
+fn shell_command(program: &str, args: &[String], limits: &ResourceLimits, local: bool) -> String {
+    let escaped_program = shell_escape::escape(program.into());
+    let escaped_args: Vec<String> = args
+        .iter()
+        .map(|a| shell_escape::escape(a.into()).to_string())
+        .collect();
+    let inner_cmd = if escaped_args.is_empty() {
+        escaped_program.to_string()
+    } else {
+        format!("{escaped_program} {}", escaped_args.join(" "))
+    };
+    let inner_cmd = if limits.is_empty() {
+        inner_cmd
+    } else {
+        limit_command(&inner_cmd, limits, local && systemd_scope_available())
+    };
+    format!("unset CLAUDECODE; {inner_cmd}")
+}
+
+#[cfg(windows)]
+fn shell_command(program: &str, args: &[String], limits: &ResourceLimits, _local: bool) -> String {
+    if !limits.is_empty() {
+        tracing::warn!("Resource limits are not enforced on Windows");
+    }
+    console::command_line(program, args)
+}
+
+/// Run `cmd` with `env` as its entire environment.
+#[cfg(unix)]
+fn clean_env_command(cmd: &str, env: &HashMap<String, String>) -> String {
+    let mut assignments: Vec<String> = env
+        .iter()
+        .map(|(k, v)| shell_escape::escape(format!("{k}={v}").into()).to_string())
+        .collect();
+    assignments.sort();
+    format!(
+        "exec env -i {} /bin/sh -c {}",
+        assignments.join(" "),
+        shell_escape::escape(cmd.into())
+    )
+}
+
+/// Prefix `cmd` with removals of every variable the new console would
+/// inherit that isn't in `env`.
+#[cfg(windows)]
+fn clean_env_command(cmd: &str, env: &HashMap<String, String>) -> String {
+    let mut line = String::new();
+    for (name, _) in std::env::vars().filter(|(name, _)| !env.contains_key(name)) {
+        line.push_str(&format!(
+            "Remove-Item 'Env:{}' -ErrorAction SilentlyContinue; ",
+            name.replace('\'', "''")
+        ));
+    }
+    line.push_str(cmd);
+    line
+}
+
+/// Wrap an escaped command line so it runs under `limits`. With a systemd
+/// user scope the CPU and memory caps are cgroup limits; without one, memory
+/// falls back to `ulimit -v` and the CPU quota can't be enforced.
+#[cfg(unix)]
+fn limit_command(cmd: &str, limits: &ResourceLimits, cgroups: bool) -> String {
+    let cmd = match limits.nice {
+        Some(n) => format!("nice -n {n} {cmd}"),
+        None => cmd.to_string(),
+    };
+    if cgroups {
+        let mut props = String::new();
+        if let Some(cpu) = limits.cpu_percent {
+            props.push_str(&format!(" -p CPUQuota={cpu}%"));
+        }
+        if let Some(mb) = limits.memory_mb {
+            props.push_str(&format!(" -p MemoryMax={mb}M"));
+        }
+        if props.is_empty() {
+            return cmd;
+        }
+        return format!("systemd-run --user --scope --quiet --collect{props} -- {cmd}");
+    }
+    if limits.cpu_percent.is_some() {
+        tracing::warn!("No systemd user scope available; cpu_percent is not enforced");
+    }
+    match limits.memory_mb {
+        Some(mb) => format!("ulimit -v {}; {cmd}", mb * 1024),
+        None => cmd,
+    }
+}
+
+/// Whether `systemd-run --user --scope` works here (Linux with a user
+/// session manager and cgroup v2 delegation).
+#[cfg(unix)]
+fn systemd_scope_available() -> bool {
+    cfg!(target_os = "linux")
+        && std::process::Command::new("systemd-run")
+            .args(["--user", "--scope", "--quiet", "true"])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success())
+}
+
+/// Spawn a process in a detached session.
+/// This is the main entry point for launching an agent.
+/// (Thin wrapper around spawn — no more server thread or reaper thread needed.)
+pub fn spawn_with_server(
+    agent_id: &str,
+    program: &str,
+    args: &[String],
+    work_dir: &Path,
+    env_vars: &HashMap<String, String>,
+) -> anyhow::Result<Pid> {
+    spawn(agent_id, program, args, work_dir, env_vars)
+}
+
+/// Kill an agent's session and all processes in its process group.
+pub fn kill_agent(agent_id: &str) -> anyhow::Result<bool> {
+    // Get the pane PID before killing the session; a remote agent's PID is
+    // on its host, where killing the session is all we can do
+    let pane_pid = remote_host(agent_id)
+        .is_none()
+        .then(|| read_pid(agent_id))
+        .flatten();
+
+    let session_existed = session_backend(agent_id).terminate(agent_id);
+
+    // Kill the process group to catch subagents spawned by Claude Code
+    // (on Windows, terminating the session already took the process tree)
+    #[cfg(unix)]
+    if let Some(pid) = pane_pid {
+        kill_process_group(pid);
+    }
+
+    cleanup(agent_id)?;
+    Ok(session_existed || pane_pid.is_some())
+}
+
+#[cfg(unix)]
+fn kill_process_group(pid: Pid) {
+    // Try to kill the entire process group
+    let pgid = nix::unistd::getpgid(Some(pid)).ok();
+    if let Some(pgid) = pgid {
+        // Kill process group with SIGTERM
+        nix::sys::signal::killpg(pgid, nix::sys::signal::Signal::SIGTERM).ok();
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        // Force kill if still around
+        nix::sys::signal::killpg(pgid, nix::sys::signal::Signal::SIGKILL).ok();
+    } else {
+        // Fall back to killing the individual PID
+        nix::sys::signal::kill(pid, nix::sys::signal::Signal::SIGTERM).ok();
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        nix::sys::signal::kill(pid, nix::sys::signal::Signal::SIGKILL).ok();
+    }
+}
+
+/// Type `text` into an agent's pane as if a user had typed it, then press
+/// Enter if `enter` is set.
+pub fn send_keys(agent_id: &str, text: &str, enter: bool) -> anyhow::Result<()> {
+    if !is_alive(agent_id) {
+        anyhow::bail!("Agent '{agent_id}' has no live session");
+    }
+    session_backend(agent_id).send_keys(agent_id, text, enter)
+}
+
+/// The working directory of an agent's pane.
+pub fn pane_current_path(agent_id: &str) -> Option<PathBuf> {
+    session_backend(agent_id).current_path(agent_id)
+}
+
+/// Result of [`shutdown_agents`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    /// Agents that wrote a checkpoint (e.g. via `rgt handoff`) during the grace period
+    pub checkpointed: Vec<String>,
+    /// Agents that exited on their own during the grace period
+    pub exited: Vec<String>,
+    /// Agents whose sessions were killed
+    pub killed: usize,
+}
+
+/// Shut agents down gracefully: type `message` (a "wrap up and hand off"
+/// nudge) into every live session, wait up to `grace` for each agent to write
+/// a checkpoint in its working directory or exit, then kill the sessions.
+pub fn shutdown_agents(
+    agent_ids: &[String],
+    message: &str,
+    grace: std::time::Duration,
+) -> ShutdownReport {
+    let nudged_at = std::time::SystemTime::now();
+    let mut waiting: Vec<(String, Option<PathBuf>)> = agent_ids
+        .iter()
+        .filter(|id| is_alive(id))
+        .filter(|id| match send_keys(id, message, true) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("Could not nudge {id} before shutdown: {e}");
+                false
+            }
+        })
+        .map(|id| (id.clone(), pane_current_path(id)))
+        .collect();
+
+    let mut report = ShutdownReport::default();
+    let deadline = std::time::Instant::now() + grace;
+    while !waiting.is_empty() && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_secs(2));
+        waiting.retain(|(id, dir)| {
+            if !is_alive(id) {
+                report.exited.push(id.clone());
+                return false;
+            }
+            let checkpointed = dir
+                .as_deref()
+                .and_then(gtr_core::checkpoint::Checkpoint::modified)
+                .is_some_and(|at| at >= nudged_at);
+            if checkpointed {
+                report.checkpointed.push(id.clone());
+            }
+            !checkpointed
+        });
+    }
+    for (id, _) in &waiting {
+        tracing::warn!(
+            "{id} did not check in within {}s of the shutdown nudge",
+            grace.as_secs()
+        );
+    }
+
+    for id in agent_ids {
+        if kill_agent(id).unwrap_or(false) {
+            report.killed += 1;
+        }
+    }
+    report
+}
+
+/// tmux sessions `rgt start` runs the worker and Temporal dev server in, and
+/// `rgt attach --grid` dashboards; none of them belong to an agent.
+fn is_agent_session(agent_id: &str) -> bool {
+    !matches!(agent_id, "worker" | "temporal-server") && !agent_id.starts_with("grid-")
+}
+
+/// Sessions and runtime dirs found by [`reconcile_sessions`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrphanReport {
+    /// Live agent sessions with no running workflow behind them
+    pub sessions: Vec<String>,
+    /// Runtime dirs (PID file, env.json, ...) left behind by dead sessions
+    pub runtime_dirs: Vec<String>,
+    /// Whether the orphans were killed and cleaned up, or only found
+    pub reaped: bool,
+}
+
+/// Find agent sessions and runtime dirs whose agent isn't in `owners` (the
+/// IDs of running workflows), e.g. after a workflow crashed or was
+/// terminated. With `reap` they are killed and cleaned up; output logs are
+/// kept either way.
+pub fn reconcile_sessions(owners: &HashSet<String>, reap: bool) -> OrphanReport {
+    // Boot spawns and watches the mayor's session itself
+    let boot_running = owners.contains(&gtr_core::state::boot_workflow_id());
+    let owned = |agent_id: &str| {
+        owners.contains(agent_id)
+            || (boot_running && agent_id == gtr_core::state::mayor_workflow_id())
+    };
+
+    let mut report = OrphanReport {
+        reaped: reap,
+        ..Default::default()
+    };
+    for (agent_id, _) in list_session_metadata() {
+        if is_agent_session(&agent_id) && !owned(&agent_id) {
+            report.sessions.push(agent_id);
+        }
+    }
+    let dirs = std::fs::read_dir(runtime_root())
+        .into_iter()
+        .flatten()
+        .flatten();
+    for entry in dirs {
+        let agent_id = entry.file_name().to_string_lossy().into_owned();
+        if owned(&agent_id) || report.sessions.contains(&agent_id) || is_alive(&agent_id) {
+            continue;
+        }
+        let leftovers = std::fs::read_dir(entry.path())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .any(|f| !crate::output_log::is_log_file(&f.file_name().to_string_lossy()));
+        if leftovers {
+            report.runtime_dirs.push(agent_id);
+        }
+    }
+    report.sessions.sort();
+    report.runtime_dirs.sort();
+
+    if reap {
+        for agent_id in &report.sessions {
+            tracing::warn!("Killing orphaned session for {agent_id}");
+            if let Err(e) = kill_agent(agent_id) {
+                tracing::warn!("Could not clean up after {agent_id}: {e}");
+            }
+        }
+        for agent_id in &report.runtime_dirs {
+            if let Err(e) = cleanup(agent_id) {
+                tracing::warn!("Could not clean up runtime dir for {agent_id}: {e}");
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_dir_structure() {
+        let dir = runtime_dir("mayor");
+        assert!(dir.ends_with(".gtr/runtime/mayor"));
+    }
+
+    #[test]
+    fn pane_sample_tracks_last_change() {
+        let first = PaneSample::next(None, "building...", 100);
+        assert_eq!(first.changed_at, 100);
+        // Same output later: still idle since the first sample
+        assert_eq!(PaneSample::next(Some(first), "building...", 400), first);
+        let changed = PaneSample::next(Some(first), "building... done", 500);
+        assert_eq!(changed.changed_at, 500);
+        assert_ne!(changed.hash, first.hash);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn limit_command_uses_scope_or_ulimit() {
+        let limits = ResourceLimits {
+            cpu_percent: Some(150),
+            memory_mb: Some(2048),
+            nice: Some(10),
+        };
+        assert_eq!(
+            limit_command("claude 'go'", &limits, true),
+            "systemd-run --user --scope --quiet --collect -p CPUQuota=150% -p MemoryMax=2048M -- nice -n 10 claude 'go'"
+        );
+        assert_eq!(
+            limit_command("claude 'go'", &limits, false),
+            "ulimit -v 2097152; nice -n 10 claude 'go'"
+        );
+        let nice_only = ResourceLimits {
+            nice: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(limit_command("sh", &nice_only, true), "nice -n 5 sh");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn clean_env_command_replaces_environment() {
+        let env = HashMap::from([
+            ("PATH".to_string(), "/usr/bin".to_string()),
+            ("GTR_ROLE".to_string(), "polecat".to_string()),
+        ]);
+        assert_eq!(
+            clean_env_command("unset CLAUDECODE; claude", &env),
+            "exec env -i GTR_ROLE=polecat PATH=/usr/bin /bin/sh -c 'unset CLAUDECODE; claude'"
+        );
+    }
+
+    #[test]
+    fn tmux_session_name_format() {
+        assert_eq!(tmux_session_name("mayor"), "gtr-mayor");
+        assert_eq!(
+            tmux_session_name("cfb-stats-polecat-nux"),
+            "gtr-cfb-stats-polecat-nux"
+        );
+    }
+
+    #[test]
+    fn infrastructure_sessions_are_not_agents() {
+        assert!(is_agent_session("gtr-polecat-nux"));
+        assert!(is_agent_session("mayor"));
+        assert!(!is_agent_session("worker"));
+        assert!(!is_agent_session("temporal-server"));
+        assert!(!is_agent_session("grid-gtr"));
+    }
+
+    #[test]
+    fn is_alive_returns_false_for_nonexistent() {
+        assert!(!is_alive("nonexistent-agent-xyz"));
+    }
+
+    #[test]
+    fn capture_pane_returns_none_for_nonexistent() {
+        assert!(capture_pane("nonexistent-agent-xyz", 100).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn spawn_and_kill_echo() {
+        // Skip if tmux not installed
+        if std::process::Command::new("tmux")
+            .arg("-V")
+            .output()
+            .is_err()
+        {
+            eprintln!("Skipping test -- tmux not installed");
+            return;
+        }
+
+        let agent_id = "test-spawn-echo";
+        cleanup(agent_id).ok();
+        // Kill any leftover session
+        let session = tmux_session_name(agent_id);
+        let _ = std::process::Command::new("tmux")
+            .args(["-L", "gtr", "kill-session", "-t", &session])
+            .output();
+
+        let mut env = HashMap::new();
+        env.insert("TEST_VAR".into(), "hello".into());
+
+        let result = spawn(
+            agent_id,
+            "/bin/sh",
+            &["-c".into(), "sleep 30".into()],
+            Path::new("/tmp"),
+            &env,
+        );
+        assert!(result.is_ok(), "spawn failed: {:?}", result.err());
+        let pid = result.unwrap();
+
+        // Verify PID file written
+        assert!(runtime_dir(agent_id).join("pid").exists());
+
+        // Verify tmux session exists
+        assert!(is_alive(agent_id));
+
+        // Verify read_pid works
+        let read = read_pid(agent_id);
+        assert!(read.is_some());
+        assert_eq!(read.unwrap(), pid);
+
+        // Kill it
+        let killed = kill_agent(agent_id);
+        assert!(killed.is_ok());
+        assert!(killed.unwrap());
+
+        // Verify session is gone
+        assert!(!is_alive(agent_id));
+    }
+}
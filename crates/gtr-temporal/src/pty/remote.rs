@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use nix::unistd::Pid;
+
+use super::tmux::TMUX_CONF;
+use super::{remote_host, tmux_session_name, SessionBackend};
+
+/// Sessions on the `tmux -L gtr` server of another machine, driven over SSH.
+/// Agents of a rig with a `host` in rigs.toml run here; the host is recorded
+/// in the agent's runtime dir at spawn, which is how later calls find it.
+/// Metadata and env.json stay on the worker, and output isn't logged.
+pub struct Remote;
+
+/// Join `argv` into one command line for the remote shell.
+fn command_line(argv: &[&str]) -> String {
+    argv.iter()
+        .map(|arg| shell_escape::escape((*arg).into()).into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Run `script` with the remote user's shell on the agent's host.
+fn ssh(agent_id: &str, script: &str) -> anyhow::Result<std::process::Output> {
+    let Some(host) = remote_host(agent_id) else {
+        anyhow::bail!("Agent '{agent_id}' has no remote host recorded");
+    };
+    std::process::Command::new("ssh")
+        .args([
+            "-o",
+            "BatchMode=yes",
+            "-o",
+            "ConnectTimeout=10",
+            &host,
+            script,
+        ])
+        .output()
+        .map_err(|e| anyhow::anyhow!("ssh not found: {e}"))
+}
+
+/// Run `tmux -L gtr <args>` on the agent's host.
+fn tmux(agent_id: &str, args: &[&str]) -> anyhow::Result<std::process::Output> {
+    let mut argv = vec!["tmux", "-L", "gtr"];
+    argv.extend(args);
+    ssh(agent_id, &command_line(&argv))
+}
+
+impl SessionBackend for Remote {
+    fn spawn(
+        &self,
+        agent_id: &str,
+        shell_cmd: &str,
+        work_dir: &Path,
+        env_vars: &HashMap<String, String>,
+    ) -> anyhow::Result<Pid> {
+        let session = tmux_session_name(agent_id);
+        let work_dir = work_dir.to_string_lossy();
+        let env: Vec<String> = env_vars.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        let mut new_session = vec![
+            "new-session",
+            "-d",
+            "-s",
+            &session,
+            "-c",
+            &work_dir,
+            "-x",
+            "200",
+            "-y",
+            "50",
+        ];
+        for var in &env {
+            new_session.extend(["-e", var.as_str()]);
+        }
+        new_session.push(shell_cmd);
+        let list_panes = [
+            "tmux",
+            "-L",
+            "gtr",
+            "list-panes",
+            "-t",
+            &session,
+            "-F",
+            "#{pane_pid}",
+        ];
+
+        // Same tmux config as on the worker, so attach and detach behave alike
+        let script = format!(
+            "conf=\"$HOME/.gtr/config/tmux.conf\"; mkdir -p \"$HOME/.gtr/config\" {work} && \
+             {{ [ -f \"$conf\" ] || printf %s {conf} > \"$conf\"; }} && \
+             tmux -L gtr -f \"$conf\" {new_session} && {list_panes}",
+            work = shell_escape::escape(work_dir.as_ref().into()),
+            conf = shell_escape::escape(TMUX_CONF.into()),
+            new_session = command_line(&new_session),
+            list_panes = command_line(&list_panes),
+        );
+        let output = ssh(agent_id, &script)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux new-session over ssh failed: {stderr}");
+        }
+        let pid_str = String::from_utf8_lossy(&output.stdout);
+        let pid: i32 = pid_str
+            .trim()
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Failed to parse remote pane PID '{pid_str}': {e}"))?;
+        Ok(Pid::from_raw(pid))
+    }
+
+    fn is_alive(&self, agent_id: &str) -> bool {
+        let session = tmux_session_name(agent_id);
+        tmux(agent_id, &["has-session", "-t", &session]).is_ok_and(|o| o.status.success())
+    }
+
+    /// The pane's PID on the remote host, for display only.
+    fn pid(&self, agent_id: &str) -> Option<Pid> {
+        let session = tmux_session_name(agent_id);
+        let output = tmux(
+            agent_id,
+            &["list-panes", "-t", &session, "-F", "#{pane_pid}"],
+        )
+        .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let pid: i32 = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .ok()?;
+        Some(Pid::from_raw(pid))
+    }
+
+    fn capture(&self, agent_id: &str, lines: u32) -> Option<String> {
+        let session = tmux_session_name(agent_id);
+        let start = format!("-{lines}");
+        let output = tmux(
+            agent_id,
+            &["capture-pane", "-t", &session, "-p", "-S", &start],
+        )
+        .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn send_keys(&self, agent_id: &str, text: &str, enter: bool) -> anyhow::Result<()> {
+        let session = tmux_session_name(agent_id);
+        let mut args = vec!["send-keys", "-t", &session, "-l", text];
+        if enter {
+            // A second command in the same tmux call, so Enter is a key name
+            args.extend([";", "send-keys", "-t", &session, "Enter"]);
+        }
+        let output = tmux(agent_id, &args)?;
+        if !output.status.success() {
+            anyhow::bail!("tmux send-keys to '{session}' over ssh failed");
+        }
+        Ok(())
+    }
+
+    fn terminate(&self, agent_id: &str) -> bool {
+        let session = tmux_session_name(agent_id);
+        tmux(agent_id, &["kill-session", "-t", &session]).is_ok_and(|o| o.status.success())
+    }
+
+    fn attach_argv(&self, agent_id: &str, read_only: bool) -> anyhow::Result<Vec<String>> {
+        let Some(host) = remote_host(agent_id) else {
+            anyhow::bail!("Agent '{agent_id}' has no remote host recorded");
+        };
+        let session = tmux_session_name(agent_id);
+        let mut attach = vec!["tmux", "-L", "gtr", "attach-session", "-t", &session];
+        if read_only {
+            attach.extend(["-f", "read-only,ignore-size"]);
+        }
+        Ok(vec!["ssh".into(), "-t".into(), host, command_line(&attach)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_line_escapes_each_arg() {
+        assert_eq!(
+            command_line(&["tmux", "send-keys", "-l", "it's $HOME"]),
+            "tmux send-keys -l 'it'\\''s $HOME'"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use nix::unistd::Pid;
+
+use super::{follow_log_argv, runtime_dir, tmux_session_name, SessionBackend};
+
+/// Sessions under GNU screen, named like the tmux ones (`gtr-<agent>`).
+/// screen logs the window to the agent's output log itself, without
+/// rotation or session recordings, and has no read-only attach, so
+/// `--read-only` follows that log instead.
+pub struct Screen;
+
+fn screen(agent_id: &str, command: &[&str]) -> std::io::Result<std::process::Output> {
+    std::process::Command::new("screen")
+        .args(["-S", &tmux_session_name(agent_id), "-p", "0", "-X"])
+        .args(command)
+        .output()
+}
+
+impl SessionBackend for Screen {
+    fn spawn(
+        &self,
+        agent_id: &str,
+        shell_cmd: &str,
+        work_dir: &Path,
+        env_vars: &HashMap<String, String>,
+    ) -> anyhow::Result<Pid> {
+        let session = tmux_session_name(agent_id);
+        let log = crate::output_log::log_path(agent_id);
+        let output = std::process::Command::new("screen")
+            .args(["-dmS", &session, "-L", "-Logfile"])
+            .arg(&log)
+            .args(["sh", "-c", shell_cmd])
+            .current_dir(work_dir)
+            .envs(env_vars)
+            .output()
+            .map_err(|_| anyhow::anyhow!("screen not found — install GNU screen >= 4.6"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("screen -dmS failed: {stderr}");
+        }
+        // Flush the log every second rather than every ten
+        let _ = screen(agent_id, &["logfile", "flush", "1"]);
+
+        self.pid(agent_id)
+            .ok_or_else(|| anyhow::anyhow!("screen session '{session}' exited immediately"))
+    }
+
+    fn is_alive(&self, agent_id: &str) -> bool {
+        self.pid(agent_id).is_some()
+    }
+
+    /// The PID of the screen process that owns the session.
+    fn pid(&self, agent_id: &str) -> Option<Pid> {
+        let session = tmux_session_name(agent_id);
+        let output = std::process::Command::new("screen")
+            .args(["-ls", &session])
+            .output()
+            .ok()?;
+        // screen -ls exits non-zero even when it lists sessions
+        parse_screen_ls(&String::from_utf8_lossy(&output.stdout), &session)
+    }
+
+    fn capture(&self, agent_id: &str, lines: u32) -> Option<String> {
+        let path = runtime_dir(agent_id).join("hardcopy.txt");
+        let output = screen(agent_id, &["hardcopy", "-h", path.to_str()?]).ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let text = std::fs::read_to_string(&path).ok();
+        let _ = std::fs::remove_file(&path);
+        let text = text?;
+        let all: Vec<&str> = text.trim_end().lines().collect();
+        let start = all.len().saturating_sub(lines as usize);
+        Some(all[start..].join("\n"))
+    }
+
+    fn send_keys(&self, agent_id: &str, text: &str, enter: bool) -> anyhow::Result<()> {
+        // `stuff` expands \ and ^ escapes, so escape them in the literal text
+        let mut keys = text.replace('\\', "\\\\").replace('^', "\\^");
+        if enter {
+            keys.push_str("^M");
+        }
+        let output = screen(agent_id, &["stuff", &keys])?;
+        if !output.status.success() {
+            anyhow::bail!("screen stuff to '{}' failed", tmux_session_name(agent_id));
+        }
+        Ok(())
+    }
+
+    fn terminate(&self, agent_id: &str) -> bool {
+        screen(agent_id, &["quit"]).is_ok_and(|o| o.status.success())
+    }
+
+    fn attach_argv(&self, agent_id: &str, read_only: bool) -> anyhow::Result<Vec<String>> {
+        if read_only {
+            return Ok(follow_log_argv(agent_id));
+        }
+        // -x joins the session even if someone else is attached
+        Ok(vec![
+            "screen".into(),
+            "-x".into(),
+            tmux_session_name(agent_id),
+        ])
+    }
+}
+
+/// Find `session` in `screen -ls` output, whose entries look like
+/// `\t12345.gtr-mayor\t(Detached)`, and return its PID.
+fn parse_screen_ls(output: &str, session: &str) -> Option<Pid> {
+    output.lines().find_map(|line| {
+        let entry = line.split_whitespace().next()?;
+        let (pid, name) = entry.split_once('.')?;
+        if name != session {
+            return None;
+        }
+        pid.parse().ok().map(Pid::from_raw)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_screen_ls_matches_exact_name() {
+        let output = "There are screens on:\n\
+                      \t4242.gtr-mayor-2\t(Detached)\n\
+                      \t4141.gtr-mayor\t(10/16/26 09:12:01)\t(Attached)\n\
+                      2 Sockets in /run/screen/S-gtr.\n";
+        assert_eq!(
+            parse_screen_ls(output, "gtr-mayor"),
+            Some(Pid::from_raw(4141))
+        );
+        assert_eq!(parse_screen_ls(output, "gtr-witness"), None);
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use nix::unistd::Pid;
+
+use super::{pid_file, tmux_session_name, SessionBackend, SessionMetadata};
+
+/// Sessions on a dedicated tmux server (`tmux -L gtr`). The default backend,
+/// and the only one with live pane titles, output piping and grid attach.
+pub struct Tmux;
+
+/// Verify tmux is installed and >= 3.2.
+pub fn ensure_tmux() -> anyhow::Result<()> {
+    let output = std::process::Command::new("tmux")
+        .arg("-V")
+        .output()
+        .map_err(|_| anyhow::anyhow!("tmux not found — install tmux >= 3.2"))?;
+
+    if !output.status.success() {
+        anyhow::bail!("tmux -V failed");
+    }
+
+    let version_str = String::from_utf8_lossy(&output.stdout);
+    // Parse "tmux 3.6a" -> "3.6"
+    let version_part = version_str
+        .trim()
+        .strip_prefix("tmux ")
+        .unwrap_or(version_str.trim());
+    // Extract major.minor (strip trailing letters like "a")
+    let numeric: String = version_part
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let major_minor: f64 = numeric.parse().unwrap_or(0.0);
+    if major_minor < 3.2 {
+        anyhow::bail!("tmux >= 3.2 required (found {version_str})");
+    }
+
+    Ok(())
+}
+
+/// Contents of `~/.gtr/config/tmux.conf`, written if missing.
+pub(super) const TMUX_CONF: &str = "set -g status off\n\
+                                    set -g mouse on\n\
+                                    set -g history-limit 50000\n\
+                                    set -g default-terminal \"xterm-256color\"\n\
+                                    set -g prefix None\n\
+                                    unbind-key C-b\n\
+                                    bind-key -n C-\\\\ detach-client\n";
+
+/// Ensure the GTR tmux config file exists at ~/.gtr/config/tmux.conf.
+/// Returns the path to the config file.
+pub fn ensure_tmux_config() -> anyhow::Result<PathBuf> {
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+    let config_dir = PathBuf::from(&home).join(".gtr").join("config");
+    std::fs::create_dir_all(&config_dir)?;
+
+    let config_path = config_dir.join("tmux.conf");
+    if !config_path.exists() {
+        std::fs::write(&config_path, TMUX_CONF)?;
+    }
+
+    Ok(config_path)
+}
+
+const SESSION_META_FORMAT: &str =
+    "#{session_name}\t#{@gtr_role}\t#{@gtr_rig}\t#{@gtr_work_item}\t#{@gtr_status}";
+
+impl SessionBackend for Tmux {
+    fn spawn(
+        &self,
+        agent_id: &str,
+        shell_cmd: &str,
+        work_dir: &Path,
+        env_vars: &HashMap<String, String>,
+    ) -> anyhow::Result<Pid> {
+        ensure_tmux()?;
+        let config_path = ensure_tmux_config()?;
+        let session = tmux_session_name(agent_id);
+
+        // Build tmux new-session command
+        let mut cmd = std::process::Command::new("tmux");
+        cmd.args([
+            "-L",
+            "gtr",
+            "-f",
+            config_path.to_str().unwrap_or(""),
+            "new-session",
+            "-d",
+            "-s",
+            &session,
+            "-c",
+            work_dir.to_str().unwrap_or("."),
+            "-x",
+            "200",
+            "-y",
+            "50",
+        ]);
+
+        // Add environment variables via -e flags (tmux >= 3.2)
+        for (k, v) in env_vars {
+            cmd.arg("-e");
+            cmd.arg(format!("{k}={v}"));
+        }
+
+        // The shell command to run
+        cmd.arg(shell_cmd);
+
+        let output = cmd.output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux new-session failed: {stderr}");
+        }
+
+        // Get PID via tmux list-panes
+        let pid_output = std::process::Command::new("tmux")
+            .args([
+                "-L",
+                "gtr",
+                "list-panes",
+                "-t",
+                &session,
+                "-F",
+                "#{pane_pid}",
+            ])
+            .output()?;
+
+        let pid_str = String::from_utf8_lossy(&pid_output.stdout);
+        let pid: i32 = pid_str
+            .trim()
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Failed to parse pane PID '{pid_str}': {e}"))?;
+
+        // Persist the pane's output; only agents spawned with RGT_BIN have a sink
+        if let Some(rgt_bin) = env_vars.get("RGT_BIN") {
+            let piped = std::process::Command::new("tmux")
+                .args([
+                    "-L",
+                    "gtr",
+                    "pipe-pane",
+                    "-o",
+                    "-t",
+                    &session,
+                    &crate::output_log::pipe_command(rgt_bin, agent_id),
+                ])
+                .status();
+            if !piped.is_ok_and(|s| s.success()) {
+                tracing::warn!("Could not start output logging for '{agent_id}'");
+            }
+        }
+
+        Ok(Pid::from_raw(pid))
+    }
+
+    fn is_alive(&self, agent_id: &str) -> bool {
+        let session = tmux_session_name(agent_id);
+        std::process::Command::new("tmux")
+            .args(["-L", "gtr", "has-session", "-t", &session])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+
+    fn pid(&self, agent_id: &str) -> Option<Pid> {
+        let session = tmux_session_name(agent_id);
+        let output = std::process::Command::new("tmux")
+            .args([
+                "-L",
+                "gtr",
+                "list-panes",
+                "-t",
+                &session,
+                "-F",
+                "#{pane_pid}",
+            ])
+            .output()
+            .ok()?;
+
+        if !output.status.success() {
+            return pid_file(agent_id);
+        }
+
+        let pid_str = String::from_utf8_lossy(&output.stdout);
+        let pid: i32 = pid_str.trim().parse().ok()?;
+        Some(Pid::from_raw(pid))
+    }
+
+    fn capture(&self, agent_id: &str, lines: u32) -> Option<String> {
+        let session = tmux_session_name(agent_id);
+        let output = std::process::Command::new("tmux")
+            .args([
+                "-L",
+                "gtr",
+                "capture-pane",
+                "-t",
+                &session,
+                "-p",
+                "-S",
+                &format!("-{lines}"),
+            ])
+            .output()
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn send_keys(&self, agent_id: &str, text: &str, enter: bool) -> anyhow::Result<()> {
+        let session = tmux_session_name(agent_id);
+        // Literal text first, then Enter as a key name
+        let mut sends = vec![vec!["-l", text]];
+        if enter {
+            sends.push(vec!["Enter"]);
+        }
+        for keys in sends {
+            let status = std::process::Command::new("tmux")
+                .args(["-L", "gtr", "send-keys", "-t", &session])
+                .args(keys)
+                .status()?;
+            if !status.success() {
+                anyhow::bail!("tmux send-keys to '{session}' failed");
+            }
+        }
+        Ok(())
+    }
+
+    fn terminate(&self, agent_id: &str) -> bool {
+        // Kill the tmux session (sends SIGHUP to foreground process)
+        let session = tmux_session_name(agent_id);
+        std::process::Command::new("tmux")
+            .args(["-L", "gtr", "kill-session", "-t", &session])
+            .output()
+            .is_ok_and(|o| o.status.success())
+    }
+
+    fn attach_argv(&self, agent_id: &str, read_only: bool) -> anyhow::Result<Vec<String>> {
+        let mut argv: Vec<String> = ["tmux", "-L", "gtr", "attach-session", "-t"]
+            .map(String::from)
+            .to_vec();
+        argv.push(tmux_session_name(agent_id));
+        if read_only {
+            // Input is dropped, and the observer's terminal size doesn't resize the agent's pane
+            argv.extend(["-f".into(), "read-only,ignore-size".into()]);
+        }
+        Ok(argv)
+    }
+
+    /// Stored as `@gtr_*` session user options, and shown as the pane title
+    /// and window name.
+    fn set_metadata(&self, agent_id: &str, meta: &SessionMetadata) -> anyhow::Result<()> {
+        let session = tmux_session_name(agent_id);
+        let options = [
+            ("@gtr_role", meta.role.as_str()),
+            ("@gtr_rig", meta.rig.as_deref().unwrap_or("")),
+            ("@gtr_work_item", meta.work_item.as_deref().unwrap_or("")),
+            ("@gtr_status", meta.status.as_deref().unwrap_or("")),
+        ];
+        for (key, value) in options {
+            let output = std::process::Command::new("tmux")
+                .args(["-L", "gtr", "set-option", "-t", &session, key, value])
+                .output()?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                anyhow::bail!("tmux set-option {key} failed for '{session}': {stderr}");
+            }
+        }
+
+        let title = meta.title();
+        std::process::Command::new("tmux")
+            .args(["-L", "gtr", "select-pane", "-t", &session, "-T", &title])
+            .output()?;
+        std::process::Command::new("tmux")
+            .args(["-L", "gtr", "rename-window", "-t", &session, &title])
+            .output()?;
+        Ok(())
+    }
+
+    fn list_metadata(&self) -> Vec<(String, SessionMetadata)> {
+        let output = std::process::Command::new("tmux")
+            .args(["-L", "gtr", "list-sessions", "-F", SESSION_META_FORMAT])
+            .output();
+        let Ok(output) = output else {
+            return Vec::new();
+        };
+        if !output.status.success() {
+            return Vec::new();
+        }
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(parse_session_meta_line)
+            .collect()
+    }
+
+    fn current_path(&self, agent_id: &str) -> Option<PathBuf> {
+        let session = tmux_session_name(agent_id);
+        let output = std::process::Command::new("tmux")
+            .args([
+                "-L",
+                "gtr",
+                "display-message",
+                "-p",
+                "-t",
+                &session,
+                "#{pane_current_path}",
+            ])
+            .output()
+            .ok()?;
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !path.is_empty()).then(|| PathBuf::from(path))
+    }
+}
+
+fn parse_session_meta_line(line: &str) -> Option<(String, SessionMetadata)> {
+    let mut fields = line.split('\t');
+    let session = fields.next()?;
+    let agent_id = session.strip_prefix("gtr-").unwrap_or(session).to_string();
+    let mut next = || fields.next().filter(|f| !f.is_empty()).map(str::to_string);
+    let role = next().unwrap_or_default();
+    let meta = SessionMetadata {
+        role,
+        rig: next(),
+        work_item: next(),
+        status: next(),
+    };
+    Some((agent_id, meta))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_metadata_title_and_parse() {
+        let meta = SessionMetadata {
+            role: "polecat".into(),
+            rig: Some("gtr".into()),
+            work_item: Some("wi-abc".into()),
+            status: None,
+        };
+        assert_eq!(meta.title(), "polecat · gtr · wi-abc");
+
+        let (agent, parsed) =
+            parse_session_meta_line("gtr-gtr-polecat-nux\tpolecat\tgtr\twi-abc\t").unwrap();
+        assert_eq!(agent, "gtr-polecat-nux");
+        assert_eq!(parsed, meta);
+    }
+}
@@ -9,6 +9,12 @@ pub const SIGNAL_CLOSE: &str = "close";
 pub const SIGNAL_RELEASE: &str = "release";
 pub const SIGNAL_HEARTBEAT: &str = "heartbeat";
 pub const SIGNAL_ESCALATE: &str = "escalate";
+pub const SIGNAL_ESCALATION_ACK: &str = "escalation_ack";
+pub const SIGNAL_ESCALATION_RESOLVE: &str = "escalation_resolve";
+pub const SIGNAL_WORK_TRIAGE: &str = "work_triage";
+pub const SIGNAL_WORK_COMMENT: &str = "work_comment";
+pub const SIGNAL_WORK_SPLIT: &str = "work_split";
+pub const SIGNAL_WORK_USAGE: &str = "work_usage";
 
 // Agent signal names
 pub const SIGNAL_AGENT_ASSIGN: &str = "agent_assign";
@@ -21,6 +27,8 @@ pub const SIGNAL_AGENT_UNASSIGN: &str = "agent_unassign";
 pub const SIGNAL_ADD_WORK_ITEM: &str = "add_work_item";
 pub const SIGNAL_ITEM_DONE: &str = "item_done";
 pub const SIGNAL_CANCEL_CONVOY: &str = "cancel_convoy";
+pub const SIGNAL_CONVOY_DISPATCH: &str = "convoy_dispatch";
+pub const SIGNAL_CONVOY_RETRY: &str = "convoy_retry";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssignSignal {
@@ -37,32 +45,190 @@ pub struct FailSignal {
     pub reason: String,
 }
 
+/// A progress note left on a work item by an agent or a human.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkCommentSignal {
+    pub author: String,
+    pub text: String,
+}
+
+/// Split a work item into one child item per title. The parent then tracks
+/// its parts and completes once they have all finished.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkSplitSignal {
+    pub titles: Vec<String>,
+}
+
+/// Tokens and cost an agent runtime reports for a stretch of work on an
+/// item. Reports add up, so send each stretch once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkUsageSignal {
+    pub agent: String,
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cost_usd: f64,
+}
+
+/// What a work item has cost: wall-clock seconds in each status and the
+/// usage its agents reported.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkItemStats {
+    pub seconds_in_status: std::collections::BTreeMap<String, u64>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl WorkItemStats {
+    pub fn add_usage(&mut self, usage: &WorkUsageSignal) {
+        self.input_tokens += usage.input_tokens;
+        self.output_tokens += usage.output_tokens;
+        self.cost_usd += usage.cost_usd;
+    }
+
+    /// Fold another item's totals into these, e.g. for a convoy.
+    pub fn add(&mut self, other: &WorkItemStats) {
+        for (status, secs) in &other.seconds_in_status {
+            *self.seconds_in_status.entry(status.clone()).or_default() += secs;
+        }
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cost_usd += other.cost_usd;
+    }
+}
+
+/// A comment as the work item keeps it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkItemComment {
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub author: String,
+    pub text: String,
+}
+
+/// A triage agent's assessment of a work item. Also sent to the triage
+/// workflow to tell it the agent is finished.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriageSignal {
+    pub complexity: String,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    pub rig: Option<String>,
+    pub priority: Option<u8>,
+    pub notes: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkItemState {
     pub id: String,
     pub title: String,
     pub status: String,
     pub assigned_to: Option<String>,
+    pub description: String,
+    pub priority: gtr_core::types::Priority,
+    pub labels: Vec<String>,
+    pub depends_on: Vec<String>,
+    pub comments: Vec<WorkItemComment>,
+    /// Reported usage; time in each status is left to the history
+    pub usage: WorkItemStats,
+}
+
+impl WorkItemState {
+    pub fn new(
+        item: &gtr_core::types::WorkItem,
+        status: String,
+        assigned_to: Option<String>,
+        comments: Vec<WorkItemComment>,
+        usage: WorkItemStats,
+    ) -> Self {
+        Self {
+            id: item.id.0.clone(),
+            title: item.title.clone(),
+            status,
+            assigned_to,
+            description: item.description.clone(),
+            priority: item.priority,
+            labels: item.labels.clone(),
+            depends_on: item.depends_on.iter().map(|d| d.0.clone()).collect(),
+            comments,
+            usage,
+        }
+    }
+}
+
+/// One entry in a work item's timeline, see
+/// [`crate::history::work_item_timeline`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkItemTransition {
+    pub at: Option<chrono::DateTime<chrono::Utc>>,
+    /// `assigned`, `in_progress`, `heartbeat`, `escalation_open`, `done`, ...
+    pub event: String,
+    pub actor: Option<String>,
+    pub detail: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddWorkItemSignal {
     pub work_item_id: String,
     pub title: String,
+    /// The full item to start the child with, when there's more than a title
+    #[serde(default)]
+    pub item: Option<gtr_core::types::WorkItem>,
+    /// Release-train stage; items of a stage are only auto-slung once every
+    /// item of an earlier stage is done. Unstaged items are stage 0.
+    #[serde(default)]
+    pub stage: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ItemDoneSignal {
     pub work_item_id: String,
+    /// The item ended failed rather than done or closed
+    #[serde(default)]
+    pub failed: bool,
 }
 
+/// Recreates a failed convoy item as a new run of its workflow and slings it
+/// again. `item.retry_of` links the failed run.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConvoyRetrySignal {
+    pub item: gtr_core::types::WorkItem,
+}
+
+/// Turns on auto-slinging of a convoy's items to a rig. At most `high_water`
+/// items are in flight (working or waiting to merge) at a time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConvoyDispatchSignal {
+    pub rig: String,
+    #[serde(default)]
+    pub runtime: Option<String>,
+    pub high_water: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ConvoyState {
     pub id: String,
     pub title: String,
     pub status: String,
     pub work_items: Vec<String>,
     pub completed_items: Vec<String>,
+    /// Items that ended failed and haven't been retried
+    #[serde(default)]
+    pub failed_items: Vec<String>,
+    /// Stage of each item, for staged convoys
+    #[serde(default)]
+    pub stages: std::collections::BTreeMap<String, u32>,
+    /// Earliest stage with unfinished items
+    #[serde(default)]
+    pub current_stage: Option<u32>,
+    /// Unfinished items closed when the convoy was cancelled
+    #[serde(default)]
+    pub cancelled_items: Vec<String>,
+    /// Polecats killed when the convoy was cancelled
+    #[serde(default)]
+    pub killed_polecats: Vec<String>,
 }
 
 // Mayor signal names
@@ -70,7 +236,17 @@ pub const SIGNAL_REGISTER_AGENT: &str = "register_agent";
 pub const SIGNAL_UNREGISTER_AGENT: &str = "unregister_agent";
 pub const SIGNAL_AGENT_STATUS_UPDATE: &str = "agent_status_update";
 pub const SIGNAL_CONVOY_CLOSED: &str = "convoy_closed";
+pub const SIGNAL_CONVOY_PROGRESS: &str = "convoy_progress";
 pub const SIGNAL_MAYOR_STOP: &str = "mayor_stop";
+pub const SIGNAL_MERGE_RESULT: &str = "merge_result";
+pub const SIGNAL_ESCALATION_UPDATE: &str = "escalation_update";
+pub const SIGNAL_MAYOR_MAINTENANCE: &str = "mayor_maintenance";
+pub const SIGNAL_MAYOR_DEPUTY: &str = "mayor_deputy";
+/// A workflow asks to run a risky operation; carries an [`ApprovalRequest`]
+pub const SIGNAL_APPROVAL_REQUEST: &str = "approval_request";
+/// An [`ApprovalDecision`], sent to the mayor by `rgt mayor approvals` and
+/// passed on by the mayor to the workflow that asked
+pub const SIGNAL_APPROVAL_DECISION: &str = "approval_decision";
 
 // Mayor signal payloads
 
@@ -92,12 +268,136 @@ pub struct ConvoyClosedSignal {
     pub convoy_id: String,
 }
 
+/// Sent by a convoy whenever an item is added or finishes.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConvoyProgressSignal {
+    pub convoy_id: String,
+    pub title: String,
+    pub done: usize,
+    pub total: usize,
+    #[serde(default)]
+    pub deadline: Option<chrono::DateTime<chrono::Utc>>,
+    /// The deadline passed with items still open
+    #[serde(default)]
+    pub at_risk: bool,
+}
+
+impl ConvoyProgressSignal {
+    /// Share of items done, rounded down; 0 for an empty convoy.
+    pub fn percent(&self) -> usize {
+        (self.done * 100).checked_div(self.total).unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeResultSignal {
+    pub work_item_id: String,
+    pub branch: String,
+    pub status: String,
+    pub merged: bool,
+    #[serde(default)]
+    pub validate_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationUpdateSignal {
+    pub work_item_id: String,
+    pub title: String,
+    pub level: u32,
+    pub status: String,
+}
+
+/// Town-wide maintenance mode; `message` is the notice sent to agents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceSignal {
+    pub enabled: bool,
+    pub message: Option<String>,
+    /// Rigs `rgt mayor pause-town` parked, for `resume-town` to unpark
+    #[serde(default)]
+    pub parked_rigs: Vec<String>,
+}
+
+/// Sent by the deputy when it takes over from a headless mayor and when it
+/// hands back; while `acting`, the mayor copies its mail to the deputy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeputySignal {
+    pub acting: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    /// Unique per request, so a late decision can't answer a newer one
+    pub id: String,
+    /// `force_push`, `protected_merge` or `delete_rig`
+    pub operation: String,
+    /// Branch or rig the operation touches
+    pub target: String,
+    pub detail: String,
+    /// Workflow ID the decision is sent back to
+    pub requested_by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalDecision {
+    pub id: String,
+    pub approved: bool,
+    /// Who decided, e.g. `human` or an agent ID
+    #[serde(default)]
+    pub by: String,
+}
+
+/// What the mayor holds, returned when it stops and carried into the next run
+/// when it continues as new.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MayorState {
     pub active_convoys: Vec<String>,
     pub agents: Vec<MayorAgentEntry>,
     #[serde(default)]
     pub polecat_reports: Vec<PolecatReportSignal>,
+    #[serde(default)]
+    pub merge_results: Vec<MergeResultSignal>,
+    #[serde(default)]
+    pub open_escalations: Vec<EscalationUpdateSignal>,
+    /// Maintenance notice while the town is in maintenance mode
+    #[serde(default)]
+    pub maintenance: Option<String>,
+    /// Rigs parked along with the town by `rgt mayor pause-town`
+    #[serde(default)]
+    pub paused_rigs: Vec<String>,
+    /// Latest progress of each open convoy
+    #[serde(default)]
+    pub convoy_progress: Vec<ConvoyProgressSignal>,
+    /// Times each mayor policy rule was applied to a work item, keyed
+    /// `<rule index>:<work item>`
+    #[serde(default)]
+    pub policy_hits: std::collections::BTreeMap<String, u32>,
+    /// The deputy is covering for the mayor agent
+    #[serde(default)]
+    pub deputy_acting: bool,
+    /// Reports and merges since the last daily digest
+    #[serde(default)]
+    pub digest: DailyDigestInput,
+    /// Approval requests waiting for a decision
+    #[serde(default)]
+    pub pending_approvals: Vec<ApprovalRequest>,
+}
+
+/// What goes into the mayor's daily digest. The mayor collects `reports` and
+/// `merges` through the day and fills in the rest when the digest is due.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DailyDigestInput {
+    /// The day covered, `YYYY-MM-DD`
+    #[serde(default)]
+    pub date: String,
+    #[serde(default)]
+    pub reports: Vec<PolecatReportSignal>,
+    /// Real merge attempts; validation runs are left out
+    #[serde(default)]
+    pub merges: Vec<MergeResultSignal>,
+    #[serde(default)]
+    pub agents: Vec<MayorAgentEntry>,
+    #[serde(default)]
+    pub open_escalations: Vec<EscalationUpdateSignal>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -116,10 +416,49 @@ pub struct AgentAssignSignal {
     pub title: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AgentMailSignal {
     pub from: String,
     pub message: String,
+    /// Message ID in the recipient's mailbox; the recipient derives one if
+    /// the sender didn't set it
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Conversation the message belongs to, see [`gtr_core::mail::Mail::thread`]
+    #[serde(default)]
+    pub thread_id: Option<String>,
+    /// ID of the message this answers
+    #[serde(default)]
+    pub reply_to: Option<String>,
+    #[serde(default)]
+    pub priority: gtr_core::mail::MailPriority,
+    /// Files sent with the message, already copied into the attachments dir
+    #[serde(default)]
+    pub attachments: Vec<std::path::PathBuf>,
+}
+
+impl AgentMailSignal {
+    /// The mailbox entry for this message as delivered to `to`.
+    pub fn to_mail(
+        &self,
+        id: String,
+        to: &str,
+        sent_at: chrono::DateTime<chrono::Utc>,
+    ) -> gtr_core::mail::Mail {
+        gtr_core::mail::Mail {
+            id,
+            from: self.from.clone(),
+            to: to.to_string(),
+            message: self.message.clone(),
+            sent_at,
+            read: false,
+            archived: false,
+            thread_id: self.thread_id.clone(),
+            reply_to: self.reply_to.clone(),
+            priority: self.priority,
+            attachments: self.attachments.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -155,12 +494,24 @@ pub struct AgentState {
 pub const SIGNAL_REFINERY_ENQUEUE: &str = "refinery_enqueue";
 pub const SIGNAL_REFINERY_DEQUEUE: &str = "refinery_dequeue";
 pub const SIGNAL_REFINERY_STOP: &str = "refinery_stop";
+pub const SIGNAL_REFINERY_BUMP: &str = "refinery_bump";
+pub const SIGNAL_REFINERY_MOVE: &str = "refinery_move";
+pub const SIGNAL_REFINERY_PAUSE: &str = "refinery_pause";
+pub const SIGNAL_REFINERY_RESUME: &str = "refinery_resume";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RefineryEnqueueSignal {
     pub work_item_id: String,
     pub branch: String,
     pub priority: u8,
+    #[serde(default)]
+    pub validate_only: bool,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+    #[serde(default)]
+    pub target_branch: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -168,18 +519,71 @@ pub struct RefineryDequeueSignal {
     pub work_item_id: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefineryBumpSignal {
+    pub work_item_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefineryMoveSignal {
+    pub work_item_id: String,
+    pub position: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RefineryEntry {
     pub work_item_id: String,
     pub branch: String,
     pub priority: u8,
     pub status: String,
+    #[serde(default)]
+    pub validate_only: bool,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub enqueued_at: Option<u64>,
+    #[serde(default)]
+    pub started_at: Option<u64>,
+    #[serde(default)]
+    pub finished_at: Option<u64>,
+    #[serde(default)]
+    pub test_duration_secs: Option<u64>,
+    #[serde(default)]
+    pub target_branch: Option<String>,
+    #[serde(default)]
+    pub pull_request_url: Option<String>,
+    /// The pull request a `pr_open` entry is waiting on
+    #[serde(default)]
+    pub pull_request_number: Option<u64>,
+    /// When to stop waiting for it, in epoch seconds
+    #[serde(default)]
+    pub pull_request_deadline: Option<u64>,
+    /// The approval an `awaiting_approval` entry is parked on
+    #[serde(default)]
+    pub awaiting_approval: Option<ParkedApproval>,
+    /// Operations already approved for this entry, e.g. `force_push`
+    #[serde(default)]
+    pub approved: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParkedApproval {
+    pub id: String,
+    pub operation: String,
+    /// When the request counts as denied, in epoch seconds
+    pub deadline: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RefineryState {
     pub queue: Vec<RefineryEntry>,
     pub processed: Vec<RefineryEntry>,
+    #[serde(default)]
+    pub paused: bool,
 }
 
 // Rig signal names
@@ -214,6 +618,11 @@ pub struct RigState {
     pub refinery_session_id: Option<String>,
 }
 
+// Boot signal names. `boot_watch` carries the agent's `SpawnAgentInput`,
+// `boot_unwatch` its agent ID.
+pub const SIGNAL_BOOT_WATCH: &str = "boot_watch";
+pub const SIGNAL_BOOT_UNWATCH: &str = "boot_unwatch";
+
 // Polecat signal names
 pub const SIGNAL_POLECAT_HEARTBEAT: &str = "polecat_heartbeat";
 pub const SIGNAL_POLECAT_DONE: &str = "polecat_done";
@@ -301,6 +710,14 @@ pub struct MolStepState {
     pub output: Option<String>,
 }
 
+/// Third start argument of an executing molecule picking up where an earlier
+/// run stopped: `steps` as that run left them, rerun from `from`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoleculeResume {
+    pub from: String,
+    pub steps: Vec<MolStepState>,
+}
+
 // Dog signal names
 pub const SIGNAL_DOG_DISPATCH: &str = "dog_dispatch";
 pub const SIGNAL_DOG_RELEASE: &str = "dog_release";
@@ -321,6 +738,18 @@ pub struct DogState {
     pub current_rig: Option<String>,
 }
 
+// Patrol signal names
+pub const SIGNAL_PATROL_EVENT: &str = "patrol_event";
+
+/// An event for patrol to run its matching `event`-gated plugins on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatrolEventSignal {
+    pub event: String,
+    /// Extra environment for the plugins, on top of `GTR_EVENT` and `GTR_RIG`
+    #[serde(default)]
+    pub env: std::collections::HashMap<String, String>,
+}
+
 // Gate signal names
 pub const SIGNAL_GATE_CLOSE: &str = "gate_close";
 pub const SIGNAL_GATE_APPROVE: &str = "gate_approve";
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use anyhow::Result;
 use temporalio_common::{
@@ -6,17 +6,29 @@ use temporalio_common::{
     worker::{WorkerConfig, WorkerTaskTypes, WorkerVersioningStrategy},
 };
 use temporalio_sdk::Worker;
-use temporalio_sdk_core::{init_worker, ClientOptions, CoreRuntime, RuntimeOptions, Url};
+use temporalio_sdk_core::{
+    init_worker, Client, ClientOptions, CoreRuntime, RetryClient, RuntimeOptions, Url,
+};
 
 use crate::activities;
+use crate::outbox;
 use crate::workflows;
 
 pub const DEFAULT_TASK_QUEUE: &str = "work";
 pub const DEFAULT_NAMESPACE: &str = "default";
 pub const DEFAULT_TARGET_URL: &str = "http://localhost:7233";
 
+/// The frontend the worker was started against, for [`connect`].
+static TARGET_URL: OnceLock<String> = OnceLock::new();
+
 /// Start a Temporal worker that registers all gtr workflows and activities.
 pub async fn run_worker() -> Result<()> {
+    run_worker_at(DEFAULT_TARGET_URL).await
+}
+
+/// [`run_worker`] against the Temporal frontend at `target_url`.
+pub async fn run_worker_at(target_url: &str) -> Result<()> {
+    let _ = TARGET_URL.set(target_url.to_string());
     let telemetry_options = TelemetryOptions::builder().build();
     let runtime_options = RuntimeOptions::builder()
         .telemetry_options(telemetry_options)
@@ -25,7 +37,7 @@ pub async fn run_worker() -> Result<()> {
     let runtime = CoreRuntime::new_assume_tokio(runtime_options)?;
 
     let client_opts = ClientOptions::builder()
-        .target_url(Url::parse(DEFAULT_TARGET_URL)?)
+        .target_url(Url::parse(target_url)?)
         .client_name("gtr-worker".to_string())
         .client_version(env!("CARGO_PKG_VERSION").to_string())
         .identity("gtr-worker".to_string())
@@ -33,6 +45,23 @@ pub async fn run_worker() -> Result<()> {
 
     let client = client_opts.connect(DEFAULT_NAMESPACE, None).await?;
 
+    // Deliver anything the CLI queued while the server was unreachable, then
+    // keep checking in the background in case the worker outlives an outage.
+    replay_outbox(&client).await;
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(outbox::REPLAY_INTERVAL).await;
+            let dir = gtr_core::dirs::outbox_dir();
+            if !gtr_core::outbox::pending(&dir).is_ok_and(|p| !p.is_empty()) {
+                continue;
+            }
+            match client_opts.connect(DEFAULT_NAMESPACE, None).await {
+                Ok(client) => replay_outbox(&client).await,
+                Err(e) => tracing::debug!("Outbox replay skipped — Temporal unreachable: {e}"),
+            }
+        }
+    });
+
     let worker_config = WorkerConfig::builder()
         .namespace(DEFAULT_NAMESPACE)
         .task_queue(DEFAULT_TASK_QUEUE)
@@ -57,34 +86,157 @@ pub async fn run_worker() -> Result<()> {
     worker.register_wf("agent_wf", workflows::agent::agent_wf);
     worker.register_wf("mayor_wf", workflows::mayor::mayor_wf);
     worker.register_wf("patrol_wf", workflows::patrol::patrol_wf);
+    worker.register_wf("plugin_test_wf", workflows::patrol::plugin_test_wf);
     worker.register_wf("formula_wf", workflows::formula::formula_wf);
     worker.register_wf("refinery_wf", workflows::refinery::refinery_wf);
     worker.register_wf("witness_wf", workflows::witness::witness_wf);
     worker.register_wf("boot_wf", workflows::boot::boot_wf);
+    worker.register_wf("deputy_wf", workflows::deputy::deputy_wf);
     worker.register_wf("rig_wf", workflows::rig::rig_wf);
+    worker.register_wf("rig_remove_wf", workflows::rig::rig_remove_wf);
     worker.register_wf("polecat_wf", workflows::polecat::polecat_wf);
     worker.register_wf("molecule_wf", workflows::molecule::molecule_wf);
     worker.register_wf("dog_wf", workflows::dog::dog_wf);
     worker.register_wf("gate_wf", workflows::gate::gate_wf);
+    worker.register_wf("triage_wf", workflows::triage::triage_wf);
 
     // Activities
     worker.register_activity("spawn_agent", activities::spawn_agent::spawn_agent);
     worker.register_activity("read_agent_output", activities::agent_io::read_agent_output);
     worker.register_activity("run_plugin", activities::run_plugin::run_plugin);
+    worker.register_activity("discover_plugins", activities::run_plugin::discover_plugins);
+    worker.register_activity(
+        "discover_event_plugins",
+        activities::run_plugin::discover_event_plugins,
+    );
     worker.register_activity("git_operation", activities::git_ops::git_operation);
     worker.register_activity(
         "send_notification",
         activities::notification::send_notification,
     );
+    worker.register_activity(
+        "load_escalation_policy",
+        activities::notification::load_escalation_policy,
+    );
     worker.register_activity("check_agent_alive", activities::heartbeat::check_agent_alive);
     worker.register_activity("kill_agent", activities::heartbeat::kill_agent_activity);
+    worker.register_activity(
+        "shutdown_agents",
+        activities::heartbeat::shutdown_agents_activity,
+    );
     worker.register_activity("capture_pane", activities::heartbeat::capture_pane_activity);
+    worker.register_activity("send_keys", activities::heartbeat::send_keys_activity);
+    worker.register_activity("rig_load", activities::heartbeat::rig_load);
+    worker.register_activity(
+        "check_pane_activity",
+        activities::heartbeat::check_pane_activity,
+    );
+    worker.register_activity(
+        "reconcile_sessions",
+        activities::heartbeat::reconcile_sessions,
+    );
+    worker.register_activity(
+        "update_session_metadata",
+        activities::heartbeat::update_session_metadata,
+    );
+    worker.register_activity(
+        "detect_patrol_checks",
+        activities::patrol_checks::detect_patrol_checks,
+    );
+    worker.register_activity(
+        "record_plugin_run",
+        activities::plugin_history::record_plugin_run,
+    );
+    worker.register_activity(
+        "resolve_checks_mode",
+        activities::github_checks::resolve_checks_mode,
+    );
+    worker.register_activity(
+        "resolve_refinery_settings",
+        activities::refinery_settings::resolve_refinery_settings,
+    );
+    worker.register_activity(
+        "record_refinery_entry",
+        activities::refinery_history::record_refinery_entry,
+    );
+    worker.register_activity(
+        "github_checks_status",
+        activities::github_checks::github_checks_status,
+    );
+    worker.register_activity(
+        "create_pull_request",
+        activities::pull_request::create_pull_request,
+    );
+    worker.register_activity(
+        "pull_request_status",
+        activities::pull_request::pull_request_status,
+    );
+    worker.register_activity("store_mail", activities::mail::store_mail);
+    worker.register_activity(
+        "redeliver_dead_letters",
+        activities::mail::redeliver_dead_letters,
+    );
+    worker.register_activity("route_mail", activities::mail::route_mail);
+    worker.register_activity("mail_human", activities::mail::mail_human);
+    worker.register_activity(
+        "load_mayor_policy",
+        activities::notification::load_mayor_policy,
+    );
+    worker.register_activity("convoy_report", activities::convoy_report::convoy_report);
+    worker.register_activity("daily_digest", activities::digest::daily_digest);
+    worker.register_activity(
+        "record_polecat_report",
+        activities::polecat_reports::record_polecat_report,
+    );
+    worker.register_activity(
+        "load_dispatch_policy",
+        activities::dispatch::load_dispatch_policy,
+    );
+    worker.register_activity(
+        "dispatch_candidates",
+        activities::dispatch::dispatch_candidates,
+    );
     worker.register_activity(
         "discover_session_id",
         activities::discover_session::discover_session_id,
     );
+    worker.register_activity(
+        "load_approval_policy",
+        activities::rig_registry::load_approval_policy,
+    );
+    worker.register_activity("remove_rig", activities::rig_registry::remove_rig);
 
     tracing::info!("gtr worker started on task queue '{DEFAULT_TASK_QUEUE}'");
     worker.run().await?;
     Ok(())
 }
+
+/// A client for the worker's own Temporal frontend, for activities that
+/// need to look up other workflows.
+pub async fn connect() -> Result<RetryClient<Client>> {
+    let target_url = TARGET_URL.get().map_or(DEFAULT_TARGET_URL, String::as_str);
+    let client_opts = ClientOptions::builder()
+        .target_url(Url::parse(target_url)?)
+        .client_name("gtr-worker".to_string())
+        .client_version(env!("CARGO_PKG_VERSION").to_string())
+        .identity("gtr-worker".to_string())
+        .build();
+    let client = client_opts.connect(DEFAULT_NAMESPACE, None).await?;
+    Ok(client)
+}
+
+async fn replay_outbox<C>(client: &C)
+where
+    C: temporalio_sdk_core::WorkflowClientTrait + Sync,
+{
+    match outbox::replay(client, &gtr_core::dirs::outbox_dir()).await {
+        Ok(summary) if summary.delivered + summary.failed + summary.dropped > 0 => tracing::info!(
+            "Outbox replay: {} delivered, {} still queued, {} dropped",
+            summary.delivered,
+            summary.failed,
+            summary.dropped
+        ),
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Outbox replay failed: {e}"),
+    }
+}
@@ -1,6 +1,11 @@
+use std::time::Duration;
+
 use futures_util::StreamExt;
-use temporalio_sdk::{WfContext, WfExitValue};
+use gtr_core::mail::{Mail, MailPriority};
+use temporalio_common::protos::coresdk::AsJsonPayloadExt;
+use temporalio_sdk::{ActivityOptions, SignalWorkflowOptions, WfContext, WfExitValue};
 
+use crate::activities::heartbeat::SendKeysInput;
 use crate::signals::*;
 
 pub async fn agent_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error> {
@@ -26,6 +31,7 @@ pub async fn agent_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Err
     let mut hook_clear_ch = ctx.make_signal_channel(SIGNAL_HOOK_CLEAR);
 
     tracing::info!("Agent {id} ({role}) started — idle");
+    register_with_mayor(&ctx, &id, &role).await;
 
     loop {
         tokio::select! {
@@ -71,6 +77,7 @@ pub async fn agent_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Err
                 if let Some(payload) = signal.input.first() {
                     if let Ok(data) = serde_json::from_slice::<AgentMailSignal>(&payload.data) {
                         tracing::info!("Agent {id}: mail from {} — {}", data.from, data.message);
+                        store_mail(&ctx, &id, &data, inbox.len()).await;
                         inbox.push(MailEntry {
                             from: data.from,
                             message: data.message,
@@ -98,3 +105,79 @@ pub async fn agent_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Err
         }
     }
 }
+
+/// Tell the mayor `agent_id` is running, which also has it redeliver any
+/// mail that was dead-lettered while the agent was away.
+pub(crate) async fn register_with_mayor(ctx: &WfContext, agent_id: &str, role: &str) {
+    let signal = RegisterAgentSignal {
+        agent_id: agent_id.to_string(),
+        role: role.to_string(),
+    };
+    let Ok(payload) = signal.as_json_payload() else {
+        return;
+    };
+    let opts = SignalWorkflowOptions::new(
+        gtr_core::state::mayor_workflow_id(),
+        "",
+        SIGNAL_REGISTER_AGENT,
+        vec![payload],
+    );
+    // The mayor may not be running
+    let _ = ctx.signal_workflow(opts).await;
+}
+
+/// Save mail received by `to` in its mailbox, and type a notice into its
+/// session if the mail is urgent. Mail sent without an ID gets one from the
+/// workflow clock and the count received before it, both of which replay
+/// identically. Returns the mail as stored.
+pub(crate) async fn store_mail(
+    ctx: &WfContext,
+    to: &str,
+    data: &AgentMailSignal,
+    received: usize,
+) -> Mail {
+    let sent_at: chrono::DateTime<chrono::Utc> =
+        ctx.workflow_time().map(Into::into).unwrap_or_default();
+    let id = data
+        .id
+        .clone()
+        .unwrap_or_else(|| format!("ml-{}-{received}", sent_at.timestamp()));
+    let mail = data.to_mail(id, to, sent_at);
+    if let Ok(input) = mail.as_json_payload() {
+        let stored = ctx
+            .activity(ActivityOptions {
+                activity_type: "store_mail".to_string(),
+                input,
+                start_to_close_timeout: Some(Duration::from_secs(30)),
+                ..Default::default()
+            })
+            .await;
+        if !stored.completed_ok() {
+            tracing::warn!("Could not store mail {} for {to}", mail.id);
+        }
+    }
+
+    if mail.priority == MailPriority::Urgent {
+        let first_line = mail.message.lines().next().unwrap_or_default();
+        let nudge = SendKeysInput {
+            agent_id: to.to_string(),
+            text: format!(
+                "[URGENT MAIL from {}] {first_line} — read it with `$RGT_BIN mail read {}`",
+                mail.from, mail.id
+            ),
+            enter: true,
+        };
+        if let Ok(input) = nudge.as_json_payload() {
+            // Best effort: the recipient may have no live session
+            let _ = ctx
+                .activity(ActivityOptions {
+                    activity_type: "send_keys".to_string(),
+                    input,
+                    start_to_close_timeout: Some(Duration::from_secs(15)),
+                    ..Default::default()
+                })
+                .await;
+        }
+    }
+    mail
+}
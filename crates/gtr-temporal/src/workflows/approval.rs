@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use temporalio_common::protos::coresdk::AsJsonPayloadExt;
+use temporalio_sdk::{SignalWorkflowOptions, WfContext};
+
+use crate::signals::{
+    ApprovalDecision, ApprovalRequest, SIGNAL_APPROVAL_DECISION, SIGNAL_APPROVAL_REQUEST,
+};
+
+/// Queue `request` with the mayor and wait for someone to decide it with
+/// `rgt mayor approvals approve|deny`. Returns whether the operation may go
+/// ahead; a request the mayor can't take, or nobody answers within `timeout`,
+/// is denied. Workflows that must keep handling other signals meanwhile use
+/// [`request_approval`] and listen for the decision themselves.
+pub(crate) async fn await_approval(
+    ctx: &WfContext,
+    request: &ApprovalRequest,
+    timeout: Duration,
+) -> anyhow::Result<bool> {
+    // Decisions for earlier requests may still be buffered; those are skipped by ID
+    let mut decision_ch = ctx.make_signal_channel(SIGNAL_APPROVAL_DECISION);
+    if !request_approval(ctx, request).await? {
+        return Ok(false);
+    }
+
+    let mut deadline = std::pin::pin!(ctx.timer(timeout));
+    loop {
+        tokio::select! {
+            Some(signal) = decision_ch.next() => {
+                let Some(decision) = signal
+                    .input
+                    .first()
+                    .and_then(|p| serde_json::from_slice::<ApprovalDecision>(&p.data).ok())
+                else {
+                    continue;
+                };
+                if decision.id != request.id {
+                    continue;
+                }
+                let verdict = if decision.approved { "approved" } else { "denied" };
+                tracing::info!("Approval {} {verdict} by {}", request.id, decision.by);
+                return Ok(decision.approved);
+            }
+            _ = &mut deadline => {
+                expire_approval(ctx, &request.id).await?;
+                return Ok(false);
+            }
+        }
+    }
+}
+
+/// Queue `request` with the mayor, who mails it to the human inbox. The
+/// decision comes back to `request.requested_by` as a
+/// `SIGNAL_APPROVAL_DECISION`. Returns false if the mayor couldn't take it.
+pub(crate) async fn request_approval(
+    ctx: &WfContext,
+    request: &ApprovalRequest,
+) -> anyhow::Result<bool> {
+    let opts = SignalWorkflowOptions::new(
+        gtr_core::state::mayor_workflow_id(),
+        "",
+        SIGNAL_APPROVAL_REQUEST,
+        vec![request.as_json_payload()?],
+    );
+    if ctx.signal_workflow(opts).await.is_err() {
+        tracing::warn!(
+            "Could not queue approval {} with the mayor — treating it as denied",
+            request.id
+        );
+        return Ok(false);
+    }
+    tracing::info!(
+        "Waiting for approval {} to {} {}",
+        request.id,
+        request.operation,
+        request.target
+    );
+    Ok(true)
+}
+
+/// Give up on approval `id` after nobody answered it, taking it off the
+/// mayor's queue. It counts as denied.
+pub(crate) async fn expire_approval(ctx: &WfContext, id: &str) -> anyhow::Result<()> {
+    tracing::warn!("Approval {id} timed out — treating it as denied");
+    let decision = ApprovalDecision {
+        id: id.to_string(),
+        approved: false,
+        by: "timeout".to_string(),
+    };
+    let opts = SignalWorkflowOptions::new(
+        gtr_core::state::mayor_workflow_id(),
+        "",
+        SIGNAL_APPROVAL_DECISION,
+        vec![decision.as_json_payload()?],
+    );
+    let _ = ctx.signal_workflow(opts).await;
+    Ok(())
+}
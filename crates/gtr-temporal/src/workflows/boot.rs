@@ -1,14 +1,86 @@
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 use futures_util::StreamExt;
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
-use temporalio_sdk::{ActivityOptions, WfContext, WfExitValue};
+use temporalio_sdk::{ActivityOptions, SignalWorkflowOptions, WfContext, WfExitValue};
 
-use crate::activities::heartbeat::HeartbeatInput;
+use crate::activities::discover_session::discover_from_workflow;
+use crate::activities::heartbeat::{HeartbeatInput, HeartbeatOutput, ReconcileSessionsInput};
 use crate::activities::spawn_agent::SpawnAgentInput;
-use crate::signals::SIGNAL_AGENT_STOP;
+use crate::pty::OrphanReport;
+use crate::signals::{
+    AgentMailSignal, AgentStatusUpdateSignal, SIGNAL_AGENT_MAIL, SIGNAL_AGENT_STATUS_UPDATE,
+    SIGNAL_AGENT_STOP, SIGNAL_BOOT_UNWATCH, SIGNAL_BOOT_WATCH,
+};
+
+/// Prompt given in place of an agent's original one when it is respawned.
+const RESPAWN_PROMPT: &str = "You are being respawned after a crash. Run `$RGT_BIN prime` to restore context. (RGT_BIN env var has the full path.)";
+
+/// Respawns allowed before an agent that keeps dying is marked `crash_looping`.
+const MAX_RESPAWNS: u32 = 5;
+/// Upper bound on the backoff between respawns, in health checks.
+const MAX_BACKOFF_CHECKS: u64 = 16;
+/// Consecutive healthy checks after which an agent's respawn count is forgiven.
+const STABLE_CHECKS: u64 = 5;
+/// Health checks between sweeps for sessions whose workflow is gone.
+const RECONCILE_CHECKS: u64 = 15;
+
+/// Respawn bookkeeping for one agent. Backoff is counted in health checks
+/// rather than wall-clock time so it replays deterministically.
+#[derive(Debug, Default)]
+struct RespawnTracker {
+    /// Respawns since the agent was last stable.
+    attempts: u32,
+    /// Health checks left before another respawn may be attempted.
+    wait_checks: u64,
+    /// Consecutive checks the agent was found alive.
+    alive_checks: u64,
+    crash_looping: bool,
+}
+
+/// What boot should do about an agent that failed its health check.
+#[derive(Debug, PartialEq)]
+enum RespawnAction {
+    Respawn,
+    Backoff,
+    CrashLoop,
+    Ignore,
+}
+
+impl RespawnTracker {
+    fn on_alive(&mut self) {
+        self.alive_checks += 1;
+        if self.alive_checks >= STABLE_CHECKS {
+            self.attempts = 0;
+        }
+        // Someone restarted it by hand — watch it again
+        self.crash_looping = false;
+    }
+
+    fn on_dead(&mut self) -> RespawnAction {
+        self.alive_checks = 0;
+        if self.crash_looping {
+            return RespawnAction::Ignore;
+        }
+        if self.wait_checks > 0 {
+            return RespawnAction::Backoff;
+        }
+        if self.attempts >= MAX_RESPAWNS {
+            self.crash_looping = true;
+            return RespawnAction::CrashLoop;
+        }
+        self.attempts += 1;
+        // 1, 2, 4, 8 ... checks between attempts
+        self.wait_checks = (1u64 << (self.attempts - 1)).min(MAX_BACKOFF_CHECKS);
+        RespawnAction::Respawn
+    }
+}
 
 /// Boot workflow — spawns mayor agent, then monitors health of all spawned agents.
+/// Other agents (witnesses, refineries) are watched once `rig_wf` sends their
+/// spawn input via `boot_watch`. Dead agents are respawned from that input with exponential backoff; after `MAX_RESPAWNS`
+/// failed attempts they are marked `crash_looping` and the mayor is notified.
 pub async fn boot_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error> {
     let args = ctx.get_args();
     let interval_secs = if let Some(payload) = args.first() {
@@ -18,18 +90,22 @@ pub async fn boot_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Erro
     };
 
     let mut stop_ch = ctx.make_signal_channel(SIGNAL_AGENT_STOP);
+    let mut watch_ch = ctx.make_signal_channel(SIGNAL_BOOT_WATCH);
+    let mut unwatch_ch = ctx.make_signal_channel(SIGNAL_BOOT_UNWATCH);
     let mut checks: u64 = 0;
-    let mut spawned: Vec<String> = vec![];
+    // Original spawn input per agent, reused on respawn
+    let mut spawned: BTreeMap<String, SpawnAgentInput> = BTreeMap::new();
+    let mut trackers: BTreeMap<String, RespawnTracker> = BTreeMap::new();
 
     tracing::info!("Boot started — health check interval {interval_secs}s");
 
     // Initial spawn: mayor agent
     let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
-    let mayor_input = SpawnAgentInput {
-        agent_id: "mayor".to_string(),
+    let mut mayor_input = SpawnAgentInput {
+        agent_id: gtr_core::state::mayor_workflow_id(),
         runtime: "claude".to_string(),
         work_dir: format!("{home}/.gtr"),
-        role: "mayor".to_string(),
+        role: gtr_core::state::roles::MAYOR.to_string(),
         rig: None,
         initial_prompt: Some(
             "You are the Mayor of Gas Town. The RGT_BIN env var has the full path to the rgt binary. \
@@ -41,8 +117,14 @@ pub async fn boot_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Erro
         ),
         env_extra: None,
         resume_session_id: None,
+        limits: None,
+        model: None,
+        permission_mode: None,
+        allowed_tools: None,
     };
 
+    // ~/.gtr is shared, so only a session written after this spawn is the mayor's
+    let spawned_at = ctx.workflow_time().map(Into::into);
     let result = ctx
         .activity(ActivityOptions {
             activity_type: "spawn_agent".to_string(),
@@ -53,7 +135,9 @@ pub async fn boot_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Erro
         .await;
 
     if result.completed_ok() {
-        spawned.push("mayor".to_string());
+        mayor_input.resume_session_id =
+            discover_from_workflow(&ctx, &mayor_input.work_dir, spawned_at).await;
+        spawned.insert(mayor_input.agent_id.clone(), mayor_input);
         tracing::info!("Boot: spawned mayor agent");
     } else {
         tracing::warn!("Boot: failed to spawn mayor agent");
@@ -65,20 +149,48 @@ pub async fn boot_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Erro
             biased;
             Some(_) = stop_ch.next() => {
                 tracing::info!("Boot stopped after {checks} checks");
+                let crash_looping: Vec<&String> = trackers
+                    .iter()
+                    .filter(|(_, t)| t.crash_looping)
+                    .map(|(id, _)| id)
+                    .collect();
                 return Ok(WfExitValue::Normal(
                     serde_json::to_string(&serde_json::json!({
                         "checks": checks,
-                        "spawned": spawned,
+                        "spawned": spawned.keys().collect::<Vec<_>>(),
+                        "crash_looping": crash_looping,
                     }))?
                 ));
             }
+            Some(signal) = watch_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(input) = serde_json::from_slice::<SpawnAgentInput>(&payload.data) {
+                        tracing::info!("Boot: watching {} ({})", input.agent_id, input.role);
+                        trackers.remove(&input.agent_id);
+                        spawned.insert(input.agent_id.clone(), input);
+                    }
+                }
+            }
+            Some(signal) = unwatch_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(agent_id) = serde_json::from_slice::<String>(&payload.data) {
+                        if spawned.remove(&agent_id).is_some() {
+                            tracing::info!("Boot: no longer watching {agent_id}");
+                        }
+                        trackers.remove(&agent_id);
+                    }
+                }
+            }
             _ = ctx.timer(Duration::from_secs(interval_secs)) => {
                 checks += 1;
                 tracing::info!("Boot health check #{checks}");
 
                 let mut dead_agents: Vec<String> = vec![];
 
-                for agent_id in &spawned {
+                for agent_id in spawned.keys() {
+                    let tracker = trackers.entry(agent_id.clone()).or_default();
+                    tracker.wait_checks = tracker.wait_checks.saturating_sub(1);
+
                     let input = HeartbeatInput {
                         agent_id: agent_id.clone(),
                     };
@@ -92,27 +204,56 @@ pub async fn boot_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Erro
                         })
                         .await;
 
-                    if !result.completed_ok() {
-                        tracing::warn!("Boot: {agent_id} appears dead — scheduling respawn");
-                        dead_agents.push(agent_id.clone());
+                    // The activity completes either way; the flag says whether it's alive
+                    let alive = result
+                        .success_payload_or_error()
+                        .ok()
+                        .flatten()
+                        .and_then(|p| serde_json::from_slice::<HeartbeatOutput>(&p.data).ok())
+                        .is_some_and(|hb| hb.alive);
+                    if alive {
+                        tracker.on_alive();
+                        continue;
+                    }
+                    match tracker.on_dead() {
+                        RespawnAction::Respawn => {
+                            tracing::warn!("Boot: {agent_id} appears dead — scheduling respawn");
+                            dead_agents.push(agent_id.clone());
+                        }
+                        RespawnAction::Backoff => {
+                            tracing::info!(
+                                "Boot: {agent_id} still dead — next respawn in {} check(s)",
+                                tracker.wait_checks
+                            );
+                        }
+                        RespawnAction::CrashLoop => {
+                            tracing::error!(
+                                "Boot: {agent_id} is crash looping after {MAX_RESPAWNS} respawns"
+                            );
+                            notify_crash_loop(&ctx, agent_id).await;
+                        }
+                        RespawnAction::Ignore => {}
                     }
                 }
 
                 // Respawn dead agents
                 for agent_id in &dead_agents {
-                    tracing::info!("Boot: respawning {agent_id}");
-                    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+                    let Some(spawn_input) = spawned.get_mut(agent_id) else {
+                        continue;
+                    };
+                    tracing::info!("Boot: respawning {agent_id} ({})", spawn_input.role);
+                    // Pick up the session the agent was in when it died
+                    let session_id = discover_from_workflow(&ctx, &spawn_input.work_dir, None).await;
+                    if session_id.is_some() {
+                        spawn_input.resume_session_id = session_id;
+                    }
                     let respawn_input = SpawnAgentInput {
-                        agent_id: agent_id.clone(),
-                        runtime: "claude".to_string(),
-                        work_dir: format!("{home}/.gtr"),
-                        role: "mayor".to_string(), // TODO: track original role per agent
-                        rig: None,
-                        initial_prompt: Some(
-                            "You are being respawned after a crash. Run `$RGT_BIN prime` to restore context. (RGT_BIN env var has the full path.)".to_string()
-                        ),
-                        env_extra: None,
-                        resume_session_id: None,
+                        // A shell runtime would run the prompt as a script
+                        initial_prompt: spawn_input
+                            .initial_prompt
+                            .as_ref()
+                            .map(|_| RESPAWN_PROMPT.to_string()),
+                        ..spawn_input.clone()
                     };
 
                     let result = ctx
@@ -125,12 +266,124 @@ pub async fn boot_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Erro
                         .await;
 
                     if result.completed_ok() {
-                        tracing::info!("Boot: respawned {agent_id}");
+                        let resumed = respawn_input.resume_session_id.is_some();
+                        tracing::info!("Boot: respawned {agent_id} (resumed: {resumed})");
                     } else {
                         tracing::error!("Boot: failed to respawn {agent_id}");
                     }
                 }
+
+                if checks.is_multiple_of(RECONCILE_CHECKS) {
+                    reconcile_sessions(&ctx, spawned.keys().cloned().collect()).await?;
+                }
             }
         }
     }
 }
+
+/// Kill sessions and clean runtime dirs left behind by workflows that crashed
+/// or were terminated. Agents boot watches count as owned.
+async fn reconcile_sessions(ctx: &WfContext, watched: Vec<String>) -> anyhow::Result<()> {
+    let input = ReconcileSessionsInput {
+        watched,
+        reap: true,
+    };
+    let result = ctx
+        .activity(ActivityOptions {
+            activity_type: "reconcile_sessions".to_string(),
+            input: input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(120)),
+            ..Default::default()
+        })
+        .await;
+    let report = result
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<OrphanReport>(&p.data).ok());
+    match report {
+        Some(report) if !report.sessions.is_empty() || !report.runtime_dirs.is_empty() => {
+            tracing::warn!(
+                "Boot: reaped orphaned sessions {:?} and runtime dirs {:?}",
+                report.sessions,
+                report.runtime_dirs
+            );
+        }
+        Some(_) => {}
+        None => tracing::warn!("Boot: orphaned session sweep failed"),
+    }
+    Ok(())
+}
+
+/// Tell the mayor an agent has been given up on: its status is updated and
+/// a mail lands in the mayor's inbox. Errors are ignored — the mayor may not
+/// be running.
+async fn notify_crash_loop(ctx: &WfContext, agent_id: &str) {
+    let status = AgentStatusUpdateSignal {
+        agent_id: agent_id.to_string(),
+        status: "crash_looping".to_string(),
+        current_work: None,
+    };
+    if let Ok(payload) = status.as_json_payload() {
+        let _ = ctx
+            .signal_workflow(SignalWorkflowOptions::new(
+                gtr_core::state::mayor_workflow_id(),
+                "",
+                SIGNAL_AGENT_STATUS_UPDATE,
+                vec![payload],
+            ))
+            .await;
+    }
+
+    let mail = AgentMailSignal {
+        from: gtr_core::state::boot_workflow_id(),
+        message: format!(
+            "{agent_id} died {MAX_RESPAWNS} times in a row and is crash looping; boot has \
+             stopped respawning it. Check its logs, then restart it by hand."
+        ),
+        ..Default::default()
+    };
+    if let Ok(payload) = mail.as_json_payload() {
+        let _ = ctx
+            .signal_workflow(SignalWorkflowOptions::new(
+                gtr_core::state::mayor_workflow_id(),
+                "",
+                SIGNAL_AGENT_MAIL,
+                vec![payload],
+            ))
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn respawn_backs_off_then_crash_loops() {
+        let mut tracker = RespawnTracker::default();
+        let mut actions = vec![];
+        for _ in 0..40 {
+            tracker.wait_checks = tracker.wait_checks.saturating_sub(1);
+            actions.push(tracker.on_dead());
+        }
+        let respawns: Vec<usize> = actions
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| **a == RespawnAction::Respawn)
+            .map(|(i, _)| i)
+            .collect();
+        // Gaps double: 1, 2, 4, 8 checks
+        assert_eq!(respawns, vec![0, 1, 3, 7, 15]);
+        let crash = actions.iter().position(|a| *a == RespawnAction::CrashLoop);
+        assert_eq!(crash, Some(31));
+        assert_eq!(actions[32], RespawnAction::Ignore);
+
+        // Coming back to life clears the flag; staying up forgives the attempts
+        for _ in 0..STABLE_CHECKS {
+            tracker.on_alive();
+        }
+        assert!(!tracker.crash_looping);
+        assert_eq!(tracker.attempts, 0);
+    }
+}
@@ -1,101 +1,573 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use futures_util::StreamExt;
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
-use temporalio_sdk::{ChildWorkflowOptions, WfContext, WfExitValue};
+use temporalio_sdk::{
+    ActivityOptions, ChildWorkflowOptions, SignalWorkflowOptions, WfContext, WfExitValue,
+};
 
+use crate::activities::convoy_report::ConvoyReportOutput;
+use crate::activities::heartbeat::{RigLoadInput, RigLoadOutput};
 use crate::signals::*;
+use crate::workflows::timer_or_never;
+
+/// How often a throttled convoy re-checks rig capacity on its own.
+const BACKPRESSURE_RECHECK: Duration = Duration::from_secs(60);
 
 pub async fn convoy_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error> {
     let args = ctx.get_args();
-    let (id, title) = if let Some(payload) = args.first() {
-        serde_json::from_slice::<(String, String)>(&payload.data)
-            .unwrap_or(("unknown".into(), "untitled".into()))
-    } else {
-        ("unknown".into(), "untitled".into())
-    };
+    let (id, title, auto_sling, mut deadline) = args
+        .first()
+        .and_then(|payload| parse_input(&payload.data))
+        .unwrap_or(("unknown".into(), "untitled".into(), None, None));
 
     let mut work_items: Vec<String> = vec![];
     let mut completed: HashSet<String> = HashSet::new();
+    // Ended failed; they hold up their stage and the convoy until retried
+    let mut failed: BTreeSet<String> = BTreeSet::new();
     let mut status = "open".to_string();
+    // Set once the deadline passes with items still open
+    let mut at_risk = false;
+    // Auto-sling target, from the input or set later by `convoy_dispatch`
+    let mut dispatch: Option<ConvoyDispatchSignal> = auto_sling;
+    // Stage of each item; a stage is only slung once every earlier one is done
+    let mut stages: BTreeMap<String, u32> = BTreeMap::new();
+    // Items not yet slung, with their stage, in the order they were added
+    let mut undispatched: VecDeque<(String, String, u32)> = VecDeque::new();
+    // Slung but not finished — working or waiting in the merge queue — with
+    // the polecat each went to
+    let mut in_flight: BTreeMap<String, String> = BTreeMap::new();
 
     let mut add_item_ch = ctx.make_signal_channel(SIGNAL_ADD_WORK_ITEM);
     let mut item_done_ch = ctx.make_signal_channel(SIGNAL_ITEM_DONE);
     let mut cancel_ch = ctx.make_signal_channel(SIGNAL_CANCEL_CONVOY);
     let mut close_ch = ctx.make_signal_channel(SIGNAL_CLOSE);
+    let mut dispatch_ch = ctx.make_signal_channel(SIGNAL_CONVOY_DISPATCH);
+    let mut retry_ch = ctx.make_signal_channel(SIGNAL_CONVOY_RETRY);
+
+    // Armed while auto-slinging is held back; replaced only once it fires
+    let mut recheck = std::pin::pin!(timer_or_never(&ctx, None));
+    let mut recheck_armed = false;
+    // Counts down to the deadline being watched, if any
+    let mut deadline_timer = std::pin::pin!(timer_or_never(&ctx, None));
+    let mut deadline_timer_for: Option<DateTime<Utc>> = None;
 
     tracing::info!("Convoy {id} started: {title}");
 
     loop {
+        let throttled = dispatch.is_some() && !undispatched.is_empty();
+        if throttled && !recheck_armed {
+            recheck.set(timer_or_never(&ctx, Some(BACKPRESSURE_RECHECK)));
+            recheck_armed = true;
+        }
+        let watched = deadline.filter(|_| !at_risk);
+        if watched != deadline_timer_for {
+            deadline_timer.set(timer_or_never(&ctx, watched.map(|at| time_until(&ctx, at))));
+            deadline_timer_for = watched;
+        }
         tokio::select! {
             Some(signal) = add_item_ch.next() => {
                 if let Some(payload) = signal.input.first() {
                     if let Ok(data) = serde_json::from_slice::<AddWorkItemSignal>(&payload.data) {
                         work_items.push(data.work_item_id.clone());
+                        stages.insert(data.work_item_id.clone(), data.stage);
 
-                        // Start child WorkItem workflow
-                        let input_payload = (data.work_item_id.as_str(), data.title.as_str())
-                            .as_json_payload()?;
-                        let child = ctx.child_workflow(ChildWorkflowOptions {
-                            workflow_id: data.work_item_id.clone(),
-                            workflow_type: "work_item_wf".to_string(),
-                            input: vec![input_payload],
-                            ..Default::default()
-                        });
-                        let pending = child.start(&ctx).await;
-                        if pending.into_started().is_some() {
-                            tracing::info!("Convoy {id}: started child work item {}", data.work_item_id);
-                        } else {
-                            tracing::warn!("Convoy {id}: failed to start child {}", data.work_item_id);
+                        // Start child WorkItem workflow; it reports back with item_done
+                        match &data.item {
+                            Some(item) => start_item(&ctx, &id, &data.work_item_id, (item, id.as_str())).await?,
+                            None => {
+                                let input = (data.work_item_id.as_str(), data.title.as_str(), id.as_str());
+                                start_item(&ctx, &id, &data.work_item_id, input).await?
+                            }
                         }
+                        undispatched.push_back((data.work_item_id, data.title, data.stage));
+                        report_progress(&ctx, &id, &title, completed.len(), work_items.len(), deadline, at_risk).await?;
+                        dispatch_pending(&ctx, &id, dispatch.as_ref(), open_stage(&stages, &completed), &mut undispatched, &mut in_flight).await?;
                     }
                 }
             }
             Some(signal) = item_done_ch.next() => {
                 if let Some(payload) = signal.input.first() {
                     if let Ok(data) = serde_json::from_slice::<ItemDoneSignal>(&payload.data) {
-                        completed.insert(data.work_item_id.clone());
-                        tracing::info!(
-                            "Convoy {id}: item {} done ({}/{})",
-                            data.work_item_id, completed.len(), work_items.len()
-                        );
+                        in_flight.remove(&data.work_item_id);
+                        if data.failed {
+                            failed.insert(data.work_item_id.clone());
+                            tracing::warn!(
+                                "Convoy {id}: item {} failed — `rgt convoy retry {id}` to try it again",
+                                data.work_item_id
+                            );
+                        } else {
+                            completed.insert(data.work_item_id.clone());
+                            tracing::info!(
+                                "Convoy {id}: item {} done ({}/{})",
+                                data.work_item_id, completed.len(), work_items.len()
+                            );
+                        }
+
+                        report_progress(&ctx, &id, &title, completed.len(), work_items.len(), deadline, at_risk).await?;
 
                         if !work_items.is_empty() && completed.len() == work_items.len() {
                             status = "closed".to_string();
                             tracing::info!("Convoy {id} complete — all items done");
-                            return Ok(WfExitValue::Normal(
-                                serde_json::to_string(&ConvoyState {
-                                    id, title, status,
-                                    work_items,
-                                    completed_items: completed.into_iter().collect(),
-                                })?
-                            ));
+                            let state = ConvoyState {
+                                id, title, status,
+                                work_items,
+                                completed_items: completed.into_iter().collect(),
+                                stages,
+                                ..Default::default()
+                            };
+                            report_closed(&ctx, &state, at_risk).await?;
+                            return Ok(WfExitValue::Normal(serde_json::to_string(&state)?));
+                        }
+                        // A landed merge frees a slot
+                        dispatch_pending(&ctx, &id, dispatch.as_ref(), open_stage(&stages, &completed), &mut undispatched, &mut in_flight).await?;
+                    }
+                }
+            }
+            Some(signal) = dispatch_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(data) = serde_json::from_slice::<ConvoyDispatchSignal>(&payload.data) {
+                        tracing::info!(
+                            "Convoy {id}: auto-slinging to rig {} (high-water mark {})",
+                            data.rig, data.high_water
+                        );
+                        dispatch = Some(data);
+                        dispatch_pending(&ctx, &id, dispatch.as_ref(), open_stage(&stages, &completed), &mut undispatched, &mut in_flight).await?;
+                    }
+                }
+            }
+            Some(signal) = retry_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(data) = serde_json::from_slice::<ConvoyRetrySignal>(&payload.data) {
+                        let work_item_id = data.item.id.0.clone();
+                        if failed.remove(&work_item_id) {
+                            tracing::info!(
+                                "Convoy {id}: retrying {work_item_id} (failed run {})",
+                                data.item.retry_of.as_deref().unwrap_or("?")
+                            );
+                            start_item(&ctx, &id, &work_item_id, (&data.item, id.as_str())).await?;
+                            let stage = stages.get(&work_item_id).copied().unwrap_or_default();
+                            undispatched.push_back((work_item_id, data.item.title, stage));
+                            dispatch_pending(&ctx, &id, dispatch.as_ref(), open_stage(&stages, &completed), &mut undispatched, &mut in_flight).await?;
+                        } else {
+                            tracing::warn!("Convoy {id}: not retrying {work_item_id}, which hasn't failed");
                         }
                     }
                 }
             }
+            _ = &mut recheck => {
+                recheck.set(timer_or_never(&ctx, None));
+                recheck_armed = false;
+                // Polecats outside this convoy may have freed the rig
+                dispatch_pending(&ctx, &id, dispatch.as_ref(), open_stage(&stages, &completed), &mut undispatched, &mut in_flight).await?;
+            }
+            _ = &mut deadline_timer => {
+                deadline_timer.set(timer_or_never(&ctx, None));
+                deadline_timer_for = None;
+                if completed.len() < work_items.len() {
+                    at_risk = true;
+                    status = "at_risk".to_string();
+                    tracing::warn!(
+                        "Convoy {id} at risk — deadline passed with {}/{} item(s) done",
+                        completed.len(), work_items.len()
+                    );
+                    report_escalation(&ctx, &id, &title, "open").await?;
+                    report_progress(&ctx, &id, &title, completed.len(), work_items.len(), deadline, at_risk).await?;
+                } else {
+                    // Nothing was left to finish; stop watching the clock
+                    deadline = None;
+                }
+            }
             Some(_) = close_ch.next() => {
                 status = "closed".to_string();
                 tracing::info!("Convoy {id} closed");
-                return Ok(WfExitValue::Normal(
-                    serde_json::to_string(&ConvoyState {
-                        id, title, status,
-                        work_items,
-                        completed_items: completed.into_iter().collect(),
-                    })?
-                ));
+                let state = ConvoyState {
+                    id, title, status,
+                    work_items,
+                    current_stage: open_stage(&stages, &completed),
+                    completed_items: completed.into_iter().collect(),
+                    failed_items: failed.into_iter().collect(),
+                    stages,
+                    ..Default::default()
+                };
+                report_closed(&ctx, &state, at_risk).await?;
+                return Ok(WfExitValue::Normal(serde_json::to_string(&state)?));
             }
             Some(_) = cancel_ch.next() => {
                 status = "cancelled".to_string();
-                tracing::info!("Convoy {id} cancelled");
-                return Ok(WfExitValue::Normal(
-                    serde_json::to_string(&ConvoyState {
-                        id, title, status,
-                        work_items,
-                        completed_items: completed.into_iter().collect(),
-                    })?
-                ));
+                let (cancelled_items, killed_polecats) =
+                    tear_down(&ctx, &work_items, &completed, &in_flight).await?;
+                tracing::info!(
+                    "Convoy {id} cancelled — closed {} item(s), killed {} polecat(s)",
+                    cancelled_items.len(), killed_polecats.len()
+                );
+                let state = ConvoyState {
+                    id, title, status,
+                    work_items,
+                    current_stage: open_stage(&stages, &completed),
+                    completed_items: completed.into_iter().collect(),
+                    failed_items: failed.into_iter().collect(),
+                    stages,
+                    cancelled_items,
+                    killed_polecats,
+                };
+                report_closed(&ctx, &state, at_risk).await?;
+                return Ok(WfExitValue::Normal(serde_json::to_string(&state)?));
             }
         }
     }
 }
+
+/// Start an item's `work_item_wf` as a child; it reports back with
+/// `item_done`. A retry starts a new run under the same workflow ID.
+async fn start_item(
+    ctx: &WfContext,
+    id: &str,
+    work_item_id: &str,
+    input: impl serde::Serialize,
+) -> anyhow::Result<()> {
+    let started = ctx
+        .child_workflow(ChildWorkflowOptions {
+            workflow_id: work_item_id.to_string(),
+            workflow_type: "work_item_wf".to_string(),
+            input: vec![input.as_json_payload()?],
+            ..Default::default()
+        })
+        .start(ctx)
+        .await
+        .into_started()
+        .is_some();
+    if started {
+        tracing::info!("Convoy {id}: started child work item {work_item_id}");
+    } else {
+        tracing::warn!("Convoy {id}: failed to start child {work_item_id}");
+    }
+    Ok(())
+}
+
+/// Stop the work of a cancelled convoy: kill the polecats on its slung items
+/// and close every item that hasn't finished. Returns the closed items and
+/// killed polecats; signals to workflows that already ended are skipped.
+async fn tear_down(
+    ctx: &WfContext,
+    work_items: &[String],
+    completed: &HashSet<String>,
+    in_flight: &BTreeMap<String, String>,
+) -> anyhow::Result<(Vec<String>, Vec<String>)> {
+    let mut cancelled_items = Vec::new();
+    let mut killed_polecats = Vec::new();
+    for work_item_id in work_items.iter().filter(|w| !completed.contains(*w)) {
+        if let Some(polecat_id) = in_flight.get(work_item_id) {
+            let opts = SignalWorkflowOptions::new(polecat_id, "", SIGNAL_POLECAT_KILL, vec![]);
+            if ctx.signal_workflow(opts).await.is_ok() {
+                killed_polecats.push(polecat_id.clone());
+            }
+        }
+        let opts = SignalWorkflowOptions::new(work_item_id, "", SIGNAL_CLOSE, vec![]);
+        if ctx.signal_workflow(opts).await.is_ok() {
+            cancelled_items.push(work_item_id.clone());
+        }
+    }
+    Ok((cancelled_items, killed_polecats))
+}
+
+/// The convoy's input: id, title, the rig to auto-sling every item to, and
+/// the time all items should be done by.
+type ConvoyInput = (
+    String,
+    String,
+    Option<ConvoyDispatchSignal>,
+    Option<DateTime<Utc>>,
+);
+
+/// Decode the convoy's input: `(id, title, dispatch, deadline)`, or the
+/// shorter `(id, title, dispatch)` and `(id, title)`.
+fn parse_input(data: &[u8]) -> Option<ConvoyInput> {
+    if let Ok(input) = serde_json::from_slice(data) {
+        return Some(input);
+    }
+    if let Ok((id, title, dispatch)) = serde_json::from_slice(data) {
+        return Some((id, title, dispatch, None));
+    }
+    let (id, title) = serde_json::from_slice::<(String, String)>(data).ok()?;
+    Some((id, title, None, None))
+}
+
+/// How long until `deadline` by the workflow clock; zero once it's passed.
+fn time_until(ctx: &WfContext, deadline: DateTime<Utc>) -> Duration {
+    let now: DateTime<Utc> = ctx.workflow_time().map(Into::into).unwrap_or_default();
+    (deadline - now).to_std().unwrap_or(Duration::ZERO)
+}
+
+/// Tell the mayor how far along the convoy is. Best effort, since the mayor
+/// may not be running.
+async fn report_progress(
+    ctx: &WfContext,
+    id: &str,
+    title: &str,
+    done: usize,
+    total: usize,
+    deadline: Option<DateTime<Utc>>,
+    at_risk: bool,
+) -> anyhow::Result<()> {
+    let progress = ConvoyProgressSignal {
+        convoy_id: id.to_string(),
+        title: title.to_string(),
+        done,
+        total,
+        deadline,
+        at_risk,
+    };
+    let sig_opts = SignalWorkflowOptions::new(
+        gtr_core::state::mayor_workflow_id(),
+        "",
+        SIGNAL_CONVOY_PROGRESS,
+        vec![progress.as_json_payload()?],
+    );
+    let _ = ctx.signal_workflow(sig_opts).await;
+    Ok(())
+}
+
+/// Escalate a convoy that missed its deadline to the mayor, or resolve that
+/// escalation. Best effort, like [`report_progress`].
+async fn report_escalation(
+    ctx: &WfContext,
+    id: &str,
+    title: &str,
+    status: &str,
+) -> anyhow::Result<()> {
+    let update = EscalationUpdateSignal {
+        work_item_id: id.to_string(),
+        title: format!("{title} (convoy past its deadline)"),
+        level: 1,
+        status: status.to_string(),
+    };
+    let sig_opts = SignalWorkflowOptions::new(
+        gtr_core::state::mayor_workflow_id(),
+        "",
+        SIGNAL_ESCALATION_UPDATE,
+        vec![update.as_json_payload()?],
+    );
+    let _ = ctx.signal_workflow(sig_opts).await;
+    Ok(())
+}
+
+/// Tell the mayor the convoy is finished, so it stops showing its progress
+/// and, if the convoy was at risk, its escalation. Writes the convoy's summary
+/// report first and mails it to the mayor and the human inbox.
+async fn report_closed(ctx: &WfContext, state: &ConvoyState, at_risk: bool) -> anyhow::Result<()> {
+    if at_risk {
+        report_escalation(ctx, &state.id, &state.title, "resolved").await?;
+    }
+
+    // The summary report goes to the human inbox from the activity; the mayor
+    // gets it as mail so it shows up in its next turn
+    let report = ctx
+        .activity(ActivityOptions {
+            activity_type: "convoy_report".to_string(),
+            input: state.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(120)),
+            ..Default::default()
+        })
+        .await
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<ConvoyReportOutput>(&p.data).ok());
+    match report {
+        Some(report) => {
+            let mail = AgentMailSignal {
+                from: state.id.clone(),
+                message: format!("{}\nReport: {}", report.summary, report.path),
+                id: Some(format!("ml-report-{}", state.id)),
+                attachments: vec![report.path.into()],
+                ..Default::default()
+            };
+            let sig_opts = SignalWorkflowOptions::new(
+                gtr_core::state::mayor_workflow_id(),
+                "",
+                SIGNAL_AGENT_MAIL,
+                vec![mail.as_json_payload()?],
+            );
+            let _ = ctx.signal_workflow(sig_opts).await;
+        }
+        None => tracing::warn!("Convoy {}: failed to write the summary report", state.id),
+    }
+
+    let closed = ConvoyClosedSignal {
+        convoy_id: state.id.clone(),
+    };
+    let sig_opts = SignalWorkflowOptions::new(
+        gtr_core::state::mayor_workflow_id(),
+        "",
+        SIGNAL_CONVOY_CLOSED,
+        vec![closed.as_json_payload()?],
+    );
+    let _ = ctx.signal_workflow(sig_opts).await;
+    Ok(())
+}
+
+/// The earliest stage with an unfinished item, `None` once all are done.
+fn open_stage(stages: &BTreeMap<String, u32>, completed: &HashSet<String>) -> Option<u32> {
+    stages
+        .iter()
+        .filter(|(work_item_id, _)| !completed.contains(*work_item_id))
+        .map(|(_, stage)| *stage)
+        .min()
+}
+
+/// Sling queued items of the open stage (or an earlier one) to the dispatch
+/// rig while the convoy is under its high-water mark and the rig has free
+/// polecat slots. Polecat names come from the work item ID so replays pick the
+/// same name.
+async fn dispatch_pending(
+    ctx: &WfContext,
+    id: &str,
+    dispatch: Option<&ConvoyDispatchSignal>,
+    open_stage: Option<u32>,
+    undispatched: &mut VecDeque<(String, String, u32)>,
+    in_flight: &mut BTreeMap<String, String>,
+) -> anyhow::Result<()> {
+    let Some(dispatch) = dispatch else {
+        return Ok(());
+    };
+    let ready =
+        |(_, _, stage): &(String, String, u32)| open_stage.is_some_and(|open| *stage <= open);
+    if !undispatched.iter().any(ready) {
+        if let (false, Some(open)) = (undispatched.is_empty(), open_stage) {
+            tracing::info!(
+                "Convoy {id}: holding {} item(s) until stage {open} is done",
+                undispatched.len()
+            );
+        }
+        return Ok(());
+    }
+    let rig = dispatch.rig.as_str();
+
+    let load = ctx
+        .activity(ActivityOptions {
+            activity_type: "rig_load".to_string(),
+            input: RigLoadInput {
+                rig: rig.to_string(),
+            }
+            .as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(15)),
+            ..Default::default()
+        })
+        .await
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<RigLoadOutput>(&p.data).ok());
+    let room = dispatch_room(in_flight.len(), dispatch.high_water, load.as_ref());
+    if room == 0 {
+        tracing::info!(
+            "Convoy {id}: holding {} item(s) — {} in flight, rig {rig} at capacity or high-water mark",
+            undispatched.len(),
+            in_flight.len()
+        );
+        return Ok(());
+    }
+
+    let runtime = dispatch.runtime.as_deref().unwrap_or("claude");
+    for _ in 0..room {
+        let Some((work_item_id, title, _)) = undispatched
+            .iter()
+            .position(ready)
+            .and_then(|i| undispatched.remove(i))
+        else {
+            break;
+        };
+        let start = work_item_id.len().saturating_sub(8);
+        let suffix = work_item_id.get(start..).unwrap_or(&work_item_id);
+        let name = format!("convoy-{suffix}");
+        let polecat_id = gtr_core::state::polecat_workflow_id(rig, &name);
+        let started = ctx
+            .child_workflow(ChildWorkflowOptions {
+                workflow_id: polecat_id.clone(),
+                workflow_type: "polecat_wf".to_string(),
+                input: vec![(
+                    name.as_str(),
+                    rig,
+                    work_item_id.as_str(),
+                    title.as_str(),
+                    runtime,
+                )
+                    .as_json_payload()?],
+                ..Default::default()
+            })
+            .start(ctx)
+            .await
+            .into_started()
+            .is_some();
+        if started {
+            tracing::info!("Convoy {id}: slung {work_item_id} → polecat {polecat_id}");
+            in_flight.insert(work_item_id, polecat_id);
+        } else {
+            tracing::warn!("Convoy {id}: could not sling {work_item_id} to {polecat_id}");
+        }
+    }
+    Ok(())
+}
+
+/// How many more items may be slung now. An unknown rig load (the activity
+/// failed) doesn't block dispatch; the high-water mark still applies.
+fn dispatch_room(in_flight: usize, high_water: usize, load: Option<&RigLoadOutput>) -> usize {
+    let convoy_room = high_water.saturating_sub(in_flight);
+    load.map_or(convoy_room, |load| convoy_room.min(load.free_slots()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_input_accepts_an_auto_sling_target() {
+        let (id, title, dispatch, deadline) = parse_input(br#"["cv-1","Sprint"]"#).unwrap();
+        assert_eq!((id.as_str(), title.as_str()), ("cv-1", "Sprint"));
+        assert!(dispatch.is_none() && deadline.is_none());
+
+        let data = br#"["cv-1","Sprint",{"rig":"webapp","high_water":2}]"#;
+        let dispatch = parse_input(data).unwrap().2.unwrap();
+        assert_eq!((dispatch.rig.as_str(), dispatch.high_water), ("webapp", 2));
+        assert!(parse_input(b"{}").is_none());
+
+        let data = br#"["cv-1","Sprint",null,"2026-03-01T17:00:00Z"]"#;
+        let deadline = parse_input(data).unwrap().3.unwrap();
+        assert_eq!(deadline.to_rfc3339(), "2026-03-01T17:00:00+00:00");
+    }
+
+    #[test]
+    fn open_stage_waits_for_every_earlier_item() {
+        let stages: BTreeMap<String, u32> = [("wi-a", 1), ("wi-b", 1), ("wi-c", 2), ("wi-d", 0)]
+            .into_iter()
+            .map(|(id, stage)| (id.to_string(), stage))
+            .collect();
+        let mut completed = HashSet::new();
+        completed.insert("wi-d".to_string());
+        assert_eq!(open_stage(&stages, &completed), Some(1));
+        completed.insert("wi-a".to_string());
+        assert_eq!(open_stage(&stages, &completed), Some(1));
+        completed.insert("wi-b".to_string());
+        assert_eq!(open_stage(&stages, &completed), Some(2));
+        completed.insert("wi-c".to_string());
+        assert_eq!(open_stage(&stages, &completed), None);
+    }
+
+    #[test]
+    fn dispatch_room_respects_high_water_and_rig_capacity() {
+        let load = |running: usize, max_polecats| RigLoadOutput {
+            polecats: (0..running).map(|i| format!("gt-polecat-{i}")).collect(),
+            max_polecats,
+        };
+        assert_eq!(dispatch_room(0, 4, None), 4);
+        assert_eq!(dispatch_room(3, 4, None), 1);
+        assert_eq!(dispatch_room(5, 4, None), 0);
+        // The rig cap counts polecats from every source, not just this convoy
+        assert_eq!(dispatch_room(1, 4, Some(&load(5, Some(6)))), 1);
+        assert_eq!(dispatch_room(0, 4, Some(&load(8, Some(6)))), 0);
+        assert_eq!(dispatch_room(0, 4, Some(&load(8, None))), 4);
+    }
+}
@@ -0,0 +1,360 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use gtr_core::config::DeputyConfig;
+use gtr_core::mail::HUMAN;
+use serde::{Deserialize, Serialize};
+use temporalio_common::protos::coresdk::workflow_commands::ContinueAsNewWorkflowExecution;
+use temporalio_common::protos::coresdk::AsJsonPayloadExt;
+use temporalio_sdk::{ActivityOptions, SignalWorkflowOptions, WfContext, WfExitValue};
+
+use crate::activities::heartbeat::{HeartbeatInput, HeartbeatOutput};
+use crate::activities::spawn_agent::SpawnAgentInput;
+use crate::signals::{
+    AgentMailSignal, DeputySignal, SIGNAL_AGENT_MAIL, SIGNAL_AGENT_STOP, SIGNAL_MAYOR_DEPUTY,
+};
+use crate::workflows::agent::store_mail;
+use crate::workflows::mayor::CONTINUE_AS_NEW_AFTER;
+use crate::workflows::timer_or_never;
+
+const DEPUTY_PROMPT: &str = "You are the Deputy Mayor of Gas Town, covering while the Mayor agent is down. \
+     The RGT_BIN env var has the full path to the rgt binary. Use $RGT_BIN instead of rgt in all commands. \
+     Mail sent to the Mayor is copied to your inbox:\n\
+     1. `$RGT_BIN mail inbox` - check for messages\n\
+     2. Handle what can't wait; leave the rest for the Mayor\n\
+     The Mayor takes over again as soon as it is back.";
+
+/// Failover bookkeeping for the mayor agent. Counted in health checks rather
+/// than wall-clock time so it replays deterministically.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MayorWatch {
+    /// Consecutive checks the mayor agent was found dead.
+    dead_checks: u32,
+    acting: bool,
+}
+
+/// What a run carries into the next one when it continues as new.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DeputyState {
+    watch: MayorWatch,
+    checks: u64,
+    takeovers: u64,
+    acting_since: Option<DateTime<Utc>>,
+    /// Mail copied from the mayor during the current takeover
+    covered: usize,
+    mail_received: usize,
+}
+
+/// What the deputy should do after a health check.
+#[derive(Debug, PartialEq)]
+enum Failover {
+    TakeOver,
+    HandBack,
+    Wait,
+}
+
+impl MayorWatch {
+    fn on_check(&mut self, alive: bool, failover_after: u32) -> Failover {
+        if alive {
+            self.dead_checks = 0;
+            if self.acting {
+                self.acting = false;
+                return Failover::HandBack;
+            }
+            return Failover::Wait;
+        }
+        self.dead_checks += 1;
+        if !self.acting && self.dead_checks >= failover_after.max(1) {
+            self.acting = true;
+            return Failover::TakeOver;
+        }
+        Failover::Wait
+    }
+}
+
+/// Deputy workflow — a standby for the mayor agent. It checks the mayor's
+/// session every `interval_secs`; after `failover_after` failed checks in a
+/// row (boot having failed to bring it back) it tells `mayor_wf` to copy its
+/// mail here, optionally spawns a `deputy` agent to read it, and mails the
+/// human. Once the mayor agent is alive again it hands back: the copying
+/// stops, the deputy agent is killed and the mayor is told what it missed.
+/// Like the mayor it continues as new after [`CONTINUE_AS_NEW_AFTER`]
+/// signals and checks, carrying its config and [`DeputyState`].
+pub async fn deputy_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error> {
+    let args = ctx.get_args();
+    let config = args
+        .first()
+        .and_then(|payload| serde_json::from_slice::<DeputyConfig>(&payload.data).ok())
+        .unwrap_or_default();
+    // A continued run starts from the state the last one carried over
+    let DeputyState {
+        mut watch,
+        mut checks,
+        mut takeovers,
+        mut acting_since,
+        mut covered,
+        mut mail_received,
+    } = args
+        .get(1)
+        .and_then(|payload| serde_json::from_slice::<DeputyState>(&payload.data).ok())
+        .unwrap_or_default();
+    let deputy_id = gtr_core::state::deputy_workflow_id();
+
+    let mut stop_ch = ctx.make_signal_channel(SIGNAL_AGENT_STOP);
+    let mut mail_ch = ctx.make_signal_channel(SIGNAL_AGENT_MAIL);
+    let mut handled = 0;
+
+    tracing::info!(
+        "Deputy started — checking the mayor every {}s, taking over after {} failed checks",
+        config.interval_secs,
+        config.failover_after
+    );
+
+    // Made once and replaced only when it fires, so mail doesn't put off the check
+    let mut check_timer = std::pin::pin!(timer_or_never(&ctx, Some(config.interval())));
+    loop {
+        let wrap_up = handled >= CONTINUE_AS_NEW_AFTER;
+        handled += 1;
+
+        tokio::select! {
+            biased;
+            Some(_) = stop_ch.next() => {
+                if watch.acting {
+                    hand_back(&ctx, &config, acting_since, covered).await?;
+                }
+                tracing::info!("Deputy stopped after {checks} checks");
+                return Ok(WfExitValue::Normal(
+                    serde_json::to_string(&serde_json::json!({
+                        "checks": checks,
+                        "takeovers": takeovers,
+                        "acting": watch.acting,
+                    }))?
+                ));
+            }
+            Some(signal) = mail_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(data) = serde_json::from_slice::<AgentMailSignal>(&payload.data) {
+                        tracing::info!("Deputy: mail from {} — {}", data.from, data.message);
+                        store_mail(&ctx, &deputy_id, &data, mail_received).await;
+                        mail_received += 1;
+                        covered += 1;
+                    }
+                }
+            }
+            _ = &mut check_timer => {
+                check_timer.set(timer_or_never(&ctx, Some(config.interval())));
+                checks += 1;
+                let alive = mayor_alive(&ctx).await?;
+                match watch.on_check(alive, config.failover_after) {
+                    Failover::TakeOver => {
+                        tracing::warn!(
+                            "Deputy: mayor agent dead for {} checks — taking over",
+                            watch.dead_checks
+                        );
+                        takeovers += 1;
+                        acting_since = ctx.workflow_time().map(Into::into);
+                        covered = 0;
+                        take_over(&ctx, &config, takeovers).await?;
+                    }
+                    Failover::HandBack => {
+                        tracing::info!("Deputy: mayor agent is back — handing back");
+                        hand_back(&ctx, &config, acting_since, covered).await?;
+                        acting_since = None;
+                    }
+                    Failover::Wait => {}
+                }
+            }
+            _ = std::future::ready(()), if wrap_up => {
+                tracing::info!("Deputy: continuing as new after {checks} checks");
+                let state = DeputyState {
+                    watch,
+                    checks,
+                    takeovers,
+                    acting_since,
+                    covered,
+                    mail_received,
+                };
+                return Ok(WfExitValue::continue_as_new(
+                    ContinueAsNewWorkflowExecution {
+                        arguments: vec![config.as_json_payload()?, state.as_json_payload()?],
+                        ..Default::default()
+                    },
+                ));
+            }
+        }
+    }
+}
+
+/// Whether the mayor's agent session is running.
+async fn mayor_alive(ctx: &WfContext) -> anyhow::Result<bool> {
+    let input = HeartbeatInput {
+        agent_id: gtr_core::state::mayor_workflow_id(),
+    };
+    let result = ctx
+        .activity(ActivityOptions {
+            activity_type: "check_agent_alive".to_string(),
+            input: input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(10)),
+            ..Default::default()
+        })
+        .await;
+    Ok(result
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<HeartbeatOutput>(&p.data).ok())
+        .is_some_and(|hb| hb.alive))
+}
+
+async fn take_over(ctx: &WfContext, config: &DeputyConfig, takeover: u64) -> anyhow::Result<()> {
+    set_acting(ctx, true).await?;
+
+    let mut note = "The deputy is copying the mayor's mail to its own inbox".to_string();
+    if config.spawn_agent {
+        let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+        let input = SpawnAgentInput {
+            agent_id: gtr_core::state::deputy_workflow_id(),
+            runtime: config.runtime.clone(),
+            work_dir: format!("{home}/.gtr"),
+            role: gtr_core::state::roles::DEPUTY.to_string(),
+            rig: None,
+            initial_prompt: Some(DEPUTY_PROMPT.to_string()),
+            env_extra: None,
+            resume_session_id: None,
+            limits: None,
+            model: None,
+            permission_mode: None,
+            allowed_tools: None,
+        };
+        let result = ctx
+            .activity(ActivityOptions {
+                activity_type: "spawn_agent".to_string(),
+                input: input.as_json_payload()?,
+                start_to_close_timeout: Some(Duration::from_secs(30)),
+                ..Default::default()
+            })
+            .await;
+        if result.completed_ok() {
+            note.push_str(" and a deputy agent is reading it");
+        } else {
+            tracing::error!("Deputy: failed to spawn the deputy agent");
+        }
+    }
+
+    let message = format!(
+        "The mayor agent is down and boot could not bring it back. {note} \
+         (`rgt mail inbox {}`) until the mayor returns.",
+        gtr_core::state::deputy_workflow_id()
+    );
+    mail_human(ctx, &format!("ml-deputy-{takeover}"), message).await
+}
+
+async fn hand_back(
+    ctx: &WfContext,
+    config: &DeputyConfig,
+    acting_since: Option<DateTime<Utc>>,
+    covered: usize,
+) -> anyhow::Result<()> {
+    set_acting(ctx, false).await?;
+
+    if config.spawn_agent {
+        let input = HeartbeatInput {
+            agent_id: gtr_core::state::deputy_workflow_id(),
+        };
+        let _ = ctx
+            .activity(ActivityOptions {
+                activity_type: "kill_agent".to_string(),
+                input: input.as_json_payload()?,
+                start_to_close_timeout: Some(Duration::from_secs(30)),
+                ..Default::default()
+            })
+            .await;
+    }
+
+    let since = acting_since
+        .map(|at| format!(" since {}", at.format("%Y-%m-%d %H:%M UTC")))
+        .unwrap_or_default();
+    let mail = AgentMailSignal {
+        from: gtr_core::state::deputy_workflow_id(),
+        message: format!(
+            "Welcome back. The deputy covered for you{since}; {covered} message(s) came in \
+             meanwhile. Read what the deputy saw with `$RGT_BIN mail inbox {}`.",
+            gtr_core::state::deputy_workflow_id()
+        ),
+        ..Default::default()
+    };
+    let opts = SignalWorkflowOptions::new(
+        gtr_core::state::mayor_workflow_id(),
+        "",
+        SIGNAL_AGENT_MAIL,
+        vec![mail.as_json_payload()?],
+    );
+    let _ = ctx.signal_workflow(opts).await;
+    Ok(())
+}
+
+/// Tell the mayor workflow whether to copy its mail here. Errors are ignored —
+/// the mayor may not be running.
+async fn set_acting(ctx: &WfContext, acting: bool) -> anyhow::Result<()> {
+    let opts = SignalWorkflowOptions::new(
+        gtr_core::state::mayor_workflow_id(),
+        "",
+        SIGNAL_MAYOR_DEPUTY,
+        vec![DeputySignal { acting }.as_json_payload()?],
+    );
+    let _ = ctx.signal_workflow(opts).await;
+    Ok(())
+}
+
+/// Mail the human operator from the deputy; `id` keeps a retried delivery
+/// from landing twice.
+async fn mail_human(ctx: &WfContext, id: &str, message: String) -> anyhow::Result<()> {
+    let sent_at: DateTime<Utc> = ctx.workflow_time().map(Into::into).unwrap_or_default();
+    let mail = AgentMailSignal {
+        from: gtr_core::state::deputy_workflow_id(),
+        message,
+        ..Default::default()
+    }
+    .to_mail(id.to_string(), HUMAN, sent_at);
+    let stored = ctx
+        .activity(ActivityOptions {
+            activity_type: "mail_human".to_string(),
+            input: mail.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await;
+    if !stored.completed_ok() {
+        tracing::warn!("Deputy: could not mail {HUMAN} ({id})");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_over_after_failed_checks_and_hands_back() {
+        let mut watch = MayorWatch::default();
+        assert_eq!(watch.on_check(false, 3), Failover::Wait);
+        assert_eq!(watch.on_check(true, 3), Failover::Wait);
+
+        let actions: Vec<Failover> = (0..4).map(|_| watch.on_check(false, 3)).collect();
+        assert_eq!(
+            actions,
+            vec![
+                Failover::Wait,
+                Failover::Wait,
+                Failover::TakeOver,
+                Failover::Wait
+            ]
+        );
+        assert!(watch.acting);
+
+        assert_eq!(watch.on_check(true, 3), Failover::HandBack);
+        assert!(!watch.acting);
+        assert_eq!(watch.on_check(true, 3), Failover::Wait);
+    }
+}
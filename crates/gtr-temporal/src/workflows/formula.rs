@@ -14,6 +14,13 @@ use serde::{Deserialize, Serialize};
 pub struct FormulaInput {
     pub formula_toml: String,
     pub vars: HashMap<String, String>,
+    /// Overrides the formula's `max_parallel` when a molecule executes it
+    #[serde(default)]
+    pub max_parallel: Option<usize>,
+    /// TOML of the formulas its `formula` steps run, nested ones included,
+    /// by name
+    #[serde(default)]
+    pub formulas: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +63,7 @@ pub async fn formula_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
             command,
             args,
             work_dir: None,
+            env: None,
         };
 
         let result = ctx
@@ -1,37 +1,132 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
 use futures_util::StreamExt;
-use temporalio_sdk::{WfContext, WfExitValue};
+use gtr_core::config::{DispatchPolicy, MailRouteAction, MayorPolicy, PolicyAction, PolicyEvent};
+use gtr_core::mail::{Mail, HUMAN};
+use gtr_core::state::WorkflowId;
+use gtr_core::types::WorkItem;
+use temporalio_common::protos::coresdk::child_workflow::ParentClosePolicy;
+use temporalio_common::protos::coresdk::workflow_commands::ContinueAsNewWorkflowExecution;
+use temporalio_common::protos::coresdk::AsJsonPayloadExt;
+use temporalio_common::protos::temporal::api::common::v1::RetryPolicy;
+use temporalio_sdk::{
+    ActivityOptions, ChildWorkflowOptions, SignalWorkflowOptions, WfContext, WfExitValue,
+};
 
+use crate::activities::digest::DailyDigestOutput;
+use crate::activities::dispatch::DispatchCandidates;
+use crate::activities::polecat_reports::StoredPolecatReport;
 use crate::signals::*;
+use crate::workflows::agent::store_mail;
+
+/// Signals a run handles before it continues as new, keeping its history
+/// well under Temporal's limit.
+pub const CONTINUE_AS_NEW_AFTER: usize = 2000;
 
+/// Polecat reports and merge results carried into the next run; older ones
+/// drop off.
+const CARRIED_TAIL: usize = 100;
+
+/// Mayor workflow — tracks agents, convoys, escalations and reports for the
+/// town. After [`CONTINUE_AS_NEW_AFTER`] signals, once none are waiting, it
+/// continues as new with its [`MayorState`], trimmed to the latest reports;
+/// a stop that is already waiting ends it instead. At each UTC midnight it
+/// sends the day's reports and merges to the `daily_digest` activity.
 pub async fn mayor_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error> {
-    let mut active_convoys: Vec<String> = vec![];
-    let mut agents: Vec<MayorAgentEntry> = vec![];
-    let mut polecat_reports: Vec<PolecatReportSignal> = vec![];
+    // A continued run starts from the state the last one carried over
+    let MayorState {
+        mut active_convoys,
+        mut agents,
+        mut polecat_reports,
+        mut merge_results,
+        mut open_escalations,
+        mut maintenance,
+        mut paused_rigs,
+        mut convoy_progress,
+        mut policy_hits,
+        mut deputy_acting,
+        mut digest,
+        mut pending_approvals,
+    } = ctx
+        .get_args()
+        .first()
+        .and_then(|payload| serde_json::from_slice::<MayorState>(&payload.data).ok())
+        .unwrap_or_default();
+    let mut mail_received = 0;
+    let mut handled = 0;
 
     let mut register_ch = ctx.make_signal_channel(SIGNAL_REGISTER_AGENT);
     let mut unregister_ch = ctx.make_signal_channel(SIGNAL_UNREGISTER_AGENT);
     let mut status_ch = ctx.make_signal_channel(SIGNAL_AGENT_STATUS_UPDATE);
     let mut convoy_closed_ch = ctx.make_signal_channel(SIGNAL_CONVOY_CLOSED);
+    let mut convoy_progress_ch = ctx.make_signal_channel(SIGNAL_CONVOY_PROGRESS);
     let mut add_convoy_ch = ctx.make_signal_channel(SIGNAL_ADD_WORK_ITEM);
     let mut stop_ch = ctx.make_signal_channel(SIGNAL_MAYOR_STOP);
     let mut report_ch = ctx.make_signal_channel(SIGNAL_POLECAT_REPORT);
+    let mut merge_result_ch = ctx.make_signal_channel(SIGNAL_MERGE_RESULT);
+    let mut escalation_ch = ctx.make_signal_channel(SIGNAL_ESCALATION_UPDATE);
+    let mut maintenance_ch = ctx.make_signal_channel(SIGNAL_MAYOR_MAINTENANCE);
+    let mut mail_ch = ctx.make_signal_channel(SIGNAL_AGENT_MAIL);
+    let mut deputy_ch = ctx.make_signal_channel(SIGNAL_MAYOR_DEPUTY);
+    let mut approval_request_ch = ctx.make_signal_channel(SIGNAL_APPROVAL_REQUEST);
+    let mut approval_decision_ch = ctx.make_signal_channel(SIGNAL_APPROVAL_DECISION);
+
+    tracing::info!(
+        "Mayor workflow started — {} agents, {} convoys",
+        agents.len(),
+        active_convoys.len()
+    );
 
-    tracing::info!("Mayor workflow started");
+    let policy = ctx
+        .activity(ActivityOptions {
+            activity_type: "load_dispatch_policy".to_string(),
+            input: ().as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<DispatchPolicy>(&p.data).ok())
+        .unwrap_or_default();
+    if policy.enabled {
+        tracing::info!(
+            "Mayor: dispatching pending work every {}s",
+            policy.interval_secs
+        );
+    }
+    // Kept as a point in time so signals arriving between runs don't restart the wait
+    let mut next_dispatch = now(&ctx) + policy.interval();
+    let mut next_digest = next_midnight(now(&ctx));
+    // Made once and replaced only when they fire, so signals don't start new timers
+    let mut dispatch_timer =
+        std::pin::pin!(wait_until(&ctx, policy.enabled.then_some(next_dispatch)));
+    let mut digest_timer = std::pin::pin!(wait_until(&ctx, Some(next_digest)));
 
     loop {
+        let wrap_up = handled >= CONTINUE_AS_NEW_AFTER;
+        handled += 1;
+
+        // Biased so every buffered signal is handled before continuing as new
         tokio::select! {
+            biased;
+
             Some(signal) = register_ch.next() => {
                 if let Some(payload) = signal.input.first() {
                     if let Ok(data) = serde_json::from_slice::<RegisterAgentSignal>(&payload.data) {
                         if !agents.iter().any(|a| a.agent_id == data.agent_id) {
                             tracing::info!("Mayor: registered agent {} ({})", data.agent_id, data.role);
                             agents.push(MayorAgentEntry {
-                                agent_id: data.agent_id,
+                                agent_id: data.agent_id.clone(),
                                 role: data.role,
                                 status: "idle".to_string(),
                                 current_work: None,
                             });
                         }
+                        redeliver_dead_letters(&ctx, &data.agent_id).await?;
                     }
                 }
             }
@@ -68,10 +163,26 @@ pub async fn mayor_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Err
                 if let Some(payload) = signal.input.first() {
                     if let Ok(data) = serde_json::from_slice::<ConvoyClosedSignal>(&payload.data) {
                         active_convoys.retain(|c| *c != data.convoy_id);
+                        convoy_progress.retain(|p| p.convoy_id != data.convoy_id);
                         tracing::info!("Mayor: convoy {} closed", data.convoy_id);
                     }
                 }
             }
+            Some(signal) = convoy_progress_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(progress) = serde_json::from_slice::<ConvoyProgressSignal>(&payload.data) {
+                        tracing::info!(
+                            "Mayor: convoy {} at {}/{} ({}%)",
+                            progress.convoy_id, progress.done, progress.total, progress.percent()
+                        );
+                        if !active_convoys.contains(&progress.convoy_id) {
+                            active_convoys.push(progress.convoy_id.clone());
+                        }
+                        convoy_progress.retain(|p| p.convoy_id != progress.convoy_id);
+                        convoy_progress.push(progress);
+                    }
+                }
+            }
             Some(signal) = report_ch.next() => {
                 if let Some(payload) = signal.input.first() {
                     if let Ok(report) = serde_json::from_slice::<PolecatReportSignal>(&payload.data) {
@@ -84,10 +195,151 @@ pub async fn mayor_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Err
                             agent.status = report.status.clone();
                             agent.current_work = Some(report.work_item_id.clone());
                         }
+                        let trigger = PolicyTrigger {
+                            on: PolicyEvent::PolecatReport,
+                            work_item_id: &report.work_item_id,
+                            title: &report.work_item_id,
+                            status: &report.status,
+                            exit_reason: &report.exit_reason,
+                            level: 0,
+                            report: Some(&report),
+                        };
+                        respond(&ctx, trigger, &mut policy_hits).await?;
+                        store_report(&ctx, &report).await?;
+                        digest.reports.push(report.clone());
                         polecat_reports.push(report);
                     }
                 }
             }
+            Some(signal) = merge_result_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(result) = serde_json::from_slice::<MergeResultSignal>(&payload.data) {
+                        if result.validate_only {
+                            tracing::info!("Mayor: '{}' validation — {} (branch {})", result.work_item_id, result.status, result.branch);
+                        } else if result.merged {
+                            tracing::info!("Mayor: '{}' merged (branch {})", result.work_item_id, result.branch);
+                        } else {
+                            tracing::warn!("Mayor: '{}' not merged — {} (branch {})", result.work_item_id, result.status, result.branch);
+                        }
+                        if !result.validate_only {
+                            digest.merges.push(result.clone());
+                        }
+                        merge_results.push(result);
+                    }
+                }
+            }
+            Some(signal) = escalation_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(update) = serde_json::from_slice::<EscalationUpdateSignal>(&payload.data) {
+                        tracing::info!(
+                            "Mayor: escalation {} L{} — {}",
+                            update.work_item_id, update.level, update.status
+                        );
+                        open_escalations.retain(|e| e.work_item_id != update.work_item_id);
+                        if update.status != "resolved" {
+                            let last_report = polecat_reports
+                                .iter()
+                                .rev()
+                                .find(|r| r.work_item_id == update.work_item_id);
+                            let trigger = PolicyTrigger {
+                                on: PolicyEvent::Escalation,
+                                work_item_id: &update.work_item_id,
+                                title: &update.title,
+                                status: &update.status,
+                                exit_reason: "",
+                                level: update.level,
+                                report: last_report,
+                            };
+                            respond(&ctx, trigger, &mut policy_hits).await?;
+                            open_escalations.push(update);
+                        }
+                    }
+                }
+            }
+            Some(signal) = maintenance_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(data) = serde_json::from_slice::<MaintenanceSignal>(&payload.data) {
+                        paused_rigs = if data.enabled { data.parked_rigs } else { Vec::new() };
+                        maintenance = data.enabled.then(|| data.message.unwrap_or_default());
+                        tracing::info!("Mayor: maintenance mode {}", if data.enabled { "on" } else { "off" });
+                    }
+                }
+            }
+            Some(signal) = mail_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(data) = serde_json::from_slice::<AgentMailSignal>(&payload.data) {
+                        tracing::info!("Mayor: mail from {} — {}", data.from, data.message);
+                        let mail = store_mail(&ctx, &gtr_core::state::mayor_workflow_id(), &data, mail_received).await;
+                        mail_received += 1;
+                        route_mail(&ctx, &mail, &agents).await?;
+                        if deputy_acting {
+                            forward_to_deputy(&ctx, AgentMailSignal { id: Some(mail.id), ..data }).await?;
+                        }
+                    }
+                }
+            }
+            Some(signal) = approval_request_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(request) = serde_json::from_slice::<ApprovalRequest>(&payload.data) {
+                        tracing::info!("Mayor: {} asks to {} {} ({})", request.requested_by, request.operation, request.target, request.id);
+                        let message = format!(
+                            "{} asks to {} {}: {}\nApprove with `rgt mayor approvals approve {}` or deny with `rgt mayor approvals deny {}`.",
+                            request.requested_by, request.operation, request.target, request.detail, request.id, request.id
+                        );
+                        mail_human(&ctx, &format!("ml-{}", request.id), message).await?;
+                        pending_approvals.retain(|r| r.id != request.id);
+                        pending_approvals.push(request);
+                    }
+                }
+            }
+            Some(signal) = approval_decision_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(decision) = serde_json::from_slice::<ApprovalDecision>(&payload.data) {
+                        match pending_approvals.iter().position(|r| r.id == decision.id) {
+                            Some(pos) => {
+                                let request = pending_approvals.remove(pos);
+                                tracing::info!("Mayor: {} {} by {}", request.id, if decision.approved { "approved" } else { "denied" }, decision.by);
+                                let opts = SignalWorkflowOptions::new(
+                                    request.requested_by.as_str(),
+                                    "",
+                                    SIGNAL_APPROVAL_DECISION,
+                                    vec![decision.as_json_payload()?],
+                                );
+                                if ctx.signal_workflow(opts).await.is_err() {
+                                    tracing::warn!("Mayor: {} is gone — dropped the decision on {}", request.requested_by, request.id);
+                                }
+                            }
+                            None => tracing::warn!("Mayor: no pending approval {}", decision.id),
+                        }
+                    }
+                }
+            }
+            Some(signal) = deputy_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(data) = serde_json::from_slice::<DeputySignal>(&payload.data) {
+                        deputy_acting = data.acting;
+                        tracing::info!("Mayor: deputy {}", if data.acting { "acting" } else { "stood down" });
+                    }
+                }
+            }
+            _ = &mut dispatch_timer => {
+                if maintenance.is_none() {
+                    auto_dispatch(&ctx, &policy, &mut agents).await?;
+                }
+                next_dispatch = now(&ctx) + policy.interval();
+                dispatch_timer.set(wait_until(&ctx, Some(next_dispatch)));
+            }
+            _ = &mut digest_timer => {
+                let input = DailyDigestInput {
+                    date: (next_digest - chrono::Duration::days(1)).format("%Y-%m-%d").to_string(),
+                    agents: agents.clone(),
+                    open_escalations: open_escalations.clone(),
+                    ..std::mem::take(&mut digest)
+                };
+                send_digest(&ctx, &input).await?;
+                next_digest = next_midnight(now(&ctx));
+                digest_timer.set(wait_until(&ctx, Some(next_digest)));
+            }
             Some(_) = stop_ch.next() => {
                 tracing::info!("Mayor stopping — {} agents, {} convoys, {} reports", agents.len(), active_convoys.len(), polecat_reports.len());
                 return Ok(WfExitValue::Normal(
@@ -95,9 +347,607 @@ pub async fn mayor_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Err
                         active_convoys,
                         agents,
                         polecat_reports,
+                        merge_results,
+                        open_escalations,
+                        maintenance,
+                        paused_rigs,
+                        convoy_progress,
+                        policy_hits,
+                        deputy_acting,
+                        digest,
+                        pending_approvals,
                     })?
                 ));
             }
+            _ = std::future::ready(()), if wrap_up => {
+                tracing::info!("Mayor: continuing as new after {} signals", handled - 1);
+                let state = MayorState {
+                    active_convoys,
+                    agents,
+                    polecat_reports: tail(polecat_reports, CARRIED_TAIL),
+                    merge_results: tail(merge_results, CARRIED_TAIL),
+                    open_escalations,
+                    maintenance,
+                    paused_rigs,
+                    convoy_progress,
+                    policy_hits,
+                    deputy_acting,
+                    digest,
+                    pending_approvals,
+                };
+                return Ok(WfExitValue::continue_as_new(
+                    ContinueAsNewWorkflowExecution {
+                        arguments: vec![state.as_json_payload()?],
+                        ..Default::default()
+                    },
+                ));
+            }
+        }
+    }
+}
+
+/// Keep a report in the report store behind `rgt polecat reports`.
+async fn store_report(ctx: &WfContext, report: &PolecatReportSignal) -> anyhow::Result<()> {
+    let stored = StoredPolecatReport {
+        reported_at: now(ctx),
+        report: report.clone(),
+    };
+    let result = ctx
+        .activity(ActivityOptions {
+            activity_type: "record_polecat_report".to_string(),
+            input: stored.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await;
+    if !result.completed_ok() {
+        tracing::warn!(
+            "Mayor: could not store the report for {}",
+            report.work_item_id
+        );
+    }
+    Ok(())
+}
+
+/// The start of the UTC day after `at`, when the next daily digest is due.
+fn next_midnight(at: DateTime<Utc>) -> DateTime<Utc> {
+    (at.date_naive() + chrono::Days::new(1))
+        .and_time(chrono::NaiveTime::MIN)
+        .and_utc()
+}
+
+/// Write and mail the digest of the day that just ended.
+async fn send_digest(ctx: &WfContext, input: &DailyDigestInput) -> anyhow::Result<()> {
+    let result = ctx
+        .activity(ActivityOptions {
+            activity_type: "daily_digest".to_string(),
+            input: input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(120)),
+            ..Default::default()
+        })
+        .await;
+    let output = result
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<DailyDigestOutput>(&p.data).ok());
+    match output {
+        Some(output) => tracing::info!("Mayor: {} ({})", output.summary, output.path),
+        None => tracing::warn!("Mayor: could not write the daily digest for {}", input.date),
+    }
+    Ok(())
+}
+
+/// The last `n` entries of `items`.
+fn tail<T>(mut items: Vec<T>, n: usize) -> Vec<T> {
+    items.drain(..items.len().saturating_sub(n));
+    items
+}
+
+fn now(ctx: &WfContext) -> DateTime<Utc> {
+    ctx.workflow_time().map(Into::into).unwrap_or_default()
+}
+
+/// Resolves at `at`, or never when there's nothing to wait for.
+async fn wait_until(ctx: &WfContext, at: Option<DateTime<Utc>>) {
+    match at {
+        Some(at) => {
+            ctx.timer((at - now(ctx)).to_std().unwrap_or(Duration::ZERO))
+                .await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Where the dispatch policy sends a pending work item.
+#[derive(Debug, Clone, PartialEq)]
+enum DispatchTarget {
+    /// An idle registered agent
+    Agent(String),
+    /// A new polecat on this rig
+    Rig(String),
+}
+
+/// Hand pending work items to idle crew agents and to polecats on the
+/// policy's rigs, assigning each item to whoever takes it.
+async fn auto_dispatch(
+    ctx: &WfContext,
+    policy: &DispatchPolicy,
+    agents: &mut [MayorAgentEntry],
+) -> anyhow::Result<()> {
+    let candidates = ctx
+        .activity(ActivityOptions {
+            activity_type: "dispatch_candidates".to_string(),
+            input: policy.rig_targets().as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(120)),
+            ..Default::default()
+        })
+        .await
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<DispatchCandidates>(&p.data).ok());
+    let Some(candidates) = candidates else {
+        tracing::warn!("Mayor: could not list pending work to dispatch");
+        return Ok(());
+    };
+
+    let idle: Vec<String> = agents
+        .iter()
+        .filter(|a| {
+            a.role == gtr_core::state::roles::CREW && a.status == "idle" && a.current_work.is_none()
+        })
+        .map(|a| a.agent_id.clone())
+        .collect();
+    let plan = plan_dispatch(policy, &candidates.pending, idle, candidates.rig_slots);
+    for (item, target) in plan {
+        let work_item_id = item.id.0.as_str();
+        let agent_id = match target {
+            DispatchTarget::Agent(agent_id) => {
+                let assign = AgentAssignSignal {
+                    work_item_id: work_item_id.to_string(),
+                    title: item.title.clone(),
+                };
+                let opts = SignalWorkflowOptions::new(
+                    agent_id.as_str(),
+                    "",
+                    SIGNAL_AGENT_ASSIGN,
+                    vec![assign.as_json_payload()?],
+                );
+                if ctx.signal_workflow(opts).await.is_err() {
+                    tracing::warn!("Mayor: could not dispatch {work_item_id} to {agent_id}");
+                    continue;
+                }
+                if let Some(agent) = agents.iter_mut().find(|a| a.agent_id == agent_id) {
+                    agent.status = "working".to_string();
+                    agent.current_work = Some(work_item_id.to_string());
+                }
+                agent_id
+            }
+            DispatchTarget::Rig(rig) => {
+                // Named after the item, so a replay starts the same polecat
+                let start = work_item_id.len().saturating_sub(8);
+                let suffix = work_item_id.get(start..).unwrap_or(work_item_id);
+                let name = format!("auto-{suffix}");
+                let polecat_id = gtr_core::state::polecat_workflow_id(&rig, &name);
+                let started = ctx
+                    .child_workflow(ChildWorkflowOptions {
+                        workflow_id: polecat_id.clone(),
+                        workflow_type: "polecat_wf".to_string(),
+                        input: vec![(
+                            name.as_str(),
+                            rig.as_str(),
+                            work_item_id,
+                            item.title.as_str(),
+                            policy.runtime.as_str(),
+                        )
+                            .as_json_payload()?],
+                        // Outlives this run when the mayor continues as new
+                        parent_close_policy: ParentClosePolicy::Abandon,
+                        ..Default::default()
+                    })
+                    .start(ctx)
+                    .await
+                    .into_started()
+                    .is_some();
+                if !started {
+                    tracing::warn!("Mayor: could not sling {work_item_id} to {polecat_id}");
+                    continue;
+                }
+                polecat_id
+            }
+        };
+        let assign = AssignSignal {
+            agent_id: agent_id.clone(),
+        };
+        let opts = SignalWorkflowOptions::new(
+            work_item_id,
+            "",
+            SIGNAL_ASSIGN,
+            vec![assign.as_json_payload()?],
+        );
+        let _ = ctx.signal_workflow(opts).await;
+        tracing::info!("Mayor: dispatched {work_item_id} → {agent_id}");
+    }
+    Ok(())
+}
+
+/// Match `pending` items, in order, to the first target with room: an idle
+/// agent, or a rig with a free polecat slot. Targets come from the item's
+/// labels (see [`DispatchPolicy::targets_for`]), else any idle agent then the
+/// policy's rigs; an item with a rig only goes to that rig or its agents.
+fn plan_dispatch<'a>(
+    policy: &DispatchPolicy,
+    pending: &'a [WorkItem],
+    mut idle: Vec<String>,
+    mut rig_slots: BTreeMap<String, usize>,
+) -> Vec<(&'a WorkItem, DispatchTarget)> {
+    let mut plan = Vec::new();
+    for item in pending {
+        let targets: Vec<String> = match policy.targets_for(&item.labels) {
+            Some(targets) => targets.into_iter().map(String::from).collect(),
+            None => idle.iter().chain(&policy.rigs).cloned().collect(),
+        };
+        let on_item_rig = |target: &String| match &item.rig {
+            None => true,
+            Some(rig) => {
+                target == rig
+                    || WorkflowId::parse(target).is_some_and(|id| id.rig() == Some(rig.as_str()))
+            }
+        };
+        for target in targets.iter().filter(|t| on_item_rig(t)) {
+            if let Some(i) = idle.iter().position(|agent| agent == target) {
+                plan.push((item, DispatchTarget::Agent(idle.remove(i))));
+                break;
+            }
+            if let Some(slots) = rig_slots.get_mut(target).filter(|slots| **slots > 0) {
+                *slots -= 1;
+                plan.push((item, DispatchTarget::Rig(target.clone())));
+                break;
+            }
+        }
+    }
+    plan
+}
+
+/// An event the mayor's policy rules may respond to.
+struct PolicyTrigger<'a> {
+    on: PolicyEvent,
+    work_item_id: &'a str,
+    title: &'a str,
+    status: &'a str,
+    exit_reason: &'a str,
+    level: u32,
+    /// The latest polecat report for the work item, which a respawn reuses
+    report: Option<&'a PolecatReportSignal>,
+}
+
+impl PolicyTrigger<'_> {
+    fn describe(&self) -> String {
+        match (self.on, self.report) {
+            (PolicyEvent::PolecatReport, Some(report)) => format!(
+                "polecat {} on {} ended {} ({})",
+                report.polecat_id, self.work_item_id, self.status, self.exit_reason
+            ),
+            _ => format!(
+                "{} ({}) escalated to L{}",
+                self.work_item_id, self.title, self.level
+            ),
+        }
+    }
+}
+
+/// Apply the first `mayor_policy.toml` rule matching `trigger`, counting it
+/// against the work item so a rule with `max_times` gives way to the next.
+/// Polecats and gates it starts are abandoned rather than terminated when the
+/// mayor continues as new.
+async fn respond(
+    ctx: &WfContext,
+    trigger: PolicyTrigger<'_>,
+    hits: &mut BTreeMap<String, u32>,
+) -> anyhow::Result<()> {
+    let policy = ctx
+        .activity(ActivityOptions {
+            activity_type: "load_mayor_policy".to_string(),
+            input: ().as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(10)),
+            ..Default::default()
+        })
+        .await
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<MayorPolicy>(&p.data).ok())
+        .unwrap_or_default();
+    let key = |i: usize| format!("{i}:{}", trigger.work_item_id);
+    let Some((i, rule)) = policy.rule_for(
+        trigger.on,
+        trigger.status,
+        trigger.exit_reason,
+        trigger.level,
+        |i| hits.get(&key(i)).copied().unwrap_or_default(),
+    ) else {
+        return Ok(());
+    };
+    let applied = hits.entry(key(i)).or_default();
+    *applied += 1;
+    let applied = *applied;
+    let work_item_id = trigger.work_item_id;
+    tracing::info!(
+        "Mayor: policy rule {} ({:?}) for {work_item_id} — {}",
+        i + 1,
+        rule.action,
+        trigger.describe()
+    );
+
+    match rule.action {
+        PolicyAction::Respawn => {
+            let Some(report) = trigger.report else {
+                tracing::warn!("Mayor: no polecat to respawn for {work_item_id}");
+                return Ok(());
+            };
+            let name = format!("{}-r{applied}", report.name);
+            let polecat_id = gtr_core::state::polecat_workflow_id(&report.rig, &name);
+            let runtime = rule.runtime.as_deref().unwrap_or("claude");
+            let started = ctx
+                .child_workflow(ChildWorkflowOptions {
+                    workflow_id: polecat_id.clone(),
+                    workflow_type: "polecat_wf".to_string(),
+                    input: vec![(
+                        name.as_str(),
+                        report.rig.as_str(),
+                        work_item_id,
+                        trigger.title,
+                        runtime,
+                    )
+                        .as_json_payload()?],
+                    parent_close_policy: ParentClosePolicy::Abandon,
+                    ..Default::default()
+                })
+                .start(ctx)
+                .await
+                .into_started()
+                .is_some();
+            if started {
+                tracing::info!("Mayor: respawned {work_item_id} as {polecat_id}");
+            } else {
+                tracing::warn!("Mayor: could not respawn {work_item_id} as {polecat_id}");
+            }
+        }
+        PolicyAction::HumanGate => {
+            let gate_id = format!(
+                "gate-human-{}-{applied}",
+                work_item_id.trim_start_matches("wi-")
+            );
+            let gate_type = GateType::Human {
+                description: trigger.describe(),
+            };
+            let started = ctx
+                .child_workflow(ChildWorkflowOptions {
+                    workflow_id: gate_id.clone(),
+                    workflow_type: "gate_wf".to_string(),
+                    input: vec![
+                        (gate_id.as_str(), &gate_type, Some(work_item_id)).as_json_payload()?
+                    ],
+                    parent_close_policy: ParentClosePolicy::Abandon,
+                    ..Default::default()
+                })
+                .start(ctx)
+                .await
+                .into_started()
+                .is_some();
+            if started {
+                let message = format!(
+                    "{}. {work_item_id} is parked on {gate_id}: `rgt gate approve {gate_id}` \
+                     or `rgt gate close {gate_id}`.",
+                    trigger.describe()
+                );
+                mail_human(ctx, &format!("ml-policy-{gate_id}"), message).await?;
+            } else {
+                tracing::warn!("Mayor: could not open {gate_id} for {work_item_id}");
+            }
+        }
+        PolicyAction::Notify => {
+            let id = format!("ml-policy-{i}-{work_item_id}-{applied}");
+            mail_human(ctx, &id, trigger.describe()).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Mail the human operator from the mayor. `id` keeps a replayed or retried
+/// delivery from landing twice.
+async fn mail_human(ctx: &WfContext, id: &str, message: String) -> anyhow::Result<()> {
+    let mail = AgentMailSignal {
+        from: gtr_core::state::mayor_workflow_id(),
+        message,
+        ..Default::default()
+    }
+    .to_mail(id.to_string(), HUMAN, now(ctx));
+    let stored = ctx
+        .activity(ActivityOptions {
+            activity_type: "mail_human".to_string(),
+            input: mail.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await;
+    if !stored.completed_ok() {
+        tracing::warn!("Mayor: could not mail {HUMAN} ({id})");
+    }
+    Ok(())
+}
+
+/// Copy mail to the deputy's inbox while it covers for the mayor agent. The
+/// mail keeps the ID it was stored under, so a replay doesn't copy it twice.
+async fn forward_to_deputy(ctx: &WfContext, mail: AgentMailSignal) -> anyhow::Result<()> {
+    let opts = SignalWorkflowOptions::new(
+        gtr_core::state::deputy_workflow_id(),
+        "",
+        SIGNAL_AGENT_MAIL,
+        vec![mail.as_json_payload()?],
+    );
+    if ctx.signal_workflow(opts).await.is_err() {
+        tracing::warn!("Mayor: could not forward mail to the deputy");
+    }
+    Ok(())
+}
+
+/// Send `agent_id` the mail that bounced while it wasn't running.
+async fn redeliver_dead_letters(ctx: &WfContext, agent_id: &str) -> anyhow::Result<()> {
+    let result = ctx
+        .activity(ActivityOptions {
+            activity_type: "redeliver_dead_letters".to_string(),
+            input: agent_id.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(60)),
+            // Don't hold up the mayor's signals; the next registration tries again
+            retry_policy: Some(RetryPolicy {
+                maximum_attempts: 3,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await;
+    let delivered = result
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<usize>(&p.data).ok());
+    match delivered {
+        Some(0) => {}
+        Some(n) => tracing::info!("Mayor: redelivered {n} dead-lettered message(s) to {agent_id}"),
+        None => tracing::warn!("Mayor: could not redeliver dead-lettered mail to {agent_id}"),
+    }
+    Ok(())
+}
+
+/// Act on mail the mayor's routes match: file it as a work item, or approve
+/// the gate it names. Only agents registered with the mayor can approve a
+/// gate by mail. Unmatched mail is left for the mayor agent to read.
+async fn route_mail(
+    ctx: &WfContext,
+    mail: &Mail,
+    agents: &[MayorAgentEntry],
+) -> anyhow::Result<()> {
+    let action = ctx
+        .activity(ActivityOptions {
+            activity_type: "route_mail".to_string(),
+            input: mail.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(10)),
+            ..Default::default()
+        })
+        .await
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<Option<MailRouteAction>>(&p.data).ok())
+        .flatten();
+
+    match action {
+        None => {}
+        Some(MailRouteAction::WorkItem) => {
+            // Named after the mail, so a redelivered message isn't filed twice
+            let work_item_id = format!("wi-{}", mail.id.trim_start_matches("ml-"));
+            let first_line = mail.message.lines().next().unwrap_or_default();
+            let title = format!("{} (mail from {})", first_line, mail.from);
+            let started = ctx
+                .child_workflow(ChildWorkflowOptions {
+                    workflow_id: work_item_id.clone(),
+                    workflow_type: "work_item_wf".to_string(),
+                    input: vec![(work_item_id.as_str(), title.as_str()).as_json_payload()?],
+                    // Outlives this run when the mayor continues as new
+                    parent_close_policy: ParentClosePolicy::Abandon,
+                    ..Default::default()
+                })
+                .start(ctx)
+                .await
+                .into_started()
+                .is_some();
+            if started {
+                tracing::info!(
+                    "Mayor: filed mail {} from {} as {work_item_id}",
+                    mail.id,
+                    mail.from
+                );
+            }
         }
+        Some(MailRouteAction::ApproveGate) => {
+            if !agents.iter().any(|a| a.agent_id == mail.from) {
+                tracing::warn!(
+                    "Mayor: mail {} would approve a gate, but {} isn't a registered agent",
+                    mail.id,
+                    mail.from
+                );
+                return Ok(());
+            }
+            let Some(gate_id) = mail.mentioned_gate() else {
+                tracing::warn!(
+                    "Mayor: mail {} matched a gate route but names no gate",
+                    mail.id
+                );
+                return Ok(());
+            };
+            let opts = SignalWorkflowOptions::new(gate_id, "", SIGNAL_GATE_APPROVE, vec![]);
+            if ctx.signal_workflow(opts).await.is_ok() {
+                tracing::info!(
+                    "Mayor: approved {gate_id} from mail {} by {}",
+                    mail.id,
+                    mail.from
+                );
+            } else {
+                tracing::warn!(
+                    "Mayor: mail {} approves {gate_id}, which isn't waiting",
+                    mail.id
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_dispatch_matches_labels_rigs_and_capacity() {
+        let policy = DispatchPolicy {
+            enabled: true,
+            rigs: vec!["api".into()],
+            labels: BTreeMap::from([(
+                "frontend".to_string(),
+                vec!["webapp".to_string(), "webapp-crew-alice".to_string()],
+            )]),
+            ..Default::default()
+        };
+        let item = |id: &str, labels: &[&str], rig: Option<&str>| {
+            let mut item = WorkItem::new(id, id);
+            item.labels = labels.iter().map(|l| l.to_string()).collect();
+            item.rig = rig.map(String::from);
+            item
+        };
+        let pending = vec![
+            item("wi-1", &["frontend"], None),
+            item("wi-2", &["frontend"], None),
+            item("wi-3", &["frontend"], None),
+            item("wi-4", &[], Some("api")),
+            item("wi-5", &[], None),
+        ];
+        let idle = vec!["webapp-crew-alice".to_string(), "api-crew-bob".to_string()];
+        let slots = BTreeMap::from([("webapp".to_string(), 1), ("api".to_string(), 5)]);
+
+        let plan: Vec<(&str, DispatchTarget)> = plan_dispatch(&policy, &pending, idle, slots)
+            .into_iter()
+            .map(|(item, target)| (item.id.0.as_str(), target))
+            .collect();
+        assert_eq!(
+            plan,
+            vec![
+                ("wi-1", DispatchTarget::Rig("webapp".into())),
+                ("wi-2", DispatchTarget::Agent("webapp-crew-alice".into())),
+                ("wi-4", DispatchTarget::Agent("api-crew-bob".into())),
+                ("wi-5", DispatchTarget::Rig("api".into())),
+            ]
+        );
     }
 }
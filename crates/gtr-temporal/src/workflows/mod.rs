@@ -1,6 +1,8 @@
 pub mod agent;
+pub mod approval;
 pub mod boot;
 pub mod convoy;
+pub mod deputy;
 pub mod dog;
 pub mod formula;
 pub mod gate;
@@ -10,5 +12,22 @@ pub mod patrol;
 pub mod polecat;
 pub mod refinery;
 pub mod rig;
+pub mod triage;
 pub mod witness;
 pub mod work_item;
+
+use std::time::Duration;
+
+use temporalio_sdk::WfContext;
+
+/// Waits `duration`, or forever when it's `None`. Meant to be pinned outside
+/// a `select!` loop and replaced only when it fires, so the other arms don't
+/// start a new timer on every pass.
+pub(crate) async fn timer_or_never(ctx: &WfContext, duration: Option<Duration>) {
+    match duration {
+        Some(duration) => {
+            ctx.timer(duration).await;
+        }
+        None => std::future::pending().await,
+    }
+}
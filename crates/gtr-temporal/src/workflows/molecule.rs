@@ -1,11 +1,80 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Poll;
+use std::time::Duration;
+
 use futures_util::StreamExt;
-use temporalio_sdk::{WfContext, WfExitValue};
+use gtr_core::formula::{
+    evaluate_condition, interpolate, FormulaDef, FormulaStep, OnFailure, StepKind,
+    DEFAULT_MAX_PARALLEL,
+};
+use temporalio_common::protos::coresdk::child_workflow::child_workflow_result;
+use temporalio_common::protos::coresdk::AsJsonPayloadExt;
+use temporalio_common::protos::temporal::api::common::v1::RetryPolicy;
+use temporalio_sdk::{
+    ActivityOptions, ChildWorkflowOptions, SignalWorkflowOptions, WfContext, WfExitValue,
+};
 
+use crate::activities::heartbeat::HeartbeatInput;
+use crate::activities::run_plugin::{RunPluginInput, RunPluginOutput};
+use crate::activities::spawn_agent::SpawnAgentInput;
 use crate::signals::*;
+use crate::workflows::formula::FormulaInput;
+use crate::workflows::timer_or_never;
+
+/// A step the executor is running, and its outcome once it finishes.
+type StepRun<'a> = Pin<Box<dyn Future<Output = Result<String, String>> + Send + 'a>>;
+
+/// The formula a molecule is executing.
+struct Execution {
+    def: FormulaDef,
+    vars: HashMap<String, String>,
+    /// Steps allowed to run at once
+    max_parallel: usize,
+    /// Formulas that `formula` steps run, by name
+    formulas: HashMap<String, String>,
+}
+
+impl Execution {
+    fn on_failure(&self, step: &str) -> OnFailure {
+        self.def
+            .steps
+            .iter()
+            .find(|s| s.name == step)
+            .map(|s| s.on_failure.clone())
+            .unwrap_or_default()
+    }
+}
 
 /// Molecule workflow — an instantiated formula with step-by-step tracking.
 /// Tracks which steps are complete, in-progress, and what's next.
 /// Advances on `mol_step_done` signals, pauses/resumes/cancels on signals.
+/// While paused nothing new starts, but agent steps already handed out
+/// still report their results.
+///
+/// Started with the formula itself (a `FormulaInput`) as a second argument,
+/// the molecule executes its steps instead: each step's command runs via the
+/// `run_plugin` activity once every step it `depends_on` is done, and is
+/// marked done or failed by its exit code. A step that `register`s a name
+/// hands its stdout to later steps as that `{{var}}`. An `agent` step's
+/// prompt is mailed to its `agent`, or given to an agent spawned for the step,
+/// and the step waits for `mol_step_done` (or `mol_step_fail`) from it. A
+/// `formula` step runs its formula as a child molecule, `<molecule>-<step>`,
+/// and succeeds if that completes. Independent steps run side by side,
+/// up to `max_parallel` at a time. A step whose `when` condition doesn't hold
+/// is skipped, which counts as done for the steps after it. A failing step is
+/// retried `retries` times, each attempt limited to `timeout_secs` (300 for a
+/// command; agent and formula steps get no limit unless it's set), then
+/// handled by its `on_failure`: `abort` fails the molecule, `continue`
+/// carries on as if it had succeeded, and `goto:<step>` runs that step next
+/// along with everything after it (once per failing step; a second failure
+/// aborts). Steps left waiting on a failure are marked blocked. Pausing lets
+/// running steps finish but starts no new ones.
+///
+/// A `MoleculeResume` third argument restarts an executing molecule that
+/// stopped: its steps start as the earlier run left them, with `from`, the
+/// steps after it and any unfinished step back to pending.
 pub async fn molecule_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error> {
     let args = ctx.get_args();
     let (id, formula_name, step_names) = if let Some(payload) = args.first() {
@@ -14,6 +83,25 @@ pub async fn molecule_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
     } else {
         ("unknown".into(), "unknown".into(), vec![])
     };
+    let execution = match args.get(1) {
+        Some(payload) => {
+            let input: FormulaInput = serde_json::from_slice(&payload.data)?;
+            let def = FormulaDef::from_toml(&input.formula_toml)?;
+            def.validate()?;
+            let max_parallel = input
+                .max_parallel
+                .or(def.max_parallel)
+                .unwrap_or(DEFAULT_MAX_PARALLEL)
+                .max(1);
+            Some(Execution {
+                vars: def.with_defaults(&input.vars),
+                def,
+                max_parallel,
+                formulas: input.formulas,
+            })
+        }
+        None => None,
+    };
 
     let mut steps: Vec<MolStepState> = step_names
         .iter()
@@ -24,16 +112,34 @@ pub async fn molecule_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
             output: None,
         })
         .collect();
+    if let (Some(exec), Some(payload)) = (&execution, args.get(2)) {
+        let resume: MoleculeResume = serde_json::from_slice(&payload.data)?;
+        tracing::info!("Molecule {id} resuming from step {}", resume.from);
+        resume_steps(&exec.def, &mut steps, &resume);
+    }
 
     let mut status = "running".to_string();
-    let mut current_step: Option<String> = steps.first().map(|s| s.ref_id.clone());
+    let mut current_step: Option<String> = None;
 
-    // Mark first step as in_progress
-    if let Some(first) = steps.first_mut() {
-        first.status = "in_progress".to_string();
+    // Mark first step as in_progress; the executor marks steps as it starts them
+    if execution.is_none() {
+        if let Some(first) = steps.first_mut() {
+            first.status = "in_progress".to_string();
+            current_step = Some(first.ref_id.clone());
+        }
     }
 
     tracing::info!("Molecule {id} started — formula {formula_name} ({} steps)", steps.len());
+    let mut running: Vec<(String, StepRun)> = Vec::new();
+    // Agent steps waiting to be marked done, with the agent spawned for each,
+    // their `timeout_secs` timers, and how many times each has started
+    let mut agent_steps: Vec<(String, Option<String>)> = Vec::new();
+    let mut agent_timers: Vec<(String, StepRun)> = Vec::new();
+    let mut agent_attempts: HashMap<String, u32> = HashMap::new();
+    // Steps to run next because of an `on_failure = "goto:..."`, and the
+    // steps whose goto has been followed
+    let mut jumps: VecDeque<String> = VecDeque::new();
+    let mut jumped: HashSet<String> = HashSet::new();
 
     let mut step_done_ch = ctx.make_signal_channel(SIGNAL_MOL_STEP_DONE);
     let mut step_fail_ch = ctx.make_signal_channel(SIGNAL_MOL_STEP_FAIL);
@@ -43,8 +149,17 @@ pub async fn molecule_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
 
     loop {
         // Check if all steps are done
-        if steps.iter().all(|s| s.status == "done" || s.status == "failed") {
-            let all_done = steps.iter().all(|s| s.status == "done");
+        if steps
+            .iter()
+            .all(|s| matches!(s.status.as_str(), "done" | "failed" | "skipped" | "blocked"))
+        {
+            let all_done = steps.iter().all(|s| match s.status.as_str() {
+                "done" | "skipped" => true,
+                "failed" => execution
+                    .as_ref()
+                    .is_some_and(|e| e.on_failure(&s.ref_id) == OnFailure::Continue),
+                _ => false,
+            });
             status = if all_done {
                 "completed".to_string()
             } else {
@@ -54,6 +169,84 @@ pub async fn molecule_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
             break;
         }
 
+        if let Some(exec) = &execution {
+            while status == "running" && running.len() + agent_steps.len() < exec.max_parallel {
+                let Some(step) = next_step(&exec.def, &steps, &mut jumps) else {
+                    break;
+                };
+                let Some(state) = steps.iter().position(|s| s.ref_id == step.name) else {
+                    break;
+                };
+                let vars = step_vars(exec, &steps);
+                if let Some(when) = &step.when {
+                    match evaluate_condition(when, &vars) {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            tracing::info!("Molecule {id}: skipping step {} — {when}", step.name);
+                            steps[state].status = "skipped".to_string();
+                            continue;
+                        }
+                        Err(e) => {
+                            tracing::warn!(
+                                "Molecule {id}: step {} condition failed — {e}",
+                                step.name
+                            );
+                            steps[state].status = "failed".to_string();
+                            steps[state].output = Some(format!("condition `{when}`: {e}"));
+                            status = "failed".to_string();
+                            break;
+                        }
+                    }
+                }
+                steps[state].status = "in_progress".to_string();
+                current_step = Some(step.name.clone());
+                tracing::info!("Molecule {id}: running step {}", step.name);
+                if step.kind != StepKind::Agent {
+                    let run = run_step(&ctx, &id, exec, step, vars);
+                    running.push((step.name.clone(), Box::pin(run)));
+                    continue;
+                }
+                *agent_attempts.entry(step.name.clone()).or_default() += 1;
+                match start_agent_step(&ctx, &id, step, &vars).await? {
+                    Ok(spawned) => {
+                        agent_steps.push((step.name.clone(), spawned));
+                        if let Some(secs) = step.timeout_secs {
+                            let timer = ctx.timer(Duration::from_secs(secs));
+                            let timeout: StepRun = Box::pin(async move {
+                                timer.await;
+                                Err(format!("timed out after {secs}s"))
+                            });
+                            agent_timers.push((step.name.clone(), timeout));
+                        }
+                    }
+                    Err(reason) => {
+                        if !fail_agent_step(
+                            exec,
+                            &step.name,
+                            reason,
+                            &mut steps,
+                            &mut agent_attempts,
+                            &mut jumps,
+                            &mut jumped,
+                        ) {
+                            status = "failed".to_string();
+                            break;
+                        }
+                    }
+                }
+            }
+            if status == "failed" {
+                break;
+            }
+            if status == "running" && running.is_empty() && agent_steps.is_empty() {
+                // Nothing running and nothing can start: the rest wait on a failure
+                for step in steps.iter_mut().filter(|s| s.status == "pending") {
+                    step.status = "blocked".to_string();
+                }
+                continue;
+            }
+        }
+
         tokio::select! {
             biased;
             Some(_) = cancel_ch.next() => {
@@ -74,16 +267,29 @@ pub async fn molecule_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
                 }
             }
             Some(signal) = step_done_ch.next() => {
-                if status != "running" {
-                    continue;
-                }
                 if let Some(payload) = signal.input.first() {
                     if let Ok(data) = serde_json::from_slice::<MolStepDoneSignal>(&payload.data) {
+                        // An executing molecule only takes these for its agent steps
+                        let agent_step = agent_steps.iter().position(|(s, _)| *s == data.step_ref);
+                        if execution.is_some() && agent_step.is_none() {
+                            continue;
+                        }
+                        // A spawned agent finishes its step even while paused
+                        if status != "running" && agent_step.is_none() {
+                            continue;
+                        }
                         if let Some(step) = steps.iter_mut().find(|s| s.ref_id == data.step_ref) {
                             step.status = "done".to_string();
                             step.output = data.output;
                             tracing::info!("Molecule {id}: step {} done", data.step_ref);
                         }
+                        if let Some(index) = agent_step {
+                            let (_, spawned) = agent_steps.remove(index);
+                            agent_timers.retain(|(s, _)| *s != data.step_ref);
+                            agent_attempts.remove(&data.step_ref);
+                            release_agent(&ctx, spawned).await?;
+                            continue;
+                        }
                         // Advance to next pending step
                         current_step = None;
                         for step in steps.iter_mut() {
@@ -98,11 +304,33 @@ pub async fn molecule_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
                 }
             }
             Some(signal) = step_fail_ch.next() => {
-                if status != "running" {
-                    continue;
-                }
                 if let Some(payload) = signal.input.first() {
                     if let Ok(data) = serde_json::from_slice::<MolStepFailSignal>(&payload.data) {
+                        let agent_step = agent_steps.iter().position(|(s, _)| *s == data.step_ref);
+                        if execution.is_some() && agent_step.is_none() {
+                            continue;
+                        }
+                        if status != "running" && agent_step.is_none() {
+                            continue;
+                        }
+                        if let (Some(exec), Some(index)) = (&execution, agent_step) {
+                            let (_, spawned) = agent_steps.remove(index);
+                            agent_timers.retain(|(s, _)| *s != data.step_ref);
+                            release_agent(&ctx, spawned).await?;
+                            if fail_agent_step(
+                                exec,
+                                &data.step_ref,
+                                data.reason,
+                                &mut steps,
+                                &mut agent_attempts,
+                                &mut jumps,
+                                &mut jumped,
+                            ) {
+                                continue;
+                            }
+                            status = "failed".to_string();
+                            break;
+                        }
                         if let Some(step) = steps.iter_mut().find(|s| s.ref_id == data.step_ref) {
                             step.status = "failed".to_string();
                             step.output = Some(data.reason.clone());
@@ -114,9 +342,64 @@ pub async fn molecule_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
                     }
                 }
             }
+            (index, outcome) = next_finished(&mut agent_timers), if !agent_timers.is_empty() => {
+                let (step_ref, _) = agent_timers.remove(index);
+                let (Some(exec), Err(reason)) = (&execution, outcome) else {
+                    continue;
+                };
+                if let Some(index) = agent_steps.iter().position(|(s, _)| *s == step_ref) {
+                    let (_, spawned) = agent_steps.remove(index);
+                    release_agent(&ctx, spawned).await?;
+                }
+                if !fail_agent_step(
+                    exec,
+                    &step_ref,
+                    reason,
+                    &mut steps,
+                    &mut agent_attempts,
+                    &mut jumps,
+                    &mut jumped,
+                ) {
+                    status = "failed".to_string();
+                    break;
+                }
+            }
+            (index, outcome) = next_finished(&mut running), if !running.is_empty() => {
+                let (step_ref, _) = running.remove(index);
+                current_step = running.last().map(|(name, _)| name.clone());
+                let Some(step) = steps.iter_mut().find(|s| s.ref_id == step_ref) else {
+                    continue;
+                };
+                match outcome {
+                    Ok(output) => {
+                        step.status = "done".to_string();
+                        step.output = Some(output);
+                        tracing::info!("Molecule {id}: step {step_ref} done");
+                    }
+                    Err(reason) => {
+                        step.status = "failed".to_string();
+                        step.output = Some(reason.clone());
+                        tracing::warn!("Molecule {id}: step {step_ref} failed — {reason}");
+                        let Some(exec) = &execution else {
+                            continue;
+                        };
+                        if !recover(exec, &step_ref, &mut steps, &mut jumps, &mut jumped) {
+                            status = "failed".to_string();
+                            break;
+                        }
+                    }
+                }
+            }
         }
     }
 
+    // Steps still running once the molecule stops are abandoned
+    drop(running);
+    drop(agent_timers);
+    for (_, spawned) in agent_steps {
+        release_agent(&ctx, spawned).await?;
+    }
+
     Ok(WfExitValue::Normal(serde_json::to_string(&MoleculeState {
         id,
         formula_name,
@@ -125,3 +408,674 @@ pub async fn molecule_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
         current_step,
     })?))
 }
+
+/// Apply a failed step's `on_failure`. Returns whether the molecule carries
+/// on; a goto resets its target and everything after it and queues the target
+/// to run next, once per failing step.
+fn recover(
+    exec: &Execution,
+    step_ref: &str,
+    steps: &mut [MolStepState],
+    jumps: &mut VecDeque<String>,
+    jumped: &mut HashSet<String>,
+) -> bool {
+    match exec.on_failure(step_ref) {
+        OnFailure::Abort => false,
+        OnFailure::Continue => {
+            tracing::info!("Continuing past failed step {step_ref}");
+            true
+        }
+        OnFailure::Goto(target) => {
+            if !jumped.insert(step_ref.to_string()) {
+                return false;
+            }
+            tracing::info!("Step {step_ref} failed — going to {target}");
+            for name in downstream(&exec.def, &target) {
+                if let Some(step) = steps
+                    .iter_mut()
+                    .find(|s| s.ref_id == name && s.status != "in_progress")
+                {
+                    step.status = "pending".to_string();
+                    step.output = None;
+                }
+            }
+            jumps.push_back(target);
+            true
+        }
+    }
+}
+
+/// Settle a failed agent step: back to pending to start again while it has
+/// `retries` left, otherwise failed and handed to [`recover`]. Returns whether
+/// the molecule carries on.
+fn fail_agent_step(
+    exec: &Execution,
+    step_ref: &str,
+    reason: String,
+    steps: &mut [MolStepState],
+    attempts: &mut HashMap<String, u32>,
+    jumps: &mut VecDeque<String>,
+    jumped: &mut HashSet<String>,
+) -> bool {
+    let Some(step) = steps.iter_mut().find(|s| s.ref_id == step_ref) else {
+        return true;
+    };
+    let retries = exec
+        .def
+        .steps
+        .iter()
+        .find(|s| s.name == step_ref)
+        .map_or(0, |s| s.retries);
+    let attempt = attempts.get(step_ref).copied().unwrap_or(1);
+    if attempt <= retries {
+        tracing::warn!("Step {step_ref} failed (attempt {attempt}) — retrying: {reason}");
+        step.status = "pending".to_string();
+        return true;
+    }
+    tracing::warn!("Step {step_ref} failed — {reason}");
+    attempts.remove(step_ref);
+    step.status = "failed".to_string();
+    step.output = Some(reason);
+    recover(exec, step_ref, steps, jumps, jumped)
+}
+
+/// Carry over the steps an earlier run of the molecule left behind, rerunning
+/// `resume.from` and everything downstream of it along with whatever hadn't
+/// finished.
+fn resume_steps(def: &FormulaDef, steps: &mut [MolStepState], resume: &MoleculeResume) {
+    let rerun = downstream(def, &resume.from);
+    for step in steps.iter_mut() {
+        let Some(previous) = resume.steps.iter().find(|s| s.ref_id == step.ref_id) else {
+            continue;
+        };
+        let finished = matches!(previous.status.as_str(), "done" | "skipped");
+        if !finished || rerun.contains(&step.ref_id) {
+            continue;
+        }
+        step.status = previous.status.clone();
+        step.output = previous.output.clone();
+    }
+}
+
+/// The step to start next: the first goto target in `jumps` that isn't still
+/// running, taken off the queue, or else the next ready step. A target that's
+/// running stays queued until it settles, so it never runs twice at once.
+fn next_step<'a>(
+    def: &'a FormulaDef,
+    steps: &[MolStepState],
+    jumps: &mut VecDeque<String>,
+) -> Option<&'a FormulaStep> {
+    let running = |name: &str| {
+        steps
+            .iter()
+            .any(|s| s.ref_id == name && s.status == "in_progress")
+    };
+    let jump = jumps
+        .iter()
+        .position(|target| !running(target))
+        .and_then(|index| jumps.remove(index))
+        .and_then(|target| def.steps.iter().find(|s| s.name == target));
+    jump.or_else(|| next_ready(def, steps))
+}
+
+/// The first pending step, in formula order, whose dependencies are all done,
+/// skipped, or failed with `on_failure = "continue"`.
+fn next_ready<'a>(def: &'a FormulaDef, steps: &[MolStepState]) -> Option<&'a FormulaStep> {
+    let status = |name: &str| {
+        steps
+            .iter()
+            .find(|s| s.ref_id == name)
+            .map(|s| s.status.as_str())
+    };
+    let satisfied = |dep: &str| match status(dep) {
+        Some("done" | "skipped") => true,
+        Some("failed") => def
+            .steps
+            .iter()
+            .any(|s| s.name == dep && s.on_failure == OnFailure::Continue),
+        _ => false,
+    };
+    def.steps.iter().find(|step| {
+        status(&step.name) == Some("pending") && step.depends_on.iter().all(|dep| satisfied(dep))
+    })
+}
+
+/// `from` and every step that depends on it, directly or not.
+fn downstream(def: &FormulaDef, from: &str) -> HashSet<String> {
+    let mut found = HashSet::from([from.to_string()]);
+    loop {
+        let before = found.len();
+        for step in &def.steps {
+            if step.depends_on.iter().any(|dep| found.contains(dep)) {
+                found.insert(step.name.clone());
+            }
+        }
+        if found.len() == before {
+            return found;
+        }
+    }
+}
+
+/// The vars a step starts with: the formula's, each finished step's stdout
+/// under the name it `register`s, and `<step>.status` and `<step>.output` for
+/// each step so far.
+fn step_vars(exec: &Execution, steps: &[MolStepState]) -> HashMap<String, String> {
+    let mut all = exec.vars.clone();
+    for step in steps.iter().filter(|s| s.status != "pending") {
+        all.insert(format!("{}.status", step.ref_id), step.status.clone());
+        let Some(output) = &step.output else {
+            continue;
+        };
+        all.insert(format!("{}.output", step.ref_id), output.clone());
+        let register = exec
+            .def
+            .steps
+            .iter()
+            .find(|s| s.name == step.ref_id)
+            .and_then(|s| s.register.as_ref());
+        if let (Some(name), "done") = (register, step.status.as_str()) {
+            all.insert(name.clone(), output.trim().to_string());
+        }
+    }
+    all
+}
+
+/// Wait for whichever running step finishes first, returning its index.
+async fn next_finished(running: &mut [(String, StepRun<'_>)]) -> (usize, Result<String, String>) {
+    std::future::poll_fn(|cx| {
+        for (index, (_, run)) in running.iter_mut().enumerate() {
+            if let Poll::Ready(outcome) = run.as_mut().poll(cx) {
+                return Poll::Ready((index, outcome));
+            }
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+/// Hand an agent step's prompt to its agent, or spawn one for it. Returns the
+/// spawned agent's ID, or why the step couldn't start.
+async fn start_agent_step(
+    ctx: &WfContext,
+    molecule_id: &str,
+    step: &FormulaStep,
+    vars: &HashMap<String, String>,
+) -> anyhow::Result<Result<Option<String>, String>> {
+    let prompt = interpolate(step.prompt.as_deref().unwrap_or_default(), vars);
+    let message = format!(
+        "Formula step '{step_name}' of molecule {molecule_id}:\n\n{prompt}\n\n\
+         When it's finished, run:\n\
+         $RGT_BIN mol step-done {molecule_id} {step_name} --output \"<what you did>\"",
+        step_name = step.name
+    );
+
+    if let Some(agent) = &step.agent {
+        let mail = AgentMailSignal {
+            from: molecule_id.to_string(),
+            message,
+            ..Default::default()
+        };
+        let opts = SignalWorkflowOptions::new(
+            agent.as_str(),
+            "",
+            SIGNAL_AGENT_MAIL,
+            vec![mail.as_json_payload()?],
+        );
+        return Ok(match ctx.signal_workflow(opts).await {
+            Ok(_) => Ok(None),
+            Err(_) => Err(format!("could not reach agent {agent}")),
+        });
+    }
+
+    let agent_id = format!("{molecule_id}-{}", step.name);
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+    let input = SpawnAgentInput {
+        agent_id: agent_id.clone(),
+        runtime: "claude".to_string(),
+        work_dir: step
+            .work_dir
+            .clone()
+            .unwrap_or_else(|| format!("{home}/.gtr")),
+        role: gtr_core::state::roles::POLECAT.to_string(),
+        rig: None,
+        initial_prompt: Some(message),
+        env_extra: None,
+        resume_session_id: None,
+        limits: None,
+        model: None,
+        permission_mode: None,
+        allowed_tools: None,
+    };
+    let result = ctx
+        .activity(ActivityOptions {
+            activity_type: "spawn_agent".to_string(),
+            input: input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await;
+    Ok(if result.completed_ok() {
+        Ok(Some(agent_id))
+    } else {
+        Err(format!("could not spawn agent {agent_id}"))
+    })
+}
+
+/// Kill the agent spawned for an agent step, if there was one.
+async fn release_agent(ctx: &WfContext, spawned: Option<String>) -> anyhow::Result<()> {
+    let Some(agent_id) = spawned else {
+        return Ok(());
+    };
+    let _ = ctx
+        .activity(ActivityOptions {
+            activity_type: "kill_agent".to_string(),
+            input: HeartbeatInput { agent_id }.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await;
+    Ok(())
+}
+
+/// Run a step's command or formula, retrying it up to `retries` times, and
+/// return its output or why the last attempt failed.
+async fn run_step(
+    ctx: &WfContext,
+    molecule_id: &str,
+    exec: &Execution,
+    step: &FormulaStep,
+    vars: HashMap<String, String>,
+) -> Result<String, String> {
+    let input = RunPluginInput {
+        plugin_name: format!("{}:{}", exec.def.name, step.name),
+        command: interpolate(&step.command, &vars),
+        args: step.args.iter().map(|a| interpolate(a, &vars)).collect(),
+        work_dir: step.work_dir.clone(),
+        env: None,
+    };
+    let timeout = Duration::from_secs(step.timeout_secs.unwrap_or(300));
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let outcome = match step.kind {
+            StepKind::Formula => {
+                let timeout = step.timeout_secs.map(Duration::from_secs);
+                run_child(ctx, molecule_id, exec, step, &vars, timeout).await
+            }
+            _ => run_attempt(ctx, &input, timeout).await,
+        };
+        match outcome {
+            Err(reason) if attempt <= step.retries => tracing::warn!(
+                "Step {} failed (attempt {attempt}) — retrying: {reason}",
+                input.plugin_name
+            ),
+            outcome => return outcome,
+        }
+    }
+}
+
+/// Run a `formula` step's formula as a child molecule, `<molecule>-<step>`,
+/// with the step's vars, and wait for it to finish. One still running after
+/// `timeout` is cancelled.
+async fn run_child(
+    ctx: &WfContext,
+    molecule_id: &str,
+    exec: &Execution,
+    step: &FormulaStep,
+    vars: &HashMap<String, String>,
+    timeout: Option<Duration>,
+) -> Result<String, String> {
+    let name = step.formula.as_deref().unwrap_or_default();
+    let formula_toml = exec
+        .formulas
+        .get(name)
+        .ok_or_else(|| format!("formula {name} wasn't loaded with the molecule"))?;
+    let def = FormulaDef::from_toml(formula_toml).map_err(|e| e.to_string())?;
+    let child_id = format!("{molecule_id}-{}", step.name);
+    let step_names: Vec<String> = def.steps.iter().map(|s| s.name.clone()).collect();
+    let input = FormulaInput {
+        formula_toml: formula_toml.clone(),
+        vars: vars.clone(),
+        max_parallel: None,
+        formulas: exec.formulas.clone(),
+    };
+    let payloads = vec![
+        (child_id.as_str(), def.name.as_str(), &step_names)
+            .as_json_payload()
+            .map_err(|e| e.to_string())?,
+        input.as_json_payload().map_err(|e| e.to_string())?,
+    ];
+
+    let started = ctx
+        .child_workflow(ChildWorkflowOptions {
+            workflow_id: child_id.clone(),
+            workflow_type: "molecule_wf".to_string(),
+            input: payloads,
+            ..Default::default()
+        })
+        .start(ctx)
+        .await
+        .into_started()
+        .ok_or_else(|| format!("could not start molecule {child_id}"))?;
+    let mut result = std::pin::pin!(started.result());
+    let result = tokio::select! {
+        result = &mut result => result,
+        _ = timer_or_never(ctx, timeout) => {
+            // Let the child wind down before a retry starts it again under its ID
+            let opts =
+                SignalWorkflowOptions::new(child_id.as_str(), "", SIGNAL_MOL_CANCEL, Vec::new());
+            let _ = ctx.signal_workflow(opts).await;
+            result.await;
+            let secs = timeout.unwrap_or_default().as_secs();
+            return Err(format!("molecule {child_id} timed out after {secs}s"));
+        }
+    };
+    let state = match result.status {
+        Some(child_workflow_result::Status::Completed(success)) => success
+            .result
+            .and_then(|p| serde_json::from_slice::<String>(&p.data).ok())
+            .and_then(|result| serde_json::from_str::<MoleculeState>(&result).ok())
+            .ok_or_else(|| format!("molecule {child_id} returned no state"))?,
+        _ => return Err(format!("molecule {child_id} didn't finish")),
+    };
+    if state.status == "completed" {
+        Ok(format!("molecule {child_id} completed"))
+    } else {
+        Err(format!("molecule {child_id} {}", state.status))
+    }
+}
+
+async fn run_attempt(
+    ctx: &WfContext,
+    input: &RunPluginInput,
+    timeout: Duration,
+) -> Result<String, String> {
+    let payload = input.as_json_payload().map_err(|e| e.to_string())?;
+    let result = ctx
+        .activity(ActivityOptions {
+            activity_type: "run_plugin".to_string(),
+            input: payload,
+            start_to_close_timeout: Some(timeout),
+            // Attempts are counted here, against the step's `retries`
+            retry_policy: Some(RetryPolicy {
+                maximum_attempts: 1,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await;
+    let output = result
+        .success_payload_or_error()
+        .map_err(|e| e.to_string())?
+        .and_then(|p| serde_json::from_slice::<RunPluginOutput>(&p.data).ok())
+        .ok_or_else(|| "no output from run_plugin".to_string())?;
+    match output.exit_code {
+        Some(0) => Ok(output.stdout.join("\n")),
+        Some(code) => Err(format!("exited with code {code}")),
+        None => Err("killed by a signal".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(name: &str, status: &str) -> MolStepState {
+        MolStepState {
+            ref_id: name.into(),
+            title: name.into(),
+            status: status.into(),
+            output: None,
+        }
+    }
+
+    #[test]
+    fn next_ready_waits_for_dependencies() {
+        let def = FormulaDef::from_toml(
+            r#"
+name = "release"
+
+[[steps]]
+name = "build"
+command = "cargo"
+
+[[steps]]
+name = "test"
+command = "cargo"
+depends_on = ["build"]
+
+[[steps]]
+name = "docs"
+command = "mdbook"
+"#,
+        )
+        .unwrap();
+        let mut steps = vec![
+            state("build", "in_progress"),
+            state("test", "pending"),
+            state("docs", "pending"),
+        ];
+        assert_eq!(next_ready(&def, &steps).unwrap().name, "docs");
+
+        steps[2].status = "done".into();
+        assert!(next_ready(&def, &steps).is_none());
+
+        steps[0].status = "skipped".into();
+        assert_eq!(next_ready(&def, &steps).unwrap().name, "test");
+    }
+
+    #[test]
+    fn failures_that_continue_unblock_dependents() {
+        let def = FormulaDef::from_toml(
+            r#"
+name = "release"
+
+[[steps]]
+name = "lint"
+command = "cargo"
+on_failure = "continue"
+
+[[steps]]
+name = "build"
+command = "cargo"
+depends_on = ["lint"]
+on_failure = "goto:lint"
+
+[[steps]]
+name = "publish"
+command = "cargo"
+depends_on = ["build"]
+"#,
+        )
+        .unwrap();
+        let steps = vec![
+            state("lint", "failed"),
+            state("build", "pending"),
+            state("publish", "pending"),
+        ];
+        assert_eq!(next_ready(&def, &steps).unwrap().name, "build");
+
+        let reset = downstream(&def, "lint");
+        assert_eq!(reset.len(), 3);
+        assert_eq!(
+            downstream(&def, "build"),
+            HashSet::from(["build".to_string(), "publish".to_string()])
+        );
+    }
+
+    #[test]
+    fn resume_reruns_from_a_step() {
+        let def = FormulaDef::from_toml(
+            r#"
+name = "release"
+
+[[steps]]
+name = "build"
+command = "cargo"
+
+[[steps]]
+name = "test"
+command = "cargo"
+depends_on = ["build"]
+
+[[steps]]
+name = "docs"
+command = "mdbook"
+
+[[steps]]
+name = "publish"
+command = "cargo"
+depends_on = ["test"]
+"#,
+        )
+        .unwrap();
+        let mut build = state("build", "done");
+        build.output = Some("built".into());
+        let resume = MoleculeResume {
+            from: "test".into(),
+            steps: vec![
+                build,
+                state("test", "failed"),
+                state("docs", "in_progress"),
+                state("publish", "blocked"),
+            ],
+        };
+        let mut steps: Vec<_> = ["build", "test", "docs", "publish"]
+            .into_iter()
+            .map(|name| state(name, "pending"))
+            .collect();
+        resume_steps(&def, &mut steps, &resume);
+
+        let statuses: Vec<_> = steps.iter().map(|s| s.status.as_str()).collect();
+        assert_eq!(statuses, ["done", "pending", "pending", "pending"]);
+        assert_eq!(steps[0].output.as_deref(), Some("built"));
+        assert_eq!(next_ready(&def, &steps).unwrap().name, "test");
+    }
+
+    #[test]
+    fn registered_output_becomes_a_var() {
+        let def = FormulaDef::from_toml(
+            r#"
+name = "release"
+
+[[steps]]
+name = "version"
+command = "git"
+args = ["describe"]
+register = "version"
+
+[[steps]]
+name = "tag"
+command = "git"
+args = ["tag", "v{{version}}"]
+depends_on = ["version"]
+"#,
+        )
+        .unwrap();
+        let exec = Execution {
+            def,
+            vars: HashMap::from([("env".to_string(), "prod".to_string())]),
+            max_parallel: 1,
+            formulas: HashMap::new(),
+        };
+        let mut steps = vec![state("version", "done"), state("tag", "pending")];
+        steps[0].output = Some("1.4.0\n".into());
+
+        let vars = step_vars(&exec, &steps);
+        assert_eq!(vars["version"], "1.4.0");
+        assert_eq!(vars["version.status"], "done");
+        assert_eq!(vars["env"], "prod");
+        assert!(!vars.contains_key("tag.status"));
+    }
+
+    #[test]
+    fn agent_steps_retry_before_failing() {
+        let def = FormulaDef::from_toml(
+            r#"
+name = "review"
+
+[[steps]]
+name = "review"
+kind = "agent"
+prompt = "Review the diff"
+retries = 1
+timeout_secs = 600
+"#,
+        )
+        .unwrap();
+        let exec = Execution {
+            def,
+            vars: HashMap::new(),
+            max_parallel: 1,
+            formulas: HashMap::new(),
+        };
+        let mut steps = vec![state("review", "in_progress")];
+        let mut attempts = HashMap::from([("review".to_string(), 1)]);
+        let (mut jumps, mut jumped) = (VecDeque::new(), HashSet::new());
+
+        let timed_out = "timed out after 600s".to_string();
+        assert!(fail_agent_step(
+            &exec,
+            "review",
+            timed_out.clone(),
+            &mut steps,
+            &mut attempts,
+            &mut jumps,
+            &mut jumped,
+        ));
+        assert_eq!(steps[0].status, "pending");
+
+        attempts.insert("review".into(), 2);
+        steps[0].status = "in_progress".into();
+        assert!(!fail_agent_step(
+            &exec,
+            "review",
+            timed_out,
+            &mut steps,
+            &mut attempts,
+            &mut jumps,
+            &mut jumped,
+        ));
+        assert_eq!(steps[0].status, "failed");
+        assert_eq!(steps[0].output.as_deref(), Some("timed out after 600s"));
+        assert!(attempts.is_empty());
+    }
+
+    #[test]
+    fn goto_waits_for_a_running_target() {
+        let def = FormulaDef::from_toml(
+            r#"
+name = "deploy"
+
+[[steps]]
+name = "migrate"
+command = "migrate.sh"
+
+[[steps]]
+name = "push"
+command = "deploy.sh"
+on_failure = "goto:migrate"
+
+[[steps]]
+name = "verify"
+command = "verify.sh"
+depends_on = ["migrate"]
+"#,
+        )
+        .unwrap();
+        // `push` failed while `migrate`, its goto target, runs beside it
+        let mut steps = vec![
+            state("migrate", "in_progress"),
+            state("push", "failed"),
+            state("verify", "pending"),
+        ];
+        let mut jumps = VecDeque::from(["migrate".to_string()]);
+
+        assert!(next_step(&def, &steps, &mut jumps).is_none());
+        assert_eq!(jumps, ["migrate"]);
+
+        steps[0].status = "done".into();
+        assert_eq!(next_step(&def, &steps, &mut jumps).unwrap().name, "migrate");
+        assert!(jumps.is_empty());
+    }
+}
@@ -1,48 +1,100 @@
-use std::time::Duration;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Utc};
 
 use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use temporalio_common::protos::coresdk::child_workflow::ParentClosePolicy;
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
-use temporalio_sdk::{ActivityOptions, WfContext, WfExitValue};
+use temporalio_common::protos::temporal::api::common::v1::RetryPolicy;
+use temporalio_sdk::{
+    ActivityOptions, ChildWorkflowOptions, SignalWorkflowOptions, WfContext, WfExitValue,
+};
+
+use gtr_core::checks::Finding;
+use gtr_core::plugin::{AlertTarget, Gate, PluginDef, PluginFailure};
+use gtr_core::types::{Priority, WorkItem};
 
-use crate::activities::run_plugin::RunPluginInput;
-use crate::signals::SIGNAL_AGENT_STOP;
+use crate::activities::patrol_checks::{DetectChecksInput, DetectChecksOutput};
+use crate::activities::plugin_history::PluginRun;
+use crate::activities::run_plugin::{DiscoverPluginsInput, RunPluginInput, RunPluginOutput};
+use crate::signals::{
+    AgentMailSignal, PatrolEventSignal, SIGNAL_AGENT_MAIL, SIGNAL_AGENT_STOP, SIGNAL_PATROL_EVENT,
+};
+use crate::workflows::timer_or_never;
 
 /// Patrol workflow — real plugin discovery and gate-checked execution.
 ///
-/// Plugin discovery uses `gtr_core::plugin::discover_plugins()` via an activity
-/// (activities can perform filesystem I/O; workflows cannot for determinism).
+/// Plugin discovery uses `gtr_core::plugin::discover_plugins()` via the
+/// `discover_plugins` activity (activities can perform filesystem I/O;
+/// workflows cannot for determinism).
 ///
 /// On each cycle:
-/// 1. Discovers plugins from `~/.gtr/config/plugins/` via `run_plugin` activity
-///    running a discovery command that lists TOML files
-/// 2. Parses discovered plugin definitions (via activity output)
-/// 3. Runs each eligible plugin via `run_plugin` activity
-/// 4. Records results for digest reporting
+/// 1. Discovers the plugins in `~/.gtr/config/plugins/` and the rig's own
+///    `~/.gtr/rigs/<rig>/plugins/` as typed `PluginDef`s; a rig plugin
+///    replaces a global one with the same name
+/// 2. Runs each eligible plugin's command via the `run_plugin` activity and
+///    checks the result against the plugin's `[fail_when]` conditions
+/// 3. Records results for digest reporting
+///
+/// A plugin's gate decides when it runs, going by workflow time:
+/// - `Gate::None` — every cycle
+/// - `Gate::Cooldown { seconds }` — once that long has passed since its last run
+/// - `Gate::Cron { schedule }` — in the first cycle after the schedule fires
+/// - `Gate::Event { event }` — never in a cycle; only when a `patrol_event`
+///   signal names its event, with `GTR_EVENT`, `GTR_RIG` and the signal's
+///   `env` set
+///
+/// Language-aware built-ins (cargo check/clippy, npm audit, uv pip check) are
+/// selected each cycle by the `detect_patrol_checks` activity based on the
+/// rig's project files. Each carries its own cooldown gate, tracked here with
+/// workflow time, and failing output is parsed into deduplicated findings.
 ///
-/// Gate evaluation integration point:
-/// - `gtr_core::plugin::Gate::None` — always run
-/// - `gtr_core::plugin::Gate::Cooldown { seconds }` — track last run time, skip if too recent
-/// - `gtr_core::plugin::Gate::Cron { schedule }` — evaluate cron expression
-/// - `gtr_core::plugin::Gate::Event { event }` — run only on matching event signal
+/// When a plugin starts failing, patrol mails its `notify` target — the mayor
+/// or the rig's witness — the reason and an excerpt of the output. It stays
+/// quiet while the plugin keeps failing and speaks up again after it passes.
 ///
-/// Currently, gate evaluation is done at the activity level (the discovery
-/// activity can filter by gate), and the workflow runs all returned plugins.
+/// With [`PatrolOptions::file_work_items`], each new finding (and each failing
+/// plugin) is filed as a `work_item_wf` whose ID is derived from the finding
+/// signature, so a problem is filed once no matter how many cycles report it.
 pub async fn patrol_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error> {
     let args = ctx.get_args();
-    let (rig, interval_secs) = if let Some(payload) = args.first() {
-        serde_json::from_slice::<(String, u64)>(&payload.data)
-            .unwrap_or(("default".into(), 60))
+    let (rig, interval_secs, options) = if let Some(payload) = args.first() {
+        serde_json::from_slice::<(String, u64, PatrolOptions)>(&payload.data)
+            .or_else(|_| {
+                serde_json::from_slice::<(String, u64)>(&payload.data)
+                    .map(|(rig, interval)| (rig, interval, PatrolOptions::default()))
+            })
+            .unwrap_or(("default".into(), 60, PatrolOptions::default()))
     } else {
-        ("default".into(), 60)
+        ("default".into(), 60, PatrolOptions::default())
     };
 
     let mut stop_ch = ctx.make_signal_channel(SIGNAL_AGENT_STOP);
+    let mut event_ch = ctx.make_signal_channel(SIGNAL_PATROL_EVENT);
     let mut cycles: u64 = 0;
     let mut plugins_run: u64 = 0;
     let mut plugins_failed: u64 = 0;
+    let mut last_run: HashMap<String, SystemTime> = HashMap::new();
+    // Plugin name → when it last ran, for cooldown gates
+    let mut plugin_last_run: HashMap<String, SystemTime> = HashMap::new();
+    // Cron gates fire for schedule times since the previous cycle
+    let mut last_cycle: Option<SystemTime> = None;
+    // Plugins whose last run failed; they're alerted on once per streak
+    let mut failing: BTreeSet<String> = BTreeSet::new();
+    let mut work_dir = rig.clone();
+    // Latest findings per check — replaced each time the check runs
+    let mut findings: BTreeMap<String, Vec<Finding>> = BTreeMap::new();
+    // Finding signature → work item filed for it
+    let mut filed: BTreeMap<String, String> = BTreeMap::new();
 
     tracing::info!("Patrol started for rig {rig} — interval {interval_secs}s");
 
+    // Replaced only when it fires, so events don't restart the interval
+    let interval = Duration::from_secs(interval_secs);
+    let mut cycle_timer = std::pin::pin!(timer_or_never(&ctx, Some(interval)));
+
     loop {
         tokio::select! {
             biased;
@@ -56,69 +108,97 @@ pub async fn patrol_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Er
                         "cycles": cycles,
                         "plugins_run": plugins_run,
                         "plugins_failed": plugins_failed,
+                        "findings": findings,
+                        "filed": filed,
                     }))?
                 ));
             }
-            _ = ctx.timer(Duration::from_secs(interval_secs)) => {
+            Some(signal) = event_ch.next() => {
+                let Some(data) = signal
+                    .input
+                    .first()
+                    .and_then(|p| serde_json::from_slice::<PatrolEventSignal>(&p.data).ok())
+                else {
+                    continue;
+                };
+                let plugins: Vec<PluginDef> = discover(&ctx, &rig)
+                    .await?
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|p| matches!(&p.gate, Gate::Event { event } if *event == data.event))
+                    .collect();
+                tracing::info!(
+                    "Patrol: event {} on rig {rig} — {} plugin(s)",
+                    data.event,
+                    plugins.len()
+                );
+                let mut env = data.env;
+                env.insert("GTR_EVENT".to_string(), data.event.clone());
+                env.insert("GTR_RIG".to_string(), rig.clone());
+                for plugin in &plugins {
+                    let failure =
+                        run_discovered(&ctx, &rig, plugin, &work_dir, Some(env.clone())).await?;
+                    let Some(failure) = failure else {
+                        plugins_run += 1;
+                        failing.remove(&plugin.name);
+                        tracing::debug!(
+                            "Patrol: plugin {} succeeded on {}",
+                            plugin.name,
+                            data.event
+                        );
+                        continue;
+                    };
+                    plugins_failed += 1;
+                    tracing::warn!(
+                        "Patrol: plugin {} failed on {}: {}",
+                        plugin.name,
+                        data.event,
+                        failure.reason
+                    );
+                    if failing.insert(plugin.name.clone()) {
+                        alert(&ctx, &rig, plugin, &failure).await?;
+                    }
+                }
+            }
+            _ = &mut cycle_timer => {
+                cycle_timer.set(timer_or_never(&ctx, Some(interval)));
                 cycles += 1;
                 tracing::info!("Patrol cycle #{cycles} for rig {rig}");
+                let now = ctx.workflow_time();
+                let since = last_cycle.or_else(|| now.map(|now| now - interval));
+                last_cycle = now;
+                // Plugin failures this cycle, filed alongside check findings
+                let mut failures: Vec<Finding> = vec![];
 
-                // Step 1: Discover plugins from ~/.gtr/config/plugins/
-                // Uses `ls` activity to list TOML files in the plugin directory.
-                // In production, this would use a dedicated discover_plugins activity
-                // that calls gtr_core::plugin::discover_plugins() and returns
-                // Vec<PluginDef> as JSON. For now, we list the directory and run
-                // built-in checks alongside any discovered plugins.
-                let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
-                let plugin_dir = format!("{home}/.gtr/config/plugins");
-
-                let discover_input = RunPluginInput {
-                    plugin_name: "discover".to_string(),
-                    command: "ls".to_string(),
-                    args: vec![plugin_dir.clone()],
-                    work_dir: None,
-                };
+                // Step 1: Discover global and rig plugins
+                let plugins = discover(&ctx, &rig).await?.unwrap_or_else(|| {
+                    tracing::warn!(
+                        "Patrol cycle #{cycles}: plugin discovery failed, running built-in checks only"
+                    );
+                    Vec::new()
+                });
 
-                let discover_result = ctx
+                // Step 2: Detect the rig's project type and its language checks
+                let detect_input = DetectChecksInput { rig: rig.clone() };
+                let detect_result = ctx
                     .activity(ActivityOptions {
-                        activity_type: "run_plugin".to_string(),
-                        input: discover_input.as_json_payload()?,
+                        activity_type: "detect_patrol_checks".to_string(),
+                        input: detect_input.as_json_payload()?,
                         start_to_close_timeout: Some(Duration::from_secs(30)),
                         ..Default::default()
                     })
                     .await;
+                let detected = detect_result
+                    .success_payload_or_error()
+                    .ok()
+                    .flatten()
+                    .and_then(|p| serde_json::from_slice::<DetectChecksOutput>(&p.data).ok());
+                work_dir = detected
+                    .as_ref()
+                    .map(|d| d.work_dir.clone())
+                    .unwrap_or_else(|| rig.clone());
 
-                // Step 2: Parse discovered plugins and build run list
-                // Each line from ls output that ends in .toml is a plugin file.
-                // We extract the plugin name from the filename and run it.
-                let mut plugin_commands: Vec<(String, String, Vec<String>)> = vec![];
-
-                if let Ok(Some(payload)) = discover_result.success_payload_or_error() {
-                    // Parse the RunPluginOutput from the activity payload
-                    if let Ok(output) = serde_json::from_slice::<crate::activities::run_plugin::RunPluginOutput>(&payload.data) {
-                        for line in &output.stdout {
-                            let trimmed = line.trim();
-                            if trimmed.ends_with(".toml") {
-                                // Run the plugin via its TOML definition
-                                // The run_plugin activity reads and parses the TOML,
-                                // then executes the command defined within it.
-                                // For now, use cat to read the plugin definition.
-                                let plugin_name = trimmed.trim_end_matches(".toml");
-                                plugin_commands.push((
-                                    plugin_name.to_string(),
-                                    "cat".to_string(),
-                                    vec![format!("{plugin_dir}/{trimmed}")],
-                                ));
-                            }
-                        }
-                    }
-                } else {
-                    tracing::debug!(
-                        "Patrol cycle #{cycles}: plugin directory not found, running built-in checks only"
-                    );
-                }
-
-                // Step 3: Always run built-in patrol checks
+                // Always run built-in patrol checks
                 let builtins = vec![
                     ("health-check", "echo", vec!["ok".to_string()]),
                     ("git-status", "git", vec!["status".to_string(), "--short".to_string()]),
@@ -129,7 +209,8 @@ pub async fn patrol_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Er
                         plugin_name: name.to_string(),
                         command: cmd.to_string(),
                         args: args.clone(),
-                        work_dir: Some(rig.clone()),
+                        work_dir: Some(work_dir.clone()),
+                        env: None,
                     };
 
                     let result = ctx
@@ -147,33 +228,138 @@ pub async fn patrol_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Er
                     } else {
                         plugins_failed += 1;
                         tracing::warn!("Patrol: built-in plugin {name} failed");
+                        failures.push(plugin_failure(name));
                     }
                 }
 
-                // Step 4: Run discovered plugins
-                for (name, cmd, args) in &plugin_commands {
+                // Language-aware checks, each behind its own cooldown gate
+                for check in detected.iter().flat_map(|d| d.checks.iter()) {
+                    if !cooldown_elapsed(&check.gate, last_run.get(&check.name), now) {
+                        continue;
+                    }
+                    if let Some(now) = now {
+                        last_run.insert(check.name.clone(), now);
+                    }
+
                     let input = RunPluginInput {
-                        plugin_name: name.clone(),
-                        command: cmd.clone(),
-                        args: args.clone(),
-                        work_dir: Some(rig.clone()),
+                        plugin_name: check.name.clone(),
+                        command: check.command.clone(),
+                        args: check.args.clone(),
+                        work_dir: Some(work_dir.clone()),
+                        env: None,
                     };
 
                     let result = ctx
                         .activity(ActivityOptions {
                             activity_type: "run_plugin".to_string(),
                             input: input.as_json_payload()?,
-                            start_to_close_timeout: Some(Duration::from_secs(60)),
+                            start_to_close_timeout: Some(Duration::from_secs(900)),
                             ..Default::default()
                         })
                         .await;
 
-                    if result.completed_ok() {
-                        plugins_run += 1;
-                        tracing::debug!("Patrol: discovered plugin {name} succeeded");
-                    } else {
+                    let output = result
+                        .success_payload_or_error()
+                        .ok()
+                        .flatten()
+                        .and_then(|p| serde_json::from_slice::<RunPluginOutput>(&p.data).ok());
+                    let Some(output) = output else {
                         plugins_failed += 1;
-                        tracing::warn!("Patrol: discovered plugin {name} failed");
+                        tracing::warn!("Patrol: check {} could not run", check.name);
+                        continue;
+                    };
+
+                    plugins_run += 1;
+                    let check_findings = gtr_core::checks::extract_findings(
+                        &check.name,
+                        check.format,
+                        output.exit_code,
+                        &output.stdout,
+                    );
+                    if check_findings.is_empty() {
+                        tracing::debug!("Patrol: check {} clean", check.name);
+                    } else {
+                        tracing::warn!(
+                            "Patrol: check {} reported {} finding(s) on rig {rig}",
+                            check.name,
+                            check_findings.len()
+                        );
+                    }
+                    findings.insert(check.name.clone(), check_findings);
+                }
+
+                // Step 3: Run the discovered plugins whose gates are open
+                for plugin in &plugins {
+                    let name = &plugin.name;
+                    if !plugin_due(&plugin.gate, plugin_last_run.get(name), since, now) {
+                        continue;
+                    }
+                    if let Some(now) = now {
+                        plugin_last_run.insert(name.clone(), now);
+                    }
+
+                    let failure = run_discovered(&ctx, &rig, plugin, &work_dir, None).await?;
+                    let Some(failure) = failure else {
+                        plugins_run += 1;
+                        failing.remove(name);
+                        tracing::debug!("Patrol: plugin {name} succeeded");
+                        continue;
+                    };
+                    plugins_failed += 1;
+                    tracing::warn!("Patrol: plugin {name} failed: {}", failure.reason);
+                    failures.push(plugin_failure(name));
+                    if failing.insert(name.clone()) {
+                        alert(&ctx, &rig, plugin, &failure).await?;
+                    }
+                }
+
+                // Step 4: File new findings as work items, optionally slinging them
+                if options.file_work_items {
+                    let mut slung = 0;
+                    let new_findings: Vec<&Finding> = findings
+                        .values()
+                        .flatten()
+                        .chain(failures.iter())
+                        .filter(|f| !filed.contains_key(&f.signature))
+                        .collect();
+                    for finding in new_findings {
+                        let work_item_id = finding.work_item_id(&rig);
+                        let title = finding.work_item_title(&rig);
+                        filed.insert(finding.signature.clone(), work_item_id.clone());
+                        let item = WorkItem {
+                            description: finding.message.clone(),
+                            priority: Priority::P3,
+                            rig: Some(rig.clone()),
+                            labels: vec!["patrol".to_string(), finding.check.clone()],
+                            ..WorkItem::new(&work_item_id, &title)
+                        };
+
+                        let started = ctx
+                            .child_workflow(ChildWorkflowOptions {
+                                workflow_id: work_item_id.clone(),
+                                workflow_type: "work_item_wf".to_string(),
+                                input: vec![item.as_json_payload()?],
+                                // Outlives patrol when it's stopped or restarted
+                                parent_close_policy: ParentClosePolicy::Abandon,
+                                ..Default::default()
+                            })
+                            .start(&ctx)
+                            .await
+                            .into_started()
+                            .is_some();
+                        if !started {
+                            // Already filed by an earlier patrol run
+                            tracing::debug!("Patrol: work item {work_item_id} already exists");
+                            continue;
+                        }
+                        tracing::info!("Patrol: filed {work_item_id} — {title}");
+
+                        if options.auto_sling
+                            && slung < options.max_slung_per_cycle
+                            && sling_finding(&ctx, &rig, finding, &work_item_id, &title).await?
+                        {
+                            slung += 1;
+                        }
                     }
                 }
 
@@ -187,3 +373,339 @@ pub async fn patrol_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Er
         }
     }
 }
+
+/// A plugin for `plugin_test_wf` to run once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginTestInput {
+    /// Rig name or path to a checked-out tree, resolved as patrol does
+    pub rig: String,
+    pub plugin: PluginDef,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginTestResult {
+    pub work_dir: String,
+    /// `None` if the plugin couldn't be run
+    pub output: Option<RunPluginOutput>,
+    pub failure: Option<PluginFailure>,
+}
+
+/// Plugin test workflow — runs one plugin once, the way patrol would on the
+/// rig, for `rgt services plugin test`. The run isn't added to the plugin's
+/// history and nobody is alerted.
+pub async fn plugin_test_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error> {
+    let input = ctx
+        .get_args()
+        .first()
+        .and_then(|payload| serde_json::from_slice::<PluginTestInput>(&payload.data).ok())
+        .ok_or_else(|| anyhow::anyhow!("plugin_test_wf needs a PluginTestInput"))?;
+
+    let detect_input = DetectChecksInput {
+        rig: input.rig.clone(),
+    };
+    let work_dir = ctx
+        .activity(ActivityOptions {
+            activity_type: "detect_patrol_checks".to_string(),
+            input: detect_input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<DetectChecksOutput>(&p.data).ok())
+        .map(|d| d.work_dir)
+        .unwrap_or(input.rig);
+
+    let env = HashMap::from([("GTR_PLUGIN_TEST".to_string(), "1".to_string())]);
+    let (output, failure) = run_once(&ctx, &input.plugin, &work_dir, Some(env)).await?;
+    tracing::info!(
+        "Plugin test: {} in {work_dir} {}",
+        input.plugin.name,
+        failure.as_ref().map_or("passed", |_| "failed")
+    );
+    Ok(WfExitValue::Normal(serde_json::to_string(
+        &PluginTestResult {
+            work_dir,
+            output,
+            failure,
+        },
+    )?))
+}
+
+/// Patrol behaviour toggles, passed as the optional third workflow argument.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PatrolOptions {
+    /// File each new finding as a deduplicated work item
+    pub file_work_items: bool,
+    /// Spawn a polecat for newly filed items
+    pub auto_sling: bool,
+    /// Auto-slung work is low priority: at most this many polecats per cycle
+    pub max_slung_per_cycle: usize,
+}
+
+impl Default for PatrolOptions {
+    fn default() -> Self {
+        Self {
+            file_work_items: false,
+            auto_sling: false,
+            max_slung_per_cycle: 1,
+        }
+    }
+}
+
+/// Discover the global plugins and the rig's own; `None` if the activity
+/// failed.
+async fn discover(ctx: &WfContext, rig: &str) -> anyhow::Result<Option<Vec<PluginDef>>> {
+    let input = DiscoverPluginsInput {
+        rig: rig.to_string(),
+    };
+    let result = ctx
+        .activity(ActivityOptions {
+            activity_type: "discover_plugins".to_string(),
+            input: input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await;
+    Ok(result
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<Vec<PluginDef>>(&p.data).ok()))
+}
+
+/// Run a discovered plugin's command in `work_dir`, check it against the
+/// plugin's `[fail_when]` and add the run to its history. Returns why it
+/// failed, or `None` if it passed.
+async fn run_discovered(
+    ctx: &WfContext,
+    rig: &str,
+    plugin: &PluginDef,
+    work_dir: &str,
+    env: Option<HashMap<String, String>>,
+) -> anyhow::Result<Option<PluginFailure>> {
+    let (output, failure) = run_once(ctx, plugin, work_dir, env).await?;
+
+    let ran_at = ctx.workflow_time().map(Into::into).unwrap_or_default();
+    let run = PluginRun::new(&plugin.name, rig, ran_at, output.as_ref(), failure.as_ref());
+    let recorded = ctx
+        .activity(ActivityOptions {
+            activity_type: "record_plugin_run".to_string(),
+            input: run.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(10)),
+            ..Default::default()
+        })
+        .await;
+    if !recorded.completed_ok() {
+        tracing::warn!("Patrol: could not record the run of plugin {}", plugin.name);
+    }
+    Ok(failure)
+}
+
+/// Run a plugin via the `run_plugin` activity and check the result against
+/// its `[fail_when]`. The output is `None` if it couldn't be run.
+async fn run_once(
+    ctx: &WfContext,
+    plugin: &PluginDef,
+    work_dir: &str,
+    env: Option<HashMap<String, String>>,
+) -> anyhow::Result<(Option<RunPluginOutput>, Option<PluginFailure>)> {
+    let input = RunPluginInput {
+        plugin_name: plugin.name.clone(),
+        command: plugin.command.clone(),
+        args: plugin.args.clone(),
+        work_dir: Some(work_dir.to_string()),
+        env,
+    };
+    let result = ctx
+        .activity(ActivityOptions {
+            activity_type: "run_plugin".to_string(),
+            input: input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(60)),
+            // One run per cycle; a retry would re-run side effects and skew history
+            retry_policy: Some(RetryPolicy {
+                maximum_attempts: 1,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await;
+    let output = result
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<RunPluginOutput>(&p.data).ok());
+    let failure = match &output {
+        Some(o) => plugin
+            .fail_when
+            .check(o.exit_code, o.duration_ms, &o.stdout, &o.stderr),
+        None => Some(PluginFailure {
+            reason: "could not be run".to_string(),
+            excerpt: vec![],
+        }),
+    };
+    Ok((output, failure))
+}
+
+/// Mail a plugin's `notify` target that it has started failing. Errors are
+/// ignored — the mayor or witness may not be running.
+async fn alert(
+    ctx: &WfContext,
+    rig: &str,
+    plugin: &PluginDef,
+    failure: &PluginFailure,
+) -> anyhow::Result<()> {
+    let to = match plugin.fail_when.notify {
+        AlertTarget::Mayor => gtr_core::state::mayor_workflow_id(),
+        AlertTarget::Witness => gtr_core::state::witness_workflow_id(rig),
+    };
+    let mut message = format!(
+        "Patrol plugin {} failed on rig {rig}: {}",
+        plugin.name, failure.reason
+    );
+    for line in &failure.excerpt {
+        message.push_str(&format!("\n    {line}"));
+    }
+    let mail = AgentMailSignal {
+        from: gtr_core::state::patrol_workflow_id(),
+        message,
+        priority: gtr_core::mail::MailPriority::High,
+        ..Default::default()
+    };
+    let opts = SignalWorkflowOptions::new(
+        to.as_str(),
+        "",
+        SIGNAL_AGENT_MAIL,
+        vec![mail.as_json_payload()?],
+    );
+    if ctx.signal_workflow(opts).await.is_err() {
+        tracing::warn!(
+            "Patrol: could not tell {to} that plugin {} is failing",
+            plugin.name
+        );
+    }
+    Ok(())
+}
+
+fn plugin_failure(name: &str) -> Finding {
+    Finding::new(name, "error", &format!("plugin {name} failed"))
+}
+
+/// Start a polecat on a freshly filed finding. The polecat name comes from the
+/// finding signature so replays pick the same name.
+async fn sling_finding(
+    ctx: &WfContext,
+    rig: &str,
+    finding: &Finding,
+    work_item_id: &str,
+    title: &str,
+) -> anyhow::Result<bool> {
+    let suffix = &finding.signature[finding.signature.len().saturating_sub(8)..];
+    let name = format!("patrol-{suffix}");
+    let polecat_id = gtr_core::state::polecat_workflow_id(rig, &name);
+    let started = ctx
+        .child_workflow(ChildWorkflowOptions {
+            workflow_id: polecat_id.clone(),
+            workflow_type: "polecat_wf".to_string(),
+            input: vec![(name.as_str(), rig, work_item_id, title).as_json_payload()?],
+            // Outlives patrol when it's stopped or restarted
+            parent_close_policy: ParentClosePolicy::Abandon,
+            ..Default::default()
+        })
+        .start(ctx)
+        .await
+        .into_started()
+        .is_some();
+    if started {
+        tracing::info!("Patrol: slung {work_item_id} → polecat {polecat_id}");
+    } else {
+        tracing::warn!("Patrol: could not sling {work_item_id} to {polecat_id}");
+    }
+    Ok(started)
+}
+
+/// Whether a check's cooldown gate has elapsed. Non-cooldown gates (and runs
+/// without a workflow clock) always pass.
+fn cooldown_elapsed(gate: &Gate, last: Option<&SystemTime>, now: Option<SystemTime>) -> bool {
+    let (Gate::Cooldown { seconds }, Some(last), Some(now)) = (gate, last, now) else {
+        return true;
+    };
+    now.duration_since(*last)
+        .map(|elapsed| elapsed >= Duration::from_secs(*seconds))
+        .unwrap_or(true)
+}
+
+/// Whether a discovered plugin runs this cycle. `since` is when the previous
+/// cycle ran; a cron gate is due if its schedule fired after that. Without a
+/// workflow clock, only ungated and cooldown plugins run.
+fn plugin_due(
+    gate: &Gate,
+    last: Option<&SystemTime>,
+    since: Option<SystemTime>,
+    now: Option<SystemTime>,
+) -> bool {
+    match gate {
+        Gate::None | Gate::Cooldown { .. } => cooldown_elapsed(gate, last, now),
+        Gate::Cron { schedule } => {
+            let schedule = match gtr_core::cron::Schedule::parse(schedule) {
+                Ok(schedule) => schedule,
+                Err(e) => {
+                    tracing::warn!("Patrol: {e}");
+                    return false;
+                }
+            };
+            let (Some(since), Some(now)) = (since, now) else {
+                return false;
+            };
+            schedule.fires_between(DateTime::<Utc>::from(since), DateTime::<Utc>::from(now))
+        }
+        Gate::Event { .. } => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> SystemTime {
+        DateTime::parse_from_rfc3339(s).unwrap().into()
+    }
+
+    #[test]
+    fn plugin_due_follows_the_gate() {
+        let since = Some(at("2026-10-16T02:59:30Z"));
+        let now = Some(at("2026-10-16T03:00:30Z"));
+        assert!(plugin_due(&Gate::None, None, since, now));
+
+        let cooldown = Gate::Cooldown { seconds: 3600 };
+        assert!(plugin_due(&cooldown, None, since, now));
+        let recent = at("2026-10-16T02:30:00Z");
+        assert!(!plugin_due(&cooldown, Some(&recent), since, now));
+        let old = at("2026-10-16T01:30:00Z");
+        assert!(plugin_due(&cooldown, Some(&old), since, now));
+
+        let nightly = Gate::Cron {
+            schedule: "0 3 * * *".into(),
+        };
+        assert!(plugin_due(&nightly, None, since, now));
+        assert!(!plugin_due(
+            &nightly,
+            None,
+            now,
+            Some(at("2026-10-16T03:01:30Z"))
+        ));
+        assert!(!plugin_due(&nightly, None, None, now));
+        let bad = Gate::Cron {
+            schedule: "nightly".into(),
+        };
+        assert!(!plugin_due(&bad, None, since, now));
+
+        let event = Gate::Event {
+            event: "post_merge".into(),
+        };
+        assert!(!plugin_due(&event, None, since, now));
+    }
+}
@@ -4,10 +4,19 @@ use futures_util::StreamExt;
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
 use temporalio_sdk::{ActivityOptions, SignalWorkflowOptions, WfContext, WfExitValue};
 
-use crate::activities::heartbeat::{CapturePaneInput, CapturePaneOutput, HeartbeatInput};
+use crate::activities::discover_session::discover_from_workflow;
+use crate::activities::heartbeat::{
+    CapturePaneInput, CapturePaneOutput, HeartbeatInput, HeartbeatOutput, SessionMetadataInput,
+};
 use crate::activities::spawn_agent::SpawnAgentInput;
 use crate::activities::git_ops::GitOperation;
+use crate::pty::SessionMetadata;
 use crate::signals::*;
+use crate::workflows::agent::{register_with_mayor, store_mail};
+
+/// Times a polecat's agent is respawned (resuming its session) before the
+/// polecat gives up as `dead`.
+const MAX_AGENT_RESPAWNS: u32 = 2;
 
 /// Polecat workflow — ephemeral worker lifecycle.
 /// Lifecycle: create worktree → spawn agent → heartbeat loop → report to mayor → cleanup.
@@ -16,14 +25,19 @@ use crate::signals::*;
 /// before returning, so the mayor always has visibility into polecat outcomes.
 pub async fn polecat_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error> {
     let args = ctx.get_args();
-    let (name, rig, work_item_id, title) = if let Some(payload) = args.first() {
-        serde_json::from_slice::<(String, String, String, String)>(&payload.data)
-            .unwrap_or(("unknown".into(), "unknown".into(), "unknown".into(), "untitled".into()))
+    // Input: (name, rig, work item, title) with an optional trailing runtime
+    let (name, rig, work_item_id, title, runtime) = if let Some(payload) = args.first() {
+        serde_json::from_slice::<(String, String, String, String, String)>(&payload.data)
+            .or_else(|_| {
+                serde_json::from_slice::<(String, String, String, String)>(&payload.data)
+                    .map(|(n, r, w, t)| (n, r, w, t, "claude".into()))
+            })
+            .unwrap_or_else(|_| unknown_input())
     } else {
-        ("unknown".into(), "unknown".into(), "unknown".into(), "untitled".into())
+        unknown_input()
     };
 
-    let polecat_id = format!("{rig}-polecat-{name}");
+    let polecat_id = gtr_core::state::polecat_workflow_id(&rig, &name);
     let branch = format!("polecat/{name}/{work_item_id}");
     let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
     let worktree_path = format!("{home}/.gtr/rigs/{rig}/polecats/{name}");
@@ -33,6 +47,7 @@ pub async fn polecat_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
     let mut exit_reason = "unknown".to_string();
     let mut agent_summary: Option<String> = None;
     let mut agent_spawned = false;
+    let mut spawn_input: Option<SpawnAgentInput> = None;
 
     tracing::info!("Polecat {name} started on rig {rig}: {title}");
 
@@ -59,13 +74,14 @@ pub async fn polecat_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
 
     // ─── Step 2: Spawn Claude Code agent (only if worktree succeeded) ───
     if status == "working" {
-        let spawn_input = SpawnAgentInput {
+        let input = SpawnAgentInput {
             agent_id: polecat_id.clone(),
-            runtime: "claude".to_string(),
+            runtime: runtime.clone(),
             work_dir: worktree_path.clone(),
             role: format!("{rig}/polecats/{name}"),
             rig: Some(rig.clone()),
-            initial_prompt: Some(format!(
+            // A shell polecat would run the prompt as a script
+            initial_prompt: (runtime != "shell").then(|| format!(
                 "You are polecat '{name}' on rig '{rig}'. Your work item: {work_item_id} — {title}.\n\
                  Work in this directory.\n\n\
                  IMPORTANT: You MUST run this command when your work is complete:\n\
@@ -80,12 +96,19 @@ pub async fn polecat_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
                 m
             }),
             resume_session_id: None,
+            // Filled from the rig's polecat_limits and roles by spawn_agent
+            limits: None,
+            model: None,
+            permission_mode: None,
+            allowed_tools: None,
         };
 
+        // An earlier polecat of the same name may have left sessions in the worktree
+        let spawned_at = ctx.workflow_time().map(Into::into);
         let spawn_result = ctx
             .activity(ActivityOptions {
                 activity_type: "spawn_agent".to_string(),
-                input: spawn_input.as_json_payload()?,
+                input: input.as_json_payload()?,
                 start_to_close_timeout: Some(Duration::from_secs(30)),
                 ..Default::default()
             })
@@ -97,7 +120,13 @@ pub async fn polecat_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
             exit_reason = "spawn_failed".to_string();
         } else {
             agent_spawned = true;
+            spawn_input = Some(SpawnAgentInput {
+                resume_session_id: discover_from_workflow(&ctx, &worktree_path, spawned_at).await,
+                ..input
+            });
             tracing::info!("Polecat {name}: agent spawned, entering heartbeat loop");
+            register_with_mayor(&ctx, &polecat_id, gtr_core::state::roles::POLECAT).await;
+            update_tmux_status(&ctx, &polecat_id, &rig, &work_item_id, &status).await?;
         }
     }
 
@@ -106,6 +135,9 @@ pub async fn polecat_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
         let mut done_ch = ctx.make_signal_channel(SIGNAL_POLECAT_DONE);
         let mut kill_ch = ctx.make_signal_channel(SIGNAL_POLECAT_KILL);
         let mut stuck_ch = ctx.make_signal_channel(SIGNAL_POLECAT_STUCK);
+        let mut mail_ch = ctx.make_signal_channel(SIGNAL_AGENT_MAIL);
+        let mut respawns: u32 = 0;
+        let mut mail_received: usize = 0;
 
         loop {
             tokio::select! {
@@ -130,8 +162,18 @@ pub async fn polecat_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
                 Some(_) = stuck_ch.next() => {
                     status = "stuck".to_string();
                     tracing::warn!("Polecat {name} reports stuck");
+                    update_tmux_status(&ctx, &polecat_id, &rig, &work_item_id, &status).await?;
                     // Continue running — witness will handle escalation
                 }
+                Some(signal) = mail_ch.next() => {
+                    if let Some(payload) = signal.input.first() {
+                        if let Ok(data) = serde_json::from_slice::<AgentMailSignal>(&payload.data) {
+                            tracing::info!("Polecat {name}: mail from {}", data.from);
+                            store_mail(&ctx, &polecat_id, &data, mail_received).await;
+                            mail_received += 1;
+                        }
+                    }
+                }
                 _ = ctx.timer(Duration::from_secs(60)) => {
                     // Heartbeat check
                     let hb_input = HeartbeatInput {
@@ -146,7 +188,27 @@ pub async fn polecat_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
                         })
                         .await;
 
-                    if !hb_result.completed_ok() {
+                    // The activity completes either way; the flag says whether it's alive
+                    let alive = hb_result
+                        .success_payload_or_error()
+                        .ok()
+                        .flatten()
+                        .and_then(|p| serde_json::from_slice::<HeartbeatOutput>(&p.data).ok())
+                        .is_some_and(|hb| hb.alive);
+                    if alive {
+                        continue;
+                    }
+                    let respawned = match spawn_input.as_mut() {
+                        Some(input) if respawns < MAX_AGENT_RESPAWNS => {
+                            respawns += 1;
+                            tracing::warn!(
+                                "Polecat {name}: agent process died — respawning ({respawns}/{MAX_AGENT_RESPAWNS})"
+                            );
+                            respawn_agent(&ctx, input).await?
+                        }
+                        _ => false,
+                    };
+                    if !respawned {
                         tracing::warn!("Polecat {name}: agent process died");
                         status = "dead".to_string();
                         exit_reason = "agent_died".to_string();
@@ -217,13 +279,32 @@ pub async fn polecat_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
 
     let report_payload = report.as_json_payload()?;
     let sig_opts = SignalWorkflowOptions::new(
-        "mayor",
+        gtr_core::state::mayor_workflow_id(),
         "",
         SIGNAL_POLECAT_REPORT,
         vec![report_payload],
     );
     // Await the signal but ignore errors — mayor may not be running
     let _ = ctx.signal_workflow(sig_opts).await;
+    if agent_spawned {
+        let unregister_opts = SignalWorkflowOptions::new(
+            gtr_core::state::mayor_workflow_id(),
+            "",
+            SIGNAL_UNREGISTER_AGENT,
+            vec![polecat_id.as_json_payload()?],
+        );
+        let _ = ctx.signal_workflow(unregister_opts).await;
+    }
+
+    // Same report to the work item, so its history answers "what did the agent do?"
+    let item_opts = SignalWorkflowOptions::new(
+        &work_item_id,
+        "",
+        SIGNAL_POLECAT_REPORT,
+        vec![report.as_json_payload()?],
+    );
+    // The work item may not have its own workflow
+    let _ = ctx.signal_workflow(item_opts).await;
 
     // ─── Return final state ───
     Ok(WfExitValue::Normal(serde_json::to_string(&PolecatState {
@@ -236,3 +317,73 @@ pub async fn polecat_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
         summary,
     })?))
 }
+
+/// Name, rig, work item, title and runtime for a polecat started without
+/// (readable) input.
+fn unknown_input() -> (String, String, String, String, String) {
+    (
+        "unknown".into(),
+        "unknown".into(),
+        "unknown".into(),
+        "untitled".into(),
+        "claude".into(),
+    )
+}
+
+/// Respawn a polecat's dead agent, resuming the session it was in so it keeps
+/// its context. The session ID is refreshed first and persisted in `input`.
+async fn respawn_agent(ctx: &WfContext, input: &mut SpawnAgentInput) -> anyhow::Result<bool> {
+    if let Some(session_id) = discover_from_workflow(ctx, &input.work_dir, None).await {
+        input.resume_session_id = Some(session_id);
+    }
+    let mut respawn_input = input.clone();
+    // A resumed session already holds the task; otherwise start over with the
+    // original prompt. A shell polecat has no prompt either way.
+    if respawn_input.resume_session_id.is_some() && respawn_input.initial_prompt.is_some() {
+        respawn_input.initial_prompt = Some(
+            "You were restarted after a crash. Continue your work item, and run \
+             `$RGT_BIN done` when it is complete."
+                .to_string(),
+        );
+    }
+    let result = ctx
+        .activity(ActivityOptions {
+            activity_type: "spawn_agent".to_string(),
+            input: respawn_input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await;
+    Ok(result.completed_ok())
+}
+
+/// Best-effort refresh of the polecat's tmux title so `rgt sessions` and
+/// `tmux -L gtr` reflect the current status.
+async fn update_tmux_status(
+    ctx: &WfContext,
+    polecat_id: &str,
+    rig: &str,
+    work_item_id: &str,
+    status: &str,
+) -> anyhow::Result<()> {
+    let input = SessionMetadataInput {
+        agent_id: polecat_id.to_string(),
+        metadata: SessionMetadata {
+            role: gtr_core::state::roles::POLECAT.to_string(),
+            rig: Some(rig.to_string()),
+            work_item: Some(work_item_id.to_string()),
+            status: Some(status.to_string()),
+        },
+    };
+
+    let _ = ctx
+        .activity(ActivityOptions {
+            activity_type: "update_session_metadata".to_string(),
+            input: input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(10)),
+            ..Default::default()
+        })
+        .await;
+
+    Ok(())
+}
@@ -1,43 +1,179 @@
-use std::time::Duration;
+use std::time::{Duration, UNIX_EPOCH};
 
 use temporalio_common::protos::coresdk::activity_result::activity_resolution::Status;
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
-use temporalio_sdk::{ActivityOptions, WfContext, WfExitValue};
+use temporalio_common::protos::temporal::api::common::v1::RetryPolicy;
+use temporalio_sdk::{ActivityOptions, SignalWorkflowOptions, WfContext, WfExitValue};
 
 use crate::activities::git_ops::GitOperation;
-use crate::activities::run_plugin::RunPluginInput;
+use crate::activities::github_checks::{
+    CheckState, ChecksModeInput, GithubChecksInput, GithubChecksOutput,
+};
+use crate::activities::pull_request::{
+    CreatePullRequestInput, PullRequestRef, PullRequestState, PullRequestStatusInput,
+};
+use crate::activities::refinery_history::RecordEntryInput;
+use crate::activities::refinery_settings::{RefinerySettings, RefinerySettingsInput};
+use crate::activities::run_plugin::{EventPluginsInput, RunPluginInput, RunPluginOutput};
 use crate::signals::{
-    RefineryEntry, RefineryEnqueueSignal, RefineryState, SIGNAL_REFINERY_DEQUEUE,
-    SIGNAL_REFINERY_ENQUEUE, SIGNAL_REFINERY_STOP,
+    AgentMailSignal, ApprovalDecision, ApprovalRequest, FailSignal, MergeResultSignal,
+    ParkedApproval, RefineryBumpSignal, RefineryEnqueueSignal, RefineryEntry, RefineryMoveSignal,
+    RefineryState, SIGNAL_AGENT_MAIL, SIGNAL_APPROVAL_DECISION, SIGNAL_COMPLETE, SIGNAL_FAIL,
+    SIGNAL_MERGE_RESULT, SIGNAL_REFINERY_BUMP, SIGNAL_REFINERY_DEQUEUE, SIGNAL_REFINERY_ENQUEUE,
+    SIGNAL_REFINERY_MOVE, SIGNAL_REFINERY_PAUSE, SIGNAL_REFINERY_RESUME, SIGNAL_REFINERY_STOP,
 };
+use crate::workflows::agent::{register_with_mayor, store_mail};
+use crate::workflows::approval::{expire_approval, request_approval};
+use crate::workflows::timer_or_never;
+
+use futures_util::{FutureExt, StreamExt};
+use gtr_core::config::{GithubChecksConfig, MergeStrategy, PullRequestConfig};
 
-use futures_util::StreamExt;
+/// Default deadline for one test run of a queued entry.
+const DEFAULT_ENTRY_TIMEOUT_SECS: u64 = 600;
+/// Default number of times a timed-out test run is retried.
+const DEFAULT_ENTRY_RETRIES: u32 = 1;
 
 /// Refinery v2 — real git rebase, test execution, and conflict detection.
 /// For each queued work item:
 /// 1. Checkout branch (git_operation activity)
-/// 2. Rebase onto main (git_operation activity)
-/// 3. Run tests (run_plugin activity)
-/// 4. If tests pass: merge to main (git_operation activity)
+/// 2. Rebase onto the target branch — the entry's `target_branch`, else the
+///    rig's `default_branch` (git_operation activity)
+/// 3. Run tests (run_plugin activity), or — when the rig has `github_checks`
+///    configured — push the rebased branch and wait for required GitHub checks
+/// 4. If tests pass: land on the target using the rig's merge strategy (merge,
+///    squash or rebase-ff via git_operation activity) — or, for
+///    `validate_only` entries, stop and record `validated` instead. With
+///    `pull_request` configured, open a PR instead; the entry stays queued
+///    as `pr_open`, polled from the main loop, until the PR merges or closes.
+///    Once landed, run the rig's `post_merge` plugins (run_plugin activity)
 /// 5. If rebase fails: mark as conflict, spawn conflict-resolution polecat
+/// 6. Report the outcome to the work item (complete/fail) and the mayor
+///
+/// With `[approvals]` set in town.toml, force-pushing the rebased branch and
+/// merging into a protected branch each need a decision through the mayor's
+/// approval queue. The entry is parked as `awaiting_approval` while the rest
+/// of the queue moves on; once approved it is queued again and reprocessed
+/// with that operation allowed. A denied or expired entry ends as
+/// `approval_denied`.
 pub async fn refinery_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error> {
     let args = ctx.get_args();
-    let repo_path = if let Some(payload) = args.first() {
-        serde_json::from_slice::<String>(&payload.data).unwrap_or_else(|_| ".".into())
+    // Input: repo path, (repo path, rig name), or (repo path, rig, queue name)
+    let (repo_path, rig, queue_name) = if let Some(payload) = args.first() {
+        serde_json::from_slice::<(String, String, String)>(&payload.data)
+            .or_else(|_| {
+                serde_json::from_slice::<(String, String)>(&payload.data)
+                    .map(|(p, r)| (p, r, String::new()))
+            })
+            .or_else(|_| {
+                serde_json::from_slice::<String>(&payload.data)
+                    .map(|p| (p, String::new(), String::new()))
+            })
+            .unwrap_or_else(|_| (".".into(), String::new(), String::new()))
     } else {
-        ".".into()
+        (".".into(), String::new(), String::new())
     };
 
+    let refinery_id = gtr_core::state::refinery_queue_workflow_id(&rig, &queue_name);
+
+    let checks_input = ChecksModeInput {
+        rig: rig.clone(),
+        repo_path: repo_path.clone(),
+        queue: queue_name.clone(),
+    };
+    let checks_mode = ctx
+        .activity(ActivityOptions {
+            activity_type: "resolve_checks_mode".to_string(),
+            input: checks_input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<Option<GithubChecksConfig>>(&p.data).ok())
+        .flatten();
+    if let Some(cfg) = &checks_mode {
+        tracing::info!("Refinery: gating merges on GitHub checks for {}", cfg.repo);
+    }
+
+    let settings_input = RefinerySettingsInput {
+        rig: rig.clone(),
+        repo_path: repo_path.clone(),
+        queue: queue_name.clone(),
+    };
+    let settings = ctx
+        .activity(ActivityOptions {
+            activity_type: "resolve_refinery_settings".to_string(),
+            input: settings_input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<RefinerySettings>(&p.data).ok())
+        .unwrap_or_default();
+    if let Some(cfg) = &settings.pull_request {
+        tracing::info!("Refinery: landing through pull requests on {}", cfg.repo);
+    }
+    tracing::info!(
+        "Refinery: merge strategy {:?}, default branch '{}'",
+        settings.merge_strategy,
+        settings.default_branch
+    );
+
     let mut queue: Vec<RefineryEntry> = Vec::new();
     let mut processed: Vec<RefineryEntry> = Vec::new();
 
     let mut enqueue_ch = ctx.make_signal_channel(SIGNAL_REFINERY_ENQUEUE);
     let mut dequeue_ch = ctx.make_signal_channel(SIGNAL_REFINERY_DEQUEUE);
     let mut stop_ch = ctx.make_signal_channel(SIGNAL_REFINERY_STOP);
+    let mut bump_ch = ctx.make_signal_channel(SIGNAL_REFINERY_BUMP);
+    let mut move_ch = ctx.make_signal_channel(SIGNAL_REFINERY_MOVE);
+    let mut pause_ch = ctx.make_signal_channel(SIGNAL_REFINERY_PAUSE);
+    let mut resume_ch = ctx.make_signal_channel(SIGNAL_REFINERY_RESUME);
+    let mut mail_ch = ctx.make_signal_channel(SIGNAL_AGENT_MAIL);
+    let mut decision_ch = ctx.make_signal_channel(SIGNAL_APPROVAL_DECISION);
+    let mut mail_received: usize = 0;
+    // While paused, enqueues are still accepted but nothing is merged.
+    let mut paused = false;
+    // Polls the open pull request, if any; armed again after each poll
+    let mut pr_poll = std::pin::pin!(timer_or_never(&ctx, None));
+    let mut pr_poll_armed = false;
+    // Fires at the earliest deadline of the parked approvals
+    let mut approval_timer = std::pin::pin!(timer_or_never(&ctx, None));
+    let mut approval_timer_for: Option<u64> = None;
 
-    tracing::info!("Refinery started — merge queue ready (repo: {repo_path})");
+    if queue_name.is_empty() {
+        tracing::info!("Refinery started — merge queue ready (repo: {repo_path})");
+    } else {
+        tracing::info!("Refinery started — merge queue '{queue_name}' ready (repo: {repo_path})");
+    }
+    register_with_mayor(&ctx, &refinery_id, gtr_core::state::roles::REFINERY).await;
 
     loop {
+        if !pr_poll_armed && queue.iter().any(|e| e.status == "pr_open") {
+            let interval = settings
+                .pull_request
+                .as_ref()
+                .map(|cfg| Duration::from_secs(cfg.poll_interval_secs.max(1)));
+            pr_poll.set(timer_or_never(&ctx, interval));
+            pr_poll_armed = true;
+        }
+        let next_expiry = queue
+            .iter()
+            .filter_map(|e| e.awaiting_approval.as_ref()?.deadline)
+            .min();
+        if next_expiry != approval_timer_for {
+            let now = epoch_secs(&ctx).unwrap_or_default();
+            let wait = next_expiry.map(|at| Duration::from_secs(at.saturating_sub(now)));
+            approval_timer.set(timer_or_never(&ctx, wait));
+            approval_timer_for = next_expiry;
+        }
+
         // Wait for any signal
         tokio::select! {
             biased;
@@ -45,15 +181,50 @@ pub async fn refinery_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
                 tracing::info!("Refinery: stopping");
                 break;
             }
+            Some(_) = pause_ch.next() => {
+                paused = true;
+                tracing::info!("Refinery: paused — merges frozen, enqueues still accepted");
+            }
+            Some(_) = resume_ch.next() => {
+                paused = false;
+                tracing::info!("Refinery: resumed ({} item(s) queued)", queue.len());
+            }
+            Some(signal) = mail_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(data) = serde_json::from_slice::<AgentMailSignal>(&payload.data) {
+                        tracing::info!("Refinery: mail from {}", data.from);
+                        store_mail(&ctx, &refinery_id, &data, mail_received).await;
+                        mail_received += 1;
+                    }
+                }
+            }
             Some(signal) = enqueue_ch.next() => {
                 if let Some(payload) = signal.input.first() {
                     if let Ok(enq) = serde_json::from_slice::<RefineryEnqueueSignal>(&payload.data) {
-                        tracing::info!("Refinery: enqueue '{}' branch '{}'", enq.work_item_id, enq.branch);
+                        tracing::info!(
+                            "Refinery: enqueue '{}' branch '{}'{}",
+                            enq.work_item_id, enq.branch,
+                            if enq.validate_only { " (validate only)" } else { "" }
+                        );
                         queue.push(RefineryEntry {
                             work_item_id: enq.work_item_id,
                             branch: enq.branch,
                             priority: enq.priority,
                             status: "queued".to_string(),
+                            validate_only: enq.validate_only,
+                            timeout_secs: enq.timeout_secs,
+                            max_retries: enq.max_retries,
+                            attempts: 0,
+                            enqueued_at: epoch_secs(&ctx),
+                            started_at: None,
+                            finished_at: None,
+                            test_duration_secs: None,
+                            target_branch: enq.target_branch,
+                            pull_request_url: None,
+                            pull_request_number: None,
+                            pull_request_deadline: None,
+                            awaiting_approval: None,
+                            approved: Vec::new(),
                         });
                     }
                 }
@@ -66,16 +237,73 @@ pub async fn refinery_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
                     }
                 }
             }
+            Some(signal) = bump_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(bump) = serde_json::from_slice::<RefineryBumpSignal>(&payload.data) {
+                        if move_entry(&mut queue, &bump.work_item_id, 0) {
+                            tracing::info!("Refinery: bumped '{}' to head of queue", bump.work_item_id);
+                        } else {
+                            tracing::warn!("Refinery: bump for unknown item '{}'", bump.work_item_id);
+                        }
+                    }
+                }
+            }
+            Some(signal) = move_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(mv) = serde_json::from_slice::<RefineryMoveSignal>(&payload.data) {
+                        if move_entry(&mut queue, &mv.work_item_id, mv.position) {
+                            tracing::info!("Refinery: moved '{}' to position {}", mv.work_item_id, mv.position);
+                        } else {
+                            tracing::warn!("Refinery: move for unknown item '{}'", mv.work_item_id);
+                        }
+                    }
+                }
+            }
+            Some(signal) = decision_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(decision) = serde_json::from_slice::<ApprovalDecision>(&payload.data) {
+                        decide_parked(&ctx, &rig, &queue_name, &mut queue, &mut processed, &decision).await;
+                    }
+                }
+            }
+            _ = &mut approval_timer => {
+                approval_timer.set(timer_or_never(&ctx, None));
+                approval_timer_for = None;
+                expire_parked(&ctx, &rig, &queue_name, &mut queue, &mut processed).await?;
+            }
+            _ = &mut pr_poll => {
+                // A fired timer can't be polled again; park it until re-armed
+                pr_poll.set(timer_or_never(&ctx, None));
+                pr_poll_armed = false;
+                check_pull_request(&ctx, &rig, &queue_name, &repo_path, &settings, &mut queue, &mut processed).await?;
+            }
         }
 
         // Sort by priority (lower = higher priority)
         queue.sort_by_key(|e| e.priority);
 
+        // One pull request at a time, so the next branch is rebased onto what it merged
+        if paused || queue.iter().any(|e| e.status == "pr_open") {
+            continue;
+        }
+
         // Process all queued items sequentially
         while let Some(idx) = queue.iter().position(|e| e.status == "queued") {
+            // Honor a pause that arrived while the previous item was merging
+            if pause_ch.next().now_or_never().flatten().is_some() {
+                paused = true;
+                tracing::info!("Refinery: paused — {} item(s) left queued", queue.len());
+                break;
+            }
+
             let item_id = queue[idx].work_item_id.clone();
             let branch = queue[idx].branch.clone();
+            let target = queue[idx]
+                .target_branch
+                .clone()
+                .unwrap_or_else(|| settings.default_branch.clone());
             queue[idx].status = "validating".to_string();
+            queue[idx].started_at = epoch_secs(&ctx);
 
             // Step 1: Checkout the feature branch
             let checkout_op = GitOperation::Checkout {
@@ -97,15 +325,37 @@ pub async fn refinery_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
                 let mut entry = queue.remove(idx);
                 entry.status = "checkout_failed".to_string();
                 tracing::warn!("Refinery: checkout failed for '{item_id}' branch '{branch}'");
-                processed.push(entry);
+                finish_entry(&ctx, &rig, &queue_name, entry, &mut processed).await;
                 continue;
             }
 
-            // Step 2: Rebase onto main
+            // The target only moves on the forge in PR mode; rebase onto its latest
+            if settings.pull_request.is_some() {
+                let fetch_op = GitOperation::Fetch {
+                    repo_path: repo_path.clone(),
+                    remote: "origin".to_string(),
+                    branch: target.clone(),
+                };
+                let fetch_result = ctx
+                    .activity(ActivityOptions {
+                        activity_type: "git_operation".to_string(),
+                        input: fetch_op.as_json_payload()?,
+                        start_to_close_timeout: Some(Duration::from_secs(120)),
+                        ..Default::default()
+                    })
+                    .await;
+                if !fetch_result.completed_ok() {
+                    tracing::warn!(
+                        "Refinery: could not fetch '{target}' — rebasing onto local copy"
+                    );
+                }
+            }
+
+            // Step 2: Rebase onto the target branch
             let rebase_op = GitOperation::Rebase {
                 repo_path: repo_path.clone(),
                 branch: branch.clone(),
-                onto: "main".to_string(),
+                onto: target.clone(),
             };
 
             let rebase_result = ctx
@@ -123,54 +373,153 @@ pub async fn refinery_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
                 tracing::warn!(
                     "Refinery: rebase conflict for '{item_id}' — needs conflict resolution"
                 );
-                processed.push(entry);
+                finish_entry(&ctx, &rig, &queue_name, entry, &mut processed).await;
                 continue;
             }
 
-            // Step 3: Run tests via run_plugin
-            let test_input = RunPluginInput {
-                plugin_name: format!("refinery:test:{item_id}"),
-                command: "cargo".to_string(),
-                args: vec!["test".to_string()],
-                work_dir: Some(repo_path.clone()),
-            };
+            // Checks and PR modes force-push the rebased branch
+            let force_pushes = checks_mode.is_some() || settings.pull_request.is_some();
+            if force_pushes
+                && settings.approvals.force_push
+                && !queue[idx].approved.iter().any(|op| op == "force_push")
+            {
+                let request = ApprovalRequest {
+                    id: format!("ap-push-{item_id}-{}", epoch_secs(&ctx).unwrap_or_default()),
+                    operation: "force_push".to_string(),
+                    target: branch.clone(),
+                    detail: format!(
+                        "force-push '{branch}' for {item_id} after rebasing it onto '{target}'"
+                    ),
+                    requested_by: refinery_id.clone(),
+                };
+                let timeout = settings.approvals.timeout();
+                if !park_for_approval(&ctx, &mut queue[idx], request, timeout).await? {
+                    let mut entry = queue.remove(idx);
+                    entry.status = "approval_denied".to_string();
+                    tracing::warn!(
+                        "Refinery: force-push of '{branch}' for '{item_id}' not approved"
+                    );
+                    finish_entry(&ctx, &rig, &queue_name, entry, &mut processed).await;
+                }
+                continue;
+            }
 
-            let test_result = ctx
-                .activity(ActivityOptions {
-                    activity_type: "run_plugin".to_string(),
-                    input: test_input.as_json_payload()?,
-                    start_to_close_timeout: Some(Duration::from_secs(600)),
-                    ..Default::default()
-                })
-                .await;
+            // Step 3: Validate — GitHub checks if configured, local tests otherwise
+            let validation_started = epoch_secs(&ctx);
+            let failure = if let Some(cfg) = &checks_mode {
+                await_github_checks(&ctx, &repo_path, &branch, cfg).await?
+            } else {
+                run_local_tests(
+                    &ctx,
+                    &repo_path,
+                    settings.test_command.as_deref(),
+                    &mut queue[idx],
+                )
+                .await?
+            };
+            if let (Some(start), Some(end)) = (validation_started, epoch_secs(&ctx)) {
+                queue[idx].test_duration_secs = Some(end.saturating_sub(start));
+            }
+            if let Some(failure) = failure {
+                let mut entry = queue.remove(idx);
+                entry.status = failure.to_string();
+                tracing::warn!(
+                    "Refinery: '{item_id}' failed validation ({failure}) after {} attempt(s)",
+                    entry.attempts
+                );
+                finish_entry(&ctx, &rig, &queue_name, entry, &mut processed).await;
+                continue;
+            }
 
-            if !test_result.completed_ok() {
+            // Dry run: the branch rebased and passed validation, stop before merge
+            if queue[idx].validate_only {
                 let mut entry = queue.remove(idx);
-                entry.status = "tests_failed".to_string();
-                tracing::warn!("Refinery: tests failed for '{item_id}' after rebase");
-                processed.push(entry);
+                entry.status = "validated".to_string();
+                tracing::info!("Refinery: '{item_id}' branch '{branch}' would merge cleanly");
+                finish_entry(&ctx, &rig, &queue_name, entry, &mut processed).await;
                 continue;
             }
 
-            // Step 4: Checkout main and merge the rebased branch
-            let checkout_main = GitOperation::Checkout {
+            // Step 4 (PR mode): the forge does the merge; the main loop polls it
+            if let Some(cfg) = &settings.pull_request {
+                match open_pull_request(&ctx, &repo_path, &item_id, &branch, &target, cfg).await? {
+                    Ok(pr) => {
+                        tracing::info!("Refinery: opened {} for '{item_id}'", pr.url);
+                        let entry = &mut queue[idx];
+                        entry.status = "pr_open".to_string();
+                        entry.pull_request_url = Some(pr.url);
+                        entry.pull_request_number = Some(pr.number);
+                        entry.pull_request_deadline =
+                            epoch_secs(&ctx).map(|now| now + cfg.timeout_secs);
+                        break;
+                    }
+                    Err(status) => {
+                        let mut entry = queue.remove(idx);
+                        entry.status = status.to_string();
+                        tracing::warn!(
+                            "Refinery: could not open a pull request for '{item_id}' ({status})"
+                        );
+                        finish_entry(&ctx, &rig, &queue_name, entry, &mut processed).await;
+                        continue;
+                    }
+                }
+            }
+
+            if settings.approvals.is_protected(&target)
+                && !queue[idx].approved.iter().any(|op| op == "protected_merge")
+            {
+                let request = ApprovalRequest {
+                    id: format!(
+                        "ap-merge-{item_id}-{}",
+                        epoch_secs(&ctx).unwrap_or_default()
+                    ),
+                    operation: "protected_merge".to_string(),
+                    target: target.clone(),
+                    detail: format!("merge '{branch}' for {item_id} into protected '{target}'"),
+                    requested_by: refinery_id.clone(),
+                };
+                let timeout = settings.approvals.timeout();
+                if !park_for_approval(&ctx, &mut queue[idx], request, timeout).await? {
+                    let mut entry = queue.remove(idx);
+                    entry.status = "approval_denied".to_string();
+                    tracing::warn!("Refinery: merge of '{item_id}' into '{target}' not approved");
+                    finish_entry(&ctx, &rig, &queue_name, entry, &mut processed).await;
+                }
+                continue;
+            }
+
+            // Step 4: Checkout the target and merge the rebased branch
+            let checkout_target = GitOperation::Checkout {
                 repo_path: repo_path.clone(),
-                branch: "main".to_string(),
+                branch: target.clone(),
                 create: false,
             };
 
             let _ = ctx
                 .activity(ActivityOptions {
                     activity_type: "git_operation".to_string(),
-                    input: checkout_main.as_json_payload()?,
+                    input: checkout_target.as_json_payload()?,
                     start_to_close_timeout: Some(Duration::from_secs(60)),
                     ..Default::default()
                 })
                 .await;
 
-            let merge_op = GitOperation::Merge {
-                repo_path: repo_path.clone(),
-                branch: branch.clone(),
+            let merge_op = match settings.merge_strategy {
+                MergeStrategy::Merge | MergeStrategy::RebaseFf => GitOperation::Merge {
+                    repo_path: repo_path.clone(),
+                    branch: branch.clone(),
+                    ff_only: settings.merge_strategy == MergeStrategy::RebaseFf,
+                },
+                MergeStrategy::Squash => GitOperation::Squash {
+                    repo_path: repo_path.clone(),
+                    branch: branch.clone(),
+                    onto: target.clone(),
+                    message: settings
+                        .squash_message
+                        .clone()
+                        .unwrap_or_else(|| gtr_core::config::DEFAULT_SQUASH_MESSAGE.to_string()),
+                    work_item_id: item_id.clone(),
+                },
             };
 
             let merge_result = ctx
@@ -186,13 +535,15 @@ pub async fn refinery_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
             match merge_result.status {
                 Some(Status::Completed(_)) => {
                     entry.status = "merged".to_string();
-                    tracing::info!("Refinery: merged '{item_id}' branch '{branch}'");
+                    tracing::info!(
+                        "Refinery: merged '{item_id}' branch '{branch}' into '{target}'"
+                    );
 
-                    // Step 5: Push main to remote
+                    // Step 5: Push the target to remote
                     let push_op = GitOperation::Push {
                         repo_path: repo_path.clone(),
                         remote: "origin".to_string(),
-                        branch: "main".to_string(),
+                        branch: target.clone(),
                     };
                     let push_result = ctx
                         .activity(ActivityOptions {
@@ -207,7 +558,10 @@ pub async fn refinery_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
                         tracing::warn!("Refinery: push to remote failed for '{item_id}' — merged locally but not pushed");
                         entry.status = "merged_push_failed".to_string();
                     } else {
-                        tracing::info!("Refinery: pushed main to remote after merging '{item_id}'");
+                        tracing::info!(
+                            "Refinery: pushed '{target}' to remote after merging '{item_id}'"
+                        );
+                        run_post_merge_hooks(&ctx, &rig, &repo_path, &entry, &target).await?;
                     }
                 }
                 _ => {
@@ -215,10 +569,563 @@ pub async fn refinery_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::
                     tracing::warn!("Refinery: merge failed for '{item_id}'");
                 }
             }
-            processed.push(entry);
+            finish_entry(&ctx, &rig, &queue_name, entry, &mut processed).await;
         }
     }
 
-    let state = RefineryState { queue, processed };
+    let state = RefineryState {
+        queue,
+        processed,
+        paused,
+    };
     Ok(WfExitValue::Normal(serde_json::to_string(&state)?))
 }
+
+/// Run the test suite with a per-entry deadline. An attempt that errors or
+/// runs past the deadline is retried up to the entry's retry count, after
+/// which the entry is `timed_out` so it stops blocking the queue. Returns the
+/// entry status to record when the branch must not be merged.
+async fn run_local_tests(
+    ctx: &WfContext,
+    repo_path: &str,
+    test_command: Option<&[String]>,
+    entry: &mut RefineryEntry,
+) -> anyhow::Result<Option<&'static str>> {
+    let timeout = Duration::from_secs(entry.timeout_secs.unwrap_or(DEFAULT_ENTRY_TIMEOUT_SECS));
+    let max_retries = entry.max_retries.unwrap_or(DEFAULT_ENTRY_RETRIES);
+    let test_input = RunPluginInput {
+        plugin_name: format!("refinery:test:{}", entry.work_item_id),
+        command: test_command
+            .and_then(|c| c.first())
+            .cloned()
+            .unwrap_or_else(|| "cargo".to_string()),
+        args: match test_command {
+            Some([_, args @ ..]) => args.to_vec(),
+            _ => vec!["test".to_string()],
+        },
+        work_dir: Some(repo_path.to_string()),
+        env: None,
+    };
+
+    loop {
+        entry.attempts += 1;
+        let test_result = ctx
+            .activity(ActivityOptions {
+                activity_type: "run_plugin".to_string(),
+                input: test_input.as_json_payload()?,
+                start_to_close_timeout: Some(timeout),
+                // Retries are counted here, not by the server
+                retry_policy: Some(RetryPolicy {
+                    maximum_attempts: 1,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .await;
+
+        if let Ok(Some(payload)) = test_result.success_payload_or_error() {
+            let passed = serde_json::from_slice::<RunPluginOutput>(&payload.data)
+                .map(|out| out.exit_code == Some(0))
+                .unwrap_or(false);
+            return Ok(if passed { None } else { Some("tests_failed") });
+        }
+        if entry.attempts > max_retries {
+            return Ok(Some("timed_out"));
+        }
+        tracing::warn!(
+            "Refinery: tests for '{}' did not finish within {}s (attempt {}) — retrying",
+            entry.work_item_id,
+            timeout.as_secs(),
+            entry.attempts
+        );
+    }
+}
+
+/// Push the rebased branch and poll GitHub until its checks settle. Returns
+/// the entry status to record when the branch must not be merged.
+async fn await_github_checks(
+    ctx: &WfContext,
+    repo_path: &str,
+    branch: &str,
+    cfg: &GithubChecksConfig,
+) -> anyhow::Result<Option<&'static str>> {
+    if !force_push_branch(ctx, repo_path, branch).await? {
+        return Ok(Some("push_failed"));
+    }
+
+    let poll_interval = cfg.poll_interval_secs.max(1);
+    let polls = (cfg.timeout_secs / poll_interval).max(1);
+    let checks_input = GithubChecksInput {
+        config: cfg.clone(),
+        git_ref: branch.to_string(),
+    };
+    for _ in 0..polls {
+        // Give CI a moment to pick up the push before each poll
+        ctx.timer(Duration::from_secs(poll_interval)).await;
+
+        let output = ctx
+            .activity(ActivityOptions {
+                activity_type: "github_checks_status".to_string(),
+                input: checks_input.as_json_payload()?,
+                start_to_close_timeout: Some(Duration::from_secs(60)),
+                ..Default::default()
+            })
+            .await
+            .success_payload_or_error()
+            .ok()
+            .flatten()
+            .and_then(|p| serde_json::from_slice::<GithubChecksOutput>(&p.data).ok());
+        match output.map(|o| o.state) {
+            Some(CheckState::Success) => return Ok(None),
+            Some(CheckState::Failure) => return Ok(Some("checks_failed")),
+            Some(CheckState::Pending) => {}
+            None => tracing::warn!("Refinery: could not read GitHub checks for '{branch}'"),
+        }
+    }
+    Ok(Some("checks_timeout"))
+}
+
+/// Force push the branch to origin — it was just rebased. Returns whether
+/// the push succeeded.
+async fn force_push_branch(ctx: &WfContext, repo_path: &str, branch: &str) -> anyhow::Result<bool> {
+    let push_op = GitOperation::Push {
+        repo_path: repo_path.to_string(),
+        remote: "origin".to_string(),
+        branch: format!("+{branch}"),
+    };
+    let push_result = ctx
+        .activity(ActivityOptions {
+            activity_type: "git_operation".to_string(),
+            input: push_op.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(120)),
+            ..Default::default()
+        })
+        .await;
+    Ok(push_result.completed_ok())
+}
+
+/// Push the validated branch and open a pull request against the target.
+/// Returns the PR, or the entry status to finish with when that fails.
+async fn open_pull_request(
+    ctx: &WfContext,
+    repo_path: &str,
+    item_id: &str,
+    branch: &str,
+    target: &str,
+    cfg: &PullRequestConfig,
+) -> anyhow::Result<Result<PullRequestRef, &'static str>> {
+    if !force_push_branch(ctx, repo_path, branch).await? {
+        return Ok(Err("push_failed"));
+    }
+
+    let create_input = CreatePullRequestInput {
+        config: cfg.clone(),
+        branch: branch.to_string(),
+        target: target.to_string(),
+        title: format!("{item_id}: merge {branch}"),
+        body: format!(
+            "Rebased onto `{target}` and validated by the gtr refinery for work item {item_id}."
+        ),
+    };
+    let pr = ctx
+        .activity(ActivityOptions {
+            activity_type: "create_pull_request".to_string(),
+            input: create_input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(60)),
+            ..Default::default()
+        })
+        .await
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<PullRequestRef>(&p.data).ok());
+    Ok(pr.ok_or("pr_failed"))
+}
+
+/// Ask the forge about the entry waiting on a pull request, and finish it
+/// once the PR is merged or closed, or has been open past its deadline.
+async fn check_pull_request(
+    ctx: &WfContext,
+    rig: &str,
+    queue_name: &str,
+    repo_path: &str,
+    settings: &RefinerySettings,
+    queue: &mut Vec<RefineryEntry>,
+    processed: &mut Vec<RefineryEntry>,
+) -> anyhow::Result<()> {
+    let Some(cfg) = &settings.pull_request else {
+        return Ok(());
+    };
+    let Some(idx) = queue.iter().position(|e| e.status == "pr_open") else {
+        return Ok(());
+    };
+    let Some(number) = queue[idx].pull_request_number else {
+        return Ok(());
+    };
+    let status_input = PullRequestStatusInput {
+        config: cfg.clone(),
+        number,
+    };
+    let state = ctx
+        .activity(ActivityOptions {
+            activity_type: "pull_request_status".to_string(),
+            input: status_input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(60)),
+            ..Default::default()
+        })
+        .await
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<PullRequestState>(&p.data).ok());
+    let expired = match (queue[idx].pull_request_deadline, epoch_secs(ctx)) {
+        (Some(deadline), Some(now)) => now >= deadline,
+        _ => false,
+    };
+    let status = match state {
+        Some(PullRequestState::Merged) => "merged",
+        Some(PullRequestState::Closed) => "pr_closed",
+        _ if expired => "pr_timeout",
+        Some(PullRequestState::Open) => return Ok(()),
+        None => {
+            tracing::warn!(
+                "Refinery: could not read status of {}",
+                queue[idx].pull_request_url.as_deref().unwrap_or_default()
+            );
+            return Ok(());
+        }
+    };
+
+    let mut entry = queue.remove(idx);
+    entry.status = status.to_string();
+    tracing::info!(
+        "Refinery: '{}' pull request finished as {status}",
+        entry.work_item_id
+    );
+    if status == "merged" {
+        let target = entry
+            .target_branch
+            .clone()
+            .unwrap_or_else(|| settings.default_branch.clone());
+        run_post_merge_hooks(ctx, rig, repo_path, &entry, &target).await?;
+    }
+    finish_entry(ctx, rig, queue_name, entry, processed).await;
+    Ok(())
+}
+
+/// Run the rig's `post_merge` plugins in the refinery checkout once a branch
+/// has landed. Hook failures are logged; the merge itself stands.
+async fn run_post_merge_hooks(
+    ctx: &WfContext,
+    rig: &str,
+    repo_path: &str,
+    entry: &RefineryEntry,
+    target: &str,
+) -> anyhow::Result<()> {
+    if rig.is_empty() {
+        return Ok(());
+    }
+    let discover_input = EventPluginsInput {
+        rig: rig.to_string(),
+        event: gtr_core::plugin::EVENT_POST_MERGE.to_string(),
+    };
+    let hooks = ctx
+        .activity(ActivityOptions {
+            activity_type: "discover_event_plugins".to_string(),
+            input: discover_input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<Vec<gtr_core::plugin::PluginDef>>(&p.data).ok())
+        .unwrap_or_default();
+
+    let env = std::collections::HashMap::from([
+        (
+            "GTR_EVENT".to_string(),
+            gtr_core::plugin::EVENT_POST_MERGE.to_string(),
+        ),
+        ("GTR_RIG".to_string(), rig.to_string()),
+        ("GTR_WORK_ITEM".to_string(), entry.work_item_id.clone()),
+        ("GTR_BRANCH".to_string(), entry.branch.clone()),
+        ("GTR_TARGET_BRANCH".to_string(), target.to_string()),
+    ]);
+    for hook in hooks {
+        let input = RunPluginInput {
+            plugin_name: hook.name.clone(),
+            command: hook.command,
+            args: hook.args,
+            work_dir: Some(repo_path.to_string()),
+            env: Some(env.clone()),
+        };
+        let passed = ctx
+            .activity(ActivityOptions {
+                activity_type: "run_plugin".to_string(),
+                input: input.as_json_payload()?,
+                start_to_close_timeout: Some(Duration::from_secs(300)),
+                ..Default::default()
+            })
+            .await
+            .success_payload_or_error()
+            .ok()
+            .flatten()
+            .and_then(|p| serde_json::from_slice::<RunPluginOutput>(&p.data).ok())
+            .is_some_and(|out| out.exit_code == Some(0));
+        if passed {
+            tracing::info!(
+                "Refinery: post-merge hook '{}' ran for '{}'",
+                hook.name,
+                entry.work_item_id
+            );
+        } else {
+            tracing::warn!(
+                "Refinery: post-merge hook '{}' failed for '{}'",
+                hook.name,
+                entry.work_item_id
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Workflow clock as seconds since the Unix epoch (replay-safe).
+fn epoch_secs(ctx: &WfContext) -> Option<u64> {
+    ctx.workflow_time()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+/// Ask for approval of `request` and park `entry` on it, so the queue keeps
+/// moving while it waits. Returns false if the mayor couldn't take the
+/// request.
+async fn park_for_approval(
+    ctx: &WfContext,
+    entry: &mut RefineryEntry,
+    request: ApprovalRequest,
+    timeout: Duration,
+) -> anyhow::Result<bool> {
+    if !request_approval(ctx, &request).await? {
+        return Ok(false);
+    }
+    entry.status = "awaiting_approval".to_string();
+    entry.awaiting_approval = Some(ParkedApproval {
+        id: request.id,
+        operation: request.operation,
+        deadline: epoch_secs(ctx).map(|now| now + timeout.as_secs()),
+    });
+    Ok(true)
+}
+
+/// Apply a decision to the entry parked on it. An approved entry is queued
+/// again with the operation allowed; a denied one ends as `approval_denied`.
+/// Decisions for entries dequeued meanwhile are ignored.
+async fn decide_parked(
+    ctx: &WfContext,
+    rig: &str,
+    queue_name: &str,
+    queue: &mut Vec<RefineryEntry>,
+    processed: &mut Vec<RefineryEntry>,
+    decision: &ApprovalDecision,
+) {
+    let Some(idx) = queue.iter().position(|e| {
+        e.awaiting_approval
+            .as_ref()
+            .is_some_and(|parked| parked.id == decision.id)
+    }) else {
+        return;
+    };
+    let verdict = if decision.approved {
+        "approved"
+    } else {
+        "denied"
+    };
+    tracing::info!(
+        "Refinery: '{}' {verdict} by {} ({})",
+        queue[idx].work_item_id,
+        decision.by,
+        decision.id
+    );
+    if decision.approved {
+        let entry = &mut queue[idx];
+        if let Some(parked) = entry.awaiting_approval.take() {
+            entry.approved.push(parked.operation);
+        }
+        entry.status = "queued".to_string();
+    } else {
+        let mut entry = queue.remove(idx);
+        entry.awaiting_approval = None;
+        entry.status = "approval_denied".to_string();
+        finish_entry(ctx, rig, queue_name, entry, processed).await;
+    }
+}
+
+/// End the parked entries whose approval nobody answered in time.
+async fn expire_parked(
+    ctx: &WfContext,
+    rig: &str,
+    queue_name: &str,
+    queue: &mut Vec<RefineryEntry>,
+    processed: &mut Vec<RefineryEntry>,
+) -> anyhow::Result<()> {
+    let Some(now) = epoch_secs(ctx) else {
+        return Ok(());
+    };
+    while let Some(idx) = queue.iter().position(|e| {
+        e.awaiting_approval
+            .as_ref()
+            .and_then(|parked| parked.deadline)
+            .is_some_and(|deadline| deadline <= now)
+    }) {
+        let mut entry = queue.remove(idx);
+        if let Some(parked) = entry.awaiting_approval.take() {
+            expire_approval(ctx, &parked.id).await?;
+        }
+        entry.status = "approval_denied".to_string();
+        finish_entry(ctx, rig, queue_name, entry, processed).await;
+    }
+    Ok(())
+}
+
+/// Stamp a finished entry, report it, append it to the rig's refinery
+/// history (for `rgt mq stats`), and move it to `processed`.
+async fn finish_entry(
+    ctx: &WfContext,
+    rig: &str,
+    queue: &str,
+    mut entry: RefineryEntry,
+    processed: &mut Vec<RefineryEntry>,
+) {
+    entry.finished_at = epoch_secs(ctx);
+    notify_merge_result(ctx, &entry).await;
+
+    let record = RecordEntryInput {
+        rig: rig.to_string(),
+        queue: queue.to_string(),
+        entry: entry.clone(),
+    };
+    if let Ok(input) = record.as_json_payload() {
+        let _ = ctx
+            .activity(ActivityOptions {
+                activity_type: "record_refinery_entry".to_string(),
+                input,
+                start_to_close_timeout: Some(Duration::from_secs(30)),
+                ..Default::default()
+            })
+            .await;
+    }
+    processed.push(entry);
+}
+
+/// Tell the work item and the mayor how a merge attempt ended, so merged
+/// items don't sit in `in_progress` until the stale timer escalates them.
+/// Validation-only runs and rebase conflicts report to the mayor but leave the
+/// work item alone — a conflict is resolved on the same item, so failing it
+/// would end the work the resolution is for. Errors are ignored — either
+/// workflow may not be running.
+async fn notify_merge_result(ctx: &WfContext, entry: &RefineryEntry) {
+    let merged = entry.status.starts_with("merged");
+    let result = MergeResultSignal {
+        work_item_id: entry.work_item_id.clone(),
+        branch: entry.branch.clone(),
+        status: entry.status.clone(),
+        merged,
+        validate_only: entry.validate_only,
+    };
+    if let Ok(payload) = result.as_json_payload() {
+        let mayor_signal = SignalWorkflowOptions::new(
+            gtr_core::state::mayor_workflow_id(),
+            "",
+            SIGNAL_MERGE_RESULT,
+            vec![payload],
+        );
+        let _ = ctx.signal_workflow(mayor_signal).await;
+    }
+
+    if entry.validate_only || entry.status == "conflict" {
+        return;
+    }
+    let item_signal = if merged {
+        SignalWorkflowOptions::new(&entry.work_item_id, "", SIGNAL_COMPLETE, vec![])
+    } else {
+        let fail = FailSignal {
+            reason: format!("refinery: {} (branch {})", entry.status, entry.branch),
+        };
+        let Ok(payload) = fail.as_json_payload() else {
+            return;
+        };
+        SignalWorkflowOptions::new(&entry.work_item_id, "", SIGNAL_FAIL, vec![payload])
+    };
+    let _ = ctx.signal_workflow(item_signal).await;
+}
+
+/// Move a queued entry to `position`, adopting the priority of the entry it
+/// displaces so the stable priority sort keeps it there. Returns false if the
+/// work item isn't in the queue.
+fn move_entry(queue: &mut Vec<RefineryEntry>, work_item_id: &str, position: usize) -> bool {
+    let Some(idx) = queue.iter().position(|e| e.work_item_id == work_item_id) else {
+        return false;
+    };
+    let mut entry = queue.remove(idx);
+    let position = position.min(queue.len());
+    entry.priority = if let Some(displaced) = queue.get(position) {
+        displaced.priority
+    } else {
+        queue.last().map(|e| e.priority).unwrap_or(entry.priority)
+    };
+    queue.insert(position, entry);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(entries: &[(&str, u8)]) -> Vec<RefineryEntry> {
+        entries
+            .iter()
+            .map(|(id, priority)| {
+                serde_json::from_value(serde_json::json!({
+                    "work_item_id": id,
+                    "branch": format!("polecat/{id}"),
+                    "priority": priority,
+                    "status": "queued",
+                }))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    fn order(queue: &[RefineryEntry]) -> Vec<(&str, u8)> {
+        queue
+            .iter()
+            .map(|e| (e.work_item_id.as_str(), e.priority))
+            .collect()
+    }
+
+    #[test]
+    fn move_entry_takes_the_displaced_priority() {
+        let mut q = queue(&[("wi-a", 1), ("wi-b", 2), ("wi-c", 3)]);
+        assert!(move_entry(&mut q, "wi-c", 0));
+        assert_eq!(order(&q), [("wi-c", 1), ("wi-a", 1), ("wi-b", 2)]);
+
+        assert!(move_entry(&mut q, "wi-c", 1));
+        assert_eq!(order(&q), [("wi-a", 1), ("wi-c", 2), ("wi-b", 2)]);
+    }
+
+    #[test]
+    fn move_entry_clamps_to_the_end_and_skips_unknown_items() {
+        let mut q = queue(&[("wi-a", 1), ("wi-b", 2), ("wi-c", 3)]);
+        assert!(move_entry(&mut q, "wi-a", 99));
+        assert_eq!(order(&q), [("wi-b", 2), ("wi-c", 3), ("wi-a", 3)]);
+
+        assert!(!move_entry(&mut q, "wi-z", 0));
+        assert_eq!(order(&q), [("wi-b", 2), ("wi-c", 3), ("wi-a", 3)]);
+
+        let mut single = queue(&[("wi-a", 4)]);
+        assert!(move_entry(&mut single, "wi-a", 5));
+        assert_eq!(order(&single), [("wi-a", 4)]);
+    }
+}
@@ -3,11 +3,16 @@ use std::time::Duration;
 use futures_util::StreamExt;
 use temporalio_common::protos::coresdk::workflow_commands::ContinueAsNewWorkflowExecution;
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
-use temporalio_sdk::{ActivityOptions, WfContext, WfExitValue};
+use temporalio_common::protos::temporal::api::common::v1::RetryPolicy;
+use temporalio_sdk::{ActivityOptions, SignalWorkflowOptions, WfContext, WfExitValue};
+
+use gtr_core::config::ApprovalPolicy;
 
 use crate::activities::discover_session::DiscoverSessionInput;
+use crate::activities::heartbeat::ShutdownAgentsInput;
 use crate::activities::spawn_agent::SpawnAgentInput;
 use crate::signals::*;
+use crate::workflows::approval::await_approval;
 
 /// Rig workflow — manages a registered git repository's lifecycle.
 /// States: operational (active), parked (paused), docked (long-term shutdown), dormant (after stop).
@@ -105,7 +110,7 @@ pub async fn rig_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error
                     // Spawn witness
                     if !state.has_witness {
                         let witness_input = SpawnAgentInput {
-                            agent_id: format!("{}-witness", state.name),
+                            agent_id: gtr_core::state::witness_workflow_id(&state.name),
                             runtime: "claude".to_string(),
                             work_dir: format!("{home}/.gtr/rigs/{}/witness", state.name),
                             role: "witness".to_string(),
@@ -119,6 +124,10 @@ pub async fn rig_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error
                             )),
                             env_extra: None,
                             resume_session_id: state.witness_session_id.clone(),
+                            limits: None,
+                            model: None,
+                            permission_mode: None,
+                            allowed_tools: None,
                         };
 
                         let result = ctx
@@ -133,10 +142,12 @@ pub async fn rig_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error
                         if result.completed_ok() {
                             state.has_witness = true;
                             tracing::info!("Rig {}: spawned witness", state.name);
+                            boot_watch(&ctx, &witness_input).await;
 
                             // Discover session ID
                             let discover_input = DiscoverSessionInput {
                                 work_dir: format!("{home}/.gtr/rigs/{}/witness", state.name),
+                                since: None,
                             };
                             let session_result = ctx
                                 .activity(ActivityOptions {
@@ -157,7 +168,7 @@ pub async fn rig_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error
                     // Spawn refinery
                     if !state.has_refinery {
                         let refinery_input = SpawnAgentInput {
-                            agent_id: format!("{}-refinery", state.name),
+                            agent_id: gtr_core::state::refinery_workflow_id(&state.name),
                             runtime: "claude".to_string(),
                             work_dir: format!("{home}/.gtr/rigs/{}/refinery", state.name),
                             role: "refinery".to_string(),
@@ -169,6 +180,10 @@ pub async fn rig_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error
                             )),
                             env_extra: None,
                             resume_session_id: state.refinery_session_id.clone(),
+                            limits: None,
+                            model: None,
+                            permission_mode: None,
+                            allowed_tools: None,
                         };
 
                         let result = ctx
@@ -183,10 +198,12 @@ pub async fn rig_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error
                         if result.completed_ok() {
                             state.has_refinery = true;
                             tracing::info!("Rig {}: spawned refinery", state.name);
+                            boot_watch(&ctx, &refinery_input).await;
 
                             // Discover session ID
                             let discover_input = DiscoverSessionInput {
                                 work_dir: format!("{home}/.gtr/rigs/{}/refinery", state.name),
+                                since: None,
                             };
                             let session_result = ctx
                                 .activity(ActivityOptions {
@@ -210,6 +227,24 @@ pub async fn rig_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error
             Some(_) = stop_ch.next() => {
                 tracing::info!("Rig {} stopping — Continue-As-New (dormant)", state.name);
                 state.status = "dormant".to_string();
+                // The agents are about to be killed — boot must not respawn them
+                let agent_ids = vec![
+                    gtr_core::state::witness_workflow_id(&state.name),
+                    gtr_core::state::refinery_workflow_id(&state.name),
+                ];
+                for agent_id in &agent_ids {
+                    boot_unwatch(&ctx, agent_id.clone()).await;
+                }
+                // Give them the shutdown grace period to hand off, then kill them
+                let shutdown_input = ShutdownAgentsInput { agent_ids };
+                let _ = ctx
+                    .activity(ActivityOptions {
+                        activity_type: "shutdown_agents".to_string(),
+                        input: shutdown_input.as_json_payload()?,
+                        start_to_close_timeout: Some(Duration::from_secs(15 * 60)),
+                        ..Default::default()
+                    })
+                    .await;
                 // Reset agent presence flags since processes will be killed
                 state.has_witness = false;
                 state.has_refinery = false;
@@ -285,3 +320,97 @@ pub async fn rig_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error
         }
     }
 }
+
+/// Delete a stopped rig for `rgt rig remove`. With `rig_delete` set under
+/// `[approvals]` in town.toml it first waits for the mayor's approval queue.
+/// Returns `removed`, `denied` or `failed`.
+pub async fn rig_remove_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error> {
+    let rig = ctx
+        .get_args()
+        .first()
+        .and_then(|p| serde_json::from_slice::<String>(&p.data).ok())
+        .ok_or_else(|| anyhow::anyhow!("rig_remove_wf needs a rig name"))?;
+
+    // If the policy can't be read, ask rather than delete unasked
+    let policy = ctx
+        .activity(ActivityOptions {
+            activity_type: "load_approval_policy".to_string(),
+            input: ().as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<ApprovalPolicy>(&p.data).ok())
+        .unwrap_or(ApprovalPolicy {
+            rig_delete: true,
+            ..Default::default()
+        });
+    if policy.rig_delete {
+        let requested_at = ctx
+            .workflow_time()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let request = ApprovalRequest {
+            id: format!("ap-rig-delete-{rig}-{requested_at}"),
+            operation: "delete_rig".to_string(),
+            target: rig.clone(),
+            detail: format!("delete rig '{rig}' with its clone, worktrees and plugins"),
+            requested_by: gtr_core::state::rig_removal_workflow_id(&rig),
+        };
+        if !await_approval(&ctx, &request, policy.timeout()).await? {
+            tracing::warn!("Removal of rig {rig} not approved");
+            return Ok(WfExitValue::Normal("denied".to_string()));
+        }
+    }
+
+    let removed = ctx
+        .activity(ActivityOptions {
+            activity_type: "remove_rig".to_string(),
+            input: rig.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(300)),
+            retry_policy: Some(RetryPolicy {
+                maximum_attempts: 3,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .completed_ok();
+    if !removed {
+        tracing::warn!("Could not remove rig {rig}");
+        return Ok(WfExitValue::Normal("failed".to_string()));
+    }
+    Ok(WfExitValue::Normal("removed".to_string()))
+}
+
+/// Hand a spawned agent to `boot_wf` so it is respawned with the same role and
+/// work dir if it dies. Errors are ignored — boot may not be running.
+async fn boot_watch(ctx: &WfContext, input: &SpawnAgentInput) {
+    if let Ok(payload) = input.as_json_payload() {
+        let _ = ctx
+            .signal_workflow(SignalWorkflowOptions::new(
+                gtr_core::state::boot_workflow_id(),
+                "",
+                SIGNAL_BOOT_WATCH,
+                vec![payload],
+            ))
+            .await;
+    }
+}
+
+async fn boot_unwatch(ctx: &WfContext, agent_id: String) {
+    if let Ok(payload) = agent_id.as_json_payload() {
+        let _ = ctx
+            .signal_workflow(SignalWorkflowOptions::new(
+                gtr_core::state::boot_workflow_id(),
+                "",
+                SIGNAL_BOOT_UNWATCH,
+                vec![payload],
+            ))
+            .await;
+    }
+}
@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use temporalio_common::protos::coresdk::AsJsonPayloadExt;
+use temporalio_sdk::{ActivityOptions, WfContext, WfExitValue};
+
+use crate::activities::heartbeat::{HeartbeatInput, HeartbeatOutput};
+use crate::activities::spawn_agent::SpawnAgentInput;
+use crate::signals::{TriageSignal, SIGNAL_WORK_TRIAGE};
+
+/// How long a triage agent gets before it is killed without an assessment.
+const TRIAGE_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Triage workflow — a short-lived agent on the cheap `triage` runtime that
+/// assesses one work item and reports back with `rgt work triage`, which
+/// signals both the work item and this workflow.
+/// Lifecycle: spawn agent → wait for the assessment (or death/timeout) → kill agent.
+pub async fn triage_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error> {
+    let args = ctx.get_args();
+    // Input: (work item, title, rig)
+    let (work_item_id, title, rig) = if let Some(payload) = args.first() {
+        serde_json::from_slice::<(String, String, Option<String>)>(&payload.data).unwrap_or((
+            "unknown".into(),
+            "untitled".into(),
+            None,
+        ))
+    } else {
+        ("unknown".into(), "untitled".into(), None)
+    };
+
+    let agent_id = gtr_core::state::triage_workflow_id(&work_item_id);
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+    let (work_dir, repo_hint) = match &rig {
+        Some(rig) => (
+            format!("{home}/.gtr/rigs/{rig}"),
+            " The rig's repository is the bare clone in .repo.git — browse it with \
+             `git --git-dir=.repo.git ls-tree -r HEAD` and `git --git-dir=.repo.git show HEAD:<path>`."
+                .to_string(),
+        ),
+        None => (format!("{home}/.gtr"), String::new()),
+    };
+
+    tracing::info!("Triage {work_item_id} started: {title}");
+
+    let spawn_input = SpawnAgentInput {
+        agent_id: agent_id.clone(),
+        runtime: "triage".to_string(),
+        work_dir,
+        role: gtr_core::state::roles::TRIAGE.to_string(),
+        rig: rig.clone(),
+        initial_prompt: Some(format!(
+            "You are a triage agent. Do NOT change any code. Assess work item {work_item_id} — {title}.{repo_hint}\n\n\
+             Estimate its complexity (small, medium or large), propose labels, the best rig and a \
+             priority (0 = highest, 4 = lowest), then record your assessment with:\n\
+             $RGT_BIN work triage {work_item_id} --complexity <small|medium|large> --label <label> \
+             --rig <rig> --priority <n> --notes \"<one or two sentences>\"\n\n\
+             That command ends your session. (RGT_BIN env var has the full path to rgt.)"
+        )),
+        env_extra: Some(std::collections::HashMap::from([(
+            "GTR_WORK_ITEM".to_string(),
+            work_item_id.clone(),
+        )])),
+        resume_session_id: None,
+        limits: None,
+        model: None,
+        permission_mode: None,
+        allowed_tools: None,
+    };
+
+    let spawn_result = ctx
+        .activity(ActivityOptions {
+            activity_type: "spawn_agent".to_string(),
+            input: spawn_input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await;
+
+    if !spawn_result.completed_ok() {
+        tracing::error!("Triage {work_item_id}: failed to spawn agent");
+        return Ok(WfExitValue::Normal(
+            serde_json::json!({ "work_item_id": work_item_id, "status": "spawn_failed" })
+                .to_string(),
+        ));
+    }
+
+    let mut triage_ch = ctx.make_signal_channel(SIGNAL_WORK_TRIAGE);
+    let mut waited = Duration::ZERO;
+    let mut assessment: Option<TriageSignal> = None;
+    let status = loop {
+        tokio::select! {
+            biased;
+            Some(signal) = triage_ch.next() => {
+                assessment = signal
+                    .input
+                    .first()
+                    .and_then(|p| serde_json::from_slice(&p.data).ok());
+                break "triaged";
+            }
+            _ = ctx.timer(HEARTBEAT_INTERVAL) => {
+                waited += HEARTBEAT_INTERVAL;
+                if waited >= TRIAGE_TIMEOUT {
+                    tracing::warn!("Triage {work_item_id}: no assessment after {}s", waited.as_secs());
+                    break "timed_out";
+                }
+                let hb_input = HeartbeatInput {
+                    agent_id: agent_id.clone(),
+                };
+                let hb_result = ctx
+                    .activity(ActivityOptions {
+                        activity_type: "check_agent_alive".to_string(),
+                        input: hb_input.as_json_payload()?,
+                        start_to_close_timeout: Some(Duration::from_secs(10)),
+                        ..Default::default()
+                    })
+                    .await;
+                let alive = hb_result
+                    .success_payload_or_error()
+                    .ok()
+                    .flatten()
+                    .and_then(|p| serde_json::from_slice::<HeartbeatOutput>(&p.data).ok())
+                    .is_some_and(|hb| hb.alive);
+                if !alive {
+                    tracing::warn!("Triage {work_item_id}: agent exited without an assessment");
+                    break "agent_died";
+                }
+            }
+        }
+    };
+
+    // Triage agents are throwaway — always clean up the session
+    let cleanup_input = HeartbeatInput {
+        agent_id: agent_id.clone(),
+    };
+    let _ = ctx
+        .activity(ActivityOptions {
+            activity_type: "kill_agent".to_string(),
+            input: cleanup_input.as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(10)),
+            ..Default::default()
+        })
+        .await;
+
+    tracing::info!("Triage {work_item_id} finished: {status}");
+    Ok(WfExitValue::Normal(
+        serde_json::json!({
+            "work_item_id": work_item_id,
+            "status": status,
+            "assessment": assessment,
+        })
+        .to_string(),
+    ))
+}
@@ -1,40 +1,60 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
 use futures_util::StreamExt;
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
-use temporalio_sdk::{ActivityOptions, WfContext, WfExitValue};
+use temporalio_sdk::{ActivityOptions, SignalWorkflowOptions, WfContext, WfExitValue};
 
-use crate::activities::heartbeat::HeartbeatInput;
+use crate::activities::heartbeat::{
+    HeartbeatInput, HeartbeatOutput, PaneActivityInput, PaneActivityOutput, RigLoadInput,
+    RigLoadOutput,
+};
 use crate::activities::notification::NotificationInput;
-use crate::signals::SIGNAL_AGENT_STOP;
+use crate::signals::{AgentMailSignal, SIGNAL_AGENT_MAIL, SIGNAL_AGENT_STOP, SIGNAL_POLECAT_STUCK};
+use crate::workflows::agent::{register_with_mayor, store_mail};
+
+/// Default quiet period before a live polecat counts as idle.
+const DEFAULT_IDLE_AFTER_SECS: u64 = 15 * 60;
 
 /// Witness workflow — real polecat heartbeat-based health monitoring and escalation.
 /// On each cycle:
 /// 1. Checks each tracked polecat via `check_agent_alive` heartbeat activity
 /// 2. If dead (heartbeat fails), sends escalation to Mayor
-/// 3. Tracks alert count per polecat to avoid spam
-/// 4. Sends periodic health reports
+/// 3. If alive but its pane has been quiet for the idle threshold, marks the
+///    polecat stuck and escalates once per idle spell
+/// 4. Tracks alert count per polecat to avoid spam
+/// 5. Sends periodic health reports
 pub async fn witness_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error> {
     let args = ctx.get_args();
-    let (rig, interval_secs) = if let Some(payload) = args.first() {
-        serde_json::from_slice::<(String, u64)>(&payload.data)
-            .unwrap_or(("default".into(), 300))
+    // Input: (rig, interval_secs, idle_after_secs) — idle threshold is optional
+    let (rig, interval_secs, idle_after_secs) = if let Some(payload) = args.first() {
+        serde_json::from_slice::<(String, u64, u64)>(&payload.data)
+            .or_else(|_| {
+                serde_json::from_slice::<(String, u64)>(&payload.data)
+                    .map(|(rig, interval)| (rig, interval, DEFAULT_IDLE_AFTER_SECS))
+            })
+            .unwrap_or(("default".into(), 300, DEFAULT_IDLE_AFTER_SECS))
     } else {
-        ("default".into(), 300)
+        ("default".into(), 300, DEFAULT_IDLE_AFTER_SECS)
     };
 
     let mut stop_ch = ctx.make_signal_channel(SIGNAL_AGENT_STOP);
+    let mut mail_ch = ctx.make_signal_channel(SIGNAL_AGENT_MAIL);
+    let mut mail_received: usize = 0;
     let mut checks: u64 = 0;
     let mut alerts_sent: u64 = 0;
     // Track last known alive state per polecat
     let mut last_alive: HashMap<String, bool> = HashMap::new();
     // Track how many consecutive dead checks per polecat (avoid spam)
     let mut dead_counts: HashMap<String, u32> = HashMap::new();
-    // Known polecats on this rig (populated as they appear)
+    // Polecats that are alive but have produced no output for idle_after_secs
+    let mut idle_polecats: HashSet<String> = HashSet::new();
+    // Polecats rig_load last listed on this rig
     let mut tracked_polecats: Vec<String> = vec![];
 
     tracing::info!("Witness started for rig {rig} — check interval {interval_secs}s");
+    let witness_id = gtr_core::state::witness_workflow_id(&rig);
+    register_with_mayor(&ctx, &witness_id, gtr_core::state::roles::WITNESS).await;
 
     loop {
         tokio::select! {
@@ -47,23 +67,53 @@ pub async fn witness_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
                         "checks": checks,
                         "alerts_sent": alerts_sent,
                         "tracked_polecats": tracked_polecats.len(),
+                        "idle_polecats": idle_polecats.len(),
                     }))?
                 ));
             }
+            Some(signal) = mail_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(data) = serde_json::from_slice::<AgentMailSignal>(&payload.data) {
+                        tracing::info!("Witness for rig {rig}: mail from {}", data.from);
+                        store_mail(&ctx, &witness_id, &data, mail_received).await;
+                        mail_received += 1;
+                    }
+                }
+            }
             _ = ctx.timer(Duration::from_secs(interval_secs)) => {
                 checks += 1;
                 tracing::info!("Witness check #{checks} for rig {rig}");
 
-                // Build polecat IDs to check — convention: {rig}-polecat-{n}
-                // Start with any previously tracked, plus standard naming pattern
-                if tracked_polecats.is_empty() {
-                    // Seed with conventional polecat names for this rig
-                    for i in 0..4 {
-                        tracked_polecats.push(format!("{rig}-polecat-{i}"));
+                // Pick up polecats that have started on this rig since the last
+                // check and drop the ones that have finished
+                let load_input = RigLoadInput { rig: rig.clone() };
+                let live = ctx
+                    .activity(ActivityOptions {
+                        activity_type: "rig_load".to_string(),
+                        input: load_input.as_json_payload()?,
+                        start_to_close_timeout: Some(Duration::from_secs(10)),
+                        ..Default::default()
+                    })
+                    .await
+                    .success_payload_or_error()
+                    .ok()
+                    .flatten()
+                    .and_then(|p| serde_json::from_slice::<RigLoadOutput>(&p.data).ok())
+                    .map(|load| load.polecats);
+                if let Some(live) = live {
+                    tracked_polecats.retain(|id| live.contains(id));
+                    last_alive.retain(|id, _| live.contains(id));
+                    dead_counts.retain(|id, _| live.contains(id));
+                    idle_polecats.retain(|id| live.contains(id));
+                    for polecat_id in live {
+                        if !tracked_polecats.contains(&polecat_id) {
+                            tracked_polecats.push(polecat_id);
+                        }
                     }
                 }
 
                 let mut dead_polecats: Vec<String> = vec![];
+                let mut newly_idle: Vec<(String, u64)> = vec![];
 
                 // Heartbeat check each tracked polecat
                 for polecat_id in &tracked_polecats {
@@ -80,10 +130,16 @@ pub async fn witness_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
                         })
                         .await;
 
-                    let alive = result.completed_ok();
+                    let alive = result
+                        .success_payload_or_error()
+                        .ok()
+                        .flatten()
+                        .and_then(|p| serde_json::from_slice::<HeartbeatOutput>(&p.data).ok())
+                        .is_some_and(|hb| hb.alive);
                     let was_alive = last_alive.insert(polecat_id.clone(), alive);
 
                     if !alive {
+                        idle_polecats.remove(polecat_id);
                         let dead_count = dead_counts.entry(polecat_id.clone()).or_insert(0);
                         *dead_count += 1;
 
@@ -102,9 +158,81 @@ pub async fn witness_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
                     } else {
                         // Reset dead count if alive
                         dead_counts.insert(polecat_id.clone(), 0);
+
+                        // Alive — but is it still producing output?
+                        let input = PaneActivityInput {
+                            agent_id: polecat_id.clone(),
+                            idle_after_secs,
+                        };
+                        let activity = ctx
+                            .activity(ActivityOptions {
+                                activity_type: "check_pane_activity".to_string(),
+                                input: input.as_json_payload()?,
+                                start_to_close_timeout: Some(Duration::from_secs(10)),
+                                ..Default::default()
+                            })
+                            .await
+                            .success_payload_or_error()
+                            .ok()
+                            .flatten()
+                            .and_then(|p| {
+                                serde_json::from_slice::<PaneActivityOutput>(&p.data).ok()
+                            });
+                        if let Some(activity) = activity {
+                            if !activity.idle {
+                                idle_polecats.remove(polecat_id);
+                            } else if idle_polecats.insert(polecat_id.clone()) {
+                                // Escalate only on the transition into idle
+                                let secs = activity.idle_secs.unwrap_or_default();
+                                newly_idle.push((polecat_id.clone(), secs));
+                            }
+                        }
                     }
                 }
 
+                if !newly_idle.is_empty() {
+                    for (polecat_id, _) in &newly_idle {
+                        let _ = ctx
+                            .signal_workflow(SignalWorkflowOptions::new(
+                                polecat_id,
+                                "",
+                                SIGNAL_POLECAT_STUCK,
+                                vec![],
+                            ))
+                            .await;
+                    }
+
+                    let message = format!(
+                        "Witness alert: {} idle polecats on rig {}: {}",
+                        newly_idle.len(),
+                        rig,
+                        newly_idle
+                            .iter()
+                            .map(|(id, secs)| format!("{id} (no output for {}m)", secs / 60))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                    tracing::warn!("{message}");
+
+                    let input = NotificationInput {
+                        channel: "signal".to_string(),
+                        target: gtr_core::state::mayor_workflow_id(),
+                        subject: format!("Witness: idle polecats on {rig}"),
+                        message,
+                    };
+
+                    let _ = ctx
+                        .activity(ActivityOptions {
+                            activity_type: "send_notification".to_string(),
+                            input: input.as_json_payload()?,
+                            start_to_close_timeout: Some(Duration::from_secs(30)),
+                            ..Default::default()
+                        })
+                        .await;
+
+                    alerts_sent += newly_idle.len() as u64;
+                }
+
                 if !dead_polecats.is_empty() {
                     let message = format!(
                         "Witness alert: {} dead polecats on rig {}: {}",
@@ -116,7 +244,7 @@ pub async fn witness_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
 
                     let input = NotificationInput {
                         channel: "signal".to_string(),
-                        target: "mayor".to_string(),
+                        target: gtr_core::state::mayor_workflow_id(),
                         subject: format!("Witness: dead polecats on {rig}"),
                         message,
                     };
@@ -136,11 +264,12 @@ pub async fn witness_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::E
                     let alive_count = last_alive.values().filter(|&&v| v).count();
                     let input = NotificationInput {
                         channel: "signal".to_string(),
-                        target: "mayor".to_string(),
+                        target: gtr_core::state::mayor_workflow_id(),
                         subject: format!("Witness health: rig {rig}"),
                         message: format!(
-                            "Check #{checks}, tracking {} polecats ({alive_count} alive), {alerts_sent} alerts total",
-                            tracked_polecats.len()
+                            "Check #{checks}, tracking {} polecats ({alive_count} alive, {} idle), {alerts_sent} alerts total",
+                            tracked_polecats.len(),
+                            idle_polecats.len()
                         ),
                     };
 
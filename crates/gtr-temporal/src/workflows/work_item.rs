@@ -1,27 +1,71 @@
+use std::collections::BTreeSet;
 use std::time::Duration;
 
 use futures_util::StreamExt;
+use gtr_core::types::WorkItem;
 use temporalio_common::protos::coresdk::AsJsonPayloadExt;
-use temporalio_sdk::{ActivityOptions, WfContext, WfExitValue};
+use temporalio_sdk::{
+    ActivityOptions, ChildWorkflowOptions, SignalWorkflowOptions, WfContext, WfExitValue,
+};
 
-use crate::activities::notification::NotificationInput;
+use crate::activities::notification::{EscalationPolicy, NotificationInput};
 use crate::signals::*;
+use crate::workflows::timer_or_never;
+
+/// Search attribute with the item's status while it runs
+pub const SA_STATUS: &str = "GtrStatus";
+/// Search attribute with the agent the item is assigned to, if any
+pub const SA_ASSIGNEE: &str = "GtrAssignee";
+/// Search attribute with the rig the item is meant for, if any
+pub const SA_RIG: &str = "GtrRig";
+/// Search attribute with the item's priority, `P0`..`P3`
+pub const SA_PRIORITY: &str = "GtrPriority";
+/// The Keyword search attributes `work_item_wf` keeps current, so
+/// `rgt work list` can filter on the server. They must be registered with
+/// the Temporal server before any work item runs.
+pub const SEARCH_ATTRIBUTES: [&str; 4] = [SA_STATUS, SA_ASSIGNEE, SA_RIG, SA_PRIORITY];
 
 const STALE_TIMEOUT: Duration = Duration::from_secs(4 * 60 * 60); // 4 hours
+const DEFAULT_MAX_RE_ESCALATIONS: u32 = 2;
+
+/// Decode `work_item_wf`'s input and the convoy it reports to. A full
+/// [`WorkItem`] is the usual form, or `(item, convoy)` from a convoy; the
+/// older `(id, title)` and `(id, title, convoy)` tuples are still accepted.
+pub fn parse_input(data: &[u8]) -> Option<(WorkItem, Option<String>)> {
+    // Tuples first: serde would also read a JSON array as a `WorkItem`,
+    // taking the convoy ID for the description
+    serde_json::from_slice::<(String, String, Option<String>)>(data)
+        .or_else(|_| {
+            serde_json::from_slice::<(String, String)>(data).map(|(id, title)| (id, title, None))
+        })
+        .map(|(id, title, convoy)| (WorkItem::new(id, title), convoy))
+        .or_else(|_| serde_json::from_slice::<(WorkItem, Option<String>)>(data))
+        .or_else(|_| serde_json::from_slice::<WorkItem>(data).map(|item| (item, None)))
+        .ok()
+}
 
 pub async fn work_item_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow::Error> {
-    // Parse input: (id, title)
     let args = ctx.get_args();
-    let (id, title) = if let Some(payload) = args.first() {
-        serde_json::from_slice::<(String, String)>(&payload.data)
-            .unwrap_or(("unknown".into(), "untitled".into()))
-    } else {
-        ("unknown".into(), "untitled".into())
-    };
+    let (mut item, convoy) = args
+        .first()
+        .and_then(|payload| parse_input(&payload.data))
+        .unwrap_or_else(|| (WorkItem::new("unknown", "untitled"), None));
+    // Finished items report to their convoy, or to the item they were split from
+    let convoy = convoy.or_else(|| item.parent.as_ref().map(|p| p.0.clone()));
+    let id = item.id.0.clone();
+    let title = item.title.clone();
 
     let mut status = "pending".to_string();
     let mut assigned_to: Option<String> = None;
+    let mut comments: Vec<WorkItemComment> = Vec::new();
+    let mut usage = WorkItemStats::default();
+    // Parts of a split item; it completes when all of them have finished
+    let mut parts: Vec<String> = Vec::new();
+    let mut parts_finished: BTreeSet<String> = BTreeSet::new();
     let mut escalation_level: u32 = 0;
+    // An open escalation is re-escalated each stale period until acknowledged
+    let mut escalation_acked = false;
+    let mut re_escalations: u32 = 0;
 
     // Set up signal channels
     let mut assign_ch = ctx.make_signal_channel(SIGNAL_ASSIGN);
@@ -32,160 +76,325 @@ pub async fn work_item_wf(ctx: WfContext) -> Result<WfExitValue<String>, anyhow:
     let mut release_ch = ctx.make_signal_channel(SIGNAL_RELEASE);
     let mut heartbeat_ch = ctx.make_signal_channel(SIGNAL_HEARTBEAT);
     let mut escalate_ch = ctx.make_signal_channel(SIGNAL_ESCALATE);
+    let mut ack_ch = ctx.make_signal_channel(SIGNAL_ESCALATION_ACK);
+    let mut resolve_ch = ctx.make_signal_channel(SIGNAL_ESCALATION_RESOLVE);
+    // Polecats report their outcome here too; `rgt work show` reads it back
+    let mut report_ch = ctx.make_signal_channel(SIGNAL_POLECAT_REPORT);
+    let mut triage_ch = ctx.make_signal_channel(SIGNAL_WORK_TRIAGE);
+    let mut comment_ch = ctx.make_signal_channel(SIGNAL_WORK_COMMENT);
+    let mut split_ch = ctx.make_signal_channel(SIGNAL_WORK_SPLIT);
+    let mut usage_ch = ctx.make_signal_channel(SIGNAL_WORK_USAGE);
+    let mut part_done_ch = ctx.make_signal_channel(SIGNAL_ITEM_DONE);
 
     tracing::info!("WorkItem {id} started: {title}");
 
+    let policy = ctx
+        .activity(ActivityOptions {
+            activity_type: "load_escalation_policy".to_string(),
+            input: ().as_json_payload()?,
+            start_to_close_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        })
+        .await
+        .success_payload_or_error()
+        .ok()
+        .flatten()
+        .and_then(|p| serde_json::from_slice::<EscalationPolicy>(&p.data).ok());
+    let (stale_timeout, max_re_escalations) = policy
+        .map(|p| (Duration::from_secs(p.stale_after_secs), p.max_re_escalations))
+        .unwrap_or((STALE_TIMEOUT, DEFAULT_MAX_RE_ESCALATIONS));
+
+    // What the search attributes last said, to upsert only on a change
+    let mut published: Option<(String, Option<String>)> = None;
+
     // Main signal loop — wait for signals and transition state
     loop {
-        // Use a staleness timer when work is in progress
-        let use_timer = status == "in_progress" || status == "assigned";
-
-        if use_timer {
-            tokio::select! {
-                biased;
-                Some(signal) = assign_ch.next() => {
-                    handle_assign(&id, &mut status, &mut assigned_to, signal);
-                }
-                Some(_) = start_ch.next() => {
-                    if status == "assigned" {
-                        status = "in_progress".to_string();
-                        escalation_level = 0;
-                        tracing::info!("WorkItem {id} in progress");
+        if published.as_ref() != Some(&(status.clone(), assigned_to.clone())) {
+            upsert_search_attributes(&ctx, &item, &status, assigned_to.as_deref());
+            published = Some((status.clone(), assigned_to.clone()));
+        }
+
+        // Staleness timer while work is in progress or an escalation is
+        // unacknowledged; any signal starts it over
+        let stale_after = (status == "in_progress"
+            || status == "assigned"
+            || (escalation_level > 0 && !escalation_acked))
+            .then_some(stale_timeout);
+
+        tokio::select! {
+            biased;
+            Some(signal) = assign_ch.next() => {
+                handle_assign(&id, &mut status, &mut assigned_to, signal);
+            }
+            Some(_) = start_ch.next() => {
+                if status == "assigned" {
+                    status = "in_progress".to_string();
+                    if escalation_level > 0 {
+                        report_escalation(&ctx, &id, &title, escalation_level, "resolved").await?;
                     }
+                    escalation_level = 0;
+                    tracing::info!("WorkItem {id} in progress");
                 }
-                Some(_) = complete_ch.next() => {
-                    if status == "in_progress" || status == "assigned" {
-                        status = "done".to_string();
-                        tracing::info!("WorkItem {id} completed");
-                        return Ok(WfExitValue::Normal(
-                            serde_json::to_string(&WorkItemState {
-                                id: id.clone(),
-                                title: title.clone(),
-                                status: status.clone(),
-                                assigned_to: assigned_to.clone(),
-                            })?
-                        ));
-                    }
+            }
+            // A tracking item waits for its parts instead
+            Some(_) = complete_ch.next(), if status != "tracking" => {
+                // Whatever the status, the refinery merging the branch means it's done
+                if escalation_level > 0 {
+                    report_escalation(&ctx, &id, &title, escalation_level, "resolved").await?;
                 }
-                Some(signal) = fail_ch.next() => {
-                    if let Some(payload) = signal.input.first() {
-                        if let Ok(data) = serde_json::from_slice::<FailSignal>(&payload.data) {
-                            status = "failed".to_string();
-                            tracing::warn!("WorkItem {id} failed: {}", data.reason);
-                            return Ok(WfExitValue::Normal(
-                                serde_json::to_string(&WorkItemState {
-                                    id, title, status, assigned_to,
-                                })?
-                            ));
+                status = "done".to_string();
+                tracing::info!("WorkItem {id} completed");
+                notify_convoy(&ctx, convoy.as_deref(), &id, false).await;
+                return Ok(WfExitValue::Normal(
+                    serde_json::to_string(&WorkItemState::new(&item, status, assigned_to, comments, usage))?
+                ));
+            }
+            Some(signal) = fail_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(data) = serde_json::from_slice::<FailSignal>(&payload.data) {
+                        if escalation_level > 0 {
+                            report_escalation(&ctx, &id, &title, escalation_level, "resolved").await?;
                         }
+                        status = "failed".to_string();
+                        tracing::warn!("WorkItem {id} failed: {}", data.reason);
+                        notify_convoy(&ctx, convoy.as_deref(), &id, true).await;
+                        return Ok(WfExitValue::Normal(
+                            serde_json::to_string(&WorkItemState::new(&item, status, assigned_to, comments, usage))?
+                        ));
                     }
                 }
-                Some(_) = close_ch.next() => {
-                    status = "closed".to_string();
-                    tracing::info!("WorkItem {id} closed");
-                    return Ok(WfExitValue::Normal(
-                        serde_json::to_string(&WorkItemState {
-                            id, title, status, assigned_to,
-                        })?
-                    ));
-                }
-                Some(_) = release_ch.next() => {
-                    if status == "assigned" || status == "in_progress" {
-                        assigned_to = None;
-                        status = "pending".to_string();
-                        escalation_level = 0;
-                        tracing::info!("WorkItem {id} released back to pending");
-                    }
+            }
+            Some(_) = close_ch.next() => {
+                if escalation_level > 0 {
+                    report_escalation(&ctx, &id, &title, escalation_level, "resolved").await?;
                 }
-                Some(signal) = heartbeat_ch.next() => {
-                    if let Some(payload) = signal.input.first() {
-                        if let Ok(hb) = serde_json::from_slice::<HeartbeatSignal>(&payload.data) {
-                            tracing::debug!("WorkItem {id} heartbeat: {:?}", hb.progress);
-                        }
+                status = "closed".to_string();
+                tracing::info!("WorkItem {id} closed");
+                notify_convoy(&ctx, convoy.as_deref(), &id, false).await;
+                return Ok(WfExitValue::Normal(
+                    serde_json::to_string(&WorkItemState::new(&item, status, assigned_to, comments, usage))?
+                ));
+            }
+            Some(_) = release_ch.next() => {
+                if status == "assigned" || status == "in_progress" {
+                    assigned_to = None;
+                    status = "pending".to_string();
+                    if escalation_level > 0 {
+                        report_escalation(&ctx, &id, &title, escalation_level, "resolved").await?;
                     }
-                    // Heartbeat resets the timer by continuing the loop
-                }
-                Some(_) = escalate_ch.next() => {
-                    escalation_level += 1;
-                    tracing::warn!("WorkItem {id} escalated (level {escalation_level})");
-                    send_escalation_notification(&ctx, &id, &title, escalation_level).await?;
-                }
-                _ = ctx.timer(STALE_TIMEOUT) => {
-                    // Staleness timeout — auto-escalate
-                    escalation_level += 1;
-                    tracing::warn!("WorkItem {id} stale — auto-escalating (level {escalation_level})");
-                    send_escalation_notification(&ctx, &id, &title, escalation_level).await?;
+                    escalation_level = 0;
+                    tracing::info!("WorkItem {id} released back to pending");
                 }
             }
-        } else {
-            // No timer needed when pending — just wait for signals
-            tokio::select! {
-                biased;
-                Some(signal) = assign_ch.next() => {
-                    handle_assign(&id, &mut status, &mut assigned_to, signal);
-                }
-                Some(_) = start_ch.next() => {
-                    if status == "assigned" {
-                        status = "in_progress".to_string();
-                        tracing::info!("WorkItem {id} in progress");
+            Some(signal) = heartbeat_ch.next() => {
+                if let Some(payload) = signal.input.first() {
+                    if let Ok(hb) = serde_json::from_slice::<HeartbeatSignal>(&payload.data) {
+                        tracing::debug!("WorkItem {id} heartbeat: {:?}", hb.progress);
                     }
                 }
-                Some(_) = complete_ch.next() => {
-                    if status == "in_progress" || status == "assigned" {
-                        status = "done".to_string();
-                        tracing::info!("WorkItem {id} completed");
-                        return Ok(WfExitValue::Normal(
-                            serde_json::to_string(&WorkItemState {
-                                id: id.clone(),
-                                title: title.clone(),
-                                status: status.clone(),
-                                assigned_to: assigned_to.clone(),
-                            })?
-                        ));
-                    }
+                // Heartbeat resets the timer by continuing the loop
+            }
+            Some(signal) = report_ch.next() => {
+                handle_report(&id, signal);
+            }
+            Some(signal) = triage_ch.next() => {
+                handle_triage(&id, signal);
+            }
+            Some(signal) = comment_ch.next() => {
+                handle_comment(&ctx, &id, &mut comments, signal);
+            }
+            Some(signal) = usage_ch.next() => {
+                handle_usage(&id, &mut usage, signal);
+            }
+            Some(signal) = split_ch.next() => {
+                split(&ctx, &mut item, &mut parts, signal).await?;
+                if !parts.is_empty() && status != "tracking" {
+                    status = "tracking".to_string();
+                    tracing::info!("WorkItem {id} split — tracking {} part(s)", parts.len());
                 }
-                Some(signal) = fail_ch.next() => {
-                    if let Some(payload) = signal.input.first() {
-                        if let Ok(data) = serde_json::from_slice::<FailSignal>(&payload.data) {
-                            status = "failed".to_string();
-                            tracing::warn!("WorkItem {id} failed: {}", data.reason);
-                            return Ok(WfExitValue::Normal(
-                                serde_json::to_string(&WorkItemState {
-                                    id, title, status, assigned_to,
-                                })?
-                            ));
-                        }
+            }
+            Some(signal) = part_done_ch.next(), if status == "tracking" => {
+                if part_finished(&id, &parts, &mut parts_finished, signal) {
+                    if escalation_level > 0 {
+                        report_escalation(&ctx, &id, &title, escalation_level, "resolved").await?;
                     }
-                }
-                Some(_) = close_ch.next() => {
-                    status = "closed".to_string();
-                    tracing::info!("WorkItem {id} closed");
+                    status = "done".to_string();
+                    tracing::info!("WorkItem {id} completed — all {} part(s) finished", parts.len());
+                    notify_convoy(&ctx, convoy.as_deref(), &id, false).await;
                     return Ok(WfExitValue::Normal(
-                        serde_json::to_string(&WorkItemState {
-                            id, title, status, assigned_to,
-                        })?
+                        serde_json::to_string(&WorkItemState::new(&item, status, assigned_to, comments, usage))?
                     ));
                 }
-                Some(_) = release_ch.next() => {
-                    if status == "assigned" || status == "in_progress" {
-                        assigned_to = None;
-                        status = "pending".to_string();
-                        tracing::info!("WorkItem {id} released back to pending");
-                    }
+            }
+            Some(_) = escalate_ch.next() => {
+                escalation_level += 1;
+                escalation_acked = false;
+                re_escalations = 0;
+                tracing::warn!("WorkItem {id} escalated (level {escalation_level})");
+                send_escalation_notification(&ctx, &id, &title, escalation_level).await?;
+                report_escalation(&ctx, &id, &title, escalation_level, "open").await?;
+            }
+            Some(_) = ack_ch.next() => {
+                if escalation_level > 0 && !escalation_acked {
+                    escalation_acked = true;
+                    tracing::info!("WorkItem {id} escalation acknowledged (level {escalation_level})");
+                    report_escalation(&ctx, &id, &title, escalation_level, "acked").await?;
                 }
-                Some(_) = heartbeat_ch.next() => {
-                    // Heartbeat while pending — ignore
+            }
+            Some(_) = resolve_ch.next() => {
+                if escalation_level > 0 {
+                    tracing::info!("WorkItem {id} escalation resolved (level {escalation_level})");
+                    report_escalation(&ctx, &id, &title, escalation_level, "resolved").await?;
+                    escalation_level = 0;
+                    escalation_acked = false;
+                    re_escalations = 0;
                 }
-                Some(_) = escalate_ch.next() => {
+            }
+            _ = timer_or_never(&ctx, stale_after) => {
+                if escalation_level == 0 {
+                    // Staleness timeout — auto-escalate
+                    escalation_level = 1;
+                    escalation_acked = false;
+                    re_escalations = 0;
+                    tracing::warn!("WorkItem {id} stale — auto-escalating (level {escalation_level})");
+                } else if escalation_acked {
+                    tracing::debug!("WorkItem {id} stale but escalation acknowledged");
+                    continue;
+                } else if re_escalations < max_re_escalations {
                     escalation_level += 1;
-                    tracing::warn!("WorkItem {id} manually escalated (level {escalation_level})");
-                    send_escalation_notification(&ctx, &id, &title, escalation_level).await?;
+                    re_escalations += 1;
+                    tracing::warn!(
+                        "WorkItem {id} escalation unacknowledged — re-escalating (level {escalation_level}, {re_escalations}/{max_re_escalations})"
+                    );
+                } else {
+                    tracing::warn!(
+                        "WorkItem {id} escalation still unacknowledged after {max_re_escalations} re-escalations"
+                    );
+                    continue;
                 }
+                send_escalation_notification(&ctx, &id, &title, escalation_level).await?;
+                report_escalation(&ctx, &id, &title, escalation_level, "open").await?;
             }
         }
     }
 }
 
+/// Publish the item's status, assignee, rig and priority for `rgt work list`.
+/// Unset values are published as empty strings.
+fn upsert_search_attributes(
+    ctx: &WfContext,
+    item: &WorkItem,
+    status: &str,
+    assigned_to: Option<&str>,
+) {
+    let priority = item.priority.to_string();
+    let values = [
+        (SA_STATUS, status),
+        (SA_ASSIGNEE, assigned_to.unwrap_or_default()),
+        (SA_RIG, item.rig.as_deref().unwrap_or_default()),
+        (SA_PRIORITY, priority.as_str()),
+    ];
+    ctx.upsert_search_attributes(
+        values
+            .into_iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.as_json_payload().ok()?))),
+    );
+}
+
+fn handle_usage(id: &str, usage: &mut WorkItemStats, signal: temporalio_sdk::SignalData) {
+    if let Some(payload) = signal.input.first() {
+        if let Ok(data) = serde_json::from_slice::<WorkUsageSignal>(&payload.data) {
+            tracing::info!(
+                "WorkItem {id}: {} used {} in / {} out tokens (${:.2})",
+                data.agent,
+                data.input_tokens,
+                data.output_tokens,
+                data.cost_usd
+            );
+            usage.add_usage(&data);
+        }
+    }
+}
+
+/// Start a child work item per title in a `work_split` signal, numbered on
+/// from the existing parts as `<id>.<n>`. Parts inherit the parent's
+/// priority, labels and rig, and are added to its `depends_on`.
+async fn split(
+    ctx: &WfContext,
+    item: &mut WorkItem,
+    parts: &mut Vec<String>,
+    signal: temporalio_sdk::SignalData,
+) -> anyhow::Result<()> {
+    let Some(data) = signal
+        .input
+        .first()
+        .and_then(|p| serde_json::from_slice::<WorkSplitSignal>(&p.data).ok())
+    else {
+        return Ok(());
+    };
+    for title in data.titles {
+        let part_id = format!("{}.{}", item.id.0, parts.len() + 1);
+        let mut part = WorkItem::new(part_id.as_str(), title);
+        part.priority = item.priority;
+        part.labels = item.labels.clone();
+        part.rig = item.rig.clone();
+        part.parent = Some(item.id.clone());
+        let child = ctx.child_workflow(ChildWorkflowOptions {
+            workflow_id: part_id.clone(),
+            workflow_type: "work_item_wf".to_string(),
+            input: vec![part.as_json_payload()?],
+            ..Default::default()
+        });
+        if child.start(ctx).await.into_started().is_some() {
+            tracing::info!("WorkItem {}: started part {part_id}", item.id.0);
+            item.depends_on.push(part.id);
+            parts.push(part_id);
+        } else {
+            tracing::warn!("WorkItem {}: failed to start part {part_id}", item.id.0);
+        }
+    }
+    Ok(())
+}
+
+/// Record a part of a split item as finished. True once all parts are.
+fn part_finished(
+    id: &str,
+    parts: &[String],
+    finished: &mut BTreeSet<String>,
+    signal: temporalio_sdk::SignalData,
+) -> bool {
+    if let Some(payload) = signal.input.first() {
+        if let Ok(done) = serde_json::from_slice::<ItemDoneSignal>(&payload.data) {
+            tracing::info!("WorkItem {id}: part {} finished", done.work_item_id);
+            finished.insert(done.work_item_id);
+        }
+    }
+    parts.iter().all(|part| finished.contains(part))
+}
+
+/// Tell the owning convoy, or the item this one was split from, that it's
+/// finished and whether it failed. For a convoy that frees a dispatch slot.
+/// Errors are ignored — either may already be closed.
+async fn notify_convoy(ctx: &WfContext, convoy: Option<&str>, id: &str, failed: bool) {
+    let Some(convoy) = convoy else {
+        return;
+    };
+    let done = ItemDoneSignal {
+        work_item_id: id.to_string(),
+        failed,
+    };
+    if let Ok(payload) = done.as_json_payload() {
+        let _ = ctx
+            .signal_workflow(SignalWorkflowOptions::new(
+                convoy,
+                "",
+                SIGNAL_ITEM_DONE,
+                vec![payload],
+            ))
+            .await;
+    }
+}
+
 fn handle_assign(
     id: &str,
     status: &mut String,
@@ -203,6 +412,49 @@ fn handle_assign(
     }
 }
 
+fn handle_report(id: &str, signal: temporalio_sdk::SignalData) {
+    if let Some(payload) = signal.input.first() {
+        if let Ok(report) = serde_json::from_slice::<PolecatReportSignal>(&payload.data) {
+            tracing::info!(
+                "WorkItem {id}: {} finished ({}) on {}",
+                report.polecat_id,
+                report.status,
+                report.branch
+            );
+        }
+    }
+}
+
+fn handle_triage(id: &str, signal: temporalio_sdk::SignalData) {
+    if let Some(payload) = signal.input.first() {
+        if let Ok(triage) = serde_json::from_slice::<TriageSignal>(&payload.data) {
+            tracing::info!(
+                "WorkItem {id} triaged: {} complexity, rig {}",
+                triage.complexity,
+                triage.rig.as_deref().unwrap_or("-")
+            );
+        }
+    }
+}
+
+fn handle_comment(
+    ctx: &WfContext,
+    id: &str,
+    comments: &mut Vec<WorkItemComment>,
+    signal: temporalio_sdk::SignalData,
+) {
+    if let Some(payload) = signal.input.first() {
+        if let Ok(data) = serde_json::from_slice::<WorkCommentSignal>(&payload.data) {
+            tracing::info!("WorkItem {id}: comment from {}", data.author);
+            comments.push(WorkItemComment {
+                at: ctx.workflow_time().map(Into::into).unwrap_or_default(),
+                author: data.author,
+                text: data.text,
+            });
+        }
+    }
+}
+
 async fn send_escalation_notification(
     ctx: &WfContext,
     id: &str,
@@ -211,7 +463,7 @@ async fn send_escalation_notification(
 ) -> anyhow::Result<()> {
     let input = NotificationInput {
         channel: "signal".to_string(),
-        target: "mayor".to_string(),
+        target: gtr_core::state::mayor_workflow_id(),
         subject: format!("Escalation L{level}: {id}"),
         message: format!("Work item '{title}' ({id}) escalated to level {level}"),
     };
@@ -227,3 +479,28 @@ async fn send_escalation_notification(
 
     Ok(())
 }
+
+/// Keep the mayor's list of open escalations current. Errors are ignored —
+/// the mayor may not be running.
+async fn report_escalation(
+    ctx: &WfContext,
+    id: &str,
+    title: &str,
+    level: u32,
+    status: &str,
+) -> anyhow::Result<()> {
+    let update = EscalationUpdateSignal {
+        work_item_id: id.to_string(),
+        title: title.to_string(),
+        level,
+        status: status.to_string(),
+    };
+    let sig_opts = SignalWorkflowOptions::new(
+        gtr_core::state::mayor_workflow_id(),
+        "",
+        SIGNAL_ESCALATION_UPDATE,
+        vec![update.as_json_payload()?],
+    );
+    let _ = ctx.signal_workflow(sig_opts).await;
+    Ok(())
+}
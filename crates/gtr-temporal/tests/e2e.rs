@@ -0,0 +1,385 @@
+//! End-to-end workflow tests against an ephemeral Temporal dev server.
+//!
+//! Gated behind the `temporal-it` feature and needs the `temporal` CLI, `git`
+//! and `tmux`. Each run starts `temporal server start-dev` on a free port
+//! (or uses the server at `GTR_IT_TEMPORAL_ADDRESS`), runs the worker
+//! in-process, and points `HOME` at a temp dir so rigs and runtime state
+//! never touch the real `~/.gtr`.
+//!
+//! Run: `cargo test -p gtr-temporal --features temporal-it --test e2e`
+
+#![cfg(feature = "temporal-it")]
+
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use temporalio_common::protos::coresdk::AsJsonPayloadExt;
+use temporalio_sdk_core::{ClientOptions, RetryClient, Url, WorkflowClientTrait};
+
+use gtr_temporal::signals::{
+    PolecatDoneSignal, RefineryEnqueueSignal, SIGNAL_MAYOR_STOP, SIGNAL_POLECAT_DONE,
+    SIGNAL_REFINERY_ENQUEUE, SIGNAL_REFINERY_STOP, SIGNAL_UNREGISTER_AGENT,
+};
+use gtr_temporal::workflows::work_item::SEARCH_ATTRIBUTES;
+
+type Client = RetryClient<temporalio_sdk_core::Client>;
+
+const RIG: &str = "e2e";
+const QUEUE: &str = "ci";
+
+/// Each test points `HOME` at its own temp dir, so they take turns.
+static SERIAL: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+const MOCK_SCRIPT: &str = r#"
+[[steps]]
+write = { path = "e2e.txt", contents = "done\n" }
+
+[[steps]]
+commit = "Add e2e file"
+"#;
+
+/// A dev server owned by the test; killed on drop.
+struct DevServer {
+    child: Option<Child>,
+    url: String,
+}
+
+impl DevServer {
+    /// `None` when no server was configured and the CLI isn't installed.
+    fn start() -> Option<Self> {
+        if let Ok(url) = std::env::var("GTR_IT_TEMPORAL_ADDRESS") {
+            return Some(Self { child: None, url });
+        }
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .ok()?
+            .port();
+        let child = Command::new("temporal")
+            .args(["server", "start-dev", "--headless", "--log-level", "error"])
+            .args(["--port", &port.to_string()])
+            .args(
+                SEARCH_ATTRIBUTES
+                    .iter()
+                    .flat_map(|name| ["--search-attribute".to_string(), format!("{name}=Keyword")]),
+            )
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        Some(Self {
+            child: Some(child),
+            url: format!("http://127.0.0.1:{port}"),
+        })
+    }
+
+    async fn connect(&self) -> anyhow::Result<Client> {
+        let opts = ClientOptions::builder()
+            .target_url(Url::parse(&self.url)?)
+            .client_name("gtr-e2e".to_string())
+            .client_version(env!("CARGO_PKG_VERSION").to_string())
+            .identity("gtr-e2e".to_string())
+            .build();
+        // The frontend takes a few seconds to come up
+        let mut last_err = None;
+        for _ in 0..60 {
+            match opts.connect("default", None).await {
+                Ok(client) => return Ok(client),
+                Err(e) => last_err = Some(e),
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        anyhow::bail!("dev server at {} never came up: {last_err:?}", self.url)
+    }
+}
+
+impl Drop for DevServer {
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+fn tool_available(tool: &str, version_arg: &str) -> bool {
+    Command::new(tool)
+        .arg(version_arg)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("failed to run git");
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// Lay out a rig under `$HOME/.gtr` the way `rgt rig add` does: a bare clone
+/// of `origin.git`, plus a `refinery` worktree on main for the merge queue.
+/// Returns the refinery worktree path.
+fn setup_rig(home: &Path) -> std::path::PathBuf {
+    std::fs::write(
+        home.join(".gitconfig"),
+        "[user]\n\tname = gtr-e2e\n\temail = e2e@gtr.test\n",
+    )
+    .unwrap();
+
+    git(home, &["init", "--bare", "-b", "main", "origin.git"]);
+    git(home, &["clone", "origin.git", "seed"]);
+    let seed = home.join("seed");
+    std::fs::write(seed.join("README.md"), "# e2e\n").unwrap();
+    git(&seed, &["add", "."]);
+    git(&seed, &["commit", "-m", "Initial commit"]);
+    git(&seed, &["push", "origin", "main"]);
+
+    let rig_dir = gtr_core::dirs::rig_dir(RIG);
+    std::fs::create_dir_all(&rig_dir).unwrap();
+    let origin = home.join("origin.git");
+    git(
+        &rig_dir,
+        &["clone", "--bare", origin.to_str().unwrap(), ".repo.git"],
+    );
+    git(
+        &rig_dir.join(".repo.git"),
+        &["worktree", "add", "../refinery", "main"],
+    );
+
+    // The named queue swaps `cargo test` for a no-op test step
+    let rigs_toml = format!(
+        "[[rigs]]\nname = \"{RIG}\"\npath = \"{}\"\n\n[rigs.queues.{QUEUE}]\ntest_command = [\"true\"]\n",
+        rig_dir.display()
+    );
+    let config_dir = gtr_core::dirs::config_dir();
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("rigs.toml"), rigs_toml).unwrap();
+
+    rig_dir.join("refinery")
+}
+
+async fn workflow_status(client: &Client, id: &str) -> Option<i32> {
+    client
+        .describe_workflow_execution(id.to_string(), None)
+        .await
+        .ok()?
+        .workflow_execution_info
+        .map(|info| info.status)
+}
+
+async fn wait_until(what: &str, timeout: Duration, mut check: impl FnMut() -> bool) {
+    let deadline = tokio::time::Instant::now() + timeout;
+    while !check() {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "timed out waiting for {what}"
+        );
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+async fn signal<T: serde::Serialize>(
+    client: &Client,
+    id: &str,
+    name: &str,
+    payload: &T,
+) -> anyhow::Result<()> {
+    client
+        .signal_workflow_execution(
+            id.to_string(),
+            String::new(),
+            name.to_string(),
+            Some(payload.as_json_payload()?.into()),
+            None,
+        )
+        .await?;
+    Ok(())
+}
+
+/// sling → polecat (mock runtime) → done → refinery merge, checked against
+/// the origin repo the refinery pushes to.
+#[tokio::test(flavor = "multi_thread")]
+async fn sling_polecat_done_refinery() -> anyhow::Result<()> {
+    if !tool_available("git", "--version") || !tool_available("tmux", "-V") {
+        eprintln!("Skipping test -- git and tmux are required");
+        return Ok(());
+    }
+    let Some(server) = DevServer::start() else {
+        eprintln!("Skipping test -- temporal CLI not installed");
+        return Ok(());
+    };
+    let _serial = SERIAL.lock().await;
+
+    let home = tempfile::tempdir()?;
+    // Set before the worker starts: every gtr path resolves from HOME
+    std::env::set_var("HOME", home.path());
+    let refinery_dir = setup_rig(home.path());
+    // The polecat's scripted "agent" commits one file and then idles
+    let script = home.path().join("mock-agent.toml");
+    std::fs::write(&script, MOCK_SCRIPT)?;
+    std::env::set_var(gtr_core::mock::MOCK_SCRIPT_ENV, &script);
+
+    let client = server.connect().await?;
+    let worker_url = server.url.clone();
+    let worker = tokio::spawn(async move {
+        if let Err(e) = gtr_temporal::worker::run_worker_at(&worker_url).await {
+            eprintln!("worker exited: {e}");
+        }
+    });
+
+    // Merge queue for the rig
+    let refinery_id = gtr_core::state::merge_queue_workflow_id(RIG, QUEUE);
+    let refinery_input = (refinery_dir.to_string_lossy().to_string(), RIG, QUEUE);
+    client
+        .start_workflow(
+            vec![refinery_input.as_json_payload()?],
+            "work".to_string(),
+            refinery_id.clone(),
+            "refinery_wf".to_string(),
+            None,
+            Default::default(),
+        )
+        .await?;
+
+    // sling: what `rgt sling <id> --target e2e --agent mock` starts
+    let work_item_id = "wi-e2e";
+    let polecat_id = gtr_core::state::polecat_workflow_id(RIG, "nux");
+    let polecat_input = ("nux", RIG, work_item_id, "E2E change", "mock");
+    client
+        .start_workflow(
+            vec![polecat_input.as_json_payload()?],
+            "work".to_string(),
+            polecat_id.clone(),
+            "polecat_wf".to_string(),
+            None,
+            Default::default(),
+        )
+        .await?;
+
+    let worktree = gtr_core::dirs::polecat_dir(RIG, "nux");
+    wait_until("polecat session", Duration::from_secs(60), || {
+        worktree.join(".git").exists() && gtr_temporal::pty::is_alive(&polecat_id)
+    })
+    .await;
+
+    let branch = format!("polecat/nux/{work_item_id}");
+    assert_eq!(git(&worktree, &["branch", "--show-current"]), branch);
+    wait_until("mock agent commit", Duration::from_secs(30), || {
+        Command::new("git")
+            .args(["log", "-1", "--format=%s"])
+            .current_dir(&worktree)
+            .output()
+            .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).trim() == "Add e2e file")
+    })
+    .await;
+
+    // done: the two signals `rgt done` sends
+    let done = PolecatDoneSignal {
+        branch: branch.clone(),
+        status: "done".to_string(),
+        summary: Some("Added e2e.txt".to_string()),
+    };
+    signal(&client, &polecat_id, SIGNAL_POLECAT_DONE, &done).await?;
+    let enqueue = RefineryEnqueueSignal {
+        work_item_id: work_item_id.to_string(),
+        branch: branch.clone(),
+        priority: 2,
+        validate_only: false,
+        timeout_secs: None,
+        max_retries: None,
+        target_branch: None,
+    };
+    signal(&client, &refinery_id, SIGNAL_REFINERY_ENQUEUE, &enqueue).await?;
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(60);
+    while workflow_status(&client, &polecat_id).await != Some(2) {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "polecat workflow never completed"
+        );
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    assert!(!gtr_temporal::pty::is_alive(&polecat_id));
+
+    let origin = home.path().join("origin.git");
+    wait_until("merge to origin/main", Duration::from_secs(120), || {
+        Command::new("git")
+            .args(["cat-file", "-e", "main:e2e.txt"])
+            .current_dir(&origin)
+            .status()
+            .is_ok_and(|s| s.success())
+    })
+    .await;
+
+    let history = gtr_core::dirs::refinery_history_path(RIG, QUEUE);
+    wait_until("refinery history", Duration::from_secs(30), || {
+        std::fs::read_to_string(&history).is_ok_and(|h| h.contains(work_item_id))
+    })
+    .await;
+
+    signal(&client, &refinery_id, SIGNAL_REFINERY_STOP, &()).await?;
+    worker.abort();
+    Ok(())
+}
+
+/// A stop already waiting when the mayor reaches its continue-as-new limit
+/// ends it rather than being dropped by the rollover.
+#[tokio::test(flavor = "multi_thread")]
+async fn mayor_stop_wins_over_continue_as_new() -> anyhow::Result<()> {
+    let Some(server) = DevServer::start() else {
+        eprintln!("Skipping test -- temporal CLI not installed");
+        return Ok(());
+    };
+    let _serial = SERIAL.lock().await;
+
+    let home = tempfile::tempdir()?;
+    std::env::set_var("HOME", home.path());
+    let client = server.connect().await?;
+
+    // Queue the signals before any worker runs, so the first run sees
+    // enough to reach the limit and the stop right behind them
+    let mayor_id = gtr_core::state::mayor_workflow_id();
+    client
+        .start_workflow(
+            vec![],
+            "work".to_string(),
+            mayor_id.clone(),
+            "mayor_wf".to_string(),
+            None,
+            Default::default(),
+        )
+        .await?;
+    for n in 0..gtr_temporal::workflows::mayor::CONTINUE_AS_NEW_AFTER {
+        let agent_id = format!("gone-{n}");
+        signal(&client, &mayor_id, SIGNAL_UNREGISTER_AGENT, &agent_id).await?;
+    }
+    signal(&client, &mayor_id, SIGNAL_MAYOR_STOP, &()).await?;
+
+    let worker_url = server.url.clone();
+    let worker = tokio::spawn(async move {
+        if let Err(e) = gtr_temporal::worker::run_worker_at(&worker_url).await {
+            eprintln!("worker exited: {e}");
+        }
+    });
+
+    // Completed, not a fresh run continued from this one
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(60);
+    while workflow_status(&client, &mayor_id).await != Some(2) {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "mayor never stopped"
+        );
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    worker.abort();
+    Ok(())
+}
@@ -6,6 +6,9 @@
 //!
 //! Run tmux tests: `cargo test --test integration`
 //! Run all (with Temporal): `TEMPORAL_TEST=1 cargo test --test integration --ignored`
+//!
+//! The sling → done → refinery path runs for real in `tests/e2e.rs`, which
+//! starts its own dev server: `cargo test -p gtr-temporal --features temporal-it`
 
 use std::collections::HashMap;
 use std::path::Path;